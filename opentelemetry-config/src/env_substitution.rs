@@ -0,0 +1,230 @@
+//! Environment variable substitution.
+use std::{env, fmt::Write};
+
+use serde_yaml::Value;
+
+/// Returns `document` as YAML text, with the references to environment
+/// variables in its string values replaced.
+///
+/// Substitution happens after parsing, so the value of a variable can't add
+/// mappings or sequences to the document. A string value which only becomes a
+/// boolean, a number or null once substituted, e.g. `${RATIO}` set to `0.5`, is
+/// written as such so that it can be deserialized into any type. Other string
+/// values are quoted, and keys are not substituted.
+pub(crate) fn substitute_document(document: &Value) -> String {
+    let mut text = String::new();
+    write_value(&mut text, document);
+    text
+}
+
+/// Writes `value` in the YAML flow style.
+fn write_value(text: &mut String, value: &Value) {
+    match value {
+        Value::Null => text.push_str("null"),
+        Value::Bool(value) => text.push_str(if *value { "true" } else { "false" }),
+        Value::Number(value) => {
+            let _ = write!(text, "{value}");
+        }
+        Value::String(value) => {
+            let substituted = substitute(value);
+            if substituted == *value {
+                write_quoted(text, value);
+            } else if substituted.is_empty() {
+                text.push_str("null");
+            } else if is_plain_scalar(&substituted) {
+                text.push_str(&substituted);
+            } else {
+                write_quoted(text, &substituted);
+            }
+        }
+        Value::Sequence(values) => {
+            text.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    text.push_str(", ");
+                }
+                write_value(text, value);
+            }
+            text.push(']');
+        }
+        Value::Mapping(mapping) => {
+            text.push('{');
+            for (i, (key, value)) in mapping.iter().enumerate() {
+                if i > 0 {
+                    text.push_str(", ");
+                }
+                match key {
+                    Value::String(key) => write_quoted(text, key),
+                    key => write_value(text, key),
+                }
+                text.push_str(": ");
+                write_value(text, value);
+            }
+            text.push('}');
+        }
+        Value::Tagged(tagged) => {
+            let _ = write!(text, "{} ", tagged.tag);
+            write_value(text, &tagged.value);
+        }
+    }
+}
+
+/// Writes `value` as a double-quoted scalar.
+fn write_quoted(text: &mut String, value: &str) {
+    text.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => text.push_str("\\\""),
+            '\\' => text.push_str("\\\\"),
+            '\n' => text.push_str("\\n"),
+            '\r' => text.push_str("\\r"),
+            '\t' => text.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(text, "\\u{:04x}", c as u32);
+            }
+            c => text.push(c),
+        }
+    }
+    text.push('"');
+}
+
+/// Returns `true` if `value` is a boolean, a number or null when written as a
+/// plain scalar.
+fn is_plain_scalar(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.' | '_'))
+        && matches!(
+            serde_yaml::from_str(value),
+            Ok(Value::Null | Value::Bool(_) | Value::Number(_))
+        )
+}
+
+/// Replaces the references to environment variables in `text`.
+///
+/// The following references are supported:
+/// - `${NAME}` and `${env:NAME}` are replaced by the value of the `NAME`
+///   environment variable, or by an empty string if it isn't set.
+/// - `${NAME:-default}` is replaced by `default` if `NAME` isn't set or is
+///   empty.
+/// - `$$` is replaced by `$`, to escape references.
+///
+/// Anything else, including references with an invalid name, is kept as is.
+pub(crate) fn substitute(text: &str) -> String {
+    let mut substituted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        substituted.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("$$") {
+            substituted.push('$');
+            rest = after;
+            continue;
+        }
+
+        match rest
+            .strip_prefix("${")
+            .and_then(|reference| Some((reference, reference.find('}')?)))
+            .and_then(|(reference, end)| Some((resolve(&reference[..end])?, end)))
+        {
+            Some((value, end)) => {
+                substituted.push_str(&value);
+                // `${` + reference + `}`
+                rest = &rest[end + 3..];
+            }
+            None => {
+                substituted.push('$');
+                rest = &rest[1..];
+            }
+        }
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+/// Returns the value of a reference, without its `${` and `}` delimiters, if it
+/// is valid.
+fn resolve(reference: &str) -> Option<String> {
+    let reference = reference.strip_prefix("env:").unwrap_or(reference);
+    let (name, default) = match reference.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (reference, None),
+    };
+    if !is_valid_name(name) {
+        return None;
+    }
+
+    let value = env::var(name).ok();
+    Some(match default {
+        Some(default) => value
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| default.to_string()),
+        None => value.unwrap_or_default(),
+    })
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_yaml::Value;
+
+    use super::{substitute, substitute_document};
+
+    #[test]
+    fn test_substitute() {
+        temp_env::with_vars(
+            [
+                ("SERVICE", Some("my-service")),
+                ("EMPTY", Some("")),
+                ("UNSET", None),
+            ],
+            || {
+                for (text, expected) in [
+                    ("name: ${SERVICE}", "name: my-service"),
+                    ("name: ${env:SERVICE}", "name: my-service"),
+                    ("name: ${UNSET}", "name: "),
+                    ("name: ${UNSET:-default}", "name: default"),
+                    ("name: ${EMPTY:-default}", "name: default"),
+                    ("name: ${SERVICE:-default}", "name: my-service"),
+                    ("${SERVICE}-${SERVICE}", "my-service-my-service"),
+                    ("price: $$5 ${SERVICE}", "price: $5 my-service"),
+                    ("escaped: $${SERVICE}", "escaped: ${SERVICE}"),
+                    ("invalid: ${1NAME} ${NAME", "invalid: ${1NAME} ${NAME"),
+                    ("dollar: $ $", "dollar: $ $"),
+                ] {
+                    assert_eq!(substitute(text), expected, "{text}");
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_substitute_document() {
+        temp_env::with_vars(
+            [
+                ("RATIO", Some("0.5")),
+                ("KEY", Some("12345")),
+                ("INJECTED", Some("value\nother: {a: 1}")),
+                ("UNSET", None),
+            ],
+            || {
+                let document: Value = serde_yaml::from_str(
+                    "ratio: ${RATIO}\nkey: \"${KEY}\"\nplain: \"0.5\"\ninjected: ${INJECTED}\nunset: ${UNSET}\n${KEY}: [\"$$\"]",
+                )
+                .unwrap();
+                assert_eq!(
+                    substitute_document(&document),
+                    r#"{"ratio": 0.5, "key": 12345, "plain": "0.5", "injected": "value\nother: {a: 1}", "unset": null, "${KEY}": ["$"]}"#
+                );
+            },
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Selection of the processors and exporters shared by the signals.
+#[cfg(any(feature = "trace", feature = "logs"))]
+use crate::model::{BatchProcessorModel, ProcessorModel};
+use crate::{
+    model::{ExporterModel, OtlpModel},
+    ConfigError,
+};
+
+/// The processor set in a [ProcessorModel].
+#[cfg(any(feature = "trace", feature = "logs"))]
+pub(crate) enum Processor<'a> {
+    Batch(&'a BatchProcessorModel),
+    Simple,
+}
+
+#[cfg(any(feature = "trace", feature = "logs"))]
+impl<'a> Processor<'a> {
+    /// Returns the processor set in `model`, and the path of its exporter.
+    pub(crate) fn from_model(
+        model: &'a ProcessorModel,
+        path: &str,
+    ) -> Result<(Self, &'a ExporterModel, String), ConfigError> {
+        match (&model.batch, &model.simple) {
+            (Some(batch), None) => Ok((
+                Processor::Batch(batch),
+                &batch.exporter,
+                format!("{path}.batch.exporter"),
+            )),
+            (None, Some(simple)) => Ok((
+                Processor::Simple,
+                &simple.exporter,
+                format!("{path}.simple.exporter"),
+            )),
+            _ => Err(ConfigError::invalid(
+                path,
+                "exactly one of `batch` or `simple` must be set",
+            )),
+        }
+    }
+}
+
+/// The exporter set in an [ExporterModel].
+///
+/// Only the exporters enabled by the features of the crate are returned.
+#[cfg_attr(
+    not(any(feature = "otlp-grpc", feature = "otlp-http")),
+    allow(dead_code)
+)]
+pub(crate) enum Exporter<'a> {
+    Otlp(&'a OtlpModel),
+    #[cfg(feature = "console")]
+    Console,
+}
+
+impl<'a> Exporter<'a> {
+    pub(crate) fn from_model(model: &'a ExporterModel, path: &str) -> Result<Self, ConfigError> {
+        match (&model.otlp, &model.console) {
+            #[cfg(any(feature = "otlp-grpc", feature = "otlp-http"))]
+            (Some(otlp), None) => Ok(Exporter::Otlp(otlp)),
+            #[cfg(not(any(feature = "otlp-grpc", feature = "otlp-http")))]
+            (Some(_), None) => Err(ConfigError::invalid(
+                format!("{path}.otlp"),
+                "the `otlp-grpc` or `otlp-http` feature of opentelemetry-config must be enabled",
+            )),
+            #[cfg(feature = "console")]
+            (None, Some(_)) => Ok(Exporter::Console),
+            #[cfg(not(feature = "console"))]
+            (None, Some(_)) => Err(ConfigError::invalid(
+                format!("{path}.console"),
+                "the `console` feature of opentelemetry-config is not enabled",
+            )),
+            _ => Err(ConfigError::invalid(
+                path,
+                "exactly one of `otlp` or `console` must be set",
+            )),
+        }
+    }
+}
+
+#[cfg(any(feature = "otlp-grpc", feature = "otlp-http"))]
+pub(crate) use otlp::builder as otlp_builder;
+
+#[cfg(any(feature = "otlp-grpc", feature = "otlp-http"))]
+mod otlp {
+    use std::time::Duration;
+
+    #[cfg(feature = "otlp-http")]
+    use opentelemetry_otlp::HttpExporterBuilder;
+    #[cfg(feature = "otlp-grpc")]
+    use opentelemetry_otlp::TonicExporterBuilder;
+    use opentelemetry_otlp::WithExportConfig;
+
+    use crate::{
+        model::{OtlpCompression, OtlpModel, OtlpProtocol},
+        ConfigError,
+    };
+
+    /// The builder of an OTLP exporter, converted into the builder of each
+    /// signal.
+    #[derive(Debug)]
+    #[allow(clippy::large_enum_variant)]
+    pub(crate) enum OtlpExporterBuilder {
+        #[cfg(feature = "otlp-grpc")]
+        Tonic(TonicExporterBuilder),
+        #[cfg(feature = "otlp-http")]
+        Http(HttpExporterBuilder),
+    }
+
+    /// Returns the builder of the OTLP exporter configured by `model`, at
+    /// `path`.
+    pub(crate) fn builder(
+        model: &OtlpModel,
+        path: &str,
+    ) -> Result<OtlpExporterBuilder, ConfigError> {
+        let path = format!("{path}.otlp");
+        match model.protocol {
+            OtlpProtocol::Grpc => grpc(model, &path),
+            OtlpProtocol::HttpProtobuf => http(model, &path, opentelemetry_otlp::Protocol::HttpBinary),
+            OtlpProtocol::HttpJson if cfg!(feature = "otlp-http-json") => {
+                http(model, &path, opentelemetry_otlp::Protocol::HttpJson)
+            }
+            OtlpProtocol::HttpJson => Err(ConfigError::invalid(
+                format!("{path}.protocol"),
+                "the `http/json` protocol requires the `otlp-http-json` feature of opentelemetry-config",
+            )),
+        }
+    }
+
+    fn with_export_config<B: WithExportConfig>(mut builder: B, model: &OtlpModel) -> B {
+        if let Some(endpoint) = &model.endpoint {
+            builder = builder.with_endpoint(endpoint.clone());
+        }
+        if let Some(timeout) = model.timeout {
+            builder = builder.with_timeout(Duration::from_millis(timeout));
+        }
+        builder
+    }
+
+    #[cfg(feature = "otlp-grpc")]
+    fn grpc(model: &OtlpModel, path: &str) -> Result<OtlpExporterBuilder, ConfigError> {
+        use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+
+        let mut metadata = MetadataMap::new();
+        for (key, value) in &model.headers {
+            let header = format!("{path}.headers.{key}");
+            let key = MetadataKey::from_bytes(key.as_bytes())
+                .map_err(|err| ConfigError::invalid(&header, err.to_string()))?;
+            let value = MetadataValue::try_from(value.as_str())
+                .map_err(|err| ConfigError::invalid(&header, err.to_string()))?;
+            metadata.insert(key, value);
+        }
+
+        let mut builder = with_export_config(opentelemetry_otlp::new_exporter().tonic(), model)
+            .with_protocol(opentelemetry_otlp::Protocol::Grpc)
+            .with_metadata(metadata);
+        if model.compression == Some(OtlpCompression::Gzip) {
+            builder = builder.with_compression(opentelemetry_otlp::Compression::Gzip);
+        }
+        Ok(OtlpExporterBuilder::Tonic(builder))
+    }
+
+    #[cfg(not(feature = "otlp-grpc"))]
+    fn grpc(_model: &OtlpModel, path: &str) -> Result<OtlpExporterBuilder, ConfigError> {
+        Err(ConfigError::invalid(
+            format!("{path}.protocol"),
+            "the `grpc` protocol requires the `otlp-grpc` feature of opentelemetry-config",
+        ))
+    }
+
+    #[cfg(feature = "otlp-http")]
+    fn http(
+        model: &OtlpModel,
+        _path: &str,
+        protocol: opentelemetry_otlp::Protocol,
+    ) -> Result<OtlpExporterBuilder, ConfigError> {
+        // The exporters run on an async runtime, so the async client is used even
+        // if another crate enables the blocking one of opentelemetry-otlp.
+        let mut builder = with_export_config(opentelemetry_otlp::new_exporter().http(), model)
+            .with_http_client(reqwest::Client::new())
+            .with_protocol(protocol)
+            .with_headers(model.headers.clone().into_iter().collect());
+        if model.compression == Some(OtlpCompression::Gzip) {
+            builder = builder.with_compression(opentelemetry_otlp::Compression::Gzip);
+        }
+        Ok(OtlpExporterBuilder::Http(builder))
+    }
+
+    #[cfg(not(feature = "otlp-http"))]
+    fn http(
+        _model: &OtlpModel,
+        path: &str,
+        _protocol: opentelemetry_otlp::Protocol,
+    ) -> Result<OtlpExporterBuilder, ConfigError> {
+        Err(ConfigError::invalid(
+            format!("{path}.protocol"),
+            "the HTTP protocols require the `otlp-http` feature of opentelemetry-config",
+        ))
+    }
+
+    #[cfg(feature = "trace")]
+    impl From<OtlpExporterBuilder> for opentelemetry_otlp::SpanExporterBuilder {
+        fn from(builder: OtlpExporterBuilder) -> Self {
+            match builder {
+                #[cfg(feature = "otlp-grpc")]
+                OtlpExporterBuilder::Tonic(builder) => builder.into(),
+                #[cfg(feature = "otlp-http")]
+                OtlpExporterBuilder::Http(builder) => builder.into(),
+            }
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    impl From<OtlpExporterBuilder> for opentelemetry_otlp::MetricsExporterBuilder {
+        fn from(builder: OtlpExporterBuilder) -> Self {
+            match builder {
+                #[cfg(feature = "otlp-grpc")]
+                OtlpExporterBuilder::Tonic(builder) => builder.into(),
+                #[cfg(feature = "otlp-http")]
+                OtlpExporterBuilder::Http(builder) => builder.into(),
+            }
+        }
+    }
+
+    #[cfg(feature = "logs")]
+    impl From<OtlpExporterBuilder> for opentelemetry_otlp::LogExporterBuilder {
+        fn from(builder: OtlpExporterBuilder) -> Self {
+            match builder {
+                #[cfg(feature = "otlp-grpc")]
+                OtlpExporterBuilder::Tonic(builder) => builder.into(),
+                #[cfg(feature = "otlp-http")]
+                OtlpExporterBuilder::Http(builder) => builder.into(),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use opentelemetry_sdk::runtime;
+
+    use crate::{ConfigError, Configuration};
+
+    fn build_tracer_provider(exporter: &str) -> Result<(), ConfigError> {
+        let text = format!(
+            "file_format: \"0.1\"\ntracer_provider:\n  processors:\n    - simple:\n        exporter:\n{exporter}"
+        );
+        Configuration::parse(&text)?.build(runtime::Tokio).map(drop)
+    }
+
+    fn assert_invalid(exporter: &str, path: &str) {
+        match build_tracer_provider(exporter) {
+            Err(ConfigError::Invalid {
+                path: error_path, ..
+            }) => assert_eq!(error_path, path, "{exporter}"),
+            other => panic!("unexpected result {other:?} for {exporter}"),
+        }
+    }
+
+    #[test]
+    fn test_exactly_one_exporter() {
+        assert_invalid(
+            "          otlp:\n            protocol: grpc\n          console:",
+            "tracer_provider.processors[0].simple.exporter",
+        );
+    }
+
+    #[cfg(feature = "otlp-grpc")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_otlp_grpc() {
+        build_tracer_provider(
+            "          otlp:\n            protocol: grpc\n            endpoint: http://localhost:4317\n            compression: gzip\n            timeout: 1000\n            headers:\n              api-key: secret",
+        )
+        .expect("valid configuration");
+        assert_invalid(
+            "          otlp:\n            protocol: grpc\n            headers:\n              \"invalid key\": value",
+            "tracer_provider.processors[0].simple.exporter.otlp.headers.invalid key",
+        );
+    }
+
+    #[cfg(feature = "otlp-http")]
+    #[test]
+    fn test_otlp_http() {
+        build_tracer_provider(
+            "          otlp:\n            protocol: http/protobuf\n            endpoint: http://localhost:4318\n            compression: gzip\n            headers:\n              api-key: secret",
+        )
+        .expect("valid configuration");
+    }
+
+    #[cfg(all(feature = "otlp-http", not(feature = "otlp-http-json")))]
+    #[test]
+    fn test_missing_protocol_feature() {
+        assert_invalid(
+            "          otlp:\n            protocol: http/json",
+            "tracer_provider.processors[0].simple.exporter.otlp.protocol",
+        );
+    }
+}
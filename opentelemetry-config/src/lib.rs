@@ -0,0 +1,499 @@
+//! # OpenTelemetry declarative configuration
+//!
+//! Builds the tracer, meter and logger providers of the OpenTelemetry SDK from
+//! a single configuration file, following the [configuration schema] of
+//! OpenTelemetry, instead of configuring each of them in code.
+//!
+//! The file is written in YAML or JSON. References to environment variables,
+//! such as `${OTEL_SERVICE_NAME}` or `${SAMPLING_RATIO:-0.1}`, are replaced by
+//! their values in the values of the file, once it is parsed.
+//!
+//! ```yaml
+//! file_format: "0.1"
+//! resource:
+//!   attributes:
+//!     service.name: ${SERVICE_NAME:-checkout}
+//!     deployment.environment: production
+//! attribute_limits:
+//!   attribute_count_limit: 64
+//! tracer_provider:
+//!   processors:
+//!     - batch:
+//!         schedule_delay: 5000
+//!         exporter:
+//!           otlp:
+//!             protocol: grpc
+//!             endpoint: http://localhost:4317
+//!             headers:
+//!               api-key: ${API_KEY}
+//!   sampler:
+//!     parent_based:
+//!       root:
+//!         trace_id_ratio_based:
+//!           ratio: 0.25
+//! meter_provider:
+//!   readers:
+//!     - periodic:
+//!         interval: 60000
+//!         exporter:
+//!           otlp:
+//!             protocol: http/protobuf
+//!             temporality_preference: delta
+//!   views:
+//!     - selector:
+//!         instrument_name: http.server.duration
+//!       stream:
+//!         aggregation:
+//!           explicit_bucket_histogram:
+//!             boundaries: [5, 10, 25, 50, 100, 250, 500, 1000]
+//! logger_provider:
+//!   processors:
+//!     - simple:
+//!         exporter:
+//!           console: {}
+//! ```
+//!
+//! The file is usually named by the `OTEL_CONFIG_FILE` environment variable:
+//!
+//! ```no_run
+//! use opentelemetry::global;
+//! use opentelemetry_config::Configuration;
+//! use opentelemetry_sdk::runtime;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # #[cfg(all(feature = "trace", feature = "metrics"))]
+//! if let Some(config) = Configuration::from_env()? {
+//!     let providers = config.build(runtime::Tokio)?;
+//!     if let Some(tracer_provider) = providers.tracer_provider {
+//!         global::set_tracer_provider(tracer_provider);
+//!     }
+//!     if let Some(meter_provider) = providers.meter_provider {
+//!         global::set_meter_provider(meter_provider);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Invalid files are reported with the path of the offending property, e.g.
+//! ``invalid configuration at `tracer_provider.sampler.trace_id_ratio_based.ratio`: the ratio must be between 0 and 1``.
+//!
+//! ## Supported properties
+//!
+//! - `resource`: `attributes` and `schema_url`. The `telemetry.sdk.*`
+//!   attributes are always added, and `service.name` defaults to
+//!   `unknown_service`.
+//! - `attribute_limits`: `attribute_count_limit` and
+//!   `attribute_value_length_limit`, used by spans, events, links and log
+//!   records unless overridden in `tracer_provider.limits` or
+//!   `logger_provider.limits`.
+//! - `tracer_provider`: `batch` and `simple` `processors`, the span `limits`,
+//!   and the `always_on`, `always_off`, `trace_id_ratio_based` and
+//!   `parent_based` (with a `root`) `sampler`s.
+//! - `meter_provider`: `periodic` `readers` and `views`.
+//! - `logger_provider`: `batch` and `simple` `processors`, and the log record
+//!   `limits`.
+//! - exporters: `console`, and `otlp` with its `protocol`, `endpoint`,
+//!   `headers`, `compression`, `timeout` and, for metrics,
+//!   `temporality_preference` and `default_histogram_aggregation`. The HTTP
+//!   endpoint is the base URL the path of each signal is appended to, as with
+//!   the `opentelemetry-otlp` exporters, whose `OTEL_EXPORTER_OTLP_*`
+//!   environment variables still take precedence.
+//!
+//! Other properties are rejected.
+//!
+//! ## Crate Feature Flags
+//!
+//! * `trace`, `metrics`, `logs`: build the tracer, meter and logger providers
+//!   respectively. Enabled by default.
+//! * `console`: the `console` exporter, writing to stdout with
+//!   `opentelemetry-stdout`. Enabled by default.
+//! * `otlp-grpc`: the `otlp` exporter with the `grpc` protocol.
+//! * `otlp-http`: the `otlp` exporter with the `http/protobuf` protocol, sent
+//!   with the async `reqwest` client, whichever `reqwest` client features of
+//!   `opentelemetry-otlp` are enabled. The `OTEL_EXPORTER_OTLP_*CERTIFICATE`
+//!   environment variables are not applied to this client.
+//! * `otlp-http-json`: the `otlp` exporter with the `http/json` protocol.
+//!
+//! [configuration schema]: https://github.com/open-telemetry/opentelemetry-configuration
+#![warn(
+    future_incompatible,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    rust_2018_idioms,
+    unreachable_pub,
+    unused
+)]
+#![cfg_attr(
+    docsrs,
+    feature(doc_cfg, doc_auto_cfg),
+    deny(rustdoc::broken_intra_doc_links)
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/open-telemetry/opentelemetry-rust/main/assets/logo.svg"
+)]
+#![cfg_attr(test, deny(warnings))]
+
+use std::{env, fs, path::Path, path::PathBuf};
+
+#[cfg(feature = "logs")]
+use opentelemetry_sdk::logs::LoggerProvider;
+#[cfg(feature = "metrics")]
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::runtime::RuntimeChannel;
+#[cfg(feature = "trace")]
+use opentelemetry_sdk::trace::TracerProvider;
+
+mod env_substitution;
+#[cfg(all(
+    any(feature = "trace", feature = "metrics", feature = "logs"),
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+mod exporter;
+#[cfg(feature = "logs")]
+mod logs;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod model;
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+mod resource;
+#[cfg(feature = "trace")]
+mod trace;
+
+use model::Model;
+
+/// Path of the configuration file read by [Configuration::from_env].
+pub const OTEL_CONFIG_FILE: &str = "OTEL_CONFIG_FILE";
+
+/// Errors returned when reading a configuration or building its providers.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The configuration file can't be read.
+    #[error("failed to read the configuration file {path}: {source}")]
+    Io {
+        /// The path of the configuration file.
+        path: PathBuf,
+        /// The error returned when reading the file.
+        source: std::io::Error,
+    },
+
+    /// The configuration is invalid or uses unsupported properties.
+    #[error("invalid configuration at `{path}`: {message}")]
+    Invalid {
+        /// The path of the offending property, e.g. `tracer_provider.processors[0]`.
+        path: String,
+        /// What is wrong with the property.
+        message: String,
+    },
+
+    /// An exporter failed to build.
+    #[error("failed to build the exporter at `{path}`: {message}")]
+    Exporter {
+        /// The path of the exporter, e.g. `tracer_provider.processors[0].batch.exporter`.
+        path: String,
+        /// The error returned by the exporter.
+        message: String,
+    },
+}
+
+impl ConfigError {
+    pub(crate) fn invalid(path: impl Into<String>, message: impl Into<String>) -> Self {
+        ConfigError::Invalid {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    #[cfg(all(
+        any(feature = "trace", feature = "metrics", feature = "logs"),
+        any(feature = "otlp-grpc", feature = "otlp-http")
+    ))]
+    pub(crate) fn exporter(path: impl Into<String>, error: impl std::fmt::Display) -> Self {
+        ConfigError::Exporter {
+            path: path.into(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// A parsed configuration file.
+///
+/// See the [crate documentation](crate) for the supported properties.
+#[derive(Debug)]
+pub struct Configuration {
+    model: Model,
+}
+
+impl Configuration {
+    /// Reads the configuration file named by the `OTEL_CONFIG_FILE` environment
+    /// variable.
+    ///
+    /// Returns `None` if the variable isn't set or is empty.
+    pub fn from_env() -> Result<Option<Self>, ConfigError> {
+        match env::var_os(OTEL_CONFIG_FILE) {
+            Some(path) if !path.is_empty() => Configuration::from_file(path).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Reads a YAML or JSON configuration file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Configuration::parse(&text)
+    }
+
+    /// Parses a YAML or JSON configuration.
+    ///
+    /// References to environment variables in the values of the configuration
+    /// are replaced by their values:
+    /// - `${NAME}` and `${env:NAME}` by the value of `NAME`, or by an empty
+    ///   string if it isn't set.
+    /// - `${NAME:-default}` by `default` if `NAME` isn't set or is empty.
+    /// - `$$` by `$`.
+    ///
+    /// A value which only becomes a boolean, a number or null once substituted,
+    /// e.g. `ratio: ${RATIO}`, is read as such. Environment variables can't add
+    /// properties to the configuration.
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let invalid = |err: serde_path_to_error::Error<serde_yaml::Error>| {
+            ConfigError::invalid(err.path().to_string(), err.inner().to_string())
+        };
+        let document: serde_yaml::Value =
+            serde_path_to_error::deserialize(serde_yaml::Deserializer::from_str(text))
+                .map_err(invalid)?;
+        let text = env_substitution::substitute_document(&document);
+        let model: Model =
+            serde_path_to_error::deserialize(serde_yaml::Deserializer::from_str(&text))
+                .map_err(invalid)?;
+
+        if !model::SUPPORTED_FILE_FORMATS.contains(&model.file_format.as_str()) {
+            return Err(ConfigError::invalid(
+                "file_format",
+                format!(
+                    "unsupported file format {:?}, expected one of {:?}",
+                    model.file_format,
+                    model::SUPPORTED_FILE_FORMATS
+                ),
+            ));
+        }
+
+        Ok(Configuration { model })
+    }
+
+    /// Builds the providers of the configuration.
+    ///
+    /// The batch span and log record processors and the periodic metric readers
+    /// run on `runtime`. A provider is only built if its section is present in
+    /// the configuration and the SDK isn't `disabled`.
+    pub fn build<R: RuntimeChannel>(&self, runtime: R) -> Result<Providers, ConfigError> {
+        let model = &self.model;
+        if model.disabled {
+            return Ok(Providers::default());
+        }
+        #[cfg(not(any(feature = "trace", feature = "metrics", feature = "logs")))]
+        let _ = runtime;
+        #[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+        let resource = resource::build(&model.resource);
+
+        #[cfg(not(feature = "trace"))]
+        if model.tracer_provider.is_some() {
+            return Err(missing_feature("tracer_provider", "trace"));
+        }
+        #[cfg(not(feature = "metrics"))]
+        if model.meter_provider.is_some() {
+            return Err(missing_feature("meter_provider", "metrics"));
+        }
+        #[cfg(not(feature = "logs"))]
+        if model.logger_provider.is_some() {
+            return Err(missing_feature("logger_provider", "logs"));
+        }
+
+        Ok(Providers {
+            #[cfg(feature = "trace")]
+            tracer_provider: model
+                .tracer_provider
+                .as_ref()
+                .map(|tracer_provider| {
+                    trace::build(
+                        tracer_provider,
+                        &model.attribute_limits,
+                        resource.clone(),
+                        runtime.clone(),
+                    )
+                })
+                .transpose()?,
+            #[cfg(feature = "metrics")]
+            meter_provider: model
+                .meter_provider
+                .as_ref()
+                .map(|meter_provider| {
+                    metrics::build(meter_provider, resource.clone(), runtime.clone())
+                })
+                .transpose()?,
+            #[cfg(feature = "logs")]
+            logger_provider: model
+                .logger_provider
+                .as_ref()
+                .map(|logger_provider| {
+                    logs::build(
+                        logger_provider,
+                        &model.attribute_limits,
+                        resource.clone(),
+                        runtime.clone(),
+                    )
+                })
+                .transpose()?,
+        })
+    }
+}
+
+#[cfg(not(all(feature = "trace", feature = "metrics", feature = "logs")))]
+fn missing_feature(path: &str, feature: &str) -> ConfigError {
+    ConfigError::invalid(
+        path,
+        format!("the `{feature}` feature of opentelemetry-config is not enabled"),
+    )
+}
+
+/// Returns the error for the processors or readers at `path`, which can't be
+/// built without an exporter.
+#[cfg(all(
+    any(feature = "trace", feature = "metrics", feature = "logs"),
+    not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))
+))]
+fn missing_exporter(path: &str) -> ConfigError {
+    ConfigError::invalid(
+        path,
+        "the `console`, `otlp-grpc` or `otlp-http` feature of opentelemetry-config must be enabled",
+    )
+}
+
+/// The providers built from a [Configuration].
+///
+/// Providers missing from the configuration are `None`.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct Providers {
+    /// The tracer provider configured by the `tracer_provider` section.
+    #[cfg(feature = "trace")]
+    pub tracer_provider: Option<TracerProvider>,
+    /// The meter provider configured by the `meter_provider` section.
+    #[cfg(feature = "metrics")]
+    pub meter_provider: Option<SdkMeterProvider>,
+    /// The logger provider configured by the `logger_provider` section.
+    #[cfg(feature = "logs")]
+    pub logger_provider: Option<LoggerProvider>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "trace", feature = "metrics", feature = "console"))]
+    #[test]
+    fn test_parse_errors_point_at_path() {
+        for (text, path) in [
+            ("file_format: \"9.9\"", "file_format"),
+            ("file_format: \"0.1\"\nunknown: true", "unknown"),
+            (
+                "file_format: \"0.1\"\ntracer_provider:\n  processors:\n    - batch:\n        max_queue_size: -1\n        exporter:\n          console: {}",
+                "tracer_provider.processors[0].batch.max_queue_size",
+            ),
+            (
+                "file_format: \"0.1\"\nmeter_provider:\n  views:\n    - selector:\n        instrument_type: timer\n      stream: {}",
+                "meter_provider.views[0].selector.instrument_type",
+            ),
+        ] {
+            match Configuration::parse(text) {
+                Err(ConfigError::Invalid { path: error_path, .. }) => {
+                    assert_eq!(error_path, path, "{text}")
+                }
+                other => panic!("unexpected result {other:?} for {text}"),
+            }
+        }
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_parse_substitutes_values() {
+        temp_env::with_vars(
+            [
+                ("RATIO", Some("0.5")),
+                ("SCHEMA_URL", Some("https://example.com\ndisabled: true")),
+            ],
+            || {
+                let config = Configuration::parse(
+                    "file_format: \"0.1\"\nresource:\n  schema_url: ${SCHEMA_URL}\ntracer_provider:\n  sampler:\n    trace_id_ratio_based:\n      ratio: ${RATIO}",
+                )
+                .expect("valid configuration");
+                assert!(!config.model.disabled);
+                assert_eq!(
+                    config.model.resource.schema_url.as_deref(),
+                    Some("https://example.com\ndisabled: true")
+                );
+                let sampler = config.model.tracer_provider.unwrap().sampler.unwrap();
+                assert_eq!(sampler.trace_id_ratio_based.unwrap().ratio, 0.5);
+            },
+        );
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let config = Configuration::parse(
+            r#"{"file_format": "0.1", "resource": {"attributes": {"service.name": "json"}}}"#,
+        )
+        .expect("valid configuration");
+        assert!(config.model.tracer_provider.is_none());
+    }
+
+    #[test]
+    fn test_from_env() {
+        temp_env::with_var_unset(OTEL_CONFIG_FILE, || {
+            assert!(Configuration::from_env().unwrap().is_none());
+        });
+        temp_env::with_var(OTEL_CONFIG_FILE, Some("/does/not/exist.yaml"), || {
+            assert!(matches!(
+                Configuration::from_env(),
+                Err(ConfigError::Io { .. })
+            ));
+        });
+    }
+
+    #[cfg(all(
+        feature = "trace",
+        not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))
+    ))]
+    #[test]
+    fn test_missing_exporter() {
+        let config = Configuration::parse(
+            "file_format: \"0.1\"\ntracer_provider:\n  processors:\n    - simple: {}",
+        )
+        .unwrap();
+        match config.build(opentelemetry_sdk::runtime::Tokio) {
+            Err(ConfigError::Invalid { path, .. }) => {
+                assert_eq!(path, "tracer_provider.processors")
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_disabled() {
+        let config = Configuration::parse(
+            "file_format: \"0.1\"\ndisabled: true\ntracer_provider:\n  processors: []",
+        )
+        .unwrap();
+        let providers = config
+            .build(opentelemetry_sdk::runtime::Tokio)
+            .expect("valid configuration");
+        #[cfg(feature = "trace")]
+        assert!(providers.tracer_provider.is_none());
+        let _ = providers;
+    }
+}
@@ -0,0 +1,201 @@
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+use std::time::Duration;
+
+#[cfg(not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")))]
+use serde::de::IgnoredAny;
+
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+use opentelemetry_sdk::{
+    export::logs::LogExporter,
+    logs::{BatchConfigBuilder, BatchLogProcessor},
+};
+use opentelemetry_sdk::{
+    logs::{self, LoggerProvider},
+    runtime::RuntimeChannel,
+    Resource,
+};
+
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+use crate::{
+    exporter::{Exporter, Processor},
+    model::ProcessorModel,
+};
+use crate::{
+    model::{AttributeLimitsModel, LoggerProviderModel},
+    ConfigError,
+};
+
+/// Builds the logger provider configured by the `logger_provider` section.
+pub(crate) fn build<R: RuntimeChannel>(
+    model: &LoggerProviderModel,
+    attribute_limits: &AttributeLimitsModel,
+    resource: Resource,
+    runtime: R,
+) -> Result<LoggerProvider, ConfigError> {
+    let mut config = logs::config().with_resource(resource);
+    if let Some(limit) = model
+        .limits
+        .attribute_count_limit
+        .or(attribute_limits.attribute_count_limit)
+    {
+        config = config.with_max_attributes_per_log_record(limit);
+    }
+    if let Some(limit) = model
+        .limits
+        .attribute_value_length_limit
+        .or(attribute_limits.attribute_value_length_limit)
+    {
+        config = config.with_max_attribute_value_length(limit);
+    }
+
+    let builder = LoggerProvider::builder().with_config(config);
+    Ok(with_processors(builder, &model.processors, runtime)?.build())
+}
+
+/// Adds the processors configured at `logger_provider.processors`.
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+fn with_processors<R: RuntimeChannel>(
+    mut builder: logs::Builder,
+    models: &[ProcessorModel],
+    runtime: R,
+) -> Result<logs::Builder, ConfigError> {
+    for (i, processor) in models.iter().enumerate() {
+        let path = format!("logger_provider.processors[{i}]");
+        let (processor, exporter, path) = Processor::from_model(processor, &path)?;
+        builder = match Exporter::from_model(exporter, &path)? {
+            #[cfg(any(feature = "otlp-grpc", feature = "otlp-http"))]
+            Exporter::Otlp(otlp) => {
+                let exporter = opentelemetry_otlp::LogExporterBuilder::from(
+                    crate::exporter::otlp_builder(otlp, &path)?,
+                )
+                .build_log_exporter()
+                .map_err(|err| ConfigError::exporter(&path, err))?;
+                with_processor(builder, processor, exporter, runtime.clone())
+            }
+            #[cfg(not(any(feature = "otlp-grpc", feature = "otlp-http")))]
+            Exporter::Otlp(_) => unreachable!("rejected by Exporter::from_model"),
+            #[cfg(feature = "console")]
+            Exporter::Console => with_processor(
+                builder,
+                processor,
+                opentelemetry_stdout::LogExporter::default(),
+                runtime.clone(),
+            ),
+        };
+    }
+
+    Ok(builder)
+}
+
+/// Rejects the processors, which can't be built without an exporter feature.
+#[cfg(not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")))]
+fn with_processors<R: RuntimeChannel>(
+    builder: logs::Builder,
+    models: &[IgnoredAny],
+    _runtime: R,
+) -> Result<logs::Builder, ConfigError> {
+    match models {
+        [] => Ok(builder),
+        _ => Err(crate::missing_exporter("logger_provider.processors")),
+    }
+}
+
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+fn with_processor<E, R>(
+    builder: logs::Builder,
+    processor: Processor<'_>,
+    exporter: E,
+    runtime: R,
+) -> logs::Builder
+where
+    E: LogExporter + 'static,
+    R: RuntimeChannel,
+{
+    match processor {
+        Processor::Batch(batch) => {
+            let mut config = BatchConfigBuilder::default();
+            if let Some(delay) = batch.schedule_delay {
+                config = config.with_scheduled_delay(Duration::from_millis(delay));
+            }
+            if let Some(timeout) = batch.export_timeout {
+                config = config.with_max_export_timeout(Duration::from_millis(timeout));
+            }
+            if let Some(size) = batch.max_queue_size {
+                config = config.with_max_queue_size(size);
+            }
+            if let Some(size) = batch.max_export_batch_size {
+                config = config.with_max_export_batch_size(size);
+            }
+            builder.with_log_processor(
+                BatchLogProcessor::builder(exporter, runtime)
+                    .with_batch_config(config.build())
+                    .build(),
+            )
+        }
+        Processor::Simple => builder.with_simple_exporter(exporter),
+    }
+}
+
+#[cfg(all(test, feature = "console"))]
+mod tests {
+    use opentelemetry::{Key, Value};
+    use opentelemetry_sdk::runtime;
+
+    use crate::Configuration;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_build() {
+        let config = Configuration::parse(
+            r#"
+file_format: "0.1"
+resource:
+  attributes:
+    service.name: logs
+logger_provider:
+  processors:
+    - batch:
+        max_export_batch_size: 64
+        exporter:
+          console:
+"#,
+        )
+        .unwrap();
+        let provider = config
+            .build(runtime::Tokio)
+            .expect("valid configuration")
+            .logger_provider
+            .expect("logger provider");
+        assert_eq!(provider.log_processors().len(), 1);
+        assert_eq!(
+            provider
+                .config()
+                .resource
+                .get(Key::from_static_str("service.name")),
+            Some(Value::from("logs"))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_limits() {
+        let config = Configuration::parse(
+            r#"
+file_format: "0.1"
+attribute_limits:
+  attribute_count_limit: 16
+  attribute_value_length_limit: 32
+logger_provider:
+  limits:
+    attribute_count_limit: 10
+"#,
+        )
+        .unwrap();
+        let provider = config
+            .build(runtime::Tokio)
+            .expect("valid configuration")
+            .logger_provider
+            .expect("logger provider");
+        let limits = provider.config().log_limits;
+        assert_eq!(limits.max_attributes_per_log_record, 10);
+        assert_eq!(limits.max_attribute_value_length, Some(32));
+    }
+}
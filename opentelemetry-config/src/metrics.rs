@@ -0,0 +1,404 @@
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+use std::time::Duration;
+
+#[cfg(not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")))]
+use serde::de::IgnoredAny;
+
+use opentelemetry::{metrics::Unit, InstrumentationLibrary, Key};
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+use opentelemetry_sdk::metrics::{exporter::PushMetricsExporter, PeriodicReader};
+use opentelemetry_sdk::{
+    metrics::{
+        new_view, Aggregation, Instrument, InstrumentKind, MeterProviderBuilder, SdkMeterProvider,
+        Stream,
+    },
+    runtime::RuntimeChannel,
+    Resource,
+};
+
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+use crate::{
+    exporter::Exporter,
+    model::{MetricReaderModel, PeriodicReaderModel},
+};
+use crate::{
+    model::{AggregationModel, InstrumentType, MeterProviderModel, ViewModel},
+    ConfigError,
+};
+
+/// The boundaries of the explicit bucket histograms, when not configured.
+const DEFAULT_BOUNDARIES: [f64; 15] = [
+    0.0, 5.0, 10.0, 25.0, 50.0, 75.0, 100.0, 250.0, 500.0, 750.0, 1000.0, 2500.0, 5000.0, 7500.0,
+    10000.0,
+];
+const DEFAULT_MAX_SCALE: i8 = 20;
+const DEFAULT_MAX_SIZE: u32 = 160;
+
+/// Builds the meter provider configured by the `meter_provider` section.
+pub(crate) fn build<R: RuntimeChannel>(
+    model: &MeterProviderModel,
+    resource: Resource,
+    runtime: R,
+) -> Result<SdkMeterProvider, ConfigError> {
+    let mut builder = with_readers(
+        SdkMeterProvider::builder().with_resource(resource),
+        &model.readers,
+        runtime,
+    )?;
+    for (i, view) in model.views.iter().enumerate() {
+        let path = format!("meter_provider.views[{i}]");
+        let view = new_view(instrument(view), stream(view, &format!("{path}.stream"))?)
+            .map_err(|err| ConfigError::invalid(path, err.to_string()))?;
+        builder = builder.with_view(view);
+    }
+
+    Ok(builder.build())
+}
+
+/// Adds the readers configured at `meter_provider.readers`.
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+fn with_readers<R: RuntimeChannel>(
+    mut builder: MeterProviderBuilder,
+    models: &[MetricReaderModel],
+    runtime: R,
+) -> Result<MeterProviderBuilder, ConfigError> {
+    for (i, reader) in models.iter().enumerate() {
+        let path = format!("meter_provider.readers[{i}]");
+        builder = match &reader.periodic {
+            Some(periodic) => {
+                with_periodic_reader(builder, periodic, &format!("{path}.periodic"), &runtime)?
+            }
+            None => {
+                return Err(ConfigError::invalid(path, "`periodic` must be set"));
+            }
+        };
+    }
+    Ok(builder)
+}
+
+/// Rejects the readers, which can't be built without an exporter feature.
+#[cfg(not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")))]
+fn with_readers<R: RuntimeChannel>(
+    builder: MeterProviderBuilder,
+    models: &[IgnoredAny],
+    _runtime: R,
+) -> Result<MeterProviderBuilder, ConfigError> {
+    match models {
+        [] => Ok(builder),
+        _ => Err(crate::missing_exporter("meter_provider.readers")),
+    }
+}
+
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+fn with_periodic_reader<R: RuntimeChannel>(
+    builder: MeterProviderBuilder,
+    model: &PeriodicReaderModel,
+    path: &str,
+    runtime: &R,
+) -> Result<MeterProviderBuilder, ConfigError> {
+    let path = format!("{path}.exporter");
+    let reader: PeriodicReader = match Exporter::from_model(&model.exporter, &path)? {
+        #[cfg(any(feature = "otlp-grpc", feature = "otlp-http"))]
+        Exporter::Otlp(otlp) => {
+            periodic_reader(model, otlp::exporter(otlp, &path)?, runtime.clone())
+        }
+        #[cfg(not(any(feature = "otlp-grpc", feature = "otlp-http")))]
+        Exporter::Otlp(_) => unreachable!("rejected by Exporter::from_model"),
+        #[cfg(feature = "console")]
+        Exporter::Console => periodic_reader(
+            model,
+            opentelemetry_stdout::MetricsExporter::default(),
+            runtime.clone(),
+        ),
+    };
+    Ok(builder.with_reader(reader))
+}
+
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+fn periodic_reader<E, R>(model: &PeriodicReaderModel, exporter: E, runtime: R) -> PeriodicReader
+where
+    E: PushMetricsExporter,
+    R: RuntimeChannel,
+{
+    let mut builder = PeriodicReader::builder(exporter, runtime);
+    if let Some(interval) = model.interval {
+        builder = builder.with_interval(Duration::from_millis(interval));
+    }
+    if let Some(timeout) = model.timeout {
+        builder = builder.with_timeout(Duration::from_millis(timeout));
+    }
+    builder.build()
+}
+
+fn instrument(view: &ViewModel) -> Instrument {
+    let selector = &view.selector;
+    let mut instrument = Instrument::new();
+    if let Some(name) = &selector.instrument_name {
+        instrument = instrument.name(name.clone());
+    }
+    if let Some(unit) = &selector.unit {
+        instrument = instrument.unit(Unit::new(unit.clone()));
+    }
+    if selector.meter_name.is_some()
+        || selector.meter_version.is_some()
+        || selector.meter_schema_url.is_some()
+    {
+        instrument = instrument.scope(InstrumentationLibrary::new(
+            selector.meter_name.clone().unwrap_or_default(),
+            selector.meter_version.clone(),
+            selector.meter_schema_url.clone(),
+            None,
+        ));
+    }
+    instrument.kind = selector.instrument_type.map(|kind| match kind {
+        InstrumentType::Counter => InstrumentKind::Counter,
+        InstrumentType::Gauge => InstrumentKind::Gauge,
+        InstrumentType::Histogram => InstrumentKind::Histogram,
+        InstrumentType::ObservableCounter => InstrumentKind::ObservableCounter,
+        InstrumentType::ObservableGauge => InstrumentKind::ObservableGauge,
+        InstrumentType::ObservableUpDownCounter => InstrumentKind::ObservableUpDownCounter,
+        InstrumentType::UpDownCounter => InstrumentKind::UpDownCounter,
+    });
+    instrument
+}
+
+fn stream(view: &ViewModel, path: &str) -> Result<Stream, ConfigError> {
+    let model = &view.stream;
+    let mut stream = Stream::new();
+    if let Some(name) = &model.name {
+        stream = stream.name(name.clone());
+    }
+    if let Some(description) = &model.description {
+        stream = stream.description(description.clone());
+    }
+    if let Some(aggregation) = &model.aggregation {
+        stream = stream.aggregation(self::aggregation(
+            aggregation,
+            &format!("{path}.aggregation"),
+        )?);
+    }
+    if let Some(keys) = &model.attribute_keys {
+        stream = stream.allowed_attribute_keys(keys.iter().cloned().map(Key::new));
+    }
+    Ok(stream)
+}
+
+fn aggregation(model: &AggregationModel, path: &str) -> Result<Aggregation, ConfigError> {
+    let mut aggregations = Vec::new();
+    if model.default.is_some() {
+        aggregations.push(Aggregation::Default);
+    }
+    if model.drop.is_some() {
+        aggregations.push(Aggregation::Drop);
+    }
+    if model.sum.is_some() {
+        aggregations.push(Aggregation::Sum);
+    }
+    if model.last_value.is_some() {
+        aggregations.push(Aggregation::LastValue);
+    }
+    if let Some(histogram) = &model.explicit_bucket_histogram {
+        aggregations.push(Aggregation::ExplicitBucketHistogram {
+            boundaries: histogram
+                .boundaries
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BOUNDARIES.to_vec()),
+            record_min_max: histogram.record_min_max.unwrap_or(true),
+        });
+    }
+    if let Some(histogram) = &model.base2_exponential_bucket_histogram {
+        aggregations.push(Aggregation::Base2ExponentialHistogram {
+            max_size: histogram.max_size.unwrap_or(DEFAULT_MAX_SIZE),
+            max_scale: histogram.max_scale.unwrap_or(DEFAULT_MAX_SCALE),
+            record_min_max: histogram.record_min_max.unwrap_or(true),
+        });
+    }
+
+    match aggregations.pop() {
+        Some(aggregation) if aggregations.is_empty() => {
+            aggregation
+                .validate()
+                .map_err(|err| ConfigError::invalid(path, err.to_string()))?;
+            Ok(aggregation)
+        }
+        _ => Err(ConfigError::invalid(
+            path,
+            "exactly one of `default`, `drop`, `sum`, `last_value`, `explicit_bucket_histogram` or `base2_exponential_bucket_histogram` must be set",
+        )),
+    }
+}
+
+#[cfg(any(feature = "otlp-grpc", feature = "otlp-http"))]
+mod otlp {
+    use opentelemetry_sdk::metrics::{
+        data::Temporality,
+        reader::{
+            AggregationSelector, DefaultAggregationSelector, DefaultTemporalitySelector,
+            TemporalitySelector,
+        },
+        Aggregation, InstrumentKind,
+    };
+
+    use super::{DEFAULT_MAX_SCALE, DEFAULT_MAX_SIZE};
+    use crate::{
+        model::{DefaultHistogramAggregation, OtlpModel, TemporalityPreference},
+        ConfigError,
+    };
+
+    /// Builds the OTLP exporter configured by `model`, at `path`.
+    pub(super) fn exporter(
+        model: &OtlpModel,
+        path: &str,
+    ) -> Result<opentelemetry_otlp::MetricsExporter, ConfigError> {
+        let temporality: Box<dyn TemporalitySelector> = match model.temporality_preference {
+            Some(TemporalityPreference::Delta) => Box::new(DeltaTemporalitySelector),
+            Some(TemporalityPreference::Cumulative) | None => {
+                Box::new(DefaultTemporalitySelector::new())
+            }
+        };
+        let aggregation: Box<dyn AggregationSelector> = match model.default_histogram_aggregation {
+            Some(DefaultHistogramAggregation::Base2ExponentialBucketHistogram) => {
+                Box::new(ExponentialHistogramAggregationSelector)
+            }
+            Some(DefaultHistogramAggregation::ExplicitBucketHistogram) | None => {
+                Box::new(DefaultAggregationSelector::new())
+            }
+        };
+        opentelemetry_otlp::MetricsExporterBuilder::from(crate::exporter::otlp_builder(
+            model, path,
+        )?)
+        .build_metrics_exporter(temporality, aggregation)
+        .map_err(|err| ConfigError::exporter(path, err))
+    }
+
+    /// Selects the delta temporality for all instruments except the up down
+    /// counters, as the `delta` temporality preference of the OTLP exporter.
+    #[derive(Debug)]
+    struct DeltaTemporalitySelector;
+
+    impl TemporalitySelector for DeltaTemporalitySelector {
+        fn temporality(&self, kind: InstrumentKind) -> Temporality {
+            match kind {
+                InstrumentKind::UpDownCounter | InstrumentKind::ObservableUpDownCounter => {
+                    Temporality::Cumulative
+                }
+                _ => Temporality::Delta,
+            }
+        }
+    }
+
+    /// Selects the base2 exponential bucket histogram aggregation for
+    /// histograms, and the default aggregation for the other instruments.
+    #[derive(Debug)]
+    struct ExponentialHistogramAggregationSelector;
+
+    impl AggregationSelector for ExponentialHistogramAggregationSelector {
+        fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+            match kind {
+                InstrumentKind::Histogram => Aggregation::Base2ExponentialHistogram {
+                    max_size: DEFAULT_MAX_SIZE,
+                    max_scale: DEFAULT_MAX_SCALE,
+                    record_min_max: true,
+                },
+                _ => DefaultAggregationSelector::new().aggregation(kind),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry_sdk::metrics::{Aggregation, InstrumentKind};
+    use opentelemetry_sdk::runtime;
+
+    use crate::{ConfigError, Configuration};
+
+    #[cfg(feature = "console")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_build() {
+        let config = Configuration::parse(
+            r#"
+file_format: "0.1"
+meter_provider:
+  readers:
+    - periodic:
+        interval: 1000
+        exporter:
+          console:
+  views:
+    - selector:
+        instrument_name: http.server.duration
+        instrument_type: histogram
+        meter_name: my-meter
+      stream:
+        name: latency
+        aggregation:
+          explicit_bucket_histogram:
+            boundaries: [1, 10, 100]
+        attribute_keys: [http.route]
+"#,
+        )
+        .unwrap();
+        let providers = config.build(runtime::Tokio).expect("valid configuration");
+        assert!(providers.meter_provider.is_some());
+    }
+
+    #[test]
+    fn test_instrument() {
+        let config = Configuration::parse(
+            "file_format: \"0.1\"\nmeter_provider:\n  views:\n    - selector:\n        instrument_name: requests\n        instrument_type: up_down_counter\n        unit: ms\n        meter_version: \"1.0\"\n      stream: {}",
+        )
+        .unwrap();
+        let view = &config.model.meter_provider.as_ref().unwrap().views[0];
+        let instrument = super::instrument(view);
+        assert_eq!(instrument.name, "requests");
+        assert_eq!(instrument.kind, Some(InstrumentKind::UpDownCounter));
+        assert_eq!(instrument.unit.as_str(), "ms");
+        assert_eq!(instrument.scope.name, "");
+        assert_eq!(instrument.scope.version.as_deref(), Some("1.0"));
+    }
+
+    #[test]
+    fn test_aggregation() {
+        let config = Configuration::parse(
+            "file_format: \"0.1\"\nmeter_provider:\n  views:\n    - selector: {}\n      stream:\n        aggregation:\n          base2_exponential_bucket_histogram:\n            max_scale: 10",
+        )
+        .unwrap();
+        let view = &config.model.meter_provider.as_ref().unwrap().views[0];
+        let stream = super::stream(view, "meter_provider.views[0].stream").unwrap();
+        assert_eq!(
+            stream.aggregation,
+            Some(Aggregation::Base2ExponentialHistogram {
+                max_size: 160,
+                max_scale: 10,
+                record_min_max: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        for (text, path) in [
+            (
+                "file_format: \"0.1\"\nmeter_provider:\n  readers:\n    - {}",
+                "meter_provider.readers[0]",
+            ),
+            (
+                "file_format: \"0.1\"\nmeter_provider:\n  views:\n    - selector: {}\n      stream:\n        aggregation:\n          sum:\n          drop:",
+                "meter_provider.views[0].stream.aggregation",
+            ),
+            (
+                "file_format: \"0.1\"\nmeter_provider:\n  views:\n    - selector: {}\n      stream:\n        aggregation:\n          explicit_bucket_histogram:\n            boundaries: [10, 1]",
+                "meter_provider.views[0].stream.aggregation",
+            ),
+        ] {
+            let config = Configuration::parse(text).unwrap();
+            match config.build(runtime::Tokio) {
+                Err(ConfigError::Invalid {
+                    path: error_path, ..
+                }) => assert_eq!(error_path, path, "{text}"),
+                other => panic!("unexpected result {other:?} for {text}"),
+            }
+        }
+    }
+}
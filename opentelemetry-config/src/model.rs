@@ -0,0 +1,450 @@
+//! The subset of the [configuration schema] supported by this crate.
+//!
+//! Durations are in milliseconds, as in the schema.
+//!
+//! [configuration schema]: https://github.com/open-telemetry/opentelemetry-configuration
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+use std::collections::BTreeMap;
+
+#[cfg(not(all(
+    feature = "trace",
+    feature = "metrics",
+    feature = "logs",
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+)))]
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+#[cfg(any(
+    feature = "trace",
+    feature = "metrics",
+    all(
+        feature = "logs",
+        any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+    )
+))]
+use serde::Deserializer;
+
+/// The versions of the configuration schema this crate can read.
+pub(crate) const SUPPORTED_FILE_FORMATS: [&str; 2] = ["0.1", "0.2"];
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Model {
+    pub(crate) file_format: String,
+    #[serde(default)]
+    pub(crate) disabled: bool,
+    #[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+    #[serde(default)]
+    pub(crate) resource: ResourceModel,
+    /// Ignored, as only used by the providers.
+    #[cfg(not(any(feature = "trace", feature = "metrics", feature = "logs")))]
+    #[serde(rename = "resource")]
+    _resource: Option<IgnoredAny>,
+    #[cfg(any(feature = "trace", feature = "logs"))]
+    #[serde(default)]
+    pub(crate) attribute_limits: AttributeLimitsModel,
+    /// Ignored, as only used by the tracer and logger providers.
+    #[cfg(not(any(feature = "trace", feature = "logs")))]
+    #[serde(rename = "attribute_limits")]
+    _attribute_limits: Option<IgnoredAny>,
+    #[cfg(feature = "trace")]
+    pub(crate) tracer_provider: Option<TracerProviderModel>,
+    /// Rejected, as the `trace` feature is disabled.
+    #[cfg(not(feature = "trace"))]
+    pub(crate) tracer_provider: Option<IgnoredAny>,
+    #[cfg(feature = "metrics")]
+    pub(crate) meter_provider: Option<MeterProviderModel>,
+    /// Rejected, as the `metrics` feature is disabled.
+    #[cfg(not(feature = "metrics"))]
+    pub(crate) meter_provider: Option<IgnoredAny>,
+    #[cfg(feature = "logs")]
+    pub(crate) logger_provider: Option<LoggerProviderModel>,
+    /// Rejected, as the `logs` feature is disabled.
+    #[cfg(not(feature = "logs"))]
+    pub(crate) logger_provider: Option<IgnoredAny>,
+}
+
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ResourceModel {
+    #[serde(default)]
+    pub(crate) attributes: BTreeMap<String, AttributeValue>,
+    pub(crate) schema_url: Option<String>,
+}
+
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum AttributeValue {
+    Bool(bool),
+    Int(i64),
+    Double(f64),
+    String(String),
+    BoolArray(Vec<bool>),
+    IntArray(Vec<i64>),
+    DoubleArray(Vec<f64>),
+    StringArray(Vec<String>),
+}
+
+/// Limits applying to the attributes of all signals, unless overridden by the
+/// limits of a provider.
+#[cfg(any(feature = "trace", feature = "logs"))]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AttributeLimitsModel {
+    pub(crate) attribute_value_length_limit: Option<u32>,
+    pub(crate) attribute_count_limit: Option<u32>,
+}
+
+#[cfg(feature = "trace")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TracerProviderModel {
+    #[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+    #[serde(default)]
+    pub(crate) processors: Vec<ProcessorModel>,
+    /// Rejected, as no processor can be built without an exporter feature.
+    #[cfg(not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")))]
+    #[serde(default)]
+    pub(crate) processors: Vec<IgnoredAny>,
+    #[serde(default)]
+    pub(crate) limits: SpanLimitsModel,
+    pub(crate) sampler: Option<SamplerModel>,
+}
+
+#[cfg(feature = "trace")]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SpanLimitsModel {
+    pub(crate) attribute_value_length_limit: Option<u32>,
+    pub(crate) attribute_count_limit: Option<u32>,
+    pub(crate) event_count_limit: Option<u32>,
+    pub(crate) link_count_limit: Option<u32>,
+    pub(crate) event_attribute_count_limit: Option<u32>,
+    pub(crate) link_attribute_count_limit: Option<u32>,
+}
+
+/// A span or log record processor, of which exactly one kind must be set.
+#[cfg(all(
+    any(feature = "trace", feature = "logs"),
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ProcessorModel {
+    pub(crate) batch: Option<BatchProcessorModel>,
+    pub(crate) simple: Option<SimpleProcessorModel>,
+}
+
+#[cfg(all(
+    any(feature = "trace", feature = "logs"),
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BatchProcessorModel {
+    pub(crate) schedule_delay: Option<u64>,
+    pub(crate) export_timeout: Option<u64>,
+    pub(crate) max_queue_size: Option<usize>,
+    pub(crate) max_export_batch_size: Option<usize>,
+    pub(crate) exporter: ExporterModel,
+}
+
+#[cfg(all(
+    any(feature = "trace", feature = "logs"),
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SimpleProcessorModel {
+    pub(crate) exporter: ExporterModel,
+}
+
+/// An exporter, of which exactly one kind must be set.
+#[cfg(all(
+    any(feature = "trace", feature = "metrics", feature = "logs"),
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ExporterModel {
+    pub(crate) otlp: Option<OtlpModel>,
+    #[serde(default, deserialize_with = "present")]
+    pub(crate) console: Option<Empty>,
+}
+
+#[cfg(all(
+    any(feature = "trace", feature = "metrics", feature = "logs"),
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(
+    not(any(feature = "otlp-grpc", feature = "otlp-http")),
+    allow(dead_code)
+)]
+pub(crate) struct OtlpModel {
+    pub(crate) protocol: OtlpProtocol,
+    pub(crate) endpoint: Option<String>,
+    #[serde(default)]
+    pub(crate) headers: BTreeMap<String, String>,
+    pub(crate) compression: Option<OtlpCompression>,
+    pub(crate) timeout: Option<u64>,
+    /// Only used by metric exporters.
+    #[cfg(feature = "metrics")]
+    pub(crate) temporality_preference: Option<TemporalityPreference>,
+    #[cfg(not(feature = "metrics"))]
+    #[serde(rename = "temporality_preference")]
+    _temporality_preference: Option<IgnoredAny>,
+    /// Only used by metric exporters.
+    #[cfg(feature = "metrics")]
+    pub(crate) default_histogram_aggregation: Option<DefaultHistogramAggregation>,
+    #[cfg(not(feature = "metrics"))]
+    #[serde(rename = "default_histogram_aggregation")]
+    _default_histogram_aggregation: Option<IgnoredAny>,
+}
+
+#[cfg(all(
+    any(feature = "trace", feature = "metrics", feature = "logs"),
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub(crate) enum OtlpProtocol {
+    #[serde(rename = "grpc")]
+    Grpc,
+    #[serde(rename = "http/protobuf")]
+    HttpProtobuf,
+    #[serde(rename = "http/json")]
+    HttpJson,
+}
+
+#[cfg(all(
+    any(feature = "trace", feature = "metrics", feature = "logs"),
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OtlpCompression {
+    Gzip,
+    None,
+}
+
+#[cfg(all(
+    feature = "metrics",
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TemporalityPreference {
+    Cumulative,
+    Delta,
+}
+
+#[cfg(all(
+    feature = "metrics",
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DefaultHistogramAggregation {
+    ExplicitBucketHistogram,
+    Base2ExponentialBucketHistogram,
+}
+
+/// A sampler, of which exactly one kind must be set.
+#[cfg(feature = "trace")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SamplerModel {
+    #[serde(default, deserialize_with = "present")]
+    pub(crate) always_on: Option<Empty>,
+    #[serde(default, deserialize_with = "present")]
+    pub(crate) always_off: Option<Empty>,
+    pub(crate) trace_id_ratio_based: Option<TraceIdRatioBasedModel>,
+    pub(crate) parent_based: Option<ParentBasedModel>,
+}
+
+#[cfg(feature = "trace")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TraceIdRatioBasedModel {
+    pub(crate) ratio: f64,
+}
+
+#[cfg(feature = "trace")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ParentBasedModel {
+    /// Defaults to `always_on`.
+    pub(crate) root: Option<Box<SamplerModel>>,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MeterProviderModel {
+    #[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+    #[serde(default)]
+    pub(crate) readers: Vec<MetricReaderModel>,
+    /// Rejected, as no reader can be built without an exporter feature.
+    #[cfg(not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")))]
+    #[serde(default)]
+    pub(crate) readers: Vec<IgnoredAny>,
+    #[serde(default)]
+    pub(crate) views: Vec<ViewModel>,
+}
+
+/// A metric reader, of which exactly one kind must be set.
+#[cfg(all(
+    feature = "metrics",
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MetricReaderModel {
+    pub(crate) periodic: Option<PeriodicReaderModel>,
+}
+
+#[cfg(all(
+    feature = "metrics",
+    any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+))]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PeriodicReaderModel {
+    pub(crate) interval: Option<u64>,
+    pub(crate) timeout: Option<u64>,
+    pub(crate) exporter: ExporterModel,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ViewModel {
+    pub(crate) selector: ViewSelectorModel,
+    pub(crate) stream: ViewStreamModel,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ViewSelectorModel {
+    pub(crate) instrument_name: Option<String>,
+    pub(crate) instrument_type: Option<InstrumentType>,
+    pub(crate) unit: Option<String>,
+    pub(crate) meter_name: Option<String>,
+    pub(crate) meter_version: Option<String>,
+    pub(crate) meter_schema_url: Option<String>,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum InstrumentType {
+    Counter,
+    Gauge,
+    Histogram,
+    ObservableCounter,
+    ObservableGauge,
+    ObservableUpDownCounter,
+    UpDownCounter,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ViewStreamModel {
+    pub(crate) name: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) aggregation: Option<AggregationModel>,
+    pub(crate) attribute_keys: Option<Vec<String>>,
+}
+
+/// An aggregation, of which exactly one kind must be set.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AggregationModel {
+    #[serde(default, deserialize_with = "present")]
+    pub(crate) default: Option<Empty>,
+    #[serde(default, deserialize_with = "present")]
+    pub(crate) drop: Option<Empty>,
+    #[serde(default, deserialize_with = "present")]
+    pub(crate) sum: Option<Empty>,
+    #[serde(default, deserialize_with = "present")]
+    pub(crate) last_value: Option<Empty>,
+    pub(crate) explicit_bucket_histogram: Option<ExplicitBucketHistogramModel>,
+    pub(crate) base2_exponential_bucket_histogram: Option<Base2ExponentialBucketHistogramModel>,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ExplicitBucketHistogramModel {
+    pub(crate) boundaries: Option<Vec<f64>>,
+    pub(crate) record_min_max: Option<bool>,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Base2ExponentialBucketHistogramModel {
+    pub(crate) max_scale: Option<i8>,
+    pub(crate) max_size: Option<u32>,
+    pub(crate) record_min_max: Option<bool>,
+}
+
+#[cfg(feature = "logs")]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LoggerProviderModel {
+    #[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+    #[serde(default)]
+    pub(crate) processors: Vec<ProcessorModel>,
+    /// Rejected, as no processor can be built without an exporter feature.
+    #[cfg(not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")))]
+    #[serde(default)]
+    pub(crate) processors: Vec<IgnoredAny>,
+    #[serde(default)]
+    pub(crate) limits: LogRecordLimitsModel,
+}
+
+#[cfg(feature = "logs")]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LogRecordLimitsModel {
+    pub(crate) attribute_value_length_limit: Option<u32>,
+    pub(crate) attribute_count_limit: Option<u32>,
+}
+
+/// The value of the properties without configuration, such as `console: {}`.
+#[cfg(any(
+    feature = "trace",
+    feature = "metrics",
+    all(
+        feature = "logs",
+        any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+    )
+))]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Empty {}
+
+/// Deserializes a property which may be set without a value, such as
+/// `always_on:`, as present.
+#[cfg(any(
+    feature = "trace",
+    feature = "metrics",
+    all(
+        feature = "logs",
+        any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")
+    )
+))]
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Option::<T>::deserialize(deserializer).map(|value| Some(value.unwrap_or_default()))
+}
@@ -0,0 +1,108 @@
+use opentelemetry::{Array, KeyValue, StringValue, Value};
+use opentelemetry_sdk::{
+    resource::{ResourceDetector, TelemetryResourceDetector},
+    Resource,
+};
+use std::time::Duration;
+
+use crate::model::{AttributeValue, ResourceModel};
+
+const SERVICE_NAME: &str = "service.name";
+
+/// Builds the resource of all providers.
+///
+/// Unlike [Resource::default], the `OTEL_RESOURCE_ATTRIBUTES` and
+/// `OTEL_SERVICE_NAME` environment variables are ignored, the configuration
+/// file referencing them explicitly if needed.
+pub(crate) fn build(model: &ResourceModel) -> Resource {
+    let attributes = model
+        .attributes
+        .iter()
+        .map(|(key, value)| KeyValue::new(key.clone(), to_value(value)));
+    let configured = match &model.schema_url {
+        Some(schema_url) => Resource::from_schema_url(attributes, schema_url.clone()),
+        None => Resource::new(attributes),
+    };
+
+    let mut resource = TelemetryResourceDetector.detect(Duration::from_secs(0));
+    if !model.attributes.contains_key(SERVICE_NAME) {
+        resource = resource.merge(&Resource::new([KeyValue::new(
+            SERVICE_NAME,
+            "unknown_service",
+        )]));
+    }
+    resource.merge(&configured)
+}
+
+fn to_value(value: &AttributeValue) -> Value {
+    match value {
+        AttributeValue::Bool(b) => Value::Bool(*b),
+        AttributeValue::Int(i) => Value::I64(*i),
+        AttributeValue::Double(d) => Value::F64(*d),
+        AttributeValue::String(s) => Value::String(s.clone().into()),
+        AttributeValue::BoolArray(values) => Value::Array(Array::Bool(values.clone())),
+        AttributeValue::IntArray(values) => Value::Array(Array::I64(values.clone())),
+        AttributeValue::DoubleArray(values) => Value::Array(Array::F64(values.clone())),
+        AttributeValue::StringArray(values) => Value::Array(Array::String(
+            values.iter().cloned().map(StringValue::from).collect(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::{Array, Key, Value};
+
+    use crate::Configuration;
+
+    #[test]
+    fn test_resource() {
+        let config = Configuration::parse(
+            r#"
+file_format: "0.1"
+resource:
+  schema_url: https://opentelemetry.io/schemas/1.23.0
+  attributes:
+    service.name: checkout
+    replicas: 3
+    ratio: 0.5
+    canary: true
+    regions: [eu, us]
+"#,
+        )
+        .unwrap();
+        let resource = super::build(&config.model.resource);
+
+        assert_eq!(
+            resource.schema_url(),
+            Some("https://opentelemetry.io/schemas/1.23.0")
+        );
+        for (key, value) in [
+            ("service.name", Value::from("checkout")),
+            ("replicas", Value::I64(3)),
+            ("ratio", Value::F64(0.5)),
+            ("canary", Value::Bool(true)),
+            (
+                "regions",
+                Value::Array(Array::String(vec!["eu".into(), "us".into()])),
+            ),
+            ("telemetry.sdk.language", Value::from("rust")),
+        ] {
+            assert_eq!(
+                resource.get(Key::from_static_str(key)),
+                Some(value),
+                "{key}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_service_name() {
+        let config = Configuration::parse("file_format: \"0.1\"").unwrap();
+        let resource = super::build(&config.model.resource);
+        assert_eq!(
+            resource.get(Key::from_static_str("service.name")),
+            Some(Value::from("unknown_service"))
+        );
+    }
+}
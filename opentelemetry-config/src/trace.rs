@@ -0,0 +1,304 @@
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+use std::time::Duration;
+
+#[cfg(not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")))]
+use serde::de::IgnoredAny;
+
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+use opentelemetry_sdk::{
+    export::trace::SpanExporter,
+    trace::{BatchConfigBuilder, BatchSpanProcessor},
+};
+use opentelemetry_sdk::{
+    runtime::RuntimeChannel,
+    trace::{self, Sampler, SpanLimits, TracerProvider},
+    Resource,
+};
+
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+use crate::{
+    exporter::{Exporter, Processor},
+    model::ProcessorModel,
+};
+use crate::{
+    model::{AttributeLimitsModel, SamplerModel, SpanLimitsModel, TracerProviderModel},
+    ConfigError,
+};
+
+/// Builds the tracer provider configured by the `tracer_provider` section.
+pub(crate) fn build<R: RuntimeChannel>(
+    model: &TracerProviderModel,
+    attribute_limits: &AttributeLimitsModel,
+    resource: Resource,
+    runtime: R,
+) -> Result<TracerProvider, ConfigError> {
+    let mut config = trace::config()
+        .with_span_limits(span_limits(&model.limits, attribute_limits))
+        .with_resource(resource);
+    if let Some(sampler) = &model.sampler {
+        config = config.with_sampler(self::sampler(sampler, "tracer_provider.sampler")?);
+    }
+
+    let builder = TracerProvider::builder().with_config(config);
+    Ok(with_processors(builder, &model.processors, runtime)?.build())
+}
+
+/// Adds the processors configured at `tracer_provider.processors`.
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+fn with_processors<R: RuntimeChannel>(
+    mut builder: trace::Builder,
+    models: &[ProcessorModel],
+    runtime: R,
+) -> Result<trace::Builder, ConfigError> {
+    for (i, processor) in models.iter().enumerate() {
+        let path = format!("tracer_provider.processors[{i}]");
+        let (processor, exporter, path) = Processor::from_model(processor, &path)?;
+        builder = match Exporter::from_model(exporter, &path)? {
+            #[cfg(any(feature = "otlp-grpc", feature = "otlp-http"))]
+            Exporter::Otlp(otlp) => {
+                let exporter = opentelemetry_otlp::SpanExporterBuilder::from(
+                    crate::exporter::otlp_builder(otlp, &path)?,
+                )
+                .build_span_exporter()
+                .map_err(|err| ConfigError::exporter(&path, err))?;
+                with_processor(builder, processor, exporter, runtime.clone())
+            }
+            #[cfg(not(any(feature = "otlp-grpc", feature = "otlp-http")))]
+            Exporter::Otlp(_) => unreachable!("rejected by Exporter::from_model"),
+            #[cfg(feature = "console")]
+            Exporter::Console => with_processor(
+                builder,
+                processor,
+                opentelemetry_stdout::SpanExporter::default(),
+                runtime.clone(),
+            ),
+        };
+    }
+
+    Ok(builder)
+}
+
+/// Rejects the processors, which can't be built without an exporter feature.
+#[cfg(not(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http")))]
+fn with_processors<R: RuntimeChannel>(
+    builder: trace::Builder,
+    models: &[IgnoredAny],
+    _runtime: R,
+) -> Result<trace::Builder, ConfigError> {
+    match models {
+        [] => Ok(builder),
+        _ => Err(crate::missing_exporter("tracer_provider.processors")),
+    }
+}
+
+#[cfg(any(feature = "console", feature = "otlp-grpc", feature = "otlp-http"))]
+fn with_processor<E, R>(
+    builder: trace::Builder,
+    processor: Processor<'_>,
+    exporter: E,
+    runtime: R,
+) -> trace::Builder
+where
+    E: SpanExporter + 'static,
+    R: RuntimeChannel,
+{
+    match processor {
+        Processor::Batch(batch) => {
+            let mut config = BatchConfigBuilder::default();
+            if let Some(delay) = batch.schedule_delay {
+                config = config.with_scheduled_delay(Duration::from_millis(delay));
+            }
+            if let Some(timeout) = batch.export_timeout {
+                config = config.with_max_export_timeout(Duration::from_millis(timeout));
+            }
+            if let Some(size) = batch.max_queue_size {
+                config = config.with_max_queue_size(size);
+            }
+            if let Some(size) = batch.max_export_batch_size {
+                config = config.with_max_export_batch_size(size);
+            }
+            builder.with_span_processor(
+                BatchSpanProcessor::builder(exporter, runtime)
+                    .with_batch_config(config.build())
+                    .build(),
+            )
+        }
+        Processor::Simple => builder.with_simple_exporter(exporter),
+    }
+}
+
+fn span_limits(model: &SpanLimitsModel, attribute_limits: &AttributeLimitsModel) -> SpanLimits {
+    let mut limits = SpanLimits::default();
+    if let Some(limit) = model
+        .attribute_count_limit
+        .or(attribute_limits.attribute_count_limit)
+    {
+        limits.max_attributes_per_span = limit;
+    }
+    if let Some(limit) = model.event_count_limit {
+        limits.max_events_per_span = limit;
+    }
+    if let Some(limit) = model.link_count_limit {
+        limits.max_links_per_span = limit;
+    }
+    if let Some(limit) = model
+        .event_attribute_count_limit
+        .or(attribute_limits.attribute_count_limit)
+    {
+        limits.max_attributes_per_event = limit;
+    }
+    if let Some(limit) = model
+        .link_attribute_count_limit
+        .or(attribute_limits.attribute_count_limit)
+    {
+        limits.max_attributes_per_link = limit;
+    }
+    if let Some(limit) = model
+        .attribute_value_length_limit
+        .or(attribute_limits.attribute_value_length_limit)
+    {
+        limits.max_attribute_value_length = Some(limit);
+    }
+    limits
+}
+
+fn sampler(model: &SamplerModel, path: &str) -> Result<Sampler, ConfigError> {
+    match model {
+        SamplerModel {
+            always_on: Some(_),
+            always_off: None,
+            trace_id_ratio_based: None,
+            parent_based: None,
+        } => Ok(Sampler::AlwaysOn),
+        SamplerModel {
+            always_on: None,
+            always_off: Some(_),
+            trace_id_ratio_based: None,
+            parent_based: None,
+        } => Ok(Sampler::AlwaysOff),
+        SamplerModel {
+            always_on: None,
+            always_off: None,
+            trace_id_ratio_based: Some(ratio_based),
+            parent_based: None,
+        } => {
+            if !(0.0..=1.0).contains(&ratio_based.ratio) {
+                return Err(ConfigError::invalid(
+                    format!("{path}.trace_id_ratio_based.ratio"),
+                    "the ratio must be between 0 and 1",
+                ));
+            }
+            Ok(Sampler::TraceIdRatioBased(ratio_based.ratio))
+        }
+        SamplerModel {
+            always_on: None,
+            always_off: None,
+            trace_id_ratio_based: None,
+            parent_based: Some(parent_based),
+        } => {
+            let root = match &parent_based.root {
+                Some(root) => sampler(root, &format!("{path}.parent_based.root"))?,
+                None => Sampler::AlwaysOn,
+            };
+            Ok(Sampler::ParentBased(Box::new(root)))
+        }
+        _ => Err(ConfigError::invalid(
+            path,
+            "exactly one of `always_on`, `always_off`, `trace_id_ratio_based` or `parent_based` must be set",
+        )),
+    }
+}
+
+#[cfg(all(test, feature = "console"))]
+mod tests {
+    use opentelemetry_sdk::runtime;
+
+    use crate::{ConfigError, Configuration};
+
+    fn build(text: &str) -> Result<opentelemetry_sdk::trace::TracerProvider, ConfigError> {
+        let config = Configuration::parse(text)?;
+        Ok(config
+            .build(runtime::Tokio)?
+            .tracer_provider
+            .expect("tracer provider"))
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_build() {
+        let provider = build(
+            r#"
+file_format: "0.1"
+attribute_limits:
+  attribute_count_limit: 16
+  attribute_value_length_limit: 32
+tracer_provider:
+  processors:
+    - batch:
+        schedule_delay: 100
+        exporter:
+          console:
+    - simple:
+        exporter:
+          console: {}
+  limits:
+    event_count_limit: 8
+    link_attribute_count_limit: 4
+  sampler:
+    parent_based:
+      root:
+        trace_id_ratio_based:
+          ratio: 0.25
+"#,
+        )
+        .expect("valid configuration");
+
+        assert_eq!(provider.span_processors().len(), 2);
+        let config = provider.config();
+        assert_eq!(
+            format!("{:?}", config.sampler),
+            "ParentBased(TraceIdRatioBased(0.25))"
+        );
+        assert_eq!(config.span_limits.max_attributes_per_span, 16);
+        assert_eq!(config.span_limits.max_events_per_span, 8);
+        assert_eq!(config.span_limits.max_links_per_span, 128);
+        assert_eq!(config.span_limits.max_attributes_per_event, 16);
+        assert_eq!(config.span_limits.max_attributes_per_link, 4);
+        assert_eq!(config.span_limits.max_attribute_value_length, Some(32));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_parent_based_defaults_to_always_on() {
+        let provider =
+            build("file_format: \"0.1\"\ntracer_provider:\n  sampler:\n    parent_based: {}")
+                .expect("valid configuration");
+        assert_eq!(
+            format!("{:?}", provider.config().sampler),
+            "ParentBased(AlwaysOn)"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_invalid() {
+        for (text, path) in [
+            (
+                "file_format: \"0.1\"\ntracer_provider:\n  sampler:\n    trace_id_ratio_based:\n      ratio: 1.5",
+                "tracer_provider.sampler.trace_id_ratio_based.ratio",
+            ),
+            (
+                "file_format: \"0.1\"\ntracer_provider:\n  sampler:\n    always_on:\n    always_off:",
+                "tracer_provider.sampler",
+            ),
+            (
+                "file_format: \"0.1\"\ntracer_provider:\n  processors:\n    - simple:\n        exporter: {}",
+                "tracer_provider.processors[0].simple.exporter",
+            ),
+        ] {
+            match build(text) {
+                Err(ConfigError::Invalid {
+                    path: error_path, ..
+                }) => assert_eq!(error_path, path, "{text}"),
+                other => panic!("unexpected result {other:?} for {text}"),
+            }
+        }
+    }
+}
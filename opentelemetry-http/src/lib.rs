@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter};
 
 #[doc(no_inline)]
 pub use bytes::Bytes;
@@ -66,20 +66,22 @@ pub trait HttpClient: Debug + Send + Sync {
 
 #[cfg(feature = "reqwest")]
 mod reqwest {
+    use crate::ResponseExt;
+
     use super::{async_trait, Bytes, HttpClient, HttpError, Request, Response};
 
     #[async_trait]
     impl HttpClient for reqwest::Client {
         async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Bytes>, HttpError> {
             let request = request.try_into()?;
-            let mut response = self.execute(request).await?.error_for_status()?;
+            let mut response = self.execute(request).await?;
             let headers = std::mem::take(response.headers_mut());
             let mut http_response = Response::builder()
                 .status(response.status())
                 .body(response.bytes().await?)?;
             *http_response.headers_mut() = headers;
 
-            Ok(http_response)
+            Ok(http_response.error_for_status()?)
         }
     }
 
@@ -87,14 +89,14 @@ mod reqwest {
     impl HttpClient for reqwest::blocking::Client {
         async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Bytes>, HttpError> {
             let request = request.try_into()?;
-            let mut response = self.execute(request)?.error_for_status()?;
+            let mut response = self.execute(request)?;
             let headers = std::mem::take(response.headers_mut());
             let mut http_response = Response::builder()
                 .status(response.status())
                 .body(response.bytes()?)?;
             *http_response.headers_mut() = headers;
 
-            Ok(http_response)
+            Ok(http_response.error_for_status()?)
         }
     }
 }
@@ -202,15 +204,67 @@ impl<T> ResponseExt for Response<T> {
         if self.status().is_success() {
             Ok(self)
         } else {
-            Err(format!("request failed with status {}", self.status()).into())
+            Err(Box::new(HttpStatusError {
+                status: self.status(),
+                headers: self.headers().clone(),
+            }))
         }
     }
 }
 
+/// Error returned when the server responds with a status that does not indicate success.
+///
+/// The built-in [`HttpClient`] implementations surface non-2xx responses as this error, so
+/// callers can downcast the [`HttpError`] to inspect the status code and response headers,
+/// e.g. to honor a `Retry-After` header.
+#[derive(Debug)]
+pub struct HttpStatusError {
+    status: http::StatusCode,
+    headers: http::HeaderMap,
+}
+
+impl HttpStatusError {
+    /// The status code of the response.
+    pub fn status(&self) -> http::StatusCode {
+        self.status
+    }
+
+    /// The headers of the response.
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+}
+
+impl Display for HttpStatusError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request failed with status {}", self.status)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn error_for_status_keeps_status_and_headers() {
+        let response = Response::builder()
+            .status(503)
+            .header("retry-after", "10")
+            .body(())
+            .unwrap();
+
+        let err = response.error_for_status().unwrap_err();
+        let status_error = err.downcast_ref::<HttpStatusError>().unwrap();
+        assert_eq!(status_error.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status_error.headers().get("retry-after").unwrap(), "10");
+        assert_eq!(
+            err.to_string(),
+            "request failed with status 503 Service Unavailable"
+        );
+    }
+
     #[test]
     fn http_headers_get() {
         let mut carrier = http::HeaderMap::new();
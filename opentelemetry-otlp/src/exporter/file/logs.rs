@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+use opentelemetry::logs::LogResult;
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+use opentelemetry_sdk::export::logs::{LogData, LogExporter};
+
+use super::OtlpFileClient;
+
+#[async_trait]
+impl LogExporter for OtlpFileClient {
+    async fn export(&mut self, batch: Vec<LogData>) -> LogResult<()> {
+        let req = ExportLogsServiceRequest {
+            resource_logs: batch.into_iter().map(Into::into).collect(),
+        };
+
+        Ok(self.write_request(&req)?)
+    }
+
+    fn shutdown(&mut self) {
+        OtlpFileClient::shutdown(self)
+    }
+}
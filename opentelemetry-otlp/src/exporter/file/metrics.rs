@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use opentelemetry::metrics::Result;
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_sdk::metrics::data::ResourceMetrics;
+
+use crate::metric::MetricsClient;
+
+use super::OtlpFileClient;
+
+#[async_trait]
+impl MetricsClient for OtlpFileClient {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()> {
+        let req: ExportMetricsServiceRequest = (&*metrics).into();
+
+        Ok(self.write_request(&req)?)
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        OtlpFileClient::shutdown(self);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,424 @@
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+use std::{
+    fs::{self, File, OpenOptions},
+    io,
+    path::Path,
+    sync::Mutex,
+};
+
+#[cfg(feature = "metrics")]
+mod metrics;
+
+#[cfg(feature = "logs")]
+mod logs;
+
+#[cfg(feature = "trace")]
+mod trace;
+
+/// Configuration for the OTLP file exporter.
+///
+/// The exporter writes each batch as a single line holding the JSON encoding of the
+/// `Export*ServiceRequest` of the signal, following the
+/// [OTLP JSON file format](https://opentelemetry.io/docs/specs/otel/protocol/file-exporter/).
+/// The lines can be read back by any OTLP JSON decoder, such as the `otlpjsonfile`
+/// receiver of the collector.
+///
+/// The exporter writes to stdout by default. Each exporter owns its destination, so
+/// exporters of different signals should write to different files.
+///
+/// ## Examples
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Write the spans to stdout
+/// # #[cfg(feature="trace")]
+/// let span_exporter = opentelemetry_otlp::new_exporter().file().build_span_exporter()?;
+///
+/// // Write the logs to `logs.jsonl`, keeping 3 rotated files of at most 10 MiB
+/// # #[cfg(feature="logs")]
+/// let log_exporter = opentelemetry_otlp::new_exporter()
+///     .file()
+///     .with_path("logs.jsonl")
+///     .with_max_file_size(10 * 1024 * 1024)
+///     .with_max_files(3)
+///     .build_log_exporter()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct FileExporterBuilder {
+    destination: Destination,
+    max_file_size: Option<u64>,
+    max_files: usize,
+}
+
+#[derive(Default)]
+// the destination is only opened by the exporters of the signals
+#[cfg_attr(
+    not(any(feature = "trace", feature = "metrics", feature = "logs")),
+    allow(dead_code)
+)]
+enum Destination {
+    #[default]
+    Stdout,
+    Writer(Box<dyn Write + Send>),
+    Path(PathBuf),
+}
+
+impl fmt::Debug for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Destination::Stdout => f.write_str("Stdout"),
+            Destination::Writer(_) => f.write_str("Writer"),
+            Destination::Path(path) => f.debug_tuple("Path").field(path).finish(),
+        }
+    }
+}
+
+impl FileExporterBuilder {
+    /// Write the lines to `writer` instead of stdout.
+    pub fn with_writer<W: Write + Send + 'static>(mut self, writer: W) -> Self {
+        self.destination = Destination::Writer(Box::new(writer));
+        self
+    }
+
+    /// Append the lines to the file at `path` instead of stdout.
+    ///
+    /// The file is created if it doesn't exist.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.destination = Destination::Path(path.into());
+        self
+    }
+
+    /// Rotate the file set with [`with_path`] before it grows larger than `max_file_size`
+    /// bytes.
+    ///
+    /// The file is rotated between two lines, so a single line larger than
+    /// `max_file_size` is still written to a file of its own. The file is never rotated
+    /// by default.
+    ///
+    /// [`with_path`]: FileExporterBuilder::with_path
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = Some(max_file_size);
+        self
+    }
+
+    /// Set how many rotated files are kept.
+    ///
+    /// When the file is rotated, `<path>` is renamed to `<path>.1`, `<path>.1` to
+    /// `<path>.2` and so on, and the oldest file is removed once there are `max_files`
+    /// rotated files. With `0`, the file is truncated when it's rotated.
+    ///
+    /// Default to be `0`.
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    #[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+    fn build_client(self) -> Result<OtlpFileClient, crate::Error> {
+        let output = match self.destination {
+            Destination::Stdout => Output::Writer(Box::new(io::stdout())),
+            Destination::Writer(writer) => Output::Writer(writer),
+            Destination::Path(path) => Output::File(RotatingFile::open(
+                path,
+                self.max_file_size,
+                self.max_files,
+            )?),
+        };
+
+        Ok(OtlpFileClient {
+            output: Mutex::new(Some(output)),
+        })
+    }
+
+    /// Create a span exporter with the current configuration
+    #[cfg(feature = "trace")]
+    pub fn build_span_exporter(
+        self,
+    ) -> Result<crate::SpanExporter, opentelemetry::trace::TraceError> {
+        Ok(crate::SpanExporter::new(self.build_client()?))
+    }
+
+    /// Create a log exporter with the current configuration
+    #[cfg(feature = "logs")]
+    pub fn build_log_exporter(self) -> opentelemetry::logs::LogResult<crate::LogExporter> {
+        Ok(crate::LogExporter::new(self.build_client()?))
+    }
+
+    /// Create a metrics exporter with the current configuration
+    #[cfg(feature = "metrics")]
+    pub fn build_metrics_exporter(
+        self,
+        aggregation_selector: Box<dyn opentelemetry_sdk::metrics::reader::AggregationSelector>,
+        temporality_selector: Box<dyn opentelemetry_sdk::metrics::reader::TemporalitySelector>,
+    ) -> opentelemetry::metrics::Result<crate::MetricsExporter> {
+        Ok(crate::MetricsExporter::new(
+            self.build_client()?,
+            temporality_selector,
+            aggregation_selector,
+        ))
+    }
+}
+
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+#[derive(Debug)]
+struct OtlpFileClient {
+    output: Mutex<Option<Output>>,
+}
+
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+impl OtlpFileClient {
+    /// Encode `req` as JSON and write it as a single line.
+    fn write_request<T: serde::Serialize>(&self, req: &T) -> Result<(), crate::Error> {
+        let mut line = serde_json::to_vec(req)?;
+        line.push(b'\n');
+
+        let mut output = self
+            .output
+            .lock()
+            .map_err(|_| crate::Error::PoisonedLock("file exporter"))?;
+        match output.as_mut() {
+            Some(output) => Ok(output.write_line(&line)?),
+            None => Err(crate::Error::ShutDown),
+        }
+    }
+
+    fn shutdown(&self) {
+        if let Ok(mut output) = self.output.lock() {
+            if let Some(mut output) = output.take() {
+                let _ = output.flush();
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+enum Output {
+    Writer(Box<dyn Write + Send>),
+    File(RotatingFile),
+}
+
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+impl fmt::Debug for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Output::Writer(_) => f.write_str("Writer"),
+            Output::File(file) => f.debug_tuple("File").field(file).finish(),
+        }
+    }
+}
+
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+impl Output {
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        match self {
+            Output::Writer(writer) => {
+                writer.write_all(line)?;
+                writer.flush()
+            }
+            Output::File(file) => file.write_line(line),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Writer(writer) => writer.flush(),
+            Output::File(file) => file.file.flush(),
+        }
+    }
+}
+
+/// A file rotated when it would grow larger than its max size.
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+#[derive(Debug)]
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: Option<u64>,
+    max_files: usize,
+}
+
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+impl RotatingFile {
+    fn open(path: PathBuf, max_size: Option<u64>, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(RotatingFile {
+            path,
+            file,
+            size,
+            max_size,
+            max_files,
+        })
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let line_size = line.len() as u64;
+        if let Some(max_size) = self.max_size {
+            if self.size > 0 && self.size + line_size > max_size {
+                self.rotate()?;
+            }
+        }
+
+        self.file.write_all(line)?;
+        self.size += line_size;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files > 0 {
+            let oldest = rotated_path(&self.path, self.max_files);
+            if oldest.exists() {
+                fs::remove_file(oldest)?;
+            }
+            for index in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, index);
+                if from.exists() {
+                    fs::rename(from, rotated_path(&self.path, index + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// The path of the `index`th rotated file, `<path>.<index>`.
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{index}"));
+    PathBuf::from(rotated)
+}
+
+#[cfg(all(test, any(feature = "trace", feature = "metrics", feature = "logs")))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "opentelemetry-otlp-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("otlp.jsonl")
+    }
+
+    #[test]
+    fn test_write_lines() {
+        let buffer = SharedBuffer::default();
+        let client = FileExporterBuilder::default()
+            .with_writer(buffer.clone())
+            .build_client()
+            .unwrap();
+
+        client.write_request(&serde_json::json!({"a": 1})).unwrap();
+        client.write_request(&serde_json::json!({"b": 2})).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap(),
+            "{\"a\":1}\n{\"b\":2}\n"
+        );
+
+        client.shutdown();
+        assert!(matches!(
+            client.write_request(&serde_json::json!({})),
+            Err(crate::Error::ShutDown)
+        ));
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_export_spans() {
+        use opentelemetry_sdk::export::trace::SpanExporter;
+        use opentelemetry_sdk::testing::trace::new_test_export_span_data;
+
+        let buffer = SharedBuffer::default();
+        let mut client = FileExporterBuilder::default()
+            .with_writer(buffer.clone())
+            .build_client()
+            .unwrap();
+
+        futures_executor::block_on(client.export(vec![new_test_export_span_data()])).unwrap();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value =
+            serde_json::from_str(output.strip_suffix('\n').unwrap()).expect("a single JSON line");
+        let span = &line["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["traceId"], "00000000000000000000000000000001");
+        assert_eq!(span["spanId"], "0000000000000001");
+        assert_eq!(span["name"], "opentelemetry");
+        assert!(span["startTimeUnixNano"].is_string());
+    }
+
+    #[test]
+    fn test_rotation() {
+        let path = temp_path("rotation");
+        let client = FileExporterBuilder::default()
+            .with_path(&path)
+            .with_max_file_size(16)
+            .with_max_files(2)
+            .build_client()
+            .unwrap();
+
+        // each line is 8 bytes long, so each file holds 2 lines
+        for i in 0..7 {
+            client.write_request(&serde_json::json!({"i": i})).unwrap();
+        }
+        client.shutdown();
+
+        let read = |index| match index {
+            0 => fs::read_to_string(&path).unwrap(),
+            index => fs::read_to_string(rotated_path(&path, index)).unwrap(),
+        };
+        assert_eq!(read(0), "{\"i\":6}\n");
+        assert_eq!(read(1), "{\"i\":4}\n{\"i\":5}\n");
+        assert_eq!(read(2), "{\"i\":2}\n{\"i\":3}\n");
+        assert!(!rotated_path(&path, 3).exists());
+
+        // the size of an existing file counts towards the max size
+        let client = FileExporterBuilder::default()
+            .with_path(&path)
+            .with_max_file_size(16)
+            .build_client()
+            .unwrap();
+        client.write_request(&serde_json::json!({"i": 7})).unwrap();
+        client.write_request(&serde_json::json!({"i": 8})).unwrap();
+        client.shutdown();
+
+        assert_eq!(read(0), "{\"i\":8}\n");
+        assert_eq!(read(1), "{\"i\":4}\n{\"i\":5}\n");
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
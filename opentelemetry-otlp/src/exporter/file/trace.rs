@@ -0,0 +1,21 @@
+use futures_core::future::BoxFuture;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+
+use super::OtlpFileClient;
+
+impl SpanExporter for OtlpFileClient {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        let req = ExportTraceServiceRequest {
+            resource_spans: batch.into_iter().map(Into::into).collect(),
+        };
+
+        Box::pin(std::future::ready(
+            self.write_request(&req).map_err(Into::into),
+        ))
+    }
+
+    fn shutdown(&mut self) {
+        OtlpFileClient::shutdown(self)
+    }
+}
@@ -1,15 +1,18 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use http::{header::CONTENT_TYPE, Method};
 use opentelemetry::logs::{LogError, LogResult};
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceResponse;
 use opentelemetry_sdk::export::logs::{LogData, LogExporter};
 
-use super::OtlpHttpClient;
+use super::{decode_response, OtlpHttpClient, SendError};
+use crate::Protocol;
 
 #[async_trait]
 impl LogExporter for OtlpHttpClient {
     async fn export(&mut self, batch: Vec<LogData>) -> LogResult<()> {
+        self.reload_certificates::<LogError>();
+
         let client = self
             .client
             .lock()
@@ -19,32 +22,36 @@ impl LogExporter for OtlpHttpClient {
                 _ => Err(LogError::Other("exporter is already shut down".into())),
             })?;
 
-        let (body, content_type) = build_body(batch)?;
-        let mut request = http::Request::builder()
-            .method(Method::POST)
-            .uri(&self.collector_endpoint)
-            .header(CONTENT_TYPE, content_type)
-            .body(body)
-            .map_err(|e| crate::Error::RequestFailed(Box::new(e)))?;
+        let (body, content_type) = build_body(batch, self.protocol)?;
+        let request = self.export_request(body, content_type)?;
+        let request_uri = request.uri.to_string();
 
-        for (k, v) in &self.headers {
-            request.headers_mut().insert(k.clone(), v.clone());
+        match request.send(client).await {
+            Ok(body) => {
+                if let Some(partial_success) =
+                    decode_response::<ExportLogsServiceResponse>(&body, self.protocol)
+                        .and_then(|response| response.partial_success)
+                {
+                    self.exporter_metrics.partial_success::<LogError>(
+                        "log records",
+                        partial_success.rejected_log_records,
+                        partial_success.error_message,
+                    );
+                }
+                Ok(())
+            }
+            Err(SendError::Build(err)) => Err(crate::Error::RequestFailed(Box::new(err)).into()),
+            Err(SendError::Transport(err)) => Err(crate::Error::RequestFailed(err).into()),
+            Err(SendError::Response(response)) => {
+                let error = format!(
+                    "OpenTelemetry logs export failed. Url: {}, Status Code: {}, Response: {:?}",
+                    request_uri,
+                    response.status().as_u16(),
+                    response.body()
+                );
+                Err(LogError::Other(error.into()))
+            }
         }
-
-        let request_uri = request.uri().to_string();
-        let response = client.send(request).await?;
-
-        if !response.status().is_success() {
-            let error = format!(
-                "OpenTelemetry logs export failed. Url: {}, Status Code: {}, Response: {:?}",
-                response.status().as_u16(),
-                request_uri,
-                response.body()
-            );
-            return Err(LogError::Other(error.into()));
-        }
-
-        Ok(())
     }
 
     fn shutdown(&mut self) {
@@ -53,21 +60,18 @@ impl LogExporter for OtlpHttpClient {
 }
 
 #[cfg(feature = "http-proto")]
-fn build_body(logs: Vec<LogData>) -> LogResult<(Vec<u8>, &'static str)> {
+fn build_body(logs: Vec<LogData>, protocol: Protocol) -> LogResult<(Vec<u8>, &'static str)> {
     use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
-    use prost::Message;
 
     let req = ExportLogsServiceRequest {
         resource_logs: logs.into_iter().map(Into::into).collect(),
     };
-    let mut buf = vec![];
-    req.encode(&mut buf).map_err(crate::Error::from)?;
 
-    Ok((buf, "application/x-protobuf"))
+    Ok(super::encode_body(&req, protocol)?)
 }
 
 #[cfg(not(feature = "http-proto"))]
-fn build_body(logs: Vec<LogData>) -> LogResult<(Vec<u8>, &'static str)> {
+fn build_body(logs: Vec<LogData>, protocol: Protocol) -> LogResult<(Vec<u8>, &'static str)> {
     Err(LogsError::Other(
         "No http protocol configured. Enable one via `http-proto`".into(),
     ))
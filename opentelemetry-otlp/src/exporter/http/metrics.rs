@@ -1,17 +1,19 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use http::{header::CONTENT_TYPE, Method};
 use opentelemetry::metrics::{MetricsError, Result};
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceResponse;
 use opentelemetry_sdk::metrics::data::ResourceMetrics;
 
-use crate::{metric::MetricsClient, Error};
+use crate::{metric::MetricsClient, Error, Protocol};
 
-use super::OtlpHttpClient;
+use super::{decode_response, OtlpHttpClient, SendError};
 
 #[async_trait]
 impl MetricsClient for OtlpHttpClient {
     async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()> {
+        self.reload_certificates::<MetricsError>();
+
         let client = self
             .client
             .lock()
@@ -21,24 +23,37 @@ impl MetricsClient for OtlpHttpClient {
                 _ => Err(MetricsError::Other("exporter is already shut down".into())),
             })?;
 
-        let (body, content_type) = build_body(metrics)?;
-        let mut request = http::Request::builder()
-            .method(Method::POST)
-            .uri(&self.collector_endpoint)
-            .header(CONTENT_TYPE, content_type)
-            .body(body)
-            .map_err(|e| crate::Error::RequestFailed(Box::new(e)))?;
+        let (body, content_type) = build_body(metrics, self.protocol)?;
+        let request = self.export_request(body, content_type)?;
+        let request_uri = request.uri.to_string();
 
-        for (k, v) in &self.headers {
-            request.headers_mut().insert(k.clone(), v.clone());
+        match request.send(client).await {
+            Ok(body) => {
+                if let Some(partial_success) =
+                    decode_response::<ExportMetricsServiceResponse>(&body, self.protocol)
+                        .and_then(|response| response.partial_success)
+                {
+                    self.exporter_metrics.partial_success::<MetricsError>(
+                        "data points",
+                        partial_success.rejected_data_points,
+                        partial_success.error_message,
+                    );
+                }
+                Ok(())
+            }
+            Err(SendError::Build(err)) => Err(MetricsError::ExportErr(Box::new(
+                Error::RequestFailed(Box::new(err)),
+            ))),
+            Err(SendError::Transport(err)) => {
+                Err(MetricsError::ExportErr(Box::new(Error::RequestFailed(err))))
+            }
+            Err(SendError::Response(response)) => Err(MetricsError::Other(format!(
+                "OpenTelemetry metrics export failed. Url: {}, Status Code: {}, Response: {:?}",
+                request_uri,
+                response.status().as_u16(),
+                response.body()
+            ))),
         }
-
-        client
-            .send(request)
-            .await
-            .map_err(|e| MetricsError::ExportErr(Box::new(Error::RequestFailed(e))))?;
-
-        Ok(())
     }
 
     fn shutdown(&self) -> Result<()> {
@@ -49,19 +64,21 @@ impl MetricsClient for OtlpHttpClient {
 }
 
 #[cfg(feature = "http-proto")]
-fn build_body(metrics: &mut ResourceMetrics) -> Result<(Vec<u8>, &'static str)> {
-    use prost::Message;
-
+fn build_body(
+    metrics: &mut ResourceMetrics,
+    protocol: Protocol,
+) -> Result<(Vec<u8>, &'static str)> {
     let req: opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest =
         (&*metrics).into();
-    let mut buf = vec![];
-    req.encode(&mut buf).map_err(crate::Error::from)?;
 
-    Ok((buf, "application/x-protobuf"))
+    Ok(super::encode_body(&req, protocol)?)
 }
 
 #[cfg(not(feature = "http-proto"))]
-fn build_body(metrics: &mut ResourceMetrics) -> Result<(Vec<u8>, &'static str)> {
+fn build_body(
+    metrics: &mut ResourceMetrics,
+    protocol: Protocol,
+) -> Result<(Vec<u8>, &'static str)> {
     Err(MetricsError::Other(
         "No http protocol configured. Enable one via `http-proto`".into(),
     ))
@@ -1,16 +1,30 @@
 use crate::{
-    ExportConfig, Protocol, OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_HEADERS,
-    OTEL_EXPORTER_OTLP_TIMEOUT,
+    Compression, ExportConfig, Protocol, OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_HEADERS,
+    OTEL_EXPORTER_OTLP_PROTOCOL,
 };
-use http::{HeaderName, HeaderValue, Uri};
-use opentelemetry_http::HttpClient;
+use http::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri,
+};
+use opentelemetry_http::{Bytes, HttpClient, HttpError, HttpStatusError};
 use std::collections::HashMap;
 use std::env;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
-use super::{default_headers, parse_header_string};
+use super::retry::{retry_with_backoff, RetryConfig, RetryErrorType};
+use super::self_observability::ExporterMetrics;
+#[cfg(all(
+    feature = "reqwest-rustls",
+    any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+))]
+use super::tls::watcher::{CertificateWatcher, Certificates};
+use super::tls::TlsSettings;
+use super::{
+    default_headers, parse_header_string, resolve_timeout, OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_JSON,
+    OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_PROTOBUF,
+};
 
 #[cfg(feature = "metrics")]
 mod metrics;
@@ -37,6 +51,12 @@ pub(crate) struct HttpConfig {
 
     /// Additional headers to send to the collector.
     headers: Option<HashMap<String, String>>,
+
+    /// Whether the client has been set with `with_http_client`.
+    custom_client: bool,
+
+    /// The compression algorithm to use when communicating with the collector.
+    compression: Option<Compression>,
 }
 
 #[cfg(any(feature = "reqwest-blocking-client", feature = "reqwest-client",))]
@@ -53,12 +73,20 @@ impl Default for HttpConfig {
             ))]
             client: None,
             headers: None,
+            custom_client: false,
+            compression: None,
         }
     }
 }
 
 /// Configuration for the OTLP HTTP exporter.
 ///
+/// With the `reqwest-rustls` feature, the default client loads the certificates set in
+/// [`OTEL_EXPORTER_OTLP_CERTIFICATE`] and the related variables, and is rebuilt when the
+/// certificate files change.
+///
+/// [`OTEL_EXPORTER_OTLP_CERTIFICATE`]: crate::OTEL_EXPORTER_OTLP_CERTIFICATE
+///
 /// ## Examples
 ///
 /// ```
@@ -92,6 +120,8 @@ impl Default for HttpConfig {
 pub struct HttpExporterBuilder {
     pub(crate) exporter_config: ExportConfig,
     pub(crate) http_config: HttpConfig,
+    #[cfg(feature = "metrics")]
+    pub(crate) meter: Option<opentelemetry::metrics::Meter>,
 }
 
 impl Default for HttpExporterBuilder {
@@ -105,14 +135,20 @@ impl Default for HttpExporterBuilder {
                 headers: Some(default_headers()),
                 ..HttpConfig::default()
             },
+            #[cfg(feature = "metrics")]
+            meter: None,
         }
     }
 }
 
 impl HttpExporterBuilder {
     /// Assign client implementation
+    ///
+    /// The certificates set in the environment are only loaded by the default clients,
+    /// `client` must be configured with them instead.
     pub fn with_http_client<T: HttpClient + 'static>(mut self, client: T) -> Self {
         self.http_config.client = Some(Arc::new(client));
+        self.http_config.custom_client = true;
         self
     }
 
@@ -129,12 +165,40 @@ impl HttpExporterBuilder {
         self
     }
 
+    /// Set the compression algorithm to use when communicating with the collector.
+    ///
+    /// `gzip` requires the `gzip-http` feature, and `zstd` the `zstd-http` feature.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.http_config.compression = Some(compression);
+        self
+    }
+
+    /// Record the number of retries of failed exports (`otel.sdk.exporter.retries`)
+    /// and the number of items rejected by the collector (`otel.sdk.exporter.rejected`)
+    /// with `meter_provider`.
+    ///
+    /// The number of exported items and the duration of the exports are recorded
+    /// by the processors and readers of the SDK, see their `with_self_observability`
+    /// methods.
+    #[cfg(feature = "metrics")]
+    pub fn with_self_observability<P>(mut self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        self.meter = Some(super::self_observability::meter(meter_provider));
+        self
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn build_client(
         &mut self,
+        signal: &'static str,
         signal_endpoint_var: &str,
         signal_endpoint_path: &str,
         signal_timeout_var: &str,
+        signal_compression_var: &str,
         signal_http_headers_var: &str,
+        tls_settings: TlsSettings,
     ) -> Result<OtlpHttpClient, crate::Error> {
         let endpoint = resolve_endpoint(
             signal_endpoint_var,
@@ -142,16 +206,11 @@ impl HttpExporterBuilder {
             self.exporter_config.endpoint.as_str(),
         )?;
 
-        let timeout = match env::var(signal_timeout_var)
-            .ok()
-            .or(env::var(OTEL_EXPORTER_OTLP_TIMEOUT).ok())
-        {
-            Some(val) => match val.parse() {
-                Ok(seconds) => Duration::from_secs(seconds),
-                Err(_) => self.exporter_config.timeout,
-            },
-            None => self.exporter_config.timeout,
-        };
+        let timeout = resolve_timeout(signal_timeout_var, self.exporter_config.timeout);
+
+        let protocol = resolve_protocol(self.exporter_config.protocol)?;
+        let compression =
+            resolve_compression(self.http_config.compression, signal_compression_var)?;
 
         let http_client = self
             .http_config
@@ -181,7 +240,53 @@ impl HttpExporterBuilder {
             add_header_from_string(&input, &mut headers);
         }
 
-        Ok(OtlpHttpClient::new(http_client, endpoint, headers, timeout))
+        #[cfg_attr(
+            not(all(
+                feature = "reqwest-rustls",
+                any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+            )),
+            allow(unused_mut)
+        )]
+        let mut client = OtlpHttpClient::new(
+            http_client,
+            endpoint,
+            headers,
+            protocol,
+            compression,
+            timeout,
+            self.exporter_config.retry_config,
+            self.exporter_metrics(signal),
+        );
+
+        // the default client is replaced by one loading the certificates
+        #[cfg(all(
+            feature = "reqwest-rustls",
+            any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+        ))]
+        if !self.http_config.custom_client && !tls_settings.certificates.is_empty() {
+            let mut watcher = CertificateWatcher::new(tls_settings.certificates);
+            client.client = Mutex::new(Some(watcher.load(tls_client)?));
+            client.certificates = Some(Mutex::new(watcher));
+        }
+        #[cfg(not(all(
+            feature = "reqwest-rustls",
+            any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+        )))]
+        if !self.http_config.custom_client && !tls_settings.certificates.is_empty() {
+            return Err(crate::Error::InvalidCertificates(
+                "the `reqwest-rustls` feature is required to load them".into(),
+            ));
+        }
+
+        Ok(client)
+    }
+
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn exporter_metrics(&self, signal: &'static str) -> ExporterMetrics {
+        #[cfg(feature = "metrics")]
+        return ExporterMetrics::new(self.meter.as_ref(), signal);
+        #[cfg(not(feature = "metrics"))]
+        ExporterMetrics::default()
     }
 
     /// Create a log exporter with the current configuration
@@ -190,15 +295,25 @@ impl HttpExporterBuilder {
         mut self,
     ) -> Result<crate::SpanExporter, opentelemetry::trace::TraceError> {
         use crate::{
+            OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE, OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE,
+            OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
             OTEL_EXPORTER_OTLP_TRACES_ENDPOINT, OTEL_EXPORTER_OTLP_TRACES_HEADERS,
-            OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+            OTEL_EXPORTER_OTLP_TRACES_INSECURE, OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
         };
 
         let client = self.build_client(
+            "traces",
             OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
             "/v1/traces",
             OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+            OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
             OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+            TlsSettings::from_env(
+                OTEL_EXPORTER_OTLP_TRACES_INSECURE,
+                OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE,
+                OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE,
+                OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY,
+            ),
         )?;
 
         Ok(crate::SpanExporter::new(client))
@@ -208,15 +323,25 @@ impl HttpExporterBuilder {
     #[cfg(feature = "logs")]
     pub fn build_log_exporter(mut self) -> opentelemetry::logs::LogResult<crate::LogExporter> {
         use crate::{
+            OTEL_EXPORTER_OTLP_LOGS_CERTIFICATE, OTEL_EXPORTER_OTLP_LOGS_CLIENT_CERTIFICATE,
+            OTEL_EXPORTER_OTLP_LOGS_CLIENT_KEY, OTEL_EXPORTER_OTLP_LOGS_COMPRESSION,
             OTEL_EXPORTER_OTLP_LOGS_ENDPOINT, OTEL_EXPORTER_OTLP_LOGS_HEADERS,
-            OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
+            OTEL_EXPORTER_OTLP_LOGS_INSECURE, OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
         };
 
         let client = self.build_client(
+            "logs",
             OTEL_EXPORTER_OTLP_LOGS_ENDPOINT,
             "/v1/logs",
             OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
+            OTEL_EXPORTER_OTLP_LOGS_COMPRESSION,
             OTEL_EXPORTER_OTLP_LOGS_HEADERS,
+            TlsSettings::from_env(
+                OTEL_EXPORTER_OTLP_LOGS_INSECURE,
+                OTEL_EXPORTER_OTLP_LOGS_CERTIFICATE,
+                OTEL_EXPORTER_OTLP_LOGS_CLIENT_CERTIFICATE,
+                OTEL_EXPORTER_OTLP_LOGS_CLIENT_KEY,
+            ),
         )?;
 
         Ok(crate::LogExporter::new(client))
@@ -230,15 +355,25 @@ impl HttpExporterBuilder {
         temporality_selector: Box<dyn opentelemetry_sdk::metrics::reader::TemporalitySelector>,
     ) -> opentelemetry::metrics::Result<crate::MetricsExporter> {
         use crate::{
+            OTEL_EXPORTER_OTLP_METRICS_CERTIFICATE, OTEL_EXPORTER_OTLP_METRICS_CLIENT_CERTIFICATE,
+            OTEL_EXPORTER_OTLP_METRICS_CLIENT_KEY, OTEL_EXPORTER_OTLP_METRICS_COMPRESSION,
             OTEL_EXPORTER_OTLP_METRICS_ENDPOINT, OTEL_EXPORTER_OTLP_METRICS_HEADERS,
-            OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
+            OTEL_EXPORTER_OTLP_METRICS_INSECURE, OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
         };
 
         let client = self.build_client(
+            "metrics",
             OTEL_EXPORTER_OTLP_METRICS_ENDPOINT,
             "/v1/metrics",
             OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
+            OTEL_EXPORTER_OTLP_METRICS_COMPRESSION,
             OTEL_EXPORTER_OTLP_METRICS_HEADERS,
+            TlsSettings::from_env(
+                OTEL_EXPORTER_OTLP_METRICS_INSECURE,
+                OTEL_EXPORTER_OTLP_METRICS_CERTIFICATE,
+                OTEL_EXPORTER_OTLP_METRICS_CLIENT_CERTIFICATE,
+                OTEL_EXPORTER_OTLP_METRICS_CLIENT_KEY,
+            ),
         )?;
 
         Ok(crate::MetricsExporter::new(
@@ -252,26 +387,342 @@ impl HttpExporterBuilder {
 #[derive(Debug)]
 struct OtlpHttpClient {
     client: Mutex<Option<Arc<dyn HttpClient>>>,
+    /// The certificates of the default client, rebuilt when they are rotated.
+    #[cfg(all(
+        feature = "reqwest-rustls",
+        any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+    ))]
+    certificates: Option<Mutex<CertificateWatcher>>,
     collector_endpoint: Uri,
     headers: HashMap<HeaderName, HeaderValue>,
-    _timeout: Duration,
+    protocol: Protocol,
+    compression: Option<Compression>,
+    timeout: Duration,
+    retry_config: RetryConfig,
+    exporter_metrics: ExporterMetrics,
 }
 
 impl OtlpHttpClient {
     #[allow(clippy::mutable_key_type)] // http headers are not mutated
+    #[allow(clippy::too_many_arguments)]
     fn new(
         client: Arc<dyn HttpClient>,
         collector_endpoint: Uri,
         headers: HashMap<HeaderName, HeaderValue>,
+        protocol: Protocol,
+        compression: Option<Compression>,
         timeout: Duration,
+        retry_config: RetryConfig,
+        exporter_metrics: ExporterMetrics,
     ) -> Self {
         OtlpHttpClient {
             client: Mutex::new(Some(client)),
+            #[cfg(all(
+                feature = "reqwest-rustls",
+                any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+            ))]
+            certificates: None,
             collector_endpoint,
             headers,
-            _timeout: timeout,
+            protocol,
+            compression,
+            timeout,
+            retry_config,
+            exporter_metrics,
+        }
+    }
+
+    /// Rebuild the client if its certificates changed since it was built.
+    ///
+    /// Certificates failing to load are reported to the global error handler, and the
+    /// current client is kept.
+    #[cfg_attr(
+        not(all(
+            feature = "reqwest-rustls",
+            any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+        )),
+        allow(clippy::extra_unused_type_parameters)
+    )]
+    fn reload_certificates<E>(&self)
+    where
+        E: From<crate::Error> + Into<opentelemetry::global::Error>,
+    {
+        #[cfg(all(
+            feature = "reqwest-rustls",
+            any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+        ))]
+        if let Some(Ok(mut watcher)) = self.certificates.as_ref().map(Mutex::lock) {
+            match watcher.reload(tls_client) {
+                Some(Ok(client)) => {
+                    if let Ok(Some(current)) = self.client.lock().as_deref_mut() {
+                        let previous = std::mem::replace(current, client);
+                        // the blocking client can't be dropped in an async context
+                        #[cfg(feature = "reqwest-blocking-client")]
+                        std::thread::spawn(move || drop_once_unused(previous));
+                        #[cfg(not(feature = "reqwest-blocking-client"))]
+                        drop(previous);
+                    }
+                }
+                Some(Err(err)) => opentelemetry::global::handle_error(E::from(err)),
+                None => {}
+            }
         }
     }
+
+    /// Prepare the export of an encoded batch, compressing it if configured.
+    fn export_request(
+        &self,
+        body: Vec<u8>,
+        content_type: &'static str,
+    ) -> Result<ExportRequest, crate::Error> {
+        let (body, content_encoding) = match self.compression {
+            Some(compression) => (compress(&body, compression)?, Some(compression)),
+            None => (body, None),
+        };
+
+        Ok(ExportRequest {
+            uri: self.collector_endpoint.clone(),
+            headers: self.headers.clone(),
+            content_type,
+            content_encoding,
+            body,
+            retry_config: self.retry_config,
+            timeout: self.timeout,
+            exporter_metrics: self.exporter_metrics.clone(),
+        })
+    }
+}
+
+/// An encoded batch, ready to be sent to the collector.
+#[derive(Debug)]
+struct ExportRequest {
+    uri: Uri,
+    headers: HashMap<HeaderName, HeaderValue>,
+    content_type: &'static str,
+    content_encoding: Option<Compression>,
+    body: Vec<u8>,
+    retry_config: RetryConfig,
+    timeout: Duration,
+    exporter_metrics: ExporterMetrics,
+}
+
+/// Failure of an export, after all retries.
+#[derive(Debug)]
+enum SendError {
+    /// The request could not be built.
+    Build(http::Error),
+    /// The request could not be sent, or the client reported a failure.
+    Transport(HttpError),
+    /// The collector responded with an unsuccessful status.
+    Response(http::Response<Bytes>),
+}
+
+impl ExportRequest {
+    fn build(&self) -> Result<http::Request<Vec<u8>>, http::Error> {
+        let mut request = http::Request::builder()
+            .method(Method::POST)
+            .uri(&self.uri)
+            .header(CONTENT_TYPE, self.content_type)
+            .body(self.body.clone())?;
+        if let Some(compression) = self.content_encoding {
+            request.headers_mut().insert(
+                CONTENT_ENCODING,
+                HeaderValue::from_static(content_encoding(compression)),
+            );
+        }
+
+        for (k, v) in &self.headers {
+            request.headers_mut().insert(k.clone(), v.clone());
+        }
+
+        Ok(request)
+    }
+
+    /// Send the batch with `client`, retrying transient failures, and return the body of
+    /// the successful response.
+    async fn send(self, client: Arc<dyn HttpClient>) -> Result<Bytes, SendError> {
+        retry_with_backoff(
+            self.retry_config,
+            self.timeout,
+            &self.exporter_metrics,
+            classify_send_error,
+            || {
+                let client = Arc::clone(&client);
+                let request = self.build();
+                async move {
+                    let request = request.map_err(SendError::Build)?;
+                    let response = client.send(request).await.map_err(SendError::Transport)?;
+                    if response.status().is_success() {
+                        Ok(response.into_body())
+                    } else {
+                        Err(SendError::Response(response))
+                    }
+                }
+            },
+        )
+        .await
+    }
+}
+
+/// Decide whether a failed export can be retried, following
+/// <https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/protocol/otlp.md#failures-1>.
+fn classify_send_error(err: &SendError) -> RetryErrorType {
+    match err {
+        SendError::Build(_) => RetryErrorType::NonRetryable,
+        SendError::Response(response) => classify_response(response.status(), response.headers()),
+        // clients may report unsuccessful statuses as errors
+        SendError::Transport(err) => match err.downcast_ref::<HttpStatusError>() {
+            Some(err) => classify_response(err.status(), err.headers()),
+            None if is_connect_or_timeout(err.as_ref()) => RetryErrorType::Retryable,
+            None => RetryErrorType::NonRetryable,
+        },
+    }
+}
+
+/// Whether the collector could not be reached or did not respond in time, looking for
+/// the errors of the known clients in the chain of sources of `err`.
+fn is_connect_or_timeout(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        #[cfg(feature = "reqwest")]
+        if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+            if err.is_connect() || err.is_timeout() {
+                return true;
+            }
+        }
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            if matches!(
+                err.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::NotConnected
+                    | std::io::ErrorKind::TimedOut
+            ) {
+                return true;
+            }
+        }
+        source = err.source();
+    }
+    false
+}
+
+fn classify_response(status: StatusCode, headers: &HeaderMap) -> RetryErrorType {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::GATEWAY_TIMEOUT => match retry_after(headers) {
+            Some(delay) => RetryErrorType::Throttled(delay),
+            None => RetryErrorType::Retryable,
+        },
+        _ => RetryErrorType::NonRetryable,
+    }
+}
+
+/// Parse the `Retry-After` header, given either in seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(http::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(feature = "http-json")]
+fn encode_body<T>(req: &T, protocol: Protocol) -> Result<(Vec<u8>, &'static str), crate::Error>
+where
+    T: prost::Message + serde::Serialize,
+{
+    match protocol {
+        Protocol::HttpJson => Ok((serde_json::to_vec(req)?, "application/json")),
+        _ => encode_protobuf_body(req),
+    }
+}
+
+#[cfg(not(feature = "http-json"))]
+fn encode_body<T>(req: &T, _protocol: Protocol) -> Result<(Vec<u8>, &'static str), crate::Error>
+where
+    T: prost::Message,
+{
+    encode_protobuf_body(req)
+}
+
+/// Decode the body of a successful response, encoded like the request. The body may be
+/// empty or not be a valid response, in which case the export is still successful.
+#[cfg(feature = "http-json")]
+fn decode_response<T>(body: &[u8], protocol: Protocol) -> Option<T>
+where
+    T: prost::Message + Default + serde::de::DeserializeOwned,
+{
+    match protocol {
+        Protocol::HttpJson => serde_json::from_slice(body).ok(),
+        _ => T::decode(body).ok(),
+    }
+}
+
+/// Decode the body of a successful response. The body may be empty or not be a valid
+/// response, in which case the export is still successful.
+#[cfg(not(feature = "http-json"))]
+fn decode_response<T>(body: &[u8], _protocol: Protocol) -> Option<T>
+where
+    T: prost::Message + Default,
+{
+    T::decode(body).ok()
+}
+
+/// Compress the body of a request with `compression`, failing if its feature is not enabled.
+#[cfg_attr(
+    not(any(feature = "gzip-http", feature = "zstd-http")),
+    allow(unused_variables)
+)]
+fn compress(body: &[u8], compression: Compression) -> Result<Vec<u8>, crate::Error> {
+    match compression {
+        #[cfg(feature = "gzip-http")]
+        Compression::Gzip => {
+            use std::io::Write;
+
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(body)?;
+            Ok(encoder.finish()?)
+        }
+        #[cfg(not(feature = "gzip-http"))]
+        Compression::Gzip => Err(crate::Error::UnsupportedCompressionAlgorithm(
+            compression.to_string(),
+        )),
+        #[cfg(feature = "zstd-http")]
+        Compression::Zstd => Ok(zstd::bulk::compress(body, zstd::DEFAULT_COMPRESSION_LEVEL)?),
+        #[cfg(not(feature = "zstd-http"))]
+        Compression::Zstd => Err(crate::Error::UnsupportedCompressionAlgorithm(
+            compression.to_string(),
+        )),
+    }
+}
+
+fn content_encoding(compression: Compression) -> &'static str {
+    match compression {
+        Compression::Gzip => "gzip",
+        Compression::Zstd => "zstd",
+    }
+}
+
+fn encode_protobuf_body<T>(req: &T) -> Result<(Vec<u8>, &'static str), crate::Error>
+where
+    T: prost::Message,
+{
+    let mut buf = vec![];
+    req.encode(&mut buf)?;
+    Ok((buf, "application/x-protobuf"))
 }
 
 fn build_endpoint_uri(endpoint: &str, path: &str) -> Result<Uri, crate::Error> {
@@ -283,6 +734,61 @@ fn build_endpoint_uri(endpoint: &str, path: &str) -> Result<Uri, crate::Error> {
     format!("{endpoint}{path}").parse().map_err(From::from)
 }
 
+/// Build the default client with the certificates loaded from the environment.
+///
+/// The blocking client is built on its own thread, as it can't be built in an async context.
+#[cfg(all(
+    feature = "reqwest-rustls",
+    any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+))]
+fn tls_client(certificates: Certificates) -> Result<Arc<dyn HttpClient>, crate::Error> {
+    #[cfg(feature = "reqwest-blocking-client")]
+    return std::thread::spawn(move || build_tls_client(certificates))
+        .join()
+        .unwrap_or_else(|_| {
+            Err(crate::Error::InvalidCertificates(
+                "the client failed to build".into(),
+            ))
+        });
+    #[cfg(not(feature = "reqwest-blocking-client"))]
+    build_tls_client(certificates)
+}
+
+#[cfg(all(
+    feature = "reqwest-rustls",
+    any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+))]
+fn build_tls_client(certificates: Certificates) -> Result<Arc<dyn HttpClient>, crate::Error> {
+    let invalid = |err: reqwest::Error| crate::Error::InvalidCertificates(err.to_string());
+
+    #[cfg(feature = "reqwest-blocking-client")]
+    let mut builder = reqwest::blocking::Client::builder();
+    #[cfg(not(feature = "reqwest-blocking-client"))]
+    let mut builder = reqwest::Client::builder();
+
+    if let Some(certificate) = certificates.certificate {
+        for certificate in reqwest::Certificate::from_pem_bundle(&certificate).map_err(invalid)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if let Some((certificate, key)) = certificates.identity {
+        let identity =
+            reqwest::Identity::from_pem(&[certificate, key].concat()).map_err(invalid)?;
+        builder = builder.identity(identity);
+    }
+
+    Ok(Arc::new(builder.build().map_err(invalid)?))
+}
+
+/// Drop a replaced blocking client once the exports still using it are done, so that it
+/// isn't dropped by one of them in an async context.
+#[cfg(all(feature = "reqwest-rustls", feature = "reqwest-blocking-client"))]
+fn drop_once_unused(client: Arc<dyn HttpClient>) {
+    while Arc::strong_count(&client) > 1 {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
 // see https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/protocol/exporter.md#endpoint-urls-for-otlphttp
 fn resolve_endpoint(
     signal_endpoint_var: &str,
@@ -309,6 +815,42 @@ fn resolve_endpoint(
     build_endpoint_uri(provided_or_default_endpoint, signal_endpoint_path)
 }
 
+// compression set in code is preferred over env vars, as with tonic
+fn resolve_compression(
+    provided_compression: Option<Compression>,
+    signal_compression_var: &str,
+) -> Result<Option<Compression>, crate::Error> {
+    let compression = match provided_compression {
+        Some(compression) => compression,
+        None => match super::compression_from_env(signal_compression_var)? {
+            Some(compression) => compression,
+            None => return Ok(None),
+        },
+    };
+
+    // fail early if the compression is not supported
+    compress(&[], compression)?;
+    Ok(Some(compression))
+}
+
+// protocol from env var is preferred, as long as the http exporter supports it
+fn resolve_protocol(provided_or_default_protocol: Protocol) -> Result<Protocol, crate::Error> {
+    let protocol = match env::var(OTEL_EXPORTER_OTLP_PROTOCOL).as_deref() {
+        Ok(OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_PROTOBUF) => Protocol::HttpBinary,
+        Ok(OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_JSON) => Protocol::HttpJson,
+        _ => provided_or_default_protocol,
+    };
+
+    // fail early rather than sending protobuf to a collector expecting JSON
+    #[cfg(not(feature = "http-json"))]
+    if protocol == Protocol::HttpJson {
+        return Err(crate::Error::UnsupportedProtocol(
+            OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_JSON.into(),
+        ));
+    }
+    Ok(protocol)
+}
+
 #[allow(clippy::mutable_key_type)] // http headers are not mutated
 fn add_header_from_string(input: &str, headers: &mut HashMap<HeaderName, HeaderValue>) {
     headers.extend(parse_header_string(input).filter_map(|(key, value)| {
@@ -322,9 +864,458 @@ fn add_header_from_string(input: &str, headers: &mut HashMap<HeaderName, HeaderV
 #[cfg(test)]
 mod tests {
     use crate::exporter::tests::run_env_test;
-    use crate::{OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_TRACES_ENDPOINT};
+    use crate::{
+        Protocol, WithExportConfig, OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_PROTOCOL,
+        OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+    };
+
+    use super::{
+        build_endpoint_uri, classify_send_error, retry_after, Bytes, Duration, HeaderMap,
+        HeaderValue, RetryConfig, RetryErrorType, SendError, SystemTime,
+    };
+    use opentelemetry_http::ResponseExt;
+
+    /// Records the requests it receives and answers them with the queued statuses, or `200`
+    /// once the queue is empty.
+    #[derive(Debug, Default, Clone)]
+    pub(crate) struct RecordingHttpClient {
+        pub(crate) requests: std::sync::Arc<std::sync::Mutex<Vec<http::Request<Vec<u8>>>>>,
+        pub(crate) responses: std::sync::Arc<std::sync::Mutex<Vec<http::Response<Bytes>>>>,
+    }
+
+    impl RecordingHttpClient {
+        fn with_responses(responses: Vec<http::Response<Bytes>>) -> Self {
+            RecordingHttpClient {
+                requests: Default::default(),
+                responses: std::sync::Arc::new(std::sync::Mutex::new(responses)),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl opentelemetry_http::HttpClient for RecordingHttpClient {
+        async fn send(
+            &self,
+            request: http::Request<Vec<u8>>,
+        ) -> Result<http::Response<Bytes>, opentelemetry_http::HttpError> {
+            self.requests.lock().unwrap().push(request);
+            let mut responses = self.responses.lock().unwrap();
+            if responses.is_empty() {
+                Ok(http::Response::builder().status(200).body(Bytes::new())?)
+            } else {
+                Ok(responses.remove(0))
+            }
+        }
+    }
+
+    fn response(status: u16, retry_after: Option<&str>) -> http::Response<Bytes> {
+        let mut builder = http::Response::builder().status(status);
+        if let Some(retry_after) = retry_after {
+            builder = builder.header(http::header::RETRY_AFTER, retry_after);
+        }
+        builder.body(Bytes::new()).unwrap()
+    }
+
+    fn no_delay_retries(max_attempts: usize) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            jitter: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_export_retries_transient_failures() {
+        use opentelemetry_sdk::export::trace::SpanExporter;
+        use opentelemetry_sdk::testing::trace::new_test_export_span_data;
+
+        run_env_test(vec![], || {
+            let client = RecordingHttpClient::with_responses(vec![
+                response(503, None),
+                response(429, Some("0")),
+            ]);
+            let mut exporter = crate::new_exporter()
+                .http()
+                .with_http_client(client.clone())
+                .with_retry_config(no_delay_retries(5))
+                .build_span_exporter()
+                .unwrap();
+
+            futures_executor::block_on(exporter.export(vec![new_test_export_span_data()])).unwrap();
+
+            let requests = client.requests.lock().unwrap();
+            assert_eq!(requests.len(), 3);
+            assert_eq!(requests[0].body(), requests[2].body());
+        });
+    }
+
+    #[test]
+    fn test_export_retries_are_recorded() {
+        use opentelemetry_sdk::export::trace::SpanExporter;
+        use opentelemetry_sdk::metrics::{data, PeriodicReader, SdkMeterProvider};
+        use opentelemetry_sdk::testing::{
+            metrics::InMemoryMetricsExporter, trace::new_test_export_span_data,
+        };
+
+        run_env_test(vec![], || {
+            // the default blocking client can't be dropped in an async context
+            let builder =
+                crate::new_exporter()
+                    .http()
+                    .with_http_client(RecordingHttpClient::with_responses(vec![
+                        response(503, None),
+                        response(503, None),
+                    ]));
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                let metrics_exporter = InMemoryMetricsExporter::default();
+                let meter_provider = SdkMeterProvider::builder()
+                    .with_reader(
+                        PeriodicReader::builder(
+                            metrics_exporter.clone(),
+                            opentelemetry_sdk::runtime::Tokio,
+                        )
+                        .build(),
+                    )
+                    .build();
+                let mut exporter = builder
+                    .with_retry_config(no_delay_retries(5))
+                    .with_self_observability(&meter_provider)
+                    .build_span_exporter()
+                    .unwrap();
+
+                exporter
+                    .export(vec![new_test_export_span_data()])
+                    .await
+                    .unwrap();
+                meter_provider.force_flush().unwrap();
+
+                let resource_metrics = metrics_exporter.get_finished_metrics().unwrap();
+                let scope_metrics = &resource_metrics.last().unwrap().scope_metrics[0];
+                assert_eq!(scope_metrics.scope.name, "opentelemetry-otlp");
+                let metric = &scope_metrics.metrics[0];
+                assert_eq!(metric.name, "otel.sdk.exporter.retries");
+                let sum = metric
+                    .data
+                    .as_any()
+                    .downcast_ref::<data::Sum<u64>>()
+                    .unwrap();
+                assert_eq!(sum.data_points[0].value, 2);
+            });
+        });
+    }
+
+    #[test]
+    fn test_export_fails_after_max_attempts() {
+        use opentelemetry_sdk::export::trace::SpanExporter;
+        use opentelemetry_sdk::testing::trace::new_test_export_span_data;
+
+        run_env_test(vec![], || {
+            let client = RecordingHttpClient::with_responses(vec![
+                response(503, None),
+                response(503, None),
+                response(503, None),
+            ]);
+            let mut exporter = crate::new_exporter()
+                .http()
+                .with_http_client(client.clone())
+                .with_retry_config(no_delay_retries(2))
+                .build_span_exporter()
+                .unwrap();
+
+            let result =
+                futures_executor::block_on(exporter.export(vec![new_test_export_span_data()]));
+            assert!(result.unwrap_err().to_string().contains("Status Code: 503"));
+            assert_eq!(client.requests.lock().unwrap().len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_export_does_not_retry_permanent_failures() {
+        use opentelemetry_sdk::export::trace::SpanExporter;
+        use opentelemetry_sdk::testing::trace::new_test_export_span_data;
+
+        run_env_test(vec![], || {
+            let client = RecordingHttpClient::with_responses(vec![response(400, None)]);
+            let mut exporter = crate::new_exporter()
+                .http()
+                .with_http_client(client.clone())
+                .with_retry_config(no_delay_retries(5))
+                .build_span_exporter()
+                .unwrap();
+
+            let result =
+                futures_executor::block_on(exporter.export(vec![new_test_export_span_data()]));
+            assert!(result.is_err());
+            assert_eq!(client.requests.lock().unwrap().len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_classify_send_error() {
+        assert_eq!(
+            classify_send_error(&SendError::Response(response(502, None))),
+            RetryErrorType::Retryable
+        );
+        assert_eq!(
+            classify_send_error(&SendError::Response(response(429, Some("30")))),
+            RetryErrorType::Throttled(Duration::from_secs(30))
+        );
+        assert_eq!(
+            classify_send_error(&SendError::Response(response(500, None))),
+            RetryErrorType::NonRetryable
+        );
+        let refused = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        assert_eq!(
+            classify_send_error(&SendError::Transport(Box::new(refused))),
+            RetryErrorType::Retryable
+        );
+        let timed_out = std::io::Error::from(std::io::ErrorKind::TimedOut);
+        assert_eq!(
+            classify_send_error(&SendError::Transport(Box::new(timed_out))),
+            RetryErrorType::Retryable
+        );
+        assert_eq!(
+            classify_send_error(&SendError::Transport("invalid request".into())),
+            RetryErrorType::NonRetryable
+        );
+        let build_error = http::Request::builder()
+            .uri("not a uri")
+            .body(Vec::<u8>::new())
+            .unwrap_err();
+        assert_eq!(
+            classify_send_error(&SendError::Build(build_error)),
+            RetryErrorType::NonRetryable
+        );
+
+        let status_error = response(504, Some("2")).error_for_status().unwrap_err();
+        assert_eq!(
+            classify_send_error(&SendError::Transport(status_error)),
+            RetryErrorType::Throttled(Duration::from_secs(2))
+        );
+        let status_error = response(401, None).error_for_status().unwrap_err();
+        assert_eq!(
+            classify_send_error(&SendError::Transport(status_error)),
+            RetryErrorType::NonRetryable
+        );
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
 
-    use super::build_endpoint_uri;
+        headers.insert(http::header::RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        // dates in the past mean retry right away
+        headers.insert(
+            http::header::RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(3600));
+        headers.insert(http::header::RETRY_AFTER, date.parse().unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(3500) && delay <= Duration::from_secs(3600));
+
+        headers.insert(http::header::RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_resolve_protocol() {
+        run_env_test(vec![], || {
+            assert_eq!(
+                super::resolve_protocol(Protocol::HttpBinary).unwrap(),
+                Protocol::HttpBinary
+            );
+        });
+
+        run_env_test(vec![(OTEL_EXPORTER_OTLP_PROTOCOL, "http/protobuf")], || {
+            assert_eq!(
+                super::resolve_protocol(Protocol::HttpJson).unwrap(),
+                Protocol::HttpBinary
+            );
+        });
+
+        // grpc is not supported by the http exporter, keep the configured protocol
+        run_env_test(vec![(OTEL_EXPORTER_OTLP_PROTOCOL, "grpc")], || {
+            assert_eq!(
+                super::resolve_protocol(Protocol::HttpBinary).unwrap(),
+                Protocol::HttpBinary
+            );
+        });
+    }
+
+    #[cfg(feature = "http-json")]
+    #[test]
+    fn test_resolve_json_protocol_from_env() {
+        run_env_test(vec![(OTEL_EXPORTER_OTLP_PROTOCOL, "http/json")], || {
+            assert_eq!(
+                super::resolve_protocol(Protocol::HttpBinary).unwrap(),
+                Protocol::HttpJson
+            );
+        });
+    }
+
+    #[cfg(not(feature = "http-json"))]
+    #[test]
+    fn test_json_protocol_requires_feature() {
+        run_env_test(vec![], || {
+            assert!(matches!(
+                super::resolve_protocol(Protocol::HttpJson),
+                Err(crate::Error::UnsupportedProtocol(_))
+            ));
+        });
+        run_env_test(vec![(OTEL_EXPORTER_OTLP_PROTOCOL, "http/json")], || {
+            assert!(super::resolve_protocol(Protocol::HttpBinary).is_err());
+        });
+    }
+
+    #[test]
+    fn test_resolve_compression() {
+        #[cfg(any(feature = "gzip-http", feature = "zstd-http"))]
+        use crate::Compression;
+        use crate::{OTEL_EXPORTER_OTLP_COMPRESSION, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION};
+
+        run_env_test(
+            vec![
+                (OTEL_EXPORTER_OTLP_TRACES_COMPRESSION, "zstd"),
+                (OTEL_EXPORTER_OTLP_COMPRESSION, "brotli"),
+            ],
+            || {
+                // the provided compression is preferred over the env vars
+                #[cfg(feature = "gzip-http")]
+                assert_eq!(
+                    super::resolve_compression(
+                        Some(Compression::Gzip),
+                        OTEL_EXPORTER_OTLP_TRACES_COMPRESSION
+                    )
+                    .unwrap(),
+                    Some(Compression::Gzip)
+                );
+                #[cfg(feature = "zstd-http")]
+                assert_eq!(
+                    super::resolve_compression(None, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION)
+                        .unwrap(),
+                    Some(Compression::Zstd)
+                );
+                #[cfg(not(feature = "zstd-http"))]
+                assert!(
+                    super::resolve_compression(None, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION)
+                        .is_err()
+                );
+                assert!(super::resolve_compression(None, "NON_EXISTENT_VAR").is_err());
+            },
+        );
+        run_env_test(vec![], || {
+            assert_eq!(
+                super::resolve_compression(None, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION).unwrap(),
+                None
+            );
+        });
+        run_env_test(vec![(OTEL_EXPORTER_OTLP_COMPRESSION, "none")], || {
+            assert_eq!(
+                super::resolve_compression(None, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION).unwrap(),
+                None
+            );
+        });
+    }
+
+    #[cfg(all(feature = "gzip-http", feature = "zstd-http"))]
+    #[test]
+    fn test_export_compressed_spans() {
+        use crate::Compression;
+        use futures_util::FutureExt;
+        use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+        use opentelemetry_sdk::export::trace::SpanExporter;
+        use opentelemetry_sdk::testing::trace::new_test_export_span_data;
+        use prost::Message;
+        use std::io::Read;
+
+        run_env_test(vec![], || {
+            for compression in [Compression::Gzip, Compression::Zstd] {
+                let client = RecordingHttpClient::default();
+                let mut exporter = crate::new_exporter()
+                    .http()
+                    .with_http_client(client.clone())
+                    .with_compression(compression)
+                    .build_span_exporter()
+                    .unwrap();
+
+                exporter
+                    .export(vec![new_test_export_span_data()])
+                    .now_or_never()
+                    .expect("export should complete immediately")
+                    .unwrap();
+
+                let requests = client.requests.lock().unwrap();
+                assert_eq!(
+                    requests[0]
+                        .headers()
+                        .get(http::header::CONTENT_ENCODING)
+                        .unwrap(),
+                    compression.to_string().as_str()
+                );
+                let body = match compression {
+                    Compression::Gzip => {
+                        let mut body = Vec::new();
+                        flate2::read::GzDecoder::new(requests[0].body().as_slice())
+                            .read_to_end(&mut body)
+                            .unwrap();
+                        body
+                    }
+                    Compression::Zstd => zstd::decode_all(requests[0].body().as_slice()).unwrap(),
+                };
+                let request = ExportTraceServiceRequest::decode(body.as_slice()).unwrap();
+                assert_eq!(request.resource_spans.len(), 1);
+            }
+        });
+    }
+
+    #[cfg(all(feature = "http-json", feature = "trace"))]
+    #[test]
+    fn test_export_spans_as_json() {
+        use crate::WithExportConfig;
+        use futures_util::FutureExt;
+        use opentelemetry_sdk::export::trace::SpanExporter;
+        use opentelemetry_sdk::testing::trace::new_test_export_span_data;
+
+        run_env_test(vec![], || {
+            let client = RecordingHttpClient::default();
+            let mut exporter = crate::new_exporter()
+                .http()
+                .with_http_client(client.clone())
+                .with_protocol(Protocol::HttpJson)
+                .build_span_exporter()
+                .unwrap();
+
+            let span = new_test_export_span_data();
+            let trace_id = span.span_context.trace_id().to_string();
+            exporter
+                .export(vec![span])
+                .now_or_never()
+                .expect("export should complete immediately")
+                .unwrap();
+
+            let requests = client.requests.lock().unwrap();
+            assert_eq!(requests.len(), 1);
+            assert_eq!(
+                requests[0]
+                    .headers()
+                    .get(http::header::CONTENT_TYPE)
+                    .unwrap(),
+                "application/json"
+            );
+            let body: serde_json::Value = serde_json::from_slice(requests[0].body()).unwrap();
+            let span = &body["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+            assert_eq!(span["traceId"], trace_id);
+            assert!(span["startTimeUnixNano"].is_string());
+        });
+    }
 
     #[test]
     fn test_append_signal_path_to_generic_env() {
@@ -387,6 +1378,134 @@ mod tests {
         });
     }
 
+    #[cfg(all(
+        feature = "reqwest-rustls",
+        any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+    ))]
+    #[test]
+    fn test_certificates_from_env() {
+        run_env_test(
+            vec![(
+                crate::OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE,
+                "/non/existent/client.pem",
+            )],
+            || {
+                // the client key is missing
+                let result = crate::new_exporter().http().build_span_exporter();
+                assert!(result.is_err());
+            },
+        );
+        run_env_test(
+            vec![(
+                crate::OTEL_EXPORTER_OTLP_CERTIFICATE,
+                "/non/existent/ca.pem",
+            )],
+            || {
+                // custom clients are configured by the user
+                let result = crate::new_exporter()
+                    .http()
+                    .with_http_client(RecordingHttpClient::default())
+                    .build_span_exporter();
+                assert!(result.is_ok());
+
+                let result = crate::new_exporter().http().build_span_exporter();
+                assert!(result
+                    .unwrap_err()
+                    .to_string()
+                    .contains("cannot load the TLS certificates: /non/existent/ca.pem"));
+            },
+        );
+    }
+
+    #[cfg(all(feature = "reqwest-rustls", feature = "reqwest-blocking-client"))]
+    #[test]
+    fn test_blocking_client_is_rebuilt_in_async_context() {
+        use crate::exporter::tls::TlsSettings;
+        use crate::{
+            OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE, OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE,
+            OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+            OTEL_EXPORTER_OTLP_TRACES_ENDPOINT, OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+            OTEL_EXPORTER_OTLP_TRACES_INSECURE, OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+        };
+        use opentelemetry::trace::TraceError;
+        use std::{fs, sync::Arc};
+
+        let dir = std::env::temp_dir().join(format!(
+            "opentelemetry-otlp-blocking-client-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let certificate = dir.join("ca.pem");
+        // an empty bundle adds no root certificate
+        fs::write(&certificate, "").unwrap();
+
+        run_env_test(vec![], || {
+            let tls_settings = temp_env::with_var(
+                OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE,
+                Some(certificate.to_str().unwrap()),
+                || {
+                    TlsSettings::from_env(
+                        OTEL_EXPORTER_OTLP_TRACES_INSECURE,
+                        OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE,
+                        OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE,
+                        OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY,
+                    )
+                },
+            );
+            let client = crate::new_exporter()
+                .http()
+                .build_client(
+                    "traces",
+                    OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+                    "/v1/traces",
+                    OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+                    OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
+                    OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+                    tls_settings,
+                )
+                .unwrap();
+            let initial = client.client.lock().unwrap().clone().unwrap();
+
+            fs::write(&certificate, "\n").unwrap();
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async { client.reload_certificates::<TraceError>() });
+
+            let reloaded = client.client.lock().unwrap().clone().unwrap();
+            assert!(!Arc::ptr_eq(&initial, &reloaded));
+        });
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_decode_response() {
+        use opentelemetry_proto::tonic::collector::trace::v1::{
+            ExportTracePartialSuccess, ExportTraceServiceResponse,
+        };
+        use prost::Message;
+
+        let response = ExportTraceServiceResponse {
+            partial_success: Some(ExportTracePartialSuccess {
+                rejected_spans: 2,
+                error_message: "spans too large".to_string(),
+            }),
+        };
+        assert_eq!(
+            super::decode_response(&response.encode_to_vec(), Protocol::HttpBinary),
+            Some(response.clone())
+        );
+        // an empty body is a full success
+        assert_eq!(
+            super::decode_response(&[], Protocol::HttpBinary),
+            Some(ExportTraceServiceResponse::default())
+        );
+        #[cfg(feature = "http-json")]
+        assert_eq!(
+            super::decode_response(&serde_json::to_vec(&response).unwrap(), Protocol::HttpJson),
+            Some(response)
+        );
+    }
+
     #[test]
     fn test_build_endpoint_uri() {
         let uri = build_endpoint_uri("https://example.com", "/v1/traces").unwrap();
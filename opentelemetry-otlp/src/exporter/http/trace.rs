@@ -1,14 +1,17 @@
 use std::sync::Arc;
 
 use futures_core::future::BoxFuture;
-use http::{header::CONTENT_TYPE, Method};
 use opentelemetry::trace::{TraceError, TraceResult};
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceResponse;
 use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
 
-use super::OtlpHttpClient;
+use super::{decode_response, OtlpHttpClient, SendError};
+use crate::Protocol;
 
 impl SpanExporter for OtlpHttpClient {
     fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        self.reload_certificates::<TraceError>();
+
         let client = match self
             .client
             .lock()
@@ -21,45 +24,48 @@ impl SpanExporter for OtlpHttpClient {
             Err(err) => return Box::pin(std::future::ready(Err(err))),
         };
 
-        let (body, content_type) = match build_body(batch) {
+        let (body, content_type) = match build_body(batch, self.protocol) {
             Ok(body) => body,
             Err(e) => return Box::pin(std::future::ready(Err(e))),
         };
 
-        let mut request = match http::Request::builder()
-            .method(Method::POST)
-            .uri(&self.collector_endpoint)
-            .header(CONTENT_TYPE, content_type)
-            .body(body)
-        {
-            Ok(req) => req,
-            Err(e) => {
-                return Box::pin(std::future::ready(Err(crate::Error::RequestFailed(
-                    Box::new(e),
-                )
-                .into())))
-            }
+        let request = match self.export_request(body, content_type) {
+            Ok(request) => request,
+            Err(e) => return Box::pin(std::future::ready(Err(e.into()))),
         };
-
-        for (k, v) in &self.headers {
-            request.headers_mut().insert(k.clone(), v.clone());
-        }
+        let exporter_metrics = self.exporter_metrics.clone();
+        let protocol = self.protocol;
 
         Box::pin(async move {
-            let request_uri = request.uri().to_string();
-            let response = client.send(request).await?;
-
-            if !response.status().is_success() {
-                let error = format!(
-                    "OpenTelemetry trace export failed. Url: {}, Status Code: {}, Response: {:?}",
-                    response.status().as_u16(),
-                    request_uri,
-                    response.body()
-                );
-                return Err(TraceError::Other(error.into()));
+            let request_uri = request.uri.to_string();
+            match request.send(client).await {
+                Ok(body) => {
+                    if let Some(partial_success) =
+                        decode_response::<ExportTraceServiceResponse>(&body, protocol)
+                            .and_then(|response| response.partial_success)
+                    {
+                        exporter_metrics.partial_success::<TraceError>(
+                            "spans",
+                            partial_success.rejected_spans,
+                            partial_success.error_message,
+                        );
+                    }
+                    Ok(())
+                }
+                Err(SendError::Build(err)) => {
+                    Err(crate::Error::RequestFailed(Box::new(err)).into())
+                }
+                Err(SendError::Transport(err)) => Err(crate::Error::RequestFailed(err).into()),
+                Err(SendError::Response(response)) => {
+                    let error = format!(
+                        "OpenTelemetry trace export failed. Url: {}, Status Code: {}, Response: {:?}",
+                        request_uri,
+                        response.status().as_u16(),
+                        response.body()
+                    );
+                    Err(TraceError::Other(error.into()))
+                }
             }
-
-            Ok(())
         })
     }
 
@@ -69,21 +75,18 @@ impl SpanExporter for OtlpHttpClient {
 }
 
 #[cfg(feature = "http-proto")]
-fn build_body(spans: Vec<SpanData>) -> TraceResult<(Vec<u8>, &'static str)> {
+fn build_body(spans: Vec<SpanData>, protocol: Protocol) -> TraceResult<(Vec<u8>, &'static str)> {
     use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
-    use prost::Message;
 
     let req = ExportTraceServiceRequest {
         resource_spans: spans.into_iter().map(Into::into).collect(),
     };
-    let mut buf = vec![];
-    req.encode(&mut buf).map_err(crate::Error::from)?;
 
-    Ok((buf, "application/x-protobuf"))
+    Ok(super::encode_body(&req, protocol)?)
 }
 
 #[cfg(not(feature = "http-proto"))]
-fn build_body(spans: Vec<SpanData>) -> TraceResult<(Vec<u8>, &'static str)> {
+fn build_body(spans: Vec<SpanData>, protocol: Protocol) -> TraceResult<(Vec<u8>, &'static str)> {
     Err(TraceError::Other(
         "No http protocol configured. Enable one via `http-proto`".into(),
     ))
@@ -4,6 +4,7 @@
 
 #[cfg(feature = "http-proto")]
 use crate::exporter::http::HttpExporterBuilder;
+pub use crate::exporter::retry::RetryConfig;
 #[cfg(feature = "grpc-tonic")]
 use crate::exporter::tonic::TonicExporterBuilder;
 use crate::{Error, Protocol};
@@ -23,10 +24,21 @@ pub const OTEL_EXPORTER_OTLP_ENDPOINT_DEFAULT: &str = OTEL_EXPORTER_OTLP_HTTP_EN
 /// Example: `k1=v1,k2=v2`
 /// Note: as of now, this is only supported for HTTP requests.
 pub const OTEL_EXPORTER_OTLP_HEADERS: &str = "OTEL_EXPORTER_OTLP_HEADERS";
-/// Protocol the exporter will use. Either `http/protobuf` or `grpc`.
+/// Protocol the exporter will use. Either `http/protobuf`, `http/json` or `grpc`.
 pub const OTEL_EXPORTER_OTLP_PROTOCOL: &str = "OTEL_EXPORTER_OTLP_PROTOCOL";
 /// Compression algorithm to use, defaults to none.
 pub const OTEL_EXPORTER_OTLP_COMPRESSION: &str = "OTEL_EXPORTER_OTLP_COMPRESSION";
+/// Whether to connect without TLS to gRPC endpoints given without a scheme, defaults to
+/// `false`. Endpoints without a scheme use `http` if `true`, `https` otherwise.
+pub const OTEL_EXPORTER_OTLP_INSECURE: &str = "OTEL_EXPORTER_OTLP_INSECURE";
+/// Path of the PEM encoded certificates trusted to verify the collector's certificate.
+///
+/// The certificate files are loaded again when they change.
+pub const OTEL_EXPORTER_OTLP_CERTIFICATE: &str = "OTEL_EXPORTER_OTLP_CERTIFICATE";
+/// Path of the PEM encoded client certificate chain, sent to the collector for mTLS.
+pub const OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE: &str = "OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE";
+/// Path of the PEM encoded private key of the client certificate.
+pub const OTEL_EXPORTER_OTLP_CLIENT_KEY: &str = "OTEL_EXPORTER_OTLP_CLIENT_KEY";
 
 #[cfg(feature = "http-proto")]
 /// Default protocol, using http-proto.
@@ -39,6 +51,8 @@ pub const OTEL_EXPORTER_OTLP_PROTOCOL_DEFAULT: &str = OTEL_EXPORTER_OTLP_PROTOCO
 pub const OTEL_EXPORTER_OTLP_PROTOCOL_DEFAULT: &str = "";
 
 const OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_PROTOBUF: &str = "http/protobuf";
+#[cfg(feature = "http-proto")]
+const OTEL_EXPORTER_OTLP_PROTOCOL_HTTP_JSON: &str = "http/json";
 const OTEL_EXPORTER_OTLP_PROTOCOL_GRPC: &str = "grpc";
 
 /// Max waiting time for the backend to process each signal batch, defaults to 10 seconds.
@@ -50,8 +64,15 @@ pub const OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT: u64 = 10;
 const OTEL_EXPORTER_OTLP_GRPC_ENDPOINT_DEFAULT: &str = "http://localhost:4317";
 const OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT: &str = "http://localhost:4318";
 
+#[cfg(feature = "file")]
+pub(crate) mod file;
 #[cfg(feature = "http-proto")]
 pub(crate) mod http;
+pub(crate) mod retry;
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+pub(crate) mod self_observability;
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+pub(crate) mod tls;
 #[cfg(feature = "grpc-tonic")]
 pub(crate) mod tonic;
 
@@ -66,6 +87,9 @@ pub struct ExportConfig {
 
     /// The timeout to the collector.
     pub timeout: Duration,
+
+    /// How failed exports are retried.
+    pub retry_config: RetryConfig,
 }
 
 impl Default for ExportConfig {
@@ -76,6 +100,7 @@ impl Default for ExportConfig {
             endpoint: default_endpoint(protocol),
             protocol,
             timeout: Duration::from_secs(OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT),
+            retry_config: RetryConfig::default(),
         }
     }
 }
@@ -83,15 +108,19 @@ impl Default for ExportConfig {
 /// The compression algorithm to use when sending data.
 #[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum Compression {
     /// Compresses data using gzip.
     Gzip,
+    /// Compresses data using zstd.
+    Zstd,
 }
 
 impl Display for Compression {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Compression::Gzip => write!(f, "gzip"),
+            Compression::Zstd => write!(f, "zstd"),
         }
     }
 }
@@ -102,6 +131,7 @@ impl FromStr for Compression {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
             _ => Err(Error::UnsupportedCompressionAlgorithm(s.to_string())),
         }
     }
@@ -120,7 +150,9 @@ fn default_protocol() -> Protocol {
 fn default_endpoint(protocol: Protocol) -> String {
     match protocol {
         Protocol::Grpc => OTEL_EXPORTER_OTLP_GRPC_ENDPOINT_DEFAULT.to_string(),
-        Protocol::HttpBinary => OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT.to_string(),
+        Protocol::HttpBinary | Protocol::HttpJson => {
+            OTEL_EXPORTER_OTLP_HTTP_ENDPOINT_DEFAULT.to_string()
+        }
     }
 }
 
@@ -135,6 +167,35 @@ fn default_headers() -> std::collections::HashMap<String, String> {
     headers
 }
 
+/// The export timeout, the signal specific environment variable is preferred over the
+/// general one, then `provided_or_default_timeout`.
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+fn resolve_timeout(signal_timeout_var: &str, provided_or_default_timeout: Duration) -> Duration {
+    match std::env::var(signal_timeout_var)
+        .ok()
+        .or(std::env::var(OTEL_EXPORTER_OTLP_TIMEOUT).ok())
+    {
+        Some(val) => match val.parse() {
+            Ok(seconds) => Duration::from_secs(seconds),
+            Err(_) => provided_or_default_timeout,
+        },
+        None => provided_or_default_timeout,
+    }
+}
+
+/// The compression set in the signal specific environment variable, or else in the general
+/// one. `none`, the spec's value to disable the compression, and unset variables give `None`.
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+fn compression_from_env(signal_compression_var: &str) -> Result<Option<Compression>, Error> {
+    match std::env::var(signal_compression_var)
+        .or_else(|_| std::env::var(OTEL_EXPORTER_OTLP_COMPRESSION))
+    {
+        Ok(compression) if compression == "none" => Ok(None),
+        Ok(compression) => compression.parse().map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
 /// Provide access to the export config field within the exporter builders.
 pub trait HasExportConfig {
     /// Return a mutable reference to the export config within the exporter builders.
@@ -178,10 +239,13 @@ pub trait WithExportConfig {
     /// will use default protocol in this case.
     ///
     /// ## Note
-    /// All exporters in this crate are only support one protocol thus choosing the protocol is an no-op at the moment
+    /// The tonic exporter only supports [`Protocol::Grpc`]. The http exporter supports
+    /// [`Protocol::HttpBinary`] and, with the `http-json` feature, [`Protocol::HttpJson`].
     fn with_protocol(self, protocol: Protocol) -> Self;
     /// Set the timeout to the collector.
     fn with_timeout(self, timeout: Duration) -> Self;
+    /// Set how failed exports are retried. Use [`RetryConfig::disabled`] to turn retries off.
+    fn with_retry_config(self, retry_config: RetryConfig) -> Self;
     /// Set export config. This will override all previous configuration.
     fn with_export_config(self, export_config: ExportConfig) -> Self;
 }
@@ -202,10 +266,16 @@ impl<B: HasExportConfig> WithExportConfig for B {
         self
     }
 
+    fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.export_config().retry_config = retry_config;
+        self
+    }
+
     fn with_export_config(mut self, exporter_config: ExportConfig) -> Self {
         self.export_config().endpoint = exporter_config.endpoint;
         self.export_config().protocol = exporter_config.protocol;
         self.export_config().timeout = exporter_config.timeout;
+        self.export_config().retry_config = exporter_config.retry_config;
         self
     }
 }
@@ -0,0 +1,341 @@
+//! Retry of failed exports with exponential backoff.
+//!
+//! See <https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/protocol/otlp.md#retry>
+//! for which failures are retryable.
+
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+use std::future::Future;
+use std::time::Duration;
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+use std::time::Instant;
+
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+use super::self_observability::ExporterMetrics;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+/// Default maximum number of attempts, including the first one.
+const DEFAULT_MAX_ATTEMPTS: usize = 5;
+/// Default delay before the first retry.
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Default upper bound of the delay between two retries.
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+/// Default randomization factor of the delay between two retries.
+const DEFAULT_JITTER: f64 = 0.2;
+
+/// Configuration of the retries performed by the exporters when an export fails.
+///
+/// Only failures the server marks as transient are retried, i.e. gRPC `UNAVAILABLE`,
+/// `CANCELLED`, `DEADLINE_EXCEEDED`, `ABORTED`, `OUT_OF_RANGE`, `DATA_LOSS` and
+/// `RESOURCE_EXHAUSTED` carrying a `RetryInfo`, and HTTP `429`, `502`, `503` and `504`.
+/// Connection failures and timeouts of the HTTP exporter are retried as well.
+///
+/// The delay between two attempts starts at [`initial_backoff`] and doubles after each
+/// retry, up to [`max_backoff`]. A delay requested by the server through gRPC `RetryInfo`
+/// or the HTTP `Retry-After` header takes precedence and is waited out in full, even when
+/// it is longer than [`max_backoff`]. An export is not retried past its timeout.
+///
+/// ```
+/// use opentelemetry_otlp::RetryConfig;
+/// use std::time::Duration;
+///
+/// let mut retry_config = RetryConfig::default();
+/// retry_config.max_attempts = 3;
+/// retry_config.max_backoff = Duration::from_secs(10);
+/// ```
+///
+/// [`initial_backoff`]: RetryConfig::initial_backoff
+/// [`max_backoff`]: RetryConfig::max_backoff
+#[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct RetryConfig {
+    /// Maximum number of attempts for one export, including the first one.
+    ///
+    /// Setting it to `1` disables retries.
+    pub max_attempts: usize,
+
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+
+    /// Upper bound of the delay between two retries.
+    pub max_backoff: Duration,
+
+    /// Randomization factor between `0.0` and `1.0` applied to each delay. A delay `d` is
+    /// picked uniformly from `[d * (1 - jitter), d * (1 + jitter)]`.
+    pub jitter: f64,
+}
+
+impl RetryConfig {
+    /// A configuration which never retries failed exports.
+    pub fn disabled() -> Self {
+        RetryConfig {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            jitter: DEFAULT_JITTER,
+        }
+    }
+}
+
+/// How a failed attempt should be handled.
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum RetryErrorType {
+    /// The failure is permanent, the export fails right away.
+    NonRetryable,
+    /// The failure is transient, retry after the next backoff delay.
+    Retryable,
+    /// The server asked to retry after the given delay.
+    Throttled(Duration),
+}
+
+/// Run `operation` until it succeeds, `classify` reports a non retryable failure, the
+/// maximum number of attempts is reached or the next attempt would start after `timeout`.
+/// The last failure is returned in the latter cases.
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+pub(crate) async fn retry_with_backoff<F, Fut, T, E, C>(
+    config: RetryConfig,
+    timeout: Duration,
+    metrics: &ExporterMetrics,
+    classify: C,
+    mut operation: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    C: Fn(&E) -> RetryErrorType,
+{
+    let deadline = Instant::now().checked_add(timeout);
+    let mut attempt = 1;
+    let mut backoff = config.initial_backoff;
+
+    loop {
+        let err = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        if attempt >= config.max_attempts {
+            return Err(err);
+        }
+
+        let delay = match classify(&err) {
+            RetryErrorType::NonRetryable => return Err(err),
+            RetryErrorType::Retryable => {
+                let delay = apply_jitter(backoff, config.jitter);
+                backoff = backoff.saturating_mul(2).min(config.max_backoff);
+                delay
+            }
+            // honored as is, the deadline below bounds arbitrarily long delays
+            RetryErrorType::Throttled(delay) => delay,
+        };
+
+        // give up rather than stalling the processor past the export timeout
+        if let Some(deadline) = deadline {
+            if Instant::now() + delay >= deadline {
+                return Err(err);
+            }
+        }
+
+        futures_timer::Delay::new(delay).await;
+        metrics.retried();
+        attempt += 1;
+    }
+}
+
+#[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+fn apply_jitter(delay: Duration, jitter: f64) -> Duration {
+    let jitter = jitter.clamp(0.0, 1.0);
+    if jitter == 0.0 {
+        return delay;
+    }
+
+    let factor = 1.0 - jitter + rand::random::<f64>() * 2.0 * jitter;
+    delay.mul_f64(factor)
+}
+
+#[cfg(all(test, any(feature = "grpc-tonic", feature = "http-proto")))]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+    use std::cell::Cell;
+
+    fn no_delay(max_attempts: usize) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            jitter: 0.0,
+        }
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn run<F: Future>(future: F) -> F::Output {
+        futures_executor::block_on(future)
+    }
+
+    #[test]
+    fn test_no_retry_on_success() {
+        let attempts = Cell::new(0);
+        let result: Result<(), ()> = retry_with_backoff(
+            no_delay(3),
+            TIMEOUT,
+            &ExporterMetrics::default(),
+            |_| RetryErrorType::Retryable,
+            || {
+                attempts.set(attempts.get() + 1);
+                async { Ok(()) }
+            },
+        )
+        .now_or_never()
+        .unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_retry_until_max_attempts() {
+        let attempts = Cell::new(0);
+        let result: Result<(), usize> = run(retry_with_backoff(
+            no_delay(3),
+            TIMEOUT,
+            &ExporterMetrics::default(),
+            |_| RetryErrorType::Retryable,
+            || {
+                attempts.set(attempts.get() + 1);
+                let attempt = attempts.get();
+                async move { Err(attempt) }
+            },
+        ));
+
+        assert_eq!(result, Err(3));
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[test]
+    fn test_retry_until_success() {
+        let attempts = Cell::new(0);
+        let result: Result<usize, ()> = run(retry_with_backoff(
+            no_delay(5),
+            TIMEOUT,
+            &ExporterMetrics::default(),
+            |_| RetryErrorType::Throttled(Duration::ZERO),
+            || {
+                attempts.set(attempts.get() + 1);
+                let attempt = attempts.get();
+                async move {
+                    if attempt < 2 {
+                        Err(())
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+        ));
+
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn test_no_retry_on_non_retryable_error() {
+        let attempts = Cell::new(0);
+        let result: Result<(), ()> = run(retry_with_backoff(
+            no_delay(5),
+            TIMEOUT,
+            &ExporterMetrics::default(),
+            |_| RetryErrorType::NonRetryable,
+            || {
+                attempts.set(attempts.get() + 1);
+                async { Err(()) }
+            },
+        ));
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_disabled_retry() {
+        let attempts = Cell::new(0);
+        let result: Result<(), ()> = run(retry_with_backoff(
+            RetryConfig::disabled(),
+            TIMEOUT,
+            &ExporterMetrics::default(),
+            |_| RetryErrorType::Retryable,
+            || {
+                attempts.set(attempts.get() + 1);
+                async { Err(()) }
+            },
+        ));
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_throttling_delay_is_not_capped() {
+        let attempts = Cell::new(0);
+        let config = RetryConfig {
+            max_backoff: Duration::from_millis(1),
+            ..no_delay(2)
+        };
+        let start = Instant::now();
+        let result: Result<(), ()> = run(retry_with_backoff(
+            config,
+            TIMEOUT,
+            &ExporterMetrics::default(),
+            |_| RetryErrorType::Throttled(Duration::from_millis(100)),
+            || {
+                attempts.set(attempts.get() + 1);
+                async { Err(()) }
+            },
+        ));
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 2);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_no_retry_past_timeout() {
+        let attempts = Cell::new(0);
+        let start = Instant::now();
+        let result: Result<(), ()> = run(retry_with_backoff(
+            no_delay(5),
+            Duration::from_millis(50),
+            &ExporterMetrics::default(),
+            |_| RetryErrorType::Throttled(Duration::from_secs(60)),
+            || {
+                attempts.set(attempts.get() + 1);
+                async { Err(()) }
+            },
+        ));
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+        assert!(start.elapsed() < TIMEOUT);
+    }
+
+    #[test]
+    fn test_apply_jitter() {
+        let delay = Duration::from_secs(10);
+        assert_eq!(apply_jitter(delay, 0.0), delay);
+        for _ in 0..100 {
+            let jittered = apply_jitter(delay, 0.5);
+            assert!(jittered >= Duration::from_secs(5));
+            assert!(jittered <= Duration::from_secs(15));
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Metrics of the exporters themselves, recorded once a meter provider is set with
+//! `with_self_observability`.
+
+#[cfg(feature = "metrics")]
+use opentelemetry::{
+    metrics::{Counter, Meter, MeterProvider, Unit},
+    KeyValue,
+};
+
+/// The meter recording the metrics of the exporters built by a builder
+/// configured with `meter_provider`.
+#[cfg(feature = "metrics")]
+pub(crate) fn meter<P: MeterProvider>(meter_provider: &P) -> Meter {
+    meter_provider.versioned_meter(
+        "opentelemetry-otlp",
+        Some(env!("CARGO_PKG_VERSION")),
+        None::<&'static str>,
+        None,
+    )
+}
+
+/// Records, for the exports of a signal:
+///
+/// - the retries of failed exports in `otel.sdk.exporter.retries`.
+/// - the items rejected by the collector in partial successes in
+///   `otel.sdk.exporter.rejected`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ExporterMetrics {
+    #[cfg(feature = "metrics")]
+    instruments: Option<Instruments>,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Clone, Debug)]
+struct Instruments {
+    retries: Counter<u64>,
+    rejected: Counter<u64>,
+    attributes: [KeyValue; 1],
+}
+
+impl ExporterMetrics {
+    #[cfg(feature = "metrics")]
+    pub(crate) fn new(meter: Option<&Meter>, signal: &'static str) -> Self {
+        ExporterMetrics {
+            instruments: meter.map(|meter| Instruments {
+                retries: meter
+                    .u64_counter("otel.sdk.exporter.retries")
+                    .with_description("The number of retries of failed exports, by signal.")
+                    .with_unit(Unit::new("{retry}"))
+                    .init(),
+                rejected: meter
+                    .u64_counter("otel.sdk.exporter.rejected")
+                    .with_description(
+                        "The number of spans, data points or log records rejected by the collector, by signal.",
+                    )
+                    .with_unit(Unit::new("{item}"))
+                    .init(),
+                attributes: [KeyValue::new("signal", signal)],
+            }),
+        }
+    }
+
+    pub(crate) fn retried(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(instruments) = &self.instruments {
+            instruments.retries.add(1, &instruments.attributes);
+        }
+    }
+
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn rejected(&self, count: u64) {
+        #[cfg(feature = "metrics")]
+        if let Some(instruments) = &self.instruments {
+            instruments.rejected.add(count, &instruments.attributes);
+        }
+    }
+
+    /// Count the `rejected` items of a partial success response, and report them to the
+    /// global error handler as a [`crate::Error::PartialSuccess`].
+    ///
+    /// The export itself succeeded, so the partial success is not returned and must not
+    /// be retried. A response without rejected items nor error message is a full success.
+    pub(crate) fn partial_success<E>(
+        &self,
+        items: &'static str,
+        rejected: i64,
+        error_message: String,
+    ) where
+        E: From<crate::Error> + Into<opentelemetry::global::Error>,
+    {
+        if rejected == 0 && error_message.is_empty() {
+            return;
+        }
+
+        if let Ok(count) = u64::try_from(rejected) {
+            self.rejected(count);
+        }
+        opentelemetry::global::handle_error(E::from(crate::Error::PartialSuccess {
+            items,
+            rejected,
+            error_message,
+        }));
+    }
+}
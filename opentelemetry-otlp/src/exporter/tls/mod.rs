@@ -0,0 +1,177 @@
+//! TLS settings of the OTLP transports, read from the environment.
+//!
+//! The certificate files are watched by the transports, which reconnect with the new
+//! certificates once they have been rotated. The certificates are only used by the
+//! transports built with TLS support, the `tls` feature for gRPC and the
+//! `reqwest-rustls` feature for the default HTTP clients.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "grpc-tonic")]
+use crate::OTEL_EXPORTER_OTLP_INSECURE;
+use crate::{
+    OTEL_EXPORTER_OTLP_CERTIFICATE, OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE,
+    OTEL_EXPORTER_OTLP_CLIENT_KEY,
+};
+
+/// Loading and reloading of the certificate files, only built with a transport using them.
+#[cfg(any(
+    all(feature = "grpc-tonic", feature = "tls"),
+    all(
+        feature = "reqwest-rustls",
+        any(feature = "reqwest-client", feature = "reqwest-blocking-client")
+    )
+))]
+pub(crate) mod watcher;
+
+/// TLS settings of a signal. Signal specific environment variables are preferred over
+/// the general ones.
+#[derive(Debug, Default)]
+pub(crate) struct TlsSettings {
+    /// Connect without TLS to endpoints given without a scheme, only used by gRPC as
+    /// OTLP/HTTP endpoints always have a scheme.
+    #[cfg(feature = "grpc-tonic")]
+    pub(crate) insecure: bool,
+    pub(crate) certificates: CertificateFiles,
+}
+
+impl TlsSettings {
+    #[cfg_attr(not(feature = "grpc-tonic"), allow(unused_variables))]
+    pub(crate) fn from_env(
+        signal_insecure_var: &str,
+        signal_certificate_var: &str,
+        signal_client_certificate_var: &str,
+        signal_client_key_var: &str,
+    ) -> Self {
+        TlsSettings {
+            #[cfg(feature = "grpc-tonic")]
+            insecure: env_var(signal_insecure_var, OTEL_EXPORTER_OTLP_INSECURE)
+                .map_or(false, |insecure| {
+                    insecure.trim().eq_ignore_ascii_case("true")
+                }),
+            certificates: CertificateFiles {
+                certificate: env_var(signal_certificate_var, OTEL_EXPORTER_OTLP_CERTIFICATE)
+                    .map(PathBuf::from),
+                client_certificate: env_var(
+                    signal_client_certificate_var,
+                    OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE,
+                )
+                .map(PathBuf::from),
+                client_key: env_var(signal_client_key_var, OTEL_EXPORTER_OTLP_CLIENT_KEY)
+                    .map(PathBuf::from),
+            },
+        }
+    }
+
+    /// Add a scheme to `endpoint` if it has none, `http` if insecure, `https` otherwise.
+    #[cfg(feature = "grpc-tonic")]
+    pub(crate) fn with_scheme(&self, endpoint: String) -> String {
+        if endpoint.contains("://") {
+            endpoint
+        } else if self.insecure {
+            format!("http://{endpoint}")
+        } else {
+            format!("https://{endpoint}")
+        }
+    }
+}
+
+fn env_var(signal_var: &str, var: &str) -> Option<String> {
+    env::var(signal_var)
+        .or_else(|_| env::var(var))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+/// Paths of the PEM files configuring TLS.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct CertificateFiles {
+    certificate: Option<PathBuf>,
+    client_certificate: Option<PathBuf>,
+    client_key: Option<PathBuf>,
+}
+
+impl CertificateFiles {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.paths().next().is_none()
+    }
+
+    fn paths(&self) -> impl Iterator<Item = &Path> {
+        [
+            &self.certificate,
+            &self.client_certificate,
+            &self.client_key,
+        ]
+        .into_iter()
+        .filter_map(|path| path.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TlsSettings;
+    use crate::exporter::tests::run_env_test;
+
+    const INSECURE: &str = "OTEL_EXPORTER_OTLP_TRACES_INSECURE";
+    const CERTIFICATE: &str = "OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE";
+    const CLIENT_CERTIFICATE: &str = "OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE";
+    const CLIENT_KEY: &str = "OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY";
+
+    #[test]
+    fn test_signal_settings_override_general_ones() {
+        use crate::{
+            OTEL_EXPORTER_OTLP_CERTIFICATE, OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE,
+            OTEL_EXPORTER_OTLP_INSECURE,
+        };
+
+        run_env_test(
+            vec![
+                (OTEL_EXPORTER_OTLP_INSECURE, "true"),
+                (INSECURE, "false"),
+                (OTEL_EXPORTER_OTLP_CERTIFICATE, "/general/ca.pem"),
+                (CERTIFICATE, "/traces/ca.pem"),
+                (OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE, "/general/client.pem"),
+            ],
+            || {
+                let settings =
+                    TlsSettings::from_env(INSECURE, CERTIFICATE, CLIENT_CERTIFICATE, CLIENT_KEY);
+                #[cfg(feature = "grpc-tonic")]
+                assert!(!settings.insecure);
+                assert_eq!(
+                    settings.certificates.certificate,
+                    Some("/traces/ca.pem".into())
+                );
+                assert_eq!(
+                    settings.certificates.client_certificate,
+                    Some("/general/client.pem".into())
+                );
+                assert_eq!(settings.certificates.client_key, None);
+            },
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "grpc-tonic")]
+    fn test_with_scheme() {
+        let secure = TlsSettings::default();
+        let insecure = TlsSettings {
+            insecure: true,
+            ..TlsSettings::default()
+        };
+
+        assert_eq!(
+            secure.with_scheme("collector:4317".into()),
+            "https://collector:4317"
+        );
+        assert_eq!(
+            insecure.with_scheme("collector:4317".into()),
+            "http://collector:4317"
+        );
+        // only endpoints without a scheme are affected
+        assert_eq!(
+            insecure.with_scheme("https://collector:4317".into()),
+            "https://collector:4317"
+        );
+    }
+}
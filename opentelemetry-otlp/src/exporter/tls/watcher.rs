@@ -0,0 +1,198 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::CertificateFiles;
+use crate::Error;
+
+/// How often the certificate files are checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The content of the certificate files.
+#[derive(Debug)]
+pub(crate) struct Certificates {
+    /// The certificates trusted to verify the collector's certificate.
+    pub(crate) certificate: Option<Vec<u8>>,
+    /// The client certificate chain and its private key.
+    pub(crate) identity: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+/// When a file was last written, and its size.
+type FileVersion = Option<(SystemTime, u64)>;
+
+impl CertificateFiles {
+    fn versions(&self) -> Vec<FileVersion> {
+        self.paths()
+            .map(|path| {
+                let metadata = fs::metadata(path).ok()?;
+                Some((metadata.modified().ok()?, metadata.len()))
+            })
+            .collect()
+    }
+
+    fn read(&self) -> Result<Certificates, Error> {
+        let read = |path: &Path| {
+            fs::read(path)
+                .map_err(|err| Error::InvalidCertificates(format!("{}: {err}", path.display())))
+        };
+
+        let identity = match (&self.client_certificate, &self.client_key) {
+            (Some(certificate), Some(key)) => Some((read(certificate)?, read(key)?)),
+            (None, None) => None,
+            _ => {
+                return Err(Error::InvalidCertificates(
+                    "the client certificate and the client key must be set together".into(),
+                ))
+            }
+        };
+
+        Ok(Certificates {
+            certificate: self.certificate.as_deref().map(read).transpose()?,
+            identity,
+        })
+    }
+}
+
+/// Loads certificate files, and detects their changes to load them again once rotated.
+#[derive(Debug)]
+pub(crate) struct CertificateWatcher {
+    files: CertificateFiles,
+    versions: Option<Vec<FileVersion>>,
+    /// When the files were last checked for changes, they are checked at most every
+    /// `check_interval`.
+    last_check: Option<Instant>,
+    check_interval: Duration,
+}
+
+impl CertificateWatcher {
+    pub(crate) fn new(files: CertificateFiles) -> Self {
+        CertificateWatcher {
+            files,
+            versions: None,
+            last_check: None,
+            check_interval: CHECK_INTERVAL,
+        }
+    }
+
+    /// Read the certificates and `build` a client with them.
+    ///
+    /// The certificates are only considered loaded if `build` succeeds.
+    pub(crate) fn load<T>(
+        &mut self,
+        build: impl FnOnce(Certificates) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let versions = self.files.versions();
+        let client = build(self.files.read()?)?;
+        self.versions = Some(versions);
+        Ok(client)
+    }
+
+    /// [Load](Self::load) the certificates if the files changed since they were last
+    /// loaded, checking them at most every [`CHECK_INTERVAL`].
+    ///
+    /// Failures are retried on the next check, as the files may not all have been
+    /// rotated yet.
+    pub(crate) fn reload<T>(
+        &mut self,
+        build: impl FnOnce(Certificates) -> Result<T, Error>,
+    ) -> Option<Result<T, Error>> {
+        let now = Instant::now();
+        if self
+            .last_check
+            .map_or(false, |last_check| now < last_check + self.check_interval)
+        {
+            return None;
+        }
+        self.last_check = Some(now);
+
+        if self.versions.as_ref() == Some(&self.files.versions()) {
+            None
+        } else {
+            Some(self.load(build))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::{CertificateWatcher, Certificates};
+    use crate::exporter::tests::run_env_test;
+    use crate::exporter::tls::TlsSettings;
+
+    const INSECURE: &str = "OTEL_EXPORTER_OTLP_TRACES_INSECURE";
+    const CERTIFICATE: &str = "OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE";
+    const CLIENT_CERTIFICATE: &str = "OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE";
+    const CLIENT_KEY: &str = "OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "opentelemetry-otlp-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_client_certificate_requires_key() {
+        run_env_test(vec![(CLIENT_CERTIFICATE, "/traces/client.pem")], || {
+            let settings =
+                TlsSettings::from_env(INSECURE, CERTIFICATE, CLIENT_CERTIFICATE, CLIENT_KEY);
+            let result = CertificateWatcher::new(settings.certificates).load(|_| Ok(()));
+            assert!(result.is_err());
+        });
+    }
+
+    #[test]
+    fn test_reload_rotated_certificates() {
+        let dir = temp_dir("certificates");
+        let certificate = dir.join("client.pem");
+        let key = dir.join("client.key");
+        fs::write(&certificate, "certificate").unwrap();
+        fs::write(&key, "key").unwrap();
+
+        let (certificate_path, key_path) = (
+            certificate.to_str().unwrap().to_string(),
+            key.to_str().unwrap().to_string(),
+        );
+        let settings = temp_env::with_vars(
+            [
+                (CLIENT_CERTIFICATE, Some(certificate_path)),
+                (CLIENT_KEY, Some(key_path)),
+            ],
+            || TlsSettings::from_env(INSECURE, CERTIFICATE, CLIENT_CERTIFICATE, CLIENT_KEY),
+        );
+        let identity = |certificates: Certificates| Ok(certificates.identity.unwrap());
+
+        let mut watcher = CertificateWatcher::new(settings.certificates);
+        watcher.check_interval = std::time::Duration::ZERO;
+        assert_eq!(
+            watcher.load(identity).unwrap(),
+            (b"certificate".to_vec(), b"key".to_vec())
+        );
+        assert!(watcher.reload(identity).is_none());
+
+        fs::write(&certificate, "rotated certificate").unwrap();
+        fs::write(&key, "rotated key").unwrap();
+        assert_eq!(
+            watcher.reload(identity).unwrap().unwrap(),
+            (b"rotated certificate".to_vec(), b"rotated key".to_vec())
+        );
+        assert!(watcher.reload(identity).is_none());
+
+        // failures are retried until the certificates are loaded
+        fs::remove_file(&key).unwrap();
+        assert!(watcher.reload(identity).unwrap().is_err());
+        assert!(watcher.reload(identity).unwrap().is_err());
+        fs::write(&key, "new key").unwrap();
+        assert!(watcher.reload(identity).unwrap().is_ok());
+        assert!(watcher.reload(identity).is_none());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
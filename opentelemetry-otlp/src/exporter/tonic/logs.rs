@@ -1,4 +1,5 @@
 use core::fmt;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use opentelemetry::logs::{LogError, LogResult};
@@ -8,14 +9,21 @@ use opentelemetry_proto::tonic::collector::logs::v1::{
 use opentelemetry_sdk::export::logs::{LogData, LogExporter};
 use tonic::{codegen::CompressionEncoding, service::Interceptor, transport::Channel, Request};
 
-use super::BoxInterceptor;
+use super::{classify_status, BoxInterceptor, TonicChannel};
+use crate::exporter::retry::{retry_with_backoff, RetryConfig};
+use crate::exporter::self_observability::ExporterMetrics;
 
 pub(crate) struct TonicLogsClient {
+    retry_config: RetryConfig,
+    timeout: Duration,
+    exporter_metrics: ExporterMetrics,
     inner: Option<ClientInner>,
 }
 
 struct ClientInner {
     client: LogsServiceClient<Channel>,
+    channel: TonicChannel,
+    compression: Option<CompressionEncoding>,
     interceptor: BoxInterceptor,
 }
 
@@ -27,31 +35,50 @@ impl fmt::Debug for TonicLogsClient {
 
 impl TonicLogsClient {
     pub(super) fn new(
-        channel: Channel,
+        channel: TonicChannel,
         interceptor: BoxInterceptor,
         compression: Option<CompressionEncoding>,
+        retry_config: RetryConfig,
+        timeout: Duration,
+        exporter_metrics: ExporterMetrics,
     ) -> Self {
-        let mut client = LogsServiceClient::new(channel);
-        if let Some(compression) = compression {
-            client = client
-                .send_compressed(compression)
-                .accept_compressed(compression);
-        }
+        let client = service_client(channel.channel(), compression);
 
         TonicLogsClient {
+            retry_config,
+            timeout,
+            exporter_metrics,
             inner: Some(ClientInner {
                 client,
+                channel,
+                compression,
                 interceptor,
             }),
         }
     }
 }
 
+fn service_client(
+    channel: Channel,
+    compression: Option<CompressionEncoding>,
+) -> LogsServiceClient<Channel> {
+    let client = LogsServiceClient::new(channel);
+    match compression {
+        Some(compression) => client
+            .send_compressed(compression)
+            .accept_compressed(compression),
+        None => client,
+    }
+}
+
 #[async_trait]
 impl LogExporter for TonicLogsClient {
     async fn export(&mut self, batch: Vec<LogData>) -> LogResult<()> {
-        let (mut client, metadata, extensions) = match &mut self.inner {
+        let (client, metadata, extensions) = match &mut self.inner {
             Some(inner) => {
+                if let Some(channel) = inner.channel.reconnect::<LogError>() {
+                    inner.client = service_client(channel, inner.compression);
+                }
                 let (m, e, _) = inner
                     .interceptor
                     .call(Request::new(()))
@@ -62,16 +89,37 @@ impl LogExporter for TonicLogsClient {
             None => return Err(LogError::Other("exporter is already shut down".into())),
         };
 
-        client
-            .export(Request::from_parts(
-                metadata,
-                extensions,
-                ExportLogsServiceRequest {
-                    resource_logs: batch.into_iter().map(Into::into).collect(),
-                },
-            ))
-            .await
-            .map_err(crate::Error::from)?;
+        let request = ExportLogsServiceRequest {
+            resource_logs: batch.into_iter().map(Into::into).collect(),
+        };
+        // extensions are not cloneable, only the first attempt carries them
+        let mut extensions = Some(extensions);
+
+        let response = retry_with_backoff(
+            self.retry_config,
+            self.timeout,
+            &self.exporter_metrics,
+            classify_status,
+            || {
+                let mut client = client.clone();
+                let request = Request::from_parts(
+                    metadata.clone(),
+                    extensions.take().unwrap_or_default(),
+                    request.clone(),
+                );
+                async move { client.export(request).await }
+            },
+        )
+        .await
+        .map_err(crate::Error::from)?;
+
+        if let Some(partial_success) = response.into_inner().partial_success {
+            self.exporter_metrics.partial_success::<LogError>(
+                "log records",
+                partial_success.rejected_log_records,
+                partial_success.error_message,
+            );
+        }
 
         Ok(())
     }
@@ -1,5 +1,6 @@
 use core::fmt;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use opentelemetry::metrics::{MetricsError, Result};
@@ -9,15 +10,22 @@ use opentelemetry_proto::tonic::collector::metrics::v1::{
 use opentelemetry_sdk::metrics::data::ResourceMetrics;
 use tonic::{codegen::CompressionEncoding, service::Interceptor, transport::Channel, Request};
 
-use super::BoxInterceptor;
+use super::{classify_status, BoxInterceptor, TonicChannel};
+use crate::exporter::retry::{retry_with_backoff, RetryConfig};
+use crate::exporter::self_observability::ExporterMetrics;
 use crate::metric::MetricsClient;
 
 pub(crate) struct TonicMetricsClient {
+    retry_config: RetryConfig,
+    timeout: Duration,
+    exporter_metrics: ExporterMetrics,
     inner: Mutex<Option<ClientInner>>,
 }
 
 struct ClientInner {
     client: MetricsServiceClient<Channel>,
+    channel: TonicChannel,
+    compression: Option<CompressionEncoding>,
     interceptor: BoxInterceptor,
 }
 
@@ -29,35 +37,54 @@ impl fmt::Debug for TonicMetricsClient {
 
 impl TonicMetricsClient {
     pub(super) fn new(
-        channel: Channel,
+        channel: TonicChannel,
         interceptor: BoxInterceptor,
         compression: Option<CompressionEncoding>,
+        retry_config: RetryConfig,
+        timeout: Duration,
+        exporter_metrics: ExporterMetrics,
     ) -> Self {
-        let mut client = MetricsServiceClient::new(channel);
-        if let Some(compression) = compression {
-            client = client
-                .send_compressed(compression)
-                .accept_compressed(compression);
-        }
+        let client = service_client(channel.channel(), compression);
 
         TonicMetricsClient {
+            retry_config,
+            timeout,
+            exporter_metrics,
             inner: Mutex::new(Some(ClientInner {
                 client,
+                channel,
+                compression,
                 interceptor,
             })),
         }
     }
 }
 
+fn service_client(
+    channel: Channel,
+    compression: Option<CompressionEncoding>,
+) -> MetricsServiceClient<Channel> {
+    let client = MetricsServiceClient::new(channel);
+    match compression {
+        Some(compression) => client
+            .send_compressed(compression)
+            .accept_compressed(compression),
+        None => client,
+    }
+}
+
 #[async_trait]
 impl MetricsClient for TonicMetricsClient {
     async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()> {
-        let (mut client, metadata, extensions) =
+        let (client, metadata, extensions) =
             self.inner
                 .lock()
                 .map_err(Into::into)
                 .and_then(|mut inner| match &mut *inner {
                     Some(inner) => {
+                        if let Some(channel) = inner.channel.reconnect::<MetricsError>() {
+                            inner.client = service_client(channel, inner.compression);
+                        }
                         let (m, e, _) = inner
                             .interceptor
                             .call(Request::new(()))
@@ -72,14 +99,35 @@ impl MetricsClient for TonicMetricsClient {
                     None => Err(MetricsError::Other("exporter is already shut down".into())),
                 })?;
 
-        client
-            .export(Request::from_parts(
-                metadata,
-                extensions,
-                ExportMetricsServiceRequest::from(&*metrics),
-            ))
-            .await
-            .map_err(crate::Error::from)?;
+        let request = ExportMetricsServiceRequest::from(&*metrics);
+        // extensions are not cloneable, only the first attempt carries them
+        let mut extensions = Some(extensions);
+
+        let response = retry_with_backoff(
+            self.retry_config,
+            self.timeout,
+            &self.exporter_metrics,
+            classify_status,
+            || {
+                let mut client = client.clone();
+                let request = Request::from_parts(
+                    metadata.clone(),
+                    extensions.take().unwrap_or_default(),
+                    request.clone(),
+                );
+                async move { client.export(request).await }
+            },
+        )
+        .await
+        .map_err(crate::Error::from)?;
+
+        if let Some(partial_success) = response.into_inner().partial_success {
+            self.exporter_metrics.partial_success::<MetricsError>(
+                "data points",
+                partial_success.rejected_data_points,
+                partial_success.error_message,
+            );
+        }
 
         Ok(())
     }
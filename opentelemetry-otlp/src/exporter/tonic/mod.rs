@@ -9,14 +9,16 @@ use tonic::metadata::{KeyAndValueRef, MetadataMap};
 use tonic::service::Interceptor;
 use tonic::transport::Channel;
 #[cfg(feature = "tls")]
-use tonic::transport::ClientTlsConfig;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity};
 
-use super::{default_headers, parse_header_string};
+use super::{compression_from_env, default_headers, parse_header_string, resolve_timeout};
+use crate::exporter::retry::RetryErrorType;
+use crate::exporter::self_observability::ExporterMetrics;
+#[cfg(feature = "tls")]
+use crate::exporter::tls::watcher::{CertificateWatcher, Certificates};
+use crate::exporter::tls::TlsSettings;
 use crate::exporter::Compression;
-use crate::{
-    ExportConfig, OTEL_EXPORTER_OTLP_COMPRESSION, OTEL_EXPORTER_OTLP_ENDPOINT,
-    OTEL_EXPORTER_OTLP_HEADERS, OTEL_EXPORTER_OTLP_TIMEOUT,
-};
+use crate::{ExportConfig, OTEL_EXPORTER_OTLP_ENDPOINT, OTEL_EXPORTER_OTLP_HEADERS};
 
 #[cfg(feature = "logs")]
 mod logs;
@@ -55,6 +57,12 @@ impl TryFrom<Compression> for tonic::codec::CompressionEncoding {
             Compression::Gzip => Err(crate::Error::UnsupportedCompressionAlgorithm(
                 value.to_string(),
             )),
+            #[cfg(feature = "zstd-tonic")]
+            Compression::Zstd => Ok(tonic::codec::CompressionEncoding::Zstd),
+            #[cfg(not(feature = "zstd-tonic"))]
+            Compression::Zstd => Err(crate::Error::UnsupportedCompressionAlgorithm(
+                value.to_string(),
+            )),
         }
     }
 }
@@ -63,22 +71,129 @@ fn resolve_compression(
     tonic_config: &TonicConfig,
     env_override: &str,
 ) -> Result<Option<CompressionEncoding>, crate::Error> {
-    if let Some(compression) = tonic_config.compression {
-        Ok(Some(compression.try_into()?))
-    } else if let Ok(compression) = env::var(env_override) {
-        Ok(Some(compression.parse::<Compression>()?.try_into()?))
-    } else if let Ok(compression) = env::var(OTEL_EXPORTER_OTLP_COMPRESSION) {
-        Ok(Some(compression.parse::<Compression>()?.try_into()?))
-    } else {
-        Ok(None)
+    let compression = match tonic_config.compression {
+        Some(compression) => Some(compression),
+        None => compression_from_env(env_override)?,
+    };
+    compression.map(TryInto::try_into).transpose()
+}
+
+/// Decide whether an export failing with `status` can be retried, following
+/// <https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/protocol/otlp.md#failures>.
+pub(crate) fn classify_status(status: &tonic::Status) -> RetryErrorType {
+    use tonic::Code;
+
+    match status.code() {
+        Code::Cancelled
+        | Code::DeadlineExceeded
+        | Code::Aborted
+        | Code::OutOfRange
+        | Code::Unavailable
+        | Code::DataLoss => match retry_delay(status) {
+            Some(delay) => RetryErrorType::Throttled(delay),
+            None => RetryErrorType::Retryable,
+        },
+        // only retryable if the server signals that it can recover
+        Code::ResourceExhausted => match retry_delay(status) {
+            Some(delay) => RetryErrorType::Throttled(delay),
+            None => RetryErrorType::NonRetryable,
+        },
+        _ => RetryErrorType::NonRetryable,
     }
 }
 
+const RETRY_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.RetryInfo";
+
+/// `google.rpc.Status`, only decoded to look up a `RetryInfo` in its details.
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    pub(crate) code: i32,
+    #[prost(string, tag = "2")]
+    pub(crate) message: String,
+    #[prost(message, repeated, tag = "3")]
+    pub(crate) details: Vec<RpcAny>,
+}
+
+/// `google.protobuf.Any`
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct RpcAny {
+    #[prost(string, tag = "1")]
+    type_url: String,
+    #[prost(bytes = "vec", tag = "2")]
+    value: Vec<u8>,
+}
+
+/// `google.rpc.RetryInfo`
+#[derive(Clone, PartialEq, prost::Message)]
+struct RetryInfo {
+    #[prost(message, optional, tag = "1")]
+    retry_delay: Option<RpcDuration>,
+}
+
+/// `google.protobuf.Duration`
+#[derive(Clone, PartialEq, prost::Message)]
+struct RpcDuration {
+    #[prost(int64, tag = "1")]
+    seconds: i64,
+    #[prost(int32, tag = "2")]
+    nanos: i32,
+}
+
+/// Extract the delay requested by the server from the `RetryInfo` in the status details.
+fn retry_delay(status: &tonic::Status) -> Option<Duration> {
+    use prost::Message;
+
+    let details = RpcStatus::decode(status.details()).ok()?;
+    let retry_info = details
+        .details
+        .iter()
+        .find(|detail| detail.type_url == RETRY_INFO_TYPE_URL)?;
+    let delay = RetryInfo::decode(retry_info.value.as_slice())
+        .ok()?
+        .retry_delay?;
+
+    Some(Duration::new(
+        u64::try_from(delay.seconds).ok()?,
+        u32::try_from(delay.nanos).ok()?,
+    ))
+}
+
+/// A status carrying a `RetryInfo` in its details, as sent by servers requesting
+/// the client to wait for `delay` before retrying.
+#[cfg(any(test, feature = "testing"))]
+pub(crate) fn status_with_retry_delay(
+    code: tonic::Code,
+    message: &str,
+    delay: Duration,
+) -> tonic::Status {
+    use prost::Message;
+
+    let retry_info = RetryInfo {
+        retry_delay: Some(RpcDuration {
+            seconds: delay.as_secs() as i64,
+            nanos: delay.subsec_nanos() as i32,
+        }),
+    };
+    let details = RpcStatus {
+        code: code as i32,
+        message: message.to_string(),
+        details: vec![RpcAny {
+            type_url: RETRY_INFO_TYPE_URL.to_string(),
+            value: retry_info.encode_to_vec(),
+        }],
+    };
+
+    tonic::Status::with_details(code, message, details.encode_to_vec().into())
+}
+
 /// Configuration for the [tonic] OTLP GRPC exporter.
 ///
 /// It allows you to
 /// - add additional metadata
-/// - set tls config (via the  `tls` feature)
+/// - set tls config (via the  `tls` feature), or load the certificates set in the
+///   `OTEL_EXPORTER_OTLP_CERTIFICATE`, `OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE` and
+///   `OTEL_EXPORTER_OTLP_CLIENT_KEY` environment variables, reconnecting when they change
 /// - specify custom [channel]s
 ///
 /// [tonic]: <https://github.com/hyperium/tonic>
@@ -118,6 +233,8 @@ pub struct TonicExporterBuilder {
     pub(crate) tonic_config: TonicConfig,
     pub(crate) channel: Option<tonic::transport::Channel>,
     pub(crate) interceptor: Option<BoxInterceptor>,
+    #[cfg(feature = "metrics")]
+    pub(crate) meter: Option<opentelemetry::metrics::Meter>,
 }
 
 pub(crate) struct BoxInterceptor(Box<dyn Interceptor + Send + Sync>);
@@ -155,12 +272,32 @@ impl Default for TonicExporterBuilder {
             tonic_config,
             channel: Option::default(),
             interceptor: Option::default(),
+            #[cfg(feature = "metrics")]
+            meter: None,
         }
     }
 }
 
 impl TonicExporterBuilder {
+    /// Record the number of retries of failed exports (`otel.sdk.exporter.retries`)
+    /// and the number of items rejected by the collector (`otel.sdk.exporter.rejected`)
+    /// with `meter_provider`.
+    ///
+    /// The number of exported items and the duration of the exports are recorded
+    /// by the processors and readers of the SDK, see their `with_self_observability`
+    /// methods.
+    #[cfg(feature = "metrics")]
+    pub fn with_self_observability<P>(mut self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        self.meter = Some(super::self_observability::meter(meter_provider));
+        self
+    }
+
     /// Set the TLS settings for the collector endpoint.
+    ///
+    /// The certificates set in the environment are ignored when TLS settings are given.
     #[cfg(feature = "tls")]
     pub fn with_tls_config(mut self, tls_config: ClientTlsConfig) -> Self {
         self.tonic_config.tls_config = Some(tls_config);
@@ -217,7 +354,8 @@ impl TonicExporterBuilder {
         signal_timeout_var: &str,
         signal_compression_var: &str,
         signal_headers_var: &str,
-    ) -> Result<(Channel, BoxInterceptor, Option<CompressionEncoding>), crate::Error> {
+        tls_settings: TlsSettings,
+    ) -> Result<(TonicChannel, BoxInterceptor, Option<CompressionEncoding>), crate::Error> {
         let tonic_config = self.tonic_config;
         let compression = resolve_compression(&tonic_config, signal_compression_var)?;
 
@@ -251,7 +389,7 @@ impl TonicExporterBuilder {
 
         // If a custom channel was provided, use that channel instead of creating one
         if let Some(channel) = self.channel {
-            return Ok((channel, interceptor, compression));
+            return Ok((TonicChannel::new(channel), interceptor, compression));
         }
 
         let config = self.exporter_config;
@@ -263,34 +401,49 @@ impl TonicExporterBuilder {
             None => format!("{}{signal_endpoint_path}", config.endpoint),
         };
 
-        let endpoint = Channel::from_shared(endpoint).map_err(crate::Error::from)?;
-        let timeout = match env::var(signal_timeout_var)
-            .ok()
-            .or(env::var(OTEL_EXPORTER_OTLP_TIMEOUT).ok())
-        {
-            Some(val) => match val.parse() {
-                Ok(seconds) => Duration::from_secs(seconds),
-                Err(_) => config.timeout,
-            },
-            None => config.timeout,
-        };
+        let endpoint =
+            Channel::from_shared(tls_settings.with_scheme(endpoint)).map_err(crate::Error::from)?;
+        let endpoint = endpoint.timeout(resolve_timeout(signal_timeout_var, config.timeout));
 
         #[cfg(feature = "tls")]
         let channel = match tonic_config.tls_config {
-            Some(tls_config) => endpoint
-                .tls_config(tls_config)
-                .map_err(crate::Error::from)?,
-            None => endpoint,
-        }
-        .timeout(timeout)
-        .connect_lazy();
+            Some(tls_config) => TonicChannel::new(
+                endpoint
+                    .tls_config(tls_config)
+                    .map_err(crate::Error::from)?
+                    .connect_lazy(),
+            ),
+            None if !tls_settings.certificates.is_empty() => {
+                let mut watcher = CertificateWatcher::new(tls_settings.certificates);
+                let channel = watcher.load(|certificates| connect(&endpoint, certificates))?;
+                TonicChannel {
+                    channel,
+                    reload: Some(ChannelReload { endpoint, watcher }),
+                }
+            }
+            None => TonicChannel::new(endpoint.connect_lazy()),
+        };
 
         #[cfg(not(feature = "tls"))]
-        let channel = endpoint.timeout(timeout).connect_lazy();
+        let channel = if tls_settings.certificates.is_empty() {
+            TonicChannel::new(endpoint.connect_lazy())
+        } else {
+            return Err(crate::Error::InvalidCertificates(
+                "the `tls` feature is required to load them".into(),
+            ));
+        };
 
         Ok((channel, interceptor, compression))
     }
 
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn exporter_metrics(&self, signal: &'static str) -> ExporterMetrics {
+        #[cfg(feature = "metrics")]
+        return ExporterMetrics::new(self.meter.as_ref(), signal);
+        #[cfg(not(feature = "metrics"))]
+        ExporterMetrics::default()
+    }
+
     /// Build a new tonic log exporter
     #[cfg(feature = "logs")]
     pub fn build_log_exporter(
@@ -298,15 +451,34 @@ impl TonicExporterBuilder {
     ) -> Result<crate::logs::LogExporter, opentelemetry::logs::LogError> {
         use crate::exporter::tonic::logs::TonicLogsClient;
 
+        let retry_config = self.exporter_config.retry_config;
+        let timeout = resolve_timeout(
+            crate::logs::OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
+            self.exporter_config.timeout,
+        );
+        let exporter_metrics = self.exporter_metrics("logs");
         let (channel, interceptor, compression) = self.build_channel(
             crate::logs::OTEL_EXPORTER_OTLP_LOGS_ENDPOINT,
             "/v1/logs",
             crate::logs::OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
             crate::logs::OTEL_EXPORTER_OTLP_LOGS_COMPRESSION,
             crate::logs::OTEL_EXPORTER_OTLP_LOGS_HEADERS,
+            TlsSettings::from_env(
+                crate::logs::OTEL_EXPORTER_OTLP_LOGS_INSECURE,
+                crate::logs::OTEL_EXPORTER_OTLP_LOGS_CERTIFICATE,
+                crate::logs::OTEL_EXPORTER_OTLP_LOGS_CLIENT_CERTIFICATE,
+                crate::logs::OTEL_EXPORTER_OTLP_LOGS_CLIENT_KEY,
+            ),
         )?;
 
-        let client = TonicLogsClient::new(channel, interceptor, compression);
+        let client = TonicLogsClient::new(
+            channel,
+            interceptor,
+            compression,
+            retry_config,
+            timeout,
+            exporter_metrics,
+        );
 
         Ok(crate::logs::LogExporter::new(client))
     }
@@ -321,15 +493,34 @@ impl TonicExporterBuilder {
         use crate::MetricsExporter;
         use metrics::TonicMetricsClient;
 
+        let retry_config = self.exporter_config.retry_config;
+        let timeout = resolve_timeout(
+            crate::metric::OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
+            self.exporter_config.timeout,
+        );
+        let exporter_metrics = self.exporter_metrics("metrics");
         let (channel, interceptor, compression) = self.build_channel(
             crate::metric::OTEL_EXPORTER_OTLP_METRICS_ENDPOINT,
             "/v1/metrics",
             crate::metric::OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
             crate::metric::OTEL_EXPORTER_OTLP_METRICS_COMPRESSION,
             crate::metric::OTEL_EXPORTER_OTLP_METRICS_HEADERS,
+            TlsSettings::from_env(
+                crate::metric::OTEL_EXPORTER_OTLP_METRICS_INSECURE,
+                crate::metric::OTEL_EXPORTER_OTLP_METRICS_CERTIFICATE,
+                crate::metric::OTEL_EXPORTER_OTLP_METRICS_CLIENT_CERTIFICATE,
+                crate::metric::OTEL_EXPORTER_OTLP_METRICS_CLIENT_KEY,
+            ),
         )?;
 
-        let client = TonicMetricsClient::new(channel, interceptor, compression);
+        let client = TonicMetricsClient::new(
+            channel,
+            interceptor,
+            compression,
+            retry_config,
+            timeout,
+            exporter_metrics,
+        );
 
         Ok(MetricsExporter::new(
             client,
@@ -345,20 +536,104 @@ impl TonicExporterBuilder {
     ) -> Result<crate::SpanExporter, opentelemetry::trace::TraceError> {
         use crate::exporter::tonic::trace::TonicTracesClient;
 
+        let retry_config = self.exporter_config.retry_config;
+        let timeout = resolve_timeout(
+            crate::span::OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
+            self.exporter_config.timeout,
+        );
+        let exporter_metrics = self.exporter_metrics("traces");
         let (channel, interceptor, compression) = self.build_channel(
             crate::span::OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
             "/v1/traces",
             crate::span::OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
             crate::span::OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
             crate::span::OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+            TlsSettings::from_env(
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_INSECURE,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE,
+                crate::span::OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY,
+            ),
         )?;
 
-        let client = TonicTracesClient::new(channel, interceptor, compression);
+        let client = TonicTracesClient::new(
+            channel,
+            interceptor,
+            compression,
+            retry_config,
+            timeout,
+            exporter_metrics,
+        );
 
         Ok(crate::SpanExporter::new(client))
     }
 }
 
+/// The channel to the collector, reconnected when the certificates loaded from the
+/// environment are rotated.
+pub(crate) struct TonicChannel {
+    channel: Channel,
+    #[cfg(feature = "tls")]
+    reload: Option<ChannelReload>,
+}
+
+#[cfg(feature = "tls")]
+struct ChannelReload {
+    endpoint: Endpoint,
+    watcher: CertificateWatcher,
+}
+
+impl TonicChannel {
+    fn new(channel: Channel) -> Self {
+        TonicChannel {
+            channel,
+            #[cfg(feature = "tls")]
+            reload: None,
+        }
+    }
+
+    pub(crate) fn channel(&self) -> Channel {
+        self.channel.clone()
+    }
+
+    /// Connect with the certificates if they changed since the last connection, and
+    /// return the new channel.
+    ///
+    /// Certificates failing to load are reported to the global error handler, and the
+    /// current channel is kept.
+    #[cfg_attr(not(feature = "tls"), allow(clippy::extra_unused_type_parameters))]
+    pub(crate) fn reconnect<E>(&mut self) -> Option<Channel>
+    where
+        E: From<crate::Error> + Into<opentelemetry::global::Error>,
+    {
+        #[cfg(feature = "tls")]
+        if let Some(ChannelReload { endpoint, watcher }) = &mut self.reload {
+            match watcher.reload(|certificates| connect(endpoint, certificates))? {
+                Ok(channel) => {
+                    self.channel = channel;
+                    return Some(self.channel());
+                }
+                Err(err) => opentelemetry::global::handle_error(E::from(err)),
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(feature = "tls")]
+fn connect(endpoint: &Endpoint, certificates: Certificates) -> Result<Channel, crate::Error> {
+    let mut tls_config = ClientTlsConfig::new();
+    if let Some(certificate) = certificates.certificate {
+        tls_config = tls_config.ca_certificate(Certificate::from_pem(certificate));
+    }
+    if let Some((certificate, key)) = certificates.identity {
+        tls_config = tls_config.identity(Identity::from_pem(certificate, key));
+    }
+
+    Ok(endpoint.clone().tls_config(tls_config)?.connect_lazy())
+}
+
 fn merge_metadata_with_headers_from_env(
     metadata: MetadataMap,
     headers_from_env: HeaderMap,
@@ -439,6 +714,37 @@ mod tests {
         assert_eq!(builder.tonic_config.compression.unwrap(), Compression::Gzip);
     }
 
+    #[test]
+    fn test_resolve_compression_from_env() {
+        use crate::OTEL_EXPORTER_OTLP_COMPRESSION;
+        // stands for the per-signal variables, which depend on the signal features
+        const SIGNAL_COMPRESSION: &str = "OTEL_EXPORTER_OTLP_SIGNAL_COMPRESSION";
+
+        let tonic_config = TonicExporterBuilder::default().tonic_config;
+        // `none` disables the compression, as with the http exporter
+        run_env_test(vec![(OTEL_EXPORTER_OTLP_COMPRESSION, "none")], || {
+            assert_eq!(
+                super::resolve_compression(&tonic_config, SIGNAL_COMPRESSION).unwrap(),
+                None
+            );
+        });
+        run_env_test(
+            vec![
+                (SIGNAL_COMPRESSION, "none"),
+                (OTEL_EXPORTER_OTLP_COMPRESSION, "brotli"),
+            ],
+            || {
+                assert_eq!(
+                    super::resolve_compression(&tonic_config, SIGNAL_COMPRESSION).unwrap(),
+                    None
+                );
+            },
+        );
+        run_env_test(vec![(OTEL_EXPORTER_OTLP_COMPRESSION, "brotli")], || {
+            assert!(super::resolve_compression(&tonic_config, SIGNAL_COMPRESSION).is_err());
+        });
+    }
+
     #[test]
     fn test_parse_headers_from_env() {
         run_env_test(
@@ -496,4 +802,99 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    #[cfg(all(feature = "tls", feature = "trace"))]
+    fn test_certificates_from_env() {
+        run_env_test(
+            vec![(
+                crate::OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE,
+                "/non/existent/ca.pem",
+            )],
+            || {
+                let result = TonicExporterBuilder::default().build_span_exporter();
+                assert!(result
+                    .unwrap_err()
+                    .to_string()
+                    .contains("cannot load the TLS certificates: /non/existent/ca.pem"));
+            },
+        );
+    }
+
+    #[test]
+    #[cfg(all(not(feature = "tls"), feature = "trace"))]
+    fn test_certificates_require_tls() {
+        run_env_test(
+            vec![(crate::OTEL_EXPORTER_OTLP_CERTIFICATE, "/etc/ssl/ca.pem")],
+            || {
+                let result = TonicExporterBuilder::default().build_span_exporter();
+                assert!(result
+                    .unwrap_err()
+                    .to_string()
+                    .contains("the `tls` feature is required"));
+            },
+        );
+    }
+
+    #[test]
+    fn test_classify_status() {
+        use crate::exporter::retry::RetryErrorType;
+        use std::time::Duration;
+        use tonic::{Code, Status};
+
+        for code in [
+            Code::Cancelled,
+            Code::DeadlineExceeded,
+            Code::Aborted,
+            Code::OutOfRange,
+            Code::Unavailable,
+            Code::DataLoss,
+        ] {
+            assert_eq!(
+                super::classify_status(&Status::new(code, "")),
+                RetryErrorType::Retryable
+            );
+        }
+
+        for code in [
+            Code::InvalidArgument,
+            Code::Unauthenticated,
+            Code::PermissionDenied,
+            Code::Internal,
+            Code::ResourceExhausted,
+        ] {
+            assert_eq!(
+                super::classify_status(&Status::new(code, "")),
+                RetryErrorType::NonRetryable
+            );
+        }
+
+        // the delay requested by the server takes precedence, and makes
+        // RESOURCE_EXHAUSTED retryable
+        let delay = Duration::from_millis(1500);
+        assert_eq!(
+            super::classify_status(&super::status_with_retry_delay(
+                Code::Unavailable,
+                "slow down",
+                delay
+            )),
+            RetryErrorType::Throttled(delay)
+        );
+        assert_eq!(
+            super::classify_status(&super::status_with_retry_delay(
+                Code::ResourceExhausted,
+                "slow down",
+                delay
+            )),
+            RetryErrorType::Throttled(delay)
+        );
+        assert_eq!(
+            super::classify_status(&super::status_with_retry_delay(
+                Code::InvalidArgument,
+                "slow down",
+                delay
+            )),
+            RetryErrorType::NonRetryable
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use core::fmt;
+use std::time::Duration;
 
 use futures_core::future::BoxFuture;
 use opentelemetry::trace::TraceError;
@@ -8,14 +9,21 @@ use opentelemetry_proto::tonic::collector::trace::v1::{
 use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
 use tonic::{codegen::CompressionEncoding, service::Interceptor, transport::Channel, Request};
 
-use super::BoxInterceptor;
+use super::{classify_status, BoxInterceptor, TonicChannel};
+use crate::exporter::retry::{retry_with_backoff, RetryConfig};
+use crate::exporter::self_observability::ExporterMetrics;
 
 pub(crate) struct TonicTracesClient {
+    retry_config: RetryConfig,
+    timeout: Duration,
+    exporter_metrics: ExporterMetrics,
     inner: Option<ClientInner>,
 }
 
 struct ClientInner {
     client: TraceServiceClient<Channel>,
+    channel: TonicChannel,
+    compression: Option<CompressionEncoding>,
     interceptor: BoxInterceptor,
 }
 
@@ -27,30 +35,49 @@ impl fmt::Debug for TonicTracesClient {
 
 impl TonicTracesClient {
     pub(super) fn new(
-        channel: Channel,
+        channel: TonicChannel,
         interceptor: BoxInterceptor,
         compression: Option<CompressionEncoding>,
+        retry_config: RetryConfig,
+        timeout: Duration,
+        exporter_metrics: ExporterMetrics,
     ) -> Self {
-        let mut client = TraceServiceClient::new(channel);
-        if let Some(compression) = compression {
-            client = client
-                .send_compressed(compression)
-                .accept_compressed(compression);
-        }
+        let client = service_client(channel.channel(), compression);
 
         TonicTracesClient {
+            retry_config,
+            timeout,
+            exporter_metrics,
             inner: Some(ClientInner {
                 client,
+                channel,
+                compression,
                 interceptor,
             }),
         }
     }
 }
 
+fn service_client(
+    channel: Channel,
+    compression: Option<CompressionEncoding>,
+) -> TraceServiceClient<Channel> {
+    let client = TraceServiceClient::new(channel);
+    match compression {
+        Some(compression) => client
+            .send_compressed(compression)
+            .accept_compressed(compression),
+        None => client,
+    }
+}
+
 impl SpanExporter for TonicTracesClient {
     fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
-        let (mut client, metadata, extensions) = match &mut self.inner {
+        let (client, metadata, extensions) = match &mut self.inner {
             Some(inner) => {
+                if let Some(channel) = inner.channel.reconnect::<TraceError>() {
+                    inner.client = service_client(channel, inner.compression);
+                }
                 let (m, e, _) = match inner.interceptor.call(Request::new(())) {
                     Ok(res) => res.into_parts(),
                     Err(e) => {
@@ -66,17 +93,41 @@ impl SpanExporter for TonicTracesClient {
             }
         };
 
+        let retry_config = self.retry_config;
+        let timeout = self.timeout;
+        let exporter_metrics = self.exporter_metrics.clone();
         Box::pin(async move {
-            client
-                .export(Request::from_parts(
-                    metadata,
-                    extensions,
-                    ExportTraceServiceRequest {
-                        resource_spans: batch.into_iter().map(Into::into).collect(),
-                    },
-                ))
-                .await
-                .map_err(crate::Error::from)?;
+            let request = ExportTraceServiceRequest {
+                resource_spans: batch.into_iter().map(Into::into).collect(),
+            };
+            // extensions are not cloneable, only the first attempt carries them
+            let mut extensions = Some(extensions);
+
+            let response = retry_with_backoff(
+                retry_config,
+                timeout,
+                &exporter_metrics,
+                classify_status,
+                || {
+                    let mut client = client.clone();
+                    let request = Request::from_parts(
+                        metadata.clone(),
+                        extensions.take().unwrap_or_default(),
+                        request.clone(),
+                    );
+                    async move { client.export(request).await }
+                },
+            )
+            .await
+            .map_err(crate::Error::from)?;
+
+            if let Some(partial_success) = response.into_inner().partial_success {
+                exporter_metrics.partial_success::<TraceError>(
+                    "spans",
+                    partial_success.rejected_spans,
+                    partial_success.error_message,
+                );
+            }
 
             Ok(())
         })
@@ -8,9 +8,9 @@
 //! Prometheus, etc.) sending to multiple open-source or commercial back-ends.
 //!
 //! Currently, this crate only support sending tracing data or metrics in OTLP
-//! via grpc and http (in binary format). Supports for other format and protocol
-//! will be added in the future. The details of what's currently offering in this
-//! crate can be found in this doc.
+//! via grpc and http (in binary or json format). Supports for other format and
+//! protocol will be added in the future. The details of what's currently offering
+//! in this crate can be found in this doc.
 //!
 //! # Quickstart
 //!
@@ -89,21 +89,37 @@
 //!
 //! The following feature flags generate additional code and types:
 //! * `serialize`: Enables serialization support for type defined in this create via `serde`.
+//! * `testing`: Includes [`testing::MockCollector`], an in-process OTLP collector to test
+//!   the export of telemetry without running a collector.
 //!
 //! The following feature flags offer additional configurations on gRPC:
 //!
 //! For users uses `tonic` as grpc layer:
 //! * `grpc-tonic`: Use `tonic` as grpc layer. This is enabled by default.
 //! * `gzip-tonic`: Use gzip compression for `tonic` grpc layer.
-//! * `tls-tonic`: Enable TLS.
+//! * `zstd-tonic`: Use zstd compression for `tonic` grpc layer.
+//! * `tls-tonic`: Enable TLS. The certificates set in `OTEL_EXPORTER_OTLP_CERTIFICATE`,
+//!   `OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE` and `OTEL_EXPORTER_OTLP_CLIENT_KEY` are loaded,
+//!   and loaded again when the files change. Without it, setting them fails the build of
+//!   the exporters.
 //! * `tls-roots`: Adds system trust roots to rustls-based gRPC clients using the rustls-native-certs crate
 //!
 //! The following feature flags offer additional configurations on http:
 //!
 //! * `http-proto`: Use http as transport layer, protobuf as body format.
+//! * `http-json`: Use http as transport layer, JSON as body format. Select it with [`Protocol::HttpJson`]
+//!   or by setting `OTEL_EXPORTER_OTLP_PROTOCOL=http/json`.
+//! * `gzip-http`: Use gzip compression for the HTTP exporter.
+//! * `zstd-http`: Use zstd compression for the HTTP exporter.
 //! * `reqwest-blocking-client`: Use reqwest blocking http client.
 //! * `reqwest-client`: Use reqwest http client.
-//! * `reqwest-rustls`: Use reqwest with TLS.
+//! * `reqwest-rustls`: Use reqwest with TLS. The default client loads the certificates set in
+//!   the environment, like `tls-tonic`.
+//!
+//! The following feature flags offer other destinations:
+//!
+//! * `file`: Write the signals as [OTLP JSON lines](https://opentelemetry.io/docs/specs/otel/protocol/file-exporter/)
+//!   to stdout, any writer or a file rotated by size. See [`FileExporterBuilder`].
 //!
 //! # Kitchen Sink Full Configuration
 //!
@@ -161,7 +177,8 @@
 //!     let export_config = ExportConfig {
 //!         endpoint: "http://localhost:4317".to_string(),
 //!         timeout: Duration::from_secs(3),
-//!         protocol: Protocol::Grpc
+//!         protocol: Protocol::Grpc,
+//!         ..ExportConfig::default()
 //!     };
 //!
 //!     let meter = opentelemetry_otlp::new_pipeline()
@@ -214,39 +231,53 @@ mod logs;
 mod metric;
 #[cfg(feature = "trace")]
 mod span;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub use crate::exporter::Compression;
 pub use crate::exporter::ExportConfig;
+pub use crate::exporter::RetryConfig;
 #[cfg(feature = "trace")]
 pub use crate::span::{
-    OtlpTracePipeline, SpanExporter, SpanExporterBuilder, OTEL_EXPORTER_OTLP_TRACES_COMPRESSION,
-    OTEL_EXPORTER_OTLP_TRACES_ENDPOINT, OTEL_EXPORTER_OTLP_TRACES_HEADERS,
+    OtlpTracePipeline, SpanExporter, SpanExporterBuilder, OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE,
+    OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE, OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY,
+    OTEL_EXPORTER_OTLP_TRACES_COMPRESSION, OTEL_EXPORTER_OTLP_TRACES_ENDPOINT,
+    OTEL_EXPORTER_OTLP_TRACES_HEADERS, OTEL_EXPORTER_OTLP_TRACES_INSECURE,
     OTEL_EXPORTER_OTLP_TRACES_TIMEOUT,
 };
 
 #[cfg(feature = "metrics")]
 pub use crate::metric::{
     MetricsExporter, MetricsExporterBuilder, OtlpMetricPipeline,
-    OTEL_EXPORTER_OTLP_METRICS_COMPRESSION, OTEL_EXPORTER_OTLP_METRICS_ENDPOINT,
-    OTEL_EXPORTER_OTLP_METRICS_HEADERS, OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
+    OTEL_EXPORTER_OTLP_METRICS_CERTIFICATE, OTEL_EXPORTER_OTLP_METRICS_CLIENT_CERTIFICATE,
+    OTEL_EXPORTER_OTLP_METRICS_CLIENT_KEY, OTEL_EXPORTER_OTLP_METRICS_COMPRESSION,
+    OTEL_EXPORTER_OTLP_METRICS_ENDPOINT, OTEL_EXPORTER_OTLP_METRICS_HEADERS,
+    OTEL_EXPORTER_OTLP_METRICS_INSECURE, OTEL_EXPORTER_OTLP_METRICS_TIMEOUT,
 };
 
 #[cfg(feature = "logs")]
 pub use crate::logs::{
-    LogExporter, LogExporterBuilder, OtlpLogPipeline, OTEL_EXPORTER_OTLP_LOGS_COMPRESSION,
-    OTEL_EXPORTER_OTLP_LOGS_ENDPOINT, OTEL_EXPORTER_OTLP_LOGS_HEADERS,
+    LogExporter, LogExporterBuilder, OtlpLogPipeline, OTEL_EXPORTER_OTLP_LOGS_CERTIFICATE,
+    OTEL_EXPORTER_OTLP_LOGS_CLIENT_CERTIFICATE, OTEL_EXPORTER_OTLP_LOGS_CLIENT_KEY,
+    OTEL_EXPORTER_OTLP_LOGS_COMPRESSION, OTEL_EXPORTER_OTLP_LOGS_ENDPOINT,
+    OTEL_EXPORTER_OTLP_LOGS_HEADERS, OTEL_EXPORTER_OTLP_LOGS_INSECURE,
     OTEL_EXPORTER_OTLP_LOGS_TIMEOUT,
 };
 
 pub use crate::exporter::{
-    HasExportConfig, WithExportConfig, OTEL_EXPORTER_OTLP_COMPRESSION, OTEL_EXPORTER_OTLP_ENDPOINT,
-    OTEL_EXPORTER_OTLP_ENDPOINT_DEFAULT, OTEL_EXPORTER_OTLP_HEADERS, OTEL_EXPORTER_OTLP_PROTOCOL,
-    OTEL_EXPORTER_OTLP_PROTOCOL_DEFAULT, OTEL_EXPORTER_OTLP_TIMEOUT,
+    HasExportConfig, WithExportConfig, OTEL_EXPORTER_OTLP_CERTIFICATE,
+    OTEL_EXPORTER_OTLP_CLIENT_CERTIFICATE, OTEL_EXPORTER_OTLP_CLIENT_KEY,
+    OTEL_EXPORTER_OTLP_COMPRESSION, OTEL_EXPORTER_OTLP_ENDPOINT,
+    OTEL_EXPORTER_OTLP_ENDPOINT_DEFAULT, OTEL_EXPORTER_OTLP_HEADERS, OTEL_EXPORTER_OTLP_INSECURE,
+    OTEL_EXPORTER_OTLP_PROTOCOL, OTEL_EXPORTER_OTLP_PROTOCOL_DEFAULT, OTEL_EXPORTER_OTLP_TIMEOUT,
     OTEL_EXPORTER_OTLP_TIMEOUT_DEFAULT,
 };
 
 use opentelemetry_sdk::export::ExportError;
 
+#[cfg(feature = "file")]
+pub use crate::exporter::file::FileExporterBuilder;
+
 #[cfg(feature = "http-proto")]
 pub use crate::exporter::http::HttpExporterBuilder;
 
@@ -282,6 +313,15 @@ impl OtlpExporterPipeline {
     pub fn http(self) -> HttpExporterBuilder {
         HttpExporterBuilder::default()
     }
+
+    /// Write the signals as OTLP JSON lines, return a `FileExporterBuilder` to config the
+    /// destination and build the exporter.
+    ///
+    /// This exporter can be used in `tracing`, `metrics` and `logs` pipelines.
+    #[cfg(feature = "file")]
+    pub fn file(self) -> FileExporterBuilder {
+        FileExporterBuilder::default()
+    }
 }
 
 /// Create a new pipeline builder with the recommended configuration.
@@ -355,11 +395,52 @@ pub enum Error {
     #[error("prost encoding error {0}")]
     EncodeError(#[from] prost::EncodeError),
 
+    /// Serde json encode failed
+    #[cfg(any(feature = "http-json", feature = "file"))]
+    #[error("json encoding error {0}")]
+    JsonEncodeError(#[from] serde_json::Error),
+
+    /// Writing or compressing the signals failed.
+    #[cfg(any(feature = "file", feature = "gzip-http", feature = "zstd-http"))]
+    #[error("io error {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The exporter has been shut down.
+    #[cfg(feature = "file")]
+    #[error("exporter is already shut down")]
+    ShutDown,
+
     /// The lock in exporters has been poisoned.
-    #[cfg(feature = "metrics")]
+    #[cfg(any(feature = "metrics", feature = "file"))]
     #[error("the lock of the {0} has been poisoned")]
     PoisonedLock(&'static str),
 
+    /// The collector accepted the export, except `rejected` items.
+    ///
+    /// The export is successful, so this error is only reported to the global error
+    /// handler. It is reported as well when the collector accepted all the items with a
+    /// warning, in which case `rejected` is `0`.
+    #[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+    #[error("the collector rejected {rejected} {items}: {error_message}")]
+    PartialSuccess {
+        /// The kind of items, `spans`, `data points` or `log records`.
+        items: &'static str,
+        /// The number of rejected items.
+        rejected: i64,
+        /// Why the items have been rejected.
+        error_message: String,
+    },
+
+    /// The TLS certificates configured through the environment cannot be loaded.
+    #[cfg(any(feature = "grpc-tonic", feature = "http-proto"))]
+    #[error("cannot load the TLS certificates: {0}")]
+    InvalidCertificates(String),
+
+    /// Unsupported protocol, its feature is not enabled.
+    #[cfg(feature = "http-proto")]
+    #[error("unsupported protocol '{0}'")]
+    UnsupportedProtocol(String),
+
     /// Unsupported compression algorithm.
     #[error("unsupported compression algorithm '{0}'")]
     UnsupportedCompressionAlgorithm(String),
@@ -390,13 +471,17 @@ impl ExportError for Error {
 /// The communication protocol to use when exporting data.
 #[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum Protocol {
     /// GRPC protocol
     Grpc,
-    // TODO add support for other protocols
-    // HttpJson,
     /// HTTP protocol with binary protobuf
     HttpBinary,
+    /// HTTP protocol with JSON payload, following the
+    /// [OTLP/JSON encoding](https://opentelemetry.io/docs/specs/otlp/#json-protobuf-encoding).
+    ///
+    /// Requires the `http-json` feature, otherwise building the HTTP exporter fails.
+    HttpJson,
 }
 
 #[derive(Debug, Default)]
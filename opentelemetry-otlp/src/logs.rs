@@ -5,6 +5,8 @@
 #[cfg(feature = "grpc-tonic")]
 use crate::exporter::tonic::TonicExporterBuilder;
 
+#[cfg(feature = "file")]
+use crate::exporter::file::FileExporterBuilder;
 #[cfg(feature = "http-proto")]
 use crate::exporter::http::HttpExporterBuilder;
 
@@ -32,6 +34,17 @@ pub const OTEL_EXPORTER_OTLP_LOGS_TIMEOUT: &str = "OTEL_EXPORTER_OTLP_LOGS_TIMEO
 /// Example: `k1=v1,k2=v2`
 /// Note: this is only supported for HTTP.
 pub const OTEL_EXPORTER_OTLP_LOGS_HEADERS: &str = "OTEL_EXPORTER_OTLP_LOGS_HEADERS";
+/// Whether to connect without TLS to the gRPC endpoint of logs if it has no scheme,
+/// defaults to `false`.
+pub const OTEL_EXPORTER_OTLP_LOGS_INSECURE: &str = "OTEL_EXPORTER_OTLP_LOGS_INSECURE";
+/// Path of the PEM encoded certificates trusted to verify the collector's certificate
+/// when sending logs.
+pub const OTEL_EXPORTER_OTLP_LOGS_CERTIFICATE: &str = "OTEL_EXPORTER_OTLP_LOGS_CERTIFICATE";
+/// Path of the PEM encoded client certificate chain used for mTLS when sending logs.
+pub const OTEL_EXPORTER_OTLP_LOGS_CLIENT_CERTIFICATE: &str =
+    "OTEL_EXPORTER_OTLP_LOGS_CLIENT_CERTIFICATE";
+/// Path of the PEM encoded private key of the client certificate used when sending logs.
+pub const OTEL_EXPORTER_OTLP_LOGS_CLIENT_KEY: &str = "OTEL_EXPORTER_OTLP_LOGS_CLIENT_KEY";
 
 impl OtlpPipeline {
     /// Create a OTLP logging pipeline.
@@ -55,6 +68,9 @@ pub enum LogExporterBuilder {
     /// Http log exporter builder
     #[cfg(feature = "http-proto")]
     Http(HttpExporterBuilder),
+    /// File log exporter builder
+    #[cfg(feature = "file")]
+    File(FileExporterBuilder),
 }
 
 impl LogExporterBuilder {
@@ -65,6 +81,8 @@ impl LogExporterBuilder {
             LogExporterBuilder::Tonic(builder) => builder.build_log_exporter(),
             #[cfg(feature = "http-proto")]
             LogExporterBuilder::Http(builder) => builder.build_log_exporter(),
+            #[cfg(feature = "file")]
+            LogExporterBuilder::File(builder) => builder.build_log_exporter(),
         }
     }
 }
@@ -83,6 +101,13 @@ impl From<HttpExporterBuilder> for LogExporterBuilder {
     }
 }
 
+#[cfg(feature = "file")]
+impl From<FileExporterBuilder> for LogExporterBuilder {
+    fn from(exporter: FileExporterBuilder) -> Self {
+        LogExporterBuilder::File(exporter)
+    }
+}
+
 /// OTLP exporter that sends log data
 #[derive(Debug)]
 pub struct LogExporter {
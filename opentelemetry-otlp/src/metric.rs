@@ -26,6 +26,8 @@ use opentelemetry_sdk::{
 use std::fmt::{Debug, Formatter};
 use std::time;
 
+#[cfg(feature = "file")]
+use crate::exporter::file::FileExporterBuilder;
 #[cfg(feature = "http-proto")]
 use crate::exporter::http::HttpExporterBuilder;
 
@@ -42,6 +44,17 @@ pub const OTEL_EXPORTER_OTLP_METRICS_COMPRESSION: &str = "OTEL_EXPORTER_OTLP_MET
 /// Example: `k1=v1,k2=v2`
 /// Note: this is only supported for HTTP.
 pub const OTEL_EXPORTER_OTLP_METRICS_HEADERS: &str = "OTEL_EXPORTER_OTLP_METRICS_HEADERS";
+/// Whether to connect without TLS to the gRPC endpoint of metrics if it has no scheme,
+/// defaults to `false`.
+pub const OTEL_EXPORTER_OTLP_METRICS_INSECURE: &str = "OTEL_EXPORTER_OTLP_METRICS_INSECURE";
+/// Path of the PEM encoded certificates trusted to verify the collector's certificate
+/// when sending metrics.
+pub const OTEL_EXPORTER_OTLP_METRICS_CERTIFICATE: &str = "OTEL_EXPORTER_OTLP_METRICS_CERTIFICATE";
+/// Path of the PEM encoded client certificate chain used for mTLS when sending metrics.
+pub const OTEL_EXPORTER_OTLP_METRICS_CLIENT_CERTIFICATE: &str =
+    "OTEL_EXPORTER_OTLP_METRICS_CLIENT_CERTIFICATE";
+/// Path of the PEM encoded private key of the client certificate used when sending metrics.
+pub const OTEL_EXPORTER_OTLP_METRICS_CLIENT_KEY: &str = "OTEL_EXPORTER_OTLP_METRICS_CLIENT_KEY";
 impl OtlpPipeline {
     /// Create a OTLP metrics pipeline.
     pub fn metrics<RT>(self, rt: RT) -> OtlpMetricPipeline<RT, NoExporterConfig>
@@ -62,6 +75,7 @@ impl OtlpPipeline {
 
 /// OTLP metrics exporter builder.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 #[non_exhaustive]
 pub enum MetricsExporterBuilder {
     /// Tonic metrics exporter builder
@@ -70,10 +84,13 @@ pub enum MetricsExporterBuilder {
     /// Http metrics exporter builder
     #[cfg(feature = "http-proto")]
     Http(HttpExporterBuilder),
+    /// File metrics exporter builder
+    #[cfg(feature = "file")]
+    File(FileExporterBuilder),
 
     /// Missing exporter builder
     #[doc(hidden)]
-    #[cfg(not(any(feature = "http-proto", feature = "grpc-tonic")))]
+    #[cfg(not(any(feature = "http-proto", feature = "grpc-tonic", feature = "file")))]
     Unconfigured,
 }
 
@@ -93,12 +110,16 @@ impl MetricsExporterBuilder {
             MetricsExporterBuilder::Http(builder) => {
                 builder.build_metrics_exporter(aggregation_selector, temporality_selector)
             }
-            #[cfg(not(any(feature = "http-proto", feature = "grpc-tonic")))]
+            #[cfg(feature = "file")]
+            MetricsExporterBuilder::File(builder) => {
+                builder.build_metrics_exporter(aggregation_selector, temporality_selector)
+            }
+            #[cfg(not(any(feature = "http-proto", feature = "grpc-tonic", feature = "file")))]
             MetricsExporterBuilder::Unconfigured => {
                 drop(temporality_selector);
                 drop(aggregation_selector);
                 Err(opentelemetry::metrics::MetricsError::Other(
-                    "no configured metrics exporter, enable `http-proto` or `grpc-tonic` feature to configure a metrics exporter".into(),
+                    "no configured metrics exporter, enable `http-proto`, `grpc-tonic` or `file` feature to configure a metrics exporter".into(),
                 ))
             }
         }
@@ -119,6 +140,13 @@ impl From<HttpExporterBuilder> for MetricsExporterBuilder {
     }
 }
 
+#[cfg(feature = "file")]
+impl From<FileExporterBuilder> for MetricsExporterBuilder {
+    fn from(exporter: FileExporterBuilder) -> Self {
+        MetricsExporterBuilder::File(exporter)
+    }
+}
+
 /// Pipeline to build OTLP metrics exporter
 ///
 /// Note that currently the OTLP metrics exporter only supports tonic as it's grpc layer and tokio as
@@ -19,6 +19,8 @@ use sdk::runtime::RuntimeChannel;
 #[cfg(feature = "grpc-tonic")]
 use crate::exporter::tonic::TonicExporterBuilder;
 
+#[cfg(feature = "file")]
+use crate::exporter::file::FileExporterBuilder;
 #[cfg(feature = "http-proto")]
 use crate::exporter::http::HttpExporterBuilder;
 
@@ -37,6 +39,17 @@ pub const OTEL_EXPORTER_OTLP_TRACES_COMPRESSION: &str = "OTEL_EXPORTER_OTLP_TRAC
 /// Example: `k1=v1,k2=v2`
 /// Note: this is only supported for HTTP.
 pub const OTEL_EXPORTER_OTLP_TRACES_HEADERS: &str = "OTEL_EXPORTER_OTLP_TRACES_HEADERS";
+/// Whether to connect without TLS to the gRPC endpoint of spans if it has no scheme,
+/// defaults to `false`.
+pub const OTEL_EXPORTER_OTLP_TRACES_INSECURE: &str = "OTEL_EXPORTER_OTLP_TRACES_INSECURE";
+/// Path of the PEM encoded certificates trusted to verify the collector's certificate
+/// when sending spans.
+pub const OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE: &str = "OTEL_EXPORTER_OTLP_TRACES_CERTIFICATE";
+/// Path of the PEM encoded client certificate chain used for mTLS when sending spans.
+pub const OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE: &str =
+    "OTEL_EXPORTER_OTLP_TRACES_CLIENT_CERTIFICATE";
+/// Path of the PEM encoded private key of the client certificate used when sending spans.
+pub const OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY: &str = "OTEL_EXPORTER_OTLP_TRACES_CLIENT_KEY";
 
 impl OtlpPipeline {
     /// Create a OTLP tracing pipeline.
@@ -189,6 +202,9 @@ pub enum SpanExporterBuilder {
     /// Http span exporter builder
     #[cfg(feature = "http-proto")]
     Http(HttpExporterBuilder),
+    /// File span exporter builder
+    #[cfg(feature = "file")]
+    File(FileExporterBuilder),
 }
 
 impl SpanExporterBuilder {
@@ -199,6 +215,8 @@ impl SpanExporterBuilder {
             SpanExporterBuilder::Tonic(builder) => builder.build_span_exporter(),
             #[cfg(feature = "http-proto")]
             SpanExporterBuilder::Http(builder) => builder.build_span_exporter(),
+            #[cfg(feature = "file")]
+            SpanExporterBuilder::File(builder) => builder.build_span_exporter(),
         }
     }
 }
@@ -217,6 +235,13 @@ impl From<HttpExporterBuilder> for SpanExporterBuilder {
     }
 }
 
+#[cfg(feature = "file")]
+impl From<FileExporterBuilder> for SpanExporterBuilder {
+    fn from(exporter: FileExporterBuilder) -> Self {
+        SpanExporterBuilder::File(exporter)
+    }
+}
+
 /// OTLP exporter that sends tracing information
 #[derive(Debug)]
 pub struct SpanExporter(Box<dyn opentelemetry_sdk::export::trace::SpanExporter>);
@@ -0,0 +1,809 @@
+//! An in-process OTLP collector to test the export of telemetry end to end.
+//!
+//! [`MockCollector`] receives traces, metrics and logs over gRPC and HTTP/protobuf on
+//! ephemeral ports of the loopback interface, records the requests, and answers with
+//! the responses scripted by the test, allowing to check retries and partial successes
+//! without running a collector.
+//!
+//! ## Examples
+//!
+//! ```no_run
+//! use opentelemetry::trace::{Tracer, TracerProvider as _};
+//! use opentelemetry_otlp::testing::{MockCollector, MockResponse, Signal};
+//! use opentelemetry_otlp::WithExportConfig;
+//! use opentelemetry_sdk::trace::TracerProvider;
+//! use std::time::Duration;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let collector = MockCollector::start().await?;
+//! // the first export is rejected, and retried by the exporter
+//! collector.respond_with(Signal::Traces, MockResponse::unavailable());
+//!
+//! let exporter = opentelemetry_otlp::new_exporter()
+//!     .tonic()
+//!     .with_endpoint(collector.grpc_endpoint())
+//!     .build_span_exporter()?;
+//! let provider = TracerProvider::builder()
+//!     .with_simple_exporter(exporter)
+//!     .build();
+//! provider.tracer("test").in_span("operation", |_cx| {});
+//!
+//! assert!(
+//!     collector
+//!         .wait_until(Duration::from_secs(5), |collector| !collector.spans().is_empty())
+//!         .await
+//! );
+//! assert_eq!(collector.spans()[0].name, "operation");
+//! assert_eq!(collector.attempts(Signal::Traces), 2);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::fmt;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use hyper::{Body, Request, Response};
+use opentelemetry_proto::tonic::collector::{
+    logs::v1::{
+        logs_service_server::{LogsService, LogsServiceServer},
+        ExportLogsPartialSuccess, ExportLogsServiceRequest, ExportLogsServiceResponse,
+    },
+    metrics::v1::{
+        metrics_service_server::{MetricsService, MetricsServiceServer},
+        ExportMetricsPartialSuccess, ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+    },
+    trace::v1::{
+        trace_service_server::{TraceService, TraceServiceServer},
+        ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
+    },
+};
+use opentelemetry_proto::tonic::{logs::v1::LogRecord, metrics::v1::Metric, trace::v1::Span};
+use prost::Message;
+use tokio::sync::{watch, Notify};
+use tonic::transport::server::TcpIncoming;
+
+use crate::exporter::tonic::{status_with_retry_delay, RpcStatus};
+
+/// A telemetry signal received by the [`MockCollector`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    /// Spans, exported to `TraceService` or `/v1/traces`.
+    Traces,
+    /// Metrics, exported to `MetricsService` or `/v1/metrics`.
+    Metrics,
+    /// Logs, exported to `LogsService` or `/v1/logs`.
+    Logs,
+}
+
+/// The transport an export request has been received with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transport {
+    /// gRPC
+    Grpc,
+    /// HTTP, with a protobuf or JSON body.
+    Http,
+}
+
+/// The response of the [`MockCollector`] to an export request.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum MockResponse {
+    /// Accept the whole request.
+    Success,
+    /// Accept the request, reporting that `rejected` items have been rejected.
+    PartialSuccess {
+        /// The number of rejected spans, data points or log records.
+        rejected: i64,
+        /// Why the items have been rejected.
+        error_message: String,
+    },
+    /// Reject the request with the gRPC `code`, or the corresponding HTTP status.
+    Failure {
+        /// The gRPC status code.
+        code: tonic::Code,
+        /// The error message.
+        message: String,
+        /// The delay requested before retrying, sent as a gRPC `RetryInfo` or the HTTP
+        /// `Retry-After` header (rounded up to the second).
+        retry_after: Option<Duration>,
+    },
+}
+
+impl MockResponse {
+    /// Accept the request, reporting that `rejected` items have been rejected.
+    pub fn partial_success(rejected: i64, error_message: impl Into<String>) -> Self {
+        MockResponse::PartialSuccess {
+            rejected,
+            error_message: error_message.into(),
+        }
+    }
+
+    /// Reject the request with the gRPC `code`, or the corresponding HTTP status.
+    pub fn failure(code: tonic::Code, message: impl Into<String>) -> Self {
+        MockResponse::Failure {
+            code,
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+
+    /// Reject the request as `UNAVAILABLE`, or `503 Service Unavailable`, which the
+    /// exporters retry.
+    pub fn unavailable() -> Self {
+        MockResponse::failure(tonic::Code::Unavailable, "unavailable")
+    }
+
+    /// Reject the request as `RESOURCE_EXHAUSTED`, or `429 Too Many Requests`,
+    /// requesting the exporter to retry after `delay`.
+    pub fn throttled(delay: Duration) -> Self {
+        MockResponse::Failure {
+            code: tonic::Code::ResourceExhausted,
+            message: "throttled".to_string(),
+            retry_after: Some(delay),
+        }
+    }
+
+    fn is_accepted(&self) -> bool {
+        !matches!(self, MockResponse::Failure { .. })
+    }
+}
+
+/// An export request received by the [`MockCollector`].
+#[derive(Clone, Debug)]
+pub struct Received<T> {
+    /// The transport of the request.
+    pub transport: Transport,
+    /// The HTTP headers, or gRPC metadata, of the request.
+    pub headers: HeaderMap,
+    /// The request.
+    pub request: T,
+}
+
+/// An in-process OTLP collector, receiving traces, metrics and logs over gRPC and
+/// HTTP/protobuf.
+///
+/// The collector accepts every request by default, the responses to the next requests
+/// of a signal can be scripted with [`respond_with`]. Only the accepted requests are
+/// recorded, while [`attempts`] counts all the requests.
+///
+/// The servers are stopped when the collector is dropped.
+///
+/// [`respond_with`]: MockCollector::respond_with
+/// [`attempts`]: MockCollector::attempts
+pub struct MockCollector {
+    grpc_addr: SocketAddr,
+    http_addr: SocketAddr,
+    state: Arc<State>,
+    shutdown: watch::Sender<bool>,
+}
+
+impl fmt::Debug for MockCollector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockCollector")
+            .field("grpc_addr", &self.grpc_addr)
+            .field("http_addr", &self.http_addr)
+            .finish()
+    }
+}
+
+impl MockCollector {
+    /// Start the gRPC and HTTP servers of a collector on ephemeral ports of the
+    /// loopback interface.
+    ///
+    /// This must be called from the context of a tokio runtime, which runs the servers.
+    pub async fn start() -> std::io::Result<Self> {
+        let state = Arc::new(State::default());
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        let grpc_listener =
+            tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).await?;
+        let grpc_addr = grpc_listener.local_addr()?;
+        let incoming = TcpIncoming::from_listener(grpc_listener, true, None)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        let service = GrpcService(Arc::clone(&state));
+        let traces = TraceServiceServer::new(service.clone());
+        let metrics = MetricsServiceServer::new(service.clone());
+        let logs = LogsServiceServer::new(service);
+        #[cfg(feature = "gzip-tonic")]
+        let (traces, metrics, logs) = {
+            use tonic::codec::CompressionEncoding::Gzip;
+            (
+                traces.accept_compressed(Gzip),
+                metrics.accept_compressed(Gzip),
+                logs.accept_compressed(Gzip),
+            )
+        };
+        #[cfg(feature = "zstd-tonic")]
+        let (traces, metrics, logs) = {
+            use tonic::codec::CompressionEncoding::Zstd;
+            (
+                traces.accept_compressed(Zstd),
+                metrics.accept_compressed(Zstd),
+                logs.accept_compressed(Zstd),
+            )
+        };
+        let router = tonic::transport::Server::builder()
+            .add_service(traces)
+            .add_service(metrics)
+            .add_service(logs);
+        tokio::spawn(router.serve_with_incoming_shutdown(incoming, stopped(shutdown_rx.clone())));
+
+        let http_listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))?;
+        let http_addr = http_listener.local_addr()?;
+        let http_state = Arc::clone(&state);
+        let make_service = hyper::service::make_service_fn(move |_| {
+            let state = Arc::clone(&http_state);
+            async move {
+                Ok::<_, Infallible>(hyper::service::service_fn(move |request| {
+                    handle_http(Arc::clone(&state), request)
+                }))
+            }
+        });
+        let server = hyper::Server::from_tcp(http_listener)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?
+            .serve(make_service)
+            .with_graceful_shutdown(stopped(shutdown_rx));
+        tokio::spawn(server);
+
+        Ok(MockCollector {
+            grpc_addr,
+            http_addr,
+            state,
+            shutdown,
+        })
+    }
+
+    /// The endpoint of the gRPC server, to configure the tonic exporters with.
+    pub fn grpc_endpoint(&self) -> String {
+        format!("http://{}", self.grpc_addr)
+    }
+
+    /// The base endpoint of the HTTP server, to configure the HTTP exporters with.
+    pub fn http_endpoint(&self) -> String {
+        format!("http://{}", self.http_addr)
+    }
+
+    /// Answer the next request of `signal` with `response`.
+    ///
+    /// The responses are used in the order they are scripted, after which the requests
+    /// are accepted again.
+    pub fn respond_with(&self, signal: Signal, response: MockResponse) {
+        self.state.with_signal(signal, |state| {
+            state.responses.push_back(response);
+        });
+    }
+
+    /// Wait for `delay` before answering the requests of `signal`.
+    pub fn set_delay(&self, signal: Signal, delay: Duration) {
+        self.state.with_signal(signal, |state| *state.delay = delay);
+    }
+
+    /// The number of requests of `signal` received, including the rejected ones.
+    pub fn attempts(&self, signal: Signal) -> usize {
+        self.state.with_signal(signal, |state| *state.attempts)
+    }
+
+    /// The accepted trace export requests.
+    pub fn trace_requests(&self) -> Vec<Received<ExportTraceServiceRequest>> {
+        self.state.traces.lock().unwrap().requests.clone()
+    }
+
+    /// The accepted metrics export requests.
+    pub fn metrics_requests(&self) -> Vec<Received<ExportMetricsServiceRequest>> {
+        self.state.metrics.lock().unwrap().requests.clone()
+    }
+
+    /// The accepted logs export requests.
+    pub fn logs_requests(&self) -> Vec<Received<ExportLogsServiceRequest>> {
+        self.state.logs.lock().unwrap().requests.clone()
+    }
+
+    /// The spans of all the accepted requests.
+    pub fn spans(&self) -> Vec<Span> {
+        self.trace_requests()
+            .into_iter()
+            .flat_map(|received| received.request.resource_spans)
+            .flat_map(|resource_spans| resource_spans.scope_spans)
+            .flat_map(|scope_spans| scope_spans.spans)
+            .collect()
+    }
+
+    /// The metrics of all the accepted requests.
+    pub fn metrics(&self) -> Vec<Metric> {
+        self.metrics_requests()
+            .into_iter()
+            .flat_map(|received| received.request.resource_metrics)
+            .flat_map(|resource_metrics| resource_metrics.scope_metrics)
+            .flat_map(|scope_metrics| scope_metrics.metrics)
+            .collect()
+    }
+
+    /// The log records of all the accepted requests.
+    pub fn log_records(&self) -> Vec<LogRecord> {
+        self.logs_requests()
+            .into_iter()
+            .flat_map(|received| received.request.resource_logs)
+            .flat_map(|resource_logs| resource_logs.scope_logs)
+            .flat_map(|scope_logs| scope_logs.log_records)
+            .collect()
+    }
+
+    /// Wait until `condition` holds, checking it again after each request.
+    ///
+    /// Returns `false` if it doesn't hold after `timeout`.
+    pub async fn wait_until<F>(&self, timeout: Duration, condition: F) -> bool
+    where
+        F: Fn(&MockCollector) -> bool,
+    {
+        let wait = async {
+            loop {
+                let received = self.state.received.notified();
+                if condition(self) {
+                    return;
+                }
+                received.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+
+    /// Forget the received requests and the scripted responses and delays.
+    pub fn reset(&self) {
+        for signal in [Signal::Traces, Signal::Metrics, Signal::Logs] {
+            self.state.with_signal(signal, |state| {
+                *state.attempts = 0;
+                state.responses.clear();
+                *state.delay = Duration::ZERO;
+            });
+        }
+        self.state.traces.lock().unwrap().requests.clear();
+        self.state.metrics.lock().unwrap().requests.clear();
+        self.state.logs.lock().unwrap().requests.clear();
+    }
+}
+
+impl Drop for MockCollector {
+    fn drop(&mut self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+async fn stopped(mut shutdown: watch::Receiver<bool>) {
+    while !*shutdown.borrow() {
+        if shutdown.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    traces: Mutex<SignalState<ExportTraceServiceRequest>>,
+    metrics: Mutex<SignalState<ExportMetricsServiceRequest>>,
+    logs: Mutex<SignalState<ExportLogsServiceRequest>>,
+    received: Notify,
+}
+
+struct SignalState<T> {
+    requests: Vec<Received<T>>,
+    attempts: usize,
+    responses: VecDeque<MockResponse>,
+    delay: Duration,
+}
+
+impl<T> Default for SignalState<T> {
+    fn default() -> Self {
+        SignalState {
+            requests: Vec::new(),
+            attempts: 0,
+            responses: VecDeque::new(),
+            delay: Duration::ZERO,
+        }
+    }
+}
+
+/// The settings of a signal, independent of its request type.
+struct Behavior<'a> {
+    attempts: &'a mut usize,
+    responses: &'a mut VecDeque<MockResponse>,
+    delay: &'a mut Duration,
+}
+
+impl State {
+    fn with_signal<R>(&self, signal: Signal, f: impl FnOnce(Behavior<'_>) -> R) -> R {
+        match signal {
+            Signal::Traces => f(Behavior::from(&mut *self.traces.lock().unwrap())),
+            Signal::Metrics => f(Behavior::from(&mut *self.metrics.lock().unwrap())),
+            Signal::Logs => f(Behavior::from(&mut *self.logs.lock().unwrap())),
+        }
+    }
+
+    /// Record `request` if the scripted response accepts it, and return the response.
+    async fn receive<T>(
+        &self,
+        signal: &Mutex<SignalState<T>>,
+        transport: Transport,
+        headers: HeaderMap,
+        request: T,
+    ) -> MockResponse {
+        let (response, delay) = {
+            let mut signal = signal.lock().unwrap();
+            signal.attempts += 1;
+            (
+                signal
+                    .responses
+                    .pop_front()
+                    .unwrap_or(MockResponse::Success),
+                signal.delay,
+            )
+        };
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+
+        if response.is_accepted() {
+            signal.lock().unwrap().requests.push(Received {
+                transport,
+                headers,
+                request,
+            });
+        }
+        self.received.notify_waiters();
+
+        response
+    }
+}
+
+impl<'a, T> From<&'a mut SignalState<T>> for Behavior<'a> {
+    fn from(state: &'a mut SignalState<T>) -> Self {
+        Behavior {
+            attempts: &mut state.attempts,
+            responses: &mut state.responses,
+            delay: &mut state.delay,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct GrpcService(Arc<State>);
+
+#[allow(clippy::result_large_err)] // the error is returned to tonic as is
+fn grpc_response<R: Default>(
+    response: MockResponse,
+    partial_success: impl FnOnce(i64, String) -> R,
+) -> Result<tonic::Response<R>, tonic::Status> {
+    match response {
+        MockResponse::Success => Ok(tonic::Response::new(R::default())),
+        MockResponse::PartialSuccess {
+            rejected,
+            error_message,
+        } => Ok(tonic::Response::new(partial_success(
+            rejected,
+            error_message,
+        ))),
+        MockResponse::Failure {
+            code,
+            message,
+            retry_after: Some(delay),
+        } => Err(status_with_retry_delay(code, &message, delay)),
+        MockResponse::Failure { code, message, .. } => Err(tonic::Status::new(code, message)),
+    }
+}
+
+#[tonic::async_trait]
+impl TraceService for GrpcService {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportTraceServiceRequest>,
+    ) -> Result<tonic::Response<ExportTraceServiceResponse>, tonic::Status> {
+        let (metadata, _, request) = request.into_parts();
+        let response = self
+            .0
+            .receive(
+                &self.0.traces,
+                Transport::Grpc,
+                metadata.into_headers(),
+                request,
+            )
+            .await;
+        grpc_response(response, trace_partial_success)
+    }
+}
+
+#[tonic::async_trait]
+impl MetricsService for GrpcService {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportMetricsServiceRequest>,
+    ) -> Result<tonic::Response<ExportMetricsServiceResponse>, tonic::Status> {
+        let (metadata, _, request) = request.into_parts();
+        let response = self
+            .0
+            .receive(
+                &self.0.metrics,
+                Transport::Grpc,
+                metadata.into_headers(),
+                request,
+            )
+            .await;
+        grpc_response(response, metrics_partial_success)
+    }
+}
+
+#[tonic::async_trait]
+impl LogsService for GrpcService {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportLogsServiceRequest>,
+    ) -> Result<tonic::Response<ExportLogsServiceResponse>, tonic::Status> {
+        let (metadata, _, request) = request.into_parts();
+        let response = self
+            .0
+            .receive(
+                &self.0.logs,
+                Transport::Grpc,
+                metadata.into_headers(),
+                request,
+            )
+            .await;
+        grpc_response(response, logs_partial_success)
+    }
+}
+
+fn trace_partial_success(rejected: i64, error_message: String) -> ExportTraceServiceResponse {
+    ExportTraceServiceResponse {
+        partial_success: Some(ExportTracePartialSuccess {
+            rejected_spans: rejected,
+            error_message,
+        }),
+    }
+}
+
+fn metrics_partial_success(rejected: i64, error_message: String) -> ExportMetricsServiceResponse {
+    ExportMetricsServiceResponse {
+        partial_success: Some(ExportMetricsPartialSuccess {
+            rejected_data_points: rejected,
+            error_message,
+        }),
+    }
+}
+
+fn logs_partial_success(rejected: i64, error_message: String) -> ExportLogsServiceResponse {
+    ExportLogsServiceResponse {
+        partial_success: Some(ExportLogsPartialSuccess {
+            rejected_log_records: rejected,
+            error_message,
+        }),
+    }
+}
+
+/// The encoding of the body of an HTTP request, used for its response as well.
+#[derive(Clone, Copy)]
+enum Encoding {
+    Protobuf,
+    #[cfg(feature = "http-json")]
+    Json,
+}
+
+const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+#[cfg(feature = "http-json")]
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+async fn handle_http(
+    state: Arc<State>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::POST {
+        return Ok(status_response(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    let encoding = match request.headers().get(header::CONTENT_TYPE) {
+        Some(content_type) if content_type == PROTOBUF_CONTENT_TYPE => Encoding::Protobuf,
+        #[cfg(feature = "http-json")]
+        Some(content_type) if content_type == JSON_CONTENT_TYPE => Encoding::Json,
+        _ => return Ok(status_response(StatusCode::UNSUPPORTED_MEDIA_TYPE)),
+    };
+
+    let path = request.uri().path().to_string();
+    let (parts, body) = request.into_parts();
+    let body = match hyper::body::to_bytes(body).await {
+        Ok(body) => body,
+        Err(_) => return Ok(status_response(StatusCode::BAD_REQUEST)),
+    };
+    let body = match decompress(parts.headers.get(header::CONTENT_ENCODING), body) {
+        Ok(body) => body,
+        Err(status) => return Ok(status_response(status)),
+    };
+
+    let response = match path.as_str() {
+        "/v1/traces" => {
+            receive_http(
+                &state,
+                &state.traces,
+                parts.headers,
+                &body,
+                encoding,
+                trace_partial_success,
+            )
+            .await
+        }
+        "/v1/metrics" => {
+            receive_http(
+                &state,
+                &state.metrics,
+                parts.headers,
+                &body,
+                encoding,
+                metrics_partial_success,
+            )
+            .await
+        }
+        "/v1/logs" => {
+            receive_http(
+                &state,
+                &state.logs,
+                parts.headers,
+                &body,
+                encoding,
+                logs_partial_success,
+            )
+            .await
+        }
+        _ => status_response(StatusCode::NOT_FOUND),
+    };
+
+    Ok(response)
+}
+
+/// Decompress the body of a request encoded with `content_encoding`, if its feature is
+/// enabled.
+fn decompress(
+    content_encoding: Option<&HeaderValue>,
+    body: hyper::body::Bytes,
+) -> Result<hyper::body::Bytes, StatusCode> {
+    match content_encoding.map(HeaderValue::as_bytes) {
+        None | Some(b"identity") => Ok(body),
+        #[cfg(feature = "gzip-http")]
+        Some(b"gzip") => {
+            use std::io::Read;
+
+            let mut decompressed = Vec::new();
+            flate2::read::GzDecoder::new(&body[..])
+                .read_to_end(&mut decompressed)
+                .map_err(|_| StatusCode::BAD_REQUEST)?;
+            Ok(decompressed.into())
+        }
+        #[cfg(feature = "zstd-http")]
+        Some(b"zstd") => zstd::decode_all(&body[..])
+            .map(Into::into)
+            .map_err(|_| StatusCode::BAD_REQUEST),
+        Some(_) => Err(StatusCode::UNSUPPORTED_MEDIA_TYPE),
+    }
+}
+
+#[cfg(not(feature = "http-json"))]
+trait HttpMessage: Message + Default {}
+
+#[cfg(not(feature = "http-json"))]
+impl<T: Message + Default> HttpMessage for T {}
+
+#[cfg(feature = "http-json")]
+trait HttpMessage: Message + Default + serde::Serialize + serde::de::DeserializeOwned {}
+
+#[cfg(feature = "http-json")]
+impl<T: Message + Default + serde::Serialize + serde::de::DeserializeOwned> HttpMessage for T {}
+
+fn decode<T: HttpMessage>(body: &[u8], encoding: Encoding) -> Option<T> {
+    match encoding {
+        Encoding::Protobuf => T::decode(body).ok(),
+        #[cfg(feature = "http-json")]
+        Encoding::Json => serde_json::from_slice(body).ok(),
+    }
+}
+
+fn encode<T: HttpMessage>(message: &T, encoding: Encoding) -> Response<Body> {
+    let (content_type, body) = match encoding {
+        Encoding::Protobuf => (PROTOBUF_CONTENT_TYPE, message.encode_to_vec()),
+        #[cfg(feature = "http-json")]
+        Encoding::Json => (
+            JSON_CONTENT_TYPE,
+            serde_json::to_vec(message).unwrap_or_default(),
+        ),
+    };
+
+    let mut response = Response::new(Body::from(body));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response
+}
+
+async fn receive_http<T, R>(
+    state: &State,
+    signal: &Mutex<SignalState<T>>,
+    headers: HeaderMap,
+    body: &[u8],
+    encoding: Encoding,
+    partial_success: impl FnOnce(i64, String) -> R,
+) -> Response<Body>
+where
+    T: HttpMessage,
+    R: HttpMessage,
+{
+    let request = match decode::<T>(body, encoding) {
+        Some(request) => request,
+        None => return status_response(StatusCode::BAD_REQUEST),
+    };
+
+    match state
+        .receive(signal, Transport::Http, headers, request)
+        .await
+    {
+        MockResponse::Success => encode(&R::default(), encoding),
+        MockResponse::PartialSuccess {
+            rejected,
+            error_message,
+        } => encode(&partial_success(rejected, error_message), encoding),
+        MockResponse::Failure {
+            code,
+            message,
+            retry_after,
+        } => {
+            let status = RpcStatus {
+                code: code as i32,
+                message,
+                details: Vec::new(),
+            };
+            let mut response = Response::new(Body::from(status.encode_to_vec()));
+            *response.status_mut() = http_status(code);
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(PROTOBUF_CONTENT_TYPE),
+            );
+            if let Some(delay) = retry_after {
+                let seconds = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+            }
+            response
+        }
+    }
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+/// The HTTP status of a gRPC status code, following the mapping of the
+/// [gRPC gateway](https://github.com/grpc-ecosystem/grpc-gateway/blob/main/runtime/errors.go).
+fn http_status(code: tonic::Code) -> StatusCode {
+    use tonic::Code;
+
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::Cancelled => StatusCode::REQUEST_TIMEOUT,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
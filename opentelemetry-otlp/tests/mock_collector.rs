@@ -0,0 +1,318 @@
+use std::time::Duration;
+
+use opentelemetry::logs::{AnyValue, LogRecord, Logger, LoggerProvider as _};
+use opentelemetry::metrics::MeterProvider as _;
+use opentelemetry::trace::{Tracer, TracerProvider as _};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::testing::{MockCollector, MockResponse, Signal, Transport};
+use opentelemetry_otlp::{RetryConfig, WithExportConfig};
+use opentelemetry_sdk::logs::LoggerProvider;
+use opentelemetry_sdk::metrics::reader::{DefaultAggregationSelector, DefaultTemporalitySelector};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::runtime;
+use opentelemetry_sdk::trace::TracerProvider;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn no_delay_retries() -> RetryConfig {
+    let mut retry_config = RetryConfig::default();
+    retry_config.max_attempts = 3;
+    retry_config.initial_backoff = Duration::from_millis(1);
+    retry_config.max_backoff = Duration::from_millis(1);
+    retry_config
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_traces_over_grpc() {
+    let collector = MockCollector::start().await.unwrap();
+
+    let mut metadata = tonic::metadata::MetadataMap::new();
+    metadata.insert("x-header-key", "header-value".parse().unwrap());
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(collector.grpc_endpoint())
+        .with_metadata(metadata)
+        .build_span_exporter()
+        .unwrap();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter)
+        .build();
+    provider.tracer("test").in_span("operation", |_cx| {});
+
+    assert!(
+        collector
+            .wait_until(TIMEOUT, |collector| !collector.spans().is_empty())
+            .await
+    );
+    let requests = collector.trace_requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].transport, Transport::Grpc);
+    assert_eq!(requests[0].headers["x-header-key"], "header-value");
+    assert_eq!(collector.spans()[0].name, "operation");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retry_failed_exports() {
+    let collector = MockCollector::start().await.unwrap();
+    collector.respond_with(Signal::Traces, MockResponse::unavailable());
+    collector.respond_with(
+        Signal::Traces,
+        MockResponse::throttled(Duration::from_millis(10)),
+    );
+
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(collector.grpc_endpoint())
+        .with_retry_config(no_delay_retries())
+        .build_span_exporter()
+        .unwrap();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter)
+        .build();
+    provider.tracer("test").in_span("operation", |_cx| {});
+
+    assert!(
+        collector
+            .wait_until(TIMEOUT, |collector| !collector.spans().is_empty())
+            .await
+    );
+    assert_eq!(collector.attempts(Signal::Traces), 3);
+    assert_eq!(collector.trace_requests().len(), 1);
+
+    // requests failing after all the attempts are not recorded
+    collector.reset();
+    for _ in 0..3 {
+        collector.respond_with(Signal::Traces, MockResponse::unavailable());
+    }
+    provider.tracer("test").in_span("dropped", |_cx| {});
+
+    assert!(
+        collector
+            .wait_until(TIMEOUT, |collector| collector.attempts(Signal::Traces) == 3)
+            .await
+    );
+    assert!(collector.spans().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_metrics_and_logs_over_grpc() {
+    let collector = MockCollector::start().await.unwrap();
+
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(collector.grpc_endpoint())
+        .build_metrics_exporter(
+            Box::new(DefaultAggregationSelector::new()),
+            Box::new(DefaultTemporalitySelector::new()),
+        )
+        .unwrap();
+    let meter_provider = SdkMeterProvider::builder()
+        .with_reader(PeriodicReader::builder(exporter, runtime::Tokio).build())
+        .build();
+    let counter = meter_provider.meter("test").u64_counter("requests").init();
+    counter.add(1, &[KeyValue::new("method", "GET")]);
+    meter_provider.force_flush().unwrap();
+
+    // the periodic reader may have exported before the flush as well
+    assert!(collector.metrics().iter().all(|m| m.name == "requests"));
+    assert!(!collector.metrics().is_empty());
+
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(collector.grpc_endpoint())
+        .build_log_exporter()
+        .unwrap();
+    let logger_provider = LoggerProvider::builder()
+        .with_simple_exporter(exporter)
+        .build();
+    logger_provider.logger("test").emit(
+        LogRecord::builder()
+            .with_body(AnyValue::from("hello"))
+            .build(),
+    );
+
+    assert!(
+        collector
+            .wait_until(TIMEOUT, |collector| !collector.log_records().is_empty())
+            .await
+    );
+    assert_eq!(collector.logs_requests()[0].transport, Transport::Grpc);
+}
+
+#[cfg(all(feature = "http-proto", feature = "reqwest-client"))]
+#[tokio::test(flavor = "multi_thread")]
+async fn export_traces_over_http() {
+    let collector = MockCollector::start().await.unwrap();
+    collector.respond_with(Signal::Traces, MockResponse::unavailable());
+    collector.set_delay(Signal::Traces, Duration::from_millis(10));
+
+    let endpoint = collector.http_endpoint();
+    // the default client may be a blocking one, which can't be dropped in an async context
+    let exporter = tokio::task::spawn_blocking(move || {
+        opentelemetry_otlp::new_exporter()
+            .http()
+            .with_http_client(reqwest::Client::new())
+            .with_endpoint(endpoint)
+            .with_retry_config(no_delay_retries())
+            .build_span_exporter()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .build();
+    provider.tracer("test").in_span("operation", |_cx| {});
+    provider.force_flush();
+
+    assert!(
+        collector
+            .wait_until(TIMEOUT, |collector| !collector.spans().is_empty())
+            .await
+    );
+    assert_eq!(collector.attempts(Signal::Traces), 2);
+    assert_eq!(collector.trace_requests()[0].transport, Transport::Http);
+    assert_eq!(collector.spans()[0].name, "operation");
+}
+
+#[cfg(any(
+    feature = "zstd-tonic",
+    all(
+        feature = "http-proto",
+        feature = "reqwest-client",
+        feature = "gzip-http"
+    )
+))]
+#[tokio::test(flavor = "multi_thread")]
+async fn export_compressed_traces() {
+    use opentelemetry_otlp::Compression;
+
+    let collector = MockCollector::start().await.unwrap();
+
+    #[cfg(feature = "zstd-tonic")]
+    {
+        let exporter = opentelemetry_otlp::new_exporter()
+            .tonic()
+            .with_endpoint(collector.grpc_endpoint())
+            .with_compression(Compression::Zstd)
+            .build_span_exporter()
+            .unwrap();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter)
+            .build();
+        provider.tracer("test").in_span("grpc", |_cx| {});
+
+        assert!(
+            collector
+                .wait_until(TIMEOUT, |collector| !collector.spans().is_empty())
+                .await
+        );
+        assert_eq!(
+            collector.trace_requests()[0].headers["grpc-encoding"],
+            "zstd"
+        );
+        collector.reset();
+    }
+
+    #[cfg(all(
+        feature = "http-proto",
+        feature = "reqwest-client",
+        feature = "gzip-http"
+    ))]
+    {
+        let endpoint = collector.http_endpoint();
+        // the default client may be a blocking one, which can't be dropped in an async context
+        let exporter = tokio::task::spawn_blocking(move || {
+            opentelemetry_otlp::new_exporter()
+                .http()
+                .with_http_client(reqwest::Client::new())
+                .with_endpoint(endpoint)
+                .with_compression(Compression::Gzip)
+                .build_span_exporter()
+                .unwrap()
+        })
+        .await
+        .unwrap();
+        let provider = TracerProvider::builder()
+            .with_batch_exporter(exporter, runtime::Tokio)
+            .build();
+        provider.tracer("test").in_span("http", |_cx| {});
+        provider.force_flush();
+
+        assert!(
+            collector
+                .wait_until(TIMEOUT, |collector| !collector.spans().is_empty())
+                .await
+        );
+        assert_eq!(
+            collector.trace_requests()[0].headers["content-encoding"],
+            "gzip"
+        );
+        assert_eq!(collector.spans()[0].name, "http");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn report_partial_successes() {
+    use opentelemetry_sdk::metrics::data::Sum;
+    use opentelemetry_sdk::testing::metrics::InMemoryMetricsExporter;
+    use opentelemetry_sdk::AttributeSet;
+    use std::sync::{Arc, Mutex};
+
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let handler_errors = Arc::clone(&errors);
+    opentelemetry::global::set_error_handler(move |err| {
+        handler_errors.lock().unwrap().push(err.to_string())
+    })
+    .unwrap();
+
+    let metrics_exporter = InMemoryMetricsExporter::default();
+    let meter_provider = SdkMeterProvider::builder()
+        .with_reader(PeriodicReader::builder(metrics_exporter.clone(), runtime::Tokio).build())
+        .build();
+
+    let collector = MockCollector::start().await.unwrap();
+    collector.respond_with(
+        Signal::Traces,
+        MockResponse::partial_success(2, "spans too large"),
+    );
+
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(collector.grpc_endpoint())
+        .with_self_observability(&meter_provider)
+        .build_span_exporter()
+        .unwrap();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter)
+        .build();
+    provider.tracer("test").in_span("operation", |_cx| {});
+
+    assert!(
+        collector
+            .wait_until(TIMEOUT, |collector| !collector.spans().is_empty())
+            .await
+    );
+    // the partial success is recorded, and not retried
+    assert_eq!(collector.attempts(Signal::Traces), 1);
+    assert!(errors
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|err| err.contains("the collector rejected 2 spans: spans too large")));
+
+    meter_provider.force_flush().unwrap();
+    let resource_metrics = metrics_exporter.get_finished_metrics().unwrap();
+    let rejected = resource_metrics
+        .iter()
+        .flat_map(|metrics| &metrics.scope_metrics)
+        .flat_map(|scope| &scope.metrics)
+        .find(|metric| metric.name == "otel.sdk.exporter.rejected")
+        .expect("rejected items are counted");
+    let sum = rejected.data.as_any().downcast_ref::<Sum<u64>>().unwrap();
+    assert_eq!(sum.data_points[0].value, 2);
+    assert_eq!(
+        sum.data_points[0].attributes,
+        AttributeSet::from(&[KeyValue::new("signal", "traces")][..])
+    );
+}
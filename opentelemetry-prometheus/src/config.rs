@@ -7,7 +7,7 @@ use opentelemetry_sdk::metrics::{
 };
 use std::sync::{Arc, Mutex};
 
-use crate::{Collector, PrometheusExporter, ResourceSelector};
+use crate::{Collector, Converter, PrometheusExporter, ResourceSelector};
 
 /// [PrometheusExporter] configuration options
 #[derive(Default)]
@@ -18,6 +18,7 @@ pub struct ExporterBuilder {
     without_counter_suffixes: bool,
     namespace: Option<String>,
     disable_scope_info: bool,
+    without_native_histograms: bool,
     reader: ManualReaderBuilder,
     resource_selector: ResourceSelector,
 }
@@ -31,6 +32,7 @@ impl fmt::Debug for ExporterBuilder {
             .field("without_counter_suffixes", &self.without_counter_suffixes)
             .field("namespace", &self.namespace)
             .field("disable_scope_info", &self.disable_scope_info)
+            .field("without_native_histograms", &self.without_native_histograms)
             .finish()
     }
 }
@@ -80,6 +82,18 @@ impl ExporterBuilder {
         self
     }
 
+    /// Configures the exporter to convert exponential histograms to classic
+    /// histograms instead of native histograms.
+    ///
+    /// By default, exponential histograms are exported as native histograms,
+    /// whose buckets are only exposed by the protobuf format. With this option
+    /// set, they are exported with a bucket per exponential bucket, including
+    /// the zero bucket, which all formats expose.
+    pub fn without_native_histograms(mut self) -> Self {
+        self.without_native_histograms = true;
+        self
+    }
+
     /// Configures the exporter to prefix metrics with the given namespace.
     ///
     /// Metrics such as `target_info` and `otel_scope_info` are not prefixed since
@@ -143,15 +157,18 @@ impl ExporterBuilder {
 
         let collector = Collector {
             reader: Arc::clone(&reader),
-            disable_target_info: self.disable_target_info,
-            without_units: self.without_units,
-            without_counter_suffixes: self.without_counter_suffixes,
-            disable_scope_info: self.disable_scope_info,
-            create_target_info_once: OnceCell::new(),
-            namespace: self.namespace,
-            inner: Mutex::new(Default::default()),
-            resource_selector: self.resource_selector,
-            resource_labels_once: OnceCell::new(),
+            converter: Converter {
+                disable_target_info: self.disable_target_info,
+                without_units: self.without_units,
+                without_counter_suffixes: self.without_counter_suffixes,
+                disable_scope_info: self.disable_scope_info,
+                without_native_histograms: self.without_native_histograms,
+                create_target_info_once: OnceCell::new(),
+                namespace: self.namespace,
+                inner: Mutex::new(Default::default()),
+                resource_selector: self.resource_selector,
+                resource_labels_once: OnceCell::new(),
+            },
         };
 
         let registry = self.registry.unwrap_or_default();
@@ -132,11 +132,22 @@ const SCOPE_INFO_KEYS: [&str; 2] = ["otel_scope_name", "otel_scope_version"];
 const COUNTER_SUFFIX: &str = "_total";
 
 mod config;
+mod native_histogram;
+mod openmetrics;
+#[cfg(feature = "remote-write")]
+mod remote_write;
 mod resource_selector;
+#[cfg(feature = "server")]
+mod server;
 mod utils;
 
 pub use config::ExporterBuilder;
+pub use openmetrics::{OpenMetricsEncoder, OPENMETRICS_FORMAT};
+#[cfg(feature = "remote-write")]
+pub use remote_write::{RemoteWriteExporter, RemoteWriteExporterBuilder};
 pub use resource_selector::ResourceSelector;
+#[cfg(feature = "server")]
+pub use server::MetricsService;
 
 /// Creates a builder to configure a [PrometheusExporter]
 pub fn exporter() -> ExporterBuilder {
@@ -168,6 +179,10 @@ impl MetricReader for PrometheusExporter {
         self.reader.register_pipeline(pipeline)
     }
 
+    fn cardinality_limit(&self, kind: InstrumentKind) -> Option<usize> {
+        self.reader.cardinality_limit(kind)
+    }
+
     fn collect(&self, rm: &mut ResourceMetrics) -> Result<()> {
         self.reader.collect(rm)
     }
@@ -183,10 +198,16 @@ impl MetricReader for PrometheusExporter {
 
 struct Collector {
     reader: Arc<ManualReader>,
+    converter: Converter,
+}
+
+/// Converts OpenTelemetry metrics to Prometheus metric families.
+struct Converter {
     disable_target_info: bool,
     without_units: bool,
     without_counter_suffixes: bool,
     disable_scope_info: bool,
+    without_native_histograms: bool,
     create_target_info_once: OnceCell<MetricFamily>,
     resource_labels_once: OnceCell<Vec<LabelPair>>,
     namespace: Option<String>,
@@ -209,6 +230,13 @@ static HISTOGRAM_TYPES: Lazy<[TypeId; 3]> = Lazy::new(|| {
         TypeId::of::<data::Histogram<f64>>(),
     ]
 });
+static EXPONENTIAL_HISTOGRAM_TYPES: Lazy<[TypeId; 3]> = Lazy::new(|| {
+    [
+        TypeId::of::<data::ExponentialHistogram<i64>>(),
+        TypeId::of::<data::ExponentialHistogram<u64>>(),
+        TypeId::of::<data::ExponentialHistogram<f64>>(),
+    ]
+});
 static SUM_TYPES: Lazy<[TypeId; 3]> = Lazy::new(|| {
     [
         TypeId::of::<data::Sum<i64>>(),
@@ -224,14 +252,14 @@ static GAUGE_TYPES: Lazy<[TypeId; 3]> = Lazy::new(|| {
     ]
 });
 
-impl Collector {
+impl Converter {
     fn metric_type_and_name(&self, m: &data::Metric) -> Option<(MetricType, Cow<'static, str>)> {
         let mut name = self.get_name(m);
 
         let data = m.data.as_any();
         let type_id = data.type_id();
 
-        if HISTOGRAM_TYPES.contains(&type_id) {
+        if HISTOGRAM_TYPES.contains(&type_id) || EXPONENTIAL_HISTOGRAM_TYPES.contains(&type_id) {
             Some((MetricType::HISTOGRAM, name))
         } else if GAUGE_TYPES.contains(&type_id) {
             Some((MetricType::GAUGE, name))
@@ -259,6 +287,44 @@ impl Collector {
         }
     }
 
+    fn add_exponential_histogram_metric<T: Numeric>(
+        &self,
+        res: &mut Vec<MetricFamily>,
+        histogram: &data::ExponentialHistogram<T>,
+        description: String,
+        extra: &[LabelPair],
+        name: Cow<'static, str>,
+    ) {
+        for dp in &histogram.data_points {
+            let kvs = get_attrs(&mut dp.attributes.iter(), extra);
+
+            let mut h = prometheus::proto::Histogram::default();
+            h.set_sample_sum(dp.sum.as_f64());
+            h.set_sample_count(dp.count as u64);
+            if self.without_native_histograms {
+                h.set_bucket(protobuf::RepeatedField::from_vec(
+                    native_histogram::classic_buckets(dp),
+                ));
+            } else if let Err(err) = native_histogram::set_native_buckets(&mut h, dp) {
+                global::handle_error(err);
+                continue;
+            }
+
+            openmetrics::set_histogram_extras(&mut h, dp.start_time, Vec::new());
+
+            let mut pm = prometheus::proto::Metric::default();
+            pm.set_label(protobuf::RepeatedField::from_vec(kvs));
+            pm.set_histogram(h);
+
+            let mut mf = prometheus::proto::MetricFamily::default();
+            mf.set_name(name.to_string());
+            mf.set_help(description.clone());
+            mf.set_field_type(prometheus::proto::MetricType::HISTOGRAM);
+            mf.set_metric(protobuf::RepeatedField::from_vec(vec![pm]));
+            res.push(mf);
+        }
+    }
+
     fn get_name(&self, m: &data::Metric) -> Cow<'static, str> {
         let name = utils::sanitize_name(&m.name);
         let unit_suffixes = if self.without_units {
@@ -281,14 +347,6 @@ impl prometheus::core::Collector for Collector {
     }
 
     fn collect(&self) -> Vec<MetricFamily> {
-        let mut inner = match self.inner.lock() {
-            Ok(guard) => guard,
-            Err(err) => {
-                global::handle_error(err);
-                return Vec::new();
-            }
-        };
-
         let mut metrics = ResourceMetrics {
             resource: Resource::empty(),
             scope_metrics: vec![],
@@ -297,6 +355,21 @@ impl prometheus::core::Collector for Collector {
             global::handle_error(err);
             return vec![];
         }
+
+        self.converter.convert(&metrics)
+    }
+}
+
+impl Converter {
+    fn convert(&self, metrics: &ResourceMetrics) -> Vec<MetricFamily> {
+        let mut inner = match self.inner.lock() {
+            Ok(guard) => guard,
+            Err(err) => {
+                global::handle_error(err);
+                return Vec::new();
+            }
+        };
+
         let mut res = Vec::with_capacity(metrics.scope_metrics.len() + 1);
 
         let target_info = self.create_target_info_once.get_or_init(|| {
@@ -312,7 +385,7 @@ impl prometheus::core::Collector for Collector {
             .resource_labels_once
             .get_or_init(|| self.resource_selector.select(&metrics.resource));
 
-        for scope_metrics in metrics.scope_metrics {
+        for scope_metrics in &metrics.scope_metrics {
             let scope_labels = if !self.disable_scope_info {
                 if !scope_metrics.scope.attributes.is_empty() {
                     let scope_info = inner
@@ -343,8 +416,8 @@ impl prometheus::core::Collector for Collector {
                 Vec::new()
             };
 
-            for metrics in scope_metrics.metrics {
-                let (metric_type, name) = match self.metric_type_and_name(&metrics) {
+            for metrics in &scope_metrics.metrics {
+                let (metric_type, name) = match self.metric_type_and_name(metrics) {
                     Some((metric_type, name)) => (metric_type, name),
                     _ => continue,
                 };
@@ -355,7 +428,7 @@ impl prometheus::core::Collector for Collector {
                     continue;
                 }
 
-                let description = help.unwrap_or_else(|| metrics.description.into());
+                let description = help.unwrap_or_else(|| metrics.description.to_string());
                 let data = metrics.data.as_any();
 
                 if let Some(hist) = data.downcast_ref::<data::Histogram<i64>>() {
@@ -364,6 +437,30 @@ impl prometheus::core::Collector for Collector {
                     add_histogram_metric(&mut res, hist, description, &scope_labels, name);
                 } else if let Some(hist) = data.downcast_ref::<data::Histogram<f64>>() {
                     add_histogram_metric(&mut res, hist, description, &scope_labels, name);
+                } else if let Some(hist) = data.downcast_ref::<data::ExponentialHistogram<i64>>() {
+                    self.add_exponential_histogram_metric(
+                        &mut res,
+                        hist,
+                        description,
+                        &scope_labels,
+                        name,
+                    );
+                } else if let Some(hist) = data.downcast_ref::<data::ExponentialHistogram<u64>>() {
+                    self.add_exponential_histogram_metric(
+                        &mut res,
+                        hist,
+                        description,
+                        &scope_labels,
+                        name,
+                    );
+                } else if let Some(hist) = data.downcast_ref::<data::ExponentialHistogram<f64>>() {
+                    self.add_exponential_histogram_metric(
+                        &mut res,
+                        hist,
+                        description,
+                        &scope_labels,
+                        name,
+                    );
                 } else if let Some(sum) = data.downcast_ref::<data::Sum<u64>>() {
                     add_sum_metric(&mut res, sum, description, &scope_labels, name);
                 } else if let Some(sum) = data.downcast_ref::<data::Sum<i64>>() {
@@ -450,7 +547,8 @@ fn add_histogram_metric<T: Numeric>(
     extra: &[LabelPair],
     name: Cow<'static, str>,
 ) {
-    // Consider supporting exemplars when `prometheus` crate has the feature
+    // Exemplars can't be carried by the `prometheus` crate data model, they are
+    // added as unknown protobuf fields.
     // See: https://github.com/tikv/rust-prometheus/issues/393
 
     for dp in &histogram.data_points {
@@ -473,6 +571,11 @@ fn add_histogram_metric<T: Numeric>(
         h.set_sample_sum(dp.sum.as_f64());
         h.set_sample_count(dp.count);
         h.set_bucket(protobuf::RepeatedField::from_vec(bucket));
+        openmetrics::set_histogram_extras(
+            &mut h,
+            dp.start_time,
+            openmetrics::Exemplar::by_bucket(&dp.exemplars, &dp.bounds),
+        );
         let mut pm = prometheus::proto::Metric::default();
         pm.set_label(protobuf::RepeatedField::from_vec(kvs));
         pm.set_histogram(h);
@@ -501,13 +604,17 @@ fn add_sum_metric<T: Numeric>(
 
     for dp in &sum.data_points {
         let kvs = get_attrs(&mut dp.attributes.iter(), extra);
-
         let mut pm = prometheus::proto::Metric::default();
         pm.set_label(protobuf::RepeatedField::from_vec(kvs));
 
         if sum.is_monotonic {
             let mut c = prometheus::proto::Counter::default();
             c.set_value(dp.value.as_f64());
+            openmetrics::set_counter_extras(
+                &mut c,
+                dp.start_time,
+                openmetrics::Exemplar::last(&dp.exemplars),
+            );
             pm.set_counter(c);
         } else {
             let mut g = prometheus::proto::Gauge::default();
@@ -0,0 +1,245 @@
+//! Conversion of exponential histograms to Prometheus native histograms.
+//!
+//! The `prometheus` crate data model predates native histograms, so their
+//! fields are added to the histograms as unknown protobuf fields, which are
+//! kept by the registries and written by the [ProtobufEncoder]. The text
+//! formats only expose the count and sum of native histograms.
+//!
+//! [ProtobufEncoder]: prometheus::ProtobufEncoder
+use opentelemetry::metrics::{MetricsError, Result};
+use opentelemetry_sdk::metrics::data::{ExponentialBucket, ExponentialHistogramDataPoint};
+use prometheus::proto::{Bucket, Histogram};
+use protobuf::Message;
+
+use crate::Numeric;
+
+// The field numbers of `io.prometheus.client.Histogram`.
+const SCHEMA_FIELD: u32 = 5;
+const ZERO_THRESHOLD_FIELD: u32 = 6;
+const ZERO_COUNT_FIELD: u32 = 7;
+const NEGATIVE_SPAN_FIELD: u32 = 9;
+const NEGATIVE_DELTA_FIELD: u32 = 10;
+const POSITIVE_SPAN_FIELD: u32 = 12;
+const POSITIVE_DELTA_FIELD: u32 = 13;
+
+/// The lowest and highest schemas supported by Prometheus.
+const MIN_SCHEMA: i8 = -4;
+const MAX_SCHEMA: i8 = 8;
+
+/// Up to this number of empty buckets are encoded as zero deltas instead of
+/// starting a new span.
+const MAX_EMPTY_BUCKETS_IN_SPAN: i32 = 2;
+
+/// The buckets of one side of a native histogram.
+#[derive(Debug, Default, PartialEq)]
+struct NativeBuckets {
+    /// The offset and length of each span of consecutive buckets.
+    spans: Vec<(i32, u32)>,
+    /// The count of the first bucket, then the difference with the previous
+    /// bucket.
+    deltas: Vec<i64>,
+}
+
+impl NativeBuckets {
+    /// Converts the buckets of an exponential histogram, downscaled by
+    /// `downscale`.
+    fn new(bucket: &ExponentialBucket, downscale: u32) -> Self {
+        // The exponential histogram bucket `i` is `(base^i, base^(i+1)]` while
+        // the native histogram bucket `i` is `(base^(i-1), base^i]`.
+        let mut buckets: Vec<(i32, u64)> = Vec::with_capacity(bucket.counts.len());
+        for (i, count) in bucket.counts.iter().enumerate() {
+            let index = ((bucket.offset + i as i32) >> downscale) + 1;
+            match buckets.last_mut() {
+                Some((last, last_count)) if *last == index => *last_count += count,
+                _ => buckets.push((index, *count)),
+            }
+        }
+
+        let mut native = NativeBuckets::default();
+        let mut previous: Option<(i32, u64)> = None;
+        for (index, count) in buckets.into_iter().filter(|(_, count)| *count > 0) {
+            let (previous_index, mut previous_count) = previous.unwrap_or((index, 0));
+            let gap = index - previous_index - 1;
+            match native.spans.last_mut() {
+                Some((_, length)) if gap <= MAX_EMPTY_BUCKETS_IN_SPAN => {
+                    for _ in 0..gap {
+                        native.deltas.push(-(previous_count as i64));
+                        previous_count = 0;
+                    }
+                    *length += gap as u32 + 1;
+                }
+                // The offset of the first span is the index of its first bucket,
+                // the one of the next spans the gap with the previous span.
+                Some(_) => native.spans.push((gap, 1)),
+                None => native.spans.push((index, 1)),
+            }
+            native.deltas.push(count as i64 - previous_count as i64);
+            previous = Some((index, count));
+        }
+        native
+    }
+
+    fn write_to(&self, histogram: &mut Histogram, span_field: u32, delta_field: u32) {
+        let fields = histogram.mut_unknown_fields();
+        for (offset, length) in &self.spans {
+            let mut span = Vec::new();
+            write_varint(&mut span, 1 << 3);
+            write_varint(&mut span, zigzag(*offset as i64));
+            write_varint(&mut span, 2 << 3);
+            write_varint(&mut span, *length as u64);
+            fields.add_length_delimited(span_field, span);
+        }
+        if !self.deltas.is_empty() {
+            let mut deltas = Vec::new();
+            for delta in &self.deltas {
+                write_varint(&mut deltas, zigzag(*delta));
+            }
+            fields.add_length_delimited(delta_field, deltas);
+        }
+    }
+}
+
+/// Sets the native histogram fields of `histogram` from the data point of an
+/// exponential histogram.
+pub(crate) fn set_native_buckets<T: Numeric>(
+    histogram: &mut Histogram,
+    dp: &ExponentialHistogramDataPoint<T>,
+) -> Result<()> {
+    if dp.scale < MIN_SCHEMA {
+        return Err(MetricsError::Other(format!(
+            "exponential histograms with a scale below {MIN_SCHEMA} can't be exported as native histograms, got {}",
+            dp.scale
+        )));
+    }
+    let downscale = dp.scale.saturating_sub(MAX_SCHEMA).max(0) as u32;
+    let schema = dp.scale.min(MAX_SCHEMA);
+
+    let positive = NativeBuckets::new(&dp.positive_bucket, downscale);
+    let negative = NativeBuckets::new(&dp.negative_bucket, downscale);
+
+    let fields = histogram.mut_unknown_fields();
+    fields.add_varint(SCHEMA_FIELD, zigzag(schema as i64));
+    fields.add_fixed64(ZERO_THRESHOLD_FIELD, dp.zero_threshold.to_bits());
+    fields.add_varint(ZERO_COUNT_FIELD, dp.zero_count);
+    positive.write_to(histogram, POSITIVE_SPAN_FIELD, POSITIVE_DELTA_FIELD);
+    negative.write_to(histogram, NEGATIVE_SPAN_FIELD, NEGATIVE_DELTA_FIELD);
+
+    // An empty span tells native histograms without any bucket apart from
+    // classic histograms.
+    if positive.spans.is_empty() && negative.spans.is_empty() && dp.zero_threshold == 0.0 {
+        NativeBuckets {
+            spans: vec![(0, 0)],
+            deltas: Vec::new(),
+        }
+        .write_to(histogram, POSITIVE_SPAN_FIELD, POSITIVE_DELTA_FIELD);
+    }
+
+    Ok(())
+}
+
+/// The classic buckets of an exponential histogram: a bucket per exponential
+/// bucket, and a bucket for the zero bucket.
+pub(crate) fn classic_buckets<T: Numeric>(dp: &ExponentialHistogramDataPoint<T>) -> Vec<Bucket> {
+    let base = 2f64.powf(2f64.powi(-(dp.scale as i32)));
+    let bound = |index: i32| base.powi(index);
+
+    let mut buckets =
+        Vec::with_capacity(dp.negative_bucket.counts.len() + 1 + dp.positive_bucket.counts.len());
+    let mut cumulative_count = 0;
+    let mut push = |upper_bound: f64, count: u64| {
+        cumulative_count += count;
+        let mut bucket = Bucket::default();
+        bucket.set_upper_bound(upper_bound);
+        bucket.set_cumulative_count(cumulative_count);
+        buckets.push(bucket);
+    };
+
+    // The negative bucket `i` is `[-base^(i+1), -base^i)`.
+    let negative = &dp.negative_bucket;
+    for (i, count) in negative.counts.iter().enumerate().rev() {
+        push(-bound(negative.offset + i as i32), *count);
+    }
+    push(dp.zero_threshold, dp.zero_count);
+    // The positive bucket `i` is `(base^i, base^(i+1)]`.
+    let positive = &dp.positive_bucket;
+    for (i, count) in positive.counts.iter().enumerate() {
+        push(bound(positive.offset + i as i32 + 1), *count);
+    }
+
+    buckets
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(offset: i32, counts: Vec<u64>) -> ExponentialBucket {
+        ExponentialBucket { offset, counts }
+    }
+
+    #[test]
+    fn test_native_buckets() {
+        for (name, bucket, downscale, spans, deltas) in [
+            ("empty", bucket(0, vec![]), 0, vec![], vec![]),
+            (
+                "contiguous",
+                bucket(-2, vec![1, 3, 2]),
+                0,
+                vec![(-1, 3)],
+                vec![1, 2, -1],
+            ),
+            (
+                "small gaps are filled",
+                bucket(0, vec![1, 0, 0, 2]),
+                0,
+                vec![(1, 4)],
+                vec![1, -1, 0, 2],
+            ),
+            (
+                "large gaps start a new span",
+                bucket(0, vec![0, 1, 0, 0, 0, 2, 0]),
+                0,
+                vec![(2, 1), (3, 1)],
+                vec![1, 1],
+            ),
+            (
+                "downscaled",
+                bucket(-3, vec![1, 1, 1, 1]),
+                1,
+                vec![(-1, 3)],
+                vec![1, 1, -1],
+            ),
+        ] {
+            let native = NativeBuckets::new(&bucket, downscale);
+            assert_eq!(native, NativeBuckets { spans, deltas }, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_varint() {
+        for (value, expected) in [
+            (0, vec![0]),
+            (1, vec![1]),
+            (300, vec![0xac, 0x02]),
+            (zigzag(-1), vec![1]),
+            (zigzag(-2), vec![3]),
+            (zigzag(2), vec![4]),
+        ] {
+            let mut buffer = Vec::new();
+            write_varint(&mut buffer, value);
+            assert_eq!(buffer, expected, "{value}");
+        }
+    }
+}
@@ -0,0 +1,624 @@
+//! Encoding of the metrics in the [OpenMetrics] text format.
+//!
+//! The `prometheus` crate data model has no place for the creation time of the
+//! series or their exemplars, so the collectors of the [PrometheusExporter]s
+//! add them to the counters, histograms and buckets as unknown protobuf
+//! fields, with the field numbers of the Prometheus data model. They are kept
+//! by the registries, written by the [ProtobufEncoder] and read by the
+//! [OpenMetricsEncoder].
+//!
+//! [OpenMetrics]: https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md
+//! [PrometheusExporter]: crate::PrometheusExporter
+//! [ProtobufEncoder]: prometheus::ProtobufEncoder
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::metrics::data;
+use prometheus::{
+    proto::{Bucket, Counter, Histogram, LabelPair, Metric, MetricFamily, MetricType},
+    Encoder, Registry,
+};
+use protobuf::{
+    well_known_types::{Empty, Timestamp},
+    Message, UnknownFields,
+};
+use std::{
+    io::{self, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{utils, Numeric};
+
+/// The content type of the OpenMetrics text format.
+pub const OPENMETRICS_FORMAT: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+// The combined length of the label names and values of an exemplar must not
+// exceed 128 characters.
+const MAX_EXEMPLAR_LABELS_LENGTH: usize = 128;
+
+// The field numbers of `io.prometheus.client.Counter`.
+const COUNTER_EXEMPLAR_FIELD: u32 = 2;
+const COUNTER_CREATED_TIMESTAMP_FIELD: u32 = 3;
+// The field number of `io.prometheus.client.Bucket`.
+const BUCKET_EXEMPLAR_FIELD: u32 = 3;
+// The field number of `io.prometheus.client.Histogram`.
+const HISTOGRAM_CREATED_TIMESTAMP_FIELD: u32 = 15;
+// The field numbers of `io.prometheus.client.Exemplar`.
+const EXEMPLAR_LABEL_FIELD: u32 = 1;
+const EXEMPLAR_VALUE_FIELD: u32 = 2;
+const EXEMPLAR_TIMESTAMP_FIELD: u32 = 3;
+
+/// An exemplar, in the OpenMetrics data model.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Exemplar {
+    labels: Vec<(String, String)>,
+    value: f64,
+    timestamp: SystemTime,
+}
+
+impl Exemplar {
+    pub(crate) fn new<T: Numeric>(exemplar: &data::Exemplar<T>) -> Self {
+        let mut labels = Vec::new();
+        let mut length = 0;
+        if exemplar.trace_id != [0; 16] {
+            labels.push(("trace_id".to_string(), hex(&exemplar.trace_id)));
+            labels.push(("span_id".to_string(), hex(&exemplar.span_id)));
+            length += "trace_id".len() + 32 + "span_id".len() + 16;
+        }
+        for KeyValue { key, value } in &exemplar.filtered_attributes {
+            let name = utils::sanitize_prom_kv(key.as_str());
+            let value = value.to_string();
+            length += name.chars().count() + value.chars().count();
+            if length > MAX_EXEMPLAR_LABELS_LENGTH {
+                break;
+            }
+            labels.push((name, value));
+        }
+
+        Exemplar {
+            labels,
+            value: exemplar.value.as_f64(),
+            timestamp: exemplar.time,
+        }
+    }
+
+    /// The last exemplar of `exemplars`.
+    pub(crate) fn last<T: Numeric>(exemplars: &[data::Exemplar<T>]) -> Option<Self> {
+        exemplars
+            .iter()
+            .max_by_key(|exemplar| exemplar.time)
+            .map(Exemplar::new)
+    }
+
+    /// The last exemplar of each bucket of a histogram with the given `bounds`,
+    /// the last one being the `+Inf` bucket.
+    pub(crate) fn by_bucket<T: Numeric>(
+        exemplars: &[data::Exemplar<T>],
+        bounds: &[f64],
+    ) -> Vec<Option<Self>> {
+        let mut buckets: Vec<Option<&data::Exemplar<T>>> = vec![None; bounds.len() + 1];
+        for exemplar in exemplars {
+            let value = exemplar.value.as_f64();
+            let bucket = bounds
+                .iter()
+                .position(|bound| value <= *bound)
+                .unwrap_or(bounds.len());
+            match buckets[bucket] {
+                Some(last) if last.time > exemplar.time => {}
+                _ => buckets[bucket] = Some(exemplar),
+            }
+        }
+        buckets
+            .into_iter()
+            .map(|exemplar| exemplar.map(Exemplar::new))
+            .collect()
+    }
+
+    /// Encodes the exemplar as an `io.prometheus.client.Exemplar` message.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut message = Empty::new();
+        let fields = message.mut_unknown_fields();
+        for (name, value) in &self.labels {
+            let mut label = LabelPair::default();
+            label.set_name(name.clone());
+            label.set_value(value.clone());
+            fields.add_length_delimited(EXEMPLAR_LABEL_FIELD, to_bytes(&label));
+        }
+        fields.add_fixed64(EXEMPLAR_VALUE_FIELD, self.value.to_bits());
+        fields.add_length_delimited(
+            EXEMPLAR_TIMESTAMP_FIELD,
+            to_bytes(&timestamp(self.timestamp)),
+        );
+        to_bytes(&message)
+    }
+
+    /// Decodes an `io.prometheus.client.Exemplar` message.
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let message = Empty::parse_from_bytes(bytes).ok()?;
+        let fields = message.get_unknown_fields();
+        let labels = fields
+            .get(EXEMPLAR_LABEL_FIELD)
+            .map(|values| {
+                values
+                    .length_delimited
+                    .iter()
+                    .filter_map(|label| LabelPair::parse_from_bytes(label).ok())
+                    .map(|label| (label.get_name().to_string(), label.get_value().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Exemplar {
+            labels,
+            value: f64::from_bits(*fields.get(EXEMPLAR_VALUE_FIELD)?.fixed64.last()?),
+            timestamp: get_timestamp(fields, EXEMPLAR_TIMESTAMP_FIELD)?,
+        })
+    }
+}
+
+/// Adds the creation time and the exemplar of a counter to `counter`.
+pub(crate) fn set_counter_extras(
+    counter: &mut Counter,
+    created: Option<SystemTime>,
+    exemplar: Option<Exemplar>,
+) {
+    let fields = counter.mut_unknown_fields();
+    if let Some(exemplar) = exemplar {
+        fields.add_length_delimited(COUNTER_EXEMPLAR_FIELD, exemplar.to_bytes());
+    }
+    if let Some(created) = created {
+        fields.add_length_delimited(
+            COUNTER_CREATED_TIMESTAMP_FIELD,
+            to_bytes(&timestamp(created)),
+        );
+    }
+}
+
+/// Adds the creation time of a histogram, and the exemplars of its buckets, the
+/// last one being the `+Inf` bucket, to `histogram`.
+///
+/// The `+Inf` bucket is only added to the buckets of `histogram` to carry its
+/// exemplar.
+pub(crate) fn set_histogram_extras(
+    histogram: &mut Histogram,
+    created: SystemTime,
+    bucket_exemplars: Vec<Option<Exemplar>>,
+) {
+    let sample_count = histogram.get_sample_count();
+    let buckets = histogram.mut_bucket();
+    for (i, exemplar) in bucket_exemplars.into_iter().enumerate() {
+        let exemplar = match exemplar {
+            Some(exemplar) => exemplar,
+            None => continue,
+        };
+        if i == buckets.len() {
+            let mut bucket = Bucket::default();
+            bucket.set_upper_bound(f64::INFINITY);
+            bucket.set_cumulative_count(sample_count);
+            buckets.push(bucket);
+        }
+        if let Some(bucket) = buckets.get_mut(i) {
+            bucket
+                .mut_unknown_fields()
+                .add_length_delimited(BUCKET_EXEMPLAR_FIELD, exemplar.to_bytes());
+        }
+    }
+
+    histogram.mut_unknown_fields().add_length_delimited(
+        HISTOGRAM_CREATED_TIMESTAMP_FIELD,
+        to_bytes(&timestamp(created)),
+    );
+}
+
+fn get_exemplar(fields: &UnknownFields, field: u32) -> Option<Exemplar> {
+    Exemplar::from_bytes(fields.get(field)?.length_delimited.last()?)
+}
+
+fn get_timestamp(fields: &UnknownFields, field: u32) -> Option<SystemTime> {
+    let timestamp =
+        Timestamp::parse_from_bytes(fields.get(field)?.length_delimited.last()?).ok()?;
+    let since_epoch = Duration::new(
+        u64::try_from(timestamp.get_seconds()).ok()?,
+        u32::try_from(timestamp.get_nanos()).ok()?,
+    );
+    UNIX_EPOCH.checked_add(since_epoch)
+}
+
+fn timestamp(time: SystemTime) -> Timestamp {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let mut timestamp = Timestamp::new();
+    timestamp.set_seconds(since_epoch.as_secs() as i64);
+    timestamp.set_nanos(since_epoch.subsec_nanos() as i32);
+    timestamp
+}
+
+fn to_bytes(message: &impl Message) -> Vec<u8> {
+    message
+        .write_to_bytes()
+        .expect("messages without required fields can always be written")
+}
+
+/// Encodes metrics in the OpenMetrics text format.
+///
+/// The metrics of the [PrometheusExporter]s include the `_created` series of
+/// the counters and histograms, and their exemplars.
+///
+/// [PrometheusExporter]: crate::PrometheusExporter
+#[derive(Debug, Default)]
+pub struct OpenMetricsEncoder;
+
+impl OpenMetricsEncoder {
+    /// Create a new OpenMetrics encoder.
+    pub fn new() -> Self {
+        OpenMetricsEncoder
+    }
+
+    /// Gathers the metrics of `registry` and encodes them to `writer`.
+    pub fn gather_and_encode<W: Write>(
+        &self,
+        registry: &Registry,
+        writer: &mut W,
+    ) -> prometheus::Result<()> {
+        self.encode(&registry.gather(), writer)
+    }
+}
+
+impl Encoder for OpenMetricsEncoder {
+    fn encode<W: Write>(
+        &self,
+        metric_families: &[MetricFamily],
+        writer: &mut W,
+    ) -> prometheus::Result<()> {
+        encode(metric_families, writer)?;
+        Ok(())
+    }
+
+    fn format_type(&self) -> &str {
+        OPENMETRICS_FORMAT
+    }
+}
+
+fn encode<W: Write>(metric_families: &[MetricFamily], writer: &mut W) -> io::Result<()> {
+    for mf in metric_families {
+        let name = mf.get_name();
+        let (family, type_name) = match mf.get_field_type() {
+            MetricType::COUNTER => (name.strip_suffix("_total").unwrap_or(name), "counter"),
+            MetricType::GAUGE => (name, "gauge"),
+            MetricType::HISTOGRAM => (name, "histogram"),
+            MetricType::SUMMARY => (name, "summary"),
+            MetricType::UNTYPED => (name, "unknown"),
+        };
+
+        if !mf.get_help().is_empty() {
+            writeln!(writer, "# HELP {} {}", family, escape(mf.get_help()))?;
+        }
+        writeln!(writer, "# TYPE {} {}", family, type_name)?;
+
+        for metric in mf.get_metric() {
+            let labels = metric.get_label();
+            match mf.get_field_type() {
+                MetricType::COUNTER => {
+                    let counter = metric.get_counter();
+                    let fields = counter.get_unknown_fields();
+                    Sample::new(writer, family, labels).suffix("_total").value(
+                        counter.get_value(),
+                        get_exemplar(fields, COUNTER_EXEMPLAR_FIELD).as_ref(),
+                    )?;
+                    write_created(
+                        writer,
+                        family,
+                        labels,
+                        fields,
+                        COUNTER_CREATED_TIMESTAMP_FIELD,
+                    )?;
+                }
+                MetricType::GAUGE => Sample::new(writer, family, labels)
+                    .value(metric.get_gauge().get_value(), None)?,
+                MetricType::UNTYPED => Sample::new(writer, family, labels)
+                    .value(metric.get_untyped().get_value(), None)?,
+                MetricType::HISTOGRAM => write_histogram(writer, family, metric)?,
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    for quantile in summary.get_quantile() {
+                        Sample::new(writer, family, labels)
+                            .label("quantile", &format_float(quantile.get_quantile()))
+                            .value(quantile.get_value(), None)?;
+                    }
+                    Sample::new(writer, family, labels)
+                        .suffix("_sum")
+                        .value(summary.get_sample_sum(), None)?;
+                    Sample::new(writer, family, labels)
+                        .suffix("_count")
+                        .value(summary.get_sample_count() as f64, None)?;
+                }
+            }
+        }
+    }
+
+    writeln!(writer, "# EOF")
+}
+
+fn write_histogram<W: Write>(writer: &mut W, family: &str, metric: &Metric) -> io::Result<()> {
+    let labels = metric.get_label();
+    let histogram = metric.get_histogram();
+
+    let mut has_inf_bucket = false;
+    for bucket in histogram.get_bucket() {
+        has_inf_bucket = bucket.get_upper_bound() == f64::INFINITY;
+        let exemplar = get_exemplar(bucket.get_unknown_fields(), BUCKET_EXEMPLAR_FIELD);
+        Sample::new(writer, family, labels)
+            .suffix("_bucket")
+            .label("le", &format_float(bucket.get_upper_bound()))
+            .value(bucket.get_cumulative_count() as f64, exemplar.as_ref())?;
+    }
+    if !has_inf_bucket {
+        Sample::new(writer, family, labels)
+            .suffix("_bucket")
+            .label("le", "+Inf")
+            .value(histogram.get_sample_count() as f64, None)?;
+    }
+
+    Sample::new(writer, family, labels)
+        .suffix("_sum")
+        .value(histogram.get_sample_sum(), None)?;
+    Sample::new(writer, family, labels)
+        .suffix("_count")
+        .value(histogram.get_sample_count() as f64, None)?;
+    write_created(
+        writer,
+        family,
+        labels,
+        histogram.get_unknown_fields(),
+        HISTOGRAM_CREATED_TIMESTAMP_FIELD,
+    )
+}
+
+fn write_created<W: Write>(
+    writer: &mut W,
+    family: &str,
+    labels: &[LabelPair],
+    fields: &UnknownFields,
+    field: u32,
+) -> io::Result<()> {
+    match get_timestamp(fields, field) {
+        Some(created) => Sample::new(writer, family, labels)
+            .suffix("_created")
+            .value(seconds(created), None),
+        None => Ok(()),
+    }
+}
+
+/// A line of the exposition.
+struct Sample<'a, W> {
+    writer: &'a mut W,
+    family: &'a str,
+    suffix: &'a str,
+    labels: &'a [LabelPair],
+    extra_label: Option<(&'a str, &'a str)>,
+}
+
+impl<'a, W: Write> Sample<'a, W> {
+    fn new(writer: &'a mut W, family: &'a str, labels: &'a [LabelPair]) -> Self {
+        Sample {
+            writer,
+            family,
+            suffix: "",
+            labels,
+            extra_label: None,
+        }
+    }
+
+    fn suffix(mut self, suffix: &'a str) -> Self {
+        self.suffix = suffix;
+        self
+    }
+
+    fn label(mut self, name: &'a str, value: &'a str) -> Self {
+        self.extra_label = Some((name, value));
+        self
+    }
+
+    fn value(self, value: f64, exemplar: Option<&Exemplar>) -> io::Result<()> {
+        write!(self.writer, "{}{}", self.family, self.suffix)?;
+        let labels = self
+            .labels
+            .iter()
+            .map(|label| (label.get_name(), label.get_value()))
+            .chain(self.extra_label);
+        write_labels(self.writer, labels)?;
+        write!(self.writer, " {}", format_float(value))?;
+        if let Some(exemplar) = exemplar {
+            write!(self.writer, " # ")?;
+            if exemplar.labels.is_empty() {
+                write!(self.writer, "{{}}")?;
+            }
+            let labels = exemplar
+                .labels
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()));
+            write_labels(self.writer, labels)?;
+            write!(
+                self.writer,
+                " {} {}",
+                format_float(exemplar.value),
+                seconds(exemplar.timestamp)
+            )?;
+        }
+        writeln!(self.writer)
+    }
+}
+
+fn write_labels<'a, W: Write>(
+    writer: &mut W,
+    labels: impl Iterator<Item = (&'a str, &'a str)>,
+) -> io::Result<()> {
+    let mut separator = "{";
+    for (name, value) in labels {
+        write!(writer, "{}{}=\"{}\"", separator, name, escape(value))?;
+        separator = ",";
+    }
+    if separator == "," {
+        write!(writer, "}}")?;
+    }
+    Ok(())
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_float(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else if value.is_nan() {
+        "NaN".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn seconds(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs_f64())
+        .unwrap_or_default()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::proto::Gauge;
+    use std::time::Duration;
+
+    fn label(name: &str, value: &str) -> LabelPair {
+        let mut label = LabelPair::new();
+        label.set_name(name.into());
+        label.set_value(value.into());
+        label
+    }
+
+    fn family(name: &str, metric_type: MetricType, metric: Metric) -> MetricFamily {
+        let mut mf = MetricFamily::default();
+        mf.set_name(name.into());
+        mf.set_help("some \"help\"\nwith a new line".into());
+        mf.set_field_type(metric_type);
+        mf.set_metric(protobuf::RepeatedField::from_vec(vec![metric]));
+        mf
+    }
+
+    #[test]
+    fn test_encode() {
+        let labels = vec![label("A", "B\\\"C\"")];
+        let created = UNIX_EPOCH + Duration::from_millis(1_500);
+        let exemplar = Exemplar {
+            labels: vec![("trace_id".into(), "0102".into())],
+            value: 15.5,
+            timestamp: UNIX_EPOCH + Duration::from_secs(2),
+        };
+
+        let mut counter = Counter::default();
+        counter.set_value(5.0);
+        set_counter_extras(&mut counter, Some(created), Some(exemplar.clone()));
+        let mut counter_metric = Metric::default();
+        counter_metric.set_label(protobuf::RepeatedField::from_vec(labels.clone()));
+        counter_metric.set_counter(counter);
+
+        let mut gauge = Gauge::default();
+        gauge.set_value(f64::NEG_INFINITY);
+        let mut gauge_metric = Metric::default();
+        gauge_metric.set_gauge(gauge);
+
+        let mut bucket = Bucket::default();
+        bucket.set_upper_bound(10.0);
+        bucket.set_cumulative_count(1);
+        let mut histogram = Histogram::default();
+        histogram.set_sample_count(2);
+        histogram.set_sample_sum(25.5);
+        histogram.set_bucket(protobuf::RepeatedField::from_vec(vec![bucket]));
+        set_histogram_extras(&mut histogram, created, vec![None, Some(exemplar)]);
+        let mut histogram_metric = Metric::default();
+        histogram_metric.set_label(protobuf::RepeatedField::from_vec(labels.clone()));
+        histogram_metric.set_histogram(histogram);
+
+        let metric_families = vec![
+            family("foo_total", MetricType::COUNTER, counter_metric),
+            family("bar", MetricType::GAUGE, gauge_metric),
+            family("baz", MetricType::HISTOGRAM, histogram_metric),
+        ];
+
+        let mut output = Vec::new();
+        OpenMetricsEncoder::new()
+            .encode(&metric_families, &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"# HELP foo some \"help\"\nwith a new line
+# TYPE foo counter
+foo_total{A="B\\\"C\""} 5 # {trace_id="0102"} 15.5 2
+foo_created{A="B\\\"C\""} 1.5
+# HELP bar some \"help\"\nwith a new line
+# TYPE bar gauge
+bar -Inf
+# HELP baz some \"help\"\nwith a new line
+# TYPE baz histogram
+baz_bucket{A="B\\\"C\"",le="10"} 1
+baz_bucket{A="B\\\"C\"",le="+Inf"} 2 # {trace_id="0102"} 15.5 2
+baz_sum{A="B\\\"C\""} 25.5
+baz_count{A="B\\\"C\""} 2
+baz_created{A="B\\\"C\""} 1.5
+# EOF
+"#
+        );
+    }
+
+    #[test]
+    fn test_exemplars_by_bucket() {
+        let exemplar = |value: f64, secs: u64| data::Exemplar {
+            filtered_attributes: vec![KeyValue::new("key.name", "value")],
+            time: UNIX_EPOCH + Duration::from_secs(secs),
+            value,
+            span_id: [0; 8],
+            trace_id: [0; 16],
+        };
+        let exemplars = Exemplar::by_bucket(
+            &[exemplar(1.0, 2), exemplar(2.0, 1), exemplar(20.0, 3)],
+            &[5.0, 10.0],
+        );
+
+        assert_eq!(exemplars.len(), 3);
+        let first = exemplars[0].as_ref().unwrap();
+        assert_eq!(first.value, 1.0);
+        assert_eq!(first.labels, vec![("key_name".into(), "value".into())]);
+        assert_eq!(exemplars[1], None);
+        assert_eq!(exemplars[2].as_ref().unwrap().value, 20.0);
+    }
+
+    #[test]
+    fn test_exemplar_labels_are_limited() {
+        let exemplar = data::Exemplar {
+            filtered_attributes: vec![
+                KeyValue::new("short", "value"),
+                KeyValue::new("long", "x".repeat(100)),
+            ],
+            time: UNIX_EPOCH,
+            value: 1u64,
+            span_id: [1; 8],
+            trace_id: [1; 16],
+        };
+        let exemplar = Exemplar::new(&exemplar);
+        assert_eq!(
+            exemplar.labels,
+            vec![
+                ("trace_id".into(), "01".repeat(16)),
+                ("span_id".into(), "01".repeat(8)),
+                ("short".into(), "value".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,751 @@
+//! Push exporter sending metrics with the Prometheus [remote write] protocol.
+//!
+//! [remote write]: https://prometheus.io/docs/concepts/remote_write_spec/
+use async_trait::async_trait;
+use core::fmt;
+use http::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE, USER_AGENT},
+    HeaderName, HeaderValue, Request, Uri,
+};
+use once_cell::sync::OnceCell;
+use opentelemetry::{
+    metrics::{MetricsError, Result},
+    Key,
+};
+use opentelemetry_http::HttpClient;
+use opentelemetry_sdk::{
+    metrics::{
+        data::{ResourceMetrics, Temporality},
+        exporter::PushMetricsExporter,
+        reader::{AggregationSelector, DefaultAggregationSelector, TemporalitySelector},
+        Aggregation, InstrumentKind,
+    },
+    Resource,
+};
+use prometheus::proto::{LabelPair, MetricFamily};
+use prost::Message;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{utils, Converter, ResourceSelector, TARGET_INFO_NAME};
+
+const REMOTE_WRITE_VERSION_HEADER: &str = "x-prometheus-remote-write-version";
+const REMOTE_WRITE_VERSION: &str = "0.1.0";
+const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+const SNAPPY_ENCODING: &str = "snappy";
+const USER_AGENT_VALUE: &str = concat!("opentelemetry-prometheus/", env!("CARGO_PKG_VERSION"));
+
+const NAME_LABEL: &str = "__name__";
+const JOB_LABEL: &str = "job";
+const INSTANCE_LABEL: &str = "instance";
+const BUCKET_LABEL: &str = "le";
+
+const SERVICE_NAME: &str = "service.name";
+const SERVICE_NAMESPACE: &str = "service.namespace";
+const SERVICE_INSTANCE_ID: &str = "service.instance.id";
+
+/// Exports metrics by pushing them to a Prometheus remote write endpoint.
+///
+/// The metrics are named and labelled like the ones of the
+/// [PrometheusExporter], with the `job` and `instance` labels of the
+/// [Resource] added to every series, and sent as remote write v1 requests,
+/// compressed with snappy, through an [HttpClient]. All samples of an export
+/// have the time of the export. Exponential histograms are sent as classic
+/// histograms.
+///
+/// It suits short-lived processes, like batch jobs, which can't be scraped.
+///
+/// ```no_run
+/// use opentelemetry_prometheus::RemoteWriteExporter;
+/// use opentelemetry_sdk::{metrics::{PeriodicReader, SdkMeterProvider}, runtime};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client = opentelemetry_http::hyper::HyperClient::new_with_timeout(
+/// #     hyper::Client::new(),
+/// #     std::time::Duration::from_secs(10),
+/// # );
+/// let exporter = RemoteWriteExporter::builder()
+///     .with_endpoint("http://localhost:9090/api/v1/write")
+///     .with_http_client(client)
+///     .build()?;
+/// let reader = PeriodicReader::builder(exporter, runtime::Tokio).build();
+/// let provider = SdkMeterProvider::builder().with_reader(reader).build();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [PrometheusExporter]: crate::PrometheusExporter
+pub struct RemoteWriteExporter {
+    client: Mutex<Option<Arc<dyn HttpClient>>>,
+    endpoint: Uri,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    converter: Converter,
+    aggregation_selector: Box<dyn AggregationSelector>,
+}
+
+impl RemoteWriteExporter {
+    /// Create a builder to configure this exporter.
+    pub fn builder() -> RemoteWriteExporterBuilder {
+        RemoteWriteExporterBuilder::default()
+    }
+}
+
+impl fmt::Debug for RemoteWriteExporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteWriteExporter")
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+impl TemporalitySelector for RemoteWriteExporter {
+    /// Note: Prometheus only supports cumulative temporality so this will always be
+    /// [Temporality::Cumulative].
+    fn temporality(&self, _kind: InstrumentKind) -> Temporality {
+        Temporality::Cumulative
+    }
+}
+
+impl AggregationSelector for RemoteWriteExporter {
+    fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+        self.aggregation_selector.aggregation(kind)
+    }
+}
+
+#[async_trait]
+impl PushMetricsExporter for RemoteWriteExporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()> {
+        let client = match &*self.client.lock()? {
+            Some(client) => Arc::clone(client),
+            None => return Err(MetricsError::Other("exporter is already shut down".into())),
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_millis() as i64)
+            .unwrap_or_default();
+        let families = self.converter.convert(metrics);
+        let write_request = write_request(&families, &metrics.resource, timestamp);
+        if write_request.timeseries.is_empty() {
+            return Ok(());
+        }
+
+        let body = snap::raw::Encoder::new()
+            .compress_vec(&write_request.encode_to_vec())
+            .map_err(|err| MetricsError::Other(err.to_string()))?;
+        let mut request = Request::post(self.endpoint.clone())
+            .header(CONTENT_ENCODING, SNAPPY_ENCODING)
+            .header(CONTENT_TYPE, PROTOBUF_CONTENT_TYPE)
+            .header(USER_AGENT, USER_AGENT_VALUE)
+            .header(REMOTE_WRITE_VERSION_HEADER, REMOTE_WRITE_VERSION)
+            .body(body)
+            .map_err(|err| MetricsError::Other(err.to_string()))?;
+        for (name, value) in &self.headers {
+            request.headers_mut().insert(name, value.clone());
+        }
+
+        let response = client
+            .send(request)
+            .await
+            .map_err(|err| MetricsError::Other(err.to_string()))?;
+        if !response.status().is_success() {
+            return Err(MetricsError::Other(format!(
+                "Prometheus remote write failed. Url: {}, Status Code: {}, Response: {:?}",
+                self.endpoint,
+                response.status().as_u16(),
+                response.body()
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn force_flush(&self) -> Result<()> {
+        // exporter holds no state, nothing to flush
+        Ok(())
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        self.client.lock()?.take();
+        Ok(())
+    }
+}
+
+/// [RemoteWriteExporter] configuration options
+#[derive(Default)]
+pub struct RemoteWriteExporterBuilder {
+    endpoint: Option<String>,
+    client: Option<Arc<dyn HttpClient>>,
+    headers: HashMap<String, String>,
+    disable_target_info: bool,
+    without_units: bool,
+    without_counter_suffixes: bool,
+    namespace: Option<String>,
+    disable_scope_info: bool,
+    aggregation_selector: Option<Box<dyn AggregationSelector>>,
+    resource_selector: ResourceSelector,
+}
+
+impl fmt::Debug for RemoteWriteExporterBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteWriteExporterBuilder")
+            .field("endpoint", &self.endpoint)
+            .field("client", &self.client)
+            .field("headers", &self.headers)
+            .field("disable_target_info", &self.disable_target_info)
+            .field("without_units", &self.without_units)
+            .field("without_counter_suffixes", &self.without_counter_suffixes)
+            .field("namespace", &self.namespace)
+            .field("disable_scope_info", &self.disable_scope_info)
+            .finish()
+    }
+}
+
+impl RemoteWriteExporterBuilder {
+    /// Sets the URL of the remote write endpoint, e.g.
+    /// `http://localhost:9090/api/v1/write`.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Sets the [HttpClient] sending the remote write requests.
+    pub fn with_http_client(mut self, client: impl HttpClient + 'static) -> Self {
+        self.client = Some(Arc::new(client));
+        self
+    }
+
+    /// Sets additional headers of the remote write requests, e.g. for
+    /// authentication.
+    pub fn with_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Disables exporter's addition of unit suffixes to metric names.
+    ///
+    /// See [ExporterBuilder::without_units].
+    ///
+    /// [ExporterBuilder::without_units]: crate::ExporterBuilder::without_units
+    pub fn without_units(mut self) -> Self {
+        self.without_units = true;
+        self
+    }
+
+    /// Disables exporter's addition `_total` suffixes on counters.
+    ///
+    /// See [ExporterBuilder::without_counter_suffixes].
+    ///
+    /// [ExporterBuilder::without_counter_suffixes]: crate::ExporterBuilder::without_counter_suffixes
+    pub fn without_counter_suffixes(mut self) -> Self {
+        self.without_counter_suffixes = true;
+        self
+    }
+
+    /// Configures the exporter to not export the resource `target_info` metric.
+    ///
+    /// If not specified, the exporter will send a `target_info` series with the
+    /// `job` and `instance` labels and the other attributes of the [Resource].
+    pub fn without_target_info(mut self) -> Self {
+        self.disable_target_info = true;
+        self
+    }
+
+    /// Configures the exporter to not export the `otel_scope_info` metric.
+    ///
+    /// See [ExporterBuilder::without_scope_info].
+    ///
+    /// [ExporterBuilder::without_scope_info]: crate::ExporterBuilder::without_scope_info
+    pub fn without_scope_info(mut self) -> Self {
+        self.disable_scope_info = true;
+        self
+    }
+
+    /// Configures the exporter to prefix metrics with the given namespace.
+    ///
+    /// See [ExporterBuilder::with_namespace].
+    ///
+    /// [ExporterBuilder::with_namespace]: crate::ExporterBuilder::with_namespace
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        let mut namespace = namespace.into();
+
+        // namespace and metric names should be separated with an underscore,
+        // adds a trailing underscore if there is not one already.
+        if !namespace.ends_with('_') {
+            namespace.push('_')
+        }
+
+        self.namespace = Some(namespace);
+        self
+    }
+
+    /// Configure the [AggregationSelector] the exporter will use.
+    ///
+    /// If no selector is provided, the [DefaultAggregationSelector] is used.
+    pub fn with_aggregation_selector(mut self, agg: impl AggregationSelector + 'static) -> Self {
+        self.aggregation_selector = Some(Box::new(agg));
+        self
+    }
+
+    /// Configures whether to export resource as attributes with every metric.
+    ///
+    /// See [ExporterBuilder::with_resource_selector].
+    ///
+    /// [ExporterBuilder::with_resource_selector]: crate::ExporterBuilder::with_resource_selector
+    pub fn with_resource_selector(
+        mut self,
+        resource_selector: impl Into<ResourceSelector>,
+    ) -> Self {
+        self.resource_selector = resource_selector.into();
+        self
+    }
+
+    /// Creates a new [RemoteWriteExporter] from this configuration.
+    ///
+    /// Returns an error if the endpoint or the HTTP client are missing, or if
+    /// the endpoint or the headers are invalid.
+    pub fn build(self) -> Result<RemoteWriteExporter> {
+        let endpoint = self
+            .endpoint
+            .ok_or_else(|| MetricsError::Config("missing remote write endpoint".into()))?
+            .parse::<Uri>()
+            .map_err(|err| MetricsError::Config(format!("invalid remote write endpoint: {err}")))?;
+        let client = self
+            .client
+            .ok_or_else(|| MetricsError::Config("missing remote write HTTP client".into()))?;
+        let headers = self
+            .headers
+            .into_iter()
+            .map(|(name, value)| {
+                Ok((
+                    HeaderName::try_from(name).map_err(|err| {
+                        MetricsError::Config(format!("invalid header name: {err}"))
+                    })?,
+                    HeaderValue::try_from(value).map_err(|err| {
+                        MetricsError::Config(format!("invalid header value: {err}"))
+                    })?,
+                ))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(RemoteWriteExporter {
+            client: Mutex::new(Some(client)),
+            endpoint,
+            headers,
+            converter: Converter {
+                disable_target_info: self.disable_target_info,
+                without_units: self.without_units,
+                without_counter_suffixes: self.without_counter_suffixes,
+                disable_scope_info: self.disable_scope_info,
+                // remote write v1 has no native histograms
+                without_native_histograms: true,
+                create_target_info_once: OnceCell::new(),
+                namespace: self.namespace,
+                inner: Mutex::new(Default::default()),
+                resource_selector: self.resource_selector,
+                resource_labels_once: OnceCell::new(),
+            },
+            aggregation_selector: self
+                .aggregation_selector
+                .unwrap_or_else(|| Box::new(DefaultAggregationSelector::new())),
+        })
+    }
+}
+
+/// Converts metric families to a remote write request.
+fn write_request(families: &[MetricFamily], resource: &Resource, timestamp: i64) -> WriteRequest {
+    let target_labels = target_labels(resource);
+    let mut request = WriteRequest::default();
+    // the converter returns a family per data point, the metadata is sent once per name
+    let mut described = HashSet::new();
+
+    for family in families {
+        let name = family.get_name();
+        let metadata_type = match family.get_field_type() {
+            prometheus::proto::MetricType::COUNTER => MetadataType::Counter,
+            prometheus::proto::MetricType::GAUGE if name == TARGET_INFO_NAME => MetadataType::Info,
+            prometheus::proto::MetricType::GAUGE => MetadataType::Gauge,
+            prometheus::proto::MetricType::HISTOGRAM => MetadataType::Histogram,
+            _ => continue,
+        };
+        if described.insert(name) {
+            request.metadata.push(MetricMetadata {
+                r#type: metadata_type as i32,
+                metric_family_name: name.to_string(),
+                help: family.get_help().to_string(),
+                unit: String::new(),
+            });
+        }
+
+        for metric in family.get_metric() {
+            let mut labels = metric.get_label().to_vec();
+            if name == TARGET_INFO_NAME {
+                // the service attributes are carried by `job` and `instance`
+                let service_labels = [SERVICE_NAME, SERVICE_NAMESPACE, SERVICE_INSTANCE_ID]
+                    .map(utils::sanitize_prom_kv);
+                labels.retain(|label| !service_labels.contains(&label.get_name().to_string()));
+            }
+            for (label, value) in &target_labels {
+                if !labels.iter().any(|existing| existing.get_name() == *label) {
+                    labels.push(label_pair(label, value));
+                }
+            }
+
+            let mut push = |suffix: &str, extra: Option<LabelPair>, value: f64| {
+                request.timeseries.push(time_series(
+                    format!("{name}{suffix}"),
+                    &labels,
+                    extra,
+                    Sample { value, timestamp },
+                ))
+            };
+            match metadata_type {
+                MetadataType::Counter => push("", None, metric.get_counter().get_value()),
+                MetadataType::Gauge | MetadataType::Info => {
+                    push("", None, metric.get_gauge().get_value())
+                }
+                MetadataType::Histogram => {
+                    let histogram = metric.get_histogram();
+                    let count = histogram.get_sample_count();
+                    let mut has_inf = false;
+                    for bucket in histogram.get_bucket() {
+                        let upper_bound = bucket.get_upper_bound();
+                        has_inf |= upper_bound == f64::INFINITY;
+                        let le = label_pair(BUCKET_LABEL, &format_float(upper_bound));
+                        push("_bucket", Some(le), bucket.get_cumulative_count() as f64);
+                    }
+                    if !has_inf {
+                        let le = label_pair(BUCKET_LABEL, &format_float(f64::INFINITY));
+                        push("_bucket", Some(le), count as f64);
+                    }
+                    push("_sum", None, histogram.get_sample_sum());
+                    push("_count", None, count as f64);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    request
+}
+
+/// The `job` and `instance` labels of the resource.
+fn target_labels(resource: &Resource) -> Vec<(&'static str, String)> {
+    let mut labels = Vec::with_capacity(2);
+    if let Some(service_name) = resource.get(Key::from_static_str(SERVICE_NAME)) {
+        let job = match resource.get(Key::from_static_str(SERVICE_NAMESPACE)) {
+            Some(namespace) => format!("{namespace}/{service_name}"),
+            None => service_name.to_string(),
+        };
+        labels.push((JOB_LABEL, job));
+    }
+    if let Some(instance) = resource.get(Key::from_static_str(SERVICE_INSTANCE_ID)) {
+        labels.push((INSTANCE_LABEL, instance.to_string()));
+    }
+    labels
+}
+
+/// A series with a single sample, whose labels are sorted by name as
+/// required by remote write.
+fn time_series(
+    name: String,
+    labels: &[LabelPair],
+    extra: Option<LabelPair>,
+    sample: Sample,
+) -> TimeSeries {
+    let mut labels: Vec<Label> = labels
+        .iter()
+        .chain(extra.as_ref())
+        .map(|label| Label {
+            name: label.get_name().to_string(),
+            value: label.get_value().to_string(),
+        })
+        .collect();
+    labels.push(Label {
+        name: NAME_LABEL.to_string(),
+        value: name,
+    });
+    labels.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+    TimeSeries {
+        labels,
+        samples: vec![sample],
+    }
+}
+
+fn label_pair(name: &str, value: &str) -> LabelPair {
+    let mut label = LabelPair::new();
+    label.set_name(name.to_string());
+    label.set_value(value.to_string());
+    label
+}
+
+fn format_float(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+// The messages of the remote write v1 protocol:
+// https://github.com/prometheus/prometheus/blob/main/prompb/remote.proto
+
+#[derive(Clone, PartialEq, Message)]
+struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    timeseries: Vec<TimeSeries>,
+    #[prost(message, repeated, tag = "3")]
+    metadata: Vec<MetricMetadata>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TimeSeries {
+    #[prost(message, repeated, tag = "1")]
+    labels: Vec<Label>,
+    #[prost(message, repeated, tag = "2")]
+    samples: Vec<Sample>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Label {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    value: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Sample {
+    #[prost(double, tag = "1")]
+    value: f64,
+    /// Milliseconds since the Unix epoch.
+    #[prost(int64, tag = "2")]
+    timestamp: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct MetricMetadata {
+    #[prost(enumeration = "MetadataType", tag = "1")]
+    r#type: i32,
+    #[prost(string, tag = "2")]
+    metric_family_name: String,
+    #[prost(string, tag = "4")]
+    help: String,
+    #[prost(string, tag = "5")]
+    unit: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+enum MetadataType {
+    Unknown = 0,
+    Counter = 1,
+    Gauge = 2,
+    Histogram = 3,
+    Info = 6,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Response, Server,
+    };
+    use opentelemetry::{metrics::MeterProvider as _, KeyValue};
+    use opentelemetry_http::hyper::HyperClient;
+    use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+    use std::{convert::Infallible, time::Duration};
+    use tokio::sync::mpsc;
+
+    fn labels(series: &TimeSeries) -> Vec<(&str, &str)> {
+        series
+            .labels
+            .iter()
+            .map(|label| (label.name.as_str(), label.value.as_str()))
+            .collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_export() {
+        // A remote write endpoint forwarding the requests it receives.
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let make_service = make_service_fn(move |_| {
+            let sender = sender.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: hyper::Request<Body>| {
+                    let sender = sender.clone();
+                    async move {
+                        let (parts, body) = request.into_parts();
+                        let body = hyper::body::to_bytes(body).await.unwrap();
+                        sender.send((parts, body)).unwrap();
+                        Ok::<_, Infallible>(Response::new(Body::empty()))
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let endpoint = format!("http://{}/api/v1/write", server.local_addr());
+        tokio::spawn(server);
+
+        let exporter = RemoteWriteExporter::builder()
+            .with_endpoint(endpoint)
+            .with_http_client(HyperClient::new_with_timeout(
+                hyper::Client::new(),
+                Duration::from_secs(10),
+            ))
+            .with_headers(HashMap::from([(
+                "authorization".to_string(),
+                "Bearer token".to_string(),
+            )]))
+            .without_scope_info()
+            .build()
+            .unwrap();
+        let provider = SdkMeterProvider::builder()
+            .with_resource(Resource::new([
+                KeyValue::new(SERVICE_NAME, "worker"),
+                KeyValue::new(SERVICE_NAMESPACE, "jobs"),
+                KeyValue::new(SERVICE_INSTANCE_ID, "1"),
+                KeyValue::new("host.name", "localhost"),
+            ]))
+            .with_reader(
+                PeriodicReader::builder(exporter, opentelemetry_sdk::runtime::Tokio).build(),
+            )
+            .build();
+        let meter = provider.meter("test");
+        meter
+            .u64_counter("processed")
+            .init()
+            .add(3, &[KeyValue::new("queue", "a")]);
+        meter
+            .u64_counter("processed")
+            .init()
+            .add(4, &[KeyValue::new("queue", "b")]);
+        meter
+            .f64_histogram("duration")
+            .with_unit(opentelemetry::metrics::Unit::new("s"))
+            .init()
+            .record(7.0, &[]);
+        provider.force_flush().unwrap();
+
+        let (parts, body) = receiver.recv().await.unwrap();
+        assert_eq!(parts.method, http::Method::POST);
+        assert_eq!(parts.uri.path(), "/api/v1/write");
+        for (name, value) in [
+            (CONTENT_ENCODING.as_str(), SNAPPY_ENCODING),
+            (CONTENT_TYPE.as_str(), PROTOBUF_CONTENT_TYPE),
+            (REMOTE_WRITE_VERSION_HEADER, REMOTE_WRITE_VERSION),
+            ("authorization", "Bearer token"),
+        ] {
+            assert_eq!(parts.headers[name], value, "{name}");
+        }
+
+        let body = snap::raw::Decoder::new().decompress_vec(&body).unwrap();
+        let request = WriteRequest::decode(body.as_slice()).unwrap();
+        let series: HashMap<_, _> = request
+            .timeseries
+            .iter()
+            .map(|series| {
+                assert_eq!(series.samples.len(), 1);
+                (labels(series), series.samples[0].value)
+            })
+            .collect();
+        let job = ("job", "jobs/worker");
+        let instance = ("instance", "1");
+
+        assert_eq!(
+            series[&vec![
+                ("__name__", "target_info"),
+                ("host_name", "localhost"),
+                instance,
+                job,
+            ]],
+            1.0
+        );
+        assert_eq!(
+            series[&vec![
+                ("__name__", "processed_total"),
+                instance,
+                job,
+                ("queue", "a")
+            ]],
+            3.0
+        );
+        assert_eq!(
+            series[&vec![
+                ("__name__", "processed_total"),
+                instance,
+                job,
+                ("queue", "b")
+            ]],
+            4.0
+        );
+        assert_eq!(
+            series[&vec![
+                ("__name__", "duration_seconds_bucket"),
+                instance,
+                job,
+                ("le", "5")
+            ]],
+            0.0
+        );
+        assert_eq!(
+            series[&vec![
+                ("__name__", "duration_seconds_bucket"),
+                instance,
+                job,
+                ("le", "10")
+            ]],
+            1.0
+        );
+        assert_eq!(
+            series[&vec![
+                ("__name__", "duration_seconds_bucket"),
+                instance,
+                job,
+                ("le", "+Inf")
+            ]],
+            1.0
+        );
+        assert_eq!(
+            series[&vec![("__name__", "duration_seconds_sum"), instance, job]],
+            7.0
+        );
+        assert_eq!(
+            series[&vec![("__name__", "duration_seconds_count"), instance, job]],
+            1.0
+        );
+
+        let metadata: Vec<_> = request
+            .metadata
+            .iter()
+            .map(|metadata| (metadata.metric_family_name.as_str(), metadata.r#type))
+            .collect();
+        assert!(metadata.contains(&("processed_total", MetadataType::Counter as i32)));
+        assert!(metadata.contains(&("duration_seconds", MetadataType::Histogram as i32)));
+        assert!(metadata.contains(&("target_info", MetadataType::Info as i32)));
+        // once per metric, not per data point
+        assert_eq!(metadata.len(), 3);
+
+        provider.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert!(RemoteWriteExporter::builder().build().is_err());
+        assert!(RemoteWriteExporter::builder()
+            .with_endpoint("not a url")
+            .with_http_client(HyperClient::new_with_timeout(
+                hyper::Client::new(),
+                Duration::from_secs(10),
+            ))
+            .build()
+            .is_err());
+    }
+}
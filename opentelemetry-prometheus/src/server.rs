@@ -0,0 +1,349 @@
+use flate2::{write::GzEncoder, Compression};
+use hyper::{
+    header::{HeaderValue, ACCEPT, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE},
+    service::{make_service_fn, Service},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use opentelemetry::metrics::{MetricsError, Result};
+use prometheus::{Encoder, ProtobufEncoder, Registry, TextEncoder};
+use std::{
+    convert::Infallible,
+    env,
+    future::{ready, Ready},
+    io::Write,
+    net::{SocketAddr, ToSocketAddrs},
+    task::{Context, Poll},
+};
+
+use crate::OpenMetricsEncoder;
+
+/// The host the metrics server binds to.
+const OTEL_EXPORTER_PROMETHEUS_HOST: &str = "OTEL_EXPORTER_PROMETHEUS_HOST";
+/// Default host the metrics server binds to.
+const OTEL_EXPORTER_PROMETHEUS_HOST_DEFAULT: &str = "localhost";
+/// The port the metrics server binds to.
+const OTEL_EXPORTER_PROMETHEUS_PORT: &str = "OTEL_EXPORTER_PROMETHEUS_PORT";
+/// Default port the metrics server binds to.
+const OTEL_EXPORTER_PROMETHEUS_PORT_DEFAULT: u16 = 9464;
+
+const METRICS_PATH: &str = "/metrics";
+const OPENMETRICS_MEDIA_TYPE: &str = "application/openmetrics-text";
+const PROTOBUF_MEDIA_TYPE: &str = "application/vnd.google.protobuf";
+const PROTOBUF_PROTO: &str = "io.prometheus.client.MetricFamily";
+const TEXT_MEDIA_TYPE: &str = "text/plain";
+
+/// The exposition formats, in order of preference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Protobuf,
+    OpenMetrics,
+    Text,
+}
+
+impl Format {
+    /// The format with the highest quality in the `Accept` headers, the
+    /// Prometheus text format if none is accepted.
+    fn negotiate(accept: &[&HeaderValue]) -> Self {
+        let quality = |media_type| {
+            accept
+                .iter()
+                .filter_map(|header| quality(header, media_type))
+                .fold(0.0, f32::max)
+        };
+        [
+            (Format::Protobuf, quality(PROTOBUF_MEDIA_TYPE)),
+            (Format::OpenMetrics, quality(OPENMETRICS_MEDIA_TYPE)),
+            (Format::Text, quality(TEXT_MEDIA_TYPE)),
+        ]
+        .into_iter()
+        .fold((Format::Text, 0.0), |best, (format, quality)| {
+            if quality > best.1 {
+                (format, quality)
+            } else {
+                best
+            }
+        })
+        .0
+    }
+}
+
+/// A [hyper] service serving the metrics of a [prometheus::Registry] on
+/// `GET /metrics`.
+///
+/// The metrics are encoded in the format preferred by the `Accept` header of
+/// the request among the protobuf format, which exposes the native
+/// histograms, the OpenMetrics text format, with the `_created` series and the
+/// exemplars of the [PrometheusExporter]s, and the Prometheus text format, the
+/// default. The response is compressed with gzip if the `Accept-Encoding`
+/// header of the request allows it.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use opentelemetry_sdk::metrics::SdkMeterProvider;
+///
+/// let registry = prometheus::Registry::new();
+/// let exporter = opentelemetry_prometheus::exporter()
+///     .with_registry(registry.clone())
+///     .build()?;
+/// let provider = SdkMeterProvider::builder().with_reader(exporter).build();
+///
+/// // Serve the metrics on the address set by the `OTEL_EXPORTER_PROMETHEUS_HOST`
+/// // and `OTEL_EXPORTER_PROMETHEUS_PORT` environment variables, localhost:9464
+/// // by default.
+/// tokio::spawn(opentelemetry_prometheus::MetricsService::new(registry).serve());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [PrometheusExporter]: crate::PrometheusExporter
+#[derive(Clone, Debug)]
+pub struct MetricsService {
+    registry: Registry,
+}
+
+impl MetricsService {
+    /// Serve the metrics of `registry`.
+    pub fn new(registry: Registry) -> Self {
+        MetricsService { registry }
+    }
+
+    /// Encodes the metrics in the format and encoding negotiated with the
+    /// headers of `request`, whatever its method and path.
+    pub fn metrics_response<B>(&self, request: &Request<B>) -> Response<Body> {
+        let accept: Vec<_> = request.headers().get_all(ACCEPT).iter().collect();
+        let mut buffer = Vec::new();
+        let (encoded, content_type) = match Format::negotiate(&accept) {
+            Format::Protobuf => {
+                let encoder = ProtobufEncoder::new();
+                (
+                    encoder.encode(&self.registry.gather(), &mut buffer),
+                    encoder.format_type().to_string(),
+                )
+            }
+            Format::OpenMetrics => {
+                let encoder = OpenMetricsEncoder::new();
+                (
+                    encoder.gather_and_encode(&self.registry, &mut buffer),
+                    encoder.format_type().to_string(),
+                )
+            }
+            Format::Text => {
+                let encoder = TextEncoder::new();
+                (
+                    encoder.encode(&self.registry.gather(), &mut buffer),
+                    encoder.format_type().to_string(),
+                )
+            }
+        };
+        if let Err(err) = encoded {
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
+        }
+
+        let mut response = Response::builder().header(CONTENT_TYPE, content_type);
+        let gzip = request
+            .headers()
+            .get_all(ACCEPT_ENCODING)
+            .iter()
+            .any(|header| quality(header, "gzip").map_or(false, |quality| quality > 0.0));
+        if gzip {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            match encoder.write_all(&buffer).and_then(|_| encoder.finish()) {
+                Ok(compressed) => {
+                    buffer = compressed;
+                    response = response.header(CONTENT_ENCODING, "gzip");
+                }
+                Err(err) => {
+                    return error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+                }
+            }
+        }
+
+        response.body(Body::from(buffer)).unwrap_or_else(|err| {
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        })
+    }
+
+    /// Serves the metrics on the host and port set by the
+    /// `OTEL_EXPORTER_PROMETHEUS_HOST` and `OTEL_EXPORTER_PROMETHEUS_PORT`
+    /// environment variables, `localhost` and `9464` by default.
+    ///
+    /// The returned future must be polled by a tokio runtime, it only completes
+    /// if the server fails.
+    pub async fn serve(self) -> Result<()> {
+        let addr = addr_from_env()?;
+        self.serve_on(addr).await
+    }
+
+    /// Serves the metrics on `addr`.
+    ///
+    /// The returned future must be polled by a tokio runtime, it only completes
+    /// if the server fails.
+    pub async fn serve_on(self, addr: SocketAddr) -> Result<()> {
+        let make_service = make_service_fn(move |_conn| {
+            let service = self.clone();
+            async move { Ok::<_, Infallible>(service) }
+        });
+        Server::try_bind(&addr)
+            .map_err(|err| MetricsError::Other(format!("cannot bind to {addr}: {err}")))?
+            .serve(make_service)
+            .await
+            .map_err(|err| MetricsError::Other(err.to_string()))
+    }
+}
+
+impl<B> Service<Request<B>> for MetricsService {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = Ready<std::result::Result<Response<Body>, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let response = match (request.method(), request.uri().path()) {
+            (&Method::GET, METRICS_PATH) => self.metrics_response(&request),
+            (_, METRICS_PATH) => {
+                error_response(StatusCode::METHOD_NOT_ALLOWED, "method not allowed".into())
+            }
+            _ => error_response(StatusCode::NOT_FOUND, "not found".into()),
+        };
+        ready(Ok(response))
+    }
+}
+
+fn error_response(status: StatusCode, message: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(message));
+    *response.status_mut() = status;
+    response
+}
+
+/// The highest quality of `value` in the `Accept` or `Accept-Encoding` header
+/// `header`, if it is listed.
+///
+/// For protobuf, only the `io.prometheus.client.MetricFamily` messages are
+/// accepted.
+fn quality(header: &HeaderValue, value: &str) -> Option<f32> {
+    let header = header.to_str().ok()?;
+    header
+        .split(',')
+        .filter_map(|accepted| {
+            let mut params = accepted.split(';').map(str::trim);
+            if !params.next()?.eq_ignore_ascii_case(value) {
+                return None;
+            }
+            let mut quality = 1.0;
+            for param in params {
+                match param
+                    .split_once('=')
+                    .map(|(name, value)| (name.trim(), value.trim()))
+                {
+                    Some(("q", q)) => quality = q.parse().ok()?,
+                    Some(("proto", proto)) if proto != PROTOBUF_PROTO => return None,
+                    _ => {}
+                }
+            }
+            Some(quality)
+        })
+        .reduce(f32::max)
+}
+
+fn addr_from_env() -> Result<SocketAddr> {
+    let host = env::var(OTEL_EXPORTER_PROMETHEUS_HOST)
+        .ok()
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| OTEL_EXPORTER_PROMETHEUS_HOST_DEFAULT.to_string());
+    let port = match env::var(OTEL_EXPORTER_PROMETHEUS_PORT) {
+        Ok(port) if !port.is_empty() => port.parse::<u16>().map_err(|_| {
+            MetricsError::Config(format!(
+                "invalid {OTEL_EXPORTER_PROMETHEUS_PORT} value {port:?}"
+            ))
+        })?,
+        _ => OTEL_EXPORTER_PROMETHEUS_PORT_DEFAULT,
+    };
+
+    // IPv6 addresses can be bracketed, as in URLs.
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    (host, port)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| MetricsError::Config(format!("cannot resolve the host {host:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        for (accept, expected) in [
+            (vec![], Format::Text),
+            (vec!["*/*"], Format::Text),
+            (
+                vec!["application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5"],
+                Format::OpenMetrics,
+            ),
+            (
+                vec!["application/openmetrics-text;q=0.2", "text/plain;q=0.5"],
+                Format::Text,
+            ),
+            (
+                vec!["application/vnd.google.protobuf;proto=io.prometheus.client.MetricFamily;encoding=delimited,application/openmetrics-text;version=1.0.0;q=0.8"],
+                Format::Protobuf,
+            ),
+            (
+                vec!["application/vnd.google.protobuf;proto=other.Message,text/plain;q=0.5"],
+                Format::Text,
+            ),
+        ] {
+            let headers: Vec<_> = accept
+                .iter()
+                .map(|header| HeaderValue::from_static(header))
+                .collect();
+            let headers: Vec<_> = headers.iter().collect();
+            assert_eq!(Format::negotiate(&headers), expected, "{accept:?}");
+        }
+    }
+
+    #[test]
+    fn test_quality() {
+        let quality = |header: &'static str| quality(&HeaderValue::from_static(header), "gzip");
+
+        assert_eq!(quality("deflate, GZIP"), Some(1.0));
+        assert_eq!(quality("gzip;q=0.5, gzip;q=0.7"), Some(0.7));
+        assert_eq!(quality("gzip;q=0, identity"), Some(0.0));
+        assert_eq!(quality("gzipped"), None);
+        assert_eq!(quality("gzip;q=invalid"), None);
+    }
+
+    #[test]
+    fn test_addr_from_env() {
+        for (host, port, expected) in [
+            (Some("127.0.0.1"), None, Some("127.0.0.1:9464")),
+            (Some("0.0.0.0"), Some("8080"), Some("0.0.0.0:8080")),
+            (Some("[::1]"), None, Some("[::1]:9464")),
+            (None, Some("invalid"), None),
+        ] {
+            let addr = temp_env::with_vars(
+                [
+                    (OTEL_EXPORTER_PROMETHEUS_HOST, host),
+                    (OTEL_EXPORTER_PROMETHEUS_PORT, port),
+                ],
+                addr_from_env,
+            );
+            match expected {
+                Some(expected) => assert_eq!(addr.unwrap(), expected.parse().unwrap()),
+                None => assert!(addr.is_err()),
+            }
+        }
+
+        // localhost may resolve to an IPv4 or IPv6 address.
+        let addr = temp_env::with_vars_unset(
+            [OTEL_EXPORTER_PROMETHEUS_HOST, OTEL_EXPORTER_PROMETHEUS_PORT],
+            addr_from_env,
+        );
+        assert!(addr.unwrap().ip().is_loopback());
+    }
+}
@@ -1,19 +1,22 @@
 use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use opentelemetry::metrics::{Meter, MeterProvider as _, Unit};
 use opentelemetry::Key;
 use opentelemetry::KeyValue;
-use opentelemetry_prometheus::{ExporterBuilder, ResourceSelector};
-use opentelemetry_sdk::metrics::{new_view, Aggregation, Instrument, SdkMeterProvider, Stream};
+use opentelemetry_prometheus::{ExporterBuilder, OpenMetricsEncoder, ResourceSelector};
+use opentelemetry_sdk::metrics::{
+    new_view, Aggregation, ExemplarFilter, Instrument, SdkMeterProvider, Stream,
+};
 use opentelemetry_sdk::resource::{
     EnvResourceDetector, SdkProvidedResourceDetector, TelemetryResourceDetector,
 };
 use opentelemetry_sdk::Resource;
 use opentelemetry_semantic_conventions::resource::{SERVICE_NAME, TELEMETRY_SDK_VERSION};
 use prometheus::{Encoder, TextEncoder};
+use protobuf::Message;
 
 #[test]
 fn prometheus_exporter_integration() {
@@ -468,6 +471,161 @@ fn multiple_scopes() {
     gather_and_compare(registry, content, "multi_scope");
 }
 
+#[test]
+fn openmetrics_created_and_exemplars() {
+    let registry = prometheus::Registry::new();
+    let exporter = ExporterBuilder::default()
+        .with_registry(registry.clone())
+        .without_scope_info()
+        .without_target_info()
+        .build()
+        .unwrap();
+    let provider = SdkMeterProvider::builder()
+        .with_reader(exporter)
+        .with_exemplar_filter(ExemplarFilter::AlwaysOn)
+        .build();
+    let meter = provider.meter("testmeter");
+
+    let start = SystemTime::now();
+    let counter = meter
+        .u64_counter("foo")
+        .with_description("a \"simple\" counter")
+        .init();
+    counter.add(3, &[KeyValue::new("A", "B")]);
+    let histogram = meter
+        .f64_histogram("bar")
+        .with_unit(Unit::new("s"))
+        .with_description("a histogram")
+        .init();
+    histogram.record(7.0, &[]);
+    histogram.record(300.0, &[]);
+
+    // The `_created` series and the exemplars are carried by the gathered
+    // metric families.
+    let mut output = Vec::new();
+    OpenMetricsEncoder::new()
+        .encode(&registry.gather(), &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    // They survive the protobuf encoding.
+    let mut encoded = Vec::new();
+    prometheus::ProtobufEncoder::new()
+        .encode(&registry.gather(), &mut encoded)
+        .unwrap();
+    let mut input = protobuf::CodedInputStream::from_bytes(&encoded);
+    let mut families = Vec::new();
+    while !input.eof().unwrap() {
+        families.push(input.read_message().unwrap());
+    }
+    let mut decoded = Vec::new();
+    OpenMetricsEncoder::new()
+        .encode(&families, &mut decoded)
+        .unwrap();
+    assert_eq!(String::from_utf8(decoded).unwrap(), output);
+
+    // Replace the timestamps, which are the last value of the `_created` and
+    // exemplar lines.
+    let start = start.duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
+    let output = output
+        .lines()
+        .map(|line| match line.rsplit_once(' ') {
+            Some((line, timestamp)) if line.contains("_created") || line.contains(" # ") => {
+                let timestamp: f64 = timestamp.parse().unwrap();
+                assert!(timestamp >= start, "{timestamp} >= {start}");
+                format!("{line} <timestamp>")
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let content = fs::read_to_string("./tests/data/openmetrics.txt").unwrap();
+    assert_eq!(output, content.trim_end());
+}
+
+#[test]
+fn exponential_histograms() {
+    fn record(builder: ExporterBuilder) -> prometheus::proto::MetricFamily {
+        let registry = prometheus::Registry::new();
+        let exporter = builder
+            .with_registry(registry.clone())
+            .without_scope_info()
+            .without_target_info()
+            .build()
+            .unwrap();
+        let view = new_view(
+            Instrument::new().name("histogram"),
+            Stream::new().aggregation(Aggregation::Base2ExponentialHistogram {
+                max_size: 160,
+                max_scale: 0,
+                record_min_max: true,
+            }),
+        )
+        .unwrap();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(exporter)
+            .with_view(view)
+            .build();
+        let histogram = provider
+            .meter("testmeter")
+            .f64_histogram("histogram")
+            .init();
+        for value in [1.0, 2.0, 4.0, 0.0, -3.0] {
+            histogram.record(value, &[]);
+        }
+
+        let mut families = registry.gather();
+        assert_eq!(families.len(), 1);
+        families.remove(0)
+    }
+
+    let family = record(ExporterBuilder::default());
+    assert_eq!(family.get_name(), "histogram");
+    assert_eq!(
+        family.get_field_type(),
+        prometheus::proto::MetricType::HISTOGRAM
+    );
+    let histogram = family.get_metric()[0].get_histogram();
+    assert_eq!(histogram.get_sample_count(), 5);
+    assert_eq!(histogram.get_sample_sum(), 4.0);
+    assert!(histogram.get_bucket().is_empty());
+
+    // The native histogram fields survive the protobuf encoding.
+    let mut output = Vec::new();
+    prometheus::ProtobufEncoder::new()
+        .encode(&[family], &mut output)
+        .unwrap();
+    let mut input = protobuf::CodedInputStream::from_bytes(&output);
+    let family: prometheus::proto::MetricFamily = input.read_message().unwrap();
+    let fields = family.get_metric()[0].get_histogram().get_unknown_fields();
+    let field = |number| fields.get(number).unwrap();
+    // schema
+    assert_eq!(field(5).varint, vec![0]);
+    // zero threshold and count
+    assert_eq!(field(6).fixed64, vec![0f64.to_bits()]);
+    assert_eq!(field(7).varint, vec![1]);
+    // negative span at 2 of length 1, with a count of 1
+    assert_eq!(field(9).length_delimited, vec![vec![0x08, 4, 0x10, 1]]);
+    assert_eq!(field(10).length_delimited, vec![vec![2]]);
+    // positive span at 0 of length 3, with counts of 1
+    assert_eq!(field(12).length_delimited, vec![vec![0x08, 0, 0x10, 3]]);
+    assert_eq!(field(13).length_delimited, vec![vec![2, 0, 0]]);
+
+    let family = record(ExporterBuilder::default().without_native_histograms());
+    let histogram = family.get_metric()[0].get_histogram();
+    assert!(histogram.get_unknown_fields().get(5).is_none());
+    let buckets: Vec<_> = histogram
+        .get_bucket()
+        .iter()
+        .map(|bucket| (bucket.get_upper_bound(), bucket.get_cumulative_count()))
+        .collect();
+    assert_eq!(
+        buckets,
+        vec![(-2.0, 1), (0.0, 2), (1.0, 3), (2.0, 4), (4.0, 5),]
+    );
+}
+
 #[test]
 fn duplicate_metrics() {
     struct TestCase {
@@ -823,3 +981,65 @@ fn gather_and_compare_multi(
         "mismatched output in {name}"
     )
 }
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn metrics_service() {
+    use hyper::{body, header, service::Service, Body, Request, StatusCode};
+    use opentelemetry_prometheus::{MetricsService, OPENMETRICS_FORMAT};
+    use std::io::Read;
+
+    let registry = prometheus::Registry::new();
+    let exporter = ExporterBuilder::default()
+        .with_registry(registry.clone())
+        .without_scope_info()
+        .without_target_info()
+        .build()
+        .unwrap();
+    let provider = SdkMeterProvider::builder().with_reader(exporter).build();
+    let counter = provider.meter("testmeter").u64_counter("foo").init();
+    counter.add(3, &[]);
+
+    let mut service = MetricsService::new(registry);
+    let mut get = |path: &str, headers: &[(header::HeaderName, &'static str)]| {
+        let mut request = Request::get(path);
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        service.call(request.body(Body::empty()).unwrap())
+    };
+
+    let response = get("/metrics", &[]).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        prometheus::TEXT_FORMAT
+    );
+    let content = body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(content, "# TYPE foo_total counter\nfoo_total 3\n");
+
+    let response = get(
+        "/metrics",
+        &[
+            (
+                header::ACCEPT,
+                "application/openmetrics-text;version=1.0.0,text/plain;q=0.5",
+            ),
+            (header::ACCEPT_ENCODING, "gzip, deflate"),
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.headers()[header::CONTENT_TYPE], OPENMETRICS_FORMAT);
+    assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+    let content = body::to_bytes(response.into_body()).await.unwrap();
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&content[..])
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert!(decoded.starts_with("# TYPE foo counter\nfoo_total 3\nfoo_created "));
+    assert!(decoded.ends_with("\n# EOF\n"));
+
+    let response = get("/", &[]).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
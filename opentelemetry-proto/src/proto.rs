@@ -77,12 +77,256 @@ pub(crate) mod serializers {
         serializer.serialize_str(&s)
     }
 
+    // receivers must accept both the string and the number form of 64 bit integers
     pub fn deserialize_string_to_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
     where
         D: Deserializer<'de>,
     {
+        struct U64Visitor;
+
+        impl<'de> Visitor<'de> for U64Visitor {
+            type Value = u64;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string or a number representing an unsigned 64 bit integer")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<u64, E>
+            where
+                E: de::Error,
+            {
+                value.parse::<u64>().map_err(E::custom)
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<u64, E>
+            where
+                E: de::Error,
+            {
+                Ok(value)
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<u64, E>
+            where
+                E: de::Error,
+            {
+                u64::try_from(value).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(U64Visitor)
+    }
+
+    pub fn serialize_vec_u64_to_strings<S>(value: &[u64], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(value.iter().map(|v| v.to_string()))
+    }
+
+    pub fn deserialize_strings_to_vec_u64<'de, D>(deserializer: D) -> Result<Vec<u64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct StringOrU64(u64);
+
+        impl<'de> Deserialize<'de> for StringOrU64 {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserialize_string_to_u64(deserializer).map(StringOrU64)
+            }
+        }
+
+        let values: Vec<StringOrU64> = Deserialize::deserialize(deserializer)?;
+        Ok(values.into_iter().map(|v| v.0).collect())
+    }
+
+    pub fn serialize_i64_to_string<S>(value: &i64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let s = value.to_string();
+        serializer.serialize_str(&s)
+    }
+
+    // receivers must accept both the string and the number form of 64 bit integers
+    pub fn deserialize_string_to_i64<'de, D>(deserializer: D) -> Result<i64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct I64Visitor;
+
+        impl<'de> Visitor<'de> for I64Visitor {
+            type Value = i64;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string or a number representing a 64 bit integer")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<i64, E>
+            where
+                E: de::Error,
+            {
+                value.parse::<i64>().map_err(E::custom)
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<i64, E>
+            where
+                E: de::Error,
+            {
+                Ok(value)
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<i64, E>
+            where
+                E: de::Error,
+            {
+                i64::try_from(value).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(I64Visitor)
+    }
+
+    // OTLP/JSON format writes the doubles which aren't finite as strings
+    pub fn serialize_f64<S>(value: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if value.is_nan() {
+            serializer.serialize_str("NaN")
+        } else if *value == f64::INFINITY {
+            serializer.serialize_str("Infinity")
+        } else if *value == f64::NEG_INFINITY {
+            serializer.serialize_str("-Infinity")
+        } else {
+            serializer.serialize_f64(*value)
+        }
+    }
+
+    // receivers must accept both the string and the number form of doubles
+    pub fn deserialize_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct F64Visitor;
+
+        impl<'de> Visitor<'de> for F64Visitor {
+            type Value = f64;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number, \"NaN\", \"Infinity\" or \"-Infinity\"")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<f64, E>
+            where
+                E: de::Error,
+            {
+                match value {
+                    "NaN" => Ok(f64::NAN),
+                    "Infinity" => Ok(f64::INFINITY),
+                    "-Infinity" => Ok(f64::NEG_INFINITY),
+                    _ => value.parse::<f64>().map_err(E::custom),
+                }
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<f64, E>
+            where
+                E: de::Error,
+            {
+                Ok(value)
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<f64, E>
+            where
+                E: de::Error,
+            {
+                Ok(value as f64)
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<f64, E>
+            where
+                E: de::Error,
+            {
+                Ok(value as f64)
+            }
+        }
+
+        deserializer.deserialize_any(F64Visitor)
+    }
+
+    pub fn serialize_option_f64<S>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serialize_f64(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize_option_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<F64>::deserialize(deserializer).map(|value| value.map(|v| v.0))
+    }
+
+    pub fn serialize_vec_f64<S>(value: &[f64], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(value.iter().map(|v| F64(*v)))
+    }
+
+    pub fn deserialize_vec_f64<'de, D>(deserializer: D) -> Result<Vec<f64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let values: Vec<F64> = Deserialize::deserialize(deserializer)?;
+        Ok(values.into_iter().map(|v| v.0).collect())
+    }
+
+    struct F64(f64);
+
+    impl Serialize for F64 {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serialize_f64(&self.0, serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for F64 {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserialize_f64(deserializer).map(F64)
+        }
+    }
+
+    // base64 string <-> bytes conversion
+
+    pub fn serialize_to_base64<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use base64::Engine;
+        serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize_from_base64<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use base64::Engine;
         let s: String = Deserialize::deserialize(deserializer)?;
-        s.parse::<u64>().map_err(de::Error::custom)
+        base64::engine::general_purpose::STANDARD
+            .decode(s)
+            .map_err(de::Error::custom)
     }
 }
 
@@ -11,6 +11,7 @@ pub struct AnyValue {
     /// The value is one of the listed fields. It is valid for all values to be unspecified
     /// in which case this AnyValue is considered to be "empty".
     #[prost(oneof = "any_value::Value", tags = "1, 2, 3, 4, 5, 6, 7")]
+    #[cfg_attr(feature = "with-serde", serde(flatten))]
     pub value: ::core::option::Option<any_value::Value>,
 }
 /// Nested message and enum types in `AnyValue`.
@@ -28,14 +29,35 @@ pub mod any_value {
         #[prost(bool, tag = "2")]
         BoolValue(bool),
         #[prost(int64, tag = "3")]
+        #[cfg_attr(
+            feature = "with-serde",
+            serde(
+                serialize_with = "crate::proto::serializers::serialize_i64_to_string",
+                deserialize_with = "crate::proto::serializers::deserialize_string_to_i64"
+            )
+        )]
         IntValue(i64),
         #[prost(double, tag = "4")]
+        #[cfg_attr(
+            feature = "with-serde",
+            serde(
+                serialize_with = "crate::proto::serializers::serialize_f64",
+                deserialize_with = "crate::proto::serializers::deserialize_f64"
+            )
+        )]
         DoubleValue(f64),
         #[prost(message, tag = "5")]
         ArrayValue(super::ArrayValue),
         #[prost(message, tag = "6")]
         KvlistValue(super::KeyValueList),
         #[prost(bytes, tag = "7")]
+        #[cfg_attr(
+            feature = "with-serde",
+            serde(
+                serialize_with = "crate::proto::serializers::serialize_to_base64",
+                deserialize_with = "crate::proto::serializers::deserialize_from_base64"
+            )
+        )]
         BytesValue(::prost::alloc::vec::Vec<u8>),
     }
 }
@@ -1,4 +1,4 @@
-#[cfg(feature = "gen-tonic")]
+#[cfg(feature = "gen-tonic-messages")]
 pub mod tonic {
     use crate::{
         tonic::{
@@ -130,7 +130,10 @@ pub mod tonic {
                         .map(Into::into)
                         .unwrap_or_default(),
                     scope: Some(log_data.instrumentation.into()),
-                    log_records: vec![log_data.record.into()],
+                    log_records: vec![LogRecord {
+                        dropped_attributes_count: log_data.dropped_attributes_count,
+                        ..log_data.record.into()
+                    }],
                 }],
             }
         }
@@ -53,6 +53,22 @@ fn build_tonic() {
         "resource.v1.Resource",
         "trace.v1.Span.Event",
         "trace.v1.Status",
+        "logs.v1.LogRecord",
+        "logs.v1.ScopeLogs",
+        "logs.v1.ResourceLogs",
+        "metrics.v1.Metric",
+        "metrics.v1.ResourceMetrics",
+        "metrics.v1.ScopeMetrics",
+        "metrics.v1.Gauge",
+        "metrics.v1.Sum",
+        "metrics.v1.Histogram",
+        "metrics.v1.ExponentialHistogram",
+        "metrics.v1.Summary",
+        "metrics.v1.NumberDataPoint",
+        "metrics.v1.HistogramDataPoint",
+        "metrics.v1.ExponentialHistogramDataPoint",
+        "metrics.v1.SummaryDataPoint",
+        "metrics.v1.Exemplar",
     ] {
         builder = builder.type_attribute(
             path,
@@ -68,6 +84,12 @@ fn build_tonic() {
         "trace.v1.Span.trace_id",
         "trace.v1.Span.span_id",
         "trace.v1.Span.parent_span_id",
+        "trace.v1.Span.Link.trace_id",
+        "trace.v1.Span.Link.span_id",
+        "logs.v1.LogRecord.trace_id",
+        "logs.v1.LogRecord.span_id",
+        "metrics.v1.Exemplar.trace_id",
+        "metrics.v1.Exemplar.span_id",
     ] {
         builder = builder
             .field_attribute(path, "#[cfg_attr(feature = \"with-serde\", serde(serialize_with = \"crate::proto::serializers::serialize_to_hex_string\", deserialize_with = \"crate::proto::serializers::deserialize_from_hex_string\"))]")
@@ -81,11 +103,90 @@ fn build_tonic() {
         "trace.v1.Span.start_time_unix_nano",
         "trace.v1.Span.end_time_unix_nano",
         "trace.v1.Span.Event.time_unix_nano",
+        "logs.v1.LogRecord.time_unix_nano",
+        "logs.v1.LogRecord.observed_time_unix_nano",
+        "metrics.v1.NumberDataPoint.start_time_unix_nano",
+        "metrics.v1.NumberDataPoint.time_unix_nano",
+        "metrics.v1.HistogramDataPoint.start_time_unix_nano",
+        "metrics.v1.HistogramDataPoint.time_unix_nano",
+        "metrics.v1.HistogramDataPoint.count",
+        "metrics.v1.ExponentialHistogramDataPoint.start_time_unix_nano",
+        "metrics.v1.ExponentialHistogramDataPoint.time_unix_nano",
+        "metrics.v1.ExponentialHistogramDataPoint.count",
+        "metrics.v1.ExponentialHistogramDataPoint.zero_count",
+        "metrics.v1.SummaryDataPoint.start_time_unix_nano",
+        "metrics.v1.SummaryDataPoint.time_unix_nano",
+        "metrics.v1.SummaryDataPoint.count",
+        "metrics.v1.Exemplar.time_unix_nano",
     ] {
         builder = builder
             .field_attribute(path, "#[cfg_attr(feature = \"with-serde\", serde(serialize_with = \"crate::proto::serializers::serialize_u64_to_string\", deserialize_with = \"crate::proto::serializers::deserialize_string_to_u64\"))]")
     }
 
+    // OTLP/JSON format uses string for 64 bit integers, including the repeated ones
+    for path in [
+        "metrics.v1.HistogramDataPoint.bucket_counts",
+        "metrics.v1.ExponentialHistogramDataPoint.Buckets.bucket_counts",
+    ] {
+        builder = builder
+            .field_attribute(path, "#[cfg_attr(feature = \"with-serde\", serde(serialize_with = \"crate::proto::serializers::serialize_vec_u64_to_strings\", deserialize_with = \"crate::proto::serializers::deserialize_strings_to_vec_u64\"))]")
+    }
+
+    // signed 64 bit integers inside oneofs are strings in OTLP/JSON as well
+    for path in [
+        "common.v1.AnyValue.value.int_value",
+        "metrics.v1.NumberDataPoint.value.as_int",
+        "metrics.v1.Exemplar.value.as_int",
+    ] {
+        builder = builder
+            .field_attribute(path, "#[cfg_attr(feature = \"with-serde\", serde(serialize_with = \"crate::proto::serializers::serialize_i64_to_string\", deserialize_with = \"crate::proto::serializers::deserialize_string_to_i64\"))]")
+    }
+
+    // OTLP/JSON format uses strings for the doubles which aren't finite
+    for path in [
+        "common.v1.AnyValue.value.double_value",
+        "metrics.v1.NumberDataPoint.value.as_double",
+        "metrics.v1.Exemplar.value.as_double",
+        "metrics.v1.ExponentialHistogramDataPoint.zero_threshold",
+        "metrics.v1.SummaryDataPoint.sum",
+        "metrics.v1.SummaryDataPoint.ValueAtQuantile.quantile",
+        "metrics.v1.SummaryDataPoint.ValueAtQuantile.value",
+    ] {
+        builder = builder
+            .field_attribute(path, "#[cfg_attr(feature = \"with-serde\", serde(serialize_with = \"crate::proto::serializers::serialize_f64\", deserialize_with = \"crate::proto::serializers::deserialize_f64\"))]")
+    }
+    for path in [
+        "metrics.v1.HistogramDataPoint.sum",
+        "metrics.v1.HistogramDataPoint.min",
+        "metrics.v1.HistogramDataPoint.max",
+        "metrics.v1.ExponentialHistogramDataPoint.sum",
+        "metrics.v1.ExponentialHistogramDataPoint.min",
+        "metrics.v1.ExponentialHistogramDataPoint.max",
+    ] {
+        builder = builder
+            .field_attribute(path, "#[cfg_attr(feature = \"with-serde\", serde(serialize_with = \"crate::proto::serializers::serialize_option_f64\", deserialize_with = \"crate::proto::serializers::deserialize_option_f64\"))]")
+    }
+    builder = builder
+        .field_attribute("metrics.v1.HistogramDataPoint.explicit_bounds", "#[cfg_attr(feature = \"with-serde\", serde(serialize_with = \"crate::proto::serializers::serialize_vec_f64\", deserialize_with = \"crate::proto::serializers::deserialize_vec_f64\"))]");
+
+    // OTLP/JSON format uses base64 for bytes
+    builder = builder
+        .field_attribute("common.v1.AnyValue.value.bytes_value", "#[cfg_attr(feature = \"with-serde\", serde(serialize_with = \"crate::proto::serializers::serialize_to_base64\", deserialize_with = \"crate::proto::serializers::deserialize_from_base64\"))]");
+
+    // oneof fields are inlined into the parent message in OTLP/JSON format,
+    // e.g. `{"asDouble": 1.0}` instead of `{"value": {"asDouble": 1.0}}`
+    for path in [
+        "common.v1.AnyValue.value",
+        "metrics.v1.Metric.data",
+        "metrics.v1.NumberDataPoint.value",
+        "metrics.v1.Exemplar.value",
+    ] {
+        builder = builder.field_attribute(
+            path,
+            "#[cfg_attr(feature = \"with-serde\", serde(flatten))]",
+        )
+    }
+
     // add custom serializer and deserializer for AnyValue
     builder = builder
         .field_attribute("common.v1.KeyValue.value", "#[cfg_attr(feature =\"with-serde\", serde(serialize_with = \"crate::proto::serializers::serialize_to_value\", deserialize_with = \"crate::proto::serializers::deserialize_from_value\"))]");
@@ -1,8 +1,21 @@
 #[cfg(all(feature = "with-serde", feature = "gen-tonic-messages"))]
 mod json_deserialize {
+    #[cfg(feature = "logs")]
+    use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+    #[cfg(feature = "metrics")]
+    use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
     use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
     use opentelemetry_proto::tonic::common::v1::any_value::Value;
+    #[cfg(any(feature = "logs", feature = "metrics"))]
+    use opentelemetry_proto::tonic::common::v1::AnyValue;
+    #[cfg(feature = "logs")]
+    use opentelemetry_proto::tonic::common::v1::ArrayValue;
     use opentelemetry_proto::tonic::common::v1::KeyValue;
+    #[cfg(feature = "metrics")]
+    use opentelemetry_proto::tonic::metrics::v1::{
+        metric, number_data_point, Gauge, Histogram, HistogramDataPoint, Metric, NumberDataPoint,
+        ResourceMetrics, ScopeMetrics,
+    };
     use opentelemetry_proto::tonic::trace::v1::span::Event;
 
     // copied from example json file
@@ -77,6 +90,330 @@ mod json_deserialize {
     }
     "#;
 
+    // copied from example json file
+    // see https://github.com/open-telemetry/opentelemetry-proto/blob/v1.1.0/examples/logs.json
+    #[cfg(feature = "logs")]
+    const LOGS_JSON: &str = r#"
+    {
+  "resourceLogs": [
+    {
+      "resource": {
+        "attributes": [
+          {
+            "key": "service.name",
+            "value": {
+              "stringValue": "my.service"
+            }
+          }
+        ]
+      },
+      "scopeLogs": [
+        {
+          "scope": {
+            "name": "my.library",
+            "version": "1.0.0"
+          },
+          "logRecords": [
+            {
+              "timeUnixNano": "1544712660300000000",
+              "observedTimeUnixNano": "1544712660300000000",
+              "severityNumber": 10,
+              "severityText": "Information",
+              "traceId": "5B8EFFF798038103D269B633813FC60C",
+              "spanId": "EEE19B7EC3C1B174",
+              "body": {
+                "stringValue": "Example log record"
+              },
+              "attributes": [
+                {
+                  "key": "int.attribute",
+                  "value": {
+                    "intValue": "10"
+                  }
+                },
+                {
+                  "key": "array.attribute",
+                  "value": {
+                    "arrayValue": {
+                      "values": [
+                        {
+                          "stringValue": "many"
+                        },
+                        {
+                          "stringValue": "values"
+                        }
+                      ]
+                    }
+                  }
+                }
+              ]
+            }
+          ]
+        }
+      ]
+    }
+  ]
+}
+    "#;
+
+    // copied from example json file
+    // see https://github.com/open-telemetry/opentelemetry-proto/blob/v1.1.0/examples/metrics.json
+    #[cfg(feature = "metrics")]
+    const METRICS_JSON: &str = r#"
+    {
+  "resourceMetrics": [
+    {
+      "resource": {
+        "attributes": [
+          {
+            "key": "service.name",
+            "value": {
+              "stringValue": "my.service"
+            }
+          }
+        ]
+      },
+      "scopeMetrics": [
+        {
+          "scope": {
+            "name": "my.library",
+            "version": "1.0.0"
+          },
+          "metrics": [
+            {
+              "name": "my.counter",
+              "unit": "1",
+              "description": "I am a Counter",
+              "sum": {
+                "aggregationTemporality": 1,
+                "isMonotonic": true,
+                "dataPoints": [
+                  {
+                    "asDouble": 5,
+                    "startTimeUnixNano": "1544712660300000000",
+                    "timeUnixNano": "1544712660300000000"
+                  }
+                ]
+              }
+            },
+            {
+              "name": "my.histogram",
+              "unit": "1",
+              "description": "I am a Histogram",
+              "histogram": {
+                "aggregationTemporality": 1,
+                "dataPoints": [
+                  {
+                    "startTimeUnixNano": "1544712660300000000",
+                    "timeUnixNano": "1544712660300000000",
+                    "count": 2,
+                    "sum": 2,
+                    "bucketCounts": [1,1],
+                    "explicitBounds": [1],
+                    "min": 0,
+                    "max": 2
+                  }
+                ]
+              }
+            }
+          ]
+        }
+      ]
+    }
+  ]
+}
+    "#;
+
+    #[cfg(feature = "logs")]
+    #[test]
+    fn test_deserialize_logs() {
+        let request: ExportLogsServiceRequest = serde_json::from_str(LOGS_JSON).unwrap();
+        let log_record = &request.resource_logs[0].scope_logs[0].log_records[0];
+        assert_eq!(log_record.time_unix_nano, 1544712660300000000);
+        assert_eq!(
+            log_record.trace_id,
+            hex::decode("5B8EFFF798038103D269B633813FC60C").unwrap()
+        );
+        assert_eq!(log_record.span_id, hex::decode("EEE19B7EC3C1B174").unwrap());
+        assert_eq!(
+            log_record.body.as_ref().unwrap().value,
+            Some(Value::StringValue("Example log record".to_string()))
+        );
+        assert_eq!(
+            log_record.attributes[0].value.as_ref().unwrap().value,
+            Some(Value::IntValue(10))
+        );
+        assert_eq!(
+            log_record.attributes[1].value.as_ref().unwrap().value,
+            Some(Value::ArrayValue(ArrayValue {
+                values: vec![
+                    AnyValue {
+                        value: Some(Value::StringValue("many".to_string()))
+                    },
+                    AnyValue {
+                        value: Some(Value::StringValue("values".to_string()))
+                    },
+                ]
+            }))
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_deserialize_metrics() {
+        let request: ExportMetricsServiceRequest = serde_json::from_str(METRICS_JSON).unwrap();
+        let metrics = &request.resource_metrics[0].scope_metrics[0].metrics;
+        match &metrics[0].data {
+            Some(metric::Data::Sum(sum)) => {
+                assert!(sum.is_monotonic);
+                assert_eq!(
+                    sum.data_points[0].value,
+                    Some(number_data_point::Value::AsDouble(5.0))
+                );
+                assert_eq!(sum.data_points[0].time_unix_nano, 1544712660300000000);
+            }
+            other => panic!("unexpected metric data {:?}", other),
+        }
+        match &metrics[1].data {
+            Some(metric::Data::Histogram(histogram)) => {
+                assert_eq!(histogram.data_points[0].count, 2);
+                assert_eq!(histogram.data_points[0].bucket_counts, vec![1, 1]);
+            }
+            other => panic!("unexpected metric data {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_serialize_metrics() {
+        let request = ExportMetricsServiceRequest {
+            resource_metrics: vec![ResourceMetrics {
+                scope_metrics: vec![ScopeMetrics {
+                    metrics: vec![Metric {
+                        name: "my.gauge".to_string(),
+                        data: Some(metric::Data::Gauge(Gauge {
+                            data_points: vec![NumberDataPoint {
+                                time_unix_nano: 1544712660300000000,
+                                value: Some(number_data_point::Value::AsInt(10)),
+                                ..Default::default()
+                            }],
+                        })),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        let metric = &json["resourceMetrics"][0]["scopeMetrics"][0]["metrics"][0];
+        assert_eq!(metric["name"], "my.gauge");
+        let data_point = &metric["gauge"]["dataPoints"][0];
+        assert_eq!(data_point["timeUnixNano"], "1544712660300000000");
+        assert_eq!(data_point["asInt"], "10");
+
+        let round_trip: ExportMetricsServiceRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, request);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_serialize_non_finite_doubles() {
+        let request = ExportMetricsServiceRequest {
+            resource_metrics: vec![ResourceMetrics {
+                scope_metrics: vec![ScopeMetrics {
+                    metrics: vec![
+                        Metric {
+                            name: "my.gauge".to_string(),
+                            data: Some(metric::Data::Gauge(Gauge {
+                                data_points: vec![NumberDataPoint {
+                                    value: Some(number_data_point::Value::AsDouble(f64::NAN)),
+                                    attributes: vec![KeyValue {
+                                        key: "inf".to_string(),
+                                        value: Some(AnyValue {
+                                            value: Some(Value::DoubleValue(f64::INFINITY)),
+                                        }),
+                                    }],
+                                    ..Default::default()
+                                }],
+                            })),
+                            ..Default::default()
+                        },
+                        Metric {
+                            name: "my.histogram".to_string(),
+                            data: Some(metric::Data::Histogram(Histogram {
+                                data_points: vec![HistogramDataPoint {
+                                    count: 2,
+                                    sum: Some(f64::INFINITY),
+                                    bucket_counts: vec![1, 1],
+                                    explicit_bounds: vec![f64::NEG_INFINITY],
+                                    min: Some(f64::NEG_INFINITY),
+                                    max: Some(1.5),
+                                    ..Default::default()
+                                }],
+                                aggregation_temporality: 1,
+                            })),
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        let metrics = &json["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        let gauge_point = &metrics[0]["gauge"]["dataPoints"][0];
+        assert_eq!(gauge_point["asDouble"], "NaN");
+        assert_eq!(
+            gauge_point["attributes"][0]["value"]["doubleValue"],
+            "Infinity"
+        );
+        let histogram_point = &metrics[1]["histogram"]["dataPoints"][0];
+        assert_eq!(histogram_point["sum"], "Infinity");
+        assert_eq!(histogram_point["explicitBounds"][0], "-Infinity");
+        assert_eq!(histogram_point["min"], "-Infinity");
+        assert_eq!(histogram_point["max"], 1.5);
+
+        let round_trip: ExportMetricsServiceRequest = serde_json::from_value(json).unwrap();
+        let metrics = &round_trip.resource_metrics[0].scope_metrics[0].metrics;
+        match &metrics[0].data {
+            Some(metric::Data::Gauge(gauge)) => {
+                assert!(matches!(
+                    gauge.data_points[0].value,
+                    Some(number_data_point::Value::AsDouble(value)) if value.is_nan()
+                ));
+                assert_eq!(
+                    gauge.data_points[0].attributes[0].value,
+                    Some(AnyValue {
+                        value: Some(Value::DoubleValue(f64::INFINITY)),
+                    })
+                );
+            }
+            other => panic!("unexpected metric data {:?}", other),
+        }
+        assert_eq!(
+            metrics[1],
+            request.resource_metrics[0].scope_metrics[0].metrics[1]
+        );
+    }
+
+    #[test]
+    fn test_serialize_traces() {
+        let request: ExportTraceServiceRequest = serde_json::from_str(TRACES_JSON).unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        let span = &json["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["traceId"], "5b8efff798038103d269b633813fc60c");
+        assert_eq!(span["spanId"], "eee19b7ec3c1b174");
+        assert_eq!(span["startTimeUnixNano"], "1544712660000000000");
+        assert_eq!(span["attributes"][0]["value"]["stringValue"], "some value");
+
+        let round_trip: ExportTraceServiceRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, request);
+    }
+
     #[test]
     fn test_deserialize_traces() {
         let request: ExportTraceServiceRequest = serde_json::from_str(TRACES_JSON).unwrap();
@@ -134,6 +471,28 @@ mod json_deserialize {
             .unwrap();
             assert_eq!(value, Value::DoubleValue(123.456));
         }
+        // ints encoded as strings
+        {
+            let value: Value = serde_json::from_str(
+                r#"
+            {
+              "intValue": "123"
+            }"#,
+            )
+            .unwrap();
+            assert_eq!(value, Value::IntValue(123));
+        }
+        // bytes
+        {
+            let value: Value = serde_json::from_str(
+                r#"
+            {
+              "bytesValue": "aGVsbG8="
+            }"#,
+            )
+            .unwrap();
+            assert_eq!(value, Value::BytesValue(b"hello".to_vec()));
+        }
         // todo(zhongyang): add tests for arrays and objects(need an example from other language)
     }
 
@@ -37,6 +37,10 @@ impl MetricReader for SharedReader {
         self.0.register_pipeline(pipeline)
     }
 
+    fn cardinality_limit(&self, kind: InstrumentKind) -> Option<usize> {
+        self.0.cardinality_limit(kind)
+    }
+
     fn collect(&self, rm: &mut ResourceMetrics) -> Result<()> {
         self.0.collect(rm)
     }
@@ -0,0 +1,17 @@
+use std::{env, process::Command};
+
+// Exposes the version of the compiler building the SDK to the process resource
+// detector, as `OTEL_RUSTC_VERSION`, e.g. `rustc 1.75.0 (82e1608df 2023-12-21)`.
+fn main() {
+    println!("cargo:rerun-if-env-changed=RUSTC");
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=OTEL_RUSTC_VERSION={}", version.trim());
+}
@@ -26,6 +26,7 @@ pub trait LogExporter: Send + Sync + Debug {
 /// `LogData` associates a [`LogRecord`] with a [`Resource`] and
 /// [`InstrumentationLibrary`].
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct LogData {
     /// Log record
     pub record: LogRecord,
@@ -33,6 +34,9 @@ pub struct LogData {
     pub resource: Cow<'static, Resource>,
     /// Instrumentation details for the emitter who produced this `LogData`.
     pub instrumentation: InstrumentationLibrary,
+    /// The number of attributes of the record dropped because they were over
+    /// the configured limit.
+    pub dropped_attributes_count: u32,
 }
 
 /// Describes the result of an export.
@@ -4,6 +4,13 @@
 #[cfg_attr(docsrs, doc(cfg(feature = "logs")))]
 pub mod logs;
 
+#[cfg(all(
+    feature = "persistent_queue",
+    any(feature = "trace", feature = "logs", feature = "metrics")
+))]
+#[cfg_attr(docsrs, doc(cfg(feature = "persistent_queue")))]
+pub mod persistent;
+
 #[cfg(feature = "trace")]
 #[cfg_attr(docsrs, doc(cfg(feature = "trace")))]
 pub mod trace;
@@ -0,0 +1,1122 @@
+//! The serialization of the batches stored in the queue.
+//!
+//! The batches are stored as JSON documents mirroring the SDK types, with the
+//! resources shared by the items of a batch stored once.
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use opentelemetry::{Array, InstrumentationLibrary, Key, KeyValue, StringValue, Value};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Resource;
+
+/// A `f64` stored as a JSON number, or as `"NaN"`, `"Infinity"` or
+/// `"-Infinity"` which JSON numbers can't represent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Float(f64);
+
+impl From<f64> for Float {
+    fn from(value: f64) -> Self {
+        Float(value)
+    }
+}
+
+impl From<Float> for f64 {
+    fn from(value: Float) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for Float {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            value if value.is_finite() => serializer.serialize_f64(value),
+            value if value.is_nan() => serializer.serialize_str("NaN"),
+            value if value > 0.0 => serializer.serialize_str("Infinity"),
+            _ => serializer.serialize_str("-Infinity"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Float {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FloatVisitor;
+
+        impl<'de> de::Visitor<'de> for FloatVisitor {
+            type Value = Float;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a number, \"NaN\", \"Infinity\" or \"-Infinity\"")
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Float, E> {
+                Ok(Float(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Float, E> {
+                Ok(Float(value as f64))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Float, E> {
+                Ok(Float(value as f64))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Float, E> {
+                match value {
+                    "NaN" => Ok(Float(f64::NAN)),
+                    "Infinity" => Ok(Float(f64::INFINITY)),
+                    "-Infinity" => Ok(Float(f64::NEG_INFINITY)),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(FloatVisitor)
+    }
+}
+
+fn encode_floats(values: &[f64]) -> Vec<Float> {
+    values.iter().copied().map(Float).collect()
+}
+
+fn decode_floats(values: Vec<Float>) -> Vec<f64> {
+    values.into_iter().map(f64::from).collect()
+}
+
+#[derive(Serialize, Deserialize)]
+enum ValueRepr {
+    Bool(bool),
+    I64(i64),
+    F64(Float),
+    String(String),
+    BoolArray(Vec<bool>),
+    I64Array(Vec<i64>),
+    F64Array(Vec<Float>),
+    StringArray(Vec<String>),
+}
+
+impl From<&Value> for ValueRepr {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Bool(b) => ValueRepr::Bool(*b),
+            Value::I64(i) => ValueRepr::I64(*i),
+            Value::F64(f) => ValueRepr::F64(Float(*f)),
+            Value::String(s) => ValueRepr::String(s.as_str().to_owned()),
+            Value::Array(Array::Bool(b)) => ValueRepr::BoolArray(b.clone()),
+            Value::Array(Array::I64(i)) => ValueRepr::I64Array(i.clone()),
+            Value::Array(Array::F64(f)) => ValueRepr::F64Array(encode_floats(f)),
+            Value::Array(Array::String(s)) => {
+                ValueRepr::StringArray(s.iter().map(|s| s.as_str().to_owned()).collect())
+            }
+        }
+    }
+}
+
+impl From<ValueRepr> for Value {
+    fn from(value: ValueRepr) -> Self {
+        match value {
+            ValueRepr::Bool(b) => Value::Bool(b),
+            ValueRepr::I64(i) => Value::I64(i),
+            ValueRepr::F64(f) => Value::F64(f.into()),
+            ValueRepr::String(s) => Value::String(s.into()),
+            ValueRepr::BoolArray(b) => Value::Array(Array::Bool(b)),
+            ValueRepr::I64Array(i) => Value::Array(Array::I64(i)),
+            ValueRepr::F64Array(f) => Value::Array(Array::F64(decode_floats(f))),
+            ValueRepr::StringArray(s) => Value::Array(Array::String(
+                s.into_iter().map(StringValue::from).collect(),
+            )),
+        }
+    }
+}
+
+type AttributesRepr = Vec<(String, ValueRepr)>;
+
+fn encode_attributes<'a>(
+    attributes: impl IntoIterator<Item = (&'a Key, &'a Value)>,
+) -> AttributesRepr {
+    attributes
+        .into_iter()
+        .map(|(key, value)| (key.as_str().to_owned(), value.into()))
+        .collect()
+}
+
+fn encode_key_values(attributes: &[KeyValue]) -> AttributesRepr {
+    encode_attributes(attributes.iter().map(|kv| (&kv.key, &kv.value)))
+}
+
+fn decode_attributes(attributes: AttributesRepr) -> Vec<KeyValue> {
+    attributes
+        .into_iter()
+        .map(|(key, value)| KeyValue::new(key, Value::from(value)))
+        .collect()
+}
+
+#[derive(Serialize, Deserialize)]
+struct ResourceRepr {
+    attributes: AttributesRepr,
+    schema_url: Option<String>,
+}
+
+impl From<&Resource> for ResourceRepr {
+    fn from(resource: &Resource) -> Self {
+        ResourceRepr {
+            attributes: encode_attributes(resource.iter()),
+            schema_url: resource.schema_url().map(str::to_owned),
+        }
+    }
+}
+
+impl From<ResourceRepr> for Resource {
+    fn from(resource: ResourceRepr) -> Self {
+        let attributes = decode_attributes(resource.attributes);
+        match resource.schema_url {
+            Some(schema_url) => Resource::from_schema_url(attributes, schema_url),
+            None => Resource::new(attributes),
+        }
+    }
+}
+
+/// The resources of a batch of spans or log records, each stored once.
+#[cfg(any(feature = "trace", feature = "logs"))]
+#[derive(Default, Serialize, Deserialize)]
+struct Resources(Vec<ResourceRepr>);
+
+#[cfg(any(feature = "trace", feature = "logs"))]
+impl Resources {
+    /// Returns the index of `resource`, `seen` holding the resources already
+    /// stored.
+    fn index<'a>(&mut self, seen: &mut Vec<&'a Resource>, resource: &'a Resource) -> usize {
+        match seen.iter().position(|r| *r == resource) {
+            Some(index) => index,
+            None => {
+                seen.push(resource);
+                self.0.push(resource.into());
+                self.0.len() - 1
+            }
+        }
+    }
+
+    fn decode(self) -> Vec<Resource> {
+        self.0.into_iter().map(Resource::from).collect()
+    }
+}
+
+#[cfg(any(feature = "trace", feature = "logs"))]
+fn resource_at(resources: &[Resource], index: usize) -> serde_json::Result<Resource> {
+    resources
+        .get(index)
+        .cloned()
+        .ok_or_else(|| serde::de::Error::custom(format!("missing resource {} in the batch", index)))
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScopeRepr {
+    name: String,
+    version: Option<String>,
+    schema_url: Option<String>,
+    attributes: AttributesRepr,
+}
+
+impl From<&InstrumentationLibrary> for ScopeRepr {
+    fn from(scope: &InstrumentationLibrary) -> Self {
+        ScopeRepr {
+            name: scope.name.to_string(),
+            version: scope.version.as_ref().map(|v| v.to_string()),
+            schema_url: scope.schema_url.as_ref().map(|url| url.to_string()),
+            attributes: encode_key_values(&scope.attributes),
+        }
+    }
+}
+
+impl From<ScopeRepr> for InstrumentationLibrary {
+    fn from(scope: ScopeRepr) -> Self {
+        InstrumentationLibrary::new(
+            scope.name,
+            scope.version,
+            scope.schema_url,
+            Some(decode_attributes(scope.attributes)),
+        )
+    }
+}
+
+/// Nanoseconds since the unix epoch.
+fn encode_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+fn decode_time(nanos: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_nanos(nanos)
+}
+
+#[cfg(feature = "trace")]
+pub(super) use self::trace::{decode_spans, encode_spans};
+
+#[cfg(feature = "trace")]
+mod trace {
+    use super::*;
+    use crate::export::trace::SpanData;
+    use crate::trace::{SpanEvents, SpanLinks};
+    use opentelemetry::trace::{
+        Event, Link, SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceState,
+    };
+    use std::borrow::Cow;
+    use std::str::FromStr;
+
+    #[derive(Serialize, Deserialize)]
+    struct SpanContextRepr {
+        trace_id: [u8; 16],
+        span_id: [u8; 8],
+        trace_flags: u8,
+        is_remote: bool,
+        trace_state: String,
+    }
+
+    impl From<&SpanContext> for SpanContextRepr {
+        fn from(cx: &SpanContext) -> Self {
+            SpanContextRepr {
+                trace_id: cx.trace_id().to_bytes(),
+                span_id: cx.span_id().to_bytes(),
+                trace_flags: cx.trace_flags().to_u8(),
+                is_remote: cx.is_remote(),
+                trace_state: cx.trace_state().header(),
+            }
+        }
+    }
+
+    impl From<SpanContextRepr> for SpanContext {
+        fn from(cx: SpanContextRepr) -> Self {
+            SpanContext::new(
+                TraceId::from_bytes(cx.trace_id),
+                SpanId::from_bytes(cx.span_id),
+                TraceFlags::new(cx.trace_flags),
+                cx.is_remote,
+                TraceState::from_str(&cx.trace_state).unwrap_or_default(),
+            )
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum SpanKindRepr {
+        Client,
+        Server,
+        Producer,
+        Consumer,
+        Internal,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum StatusRepr {
+        Unset,
+        Error(String),
+        Ok,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct EventRepr {
+        name: String,
+        timestamp: u64,
+        attributes: AttributesRepr,
+        dropped_attributes_count: u32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct LinkRepr {
+        span_context: SpanContextRepr,
+        attributes: AttributesRepr,
+        dropped_attributes_count: u32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct SpanRepr {
+        span_context: SpanContextRepr,
+        parent_span_id: [u8; 8],
+        span_kind: SpanKindRepr,
+        name: String,
+        start_time: u64,
+        end_time: u64,
+        attributes: AttributesRepr,
+        dropped_attributes_count: u32,
+        events: Vec<EventRepr>,
+        dropped_events_count: u32,
+        links: Vec<LinkRepr>,
+        dropped_links_count: u32,
+        status: StatusRepr,
+        resource: usize,
+        instrumentation_lib: ScopeRepr,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct SpansRepr {
+        resources: Resources,
+        spans: Vec<SpanRepr>,
+    }
+
+    pub(in crate::export::persistent) fn encode_spans(
+        batch: &[SpanData],
+    ) -> serde_json::Result<Vec<u8>> {
+        let mut resources = Resources::default();
+        let mut seen = Vec::new();
+        let spans = batch
+            .iter()
+            .map(|span| SpanRepr {
+                span_context: (&span.span_context).into(),
+                parent_span_id: span.parent_span_id.to_bytes(),
+                span_kind: match span.span_kind {
+                    SpanKind::Client => SpanKindRepr::Client,
+                    SpanKind::Server => SpanKindRepr::Server,
+                    SpanKind::Producer => SpanKindRepr::Producer,
+                    SpanKind::Consumer => SpanKindRepr::Consumer,
+                    SpanKind::Internal => SpanKindRepr::Internal,
+                },
+                name: span.name.to_string(),
+                start_time: encode_time(span.start_time),
+                end_time: encode_time(span.end_time),
+                attributes: encode_key_values(&span.attributes),
+                dropped_attributes_count: span.dropped_attributes_count,
+                events: span
+                    .events
+                    .iter()
+                    .map(|event| EventRepr {
+                        name: event.name.to_string(),
+                        timestamp: encode_time(event.timestamp),
+                        attributes: encode_key_values(&event.attributes),
+                        dropped_attributes_count: event.dropped_attributes_count,
+                    })
+                    .collect(),
+                dropped_events_count: span.events.dropped_count,
+                links: span
+                    .links
+                    .iter()
+                    .map(|link| LinkRepr {
+                        span_context: (&link.span_context).into(),
+                        attributes: encode_key_values(&link.attributes),
+                        dropped_attributes_count: link.dropped_attributes_count,
+                    })
+                    .collect(),
+                dropped_links_count: span.links.dropped_count,
+                status: match &span.status {
+                    Status::Unset => StatusRepr::Unset,
+                    Status::Error { description } => StatusRepr::Error(description.to_string()),
+                    Status::Ok => StatusRepr::Ok,
+                },
+                resource: resources.index(&mut seen, &span.resource),
+                instrumentation_lib: (&span.instrumentation_lib).into(),
+            })
+            .collect();
+
+        serde_json::to_vec(&SpansRepr { resources, spans })
+    }
+
+    pub(in crate::export::persistent) fn decode_spans(
+        bytes: &[u8],
+    ) -> serde_json::Result<Vec<SpanData>> {
+        let batch: SpansRepr = serde_json::from_slice(bytes)?;
+        let resources = batch.resources.decode();
+
+        batch
+            .spans
+            .into_iter()
+            .map(|span| {
+                let mut events = SpanEvents::default();
+                events.events = span
+                    .events
+                    .into_iter()
+                    .map(|event| {
+                        Event::new(
+                            event.name,
+                            decode_time(event.timestamp),
+                            decode_attributes(event.attributes),
+                            event.dropped_attributes_count,
+                        )
+                    })
+                    .collect();
+                events.dropped_count = span.dropped_events_count;
+
+                let mut links = SpanLinks::default();
+                links.links = span
+                    .links
+                    .into_iter()
+                    .map(|link| {
+                        Link::new(
+                            link.span_context.into(),
+                            decode_attributes(link.attributes),
+                            link.dropped_attributes_count,
+                        )
+                    })
+                    .collect();
+                links.dropped_count = span.dropped_links_count;
+
+                Ok(SpanData {
+                    span_context: span.span_context.into(),
+                    parent_span_id: SpanId::from_bytes(span.parent_span_id),
+                    span_kind: match span.span_kind {
+                        SpanKindRepr::Client => SpanKind::Client,
+                        SpanKindRepr::Server => SpanKind::Server,
+                        SpanKindRepr::Producer => SpanKind::Producer,
+                        SpanKindRepr::Consumer => SpanKind::Consumer,
+                        SpanKindRepr::Internal => SpanKind::Internal,
+                    },
+                    name: span.name.into(),
+                    start_time: decode_time(span.start_time),
+                    end_time: decode_time(span.end_time),
+                    attributes: decode_attributes(span.attributes),
+                    dropped_attributes_count: span.dropped_attributes_count,
+                    events,
+                    links,
+                    status: match span.status {
+                        StatusRepr::Unset => Status::Unset,
+                        StatusRepr::Error(description) => Status::error(description),
+                        StatusRepr::Ok => Status::Ok,
+                    },
+                    resource: Cow::Owned(resource_at(&resources, span.resource)?),
+                    instrumentation_lib: span.instrumentation_lib.into(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "logs")]
+pub(super) use self::logs::{decode_logs, encode_logs};
+
+#[cfg(feature = "logs")]
+mod logs {
+    use super::*;
+    use crate::export::logs::LogData;
+    use opentelemetry::logs::{AnyValue, LogRecord, Severity, TraceContext};
+    use opentelemetry::trace::{SpanContext, SpanId, TraceFlags, TraceId, TraceState};
+    use std::borrow::Cow;
+
+    const SEVERITIES: [Severity; 24] = [
+        Severity::Trace,
+        Severity::Trace2,
+        Severity::Trace3,
+        Severity::Trace4,
+        Severity::Debug,
+        Severity::Debug2,
+        Severity::Debug3,
+        Severity::Debug4,
+        Severity::Info,
+        Severity::Info2,
+        Severity::Info3,
+        Severity::Info4,
+        Severity::Warn,
+        Severity::Warn2,
+        Severity::Warn3,
+        Severity::Warn4,
+        Severity::Error,
+        Severity::Error2,
+        Severity::Error3,
+        Severity::Error4,
+        Severity::Fatal,
+        Severity::Fatal2,
+        Severity::Fatal3,
+        Severity::Fatal4,
+    ];
+
+    #[derive(Serialize, Deserialize)]
+    enum AnyValueRepr {
+        Int(i64),
+        Double(Float),
+        String(String),
+        Boolean(bool),
+        Bytes(Vec<u8>),
+        ListAny(Vec<AnyValueRepr>),
+        Map(Vec<(String, AnyValueRepr)>),
+    }
+
+    impl From<&AnyValue> for AnyValueRepr {
+        fn from(value: &AnyValue) -> Self {
+            match value {
+                AnyValue::Int(i) => AnyValueRepr::Int(*i),
+                AnyValue::Double(d) => AnyValueRepr::Double(Float(*d)),
+                AnyValue::String(s) => AnyValueRepr::String(s.as_str().to_owned()),
+                AnyValue::Boolean(b) => AnyValueRepr::Boolean(*b),
+                AnyValue::Bytes(b) => AnyValueRepr::Bytes(b.clone()),
+                AnyValue::ListAny(list) => {
+                    AnyValueRepr::ListAny(list.iter().map(AnyValueRepr::from).collect())
+                }
+                AnyValue::Map(map) => AnyValueRepr::Map(
+                    map.iter()
+                        .map(|(key, value)| (key.as_str().to_owned(), value.into()))
+                        .collect(),
+                ),
+            }
+        }
+    }
+
+    impl From<AnyValueRepr> for AnyValue {
+        fn from(value: AnyValueRepr) -> Self {
+            match value {
+                AnyValueRepr::Int(i) => AnyValue::Int(i),
+                AnyValueRepr::Double(d) => AnyValue::Double(d.into()),
+                AnyValueRepr::String(s) => AnyValue::String(s.into()),
+                AnyValueRepr::Boolean(b) => AnyValue::Boolean(b),
+                AnyValueRepr::Bytes(b) => AnyValue::Bytes(b),
+                AnyValueRepr::ListAny(list) => {
+                    AnyValue::ListAny(list.into_iter().map(AnyValue::from).collect())
+                }
+                AnyValueRepr::Map(map) => AnyValue::Map(
+                    map.into_iter()
+                        .map(|(key, value)| (Key::from(key), value.into()))
+                        .collect(),
+                ),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct TraceContextRepr {
+        trace_id: [u8; 16],
+        span_id: [u8; 8],
+        trace_flags: Option<u8>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct LogRepr {
+        event_name: Option<String>,
+        timestamp: Option<u64>,
+        observed_timestamp: u64,
+        trace_context: Option<TraceContextRepr>,
+        severity_text: Option<String>,
+        severity_number: Option<u8>,
+        body: Option<AnyValueRepr>,
+        attributes: Option<Vec<(String, AnyValueRepr)>>,
+        resource: usize,
+        instrumentation: ScopeRepr,
+        #[serde(default)]
+        dropped_attributes_count: u32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct LogsRepr {
+        resources: Resources,
+        logs: Vec<LogRepr>,
+    }
+
+    pub(in crate::export::persistent) fn encode_logs(
+        batch: &[LogData],
+    ) -> serde_json::Result<Vec<u8>> {
+        let mut resources = Resources::default();
+        let mut seen = Vec::new();
+        let logs = batch
+            .iter()
+            .map(|log| {
+                let record = &log.record;
+                LogRepr {
+                    event_name: record.event_name.as_ref().map(|name| name.to_string()),
+                    timestamp: record.timestamp.map(encode_time),
+                    observed_timestamp: encode_time(record.observed_timestamp),
+                    trace_context: record.trace_context.as_ref().map(|cx| TraceContextRepr {
+                        trace_id: cx.trace_id.to_bytes(),
+                        span_id: cx.span_id.to_bytes(),
+                        trace_flags: cx.trace_flags.map(TraceFlags::to_u8),
+                    }),
+                    severity_text: record.severity_text.as_ref().map(|text| text.to_string()),
+                    severity_number: record.severity_number.map(|severity| severity as u8),
+                    body: record.body.as_ref().map(AnyValueRepr::from),
+                    attributes: record.attributes.as_ref().map(|attributes| {
+                        attributes
+                            .iter()
+                            .map(|(key, value)| (key.as_str().to_owned(), value.into()))
+                            .collect()
+                    }),
+                    resource: resources.index(&mut seen, &log.resource),
+                    instrumentation: (&log.instrumentation).into(),
+                    dropped_attributes_count: log.dropped_attributes_count,
+                }
+            })
+            .collect();
+
+        serde_json::to_vec(&LogsRepr { resources, logs })
+    }
+
+    pub(in crate::export::persistent) fn decode_logs(
+        bytes: &[u8],
+    ) -> serde_json::Result<Vec<LogData>> {
+        let batch: LogsRepr = serde_json::from_slice(bytes)?;
+        let resources = batch.resources.decode();
+
+        batch
+            .logs
+            .into_iter()
+            .map(|log| {
+                let mut record = LogRecord::default();
+                record.event_name = log.event_name.map(Into::into);
+                record.timestamp = log.timestamp.map(decode_time);
+                record.observed_timestamp = decode_time(log.observed_timestamp);
+                record.trace_context = log.trace_context.map(|cx| {
+                    let mut trace_context = TraceContext::from(&SpanContext::new(
+                        TraceId::from_bytes(cx.trace_id),
+                        SpanId::from_bytes(cx.span_id),
+                        TraceFlags::new(cx.trace_flags.unwrap_or_default()),
+                        false,
+                        TraceState::NONE,
+                    ));
+                    trace_context.trace_flags = cx.trace_flags.map(TraceFlags::new);
+                    trace_context
+                });
+                record.severity_text = log.severity_text.map(Into::into);
+                record.severity_number = log
+                    .severity_number
+                    .and_then(|number| SEVERITIES.get(usize::from(number).checked_sub(1)?))
+                    .copied();
+                record.body = log.body.map(AnyValue::from);
+                record.attributes = log.attributes.map(|attributes| {
+                    attributes
+                        .into_iter()
+                        .map(|(key, value)| (Key::from(key), value.into()))
+                        .collect()
+                });
+
+                Ok(LogData {
+                    record,
+                    resource: Cow::Owned(resource_at(&resources, log.resource)?),
+                    instrumentation: log.instrumentation.into(),
+                    dropped_attributes_count: log.dropped_attributes_count,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "metrics")]
+pub(super) use self::metrics::{decode_metrics, encode_metrics};
+
+#[cfg(feature = "metrics")]
+mod metrics {
+    use super::*;
+    use crate::metrics::data::{
+        Aggregation, DataPoint, Exemplar, ExponentialBucket, ExponentialHistogram,
+        ExponentialHistogramDataPoint, Gauge, Histogram, HistogramDataPoint, Metric,
+        ResourceMetrics, ScopeMetrics, Sum, Temporality,
+    };
+    use crate::AttributeSet;
+    use opentelemetry::metrics::Unit;
+    use std::fmt::Debug;
+
+    #[derive(Serialize, Deserialize)]
+    enum TemporalityRepr {
+        Cumulative,
+        Delta,
+    }
+
+    impl From<Temporality> for TemporalityRepr {
+        fn from(temporality: Temporality) -> Self {
+            match temporality {
+                Temporality::Delta => TemporalityRepr::Delta,
+                _ => TemporalityRepr::Cumulative,
+            }
+        }
+    }
+
+    impl From<TemporalityRepr> for Temporality {
+        fn from(temporality: TemporalityRepr) -> Self {
+            match temporality {
+                TemporalityRepr::Cumulative => Temporality::Cumulative,
+                TemporalityRepr::Delta => Temporality::Delta,
+            }
+        }
+    }
+
+    fn encode_attribute_set(attributes: &AttributeSet) -> AttributesRepr {
+        encode_attributes(attributes.iter())
+    }
+
+    fn decode_attribute_set(attributes: AttributesRepr) -> AttributeSet {
+        AttributeSet::from(&decode_attributes(attributes)[..])
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct ExemplarRepr<T> {
+        filtered_attributes: AttributesRepr,
+        time: u64,
+        value: T,
+        span_id: [u8; 8],
+        trace_id: [u8; 16],
+    }
+
+    impl<T: Copy + Into<R>, R> From<&Exemplar<T>> for ExemplarRepr<R> {
+        fn from(exemplar: &Exemplar<T>) -> Self {
+            ExemplarRepr {
+                filtered_attributes: encode_key_values(&exemplar.filtered_attributes),
+                time: encode_time(exemplar.time),
+                value: exemplar.value.into(),
+                span_id: exemplar.span_id,
+                trace_id: exemplar.trace_id,
+            }
+        }
+    }
+
+    impl<T, R: Into<T>> From<ExemplarRepr<R>> for Exemplar<T> {
+        fn from(exemplar: ExemplarRepr<R>) -> Self {
+            Exemplar {
+                filtered_attributes: decode_attributes(exemplar.filtered_attributes),
+                time: decode_time(exemplar.time),
+                value: exemplar.value.into(),
+                span_id: exemplar.span_id,
+                trace_id: exemplar.trace_id,
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct DataPointRepr<T> {
+        attributes: AttributesRepr,
+        start_time: Option<u64>,
+        time: Option<u64>,
+        value: T,
+        exemplars: Vec<ExemplarRepr<T>>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct HistogramDataPointRepr<T> {
+        attributes: AttributesRepr,
+        start_time: u64,
+        time: u64,
+        count: u64,
+        bounds: Vec<Float>,
+        bucket_counts: Vec<u64>,
+        min: Option<T>,
+        max: Option<T>,
+        sum: T,
+        exemplars: Vec<ExemplarRepr<T>>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct ExponentialHistogramDataPointRepr<T> {
+        attributes: AttributesRepr,
+        start_time: u64,
+        time: u64,
+        count: usize,
+        min: Option<T>,
+        max: Option<T>,
+        sum: T,
+        scale: i8,
+        zero_count: u64,
+        positive_bucket: (i32, Vec<u64>),
+        negative_bucket: (i32, Vec<u64>),
+        zero_threshold: Float,
+        exemplars: Vec<ExemplarRepr<T>>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum AggregationRepr<T> {
+        Gauge(Vec<DataPointRepr<T>>),
+        Sum {
+            data_points: Vec<DataPointRepr<T>>,
+            temporality: TemporalityRepr,
+            is_monotonic: bool,
+        },
+        Histogram {
+            data_points: Vec<HistogramDataPointRepr<T>>,
+            temporality: TemporalityRepr,
+        },
+        ExponentialHistogram {
+            data_points: Vec<ExponentialHistogramDataPointRepr<T>>,
+            temporality: TemporalityRepr,
+        },
+    }
+
+    /// The aggregations, by type of value.
+    #[derive(Serialize, Deserialize)]
+    enum DataRepr {
+        U64(AggregationRepr<u64>),
+        I64(AggregationRepr<i64>),
+        F64(AggregationRepr<Float>),
+    }
+
+    fn encode_data_points<T: Copy + Into<R>, R>(
+        data_points: &[DataPoint<T>],
+    ) -> Vec<DataPointRepr<R>> {
+        data_points
+            .iter()
+            .map(|dp| DataPointRepr {
+                attributes: encode_attribute_set(&dp.attributes),
+                start_time: dp.start_time.map(encode_time),
+                time: dp.time.map(encode_time),
+                value: dp.value.into(),
+                exemplars: dp.exemplars.iter().map(ExemplarRepr::from).collect(),
+            })
+            .collect()
+    }
+
+    fn decode_data_points<T, R: Into<T>>(data_points: Vec<DataPointRepr<R>>) -> Vec<DataPoint<T>> {
+        data_points
+            .into_iter()
+            .map(|dp| DataPoint {
+                attributes: decode_attribute_set(dp.attributes),
+                start_time: dp.start_time.map(decode_time),
+                time: dp.time.map(decode_time),
+                value: dp.value.into(),
+                exemplars: dp.exemplars.into_iter().map(Exemplar::from).collect(),
+            })
+            .collect()
+    }
+
+    /// Serializes `data` if it aggregates values of type `T`, stored as `R`.
+    fn encode_aggregation<T, R>(data: &dyn Aggregation) -> Option<AggregationRepr<R>>
+    where
+        T: Copy + Into<R> + 'static,
+    {
+        let data = data.as_any();
+        if let Some(gauge) = data.downcast_ref::<Gauge<T>>() {
+            Some(AggregationRepr::Gauge(encode_data_points(
+                &gauge.data_points,
+            )))
+        } else if let Some(sum) = data.downcast_ref::<Sum<T>>() {
+            Some(AggregationRepr::Sum {
+                data_points: encode_data_points(&sum.data_points),
+                temporality: sum.temporality.into(),
+                is_monotonic: sum.is_monotonic,
+            })
+        } else if let Some(histogram) = data.downcast_ref::<Histogram<T>>() {
+            Some(AggregationRepr::Histogram {
+                data_points: histogram
+                    .data_points
+                    .iter()
+                    .map(|dp| HistogramDataPointRepr {
+                        attributes: encode_attribute_set(&dp.attributes),
+                        start_time: encode_time(dp.start_time),
+                        time: encode_time(dp.time),
+                        count: dp.count,
+                        bounds: encode_floats(&dp.bounds),
+                        bucket_counts: dp.bucket_counts.clone(),
+                        min: dp.min.map(Into::into),
+                        max: dp.max.map(Into::into),
+                        sum: dp.sum.into(),
+                        exemplars: dp.exemplars.iter().map(ExemplarRepr::from).collect(),
+                    })
+                    .collect(),
+                temporality: histogram.temporality.into(),
+            })
+        } else {
+            data.downcast_ref::<ExponentialHistogram<T>>()
+                .map(|histogram| AggregationRepr::ExponentialHistogram {
+                    data_points: histogram
+                        .data_points
+                        .iter()
+                        .map(|dp| ExponentialHistogramDataPointRepr {
+                            attributes: encode_attribute_set(&dp.attributes),
+                            start_time: encode_time(dp.start_time),
+                            time: encode_time(dp.time),
+                            count: dp.count,
+                            min: dp.min.map(Into::into),
+                            max: dp.max.map(Into::into),
+                            sum: dp.sum.into(),
+                            scale: dp.scale,
+                            zero_count: dp.zero_count,
+                            positive_bucket: (
+                                dp.positive_bucket.offset,
+                                dp.positive_bucket.counts.clone(),
+                            ),
+                            negative_bucket: (
+                                dp.negative_bucket.offset,
+                                dp.negative_bucket.counts.clone(),
+                            ),
+                            zero_threshold: Float(dp.zero_threshold),
+                            exemplars: dp.exemplars.iter().map(ExemplarRepr::from).collect(),
+                        })
+                        .collect(),
+                    temporality: histogram.temporality.into(),
+                })
+        }
+    }
+
+    fn decode_aggregation<T, R>(data: AggregationRepr<R>) -> Box<dyn Aggregation>
+    where
+        T: Debug + Send + Sync + 'static,
+        R: Into<T>,
+    {
+        match data {
+            AggregationRepr::Gauge(data_points) => Box::new(Gauge {
+                data_points: decode_data_points(data_points),
+            }),
+            AggregationRepr::Sum {
+                data_points,
+                temporality,
+                is_monotonic,
+            } => Box::new(Sum {
+                data_points: decode_data_points(data_points),
+                temporality: temporality.into(),
+                is_monotonic,
+            }),
+            AggregationRepr::Histogram {
+                data_points,
+                temporality,
+            } => Box::new(Histogram {
+                data_points: data_points
+                    .into_iter()
+                    .map(|dp| HistogramDataPoint {
+                        attributes: decode_attribute_set(dp.attributes),
+                        start_time: decode_time(dp.start_time),
+                        time: decode_time(dp.time),
+                        count: dp.count,
+                        bounds: decode_floats(dp.bounds),
+                        bucket_counts: dp.bucket_counts,
+                        min: dp.min.map(Into::into),
+                        max: dp.max.map(Into::into),
+                        sum: dp.sum.into(),
+                        exemplars: dp.exemplars.into_iter().map(Exemplar::from).collect(),
+                    })
+                    .collect(),
+                temporality: temporality.into(),
+            }),
+            AggregationRepr::ExponentialHistogram {
+                data_points,
+                temporality,
+            } => Box::new(ExponentialHistogram {
+                data_points: data_points
+                    .into_iter()
+                    .map(|dp| ExponentialHistogramDataPoint {
+                        attributes: decode_attribute_set(dp.attributes),
+                        start_time: decode_time(dp.start_time),
+                        time: decode_time(dp.time),
+                        count: dp.count,
+                        min: dp.min.map(Into::into),
+                        max: dp.max.map(Into::into),
+                        sum: dp.sum.into(),
+                        scale: dp.scale,
+                        zero_count: dp.zero_count,
+                        positive_bucket: ExponentialBucket {
+                            offset: dp.positive_bucket.0,
+                            counts: dp.positive_bucket.1,
+                        },
+                        negative_bucket: ExponentialBucket {
+                            offset: dp.negative_bucket.0,
+                            counts: dp.negative_bucket.1,
+                        },
+                        zero_threshold: dp.zero_threshold.into(),
+                        exemplars: dp.exemplars.into_iter().map(Exemplar::from).collect(),
+                    })
+                    .collect(),
+                temporality: temporality.into(),
+            }),
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct MetricRepr {
+        name: String,
+        description: String,
+        unit: String,
+        data: DataRepr,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct ScopeMetricsRepr {
+        scope: ScopeRepr,
+        metrics: Vec<MetricRepr>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct ResourceMetricsRepr {
+        resource: ResourceRepr,
+        scope_metrics: Vec<ScopeMetricsRepr>,
+    }
+
+    /// Serializes `metrics`, leaving out the aggregations of unknown types.
+    pub(in crate::export::persistent) fn encode_metrics(
+        metrics: &ResourceMetrics,
+    ) -> serde_json::Result<Vec<u8>> {
+        let scope_metrics = metrics
+            .scope_metrics
+            .iter()
+            .map(|scope_metrics| ScopeMetricsRepr {
+                scope: (&scope_metrics.scope).into(),
+                metrics: scope_metrics
+                    .metrics
+                    .iter()
+                    .filter_map(|metric| {
+                        let data = metric.data.as_ref();
+                        let data = encode_aggregation::<u64, _>(data)
+                            .map(DataRepr::U64)
+                            .or_else(|| encode_aggregation::<i64, _>(data).map(DataRepr::I64))
+                            .or_else(|| encode_aggregation::<f64, _>(data).map(DataRepr::F64))?;
+                        Some(MetricRepr {
+                            name: metric.name.to_string(),
+                            description: metric.description.to_string(),
+                            unit: metric.unit.as_str().to_owned(),
+                            data,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        serde_json::to_vec(&ResourceMetricsRepr {
+            resource: (&metrics.resource).into(),
+            scope_metrics,
+        })
+    }
+
+    pub(in crate::export::persistent) fn decode_metrics(
+        bytes: &[u8],
+    ) -> serde_json::Result<ResourceMetrics> {
+        let metrics: ResourceMetricsRepr = serde_json::from_slice(bytes)?;
+
+        Ok(ResourceMetrics {
+            resource: metrics.resource.into(),
+            scope_metrics: metrics
+                .scope_metrics
+                .into_iter()
+                .map(|scope_metrics| ScopeMetrics {
+                    scope: scope_metrics.scope.into(),
+                    metrics: scope_metrics
+                        .metrics
+                        .into_iter()
+                        .map(|metric| Metric {
+                            name: metric.name.into(),
+                            description: metric.description.into(),
+                            unit: Unit::new(metric.unit),
+                            data: match metric.data {
+                                DataRepr::U64(data) => decode_aggregation::<u64, _>(data),
+                                DataRepr::I64(data) => decode_aggregation::<i64, _>(data),
+                                DataRepr::F64(data) => decode_aggregation::<f64, _>(data),
+                            },
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_attributes, encode_key_values, AttributesRepr};
+    use opentelemetry::{Array, KeyValue, Value};
+
+    #[test]
+    fn non_finite_floats_round_trip() {
+        let attributes = vec![
+            KeyValue::new("nan", f64::NAN),
+            KeyValue::new("inf", f64::INFINITY),
+            KeyValue::new("neg_inf", f64::NEG_INFINITY),
+            KeyValue::new("finite", 1.5),
+            KeyValue::new(
+                "array",
+                Value::Array(Array::F64(vec![f64::NAN, f64::INFINITY, -2.0])),
+            ),
+        ];
+
+        let bytes = serde_json::to_vec(&encode_key_values(&attributes)).unwrap();
+        let decoded: AttributesRepr = serde_json::from_slice(&bytes).unwrap();
+        let decoded = decode_attributes(decoded);
+
+        assert!(matches!(decoded[0].value, Value::F64(f) if f.is_nan()));
+        assert_eq!(decoded[1].value, Value::F64(f64::INFINITY));
+        assert_eq!(decoded[2].value, Value::F64(f64::NEG_INFINITY));
+        assert_eq!(decoded[3].value, Value::F64(1.5));
+        match &decoded[4].value {
+            Value::Array(Array::F64(values)) => {
+                assert!(values[0].is_nan());
+                assert_eq!(values[1..], [f64::INFINITY, -2.0]);
+            }
+            other => panic!("unexpected value {:?}", other),
+        }
+    }
+}
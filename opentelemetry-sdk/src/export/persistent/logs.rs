@@ -0,0 +1,141 @@
+use std::{fmt, io, sync::Arc};
+
+use async_trait::async_trait;
+use futures_util::lock::Mutex;
+#[cfg(feature = "logs_level_enabled")]
+use opentelemetry::logs::Severity;
+use opentelemetry::{
+    global,
+    logs::{LogError, LogResult},
+};
+
+use super::{codec, Batch, Persistent, PersistentQueueConfig};
+use crate::export::logs::{LogData, LogExporter};
+
+impl Batch for Vec<LogData> {
+    type Error = LogError;
+
+    fn encode(&self) -> serde_json::Result<Vec<u8>> {
+        codec::encode_logs(self)
+    }
+
+    fn decode(bytes: &[u8]) -> serde_json::Result<Self> {
+        codec::decode_logs(bytes)
+    }
+
+    fn error(message: String) -> LogError {
+        LogError::from(message)
+    }
+}
+
+/// A [`LogExporter`] queueing the log records on disk before exporting them
+/// with the wrapped exporter.
+///
+/// The log records are only removed from the queue once exported. When the
+/// wrapped exporter fails, the log records are kept and exported again, after
+/// the ones queued before them, on the next export.
+pub struct PersistentLogExporter<E> {
+    state: Arc<Mutex<Persistent<E>>>,
+}
+
+impl<E> fmt::Debug for PersistentLogExporter<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentLogExporter")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<E: LogExporter> PersistentLogExporter<E> {
+    /// Wraps `exporter`, opening the queue configured by `config`.
+    ///
+    /// The log records left in the queue by a previous process are exported
+    /// on the first export.
+    pub fn new(exporter: E, config: PersistentQueueConfig) -> io::Result<Self> {
+        Ok(PersistentLogExporter {
+            state: Arc::new(Mutex::new(Persistent::open(exporter, &config)?)),
+        })
+    }
+}
+
+#[async_trait]
+impl<E: LogExporter> LogExporter for PersistentLogExporter<E> {
+    async fn export(&mut self, batch: Vec<LogData>) -> LogResult<()> {
+        self.state
+            .lock()
+            .await
+            .export(batch, |exporter: &mut E, batch| exporter.export(batch))
+            .await
+    }
+
+    fn shutdown(&mut self) {
+        match self.state.try_lock() {
+            Some(mut state) => state.exporter.shutdown(),
+            None => global::handle_error(LogError::from(
+                "cannot shut down the persistent log exporter while it is exporting",
+            )),
+        }
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn event_enabled(&self, level: Severity, target: &str, name: &str) -> bool {
+        self.state.try_lock().map_or(true, |state| {
+            state.exporter.event_enabled(level, target, name)
+        })
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::PersistentLogExporter;
+    use crate::export::logs::{LogData, LogExporter};
+    use crate::export::persistent::PersistentQueueConfig;
+    use crate::testing::logs::InMemoryLogsExporter;
+    use crate::Resource;
+    use opentelemetry::logs::{AnyValue, LogRecord, Severity};
+    use opentelemetry::{Key, KeyValue};
+    use std::{borrow::Cow, collections::HashMap, fs, time::SystemTime};
+
+    #[test]
+    fn log_records_are_exported_from_the_queue() {
+        let dir = std::env::temp_dir().join(format!(
+            "opentelemetry-sdk-persistent-logs-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let in_memory = InMemoryLogsExporter::default();
+        let mut exporter =
+            PersistentLogExporter::new(in_memory.clone(), PersistentQueueConfig::new(&dir))
+                .unwrap();
+
+        let mut record = LogRecord::default();
+        record.timestamp = Some(SystemTime::now());
+        record.severity_number = Some(Severity::Warn2);
+        record.severity_text = Some("WARN".into());
+        record.body = Some(AnyValue::Map(HashMap::from([(
+            Key::from("nested"),
+            AnyValue::ListAny(vec![AnyValue::Int(1), AnyValue::Bytes(vec![2])]),
+        )])));
+        record.attributes = Some(vec![(Key::from("key"), AnyValue::Boolean(true))]);
+        let log = LogData {
+            record,
+            resource: Cow::Owned(Resource::new([KeyValue::new("service.name", "test")])),
+            instrumentation: Default::default(),
+            dropped_attributes_count: 2,
+        };
+
+        futures_executor::block_on(exporter.export(vec![log.clone()])).unwrap();
+
+        let logs = in_memory.get_emitted_logs().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].record.timestamp, log.record.timestamp);
+        assert_eq!(logs[0].record.severity_number, Some(Severity::Warn2));
+        assert_eq!(logs[0].record.severity_text, log.record.severity_text);
+        assert_eq!(logs[0].record.body, log.record.body);
+        assert_eq!(logs[0].record.attributes, log.record.attributes);
+        assert_eq!(logs[0].resource, log.resource);
+        assert_eq!(logs[0].dropped_attributes_count, 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
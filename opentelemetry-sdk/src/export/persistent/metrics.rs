@@ -0,0 +1,172 @@
+use std::{fmt, io, mem, sync::Arc};
+
+use async_trait::async_trait;
+use futures_util::lock::Mutex;
+use opentelemetry::metrics::{MetricsError, Result};
+
+use super::{codec, Batch, Persistent, PersistentQueueConfig};
+use crate::metrics::{
+    data::{ResourceMetrics, Temporality},
+    exporter::PushMetricsExporter,
+    reader::{AggregationSelector, TemporalitySelector},
+    Aggregation, InstrumentKind,
+};
+
+impl Batch for ResourceMetrics {
+    type Error = MetricsError;
+
+    fn encode(&self) -> serde_json::Result<Vec<u8>> {
+        codec::encode_metrics(self)
+    }
+
+    fn decode(bytes: &[u8]) -> serde_json::Result<Self> {
+        codec::decode_metrics(bytes)
+    }
+
+    fn error(message: String) -> MetricsError {
+        MetricsError::Other(message)
+    }
+}
+
+/// A [`PushMetricsExporter`] queueing the metrics on disk before exporting
+/// them with the wrapped exporter.
+///
+/// The metrics are only removed from the queue once exported. When the
+/// wrapped exporter fails, the metrics are kept and exported again, after the
+/// ones queued before them, on the next export. The queued metrics keep the
+/// temporality they were collected with.
+///
+/// Only the [aggregations] of the SDK are queued, the other ones are dropped.
+///
+/// [aggregations]: crate::metrics::data
+pub struct PersistentMetricsExporter<E> {
+    exporter: Arc<E>,
+    state: Mutex<Persistent<Arc<E>>>,
+}
+
+impl<E> fmt::Debug for PersistentMetricsExporter<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentMetricsExporter")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<E: PushMetricsExporter> PersistentMetricsExporter<E> {
+    /// Wraps `exporter`, opening the queue configured by `config`.
+    ///
+    /// The metrics left in the queue by a previous process are exported on
+    /// the first export.
+    pub fn new(exporter: E, config: PersistentQueueConfig) -> io::Result<Self> {
+        let exporter = Arc::new(exporter);
+        Ok(PersistentMetricsExporter {
+            state: Mutex::new(Persistent::open(exporter.clone(), &config)?),
+            exporter,
+        })
+    }
+}
+
+impl<E: PushMetricsExporter> AggregationSelector for PersistentMetricsExporter<E> {
+    fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+        self.exporter.aggregation(kind)
+    }
+}
+
+impl<E: PushMetricsExporter> TemporalitySelector for PersistentMetricsExporter<E> {
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.exporter.temporality(kind)
+    }
+}
+
+#[async_trait]
+impl<E: PushMetricsExporter> PushMetricsExporter for PersistentMetricsExporter<E> {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()> {
+        let batch = ResourceMetrics {
+            resource: metrics.resource.clone(),
+            scope_metrics: mem::take(&mut metrics.scope_metrics),
+        };
+
+        self.state
+            .lock()
+            .await
+            .export(batch, |exporter: &mut Arc<E>, mut batch| {
+                Box::pin(async move { exporter.export(&mut batch).await })
+            })
+            .await
+    }
+
+    async fn force_flush(&self) -> Result<()> {
+        self.exporter.force_flush().await
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        self.exporter.shutdown()
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::PersistentMetricsExporter;
+    use crate::export::persistent::PersistentQueueConfig;
+    use crate::metrics::{
+        data::{DataPoint, Metric, ResourceMetrics, ScopeMetrics, Sum, Temporality},
+        exporter::PushMetricsExporter,
+    };
+    use crate::testing::metrics::InMemoryMetricsExporter;
+    use crate::{AttributeSet, Resource};
+    use opentelemetry::{metrics::Unit, KeyValue};
+    use std::{fs, time::SystemTime};
+
+    #[test]
+    fn metrics_are_exported_from_the_queue() {
+        let dir = std::env::temp_dir().join(format!(
+            "opentelemetry-sdk-persistent-metrics-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let in_memory = InMemoryMetricsExporter::default();
+        let exporter =
+            PersistentMetricsExporter::new(in_memory.clone(), PersistentQueueConfig::new(&dir))
+                .unwrap();
+
+        let mut metrics = ResourceMetrics {
+            resource: Resource::new([KeyValue::new("service.name", "test")]),
+            scope_metrics: vec![ScopeMetrics {
+                scope: Default::default(),
+                metrics: vec![Metric {
+                    name: "requests".into(),
+                    description: "".into(),
+                    unit: Unit::new("1"),
+                    data: Box::new(Sum {
+                        data_points: vec![DataPoint {
+                            attributes: AttributeSet::from(&[KeyValue::new("key", "value")][..]),
+                            start_time: Some(SystemTime::now()),
+                            time: Some(SystemTime::now()),
+                            value: 3u64,
+                            exemplars: vec![],
+                        }],
+                        temporality: Temporality::Delta,
+                        is_monotonic: true,
+                    }),
+                }],
+            }],
+        };
+        futures_executor::block_on(exporter.export(&mut metrics)).unwrap();
+
+        let finished = in_memory.get_finished_metrics().unwrap();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].resource, metrics.resource);
+        let metric = &finished[0].scope_metrics[0].metrics[0];
+        assert_eq!(metric.name, "requests");
+        let sum = metric.data.as_any().downcast_ref::<Sum<u64>>().unwrap();
+        assert_eq!(sum.temporality, Temporality::Delta);
+        assert!(sum.is_monotonic);
+        assert_eq!(sum.data_points[0].value, 3);
+        assert_eq!(
+            sum.data_points[0].attributes,
+            AttributeSet::from(&[KeyValue::new("key", "value")][..])
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
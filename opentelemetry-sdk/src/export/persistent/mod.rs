@@ -0,0 +1,305 @@
+//! Disk-backed buffering of the exported telemetry.
+//!
+//! The exporters of this module wrap any exporter to write the exported
+//! batches to a queue of segment files in a directory before handing them to
+//! the wrapped exporter. A batch is only removed from the queue once the
+//! wrapped exporter has exported it, so the telemetry survives connectivity
+//! losses and restarts of the process: the batches failing to export are
+//! retried, oldest first, on the next exports. Each export sends at most 16
+//! queued batches, so that a large backlog is caught up on over several
+//! exports instead of delaying one of them. A batch failing to export 100
+//! times in a row is dropped, so that a batch the backend keeps rejecting
+//! doesn't hold back the ones queued after it.
+//!
+//! The segment files are written and read from a thread dedicated to each
+//! exporter, so that the exports don't block the async runtime running them.
+//!
+//! The size of the queue is capped, the oldest batches being dropped first,
+//! and the batches can be given a time to live.
+//!
+//! ```no_run
+//! # #[cfg(all(feature = "trace", feature = "testing"))]
+//! # {
+//! use opentelemetry_sdk::export::persistent::{PersistentQueueConfig, PersistentSpanExporter};
+//! use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+//! use std::time::Duration;
+//!
+//! let config = PersistentQueueConfig::new("/var/lib/my-service/spans")
+//!     .with_max_size(64 * 1024 * 1024)
+//!     .with_ttl(Duration::from_secs(24 * 60 * 60));
+//! let exporter = PersistentSpanExporter::new(InMemorySpanExporter::default(), config)
+//!     .expect("the queue directory should be writable");
+//! # }
+//! ```
+use futures_channel::oneshot;
+use futures_util::future::BoxFuture;
+use opentelemetry::global;
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use self::queue::SegmentQueue;
+
+mod codec;
+mod queue;
+
+#[cfg(feature = "logs")]
+mod logs;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "trace")]
+mod trace;
+
+#[cfg(feature = "logs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "logs")))]
+pub use logs::PersistentLogExporter;
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub use metrics::PersistentMetricsExporter;
+#[cfg(feature = "trace")]
+#[cfg_attr(docsrs, doc(cfg(feature = "trace")))]
+pub use trace::PersistentSpanExporter;
+
+/// Default size of a segment file, 16 MiB.
+const DEFAULT_MAX_SEGMENT_SIZE: u64 = 16 * 1024 * 1024;
+/// Default size of the whole queue, 256 MiB.
+const DEFAULT_MAX_SIZE: u64 = 256 * 1024 * 1024;
+/// The number of queued batches sent by each export.
+const MAX_BATCHES_PER_EXPORT: usize = 16;
+/// Default number of failed exports after which a batch is dropped.
+const DEFAULT_MAX_ATTEMPTS: u32 = 100;
+
+/// Configuration of the queue of a persistent exporter.
+///
+/// Each exporter must be given its own directory.
+#[derive(Clone, Debug)]
+pub struct PersistentQueueConfig {
+    pub(crate) dir: PathBuf,
+    pub(crate) max_segment_size: u64,
+    pub(crate) max_size: u64,
+    pub(crate) ttl: Option<Duration>,
+    pub(crate) max_attempts: u32,
+}
+
+impl PersistentQueueConfig {
+    /// Stores the queue in `dir`, which is created if missing.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        PersistentQueueConfig {
+            dir: dir.as_ref().to_path_buf(),
+            max_segment_size: DEFAULT_MAX_SEGMENT_SIZE,
+            max_size: DEFAULT_MAX_SIZE,
+            ttl: None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// Set the size in bytes after which a new segment file is started.
+    ///
+    /// Segment files are deleted once all of their batches are exported.
+    /// It must not exceed the maximum size of the queue, otherwise the queue
+    /// fails to open. Default to 16 MiB.
+    pub fn with_max_segment_size(mut self, max_segment_size: u64) -> Self {
+        self.max_segment_size = max_segment_size;
+        self
+    }
+
+    /// Set the maximum size in bytes of the queue.
+    ///
+    /// When the queue grows past it, its oldest segments are deleted.
+    /// Default to 256 MiB.
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Drop the batches which have been queued for longer than `ttl` instead
+    /// of exporting them.
+    ///
+    /// By default, the batches don't expire.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Set the number of exports of the oldest queued batch failing in a row
+    /// after which it is dropped, reporting the last error.
+    ///
+    /// The count starts over when the exporter is created. Default to 100.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
+
+/// A batch of telemetry, as stored in the queue.
+trait Batch: Sized {
+    /// The error type of the exporters of the batch.
+    type Error: Into<global::Error>;
+
+    fn encode(&self) -> serde_json::Result<Vec<u8>>;
+
+    fn decode(bytes: &[u8]) -> serde_json::Result<Self>;
+
+    fn error(message: String) -> Self::Error;
+}
+
+/// Exports a batch with the wrapped exporter `X`.
+type ExportFn<X, T> = for<'a> fn(&'a mut X, T) -> BoxFuture<'a, Result<(), <T as Batch>::Error>>;
+
+/// The queue of a persistent exporter, with the exporter it feeds.
+#[derive(Debug)]
+struct Persistent<X> {
+    queue: QueueThread,
+    exporter: X,
+    max_attempts: u32,
+    /// The number of failed exports of the oldest queued batch.
+    failed_attempts: u32,
+}
+
+impl<X> Persistent<X> {
+    fn open(exporter: X, config: &PersistentQueueConfig) -> io::Result<Self> {
+        Ok(Persistent {
+            queue: QueueThread::spawn(SegmentQueue::open(config)?)?,
+            exporter,
+            max_attempts: config.max_attempts,
+            failed_attempts: 0,
+        })
+    }
+
+    /// Queues `batch`, then exports the queued batches, oldest first, until
+    /// the queue is empty, an export fails, or [`MAX_BATCHES_PER_EXPORT`]
+    /// batches are exported.
+    ///
+    /// A batch is dropped once its exports failed `max_attempts` times.
+    ///
+    /// If `batch` can't be queued, it is exported directly instead.
+    async fn export<T: Batch>(&mut self, batch: T, export: ExportFn<X, T>) -> Result<(), T::Error> {
+        let queued = match batch.encode() {
+            Ok(bytes) => self
+                .queue
+                .run(move |queue| queue.push(&bytes))
+                .await
+                .map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+        match queued {
+            Ok(0) => drop(batch),
+            Ok(dropped) => {
+                drop(batch);
+                global::handle_error(T::error(format!(
+                    "persistent queue is full, dropped {} bytes of the oldest batches",
+                    dropped
+                )));
+            }
+            Err(err) => {
+                global::handle_error(T::error(format!(
+                    "failed to write the batch to the persistent queue: {}",
+                    err
+                )));
+                return export(&mut self.exporter, batch).await;
+            }
+        }
+
+        for _ in 0..MAX_BATCHES_PER_EXPORT {
+            let bytes = match self.queue.run(SegmentQueue::peek).await {
+                Ok(Some(bytes)) => bytes,
+                Ok(None) => return Ok(()),
+                Err(err) => {
+                    return Err(T::error(format!(
+                        "failed to read the persistent queue: {}",
+                        err
+                    )))
+                }
+            };
+
+            let result = match T::decode(&bytes) {
+                Ok(batch) => match export(&mut self.exporter, batch).await {
+                    Ok(()) => Ok(()),
+                    Err(err) => {
+                        self.failed_attempts += 1;
+                        if self.failed_attempts < self.max_attempts {
+                            return Err(err);
+                        }
+                        let err: global::Error = err.into();
+                        Err(T::error(format!(
+                            "dropped a batch of the persistent queue after {} failed exports: {}",
+                            self.failed_attempts, err
+                        )))
+                    }
+                },
+                Err(err) => {
+                    global::handle_error(T::error(format!(
+                        "dropping a batch of the persistent queue which can't be decoded: {}",
+                        err
+                    )));
+                    Ok(())
+                }
+            };
+            self.failed_attempts = 0;
+
+            self.queue.run(SegmentQueue::ack).await.map_err(|err| {
+                T::error(format!(
+                    "failed to acknowledge a batch of the persistent queue: {}",
+                    err
+                ))
+            })?;
+            result?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An operation on the queue, run by its thread.
+type Job = Box<dyn FnOnce(&mut SegmentQueue) + Send>;
+
+/// The thread owning a [`SegmentQueue`], running the blocking file system
+/// calls of the operations sent to it.
+///
+/// The thread stops once the `QueueThread` is dropped.
+struct QueueThread {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl fmt::Debug for QueueThread {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueueThread").finish_non_exhaustive()
+    }
+}
+
+impl QueueThread {
+    fn spawn(mut queue: SegmentQueue) -> io::Result<Self> {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name("opentelemetry-persistent-queue".into())
+            .spawn(move || {
+                for job in receiver {
+                    job(&mut queue);
+                }
+            })?;
+        Ok(QueueThread { jobs })
+    }
+
+    /// Runs `operation` on the queue, waiting for its result without blocking.
+    async fn run<R, F>(&self, operation: F) -> io::Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut SegmentQueue) -> io::Result<R> + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        self.jobs
+            .send(Box::new(move |queue| {
+                let _ = sender.send(operation(queue));
+            }))
+            .map_err(|_| stopped())?;
+        receiver.await.map_err(|_| stopped())?
+    }
+}
+
+fn stopped() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "the persistent queue thread stopped")
+}
@@ -0,0 +1,547 @@
+//! A write-ahead queue of records stored in segment files.
+//!
+//! Records are appended to the last segment of the directory, named after its
+//! sequence number (`00000000000000000001.wal`), and a new segment is started
+//! once it reaches `max_segment_size`. Each record is stored as
+//!
+//! ```text
+//! | length (u32) | checksum (u32) | written at, ms since epoch (u64) | payload |
+//! ```
+//!
+//! in little endian. The position of the first record which hasn't been
+//! acknowledged is stored in the `cursor` file, so that delivered records are
+//! not read again after a restart. Segments are deleted once all their records
+//! are acknowledged, or to keep the directory under `max_size`.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::PersistentQueueConfig;
+
+const HEADER_LEN: u64 = 16;
+const SEGMENT_EXTENSION: &str = "wal";
+const CURSOR_FILE: &str = "cursor";
+
+#[derive(Debug)]
+struct Segment {
+    id: u64,
+    len: u64,
+}
+
+/// The position of a record: its segment and its offset in the segment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Position {
+    segment: u64,
+    offset: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct SegmentQueue {
+    dir: PathBuf,
+    max_segment_size: u64,
+    max_size: u64,
+    ttl: Option<Duration>,
+    /// The segments of the directory, oldest first. The last one is written to.
+    segments: Vec<Segment>,
+    writer: Option<File>,
+    /// The first record not acknowledged yet.
+    cursor: Position,
+    /// The position following the record returned by the last `peek`.
+    peeked: Option<Position>,
+}
+
+impl SegmentQueue {
+    /// Opens the queue stored in the configured directory, creating it if
+    /// needed.
+    pub(crate) fn open(config: &PersistentQueueConfig) -> io::Result<Self> {
+        // The segment written to is never deleted, so it must fit in the queue.
+        if config.max_segment_size > config.max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the maximum segment size ({} bytes) exceeds the maximum size of the queue ({} bytes)",
+                    config.max_segment_size, config.max_size
+                ),
+            ));
+        }
+        fs::create_dir_all(&config.dir)?;
+
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(&config.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+                continue;
+            }
+            if let Some(id) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse().ok())
+            {
+                segments.push(Segment {
+                    id,
+                    len: fs::metadata(&path)?.len(),
+                });
+            }
+        }
+        segments.sort_by_key(|segment| segment.id);
+
+        let mut queue = SegmentQueue {
+            dir: config.dir.clone(),
+            max_segment_size: config.max_segment_size,
+            max_size: config.max_size,
+            ttl: config.ttl,
+            segments,
+            writer: None,
+            cursor: Position {
+                segment: 0,
+                offset: 0,
+            },
+            peeked: None,
+        };
+
+        // A crash may have left a partially written record at the end of the
+        // last segment, drop it so that new records are readable.
+        if let Some(last) = queue.segments.last() {
+            let valid_len = queue.valid_len(last.id)?;
+            if valid_len < last.len {
+                OpenOptions::new()
+                    .write(true)
+                    .open(queue.segment_path(last.id))?
+                    .set_len(valid_len)?;
+                queue.segments.last_mut().expect("segment exists").len = valid_len;
+            }
+        }
+
+        queue.cursor = queue.read_cursor().unwrap_or(Position {
+            segment: queue.segments.first().map_or(0, |segment| segment.id),
+            offset: 0,
+        });
+        if !queue.segments.iter().any(|s| s.id == queue.cursor.segment) {
+            queue.cursor = Position {
+                segment: queue.first_segment_after(queue.cursor.segment),
+                offset: 0,
+            };
+        }
+
+        Ok(queue)
+    }
+
+    /// Appends a record to the queue.
+    ///
+    /// Returns the number of bytes of the oldest records deleted to keep the
+    /// queue under its maximum size.
+    pub(crate) fn push(&mut self, payload: &[u8]) -> io::Result<u64> {
+        let payload_len = u32::try_from(payload.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("the record of {} bytes is too large", payload.len()),
+            )
+        })?;
+        let record_len = HEADER_LEN + u64::from(payload_len);
+        let rotate = match self.segments.last() {
+            Some(last) => last.len > 0 && last.len + record_len > self.max_segment_size,
+            None => true,
+        };
+        if rotate {
+            let id = self.segments.last().map_or(1, |last| last.id + 1);
+            self.writer = None;
+            self.segments.push(Segment { id, len: 0 });
+        }
+
+        let last = self.segments.last_mut().expect("a segment is open");
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            writer => writer.insert(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(self.dir.join(segment_file_name(last.id)))?,
+            ),
+        };
+
+        let mut record = Vec::with_capacity(record_len as usize);
+        record.extend_from_slice(&payload_len.to_le_bytes());
+        record.extend_from_slice(&checksum(payload).to_le_bytes());
+        record.extend_from_slice(&now_millis().to_le_bytes());
+        record.extend_from_slice(payload);
+        writer.write_all(&record)?;
+        writer.sync_data()?;
+        last.len += record_len;
+
+        self.enforce_max_size()
+    }
+
+    /// Returns the oldest record which hasn't been acknowledged, skipping the
+    /// expired and corrupted ones.
+    ///
+    /// Calling `peek` again without an `ack` returns the same record.
+    pub(crate) fn peek(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            let (id, len) = match self.segments.iter().find(|s| s.id >= self.cursor.segment) {
+                Some(segment) => (segment.id, segment.len),
+                None => return Ok(None),
+            };
+            if id != self.cursor.segment {
+                self.cursor = Position {
+                    segment: id,
+                    offset: 0,
+                };
+            }
+            let is_last = self.segments.last().map(|s| s.id) == Some(id);
+
+            if self.cursor.offset + HEADER_LEN > len {
+                if is_last {
+                    return Ok(None);
+                }
+                // The segment is fully read, continue with the next one.
+                self.advance_to(Position {
+                    segment: self.first_segment_after(id),
+                    offset: 0,
+                })?;
+                continue;
+            }
+
+            let mut file = File::open(self.segment_path(id))?;
+            file.seek(SeekFrom::Start(self.cursor.offset))?;
+            let mut header = [0; HEADER_LEN as usize];
+            file.read_exact(&mut header)?;
+            let (payload_len, sum, written_at) = parse_header(&header);
+
+            let next = Position {
+                segment: id,
+                offset: self.cursor.offset + HEADER_LEN + payload_len,
+            };
+            let mut payload = Vec::new();
+            if next.offset <= len {
+                payload.resize(payload_len as usize, 0);
+                file.read_exact(&mut payload)?;
+            }
+            if next.offset > len || checksum(&payload) != sum {
+                // The rest of the segment can't be trusted, skip it.
+                self.advance_to(Position {
+                    segment: id,
+                    offset: len,
+                })?;
+                continue;
+            }
+
+            if self.is_expired(written_at) {
+                self.advance_to(next)?;
+                continue;
+            }
+
+            self.peeked = Some(next);
+            return Ok(Some(payload));
+        }
+    }
+
+    /// Acknowledges the record returned by the last `peek`, which won't be
+    /// returned again.
+    pub(crate) fn ack(&mut self) -> io::Result<()> {
+        match self.peeked.take() {
+            Some(next) => self.advance_to(next),
+            None => Ok(()),
+        }
+    }
+
+    /// Moves the cursor to `position`, deleting the segments left behind.
+    fn advance_to(&mut self, position: Position) -> io::Result<()> {
+        self.peeked = None;
+        while let Some(first) = self.segments.first() {
+            let is_last = self.segments.len() == 1;
+            if first.id >= position.segment || is_last {
+                break;
+            }
+            let id = first.id;
+            self.segments.remove(0);
+            remove_if_exists(&self.segment_path(id))?;
+        }
+        self.cursor = position;
+        self.write_cursor()
+    }
+
+    /// Deletes the oldest segments until the queue is under its maximum size.
+    fn enforce_max_size(&mut self) -> io::Result<u64> {
+        let mut dropped = 0;
+        while self.segments.len() > 1 && self.size() > self.max_size {
+            let first = self.segments.remove(0);
+            remove_if_exists(&self.segment_path(first.id))?;
+            if self.cursor.segment <= first.id {
+                dropped += first
+                    .len
+                    .saturating_sub(if self.cursor.segment == first.id {
+                        self.cursor.offset
+                    } else {
+                        0
+                    });
+                self.cursor = Position {
+                    segment: self.segments[0].id,
+                    offset: 0,
+                };
+                self.peeked = None;
+                self.write_cursor()?;
+            }
+        }
+        Ok(dropped)
+    }
+
+    /// The size of the segments, in bytes.
+    fn size(&self) -> u64 {
+        self.segments.iter().map(|segment| segment.len).sum()
+    }
+
+    fn first_segment_after(&self, id: u64) -> u64 {
+        self.segments
+            .iter()
+            .map(|segment| segment.id)
+            .find(|&segment| segment > id)
+            .unwrap_or(id + 1)
+    }
+
+    fn is_expired(&self, written_at: u64) -> bool {
+        match self.ttl {
+            Some(ttl) => now_millis().saturating_sub(written_at) > ttl.as_millis() as u64,
+            None => false,
+        }
+    }
+
+    /// The length of the valid records at the start of the segment.
+    fn valid_len(&self, id: u64) -> io::Result<u64> {
+        let mut file = File::open(self.segment_path(id))?;
+        let len = file.metadata()?.len();
+        let mut offset = 0;
+        let mut header = [0; HEADER_LEN as usize];
+        let mut payload = Vec::new();
+
+        while offset + HEADER_LEN <= len {
+            file.read_exact(&mut header)?;
+            let (payload_len, sum, _) = parse_header(&header);
+            if offset + HEADER_LEN + payload_len > len {
+                break;
+            }
+            payload.resize(payload_len as usize, 0);
+            file.read_exact(&mut payload)?;
+            if checksum(&payload) != sum {
+                break;
+            }
+            offset += HEADER_LEN + payload_len;
+        }
+
+        Ok(offset)
+    }
+
+    fn segment_path(&self, id: u64) -> PathBuf {
+        self.dir.join(segment_file_name(id))
+    }
+
+    fn read_cursor(&self) -> Option<Position> {
+        let bytes = fs::read(self.dir.join(CURSOR_FILE)).ok()?;
+        if bytes.len() != 16 {
+            return None;
+        }
+        Some(Position {
+            segment: u64::from_le_bytes(bytes[..8].try_into().ok()?),
+            offset: u64::from_le_bytes(bytes[8..].try_into().ok()?),
+        })
+    }
+
+    fn write_cursor(&self) -> io::Result<()> {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.cursor.segment.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.cursor.offset.to_le_bytes());
+
+        // Replace the cursor atomically, a torn write would lose the position.
+        let tmp = self.dir.join(format!("{}.tmp", CURSOR_FILE));
+        fs::write(&tmp, bytes)?;
+        fs::rename(tmp, self.dir.join(CURSOR_FILE))
+    }
+}
+
+fn segment_file_name(id: u64) -> String {
+    format!("{:020}.{}", id, SEGMENT_EXTENSION)
+}
+
+fn parse_header(header: &[u8; HEADER_LEN as usize]) -> (u64, u32, u64) {
+    let mut len = [0; 4];
+    let mut sum = [0; 4];
+    let mut written_at = [0; 8];
+    len.copy_from_slice(&header[..4]);
+    sum.copy_from_slice(&header[4..8]);
+    written_at.copy_from_slice(&header[8..]);
+    (
+        u32::from_le_bytes(len) as u64,
+        u32::from_le_bytes(sum),
+        u64::from_le_bytes(written_at),
+    )
+}
+
+/// FNV-1a, enough to detect torn writes.
+fn checksum(payload: &[u8]) -> u32 {
+    payload.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SegmentQueue, CURSOR_FILE};
+    use crate::export::persistent::PersistentQueueConfig;
+    use std::{fs, path::PathBuf, time::Duration};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "opentelemetry-sdk-queue-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn drain(queue: &mut SegmentQueue) -> Vec<Vec<u8>> {
+        let mut records = Vec::new();
+        while let Some(record) = queue.peek().unwrap() {
+            records.push(record);
+            queue.ack().unwrap();
+        }
+        records
+    }
+
+    #[test]
+    fn records_are_read_in_order_across_segments() {
+        let dir = temp_dir("order");
+        let config = PersistentQueueConfig::new(&dir).with_max_segment_size(50);
+        let mut queue = SegmentQueue::open(&config).unwrap();
+
+        for record in [b"first", b"secnd", b"third"] {
+            queue.push(record).unwrap();
+        }
+        assert_eq!(queue.segments.len(), 2);
+
+        assert_eq!(queue.peek().unwrap(), Some(b"first".to_vec()));
+        // Without an ack, the record is returned again.
+        assert_eq!(queue.peek().unwrap(), Some(b"first".to_vec()));
+        queue.ack().unwrap();
+        assert_eq!(
+            drain(&mut queue),
+            vec![b"secnd".to_vec(), b"third".to_vec()]
+        );
+        // The fully acknowledged segment is deleted.
+        assert_eq!(queue.segments.len(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn unacknowledged_records_survive_a_restart() {
+        let dir = temp_dir("restart");
+        let config = PersistentQueueConfig::new(&dir);
+        {
+            let mut queue = SegmentQueue::open(&config).unwrap();
+            queue.push(b"delivered").unwrap();
+            queue.push(b"pending").unwrap();
+            queue.peek().unwrap();
+            queue.ack().unwrap();
+            queue.peek().unwrap();
+        }
+
+        let mut queue = SegmentQueue::open(&config).unwrap();
+        queue.push(b"new").unwrap();
+        assert_eq!(
+            drain(&mut queue),
+            vec![b"pending".to_vec(), b"new".to_vec()]
+        );
+        assert!(dir.join(CURSOR_FILE).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn torn_writes_are_discarded() {
+        let dir = temp_dir("torn");
+        let config = PersistentQueueConfig::new(&dir);
+        {
+            let mut queue = SegmentQueue::open(&config).unwrap();
+            queue.push(b"complete").unwrap();
+        }
+        let segment = dir.join("00000000000000000001.wal");
+        let mut bytes = fs::read(&segment).unwrap();
+        bytes.extend_from_slice(&[42, 0, 0, 0, 1, 2]);
+        fs::write(&segment, bytes).unwrap();
+
+        let mut queue = SegmentQueue::open(&config).unwrap();
+        queue.push(b"after").unwrap();
+        assert_eq!(
+            drain(&mut queue),
+            vec![b"complete".to_vec(), b"after".to_vec()]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn oldest_segments_are_dropped_over_max_size() {
+        let dir = temp_dir("max-size");
+        let config = PersistentQueueConfig::new(&dir)
+            .with_max_segment_size(20)
+            .with_max_size(60);
+        let mut queue = SegmentQueue::open(&config).unwrap();
+
+        assert_eq!(queue.push(b"one").unwrap(), 0);
+        assert_eq!(queue.push(b"two").unwrap(), 0);
+        assert_eq!(queue.push(b"six").unwrap(), 0);
+        assert_eq!(queue.push(b"ten").unwrap(), 19);
+
+        assert_eq!(
+            drain(&mut queue),
+            vec![b"two".to_vec(), b"six".to_vec(), b"ten".to_vec()]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn segments_larger_than_the_queue_are_rejected() {
+        let dir = temp_dir("segment-size");
+        let config = PersistentQueueConfig::new(&dir)
+            .with_max_segment_size(100)
+            .with_max_size(60);
+
+        let err = SegmentQueue::open(&config).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn expired_records_are_skipped() {
+        let dir = temp_dir("ttl");
+        let config = PersistentQueueConfig::new(&dir).with_ttl(Duration::from_millis(10));
+        let mut queue = SegmentQueue::open(&config).unwrap();
+
+        queue.push(b"expired").unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        queue.push(b"fresh").unwrap();
+
+        assert_eq!(drain(&mut queue), vec![b"fresh".to_vec()]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,247 @@
+use std::{fmt, io, sync::Arc};
+
+use futures_util::{future::BoxFuture, lock::Mutex};
+use opentelemetry::{global, trace::TraceError};
+
+use super::{codec, Batch, Persistent, PersistentQueueConfig};
+use crate::export::trace::{ExportResult, SpanData, SpanExporter};
+
+impl Batch for Vec<SpanData> {
+    type Error = TraceError;
+
+    fn encode(&self) -> serde_json::Result<Vec<u8>> {
+        codec::encode_spans(self)
+    }
+
+    fn decode(bytes: &[u8]) -> serde_json::Result<Self> {
+        codec::decode_spans(bytes)
+    }
+
+    fn error(message: String) -> TraceError {
+        TraceError::from(message)
+    }
+}
+
+/// A [`SpanExporter`] queueing the spans on disk before exporting them with
+/// the wrapped exporter.
+///
+/// The spans are only removed from the queue once exported. When the wrapped
+/// exporter fails, the spans are kept and exported again, after the spans
+/// queued before them, on the next export.
+pub struct PersistentSpanExporter<E> {
+    state: Arc<Mutex<Persistent<E>>>,
+}
+
+impl<E> fmt::Debug for PersistentSpanExporter<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentSpanExporter")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<E: SpanExporter> PersistentSpanExporter<E> {
+    /// Wraps `exporter`, opening the queue configured by `config`.
+    ///
+    /// The spans left in the queue by a previous process are exported on the
+    /// first export.
+    pub fn new(exporter: E, config: PersistentQueueConfig) -> io::Result<Self> {
+        Ok(PersistentSpanExporter {
+            state: Arc::new(Mutex::new(Persistent::open(exporter, &config)?)),
+        })
+    }
+}
+
+impl<E: SpanExporter + 'static> SpanExporter for PersistentSpanExporter<E> {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        let state = self.state.clone();
+        Box::pin(async move {
+            state
+                .lock()
+                .await
+                .export(batch, |exporter: &mut E, batch| exporter.export(batch))
+                .await
+        })
+    }
+
+    fn shutdown(&mut self) {
+        match self.state.try_lock() {
+            Some(mut state) => state.exporter.shutdown(),
+            None => global::handle_error(TraceError::from(
+                "cannot shut down the persistent span exporter while it is exporting",
+            )),
+        }
+    }
+
+    fn force_flush(&mut self) -> BoxFuture<'static, ExportResult> {
+        let state = self.state.clone();
+        Box::pin(async move {
+            let flush = state.lock().await.exporter.force_flush();
+            flush.await
+        })
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::PersistentSpanExporter;
+    use crate::export::persistent::PersistentQueueConfig;
+    use crate::export::trace::{ExportResult, SpanData, SpanExporter};
+    use crate::testing::trace::{new_test_export_span_data, InMemorySpanExporter};
+    use crate::Resource;
+    use futures_util::future::BoxFuture;
+    use opentelemetry::{trace::TraceError, KeyValue};
+    use std::{
+        borrow::Cow,
+        fs,
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "opentelemetry-sdk-persistent-spans-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Fails the exports while `offline` is set.
+    #[derive(Debug)]
+    struct FlakyExporter {
+        offline: Arc<AtomicBool>,
+        exporter: InMemorySpanExporter,
+    }
+
+    impl SpanExporter for FlakyExporter {
+        fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+            if self.offline.load(Ordering::SeqCst) {
+                Box::pin(async { Err(TraceError::from("offline")) })
+            } else {
+                self.exporter.export(batch)
+            }
+        }
+    }
+
+    fn span(name: &'static str) -> SpanData {
+        let mut span = new_test_export_span_data();
+        span.name = name.into();
+        span.attributes = vec![KeyValue::new("key", "value")];
+        span.resource = Cow::Owned(Resource::new([KeyValue::new("service.name", "test")]));
+        span
+    }
+
+    #[test]
+    fn spans_are_exported_once_back_online() {
+        let dir = temp_dir("offline");
+        let offline = Arc::new(AtomicBool::new(true));
+        let in_memory = InMemorySpanExporter::default();
+        let mut exporter = PersistentSpanExporter::new(
+            FlakyExporter {
+                offline: offline.clone(),
+                exporter: in_memory.clone(),
+            },
+            PersistentQueueConfig::new(&dir),
+        )
+        .unwrap();
+
+        let (first, second) = (span("first"), span("second"));
+        let result = futures_executor::block_on(exporter.export(vec![first.clone()]));
+        assert!(result.is_err());
+        assert!(in_memory.get_finished_spans().unwrap().is_empty());
+
+        offline.store(false, Ordering::SeqCst);
+        futures_executor::block_on(exporter.export(vec![second.clone()])).unwrap();
+
+        let spans = in_memory.get_finished_spans().unwrap();
+        assert_eq!(spans, vec![first, second]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn backlog_is_exported_over_several_exports() {
+        let dir = temp_dir("backlog");
+        let offline = Arc::new(AtomicBool::new(true));
+        let in_memory = InMemorySpanExporter::default();
+        let mut exporter = PersistentSpanExporter::new(
+            FlakyExporter {
+                offline: offline.clone(),
+                exporter: in_memory.clone(),
+            },
+            PersistentQueueConfig::new(&dir),
+        )
+        .unwrap();
+
+        for _ in 0..20 {
+            assert!(futures_executor::block_on(exporter.export(vec![span("queued")])).is_err());
+        }
+        offline.store(false, Ordering::SeqCst);
+
+        futures_executor::block_on(exporter.export(vec![])).unwrap();
+        assert_eq!(in_memory.get_finished_spans().unwrap().len(), 16);
+        futures_executor::block_on(exporter.export(vec![])).unwrap();
+        assert_eq!(in_memory.get_finished_spans().unwrap().len(), 20);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failing_batches_are_dropped_after_max_attempts() {
+        let dir = temp_dir("attempts");
+        let offline = Arc::new(AtomicBool::new(true));
+        let in_memory = InMemorySpanExporter::default();
+        let mut exporter = PersistentSpanExporter::new(
+            FlakyExporter {
+                offline: offline.clone(),
+                exporter: in_memory.clone(),
+            },
+            PersistentQueueConfig::new(&dir).with_max_attempts(2),
+        )
+        .unwrap();
+
+        let (second, third) = (span("second"), span("third"));
+        assert!(futures_executor::block_on(exporter.export(vec![span("first")])).is_err());
+        // The second failure of the first batch drops it.
+        assert!(futures_executor::block_on(exporter.export(vec![second.clone()])).is_err());
+
+        offline.store(false, Ordering::SeqCst);
+        futures_executor::block_on(exporter.export(vec![third.clone()])).unwrap();
+
+        assert_eq!(in_memory.get_finished_spans().unwrap(), vec![second, third]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn queued_spans_survive_a_restart() {
+        let dir = temp_dir("restart");
+        let offline = Arc::new(AtomicBool::new(true));
+        let mut exporter = PersistentSpanExporter::new(
+            FlakyExporter {
+                offline,
+                exporter: InMemorySpanExporter::default(),
+            },
+            PersistentQueueConfig::new(&dir),
+        )
+        .unwrap();
+        let first = span("first");
+        assert!(futures_executor::block_on(exporter.export(vec![first.clone()])).is_err());
+        drop(exporter);
+
+        let in_memory = InMemorySpanExporter::default();
+        let mut exporter =
+            PersistentSpanExporter::new(in_memory.clone(), PersistentQueueConfig::new(&dir))
+                .unwrap();
+        futures_executor::block_on(exporter.export(vec![])).unwrap();
+
+        assert_eq!(in_memory.get_finished_spans().unwrap(), vec![first]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -134,6 +134,7 @@ pub mod metrics;
 pub mod propagation;
 pub mod resource;
 pub mod runtime;
+pub(crate) mod self_observability;
 #[cfg(any(feature = "testing", test))]
 #[cfg_attr(docsrs, doc(cfg(any(feature = "testing", test))))]
 pub mod testing;
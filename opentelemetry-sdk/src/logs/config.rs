@@ -1,5 +1,6 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, env, str::FromStr};
 
+use super::LogLimits;
 use crate::Resource;
 
 /// Default log configuration
@@ -8,10 +9,13 @@ pub fn config() -> Config {
 }
 
 /// Log emitter configuration.
-#[derive(Debug, Default)]
+#[derive(Debug)]
+#[non_exhaustive]
 pub struct Config {
     /// Contains attributes representing an entity that produces telemetry.
     pub resource: Cow<'static, crate::Resource>,
+    /// Log record limits
+    pub log_limits: LogLimits,
 }
 
 impl Config {
@@ -20,4 +24,52 @@ impl Config {
         self.resource = Cow::Owned(resource);
         self
     }
+
+    /// Specify the number of attributes one log record can have.
+    pub fn with_max_attributes_per_log_record(mut self, max_attributes: u32) -> Self {
+        self.log_limits.max_attributes_per_log_record = max_attributes;
+        self
+    }
+
+    /// Specify the max length, in characters, of the string attribute values of
+    /// log records. Longer values are truncated.
+    pub fn with_max_attribute_value_length(mut self, max_length: u32) -> Self {
+        self.log_limits.max_attribute_value_length = Some(max_length);
+        self
+    }
+
+    /// Specify all limit via the log_limits
+    pub fn with_log_limits(mut self, log_limits: LogLimits) -> Self {
+        self.log_limits = log_limits;
+        self
+    }
+}
+
+impl Default for Config {
+    /// Create default global sdk configuration.
+    fn default() -> Self {
+        let mut config = Config {
+            resource: Cow::default(),
+            log_limits: LogLimits::default(),
+        };
+
+        if let Some(max_attributes_per_log_record) =
+            env::var("OTEL_LOGRECORD_ATTRIBUTE_COUNT_LIMIT")
+                .ok()
+                .and_then(|count_limit| u32::from_str(&count_limit).ok())
+        {
+            config.log_limits.max_attributes_per_log_record = max_attributes_per_log_record;
+        }
+
+        if let Some(max_attribute_value_length) =
+            env::var("OTEL_LOGRECORD_ATTRIBUTE_VALUE_LENGTH_LIMIT")
+                .or_else(|_| env::var("OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT"))
+                .ok()
+                .and_then(|max_length| u32::from_str(&max_length).ok())
+        {
+            config.log_limits.max_attribute_value_length = Some(max_attribute_value_length);
+        }
+
+        config
+    }
 }
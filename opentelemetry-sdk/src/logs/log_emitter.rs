@@ -198,7 +198,7 @@ impl Logger {
 
 impl opentelemetry::logs::Logger for Logger {
     /// Emit a `LogRecord`.
-    fn emit(&self, record: LogRecord) {
+    fn emit(&self, mut record: LogRecord) {
         let provider = self.provider();
         let config = provider.config();
         let processors = provider.log_processors();
@@ -206,6 +206,7 @@ impl opentelemetry::logs::Logger for Logger {
             cx.has_active_span()
                 .then(|| TraceContext::from(cx.span().span_context()))
         });
+        let dropped_attributes_count = config.log_limits.apply(&mut record);
         for p in processors {
             let mut record = record.clone();
             if let Some(ref trace_context) = trace_context {
@@ -215,6 +216,7 @@ impl opentelemetry::logs::Logger for Logger {
                 record,
                 resource: config.resource.clone(),
                 instrumentation: self.instrumentation_library().clone(),
+                dropped_attributes_count,
             };
             p.emit(data);
         }
@@ -246,8 +248,9 @@ mod tests {
 
     use super::*;
     use opentelemetry::global::{logger, set_logger_provider, shutdown_logger_provider};
-    use opentelemetry::logs::Logger;
+    use opentelemetry::logs::{AnyValue, Logger, LoggerProvider as _};
     use opentelemetry::{Key, KeyValue, Value};
+    use std::collections::HashMap;
     use std::sync::Mutex;
     use std::thread;
 
@@ -301,6 +304,7 @@ mod tests {
                     SERVICE_NAME,
                     "test_service",
                 )])),
+                ..Default::default()
             })
             .build();
         assert_resource(&custom_config_provider, SERVICE_NAME, Some("test_service"));
@@ -335,6 +339,7 @@ mod tests {
                             KeyValue::new("my-custom-key", "my-custom-value"),
                             KeyValue::new("my-custom-key2", "my-custom-value2"),
                         ]))),
+                        ..Default::default()
                     })
                     .build();
                 assert_resource(
@@ -372,11 +377,91 @@ mod tests {
         let no_service_name = super::LoggerProvider::builder()
             .with_config(Config {
                 resource: Cow::Owned(Resource::empty()),
+                ..Default::default()
             })
             .build();
         assert_eq!(no_service_name.config().resource.len(), 0);
     }
 
+    #[test]
+    #[cfg(feature = "testing")]
+    fn test_log_limits() {
+        let exporter = crate::testing::logs::InMemoryLogsExporter::default();
+        let logger_provider = LoggerProvider::builder()
+            .with_config(
+                Config::default()
+                    .with_max_attributes_per_log_record(3)
+                    .with_max_attribute_value_length(3),
+            )
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let logger = logger_provider.logger("test-logger");
+
+        let record = LogRecord::builder()
+            .with_body("the body isn't truncated")
+            .with_attributes(vec![
+                (Key::new("multibyte"), "héllo".into()),
+                (
+                    Key::new("list"),
+                    AnyValue::ListAny(vec!["abcd".into(), 123456.into()]),
+                ),
+                (
+                    Key::new("map"),
+                    AnyValue::Map(HashMap::from([(Key::new("nested"), "€€€€".into())])),
+                ),
+                (Key::new("dropped"), "abc".into()),
+                (Key::new("dropped2"), "abc".into()),
+            ])
+            .build();
+        logger.emit(record);
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].dropped_attributes_count, 2);
+        assert_eq!(logs[0].record.body, Some("the body isn't truncated".into()));
+        assert_eq!(
+            logs[0].record.attributes,
+            Some(vec![
+                (Key::new("multibyte"), "hél".into()),
+                (
+                    Key::new("list"),
+                    AnyValue::ListAny(vec!["abc".into(), 123456.into()]),
+                ),
+                (
+                    Key::new("map"),
+                    AnyValue::Map(HashMap::from([(Key::new("nested"), "€€€".into())])),
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_log_limits_from_env() {
+        for (count, general, log, expected_count, expected_length) in [
+            (None, None, None, 128, None),
+            (Some("10"), Some("20"), None, 10, Some(20)),
+            (None, Some("20"), Some("30"), 128, Some(30)),
+            (Some("invalid"), None, Some("invalid"), 128, None),
+        ] {
+            let config = temp_env::with_vars(
+                [
+                    ("OTEL_LOGRECORD_ATTRIBUTE_COUNT_LIMIT", count),
+                    ("OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT", general),
+                    ("OTEL_LOGRECORD_ATTRIBUTE_VALUE_LENGTH_LIMIT", log),
+                ],
+                Config::default,
+            );
+            assert_eq!(
+                config.log_limits.max_attributes_per_log_record, expected_count,
+                "{count:?}"
+            );
+            assert_eq!(
+                config.log_limits.max_attribute_value_length, expected_length,
+                "{general:?} {log:?}"
+            );
+        }
+    }
+
     #[test]
     fn shutdown_test() {
         // cargo test shutdown_test --features=logs
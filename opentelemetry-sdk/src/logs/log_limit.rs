@@ -0,0 +1,90 @@
+/// # Log record limit
+/// Erroneous code can add unintended attributes to a log record, or attribute
+/// values of unbounded length, exhausting the memory and the size accepted by
+/// the backends.
+///
+/// To protect against those errors, users can use log record limits to
+/// configure
+///  - Maximum allowed attribute count per log record
+///  - Maximum allowed length of the attribute values of log records
+///
+/// If the count limit has been breached, the attributes added last are dropped
+/// and counted in the `dropped_attributes_count` of the [`LogData`]. The string
+/// values longer than the length limit are truncated to their first
+/// characters, including the strings nested in lists and maps.
+///
+/// [`LogData`]: crate::export::logs::LogData
+use crate::util::truncated;
+use opentelemetry::{
+    logs::{AnyValue, LogRecord},
+    StringValue,
+};
+
+pub(crate) const DEFAULT_MAX_ATTRIBUTES_PER_LOG_RECORD: u32 = 128;
+
+/// Log record limit configuration to keep the attributes of log records in a
+/// reasonable size.
+#[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
+pub struct LogLimits {
+    /// The max attributes that can be added to a log record.
+    pub max_attributes_per_log_record: u32,
+    /// The max length, in characters, of the string attribute values of a log
+    /// record. Unlimited if `None`.
+    pub max_attribute_value_length: Option<u32>,
+}
+
+impl Default for LogLimits {
+    fn default() -> Self {
+        LogLimits {
+            max_attributes_per_log_record: DEFAULT_MAX_ATTRIBUTES_PER_LOG_RECORD,
+            max_attribute_value_length: None,
+        }
+    }
+}
+
+impl LogLimits {
+    /// Drops the attributes of `record` over the count limit, and truncates
+    /// the values longer than the length limit.
+    ///
+    /// Returns the number of dropped attributes.
+    pub(crate) fn apply(&self, record: &mut LogRecord) -> u32 {
+        let attributes = match record.attributes.as_mut() {
+            Some(attributes) => attributes,
+            None => return 0,
+        };
+
+        let max_attributes = self.max_attributes_per_log_record as usize;
+        let dropped_attributes_count = attributes.len().saturating_sub(max_attributes);
+        attributes.truncate(max_attributes);
+
+        if let Some(max_length) = self.max_attribute_value_length {
+            for (_, value) in attributes.iter_mut() {
+                truncate_value(value, max_length as usize);
+            }
+        }
+
+        dropped_attributes_count as u32
+    }
+}
+
+fn truncate_value(value: &mut AnyValue, max_length: usize) {
+    match value {
+        AnyValue::String(value) => {
+            if let Some(prefix) = truncated(value.as_str(), max_length).map(str::to_owned) {
+                *value = StringValue::from(prefix);
+            }
+        }
+        AnyValue::ListAny(values) => {
+            for value in values {
+                truncate_value(value, max_length);
+            }
+        }
+        AnyValue::Map(values) => {
+            for value in values.values_mut() {
+                truncate_value(value, max_length);
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     export::logs::{ExportResult, LogData, LogExporter},
     runtime::{RuntimeChannel, TrySend},
+    self_observability::ProcessorMetrics,
 };
 use futures_channel::oneshot;
 use futures_util::{
@@ -17,7 +18,7 @@ use std::{cmp::min, env, sync::Mutex};
 use std::{
     fmt::{self, Debug, Formatter},
     str::FromStr,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 /// Delay interval between two consecutive exports.
@@ -106,6 +107,7 @@ impl LogProcessor for SimpleLogProcessor {
 /// them at a pre-configured interval.
 pub struct BatchLogProcessor<R: RuntimeChannel> {
     message_sender: R::Sender<BatchMessage>,
+    metrics: ProcessorMetrics,
 }
 
 impl<R: RuntimeChannel> Debug for BatchLogProcessor<R> {
@@ -120,8 +122,12 @@ impl<R: RuntimeChannel> LogProcessor for BatchLogProcessor<R> {
     fn emit(&self, data: LogData) {
         let result = self.message_sender.try_send(BatchMessage::ExportLog(data));
 
-        if let Err(err) = result {
-            global::handle_error(LogError::Other(err.into()));
+        match result {
+            Ok(()) => self.metrics.enqueued(),
+            Err(err) => {
+                self.metrics.dropped(&err);
+                global::handle_error(LogError::Other(err.into()));
+            }
         }
     }
 
@@ -154,13 +160,19 @@ impl<R: RuntimeChannel> LogProcessor for BatchLogProcessor<R> {
 }
 
 impl<R: RuntimeChannel> BatchLogProcessor<R> {
-    pub(crate) fn new(mut exporter: Box<dyn LogExporter>, config: BatchConfig, runtime: R) -> Self {
+    pub(crate) fn new(
+        mut exporter: Box<dyn LogExporter>,
+        config: BatchConfig,
+        runtime: R,
+        metrics: ProcessorMetrics,
+    ) -> Self {
         let (message_sender, message_receiver) =
             runtime.batch_message_channel(config.max_queue_size);
         let ticker = runtime
             .interval(config.scheduled_delay)
             .map(|_| BatchMessage::Flush(None));
         let timeout_runtime = runtime.clone();
+        let worker_metrics = metrics.clone();
 
         // Spawn worker process via user-defined spawn function.
         runtime.spawn(Box::pin(async move {
@@ -179,6 +191,7 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
                                 exporter.as_mut(),
                                 &timeout_runtime,
                                 logs.split_off(0),
+                                &worker_metrics,
                             )
                             .await;
 
@@ -194,6 +207,7 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
                             exporter.as_mut(),
                             &timeout_runtime,
                             logs.split_off(0),
+                            &worker_metrics,
                         )
                         .await;
 
@@ -215,6 +229,7 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
                             exporter.as_mut(),
                             &timeout_runtime,
                             logs.split_off(0),
+                            &worker_metrics,
                         )
                         .await;
 
@@ -234,7 +249,10 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
         }));
 
         // Return batch processor with link to worker
-        BatchLogProcessor { message_sender }
+        BatchLogProcessor {
+            message_sender,
+            metrics,
+        }
     }
 
     /// Create a new batch processor builder
@@ -246,6 +264,7 @@ impl<R: RuntimeChannel> BatchLogProcessor<R> {
             exporter,
             config: Default::default(),
             runtime,
+            metrics: Default::default(),
         }
     }
 }
@@ -255,6 +274,7 @@ async fn export_with_timeout<R, E>(
     exporter: &mut E,
     runtime: &R,
     batch: Vec<LogData>,
+    metrics: &ProcessorMetrics,
 ) -> ExportResult
 where
     R: RuntimeChannel,
@@ -264,14 +284,22 @@ where
         return Ok(());
     }
 
+    let batch_size = batch.len();
+    metrics.batch(batch_size);
+    let start = Instant::now();
+
     let export = exporter.export(batch);
     let timeout = runtime.delay(time_out);
     pin_mut!(export);
     pin_mut!(timeout);
-    match future::select(export, timeout).await {
+    let result = match future::select(export, timeout).await {
         Either::Left((export_res, _)) => export_res,
         Either::Right((_, _)) => ExportResult::Err(LogError::ExportTimedOut(time_out)),
-    }
+    };
+    metrics
+        .exporter
+        .exported(batch_size, result.is_ok(), start.elapsed());
+    result
 }
 
 /// Batch log processor configuration.
@@ -280,20 +308,20 @@ where
 pub struct BatchConfig {
     /// The maximum queue size to buffer logs for delayed processing. If the
     /// queue gets full it drops the logs. The default value of is 2048.
-    max_queue_size: usize,
+    pub(crate) max_queue_size: usize,
 
     /// The delay interval in milliseconds between two consecutive processing
     /// of batches. The default value is 1 second.
-    scheduled_delay: Duration,
+    pub(crate) scheduled_delay: Duration,
 
     /// The maximum number of logs to process in a single batch. If there are
     /// more than one batch worth of logs then it processes multiple batches
     /// of logs one batch after the other without any delay. The default value
     /// is 512.
-    max_export_batch_size: usize,
+    pub(crate) max_export_batch_size: usize,
 
     /// The maximum duration to export a batch of data.
-    max_export_timeout: Duration,
+    pub(crate) max_export_timeout: Duration,
 }
 
 impl Default for BatchConfig {
@@ -421,6 +449,7 @@ pub struct BatchLogProcessorBuilder<E, R> {
     exporter: E,
     config: BatchConfig,
     runtime: R,
+    metrics: ProcessorMetrics,
 }
 
 impl<E, R> BatchLogProcessorBuilder<E, R>
@@ -433,9 +462,37 @@ where
         BatchLogProcessorBuilder { config, ..self }
     }
 
+    /// Record metrics about the processor and its exporter with `meter_provider`.
+    ///
+    /// The processor reports the number of log records waiting in its queue
+    /// (`otel.sdk.log.processor.queue_size`), the log records dropped because the
+    /// queue is full or the processor is shut down
+    /// (`otel.sdk.log.processor.logs.dropped`), the size of the exported batches
+    /// (`otel.sdk.log.processor.batch_size`), the number of exported log records
+    /// by outcome (`otel.sdk.exporter.logs.exported`) and the duration of the
+    /// exports (`otel.sdk.exporter.duration`).
+    #[cfg(feature = "metrics")]
+    pub fn with_self_observability<P>(self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        BatchLogProcessorBuilder {
+            metrics: ProcessorMetrics::new(
+                meter_provider,
+                crate::self_observability::ProcessorSignal::Logs,
+            ),
+            ..self
+        }
+    }
+
     /// Build a batch processor
     pub fn build(self) -> BatchLogProcessor<R> {
-        BatchLogProcessor::new(Box::new(self.exporter), self.config, self.runtime)
+        BatchLogProcessor::new(
+            Box::new(self.exporter),
+            self.config,
+            self.runtime,
+            self.metrics,
+        )
     }
 }
 
@@ -2,14 +2,18 @@
 
 mod config;
 mod log_emitter;
+mod log_limit;
 mod log_processor;
+mod threaded_log_processor;
 
 pub use config::{config, Config};
 pub use log_emitter::{Builder, Logger, LoggerProvider};
+pub use log_limit::LogLimits;
 pub use log_processor::{
     BatchConfig, BatchConfigBuilder, BatchLogProcessor, BatchLogProcessorBuilder, LogProcessor,
     SimpleLogProcessor,
 };
+pub use threaded_log_processor::{ThreadedBatchLogProcessor, ThreadedBatchLogProcessorBuilder};
 
 #[cfg(all(test, feature = "testing"))]
 mod tests {
@@ -0,0 +1,370 @@
+//! A batch log processor exporting from a dedicated OS thread.
+//!
+//! Unlike [`BatchLogProcessor`], the [`ThreadedBatchLogProcessor`] doesn't
+//! need an async runtime: the log records are queued in a bounded channel, and
+//! a background thread blocks on the exporter to export them.
+//!
+//! [`BatchLogProcessor`]: crate::logs::BatchLogProcessor
+
+use crate::{
+    export::logs::{ExportResult, LogData, LogExporter},
+    logs::{BatchConfig, LogProcessor},
+    runtime::TrySendError,
+    self_observability::ProcessorMetrics,
+    util::block_on_with_timeout,
+};
+#[cfg(feature = "logs_level_enabled")]
+use opentelemetry::logs::Severity;
+use opentelemetry::{
+    global,
+    logs::{LogError, LogResult},
+};
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+/// Messages sent from the application threads to the export thread.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+enum BatchMessage {
+    /// Queue a log record for export.
+    ExportLog(LogData),
+    /// Export the queued log records, and send back the result.
+    Flush(mpsc::Sender<ExportResult>),
+    /// Export the queued log records, shut down the exporter and stop the thread.
+    Shutdown(mpsc::Sender<ExportResult>),
+}
+
+/// A [`LogProcessor`] that buffers log records and exports them in batches
+/// from a dedicated background thread.
+///
+/// Log records are queued in a channel bounded by the `max_queue_size` of the
+/// [`BatchConfig`], and dropped when it is full. The thread exports a batch
+/// once `max_export_batch_size` records are buffered, every `scheduled_delay`,
+/// and when the processor is flushed or shut down. The exports are blocking
+/// calls of the exporter, abandoned after `max_export_timeout`.
+/// The exporter is polled outside of any async runtime, so it must not
+/// depend on one, like an exporter using a blocking HTTP client.
+///
+/// [`force_flush`] and [`shutdown`] block the calling thread until the export
+/// thread has handled them, so they can be called from any context, including
+/// a single threaded async runtime.
+///
+/// [`force_flush`]: LogProcessor::force_flush
+/// [`shutdown`]: LogProcessor::shutdown
+pub struct ThreadedBatchLogProcessor {
+    message_sender: SyncSender<BatchMessage>,
+    handle: Option<JoinHandle<()>>,
+    metrics: ProcessorMetrics,
+}
+
+impl Debug for ThreadedBatchLogProcessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThreadedBatchLogProcessor")
+            .field("message_sender", &self.message_sender)
+            .finish()
+    }
+}
+
+impl ThreadedBatchLogProcessor {
+    pub(crate) fn new(
+        exporter: Box<dyn LogExporter>,
+        config: BatchConfig,
+        metrics: ProcessorMetrics,
+    ) -> Self {
+        let (message_sender, message_receiver) = mpsc::sync_channel(config.max_queue_size);
+        let worker = BatchWorker {
+            logs: Vec::new(),
+            exporter,
+            config,
+            metrics: metrics.clone(),
+        };
+
+        let handle = thread::Builder::new()
+            .name("opentelemetry-batch-log-processor".into())
+            .spawn(move || worker.run(message_receiver))
+            .expect("failed to spawn the batch log processor thread");
+
+        ThreadedBatchLogProcessor {
+            message_sender,
+            handle: Some(handle),
+            metrics,
+        }
+    }
+
+    /// Create a new threaded batch processor builder
+    pub fn builder<E>(exporter: E) -> ThreadedBatchLogProcessorBuilder<E>
+    where
+        E: LogExporter,
+    {
+        ThreadedBatchLogProcessorBuilder {
+            exporter,
+            config: Default::default(),
+            metrics: Default::default(),
+        }
+    }
+
+    /// Sends a control message, waiting for room in the queue, then waits for
+    /// the export thread to answer it.
+    fn request(
+        &self,
+        message: impl FnOnce(mpsc::Sender<ExportResult>) -> BatchMessage,
+    ) -> LogResult<()> {
+        let (res_sender, res_receiver) = mpsc::channel();
+        self.message_sender
+            .send(message(res_sender))
+            .map_err(|_| LogError::from("the batch log processor is shut down"))?;
+
+        res_receiver
+            .recv()
+            .map_err(|err| LogError::Other(err.into()))
+            .and_then(std::convert::identity)
+    }
+}
+
+impl LogProcessor for ThreadedBatchLogProcessor {
+    fn emit(&self, data: LogData) {
+        match self
+            .message_sender
+            .try_send(BatchMessage::ExportLog(data))
+            .map_err(TrySendError::from)
+        {
+            Ok(()) => self.metrics.enqueued(),
+            Err(err) => {
+                self.metrics.dropped(&err);
+                global::handle_error(LogError::Other(err.into()));
+            }
+        }
+    }
+
+    #[cfg(feature = "logs_level_enabled")]
+    fn event_enabled(&self, _level: Severity, _target: &str, _name: &str) -> bool {
+        true
+    }
+
+    fn force_flush(&self) -> LogResult<()> {
+        self.request(BatchMessage::Flush)
+    }
+
+    fn shutdown(&mut self) -> LogResult<()> {
+        let result = self.request(BatchMessage::Shutdown);
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|_| LogError::from("the batch log processor thread panicked"))?;
+        }
+        result
+    }
+}
+
+impl Drop for ThreadedBatchLogProcessor {
+    /// Exports the queued logs and stops the thread, unless already shut down.
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            if let Err(err) = self.shutdown() {
+                global::handle_error(err);
+            }
+        }
+    }
+}
+
+struct BatchWorker {
+    logs: Vec<LogData>,
+    exporter: Box<dyn LogExporter>,
+    config: BatchConfig,
+    metrics: ProcessorMetrics,
+}
+
+impl BatchWorker {
+    fn run(mut self, messages: Receiver<BatchMessage>) {
+        let mut next_export = Instant::now() + self.config.scheduled_delay;
+
+        loop {
+            let timeout = next_export.saturating_duration_since(Instant::now());
+            match messages.recv_timeout(timeout) {
+                Ok(BatchMessage::ExportLog(log)) => {
+                    self.logs.push(log);
+
+                    if self.logs.len() >= self.config.max_export_batch_size {
+                        if let Err(err) = self.export() {
+                            global::handle_error(err);
+                        }
+                    }
+                }
+                Ok(BatchMessage::Flush(ch)) => {
+                    let result = self.export();
+                    next_export = Instant::now() + self.config.scheduled_delay;
+
+                    if let Err(result) = ch.send(result) {
+                        global::handle_error(LogError::from(format!(
+                            "failed to send flush result: {:?}",
+                            result
+                        )));
+                    }
+                }
+                Ok(BatchMessage::Shutdown(ch)) => {
+                    let result = self.export();
+                    self.exporter.shutdown();
+
+                    if let Err(result) = ch.send(result) {
+                        global::handle_error(LogError::from(format!(
+                            "failed to send batch processor shutdown result: {:?}",
+                            result
+                        )));
+                    }
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Err(err) = self.export() {
+                        global::handle_error(err);
+                    }
+                    next_export = Instant::now() + self.config.scheduled_delay;
+                }
+                // The processor was dropped without being shut down.
+                Err(RecvTimeoutError::Disconnected) => {
+                    if let Err(err) = self.export() {
+                        global::handle_error(err);
+                    }
+                    self.exporter.shutdown();
+                    break;
+                }
+            }
+        }
+    }
+
+    fn export(&mut self) -> ExportResult {
+        if self.logs.is_empty() {
+            return Ok(());
+        }
+
+        let batch_size = self.logs.len();
+        self.metrics.batch(batch_size);
+        let start = Instant::now();
+
+        let time_out = self.config.max_export_timeout;
+        let export = self.exporter.export(self.logs.split_off(0));
+        let result = block_on_with_timeout(export, time_out)
+            .unwrap_or(Err(LogError::ExportTimedOut(time_out)));
+
+        self.metrics
+            .exporter
+            .exported(batch_size, result.is_ok(), start.elapsed());
+        result
+    }
+}
+
+/// A builder for creating [`ThreadedBatchLogProcessor`] instances.
+#[derive(Debug)]
+pub struct ThreadedBatchLogProcessorBuilder<E> {
+    exporter: E,
+    config: BatchConfig,
+    metrics: ProcessorMetrics,
+}
+
+impl<E> ThreadedBatchLogProcessorBuilder<E>
+where
+    E: LogExporter + 'static,
+{
+    /// Set the BatchConfig for [ThreadedBatchLogProcessorBuilder].
+    pub fn with_batch_config(self, config: BatchConfig) -> Self {
+        ThreadedBatchLogProcessorBuilder { config, ..self }
+    }
+
+    /// Record metrics about the processor and its exporter with `meter_provider`.
+    ///
+    /// The instruments are the ones of
+    /// [`BatchLogProcessorBuilder::with_self_observability`].
+    ///
+    /// [`BatchLogProcessorBuilder::with_self_observability`]: crate::logs::BatchLogProcessorBuilder::with_self_observability
+    #[cfg(feature = "metrics")]
+    pub fn with_self_observability<P>(self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        ThreadedBatchLogProcessorBuilder {
+            metrics: ProcessorMetrics::new(
+                meter_provider,
+                crate::self_observability::ProcessorSignal::Logs,
+            ),
+            ..self
+        }
+    }
+
+    /// Build a threaded batch processor, spawning its export thread.
+    pub fn build(self) -> ThreadedBatchLogProcessor {
+        ThreadedBatchLogProcessor::new(Box::new(self.exporter), self.config, self.metrics)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::ThreadedBatchLogProcessor;
+    use crate::{
+        export::logs::LogData,
+        logs::{BatchConfigBuilder, LogProcessor},
+        testing::logs::InMemoryLogsExporter,
+        Resource,
+    };
+    use std::{borrow::Cow, time::Duration};
+
+    fn log_data() -> LogData {
+        LogData {
+            record: Default::default(),
+            resource: Cow::Owned(Resource::empty()),
+            instrumentation: Default::default(),
+            dropped_attributes_count: 0,
+        }
+    }
+
+    #[test]
+    fn exports_full_batches_and_flushes() {
+        let exporter = InMemoryLogsExporter::default();
+        let processor = ThreadedBatchLogProcessor::builder(exporter.clone())
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_max_export_batch_size(2)
+                    .with_scheduled_delay(Duration::from_secs(3600))
+                    .build(),
+            )
+            .build();
+
+        for _ in 0..3 {
+            processor.emit(log_data());
+        }
+        processor.force_flush().expect("flush should succeed");
+
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn exports_on_scheduled_delay() {
+        let exporter = InMemoryLogsExporter::default();
+        let processor = ThreadedBatchLogProcessor::builder(exporter.clone())
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_scheduled_delay(Duration::from_millis(10))
+                    .build(),
+            )
+            .build();
+
+        processor.emit(log_data());
+        std::thread::sleep(Duration::from_millis(200));
+
+        assert_eq!(exporter.get_emitted_logs().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn shutdown_stops_the_thread() {
+        let exporter = InMemoryLogsExporter::default();
+        let mut processor = ThreadedBatchLogProcessor::builder(exporter).build();
+
+        processor.emit(log_data());
+        processor.shutdown().expect("shutdown should succeed");
+
+        assert!(processor.handle.is_none());
+        assert!(processor.force_flush().is_err());
+    }
+}
@@ -269,7 +269,7 @@ pub struct ExponentialBucket {
 }
 
 /// A measurement sampled from a time series providing a typical example.
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Exemplar<T> {
     /// The attributes recorded with the measurement but filtered out of the
     /// time series' aggregated data.
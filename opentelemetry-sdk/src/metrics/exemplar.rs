@@ -0,0 +1,256 @@
+use std::{env, fmt, sync::Arc};
+
+use opentelemetry::{global, metrics::MetricsError, Context};
+use rand::Rng;
+
+const OTEL_METRICS_EXEMPLAR_FILTER: &str = "OTEL_METRICS_EXEMPLAR_FILTER";
+
+/// Selects the measurements that can be sampled as [Exemplar]s.
+///
+/// Only measurements of synchronous counters, up-down counters and histograms
+/// are offered to the exemplar reservoirs of their data point.
+///
+/// [Exemplar]: crate::metrics::data::Exemplar
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExemplarFilter {
+    /// Every measurement can be sampled.
+    AlwaysOn,
+    /// No measurement is sampled.
+    AlwaysOff,
+    /// Only measurements recorded in the context of a sampled span can be
+    /// sampled.
+    #[default]
+    TraceBased,
+}
+
+impl ExemplarFilter {
+    /// Reads the filter from the `OTEL_METRICS_EXEMPLAR_FILTER` environment
+    /// variable.
+    ///
+    /// Unrecognised values are reported to the global error handler and ignored.
+    pub(crate) fn from_env() -> Option<Self> {
+        let filter = env::var(OTEL_METRICS_EXEMPLAR_FILTER).ok()?;
+        match filter.as_str() {
+            "always_on" => Some(ExemplarFilter::AlwaysOn),
+            "always_off" => Some(ExemplarFilter::AlwaysOff),
+            "trace_based" => Some(ExemplarFilter::TraceBased),
+            s => {
+                global::handle_error(MetricsError::Config(format!(
+                    "Unrecognised {} value: {}. Falling back to default: trace_based",
+                    OTEL_METRICS_EXEMPLAR_FILTER, s
+                )));
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if a measurement recorded in `cx` can be sampled.
+    pub(crate) fn should_sample(&self, cx: &Context) -> bool {
+        match self {
+            ExemplarFilter::AlwaysOn => true,
+            ExemplarFilter::AlwaysOff => false,
+            #[cfg(feature = "trace")]
+            ExemplarFilter::TraceBased => {
+                use opentelemetry::trace::TraceContextExt;
+
+                cx.has_active_span() && cx.span().span_context().is_sampled()
+            }
+            #[cfg(not(feature = "trace"))]
+            ExemplarFilter::TraceBased => {
+                let _ = cx;
+                false
+            }
+        }
+    }
+}
+
+/// Samples the measurements of a data point as its [Exemplar]s.
+///
+/// A reservoir is created for each data point of a stream, and is offered the
+/// measurements selected by the [ExemplarFilter]. The exemplars of a data point
+/// are stored in cells, `offer` picks the cell each measurement is stored in.
+///
+/// [Exemplar]: crate::metrics::data::Exemplar
+pub trait ExemplarReservoir: Send + fmt::Debug {
+    /// Returns the index of the cell the measurement of `value` replaces, or
+    /// `None` to drop it.
+    ///
+    /// Reservoirs should use a small, bounded number of cells.
+    fn offer(&mut self, value: f64) -> Option<usize>;
+
+    /// Called after the exemplars of the data point have been collected.
+    fn reset(&mut self) {}
+}
+
+/// Keeps up to `size` exemplars uniformly sampled from the offered measurements.
+///
+/// This is the `SimpleFixedSizeExemplarReservoir` of the specification, used by
+/// sums with a size of one and by exponential histograms. The sampling restarts
+/// after each collection, while the exemplars of the previous collection are
+/// kept until replaced.
+#[derive(Debug)]
+pub struct SimpleFixedSizeExemplarReservoir {
+    size: usize,
+    /// The number of measurements offered since the last collection.
+    offered: usize,
+}
+
+impl SimpleFixedSizeExemplarReservoir {
+    /// Creates a reservoir holding up to `size` exemplars.
+    pub fn new(size: usize) -> Self {
+        SimpleFixedSizeExemplarReservoir { size, offered: 0 }
+    }
+}
+
+impl ExemplarReservoir for SimpleFixedSizeExemplarReservoir {
+    fn offer(&mut self, _value: f64) -> Option<usize> {
+        let idx = if self.offered < self.size {
+            self.offered
+        } else {
+            rand::thread_rng().gen_range(0..=self.offered)
+        };
+        self.offered += 1;
+
+        (idx < self.size).then_some(idx)
+    }
+
+    fn reset(&mut self) {
+        self.offered = 0;
+    }
+}
+
+/// Keeps the last exemplar offered in each bucket of a histogram.
+///
+/// This is the `AlignedHistogramBucketExemplarReservoir` of the specification,
+/// used by histograms with explicit buckets.
+#[derive(Debug)]
+pub struct AlignedHistogramBucketExemplarReservoir {
+    bounds: Vec<f64>,
+}
+
+impl AlignedHistogramBucketExemplarReservoir {
+    /// Creates a reservoir holding an exemplar for each bucket delimited by the
+    /// sorted `bounds`.
+    pub fn new(bounds: Vec<f64>) -> Self {
+        AlignedHistogramBucketExemplarReservoir { bounds }
+    }
+}
+
+impl ExemplarReservoir for AlignedHistogramBucketExemplarReservoir {
+    fn offer(&mut self, value: f64) -> Option<usize> {
+        Some(self.bounds.partition_point(|&bound| bound < value))
+    }
+}
+
+/// Creates the exemplar reservoir of each data point of a stream.
+#[derive(Clone)]
+pub(crate) struct ExemplarReservoirFactory(
+    Arc<dyn Fn() -> Box<dyn ExemplarReservoir> + Send + Sync>,
+);
+
+impl ExemplarReservoirFactory {
+    pub(crate) fn new<R, F>(reservoir: F) -> Self
+    where
+        R: ExemplarReservoir + 'static,
+        F: Fn() -> R + Send + Sync + 'static,
+    {
+        ExemplarReservoirFactory(Arc::new(move || Box::new(reservoir())))
+    }
+
+    pub(crate) fn create(&self) -> Box<dyn ExemplarReservoir> {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for ExemplarReservoirFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ExemplarReservoirFactory")
+    }
+}
+
+#[cfg(test)]
+mod reservoir_tests {
+    use super::{
+        AlignedHistogramBucketExemplarReservoir, ExemplarReservoir,
+        SimpleFixedSizeExemplarReservoir,
+    };
+
+    #[test]
+    fn test_simple_fixed_size_reservoir() {
+        let mut reservoir = SimpleFixedSizeExemplarReservoir::new(2);
+        assert_eq!(reservoir.offer(1.0), Some(0));
+        assert_eq!(reservoir.offer(2.0), Some(1));
+        for _ in 0..100 {
+            assert!(reservoir.offer(3.0).map_or(true, |idx| idx < 2));
+        }
+
+        // the first measurements after a collection always replace the old ones
+        reservoir.reset();
+        assert_eq!(reservoir.offer(4.0), Some(0));
+    }
+
+    #[test]
+    fn test_empty_simple_fixed_size_reservoir() {
+        let mut reservoir = SimpleFixedSizeExemplarReservoir::new(0);
+        assert_eq!(reservoir.offer(1.0), None);
+    }
+
+    #[test]
+    fn test_aligned_histogram_bucket_reservoir() {
+        let mut reservoir = AlignedHistogramBucketExemplarReservoir::new(vec![0.0, 5.0, 10.0]);
+        assert_eq!(reservoir.offer(-1.0), Some(0));
+        assert_eq!(reservoir.offer(5.0), Some(1));
+        assert_eq!(reservoir.offer(7.0), Some(2));
+        assert_eq!(reservoir.offer(100.0), Some(3));
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use opentelemetry::{
+        trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+        Context,
+    };
+
+    use super::ExemplarFilter;
+
+    fn span_context(flags: TraceFlags) -> Context {
+        Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from(1),
+            SpanId::from(1),
+            flags,
+            true,
+            TraceState::default(),
+        ))
+    }
+
+    #[test]
+    fn test_should_sample() {
+        let sampled = span_context(TraceFlags::SAMPLED);
+        let not_sampled = span_context(TraceFlags::default());
+
+        assert!(ExemplarFilter::AlwaysOn.should_sample(&Context::new()));
+        assert!(!ExemplarFilter::AlwaysOff.should_sample(&sampled));
+        assert!(ExemplarFilter::TraceBased.should_sample(&sampled));
+        assert!(!ExemplarFilter::TraceBased.should_sample(&not_sampled));
+        assert!(!ExemplarFilter::TraceBased.should_sample(&Context::new()));
+    }
+
+    #[test]
+    fn test_from_env() {
+        for (value, expected) in [
+            ("always_on", Some(ExemplarFilter::AlwaysOn)),
+            ("always_off", Some(ExemplarFilter::AlwaysOff)),
+            ("trace_based", Some(ExemplarFilter::TraceBased)),
+            ("unknown", None),
+        ] {
+            temp_env::with_var("OTEL_METRICS_EXEMPLAR_FILTER", Some(value), || {
+                assert_eq!(ExemplarFilter::from_env(), expected);
+            });
+        }
+        temp_env::with_var_unset("OTEL_METRICS_EXEMPLAR_FILTER", || {
+            assert_eq!(ExemplarFilter::from_env(), None);
+        });
+    }
+}
@@ -11,7 +11,11 @@ use opentelemetry::{
 use crate::{
     attributes::AttributeSet,
     instrumentation::Scope,
-    metrics::{aggregation::Aggregation, internal::Measure},
+    metrics::{
+        aggregation::Aggregation,
+        exemplar::{ExemplarReservoir, ExemplarReservoirFactory},
+        internal::Measure,
+    },
 };
 
 pub(crate) const EMPTY_MEASURE_MSG: &str = "no aggregators for observable instrument";
@@ -180,6 +184,15 @@ pub struct Stream {
     /// dropped. If the set is empty, all attributes will be dropped, if `None` all
     /// attributes will be kept.
     pub allowed_attribute_keys: Option<Arc<HashSet<Key>>>,
+    /// The maximum number of data points the stream can hold.
+    ///
+    /// Measurements with new attribute sets past this limit are aggregated into a
+    /// single data point with the `otel.metric.overflow=true` attribute. If `None`,
+    /// the limit of the reader or the meter provider is used.
+    pub cardinality_limit: Option<usize>,
+    /// Creates the exemplar reservoir of each data point of the stream, if
+    /// `None` the default reservoir of the aggregation is used.
+    pub(crate) exemplar_reservoir: Option<ExemplarReservoirFactory>,
 }
 
 impl Stream {
@@ -221,6 +234,30 @@ impl Stream {
 
         self
     }
+
+    /// Set the maximum number of data points of the stream.
+    ///
+    /// A limit of zero is ignored.
+    pub fn cardinality_limit(mut self, limit: usize) -> Self {
+        if limit > 0 {
+            self.cardinality_limit = Some(limit);
+        }
+        self
+    }
+
+    /// Set the exemplar reservoir of the stream.
+    ///
+    /// `reservoir` is called to create the reservoir of each data point. By
+    /// default, histograms with explicit buckets keep the last exemplar of each
+    /// bucket and other aggregations keep a fixed number of sampled exemplars.
+    pub fn exemplar_reservoir<R, F>(mut self, reservoir: F) -> Self
+    where
+        R: ExemplarReservoir + 'static,
+        F: Fn() -> R + Send + Sync + 'static,
+    {
+        self.exemplar_reservoir = Some(ExemplarReservoirFactory::new(reservoir));
+        self
+    }
 }
 
 /// The identifying properties of an instrument.
@@ -1,14 +1,25 @@
-use std::{marker, sync::Arc};
+use std::{
+    marker,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 use once_cell::sync::Lazy;
 use opentelemetry::KeyValue;
 
 use crate::{
-    metrics::data::{Aggregation, Gauge, Temporality},
+    metrics::{
+        data::{Aggregation, Exemplar, Gauge, Temporality},
+        exemplar::ExemplarReservoirFactory,
+        ExemplarFilter,
+    },
     AttributeSet,
 };
 
 use super::{
+    exemplar,
     exponential_histogram::ExpoHistogram,
     histogram::Histogram,
     last_value::LastValue,
@@ -16,15 +27,57 @@ use super::{
     Number,
 };
 
-const STREAM_CARDINALITY_LIMIT: u32 = 2000;
+/// The default maximum number of data points of a metric stream.
+pub(crate) const STREAM_CARDINALITY_LIMIT: usize = 2000;
 pub(crate) static STREAM_OVERFLOW_ATTRIBUTE_SET: Lazy<AttributeSet> = Lazy::new(|| {
     let key_values: [KeyValue; 1] = [KeyValue::new("otel.metric.overflow", "true")];
     AttributeSet::from(&key_values[..])
 });
 
-/// Checks whether aggregator has hit cardinality limit for metric streams
-pub(crate) fn is_under_cardinality_limit(size: usize) -> bool {
-    size < STREAM_CARDINALITY_LIMIT as usize - 1
+/// The cardinality limit of a metric stream, and the number of measurements
+/// that exceeded it since the last collection.
+#[derive(Debug)]
+pub(crate) struct CardinalityLimit {
+    limit: usize,
+    overflowed: AtomicU64,
+}
+
+impl Default for CardinalityLimit {
+    fn default() -> Self {
+        CardinalityLimit::new(STREAM_CARDINALITY_LIMIT)
+    }
+}
+
+impl CardinalityLimit {
+    pub(crate) fn new(limit: usize) -> Self {
+        CardinalityLimit {
+            limit,
+            overflowed: AtomicU64::new(0),
+        }
+    }
+
+    /// The maximum number of data points of the stream.
+    pub(crate) fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Checks whether a stream holding `size` attribute sets can accept a new one.
+    ///
+    /// One data point is reserved for the overflow attribute set.
+    pub(crate) fn is_under_limit(&self, size: usize) -> bool {
+        size < self.limit.saturating_sub(1)
+    }
+
+    /// Records a measurement with a new attribute set that was aggregated into the
+    /// overflow data point.
+    pub(crate) fn record_overflow(&self) {
+        self.overflowed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the number of overflowed measurements since the last call.
+    pub(crate) fn take_overflowed(&self) -> u64 {
+        self.overflowed.swap(0, Ordering::Relaxed)
+    }
 }
 
 /// Receives measurements to be aggregated.
@@ -77,20 +130,47 @@ pub(crate) struct AggregateBuilder<T> {
     /// measurements.
     filter: Option<Filter>,
 
+    /// The cardinality limit of the aggregate function's stream.
+    cardinality_limit: Arc<CardinalityLimit>,
+
+    /// Selects the measurements offered to the exemplar reservoirs.
+    exemplar_filter: ExemplarFilter,
+
+    /// Creates the exemplar reservoirs of the data points, or the default
+    /// reservoirs of the aggregation if `None`.
+    exemplar_reservoir: Option<ExemplarReservoirFactory>,
+
     _marker: marker::PhantomData<T>,
 }
 
 type Filter = Arc<dyn Fn(&KeyValue) -> bool + Send + Sync>;
 
 impl<T: Number<T>> AggregateBuilder<T> {
-    pub(crate) fn new(temporality: Option<Temporality>, filter: Option<Filter>) -> Self {
+    pub(crate) fn new(
+        temporality: Option<Temporality>,
+        filter: Option<Filter>,
+        cardinality_limit: Arc<CardinalityLimit>,
+        exemplar_filter: ExemplarFilter,
+    ) -> Self {
         AggregateBuilder {
             temporality,
             filter,
+            cardinality_limit,
+            exemplar_filter,
+            exemplar_reservoir: None,
             _marker: marker::PhantomData,
         }
     }
 
+    /// Sets the exemplar reservoir of the data points of the aggregate functions.
+    pub(crate) fn with_exemplar_reservoir(
+        mut self,
+        exemplar_reservoir: Option<ExemplarReservoirFactory>,
+    ) -> Self {
+        self.exemplar_reservoir = exemplar_reservoir;
+        self
+    }
+
     /// Wraps the passed in measure with an attribute filtering function.
     fn filter(&self, f: impl Measure<T>) -> impl Measure<T> {
         let filter = self.filter.clone();
@@ -102,13 +182,36 @@ impl<T: Number<T>> AggregateBuilder<T> {
         }
     }
 
+    /// Wraps the passed in measure with an attribute filtering function, also
+    /// passing it the exemplar sampled from the measurement, if any.
+    ///
+    /// The attributes removed by the filter are recorded on the exemplar.
+    fn filter_with_exemplars(
+        &self,
+        f: impl Fn(T, AttributeSet, Option<Exemplar<T>>) + Send + Sync + 'static,
+    ) -> impl Measure<T> {
+        let filter = self.filter.clone();
+        let exemplar_filter = self.exemplar_filter;
+        move |n, mut attrs: AttributeSet| {
+            let mut exemplar = exemplar::sample(exemplar_filter, n);
+            if let Some(filter) = &filter {
+                if let Some(exemplar) = exemplar.as_mut() {
+                    exemplar.filtered_attributes =
+                        exemplar::filtered_attributes(&attrs, filter.as_ref());
+                }
+                attrs.retain(filter.as_ref());
+            }
+            f(n, attrs, exemplar)
+        }
+    }
+
     /// Builds a last-value aggregate function input and output.
     ///
     /// [Builder::temporality] is ignored and delta is always used.
     pub(crate) fn last_value(&self) -> (impl Measure<T>, impl ComputeAggregation) {
         // Delta temporality is the only temporality that makes semantic sense for
         // a last-value aggregate.
-        let lv_filter = Arc::new(LastValue::new());
+        let lv_filter = Arc::new(LastValue::new(Arc::clone(&self.cardinality_limit)));
         let lv_agg = Arc::clone(&lv_filter);
 
         (
@@ -136,7 +239,10 @@ impl<T: Number<T>> AggregateBuilder<T> {
         &self,
         monotonic: bool,
     ) -> (impl Measure<T>, impl ComputeAggregation) {
-        let s = Arc::new(PrecomputedSum::new(monotonic));
+        let s = Arc::new(PrecomputedSum::new(
+            monotonic,
+            Arc::clone(&self.cardinality_limit),
+        ));
         let agg_sum = Arc::clone(&s);
         let t = self.temporality;
 
@@ -151,12 +257,16 @@ impl<T: Number<T>> AggregateBuilder<T> {
 
     /// Builds a sum aggregate function input and output.
     pub(crate) fn sum(&self, monotonic: bool) -> (impl Measure<T>, impl ComputeAggregation) {
-        let s = Arc::new(Sum::new(monotonic));
+        let s = Arc::new(Sum::new(
+            monotonic,
+            Arc::clone(&self.cardinality_limit),
+            self.exemplar_reservoir.clone(),
+        ));
         let agg_sum = Arc::clone(&s);
         let t = self.temporality;
 
         (
-            self.filter(move |n, a| s.measure(n, a)),
+            self.filter_with_exemplars(move |n, a, e| s.measure(n, a, e)),
             move |dest: Option<&mut dyn Aggregation>| match t {
                 Some(Temporality::Delta) => agg_sum.delta(dest),
                 _ => agg_sum.cumulative(dest),
@@ -171,12 +281,18 @@ impl<T: Number<T>> AggregateBuilder<T> {
         record_min_max: bool,
         record_sum: bool,
     ) -> (impl Measure<T>, impl ComputeAggregation) {
-        let h = Arc::new(Histogram::new(boundaries, record_min_max, record_sum));
+        let h = Arc::new(Histogram::new(
+            boundaries,
+            record_min_max,
+            record_sum,
+            Arc::clone(&self.cardinality_limit),
+            self.exemplar_reservoir.clone(),
+        ));
         let agg_h = Arc::clone(&h);
         let t = self.temporality;
 
         (
-            self.filter(move |n, a| h.measure(n, a)),
+            self.filter_with_exemplars(move |n, a, e| h.measure(n, a, e)),
             move |dest: Option<&mut dyn Aggregation>| match t {
                 Some(Temporality::Delta) => agg_h.delta(dest),
                 _ => agg_h.cumulative(dest),
@@ -197,12 +313,14 @@ impl<T: Number<T>> AggregateBuilder<T> {
             max_scale,
             record_min_max,
             record_sum,
+            Arc::clone(&self.cardinality_limit),
+            self.exemplar_reservoir.clone(),
         ));
         let agg_h = Arc::clone(&h);
         let t = self.temporality;
 
         (
-            self.filter(move |n, a| h.measure(n, a)),
+            self.filter_with_exemplars(move |n, a, e| h.measure(n, a, e)),
             move |dest: Option<&mut dyn Aggregation>| match t {
                 Some(Temporality::Delta) => agg_h.delta(dest),
                 _ => agg_h.cumulative(dest),
@@ -223,7 +341,9 @@ mod tests {
 
     #[test]
     fn last_value_aggregation() {
-        let (measure, agg) = AggregateBuilder::<u64>::new(None, None).last_value();
+        let (measure, agg) =
+            AggregateBuilder::<u64>::new(None, None, Default::default(), Default::default())
+                .last_value();
         let mut a = Gauge {
             data_points: vec![DataPoint {
                 attributes: AttributeSet::from(&[KeyValue::new("a", 1)][..]),
@@ -251,8 +371,13 @@ mod tests {
     #[test]
     fn precomputed_sum_aggregation() {
         for temporality in [Temporality::Delta, Temporality::Cumulative] {
-            let (measure, agg) =
-                AggregateBuilder::<u64>::new(Some(temporality), None).precomputed_sum(true);
+            let (measure, agg) = AggregateBuilder::<u64>::new(
+                Some(temporality),
+                None,
+                Default::default(),
+                Default::default(),
+            )
+            .precomputed_sum(true);
             let mut a = Sum {
                 data_points: vec![
                     DataPoint {
@@ -298,7 +423,13 @@ mod tests {
     #[test]
     fn sum_aggregation() {
         for temporality in [Temporality::Delta, Temporality::Cumulative] {
-            let (measure, agg) = AggregateBuilder::<u64>::new(Some(temporality), None).sum(true);
+            let (measure, agg) = AggregateBuilder::<u64>::new(
+                Some(temporality),
+                None,
+                Default::default(),
+                Default::default(),
+            )
+            .sum(true);
             let mut a = Sum {
                 data_points: vec![
                     DataPoint {
@@ -344,8 +475,13 @@ mod tests {
     #[test]
     fn explicit_bucket_histogram_aggregation() {
         for temporality in [Temporality::Delta, Temporality::Cumulative] {
-            let (measure, agg) = AggregateBuilder::<u64>::new(Some(temporality), None)
-                .explicit_bucket_histogram(vec![1.0], true, true);
+            let (measure, agg) = AggregateBuilder::<u64>::new(
+                Some(temporality),
+                None,
+                Default::default(),
+                Default::default(),
+            )
+            .explicit_bucket_histogram(vec![1.0], true, true);
             let mut a = Histogram {
                 data_points: vec![HistogramDataPoint {
                     attributes: AttributeSet::from(&[KeyValue::new("a2", 2)][..]),
@@ -390,8 +526,13 @@ mod tests {
     #[test]
     fn exponential_histogram_aggregation() {
         for temporality in [Temporality::Delta, Temporality::Cumulative] {
-            let (measure, agg) = AggregateBuilder::<u64>::new(Some(temporality), None)
-                .exponential_bucket_histogram(4, 20, true, true);
+            let (measure, agg) = AggregateBuilder::<u64>::new(
+                Some(temporality),
+                None,
+                Default::default(),
+                Default::default(),
+            )
+            .exponential_bucket_histogram(4, 20, true, true);
             let mut a = ExponentialHistogram {
                 data_points: vec![ExponentialHistogramDataPoint {
                     attributes: AttributeSet::from(&[KeyValue::new("a2", 2)][..]),
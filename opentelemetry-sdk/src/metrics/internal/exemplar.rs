@@ -0,0 +1,211 @@
+//! Exemplar reservoirs as defined by the [specification].
+//!
+//! [specification]: https://github.com/open-telemetry/opentelemetry-specification/blob/v1.30.0/specification/metrics/sdk.md#exemplar
+use std::time::SystemTime;
+
+use opentelemetry::{Context, KeyValue};
+
+use crate::{
+    metrics::{
+        data::Exemplar, exemplar::ExemplarReservoirFactory, ExemplarFilter, ExemplarReservoir,
+    },
+    AttributeSet,
+};
+
+use super::Number;
+
+/// The maximum size of the reservoir of an exponential histogram data point.
+pub(crate) const EXPO_HISTOGRAM_RESERVOIR_SIZE: usize = 20;
+
+/// Returns an [Exemplar] for `value` if `filter` samples the measurement made in
+/// the current context.
+///
+/// The trace and span IDs are the ones of the active span, if any.
+pub(crate) fn sample<T>(filter: ExemplarFilter, value: T) -> Option<Exemplar<T>> {
+    if filter == ExemplarFilter::AlwaysOff {
+        return None;
+    }
+
+    Context::map_current(|cx| {
+        if !filter.should_sample(cx) {
+            return None;
+        }
+
+        #[cfg(feature = "trace")]
+        let (trace_id, span_id) = {
+            use opentelemetry::trace::TraceContextExt;
+
+            let span_context = cx.span().span_context().clone();
+            if span_context.is_valid() {
+                (
+                    span_context.trace_id().to_bytes(),
+                    span_context.span_id().to_bytes(),
+                )
+            } else {
+                ([0; 16], [0; 8])
+            }
+        };
+        #[cfg(not(feature = "trace"))]
+        let (trace_id, span_id) = ([0; 16], [0; 8]);
+
+        Some(Exemplar {
+            filtered_attributes: vec![],
+            time: SystemTime::now(),
+            value,
+            span_id,
+            trace_id,
+        })
+    })
+}
+
+/// Returns the attributes of `attrs` removed by the attribute `filter` of a view.
+pub(crate) fn filtered_attributes(
+    attrs: &AttributeSet,
+    filter: &dyn Fn(&KeyValue) -> bool,
+) -> Vec<KeyValue> {
+    attrs
+        .iter()
+        .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+        .filter(|kv| !filter(kv))
+        .collect()
+}
+
+/// The exemplars sampled for a data point, stored in the cells selected by its
+/// [ExemplarReservoir].
+#[derive(Debug)]
+pub(crate) struct Exemplars<T> {
+    reservoir: Box<dyn ExemplarReservoir>,
+    cells: Vec<Option<Exemplar<T>>>,
+}
+
+impl<T: Number<T>> Exemplars<T> {
+    pub(crate) fn new(reservoir: Box<dyn ExemplarReservoir>) -> Self {
+        Exemplars {
+            reservoir,
+            cells: Vec::new(),
+        }
+    }
+
+    pub(crate) fn offer(&mut self, exemplar: Exemplar<T>) {
+        let Some(idx) = self.reservoir.offer(exemplar.value.into_float()) else {
+            return;
+        };
+        if self.cells.len() <= idx {
+            self.cells.resize_with(idx + 1, || None);
+        }
+        self.cells[idx] = Some(exemplar);
+    }
+
+    /// Returns the sampled exemplars, ordered by cell, and resets the reservoir.
+    pub(crate) fn collect(&mut self) -> Vec<Exemplar<T>> {
+        self.reservoir.reset();
+        self.cells.iter().flatten().cloned().collect()
+    }
+}
+
+/// Offers `exemplar` to the exemplars of a data point, creating them from
+/// `reservoir` on the first sampled measurement.
+pub(crate) fn offer<T: Number<T>>(
+    exemplars: &mut Option<Exemplars<T>>,
+    reservoir: &ExemplarReservoirFactory,
+    exemplar: Exemplar<T>,
+) {
+    exemplars
+        .get_or_insert_with(|| Exemplars::new(reservoir.create()))
+        .offer(exemplar)
+}
+
+/// Returns the exemplars sampled for a data point, if any.
+pub(crate) fn collect<T: Number<T>>(exemplars: &mut Option<Exemplars<T>>) -> Vec<Exemplar<T>> {
+    exemplars
+        .as_mut()
+        .map(Exemplars::collect)
+        .unwrap_or_default()
+}
+
+impl<T: PartialEq> PartialEq for Exemplars<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{
+        AlignedHistogramBucketExemplarReservoir, SimpleFixedSizeExemplarReservoir,
+    };
+
+    fn exemplar(value: u64) -> Exemplar<u64> {
+        Exemplar {
+            filtered_attributes: vec![],
+            time: SystemTime::now(),
+            value,
+            span_id: [0; 8],
+            trace_id: [0; 16],
+        }
+    }
+
+    fn values(exemplars: Vec<Exemplar<u64>>) -> Vec<u64> {
+        exemplars.into_iter().map(|e| e.value).collect()
+    }
+
+    #[test]
+    fn test_sample_always_off() {
+        assert!(sample(ExemplarFilter::AlwaysOff, 1u64).is_none());
+    }
+
+    #[test]
+    fn test_sample_always_on_without_span() {
+        let exemplar = sample(ExemplarFilter::AlwaysOn, 1u64).expect("exemplar expected");
+        assert_eq!(exemplar.value, 1);
+        assert_eq!(exemplar.trace_id, [0; 16]);
+        assert_eq!(exemplar.span_id, [0; 8]);
+    }
+
+    #[test]
+    fn test_filtered_attributes() {
+        let attrs = AttributeSet::from(&[KeyValue::new("a", 1), KeyValue::new("b", 2)][..]);
+        let filtered = filtered_attributes(&attrs, &|kv: &KeyValue| kv.key.as_str() == "a");
+        assert_eq!(filtered, vec![KeyValue::new("b", 2)]);
+    }
+
+    #[test]
+    fn test_exemplars_fixed_size() {
+        let mut exemplars = Exemplars::new(Box::new(SimpleFixedSizeExemplarReservoir::new(2)));
+        assert!(exemplars.collect().is_empty());
+
+        exemplars.offer(exemplar(1));
+        exemplars.offer(exemplar(2));
+        assert_eq!(values(exemplars.collect()), vec![1, 2]);
+
+        // the first measurements after a collection always replace the old ones
+        exemplars.offer(exemplar(3));
+        assert_eq!(values(exemplars.collect()), vec![3, 2]);
+
+        for i in 0..100 {
+            exemplars.offer(exemplar(i));
+        }
+        let sampled = values(exemplars.collect());
+        assert_eq!(sampled.len(), 2);
+        assert!(sampled.iter().all(|v| *v < 100));
+    }
+
+    #[test]
+    fn test_exemplars_empty_reservoir() {
+        let mut exemplars = Exemplars::new(Box::new(SimpleFixedSizeExemplarReservoir::new(0)));
+        exemplars.offer(exemplar(1));
+        assert!(exemplars.collect().is_empty());
+    }
+
+    #[test]
+    fn test_exemplars_histogram_buckets() {
+        let mut exemplars = Exemplars::new(Box::new(AlignedHistogramBucketExemplarReservoir::new(
+            vec![1.0, 2.0],
+        )));
+        exemplars.offer(exemplar(3));
+        exemplars.offer(exemplar(0));
+        exemplars.offer(exemplar(4));
+        assert_eq!(values(exemplars.collect()), vec![0, 4]);
+    }
+}
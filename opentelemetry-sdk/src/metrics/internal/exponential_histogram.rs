@@ -1,14 +1,27 @@
-use std::{collections::HashMap, f64::consts::LOG2_E, sync::Mutex, time::SystemTime};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    f64::consts::LOG2_E,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
 
 use once_cell::sync::Lazy;
 use opentelemetry::metrics::MetricsError;
 
 use crate::{
-    metrics::data::{self, Aggregation, Temporality},
+    metrics::{
+        data::{self, Aggregation, Exemplar, Temporality},
+        exemplar::ExemplarReservoirFactory,
+        SimpleFixedSizeExemplarReservoir,
+    },
     AttributeSet,
 };
 
-use super::Number;
+use super::{
+    aggregate::{CardinalityLimit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    exemplar::{self, Exemplars, EXPO_HISTOGRAM_RESERVOIR_SIZE},
+    Number,
+};
 
 pub(crate) const EXPO_MAX_SCALE: i8 = 20;
 pub(crate) const EXPO_MIN_SCALE: i8 = -10;
@@ -30,6 +43,9 @@ struct ExpoHistogramDataPoint<T> {
     pos_buckets: ExpoBuckets,
     neg_buckets: ExpoBuckets,
     zero_count: u64,
+
+    /// Created on the first sampled measurement of the data point.
+    exemplars: Option<Exemplars<T>>,
 }
 
 impl<T: Number<T>> ExpoHistogramDataPoint<T> {
@@ -46,6 +62,7 @@ impl<T: Number<T>> ExpoHistogramDataPoint<T> {
             pos_buckets: ExpoBuckets::default(),
             neg_buckets: ExpoBuckets::default(),
             zero_count: 0,
+            exemplars: None,
         }
     }
 }
@@ -318,6 +335,8 @@ pub(crate) struct ExpoHistogram<T> {
     max_scale: i8,
 
     values: Mutex<HashMap<AttributeSet, ExpoHistogramDataPoint<T>>>,
+    cardinality_limit: Arc<CardinalityLimit>,
+    reservoir: ExemplarReservoirFactory,
 
     start: Mutex<SystemTime>,
 }
@@ -329,18 +348,27 @@ impl<T: Number<T>> ExpoHistogram<T> {
         max_scale: i8,
         record_min_max: bool,
         record_sum: bool,
+        cardinality_limit: Arc<CardinalityLimit>,
+        reservoir: Option<ExemplarReservoirFactory>,
     ) -> Self {
+        let reservoir = reservoir.unwrap_or_else(|| {
+            let size = EXPO_HISTOGRAM_RESERVOIR_SIZE.min(max_size as usize);
+            ExemplarReservoirFactory::new(move || SimpleFixedSizeExemplarReservoir::new(size))
+        });
+
         ExpoHistogram {
             record_sum,
             record_min_max,
             max_size: max_size as i32,
             max_scale,
             values: Mutex::new(HashMap::default()),
+            cardinality_limit,
+            reservoir,
             start: Mutex::new(SystemTime::now()),
         }
     }
 
-    pub(crate) fn measure(&self, value: T, attrs: AttributeSet) {
+    pub(crate) fn measure(&self, value: T, attrs: AttributeSet, exemplar: Option<Exemplar<T>>) {
         let f_value = value.into_float();
         // Ignore NaN and infinity.
         if f_value.is_infinite() || f_value.is_nan() {
@@ -348,15 +376,32 @@ impl<T: Number<T>> ExpoHistogram<T> {
         }
 
         if let Ok(mut values) = self.values.lock() {
-            let v = values.entry(attrs).or_insert_with(|| {
+            let size = values.len();
+            let new_data_point = || {
                 ExpoHistogramDataPoint::new(
                     self.max_size,
                     self.max_scale,
                     self.record_min_max,
                     self.record_sum,
                 )
-            });
-            v.record(value)
+            };
+            let v = match values.entry(attrs) {
+                Entry::Occupied(occupied_entry) => occupied_entry.into_mut(),
+                Entry::Vacant(vacant_entry) => {
+                    if self.cardinality_limit.is_under_limit(size) {
+                        vacant_entry.insert(new_data_point())
+                    } else {
+                        self.cardinality_limit.record_overflow();
+                        values
+                            .entry(STREAM_OVERFLOW_ATTRIBUTE_SET.clone())
+                            .or_insert_with(new_data_point)
+                    }
+                }
+            };
+            v.record(value);
+            if let Some(exemplar) = exemplar {
+                exemplar::offer(&mut v.exemplars, &self.reservoir, exemplar);
+            }
         }
     }
 
@@ -394,7 +439,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
             h.data_points.reserve_exact(n - h.data_points.capacity());
         }
 
-        for (a, b) in values.drain() {
+        for (a, mut b) in values.drain() {
             h.data_points.push(data::ExponentialHistogramDataPoint {
                 attributes: a,
                 start_time: start,
@@ -422,7 +467,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
                     counts: b.neg_buckets.counts.clone(),
                 },
                 zero_threshold: 0.0,
-                exemplars: vec![],
+                exemplars: exemplar::collect(&mut b.exemplars),
             });
         }
 
@@ -457,7 +502,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
         let h = h.unwrap_or_else(|| new_agg.as_mut().expect("present if h is none"));
         h.temporality = Temporality::Cumulative;
 
-        let values = match self.values.lock() {
+        let mut values = match self.values.lock() {
             Ok(g) => g,
             Err(_) => return (0, None),
         };
@@ -472,7 +517,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
         // are unbounded number of attribute sets being aggregated. Attribute
         // sets that become "stale" need to be forgotten so this will not
         // overload the system.
-        for (a, b) in values.iter() {
+        for (a, b) in values.iter_mut() {
             h.data_points.push(data::ExponentialHistogramDataPoint {
                 attributes: a.clone(),
                 start_time: start,
@@ -500,7 +545,7 @@ impl<T: Number<T>> ExpoHistogram<T> {
                     counts: b.neg_buckets.counts.clone(),
                 },
                 zero_threshold: 0.0,
-                exemplars: vec![],
+                exemplars: exemplar::collect(&mut b.exemplars),
             });
         }
 
@@ -673,9 +718,9 @@ mod tests {
         ];
 
         for test in test_cases {
-            let h = ExpoHistogram::new(4, 20, true, true);
+            let h = ExpoHistogram::new(4, 20, true, true, Default::default(), None);
             for v in test.values {
-                h.measure(v, alice.clone());
+                h.measure(v, alice.clone(), None);
             }
             let values = h.values.lock().unwrap();
             let dp = values.get(&alice).unwrap();
@@ -724,9 +769,9 @@ mod tests {
         ];
 
         for test in test_cases {
-            let h = ExpoHistogram::new(4, 20, true, true);
+            let h = ExpoHistogram::new(4, 20, true, true, Default::default(), None);
             for v in test.values {
-                h.measure(v, alice.clone());
+                h.measure(v, alice.clone(), None);
             }
             let values = h.values.lock().unwrap();
             let dp = values.get(&alice).unwrap();
@@ -1194,6 +1239,7 @@ mod tests {
             record_min_max: true,
             record_sum: true,
             zero_count: 0,
+            exemplars: None,
         };
 
         let mut ehdp = ExpoHistogramDataPoint::new(4, 20, true, true);
@@ -1244,13 +1290,18 @@ mod tests {
                 name: "Delta Single",
                 build: Box::new(move || {
                     box_val(
-                        AggregateBuilder::new(Some(Temporality::Delta), None)
-                            .exponential_bucket_histogram(
-                                max_size,
-                                max_scale,
-                                record_min_max,
-                                record_sum,
-                            ),
+                        AggregateBuilder::new(
+                            Some(Temporality::Delta),
+                            None,
+                            Default::default(),
+                            Default::default(),
+                        )
+                        .exponential_bucket_histogram(
+                            max_size,
+                            max_scale,
+                            record_min_max,
+                            record_sum,
+                        ),
                     )
                 }),
                 input: vec![vec![4, 4, 4, 2, 16, 1]
@@ -1287,13 +1338,18 @@ mod tests {
                 name: "Cumulative Single",
                 build: Box::new(move || {
                     box_val(
-                        internal::AggregateBuilder::new(Some(Temporality::Cumulative), None)
-                            .exponential_bucket_histogram(
-                                max_size,
-                                max_scale,
-                                record_min_max,
-                                record_sum,
-                            ),
+                        internal::AggregateBuilder::new(
+                            Some(Temporality::Cumulative),
+                            None,
+                            Default::default(),
+                            Default::default(),
+                        )
+                        .exponential_bucket_histogram(
+                            max_size,
+                            max_scale,
+                            record_min_max,
+                            record_sum,
+                        ),
                     )
                 }),
                 input: vec![vec![4, 4, 4, 2, 16, 1]
@@ -1330,13 +1386,18 @@ mod tests {
                 name: "Delta Multiple",
                 build: Box::new(move || {
                     box_val(
-                        internal::AggregateBuilder::new(Some(Temporality::Delta), None)
-                            .exponential_bucket_histogram(
-                                max_size,
-                                max_scale,
-                                record_min_max,
-                                record_sum,
-                            ),
+                        internal::AggregateBuilder::new(
+                            Some(Temporality::Delta),
+                            None,
+                            Default::default(),
+                            Default::default(),
+                        )
+                        .exponential_bucket_histogram(
+                            max_size,
+                            max_scale,
+                            record_min_max,
+                            record_sum,
+                        ),
                     )
                 }),
                 input: vec![
@@ -1376,13 +1437,18 @@ mod tests {
                 name: "Cumulative Multiple ",
                 build: Box::new(move || {
                     box_val(
-                        internal::AggregateBuilder::new(Some(Temporality::Cumulative), None)
-                            .exponential_bucket_histogram(
-                                max_size,
-                                max_scale,
-                                record_min_max,
-                                record_sum,
-                            ),
+                        internal::AggregateBuilder::new(
+                            Some(Temporality::Cumulative),
+                            None,
+                            Default::default(),
+                            Default::default(),
+                        )
+                        .exponential_bucket_histogram(
+                            max_size,
+                            max_scale,
+                            record_min_max,
+                            record_sum,
+                        ),
                     )
                 }),
                 input: vec![
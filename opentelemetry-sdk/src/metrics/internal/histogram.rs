@@ -1,11 +1,16 @@
-use std::{collections::HashMap, sync::Mutex, time::SystemTime};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
 
-use crate::metrics::data::{self, Aggregation, Temporality};
+use crate::metrics::data::{self, Aggregation, Exemplar, Temporality};
+use crate::metrics::{exemplar::ExemplarReservoirFactory, AlignedHistogramBucketExemplarReservoir};
 use crate::{attributes::AttributeSet, metrics::data::HistogramDataPoint};
-use opentelemetry::{global, metrics::MetricsError};
 
 use super::{
-    aggregate::{is_under_cardinality_limit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    aggregate::{CardinalityLimit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    exemplar::{self, Exemplars},
     Number,
 };
 
@@ -16,6 +21,8 @@ struct Buckets<T> {
     total: T,
     min: T,
     max: T,
+    /// Created on the first sampled measurement of the data point.
+    exemplars: Option<Exemplars<T>>,
 }
 
 impl<T: Number<T>> Buckets<T> {
@@ -47,23 +54,40 @@ struct HistValues<T> {
     record_sum: bool,
     bounds: Vec<f64>,
     values: Mutex<HashMap<AttributeSet, Buckets<T>>>,
+    cardinality_limit: Arc<CardinalityLimit>,
+    reservoir: ExemplarReservoirFactory,
 }
 
 impl<T: Number<T>> HistValues<T> {
-    fn new(mut bounds: Vec<f64>, record_sum: bool) -> Self {
+    fn new(
+        mut bounds: Vec<f64>,
+        record_sum: bool,
+        cardinality_limit: Arc<CardinalityLimit>,
+        reservoir: Option<ExemplarReservoirFactory>,
+    ) -> Self {
         bounds.retain(|v| !v.is_nan());
         bounds.sort_by(|a, b| a.partial_cmp(b).expect("NaNs filtered out"));
 
+        // Histograms keep an exemplar per bucket by default.
+        let reservoir = reservoir.unwrap_or_else(|| {
+            let bounds = bounds.clone();
+            ExemplarReservoirFactory::new(move || {
+                AlignedHistogramBucketExemplarReservoir::new(bounds.clone())
+            })
+        });
+
         HistValues {
             record_sum,
             bounds,
             values: Mutex::new(Default::default()),
+            cardinality_limit,
+            reservoir,
         }
     }
 }
 
 impl<T: Number<T>> HistValues<T> {
-    fn measure(&self, measurement: T, attrs: AttributeSet) {
+    fn measure(&self, measurement: T, attrs: AttributeSet, exemplar: Option<Exemplar<T>>) {
         let f = measurement.into_float();
 
         // This search will return an index in the range `[0, bounds.len()]`, where
@@ -93,10 +117,10 @@ impl<T: Number<T>> HistValues<T> {
             // Ensure min and max are recorded values (not zero), for new buckets.
             (b.min, b.max) = (measurement, measurement);
 
-            if is_under_cardinality_limit(size) {
+            if self.cardinality_limit.is_under_limit(size) {
                 values.entry(attrs).or_insert(b)
             } else {
-                global::handle_error(MetricsError::Other("Warning: Maximum data points for metric stream exceeded. Entry added to overflow.".into()));
+                self.cardinality_limit.record_overflow();
                 values
                     .entry(STREAM_OVERFLOW_ATTRIBUTE_SET.clone())
                     .or_insert(b)
@@ -107,6 +131,9 @@ impl<T: Number<T>> HistValues<T> {
         if self.record_sum {
             b.sum(measurement)
         }
+        if let Some(exemplar) = exemplar {
+            exemplar::offer(&mut b.exemplars, &self.reservoir, exemplar);
+        }
     }
 }
 
@@ -119,16 +146,27 @@ pub(crate) struct Histogram<T> {
 }
 
 impl<T: Number<T>> Histogram<T> {
-    pub(crate) fn new(boundaries: Vec<f64>, record_min_max: bool, record_sum: bool) -> Self {
+    pub(crate) fn new(
+        boundaries: Vec<f64>,
+        record_min_max: bool,
+        record_sum: bool,
+        cardinality_limit: Arc<CardinalityLimit>,
+        reservoir: Option<ExemplarReservoirFactory>,
+    ) -> Self {
         Histogram {
-            hist_values: HistValues::new(boundaries, record_sum),
+            hist_values: HistValues::new(boundaries, record_sum, cardinality_limit, reservoir),
             record_min_max,
             start: Mutex::new(SystemTime::now()),
         }
     }
 
-    pub(crate) fn measure(&self, measurement: T, attrs: AttributeSet) {
-        self.hist_values.measure(measurement, attrs)
+    pub(crate) fn measure(
+        &self,
+        measurement: T,
+        attrs: AttributeSet,
+        exemplar: Option<Exemplar<T>>,
+    ) {
+        self.hist_values.measure(measurement, attrs, exemplar)
     }
 
     pub(crate) fn delta(
@@ -163,7 +201,7 @@ impl<T: Number<T>> Histogram<T> {
             h.data_points.reserve_exact(n - h.data_points.capacity());
         }
 
-        for (a, b) in values.drain() {
+        for (a, mut b) in values.drain() {
             h.data_points.push(HistogramDataPoint {
                 attributes: a,
                 start_time: start,
@@ -186,7 +224,7 @@ impl<T: Number<T>> Histogram<T> {
                 } else {
                     None
                 },
-                exemplars: vec![],
+                exemplars: exemplar::collect(&mut b.exemplars),
             });
         }
 
@@ -202,7 +240,7 @@ impl<T: Number<T>> Histogram<T> {
         &self,
         dest: Option<&mut dyn Aggregation>,
     ) -> (usize, Option<Box<dyn Aggregation>>) {
-        let values = match self.hist_values.values.lock() {
+        let mut values = match self.hist_values.values.lock() {
             Ok(guard) if !guard.is_empty() => guard,
             _ => return (0, None),
         };
@@ -234,7 +272,7 @@ impl<T: Number<T>> Histogram<T> {
         // are unbounded number of attribute sets being aggregated. Attribute
         // sets that become "stale" need to be forgotten so this will not
         // overload the system.
-        for (a, b) in values.iter() {
+        for (a, b) in values.iter_mut() {
             h.data_points.push(HistogramDataPoint {
                 attributes: a.clone(),
                 start_time: start,
@@ -257,7 +295,7 @@ impl<T: Number<T>> Histogram<T> {
                 } else {
                     None
                 },
-                exemplars: vec![],
+                exemplars: exemplar::collect(&mut b.exemplars),
             });
         }
 
@@ -1,14 +1,13 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
-    sync::Mutex,
+    sync::{Arc, Mutex},
     time::SystemTime,
 };
 
 use crate::{attributes::AttributeSet, metrics::data::DataPoint};
-use opentelemetry::{global, metrics::MetricsError};
 
 use super::{
-    aggregate::{is_under_cardinality_limit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    aggregate::{CardinalityLimit, STREAM_OVERFLOW_ATTRIBUTE_SET},
     Number,
 };
 
@@ -19,14 +18,17 @@ struct DataPointValue<T> {
 }
 
 /// Summarizes a set of measurements as the last one made.
-#[derive(Default)]
 pub(crate) struct LastValue<T> {
     values: Mutex<HashMap<AttributeSet, DataPointValue<T>>>,
+    cardinality_limit: Arc<CardinalityLimit>,
 }
 
 impl<T: Number<T>> LastValue<T> {
-    pub(crate) fn new() -> Self {
-        Self::default()
+    pub(crate) fn new(cardinality_limit: Arc<CardinalityLimit>) -> Self {
+        LastValue {
+            values: Mutex::new(HashMap::new()),
+            cardinality_limit,
+        }
     }
 
     pub(crate) fn measure(&self, measurement: T, attrs: AttributeSet) {
@@ -41,11 +43,11 @@ impl<T: Number<T>> LastValue<T> {
                     occupied_entry.insert(d);
                 }
                 Entry::Vacant(vacant_entry) => {
-                    if is_under_cardinality_limit(size) {
+                    if self.cardinality_limit.is_under_limit(size) {
                         vacant_entry.insert(d);
                     } else {
                         values.insert(STREAM_OVERFLOW_ATTRIBUTE_SET.clone(), d);
-                        global::handle_error(MetricsError::Other("Warning: Maximum data points for metric stream exceeded. Entry added to overflow.".into()));
+                        self.cardinality_limit.record_overflow();
                     }
                 }
             }
@@ -1,69 +1,119 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::{
     collections::{hash_map::Entry, HashMap},
-    sync::Mutex,
+    sync::{Arc, Mutex},
     time::SystemTime,
 };
 
 use crate::attributes::AttributeSet;
-use crate::metrics::data::{self, Aggregation, DataPoint, Temporality};
-use opentelemetry::{global, metrics::MetricsError};
+use crate::metrics::data::{self, Aggregation, DataPoint, Exemplar, Temporality};
+use crate::metrics::{exemplar::ExemplarReservoirFactory, SimpleFixedSizeExemplarReservoir};
 
 use super::{
-    aggregate::{is_under_cardinality_limit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    aggregate::{CardinalityLimit, STREAM_OVERFLOW_ATTRIBUTE_SET},
+    exemplar::{self, Exemplars},
     AtomicTracker, Number,
 };
 
+/// The sum of a data point and its sampled exemplars.
+struct SumValue<T> {
+    value: T,
+    /// Created on the first sampled measurement of the data point.
+    exemplars: Option<Exemplars<T>>,
+}
+
+impl<T> SumValue<T> {
+    fn new(value: T) -> Self {
+        SumValue {
+            value,
+            exemplars: None,
+        }
+    }
+}
+
 /// The storage for sums.
 struct ValueMap<T: Number<T>> {
-    values: Mutex<HashMap<AttributeSet, T>>,
+    values: Mutex<HashMap<AttributeSet, SumValue<T>>>,
     has_no_value_attribute_value: AtomicBool,
     no_attribute_value: T::AtomicTracker,
-}
-
-impl<T: Number<T>> Default for ValueMap<T> {
-    fn default() -> Self {
-        ValueMap::new()
-    }
+    /// The exemplars of the data point without attributes, only locked by
+    /// sampled measurements.
+    no_attribute_exemplars: Mutex<Option<Exemplars<T>>>,
+    cardinality_limit: Arc<CardinalityLimit>,
+    /// Creates the exemplar reservoir of each data point, `None` if the
+    /// measurements are never sampled.
+    reservoir: Option<ExemplarReservoirFactory>,
 }
 
 impl<T: Number<T>> ValueMap<T> {
-    fn new() -> Self {
+    fn new(
+        cardinality_limit: Arc<CardinalityLimit>,
+        reservoir: Option<ExemplarReservoirFactory>,
+    ) -> Self {
         ValueMap {
             values: Mutex::new(HashMap::new()),
             has_no_value_attribute_value: AtomicBool::new(false),
             no_attribute_value: T::new_atomic_tracker(),
+            no_attribute_exemplars: Mutex::new(None),
+            cardinality_limit,
+            reservoir,
         }
     }
 }
 
 impl<T: Number<T>> ValueMap<T> {
-    fn measure(&self, measurement: T, attrs: AttributeSet) {
+    fn measure(&self, measurement: T, attrs: AttributeSet, exemplar: Option<Exemplar<T>>) {
+        let exemplar = exemplar.zip(self.reservoir.as_ref());
+
         if attrs.is_empty() {
             self.no_attribute_value.add(measurement);
             self.has_no_value_attribute_value
                 .store(true, Ordering::Release);
+            if let Some((exemplar, reservoir)) = exemplar {
+                if let Ok(mut exemplars) = self.no_attribute_exemplars.lock() {
+                    exemplar::offer(&mut exemplars, reservoir, exemplar);
+                }
+            }
         } else if let Ok(mut values) = self.values.lock() {
             let size = values.len();
             match values.entry(attrs) {
                 Entry::Occupied(mut occupied_entry) => {
                     let sum = occupied_entry.get_mut();
-                    *sum += measurement;
+                    sum.value += measurement;
+                    if let Some((exemplar, reservoir)) = exemplar {
+                        exemplar::offer(&mut sum.exemplars, reservoir, exemplar);
+                    }
                 }
                 Entry::Vacant(vacant_entry) => {
-                    if is_under_cardinality_limit(size) {
-                        vacant_entry.insert(measurement);
+                    let sum = if self.cardinality_limit.is_under_limit(size) {
+                        vacant_entry.insert(SumValue::new(measurement))
                     } else {
+                        self.cardinality_limit.record_overflow();
                         values
                             .entry(STREAM_OVERFLOW_ATTRIBUTE_SET.clone())
-                            .and_modify(|val| *val += measurement)
-                            .or_insert(measurement);
-                        global::handle_error(MetricsError::Other("Warning: Maximum data points for metric stream exceeded. Entry added to overflow.".into()));
+                            .and_modify(|sum| sum.value += measurement)
+                            .or_insert_with(|| SumValue::new(measurement))
+                    };
+                    if let Some((exemplar, reservoir)) = exemplar {
+                        exemplar::offer(&mut sum.exemplars, reservoir, exemplar);
                     }
                 }
             }
         }
     }
+
+    /// Returns the exemplars of the data point without attributes, dropping
+    /// its reservoir if `reset` is set.
+    fn collect_no_attribute_exemplars(&self, reset: bool) -> Vec<Exemplar<T>> {
+        let Ok(mut exemplars) = self.no_attribute_exemplars.lock() else {
+            return vec![];
+        };
+        let collected = exemplar::collect(&mut exemplars);
+        if reset {
+            *exemplars = None;
+        }
+        collected
+    }
 }
 
 /// Summarizes a set of measurements made as their arithmetic sum.
@@ -79,16 +129,29 @@ impl<T: Number<T>> Sum<T> {
     ///
     /// Each sum is scoped by attributes and the aggregation cycle the measurements
     /// were made in.
-    pub(crate) fn new(monotonic: bool) -> Self {
+    pub(crate) fn new(
+        monotonic: bool,
+        cardinality_limit: Arc<CardinalityLimit>,
+        reservoir: Option<ExemplarReservoirFactory>,
+    ) -> Self {
+        // Sums keep a single exemplar per data point by default.
+        let reservoir = reservoir.unwrap_or_else(|| {
+            ExemplarReservoirFactory::new(|| SimpleFixedSizeExemplarReservoir::new(1))
+        });
         Sum {
-            value_map: ValueMap::new(),
+            value_map: ValueMap::new(cardinality_limit, Some(reservoir)),
             monotonic,
             start: Mutex::new(SystemTime::now()),
         }
     }
 
-    pub(crate) fn measure(&self, measurement: T, attrs: AttributeSet) {
-        self.value_map.measure(measurement, attrs)
+    pub(crate) fn measure(
+        &self,
+        measurement: T,
+        attrs: AttributeSet,
+        exemplar: Option<Exemplar<T>>,
+    ) {
+        self.value_map.measure(measurement, attrs, exemplar)
     }
 
     pub(crate) fn delta(
@@ -135,17 +198,17 @@ impl<T: Number<T>> Sum<T> {
                 start_time: Some(prev_start),
                 time: Some(t),
                 value: self.value_map.no_attribute_value.get_and_reset_value(),
-                exemplars: vec![],
+                exemplars: self.value_map.collect_no_attribute_exemplars(true),
             });
         }
 
-        for (attrs, value) in values.drain() {
+        for (attrs, mut sum) in values.drain() {
             s_data.data_points.push(DataPoint {
+                exemplars: exemplar::collect(&mut sum.exemplars),
                 attributes: attrs,
                 start_time: Some(prev_start),
                 time: Some(t),
-                value,
-                exemplars: vec![],
+                value: sum.value,
             });
         }
 
@@ -181,7 +244,7 @@ impl<T: Number<T>> Sum<T> {
         s_data.is_monotonic = self.monotonic;
         s_data.data_points.clear();
 
-        let values = match self.value_map.values.lock() {
+        let mut values = match self.value_map.values.lock() {
             Ok(v) => v,
             Err(_) => return (0, None),
         };
@@ -205,7 +268,7 @@ impl<T: Number<T>> Sum<T> {
                 start_time: Some(prev_start),
                 time: Some(t),
                 value: self.value_map.no_attribute_value.get_value(),
-                exemplars: vec![],
+                exemplars: self.value_map.collect_no_attribute_exemplars(false),
             });
         }
 
@@ -213,13 +276,13 @@ impl<T: Number<T>> Sum<T> {
         // are unbounded number of attribute sets being aggregated. Attribute
         // sets that become "stale" need to be forgotten so this will not
         // overload the system.
-        for (attrs, value) in values.iter() {
+        for (attrs, sum) in values.iter_mut() {
             s_data.data_points.push(DataPoint {
                 attributes: attrs.clone(),
                 start_time: Some(prev_start),
                 time: Some(t),
-                value: *value,
-                exemplars: vec![],
+                value: sum.value,
+                exemplars: exemplar::collect(&mut sum.exemplars),
             });
         }
 
@@ -239,9 +302,9 @@ pub(crate) struct PrecomputedSum<T: Number<T>> {
 }
 
 impl<T: Number<T>> PrecomputedSum<T> {
-    pub(crate) fn new(monotonic: bool) -> Self {
+    pub(crate) fn new(monotonic: bool, cardinality_limit: Arc<CardinalityLimit>) -> Self {
         PrecomputedSum {
-            value_map: ValueMap::new(),
+            value_map: ValueMap::new(cardinality_limit, None),
             monotonic,
             start: Mutex::new(SystemTime::now()),
             reported: Mutex::new(Default::default()),
@@ -249,7 +312,7 @@ impl<T: Number<T>> PrecomputedSum<T> {
     }
 
     pub(crate) fn measure(&self, measurement: T, attrs: AttributeSet) {
-        self.value_map.measure(measurement, attrs)
+        self.value_map.measure(measurement, attrs, None)
     }
 
     pub(crate) fn delta(
@@ -306,7 +369,7 @@ impl<T: Number<T>> PrecomputedSum<T> {
         }
 
         let default = T::default();
-        for (attrs, value) in values.drain() {
+        for (attrs, SumValue { value, .. }) in values.drain() {
             let delta = value - *reported.get(&attrs).unwrap_or(&default);
             if delta != default {
                 new_reported.insert(attrs.clone(), value);
@@ -388,7 +451,7 @@ impl<T: Number<T>> PrecomputedSum<T> {
         }
 
         let default = T::default();
-        for (attrs, value) in values.iter() {
+        for (attrs, SumValue { value, .. }) in values.iter() {
             let delta = *value - *reported.get(attrs).unwrap_or(&default);
             if delta != default {
                 new_reported.insert(attrs.clone(), *value);
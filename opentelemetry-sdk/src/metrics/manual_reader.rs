@@ -35,6 +35,7 @@ pub struct ManualReader {
     inner: Box<Mutex<ManualReaderInner>>,
     temporality_selector: Box<dyn TemporalitySelector>,
     aggregation_selector: Box<dyn AggregationSelector>,
+    cardinality_limit: Option<usize>,
 }
 
 impl Default for ManualReader {
@@ -67,6 +68,7 @@ impl ManualReader {
         temporality_selector: Box<dyn TemporalitySelector>,
         aggregation_selector: Box<dyn AggregationSelector>,
         producers: Vec<Box<dyn MetricProducer>>,
+        cardinality_limit: Option<usize>,
     ) -> Self {
         ManualReader {
             inner: Box::new(Mutex::new(ManualReaderInner {
@@ -76,6 +78,7 @@ impl ManualReader {
             })),
             temporality_selector,
             aggregation_selector,
+            cardinality_limit,
         }
     }
 }
@@ -108,6 +111,10 @@ impl MetricReader for ManualReader {
         });
     }
 
+    fn cardinality_limit(&self, _kind: InstrumentKind) -> Option<usize> {
+        self.cardinality_limit
+    }
+
     /// Gathers all metrics from the SDK and other [MetricProducer]s, calling any
     /// callbacks necessary and returning the results.
     ///
@@ -161,6 +168,7 @@ pub struct ManualReaderBuilder {
     temporality_selector: Box<dyn TemporalitySelector>,
     aggregation_selector: Box<dyn AggregationSelector>,
     producers: Vec<Box<dyn MetricProducer>>,
+    cardinality_limit: Option<usize>,
 }
 
 impl fmt::Debug for ManualReaderBuilder {
@@ -175,6 +183,7 @@ impl Default for ManualReaderBuilder {
             temporality_selector: Box::new(DefaultTemporalitySelector { _private: () }),
             aggregation_selector: Box::new(DefaultAggregationSelector { _private: () }),
             producers: vec![],
+            cardinality_limit: None,
         }
     }
 }
@@ -219,12 +228,30 @@ impl ManualReaderBuilder {
         self
     }
 
+    /// Configures the maximum number of data points of each metric stream read by
+    /// the [ManualReader].
+    ///
+    /// This option overrides the limit set on the meter provider, and is overridden
+    /// by the limit set on a view's [Stream].
+    ///
+    /// If this option is not used or `limit` is equal to zero, the meter provider
+    /// limit is used.
+    ///
+    /// [Stream]: crate::metrics::Stream
+    pub fn with_cardinality_limit(mut self, limit: usize) -> Self {
+        if limit > 0 {
+            self.cardinality_limit = Some(limit);
+        }
+        self
+    }
+
     /// Create a new [ManualReader] from this configuration.
     pub fn build(self) -> ManualReader {
         ManualReader::new(
             self.temporality_selector,
             self.aggregation_selector,
             self.producers,
+            self.cardinality_limit,
         )
     }
 }
@@ -769,14 +769,25 @@ mod tests {
         INSTRUMENT_NAME_INVALID_CHAR, INSTRUMENT_NAME_LENGTH, INSTRUMENT_UNIT_INVALID_CHAR,
         INSTRUMENT_UNIT_LENGTH,
     };
-    use crate::{metrics::pipeline::Pipelines, Resource, Scope};
+    use crate::{
+        metrics::{internal::STREAM_CARDINALITY_LIMIT, pipeline::Pipelines, ExemplarFilter},
+        Resource, Scope,
+    };
 
     #[test]
     fn test_instrument_config_validation() {
         // scope and pipelines are not related to test
         let meter = SdkMeter::new(
             Scope::default(),
-            Arc::new(Pipelines::new(Resource::default(), Vec::new(), Vec::new())),
+            Arc::new(Pipelines::new(
+                Resource::default(),
+                Vec::new(),
+                Vec::new(),
+                STREAM_CARDINALITY_LIMIT,
+                None,
+                ExemplarFilter::default(),
+                Default::default(),
+            )),
         )
         .with_validation_policy(InstrumentValidationPolicy::Strict);
         // (name, expected error)
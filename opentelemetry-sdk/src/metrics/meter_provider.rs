@@ -14,9 +14,12 @@ use opentelemetry::{
     KeyValue,
 };
 
-use crate::{instrumentation::Scope, Resource};
+use crate::{instrumentation::Scope, self_observability::MeterProviderMetrics, Resource};
 
-use super::{meter::SdkMeter, pipeline::Pipelines, reader::MetricReader, view::View};
+use super::{
+    exemplar::ExemplarFilter, internal::STREAM_CARDINALITY_LIMIT, meter::SdkMeter,
+    pipeline::Pipelines, reader::MetricReader, view::View,
+};
 
 /// Handles the creation and coordination of [Meter]s.
 ///
@@ -149,12 +152,39 @@ impl MeterProvider for SdkMeterProvider {
     }
 }
 
+/// Measurements of a metric stream that exceeded its cardinality limit since the
+/// previous collection.
+///
+/// Reported to the handler set with
+/// [MeterProviderBuilder::with_cardinality_overflow_handler] when a reader
+/// collects the stream.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct StreamOverflow<'a> {
+    /// The instrumentation scope of the stream.
+    pub scope: &'a Scope,
+    /// The name of the stream.
+    pub name: &'a str,
+    /// The maximum number of data points of the stream.
+    pub cardinality_limit: usize,
+    /// The number of measurements with a new attribute set that were aggregated
+    /// into the `otel.metric.overflow=true` data point instead of a data point of
+    /// their own.
+    pub dropped: u64,
+}
+
+pub(crate) type OverflowHandler = Arc<dyn Fn(&StreamOverflow<'_>) + Send + Sync>;
+
 /// Configuration options for a [MeterProvider].
 #[derive(Default)]
 pub struct MeterProviderBuilder {
     resource: Option<Resource>,
     readers: Vec<Box<dyn MetricReader>>,
     views: Vec<Arc<dyn View>>,
+    cardinality_limit: Option<usize>,
+    overflow_handler: Option<OverflowHandler>,
+    exemplar_filter: Option<ExemplarFilter>,
+    metrics: MeterProviderMetrics,
 }
 
 impl MeterProviderBuilder {
@@ -192,6 +222,67 @@ impl MeterProviderBuilder {
         self
     }
 
+    /// Sets the maximum number of data points of each metric stream.
+    ///
+    /// Once a stream holds this many data points, measurements with new attribute
+    /// sets are aggregated into a single data point with the
+    /// `otel.metric.overflow=true` attribute. Limits configured on a
+    /// [MetricReader] or on a view's [Stream] take precedence.
+    ///
+    /// By default, if this option is not used or `limit` is equal to zero, a
+    /// limit of 2000 is used.
+    ///
+    /// [Stream]: crate::metrics::Stream
+    pub fn with_cardinality_limit(mut self, limit: usize) -> Self {
+        if limit > 0 {
+            self.cardinality_limit = Some(limit);
+        }
+        self
+    }
+
+    /// Sets a handler notified of the measurements that exceeded the cardinality
+    /// limit of their stream.
+    ///
+    /// The handler is called once per overflowing stream each time a reader
+    /// collects metrics, with the number of measurements dropped into the
+    /// overflow data point since the previous collection.
+    ///
+    /// By default, if this option is not used, the first overflow of each stream
+    /// is reported to the global error handler.
+    pub fn with_cardinality_overflow_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&StreamOverflow<'_>) + Send + Sync + 'static,
+    {
+        self.overflow_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Sets the [ExemplarFilter] selecting the measurements that can be sampled
+    /// as exemplars.
+    ///
+    /// By default, if this option is not used, the filter is read from the
+    /// `OTEL_METRICS_EXEMPLAR_FILTER` environment variable (`always_on`,
+    /// `always_off` or `trace_based`), falling back to
+    /// [ExemplarFilter::TraceBased].
+    pub fn with_exemplar_filter(mut self, filter: ExemplarFilter) -> Self {
+        self.exemplar_filter = Some(filter);
+        self
+    }
+
+    /// Record metrics about the [MeterProvider] with `meter_provider`.
+    ///
+    /// The provider reports the number of measurements that exceeded the
+    /// cardinality limit of their stream (`otel.sdk.metric.overflow.count`), by
+    /// scope and stream name, when its readers collect metrics. The overflow
+    /// handler is still called.
+    ///
+    /// The SDK metrics are recorded by another meter provider, which is usually
+    /// exported independently of the metrics it describes.
+    pub fn with_self_observability<P: MeterProvider>(mut self, meter_provider: &P) -> Self {
+        self.metrics = MeterProviderMetrics::new(meter_provider);
+        self
+    }
+
     /// Construct a new [MeterProvider] with this configuration.
     pub fn build(self) -> SdkMeterProvider {
         SdkMeterProvider {
@@ -199,6 +290,12 @@ impl MeterProviderBuilder {
                 self.resource.unwrap_or_default(),
                 self.readers,
                 self.views,
+                self.cardinality_limit.unwrap_or(STREAM_CARDINALITY_LIMIT),
+                self.overflow_handler,
+                self.exemplar_filter
+                    .or_else(ExemplarFilter::from_env)
+                    .unwrap_or_default(),
+                self.metrics,
             )),
             meters: Default::default(),
             is_shutdown: Arc::new(AtomicBool::new(false)),
@@ -212,6 +309,8 @@ impl fmt::Debug for MeterProviderBuilder {
             .field("resource", &self.resource)
             .field("readers", &self.readers)
             .field("views", &self.views.len())
+            .field("cardinality_limit", &self.cardinality_limit)
+            .field("exemplar_filter", &self.exemplar_filter)
             .finish()
     }
 }
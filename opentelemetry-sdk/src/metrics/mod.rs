@@ -38,6 +38,7 @@
 
 pub(crate) mod aggregation;
 pub mod data;
+pub(crate) mod exemplar;
 pub mod exporter;
 pub(crate) mod instrument;
 pub(crate) mod internal;
@@ -50,6 +51,10 @@ pub mod reader;
 pub(crate) mod view;
 
 pub use aggregation::*;
+pub use exemplar::{
+    AlignedHistogramBucketExemplarReservoir, ExemplarFilter, ExemplarReservoir,
+    SimpleFixedSizeExemplarReservoir,
+};
 pub use instrument::*;
 pub use manual_reader::*;
 pub use meter::*;
@@ -67,11 +72,15 @@ mod tests {
     use crate::testing::metrics::InMemoryMetricsExporterBuilder;
     use crate::{runtime, testing::metrics::InMemoryMetricsExporter};
     use opentelemetry::metrics::{Counter, UpDownCounter};
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
     use opentelemetry::{
         metrics::{MeterProvider as _, Unit},
-        KeyValue,
+        Context, Key, KeyValue,
     };
     use std::borrow::Cow;
+    use std::sync::{Arc, Mutex};
 
     // "multi_thread" tokio flavor must be used else flush won't
     // be able to make progress!
@@ -866,6 +875,376 @@ mod tests {
         assert!(resource_metrics.is_empty(), "No metrics should be exported as no new measurements were recorded since last collect.");
     }
 
+    // "multi_thread" tokio flavor must be used else flush won't
+    // be able to make progress!
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn cardinality_limit_from_meter_provider() {
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_cardinality_limit(3)
+            .build();
+
+        // Act
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("my_counter")
+            .init();
+        for i in 0..5 {
+            counter.add(1, &[KeyValue::new("key1", i)]);
+        }
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let data_points = exported_data_points(&exporter);
+        assert_eq!(data_points.len(), 3);
+        let overflow = data_points
+            .iter()
+            .find(|dp| {
+                dp.attributes
+                    .iter()
+                    .any(|(k, _)| k.as_str() == "otel.metric.overflow")
+            })
+            .expect("overflow data point expected");
+        assert_eq!(overflow.value, 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn cardinality_limit_reader_overrides_meter_provider() {
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio)
+            .with_cardinality_limit(5)
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_cardinality_limit(3)
+            .build();
+
+        // Act
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("my_counter")
+            .init();
+        for i in 0..10 {
+            counter.add(1, &[KeyValue::new("key1", i)]);
+        }
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        assert_eq!(exported_data_points(&exporter).len(), 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn cardinality_limit_view_overrides_reader() {
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio)
+            .with_cardinality_limit(5)
+            .build();
+        let view = new_view(
+            Instrument::new().name("my_counter"),
+            Stream::new().cardinality_limit(2),
+        )
+        .expect("Expected to create a new view");
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_view(view)
+            .build();
+
+        // Act
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("my_counter")
+            .init();
+        for i in 0..10 {
+            counter.add(1, &[KeyValue::new("key1", i)]);
+        }
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let data_points = exported_data_points(&exporter);
+        assert_eq!(data_points.len(), 2);
+        assert_eq!(data_points.iter().map(|dp| dp.value).sum::<u64>(), 10);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn cardinality_overflow_handler_reports_dropped_measurements() {
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let handler_reports = Arc::clone(&reports);
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_cardinality_limit(3)
+            .with_cardinality_overflow_handler(move |overflow: &StreamOverflow<'_>| {
+                handler_reports.lock().unwrap().push((
+                    overflow.scope.name.to_string(),
+                    overflow.name.to_string(),
+                    overflow.cardinality_limit,
+                    overflow.dropped,
+                ))
+            })
+            .build();
+
+        // Act
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("my_counter")
+            .init();
+        for i in 0..5 {
+            counter.add(1, &[KeyValue::new("key1", i)]);
+        }
+        meter_provider.force_flush().unwrap();
+        // no new measurement overflowed, nothing to report
+        counter.add(1, &[KeyValue::new("key1", 0)]);
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        assert_eq!(
+            *reports.lock().unwrap(),
+            vec![("test".to_string(), "my_counter".to_string(), 3, 3)]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn self_observability_reports_overflows_and_exports() {
+        // Arrange
+        let sdk_exporter = InMemoryMetricsExporter::default();
+        let sdk_meter_provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(sdk_exporter.clone(), runtime::Tokio).build())
+            .build();
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio)
+            .with_self_observability(&sdk_meter_provider)
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_cardinality_limit(3)
+            .with_self_observability(&sdk_meter_provider)
+            .build();
+
+        // Act
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("my_counter")
+            .init();
+        for i in 0..5 {
+            counter.add(1, &[KeyValue::new("key1", i)]);
+        }
+        meter_provider.force_flush().unwrap();
+        sdk_meter_provider.force_flush().unwrap();
+
+        // Assert
+        let resource_metrics = sdk_exporter
+            .get_finished_metrics()
+            .expect("metrics are expected to be exported.");
+        let scope_metrics = find_scope_metric(
+            &resource_metrics.last().unwrap().scope_metrics,
+            "opentelemetry_sdk",
+        )
+        .expect("SDK metrics are expected to be exported.");
+        let sum = |name: &str| {
+            let metric = scope_metrics
+                .metrics
+                .iter()
+                .find(|metric| metric.name == name)
+                .unwrap_or_else(|| panic!("{name} is expected to be exported"));
+            let sum = metric
+                .data
+                .as_any()
+                .downcast_ref::<data::Sum<u64>>()
+                .expect("Sum aggregation expected for Counter instruments by default");
+            assert_eq!(sum.data_points.len(), 1);
+            sum.data_points[0].clone()
+        };
+
+        let overflow = sum("otel.sdk.metric.overflow.count");
+        assert_eq!(overflow.value, 3);
+        assert!(overflow.attributes.iter().any(
+            |(key, value)| key.as_str() == "otel.metric.name" && value.as_str() == "my_counter"
+        ));
+        // at least the 2 data points and the overflow data point, the periodic
+        // reader may also have exported before the flush
+        let exported = sum("otel.sdk.exporter.metric_points.exported");
+        assert!(exported.value >= 3);
+        assert!(exported
+            .attributes
+            .iter()
+            .any(|(key, value)| key.as_str() == "outcome" && value.as_str() == "success"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn counter_exemplars_record_filtered_attributes() {
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
+        let view = new_view(
+            Instrument::new().name("my_counter"),
+            Stream::new().allowed_attribute_keys(vec![Key::new("key1")]),
+        )
+        .expect("Expected to create a new view");
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_view(view)
+            .with_exemplar_filter(ExemplarFilter::AlwaysOn)
+            .build();
+
+        // Act
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("my_counter")
+            .init();
+        counter.add(
+            5,
+            &[
+                KeyValue::new("key1", "value1"),
+                KeyValue::new("key2", "value2"),
+            ],
+        );
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let data_points = exported_data_points(&exporter);
+        assert_eq!(data_points.len(), 1);
+        let exemplars = &data_points[0].exemplars;
+        assert_eq!(exemplars.len(), 1);
+        assert_eq!(exemplars[0].value, 5);
+        assert_eq!(
+            exemplars[0].filtered_attributes,
+            vec![KeyValue::new("key2", "value2")]
+        );
+        assert_eq!(exemplars[0].trace_id, [0; 16]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn histogram_exemplars_trace_based() {
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_exemplar_filter(ExemplarFilter::TraceBased)
+            .build();
+        let span_context = |flags| {
+            Context::new().with_remote_span_context(SpanContext::new(
+                TraceId::from(7),
+                SpanId::from(3),
+                flags,
+                true,
+                TraceState::default(),
+            ))
+        };
+
+        // Act
+        let histogram = meter_provider
+            .meter("test")
+            .u64_histogram("my_histogram")
+            .init();
+        histogram.record(1, &[]);
+        {
+            let _guard = span_context(TraceFlags::default()).attach();
+            histogram.record(2, &[]);
+        }
+        {
+            let _guard = span_context(TraceFlags::SAMPLED).attach();
+            histogram.record(30, &[]);
+        }
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let resource_metrics = exporter
+            .get_finished_metrics()
+            .expect("metrics are expected to be exported.");
+        let histogram = resource_metrics[0].scope_metrics[0].metrics[0]
+            .data
+            .as_any()
+            .downcast_ref::<data::Histogram<u64>>()
+            .expect("Histogram aggregation expected for Histogram instruments by default");
+        let exemplars = &histogram.data_points[0].exemplars;
+        assert_eq!(exemplars.len(), 1);
+        assert_eq!(exemplars[0].value, 30);
+        assert_eq!(exemplars[0].trace_id, TraceId::from(7).to_bytes());
+        assert_eq!(exemplars[0].span_id, SpanId::from(3).to_bytes());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn no_exemplars_when_always_off() {
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_exemplar_filter(ExemplarFilter::AlwaysOff)
+            .build();
+
+        // Act
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("my_counter")
+            .init();
+        counter.add(5, &[KeyValue::new("key1", "value1")]);
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let data_points = exported_data_points(&exporter);
+        assert!(data_points[0].exemplars.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn counter_exemplars_custom_reservoir() {
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::builder(exporter.clone(), runtime::Tokio).build();
+        let view = new_view(
+            Instrument::new().name("my_counter"),
+            Stream::new().exemplar_reservoir(|| SimpleFixedSizeExemplarReservoir::new(3)),
+        )
+        .expect("Expected to create a new view");
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_view(view)
+            .with_exemplar_filter(ExemplarFilter::AlwaysOn)
+            .build();
+
+        // Act
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("my_counter")
+            .init();
+        for value in 1..=3 {
+            counter.add(value, &[KeyValue::new("key1", "value1")]);
+        }
+        meter_provider.force_flush().unwrap();
+
+        // Assert
+        let data_points = exported_data_points(&exporter);
+        let values: Vec<u64> = data_points[0].exemplars.iter().map(|e| e.value).collect();
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    fn exported_data_points(exporter: &InMemoryMetricsExporter) -> Vec<data::DataPoint<u64>> {
+        let resource_metrics = exporter
+            .get_finished_metrics()
+            .expect("metrics are expected to be exported.");
+        let metric = &resource_metrics
+            .last()
+            .expect("metrics are expected to be exported.")
+            .scope_metrics[0]
+            .metrics[0];
+        assert_eq!(metric.name, "my_counter");
+        metric
+            .data
+            .as_any()
+            .downcast_ref::<data::Sum<u64>>()
+            .expect("Sum aggregation expected for Counter instruments by default")
+            .data_points
+            .clone()
+    }
+
     fn find_scope_metric<'a>(
         metrics: &'a [ScopeMetrics],
         name: &'a str,
@@ -1,7 +1,8 @@
 use std::{
     env, fmt, mem,
     sync::{Arc, Mutex, Weak},
-    time::Duration,
+    thread,
+    time::{Duration, Instant},
 };
 
 use futures_channel::{mpsc, oneshot};
@@ -17,6 +18,8 @@ use opentelemetry::{
 };
 
 use crate::runtime::Runtime;
+use crate::self_observability::{self, ExporterMetrics};
+use crate::util::block_on_with_timeout;
 use crate::{
     metrics::{
         exporter::PushMetricsExporter,
@@ -39,6 +42,20 @@ const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
 const METRIC_EXPORT_INTERVAL_NAME: &str = "OTEL_METRIC_EXPORT_INTERVAL";
 const METRIC_EXPORT_TIMEOUT_NAME: &str = "OTEL_METRIC_EXPORT_TIMEOUT";
 
+fn interval_from_env() -> Duration {
+    env::var(METRIC_EXPORT_INTERVAL_NAME)
+        .ok()
+        .and_then(|v| v.parse().map(Duration::from_millis).ok())
+        .unwrap_or(DEFAULT_INTERVAL)
+}
+
+fn timeout_from_env() -> Duration {
+    env::var(METRIC_EXPORT_TIMEOUT_NAME)
+        .ok()
+        .and_then(|v| v.parse().map(Duration::from_millis).ok())
+        .unwrap_or(DEFAULT_TIMEOUT)
+}
+
 /// Configuration options for [PeriodicReader].
 ///
 /// A periodic reader is a [MetricReader] that collects and exports metric data
@@ -59,6 +76,8 @@ pub struct PeriodicReaderBuilder<E, RT> {
     timeout: Duration,
     exporter: E,
     producers: Vec<Box<dyn MetricProducer>>,
+    cardinality_limit: Option<usize>,
+    metrics: ExporterMetrics,
     runtime: RT,
 }
 
@@ -68,19 +87,12 @@ where
     RT: Runtime,
 {
     fn new(exporter: E, runtime: RT) -> Self {
-        let interval = env::var(METRIC_EXPORT_INTERVAL_NAME)
-            .ok()
-            .and_then(|v| v.parse().map(Duration::from_millis).ok())
-            .unwrap_or(DEFAULT_INTERVAL);
-        let timeout = env::var(METRIC_EXPORT_TIMEOUT_NAME)
-            .ok()
-            .and_then(|v| v.parse().map(Duration::from_millis).ok())
-            .unwrap_or(DEFAULT_TIMEOUT);
-
         PeriodicReaderBuilder {
-            interval,
-            timeout,
+            interval: interval_from_env(),
+            timeout: timeout_from_env(),
             producers: vec![],
+            cardinality_limit: None,
+            metrics: ExporterMetrics::default(),
             exporter,
             runtime,
         }
@@ -124,6 +136,40 @@ where
         self
     }
 
+    /// Configures the maximum number of data points of each metric stream read by
+    /// the [PeriodicReader].
+    ///
+    /// This option overrides the limit set on the meter provider, and is overridden
+    /// by the limit set on a view's [Stream].
+    ///
+    /// If this option is not used or `limit` is equal to zero, the meter provider
+    /// limit is used.
+    ///
+    /// [Stream]: crate::metrics::Stream
+    pub fn with_cardinality_limit(mut self, limit: usize) -> Self {
+        if limit > 0 {
+            self.cardinality_limit = Some(limit);
+        }
+        self
+    }
+
+    /// Record metrics about the exports of the [PeriodicReader] with
+    /// `meter_provider`.
+    ///
+    /// The reader reports the number of exported data points by outcome
+    /// (`otel.sdk.exporter.metric_points.exported`) and the duration of the
+    /// exports (`otel.sdk.exporter.duration`).
+    pub fn with_self_observability<P>(mut self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        self.metrics = ExporterMetrics::new(
+            &self_observability::meter(meter_provider),
+            self_observability::Signal::Metrics,
+        );
+        self
+    }
+
     /// Create a [PeriodicReader] with the given config.
     pub fn build(self) -> PeriodicReader {
         let (message_sender, message_receiver) = mpsc::channel(256);
@@ -141,6 +187,7 @@ where
                 PeriodicReaderWorker {
                     reader: reader.clone(),
                     timeout: self.timeout,
+                    metrics: self.metrics,
                     runtime,
                     rm: ResourceMetrics {
                         resource: Resource::empty(),
@@ -153,6 +200,129 @@ where
 
         PeriodicReader {
             exporter: Arc::new(self.exporter),
+            cardinality_limit: self.cardinality_limit,
+            inner: Arc::new(Mutex::new(PeriodicReaderInner {
+                message_sender,
+                is_shutdown: false,
+                external_producers: self.producers,
+                sdk_producer_or_worker: ProducerOrWorker::Worker(Box::new(worker)),
+            })),
+        }
+    }
+}
+
+/// Configuration options for a [PeriodicReader] exporting from a dedicated
+/// thread.
+///
+/// The reader behaves as one built by [PeriodicReaderBuilder], except that its
+/// collections and exports run on a background OS thread instead of a task of
+/// an async runtime. The exports are blocking calls of the exporter, so it must
+/// not depend on an async runtime, like an exporter using a blocking HTTP
+/// client.
+///
+/// The thread is spawned when the reader is registered with a meter provider,
+/// and stops when the reader is shut down.
+#[derive(Debug)]
+pub struct ThreadedPeriodicReaderBuilder<E> {
+    interval: Duration,
+    timeout: Duration,
+    exporter: E,
+    producers: Vec<Box<dyn MetricProducer>>,
+    cardinality_limit: Option<usize>,
+    metrics: ExporterMetrics,
+}
+
+impl<E> ThreadedPeriodicReaderBuilder<E>
+where
+    E: PushMetricsExporter,
+{
+    fn new(exporter: E) -> Self {
+        ThreadedPeriodicReaderBuilder {
+            interval: interval_from_env(),
+            timeout: timeout_from_env(),
+            producers: vec![],
+            cardinality_limit: None,
+            metrics: ExporterMetrics::default(),
+            exporter,
+        }
+    }
+
+    /// Configures the intervening time between exports, see
+    /// [PeriodicReaderBuilder::with_interval].
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        if !interval.is_zero() {
+            self.interval = interval;
+        }
+        self
+    }
+
+    /// Configures the time to wait for an export to complete, see
+    /// [PeriodicReaderBuilder::with_timeout].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        if !timeout.is_zero() {
+            self.timeout = timeout;
+        }
+        self
+    }
+
+    /// Registers a an external [MetricProducer] with this reader.
+    pub fn with_producer(mut self, producer: impl MetricProducer + 'static) -> Self {
+        self.producers.push(Box::new(producer));
+        self
+    }
+
+    /// Configures the maximum number of data points of each metric stream, see
+    /// [PeriodicReaderBuilder::with_cardinality_limit].
+    pub fn with_cardinality_limit(mut self, limit: usize) -> Self {
+        if limit > 0 {
+            self.cardinality_limit = Some(limit);
+        }
+        self
+    }
+
+    /// Record metrics about the exports of the reader with `meter_provider`, see
+    /// [PeriodicReaderBuilder::with_self_observability].
+    pub fn with_self_observability<P>(mut self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        self.metrics = ExporterMetrics::new(
+            &self_observability::meter(meter_provider),
+            self_observability::Signal::Metrics,
+        );
+        self
+    }
+
+    /// Create a [PeriodicReader] with the given config.
+    pub fn build(self) -> PeriodicReader {
+        let (message_sender, message_receiver) = mpsc::channel(256);
+
+        let worker = move |reader: &PeriodicReader| {
+            let worker = ThreadedPeriodicReaderWorker {
+                reader: reader.clone(),
+                interval: self.interval,
+                timeout: self.timeout,
+                metrics: self.metrics,
+                rm: ResourceMetrics {
+                    resource: Resource::empty(),
+                    scope_metrics: Vec::new(),
+                },
+            };
+
+            let spawned = thread::Builder::new()
+                .name("opentelemetry-periodic-reader".into())
+                .spawn(move || worker.run(message_receiver));
+            if let Err(err) = spawned {
+                global::handle_error(MetricsError::Other(format!(
+                    "failed to spawn the periodic reader thread: {}",
+                    err
+                )));
+            }
+        };
+
+        PeriodicReader {
+            exporter: Arc::new(self.exporter),
+            cardinality_limit: self.cardinality_limit,
             inner: Arc::new(Mutex::new(PeriodicReaderInner {
                 message_sender,
                 is_shutdown: false,
@@ -203,6 +373,7 @@ where
 #[derive(Clone)]
 pub struct PeriodicReader {
     exporter: Arc<dyn PushMetricsExporter>,
+    cardinality_limit: Option<usize>,
     inner: Arc<Mutex<PeriodicReaderInner>>,
 }
 
@@ -215,6 +386,29 @@ impl PeriodicReader {
     {
         PeriodicReaderBuilder::new(exporter, runtime)
     }
+
+    /// Configuration options for a periodic reader collecting and exporting on
+    /// a dedicated thread, without an async runtime.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+    /// use opentelemetry_sdk::testing::metrics::InMemoryMetricsExporter;
+    /// use std::time::Duration;
+    ///
+    /// let reader = PeriodicReader::threaded_builder(InMemoryMetricsExporter::default())
+    ///     .with_interval(Duration::from_secs(10))
+    ///     .build();
+    /// let provider = SdkMeterProvider::builder().with_reader(reader).build();
+    /// # drop(provider);
+    /// ```
+    pub fn threaded_builder<E>(exporter: E) -> ThreadedPeriodicReaderBuilder<E>
+    where
+        E: PushMetricsExporter,
+    {
+        ThreadedPeriodicReaderBuilder::new(exporter)
+    }
 }
 
 impl fmt::Debug for PeriodicReader {
@@ -245,6 +439,7 @@ enum ProducerOrWorker {
 struct PeriodicReaderWorker<RT: Runtime> {
     reader: PeriodicReader,
     timeout: Duration,
+    metrics: ExporterMetrics,
     runtime: RT,
     rm: ResourceMetrics,
 }
@@ -253,15 +448,21 @@ impl<RT: Runtime> PeriodicReaderWorker<RT> {
     async fn collect_and_export(&mut self) -> Result<()> {
         self.reader.collect(&mut self.rm)?;
 
+        let data_points = self_observability::data_points(&self.rm);
+        let start = Instant::now();
+
         let export = self.reader.exporter.export(&mut self.rm);
         let timeout = self.runtime.delay(self.timeout);
         pin_mut!(export);
         pin_mut!(timeout);
 
-        match future::select(export, timeout).await {
-            Either::Left(_) => Ok(()),
+        let result = match future::select(export, timeout).await {
+            Either::Left((result, _)) => result,
             Either::Right(_) => Err(MetricsError::Other("export timed out".into())),
-        }
+        };
+        self.metrics
+            .exported(data_points, result.is_ok(), start.elapsed());
+        result
     }
 
     async fn process_message(&mut self, message: Message) -> bool {
@@ -299,6 +500,66 @@ impl<RT: Runtime> PeriodicReaderWorker<RT> {
     }
 }
 
+struct ThreadedPeriodicReaderWorker {
+    reader: PeriodicReader,
+    interval: Duration,
+    timeout: Duration,
+    metrics: ExporterMetrics,
+    rm: ResourceMetrics,
+}
+
+impl ThreadedPeriodicReaderWorker {
+    fn collect_and_export(&mut self) -> Result<()> {
+        self.reader.collect(&mut self.rm)?;
+
+        let data_points = self_observability::data_points(&self.rm);
+        let start = Instant::now();
+
+        let export = self.reader.exporter.export(&mut self.rm);
+        let result = block_on_with_timeout(export, self.timeout)
+            .unwrap_or_else(|| Err(MetricsError::Other("export timed out".into())));
+        self.metrics
+            .exported(data_points, result.is_ok(), start.elapsed());
+        result
+    }
+
+    fn run(mut self, mut messages: mpsc::Receiver<Message>) {
+        let mut next_export = Instant::now() + self.interval;
+
+        loop {
+            let timeout = next_export.saturating_duration_since(Instant::now());
+            let message = match block_on_with_timeout(messages.next(), timeout) {
+                Some(Some(message)) => message,
+                Some(None) => break,
+                None => Message::Export,
+            };
+
+            match message {
+                Message::Export => {
+                    next_export = Instant::now() + self.interval;
+                    if let Err(err) = self.collect_and_export() {
+                        global::handle_error(err)
+                    }
+                }
+                Message::Flush(ch) => {
+                    let res = self.collect_and_export();
+                    if ch.send(res).is_err() {
+                        global::handle_error(MetricsError::Other("flush channel closed".into()))
+                    }
+                }
+                Message::Shutdown(ch) => {
+                    let res = self.collect_and_export();
+                    let _ = self.reader.exporter.shutdown();
+                    if ch.send(res).is_err() {
+                        global::handle_error(MetricsError::Other("shutdown channel closed".into()))
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
 impl AggregationSelector for PeriodicReader {
     fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
         self.exporter.aggregation(kind)
@@ -333,6 +594,10 @@ impl MetricReader for PeriodicReader {
         worker(self);
     }
 
+    fn cardinality_limit(&self, _kind: InstrumentKind) -> Option<usize> {
+        self.cardinality_limit
+    }
+
     fn collect(&self, rm: &mut ResourceMetrics) -> Result<()> {
         let inner = self.inner.lock()?;
         if inner.is_shutdown {
@@ -457,4 +722,54 @@ mod tests {
         // Assert
         result.expect_err("error expected when reader is not registered");
     }
+
+    #[test]
+    fn threaded_registration_triggers_collection() {
+        // Arrange
+        let interval = std::time::Duration::from_millis(1);
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::threaded_builder(exporter.clone())
+            .with_interval(interval)
+            .build();
+        let (sender, receiver) = mpsc::channel();
+
+        // Act
+        let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+        let meter = meter_provider.meter("test");
+        let counter = meter.u64_observable_counter("testcounter").init();
+        meter
+            .register_callback(&[counter.as_any()], move |_| {
+                let _ = sender.send(());
+            })
+            .expect("callback registration should succeed");
+
+        // Assert
+        receiver
+            .recv_timeout(std::time::Duration::from_secs(1))
+            .expect("message should be available in channel, indicating a collection occurred");
+        meter_provider.shutdown().expect("shutdown should succeed");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn threaded_flush_and_shutdown_in_current_thread_runtime() {
+        // Arrange
+        let exporter = InMemoryMetricsExporter::default();
+        let reader = PeriodicReader::threaded_builder(exporter.clone()).build();
+        let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+        let counter = meter_provider
+            .meter("test")
+            .u64_counter("testcounter")
+            .init();
+        counter.add(1, &[]);
+
+        // Act
+        meter_provider.force_flush().expect("flush should succeed");
+
+        // Assert
+        let exported = exporter.get_finished_metrics().unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].scope_metrics[0].metrics[0].name, "testcounter");
+        meter_provider.shutdown().expect("shutdown should succeed");
+        assert!(meter_provider.force_flush().is_err());
+    }
 }
@@ -2,7 +2,10 @@ use core::fmt;
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use opentelemetry::{
@@ -16,13 +19,17 @@ use crate::{
     metrics::{
         aggregation,
         data::{Metric, ResourceMetrics, ScopeMetrics},
+        exemplar::ExemplarFilter,
         instrument::{Instrument, InstrumentId, InstrumentKind, Stream},
         internal,
         internal::AggregateBuilder,
+        internal::CardinalityLimit,
         internal::Number,
+        meter_provider::{OverflowHandler, StreamOverflow},
         reader::{AggregationSelector, DefaultAggregationSelector, MetricReader, SdkProducer},
         view::View,
     },
+    self_observability::MeterProviderMetrics,
     Resource,
 };
 
@@ -39,6 +46,10 @@ pub struct Pipeline {
     pub(crate) resource: Resource,
     reader: Box<dyn MetricReader>,
     views: Vec<Arc<dyn View>>,
+    cardinality_limit: usize,
+    overflow_handler: Option<OverflowHandler>,
+    exemplar_filter: ExemplarFilter,
+    metrics: MeterProviderMetrics,
     inner: Box<Mutex<PipelineInner>>,
 }
 
@@ -104,6 +115,33 @@ impl Pipeline {
         })
     }
 
+    /// Reports the measurements of `inst` that overflowed its cardinality limit
+    /// since the previous collection.
+    fn report_overflow(&self, scope: &Scope, inst: &InstrumentSync) {
+        let dropped = inst.cardinality_limit.take_overflowed();
+        if dropped == 0 {
+            return;
+        }
+        self.metrics.overflowed(scope, &inst.name, dropped);
+
+        match &self.overflow_handler {
+            Some(handler) => handler(&StreamOverflow {
+                scope,
+                name: &inst.name,
+                cardinality_limit: inst.cardinality_limit.limit(),
+                dropped,
+            }),
+            None => {
+                if !inst.overflow_reported.swap(true, Ordering::Relaxed) {
+                    global::handle_error(MetricsError::Other(format!(
+                        "Warning: Maximum data points for metric stream {} exceeded. Entries added to overflow.",
+                        inst.name
+                    )));
+                }
+            }
+        }
+    }
+
     /// Send accumulated telemetry
     fn force_flush(&self) -> Result<()> {
         self.reader.force_flush()
@@ -150,6 +188,8 @@ impl SdkProducer for Pipeline {
 
             let mut j = 0;
             for inst in instruments {
+                self.report_overflow(scope, inst);
+
                 let mut m = sm.metrics.get_mut(j);
                 match (inst.comp_agg.call(m.as_mut().map(|m| m.data.as_mut())), m) {
                     // No metric to re-use, expect agg to create new metric data
@@ -194,6 +234,9 @@ struct InstrumentSync {
     description: Cow<'static, str>,
     unit: Unit,
     comp_agg: Box<dyn internal::ComputeAggregation>,
+    cardinality_limit: Arc<CardinalityLimit>,
+    /// Whether an overflow was reported to the global error handler.
+    overflow_reported: AtomicBool,
 }
 
 impl fmt::Debug for InstrumentSync {
@@ -202,6 +245,7 @@ impl fmt::Debug for InstrumentSync {
             .field("name", &self.name)
             .field("description", &self.description)
             .field("unit", &self.unit)
+            .field("cardinality_limit", &self.cardinality_limit.limit())
             .finish()
     }
 }
@@ -315,6 +359,8 @@ where
             unit: inst.unit,
             aggregation: None,
             allowed_attribute_keys: None,
+            cardinality_limit: None,
+            exemplar_reservoir: None,
         };
 
         match self.cached_aggregator(&inst.scope, kind, stream) {
@@ -389,7 +435,21 @@ where
                 .clone()
                 .map(|allowed| Arc::new(move |kv: &KeyValue| allowed.contains(&kv.key)) as Arc<_>);
 
-            let b = AggregateBuilder::new(Some(self.pipeline.reader.temporality(kind)), filter);
+            // The stream limit takes precedence over the reader and provider ones.
+            let cardinality_limit = Arc::new(CardinalityLimit::new(
+                stream
+                    .cardinality_limit
+                    .or_else(|| self.pipeline.reader.cardinality_limit(kind))
+                    .unwrap_or(self.pipeline.cardinality_limit),
+            ));
+
+            let b = AggregateBuilder::new(
+                Some(self.pipeline.reader.temporality(kind)),
+                filter,
+                Arc::clone(&cardinality_limit),
+                self.pipeline.exemplar_filter,
+            )
+            .with_exemplar_reservoir(stream.exemplar_reservoir.clone());
             let (m, ca) = match aggregate_fn(b, &agg, kind) {
                 Ok(Some((m, ca))) => (m, ca),
                 other => return other.map(|fs| fs.map(|(m, _)| m)), // Drop aggregator or error
@@ -402,6 +462,8 @@ where
                     description: stream.description,
                     unit: stream.unit,
                     comp_agg: ca,
+                    cardinality_limit,
+                    overflow_reported: AtomicBool::new(false),
                 },
             );
 
@@ -594,6 +656,10 @@ impl Pipelines {
         res: Resource,
         readers: Vec<Box<dyn MetricReader>>,
         views: Vec<Arc<dyn View>>,
+        cardinality_limit: usize,
+        overflow_handler: Option<OverflowHandler>,
+        exemplar_filter: ExemplarFilter,
+        metrics: MeterProviderMetrics,
     ) -> Self {
         let mut pipes = Vec::with_capacity(readers.len());
         for r in readers {
@@ -601,6 +667,10 @@ impl Pipelines {
                 resource: res.clone(),
                 reader: r,
                 views: views.clone(),
+                cardinality_limit,
+                overflow_handler: overflow_handler.clone(),
+                exemplar_filter,
+                metrics: metrics.clone(),
                 inner: Default::default(),
             });
             p.reader.register_pipeline(Arc::downgrade(&p));
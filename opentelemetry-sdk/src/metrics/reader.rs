@@ -33,6 +33,18 @@ pub trait MetricReader:
     /// and send aggregated metric measurements.
     fn register_pipeline(&self, pipeline: Weak<Pipeline>);
 
+    /// The maximum number of data points a metric stream of an instrument of the
+    /// given [InstrumentKind] can hold before new attribute sets are aggregated
+    /// into the `otel.metric.overflow` data point.
+    ///
+    /// A limit set on a view's [Stream] takes precedence. If `None` is returned,
+    /// the default, the limit configured on the meter provider is used.
+    ///
+    /// [Stream]: crate::metrics::Stream
+    fn cardinality_limit(&self, _kind: InstrumentKind) -> Option<usize> {
+        None
+    }
+
     /// Gathers and returns all metric data related to the [MetricReader] from the
     /// SDK and stores it in the provided [ResourceMetrics] reference.
     ///
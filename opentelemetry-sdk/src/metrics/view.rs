@@ -84,8 +84,8 @@ impl View for Box<dyn View> {
 ///
 /// The [Stream] mask only applies updates for non-empty fields. By default, the
 /// [Instrument] the [View] matches against will be use for the name,
-/// description, and unit of the returned [Stream] and no `aggregation` or
-/// `allowed_attribute_keys` are set. All non-empty fields of mask are used
+/// description, and unit of the returned [Stream] and no `aggregation`,
+/// `allowed_attribute_keys`, `cardinality_limit` or `exemplar_reservoir` are set. All non-empty fields of mask are used
 /// instead of the default. If you need to set a an empty value in the returned
 /// stream, create a custom [View] directly.
 ///
@@ -167,6 +167,8 @@ pub fn new_view(criteria: Instrument, mask: Stream) -> Result<Box<dyn View>> {
                 },
                 aggregation: agg.clone(),
                 allowed_attribute_keys: mask.allowed_attribute_keys.clone(),
+                cardinality_limit: mask.cardinality_limit,
+                exemplar_reservoir: mask.exemplar_reservoir.clone(),
             })
         } else {
             None
@@ -30,3 +30,137 @@ pub(crate) const TELEMETRY_SDK_NAME: &str = "telemetry.sdk.name";
 ///
 /// - `1.2.3`
 pub(crate) const TELEMETRY_SDK_VERSION: &str = "telemetry.sdk.version";
+
+/// The CPU architecture the host system is running on.
+pub(crate) const HOST_ARCH: &str = "host.arch";
+
+/// Unique host ID. For Cloud, this must be the instance_id assigned by the cloud provider. For non-containerized systems, this should be the `machine-id`.
+///
+/// # Examples
+///
+/// - `fdbf79e8af94cb7f9e8df36789187052`
+pub(crate) const HOST_ID: &str = "host.id";
+
+/// Name of the host. On Unix systems, it may contain what the hostname command returns, or the fully qualified hostname, or another name specified by the user.
+///
+/// # Examples
+///
+/// - `opentelemetry-test`
+pub(crate) const HOST_NAME: &str = "host.name";
+
+/// Human readable (not intended to be parsed) OS version information, like e.g. reported by `ver` or `lsb_release -a` commands.
+///
+/// # Examples
+///
+/// - `Microsoft Windows [Version 10.0.18363.778]`
+/// - `Ubuntu 18.04.1 LTS`
+pub(crate) const OS_DESCRIPTION: &str = "os.description";
+
+/// The operating system type.
+pub(crate) const OS_TYPE: &str = "os.type";
+
+/// The command used to launch the process (i.e. the command name).
+///
+/// # Examples
+///
+/// - `cmd/otelcol`
+pub(crate) const PROCESS_COMMAND: &str = "process.command";
+
+/// All the command arguments (including the command/executable itself) as received by the process.
+///
+/// # Examples
+///
+/// - `cmd/otecol`
+/// - `--config=config.yaml`
+pub(crate) const PROCESS_COMMAND_ARGS: &str = "process.command_args";
+
+/// The name of the process executable.
+///
+/// # Examples
+///
+/// - `otelcol`
+pub(crate) const PROCESS_EXECUTABLE_NAME: &str = "process.executable.name";
+
+/// The full path to the process executable.
+///
+/// # Examples
+///
+/// - `/usr/bin/cmd/otelcol`
+pub(crate) const PROCESS_EXECUTABLE_PATH: &str = "process.executable.path";
+
+/// The username of the user that owns the process.
+///
+/// # Examples
+///
+/// - `root`
+pub(crate) const PROCESS_OWNER: &str = "process.owner";
+
+/// Parent Process identifier (PPID).
+///
+/// # Examples
+///
+/// - `111`
+pub(crate) const PROCESS_PARENT_PID: &str = "process.parent_pid";
+
+/// Process identifier (PID).
+///
+/// # Examples
+///
+/// - `1234`
+pub(crate) const PROCESS_PID: &str = "process.pid";
+
+/// An additional description about the runtime of the process, for example a specific vendor customization of the runtime environment.
+///
+/// # Examples
+///
+/// - `rustc 1.75.0 (82e1608df 2023-12-21)`
+pub(crate) const PROCESS_RUNTIME_DESCRIPTION: &str = "process.runtime.description";
+
+/// The name of the runtime of this process. For compiled native binaries, this SHOULD be the name of the compiler.
+///
+/// # Examples
+///
+/// - `rustc`
+pub(crate) const PROCESS_RUNTIME_NAME: &str = "process.runtime.name";
+
+/// The version of the runtime of this process, as returned by the runtime without modification.
+///
+/// # Examples
+///
+/// - `1.75.0`
+pub(crate) const PROCESS_RUNTIME_VERSION: &str = "process.runtime.version";
+
+/// Container ID. Usually a UUID, as for example used to [identify Docker containers](https://docs.docker.com/engine/reference/run/#container-identification). The UUID might be abbreviated.
+///
+/// # Examples
+///
+/// - `a3bf90e006b2`
+pub(crate) const CONTAINER_ID: &str = "container.id";
+
+/// The name of the namespace that the pod is running in.
+///
+/// # Examples
+///
+/// - `default`
+pub(crate) const K8S_NAMESPACE_NAME: &str = "k8s.namespace.name";
+
+/// The name of the Node.
+///
+/// # Examples
+///
+/// - `node-1`
+pub(crate) const K8S_NODE_NAME: &str = "k8s.node.name";
+
+/// The name of the Pod.
+///
+/// # Examples
+///
+/// - `opentelemetry-pod-autoconf`
+pub(crate) const K8S_POD_NAME: &str = "k8s.pod.name";
+
+/// The UID of the Pod.
+///
+/// # Examples
+///
+/// - `275ecb36-5aa8-4c2a-9c47-d8bb681b9aff`
+pub(crate) const K8S_POD_UID: &str = "k8s.pod.uid";
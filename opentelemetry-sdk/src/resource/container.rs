@@ -0,0 +1,199 @@
+//! Container resource detector
+//!
+//! Implementation of `ResourceDetector` to extract the id of the container the
+//! process is running in from the `/proc` file system.
+use crate::resource::{Resource, ResourceDetector};
+use opentelemetry::KeyValue;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const CGROUP_PATH: &str = "/proc/self/cgroup";
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+const CONTAINER_ID_LENGTH: usize = 64;
+
+/// Detect the id of the container the process is running in (`container.id`).
+///
+/// The id is read from `/proc/self/cgroup` with cgroup v1, where the cgroup
+/// paths of the process end with the container id, e.g. `/docker/<id>` or
+/// `/kubepods/burstable/pod<uid>/<id>`. With cgroup v2 the cgroup path of the
+/// process is usually `/`, so the id is read from the mounts of
+/// `/proc/self/mountinfo` instead, e.g. `/var/lib/docker/containers/<id>/hostname`.
+///
+/// No attribute is provided if the process isn't running in a container.
+///
+/// See [semantic conventions](https://github.com/open-telemetry/semantic-conventions/blob/main/docs/resource/container.md) for details.
+#[derive(Clone, Debug)]
+pub struct ContainerResourceDetector {
+    root: PathBuf,
+}
+
+impl ContainerResourceDetector {
+    /// Create `ContainerResourceDetector` instance.
+    pub fn new() -> Self {
+        ContainerResourceDetector {
+            root: PathBuf::from("/"),
+        }
+    }
+
+    /// Reads the `/proc` files under `root` instead of the root of the file
+    /// system, e.g. `<root>/proc/self/cgroup`.
+    pub fn with_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub(crate) fn container_id(&self) -> Option<String> {
+        read(&self.root, CGROUP_PATH)
+            .and_then(|cgroup| container_id_from_cgroup(&cgroup))
+            .or_else(|| {
+                read(&self.root, MOUNTINFO_PATH)
+                    .and_then(|mountinfo| container_id_from_mountinfo(&mountinfo))
+            })
+    }
+}
+
+impl Default for ContainerResourceDetector {
+    fn default() -> Self {
+        ContainerResourceDetector::new()
+    }
+}
+
+impl ResourceDetector for ContainerResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        Resource::new(
+            self.container_id()
+                .map(|id| KeyValue::new(super::CONTAINER_ID, id)),
+        )
+    }
+}
+
+/// Resolves the absolute `path` under `root`.
+pub(crate) fn resolve(root: &Path, path: &Path) -> PathBuf {
+    root.join(path.strip_prefix("/").unwrap_or(path))
+}
+
+/// Returns the content of the file at `path` under `root`.
+pub(crate) fn read<P: AsRef<Path>>(root: &Path, path: P) -> Option<String> {
+    fs::read_to_string(resolve(root, path.as_ref())).ok()
+}
+
+/// Parses the lines of a `/proc/self/cgroup` file, formatted as
+/// `<hierarchy id>:<controllers>:<cgroup path>`.
+pub(crate) fn cgroup_paths(cgroup: &str) -> impl Iterator<Item = &str> {
+    cgroup.lines().filter_map(|line| line.splitn(3, ':').nth(2))
+}
+
+fn container_id_from_cgroup(cgroup: &str) -> Option<String> {
+    cgroup_paths(cgroup)
+        .filter_map(|path| path.rsplit('/').next())
+        .find_map(container_id_from_segment)
+}
+
+/// Parses the lines of a `/proc/self/mountinfo` file, of which the fourth field
+/// is the root of the mount, looking for the files the container runtimes
+/// mount from their container directory.
+fn container_id_from_mountinfo(mountinfo: &str) -> Option<String> {
+    mountinfo
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(3))
+        .find_map(|root| {
+            // e.g. /var/lib/docker/containers/<id>/hostname or
+            // /var/lib/containers/storage/overlay-containers/<id>/userdata/hostname
+            let segments = root.split('/').collect::<Vec<_>>();
+            segments.windows(2).find_map(|pair| match pair {
+                ["containers" | "overlay-containers", id] => container_id_from_segment(id),
+                _ => None,
+            })
+        })
+}
+
+/// Extracts the container id from a cgroup path segment, either the id itself
+/// or a systemd scope such as `docker-<id>.scope`, `cri-containerd-<id>.scope`
+/// or `crio-<id>.scope`.
+fn container_id_from_segment(segment: &str) -> Option<String> {
+    let id = segment.strip_suffix(".scope").unwrap_or(segment);
+    let id = id.rsplit(['-', ':']).next()?;
+    (id.len() == CONTAINER_ID_LENGTH && id.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then(|| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{container_id_from_cgroup, container_id_from_mountinfo, ContainerResourceDetector};
+    use crate::resource::{Resource, ResourceDetector};
+    use opentelemetry::KeyValue;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    const ID: &str = "7be92808767a667f35c8505cbf40d14e931ef6db5b0210329cf193b15ba9d605";
+
+    fn fixture(name: &str) -> PathBuf {
+        [env!("CARGO_MANIFEST_DIR"), "tests/data/resource", name]
+            .iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_container_id_from_cgroup() {
+        for cgroup in [
+            format!("12:pids:/docker/{ID}\n11:memory:/docker/{ID}"),
+            format!("1:name=systemd:/system.slice/docker-{ID}.scope"),
+            format!("0::/kubepods.slice/kubepods-pod1.slice/cri-containerd-{ID}.scope"),
+            format!("0::/kubepods.slice/kubepods-pod1.slice/crio-{ID}.scope"),
+            format!("0::/system.slice/containerd.service/kubepods-besteffort-pod1.slice:cri-containerd:{ID}"),
+        ] {
+            assert_eq!(container_id_from_cgroup(&cgroup).as_deref(), Some(ID));
+        }
+
+        assert_eq!(container_id_from_cgroup("0::/"), None);
+        assert_eq!(
+            container_id_from_cgroup("1:name=systemd:/user.slice/user-1000.slice/session-2.scope"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_container_id_from_mountinfo() {
+        for mountinfo in [
+            format!("1 2 8:1 /var/lib/docker/containers/{ID}/hostname /etc/hostname rw - ext4 /dev/sda1 rw"),
+            format!("1 2 0:1 /var/lib/containers/storage/overlay-containers/{ID}/userdata/hostname /etc/hostname rw - tmpfs tmpfs rw"),
+        ] {
+            assert_eq!(container_id_from_mountinfo(&mountinfo).as_deref(), Some(ID));
+        }
+
+        assert_eq!(
+            container_id_from_mountinfo(&format!(
+                "1 2 0:1 /var/lib/containerd/sandboxes/{ID}/hostname /etc/hostname rw - tmpfs tmpfs rw"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detect_from_fixtures() {
+        for (fixture_name, expected) in [
+            (
+                "docker-cgroupv1",
+                Resource::new(vec![KeyValue::new("container.id", ID)]),
+            ),
+            (
+                "docker-cgroupv2",
+                Resource::new(vec![KeyValue::new("container.id", ID)]),
+            ),
+            ("host", Resource::empty()),
+            ("missing", Resource::empty()),
+        ] {
+            let detector = ContainerResourceDetector::new().with_root(fixture(fixture_name));
+            assert_eq!(
+                detector.detect(Duration::from_secs(0)),
+                expected,
+                "{fixture_name}"
+            );
+        }
+    }
+}
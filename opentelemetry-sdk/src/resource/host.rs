@@ -0,0 +1,97 @@
+//! Host resource detector
+//!
+//! Implementation of `ResourceDetector` to describe the host the process is
+//! running on.
+use crate::resource::{Resource, ResourceDetector};
+use opentelemetry::KeyValue;
+use std::env;
+use std::fs;
+use std::time::Duration;
+
+/// Files holding the machine id, in order of preference.
+const MACHINE_ID_PATHS: [&str; 2] = ["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
+/// Detect the host the process is running on.
+///
+/// It provides:
+/// - The name of the host (`host.name`), read from `/proc/sys/kernel/hostname`
+///   or `/etc/hostname` on Unix and from the `COMPUTERNAME` environment variable
+///   on Windows.
+/// - The machine id (`host.id`), read from `/etc/machine-id` or
+///   `/var/lib/dbus/machine-id`.
+/// - The CPU architecture (`host.arch`).
+///
+/// Attributes which cannot be detected are omitted.
+///
+/// See [semantic conventions](https://github.com/open-telemetry/semantic-conventions/blob/main/docs/resource/host.md) for details.
+#[derive(Debug)]
+pub struct HostResourceDetector;
+
+impl ResourceDetector for HostResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let mut attributes = vec![KeyValue::new(super::HOST_ARCH, host_arch())];
+        if let Some(name) = host_name() {
+            attributes.push(KeyValue::new(super::HOST_NAME, name));
+        }
+        if let Some(id) = MACHINE_ID_PATHS.iter().find_map(|path| read_trimmed(path)) {
+            attributes.push(KeyValue::new(super::HOST_ID, id));
+        }
+
+        Resource::new(attributes)
+    }
+}
+
+fn host_name() -> Option<String> {
+    if cfg!(windows) {
+        env::var("COMPUTERNAME")
+            .ok()
+            .filter(|name| !name.is_empty())
+    } else {
+        read_trimmed("/proc/sys/kernel/hostname").or_else(|| read_trimmed("/etc/hostname"))
+    }
+}
+
+/// Maps the target architecture to the well-known values of `host.arch`.
+fn host_arch() -> &'static str {
+    match env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "arm" => "arm32",
+        "powerpc" => "ppc32",
+        "powerpc64" => "ppc64",
+        arch => arch,
+    }
+}
+
+/// Returns the trimmed content of the file at `path`, if it isn't empty.
+fn read_trimmed(path: &str) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+        .filter(|content| !content.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HostResourceDetector;
+    use crate::resource::ResourceDetector;
+    use opentelemetry::{Key, Value};
+    use std::time::Duration;
+
+    #[test]
+    fn test_host_resource_detector() {
+        let resource = HostResourceDetector.detect(Duration::from_secs(0));
+
+        let arch = resource.get(Key::from_static_str(crate::resource::HOST_ARCH));
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(arch, Some(Value::from("amd64")));
+        #[cfg(target_arch = "aarch64")]
+        assert_eq!(arch, Some(Value::from("arm64")));
+        assert!(arch.is_some());
+
+        #[cfg(target_os = "linux")]
+        assert!(resource
+            .get(Key::from_static_str(crate::resource::HOST_NAME))
+            .is_some());
+    }
+}
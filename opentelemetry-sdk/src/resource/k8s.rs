@@ -0,0 +1,300 @@
+//! Kubernetes resource detector
+//!
+//! Implementation of `ResourceDetector` to extract the attributes of the
+//! Kubernetes pod the process is running in.
+use crate::resource::container::{cgroup_paths, read, ContainerResourceDetector};
+use crate::resource::{Resource, ResourceDetector};
+use opentelemetry::KeyValue;
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const CGROUP_PATH: &str = "/proc/self/cgroup";
+const SERVICE_ACCOUNT_NAMESPACE_PATH: &str =
+    "/var/run/secrets/kubernetes.io/serviceaccount/namespace";
+const POD_UID_LENGTH: usize = 36;
+
+/// Where the value of a Kubernetes attribute is read from.
+///
+/// Both are usually filled by the [downward API] of the pod.
+///
+/// [downward API]: https://kubernetes.io/docs/concepts/workloads/pods/downward-api/
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum K8sAttributeSource {
+    /// An environment variable, e.g. set from a `fieldRef` in the pod spec.
+    Env(String),
+    /// A file, e.g. mounted from a `downwardAPI` volume.
+    ///
+    /// The path is resolved under the root of the detector.
+    File(PathBuf),
+}
+
+impl K8sAttributeSource {
+    fn read(&self, detector: &K8sResourceDetector) -> Option<String> {
+        let value = match self {
+            K8sAttributeSource::Env(name) => env::var(name).ok(),
+            K8sAttributeSource::File(path) => read(detector.container.root(), path),
+        }?;
+        let value = value.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    }
+}
+
+/// Detect the Kubernetes pod the process is running in.
+///
+/// It provides:
+/// - The id of the container (`container.id`), as detected by [ContainerResourceDetector].
+/// - The name of the pod (`k8s.pod.name`), read from the `K8S_POD_NAME` environment variable by default.
+/// - The UID of the pod (`k8s.pod.uid`), read from the `K8S_POD_UID` environment variable or the cgroup
+///   of the process by default.
+/// - The namespace of the pod (`k8s.namespace.name`), read from the `K8S_NAMESPACE_NAME` environment variable
+///   or the namespace of the service account mounted in the pod by default.
+/// - The name of the node (`k8s.node.name`), read from the `K8S_NODE_NAME` environment variable by default.
+///
+/// The environment variables are set with the downward API of the pod:
+///
+/// ```yaml
+/// env:
+///   - name: K8S_POD_NAME
+///     valueFrom:
+///       fieldRef:
+///         fieldPath: metadata.name
+///   - name: K8S_POD_UID
+///     valueFrom:
+///       fieldRef:
+///         fieldPath: metadata.uid
+///   - name: K8S_NAMESPACE_NAME
+///     valueFrom:
+///       fieldRef:
+///         fieldPath: metadata.namespace
+///   - name: K8S_NODE_NAME
+///     valueFrom:
+///       fieldRef:
+///         fieldPath: spec.nodeName
+/// ```
+///
+/// Other sources are configured with the `with_*` methods, and are tried before
+/// the default ones. Attributes which cannot be detected are omitted.
+///
+/// See [semantic conventions](https://github.com/open-telemetry/semantic-conventions/blob/main/docs/resource/k8s.md) for details.
+#[derive(Clone, Debug)]
+pub struct K8sResourceDetector {
+    container: ContainerResourceDetector,
+    pod_name: Vec<K8sAttributeSource>,
+    pod_uid: Vec<K8sAttributeSource>,
+    namespace_name: Vec<K8sAttributeSource>,
+    node_name: Vec<K8sAttributeSource>,
+}
+
+impl K8sResourceDetector {
+    /// Create `K8sResourceDetector` instance.
+    pub fn new() -> Self {
+        K8sResourceDetector {
+            container: ContainerResourceDetector::new(),
+            pod_name: vec![K8sAttributeSource::Env("K8S_POD_NAME".into())],
+            pod_uid: vec![K8sAttributeSource::Env("K8S_POD_UID".into())],
+            namespace_name: vec![
+                K8sAttributeSource::Env("K8S_NAMESPACE_NAME".into()),
+                K8sAttributeSource::File(SERVICE_ACCOUNT_NAMESPACE_PATH.into()),
+            ],
+            node_name: vec![K8sAttributeSource::Env("K8S_NODE_NAME".into())],
+        }
+    }
+
+    /// Reads the files under `root` instead of the root of the file system,
+    /// e.g. `<root>/proc/self/cgroup`.
+    pub fn with_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.container = self.container.with_root(root);
+        self
+    }
+
+    /// Reads the name of the pod from `source` first.
+    pub fn with_pod_name(mut self, source: K8sAttributeSource) -> Self {
+        self.pod_name.insert(0, source);
+        self
+    }
+
+    /// Reads the UID of the pod from `source` first.
+    pub fn with_pod_uid(mut self, source: K8sAttributeSource) -> Self {
+        self.pod_uid.insert(0, source);
+        self
+    }
+
+    /// Reads the namespace of the pod from `source` first.
+    pub fn with_namespace_name(mut self, source: K8sAttributeSource) -> Self {
+        self.namespace_name.insert(0, source);
+        self
+    }
+
+    /// Reads the name of the node from `source` first.
+    pub fn with_node_name(mut self, source: K8sAttributeSource) -> Self {
+        self.node_name.insert(0, source);
+        self
+    }
+
+    fn read(&self, sources: &[K8sAttributeSource]) -> Option<String> {
+        sources.iter().find_map(|source| source.read(self))
+    }
+
+    fn pod_uid_from_cgroup(&self) -> Option<String> {
+        cgroup_paths(&read(self.container.root(), CGROUP_PATH)?)
+            .flat_map(|path| path.split('/'))
+            .find_map(pod_uid_from_segment)
+    }
+}
+
+impl Default for K8sResourceDetector {
+    fn default() -> Self {
+        K8sResourceDetector::new()
+    }
+}
+
+impl ResourceDetector for K8sResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let mut attributes = Vec::new();
+        if let Some(id) = self.container.container_id() {
+            attributes.push(KeyValue::new(super::CONTAINER_ID, id));
+        }
+        if let Some(name) = self.read(&self.pod_name) {
+            attributes.push(KeyValue::new(super::K8S_POD_NAME, name));
+        }
+        if let Some(uid) = self
+            .read(&self.pod_uid)
+            .or_else(|| self.pod_uid_from_cgroup())
+        {
+            attributes.push(KeyValue::new(super::K8S_POD_UID, uid));
+        }
+        if let Some(namespace) = self.read(&self.namespace_name) {
+            attributes.push(KeyValue::new(super::K8S_NAMESPACE_NAME, namespace));
+        }
+        if let Some(node) = self.read(&self.node_name) {
+            attributes.push(KeyValue::new(super::K8S_NODE_NAME, node));
+        }
+
+        Resource::new(attributes)
+    }
+}
+
+/// Extracts the pod UID from a cgroup path segment, either `pod<uid>` with the
+/// cgroupfs driver or `kubepods-<qos class>-pod<uid>.slice` with the systemd
+/// driver, where the dashes of the UID are replaced by underscores.
+fn pod_uid_from_segment(segment: &str) -> Option<String> {
+    let segment = segment.strip_suffix(".slice").unwrap_or(segment);
+    // the UID can't contain `pod`, being made of hexadecimal digits
+    let start = segment.rfind("pod")?;
+    if start > 0 && !segment[..start].ends_with('-') {
+        return None;
+    }
+    let uid = &segment[start + "pod".len()..];
+    let uid = uid.replace('_', "-");
+    (uid.len() == POD_UID_LENGTH && uid.bytes().all(|b| b == b'-' || b.is_ascii_hexdigit()))
+        .then_some(uid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pod_uid_from_segment, K8sAttributeSource, K8sResourceDetector};
+    use crate::resource::{Resource, ResourceDetector};
+    use opentelemetry::KeyValue;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    const CONTAINER_ID: &str = "7be92808767a667f35c8505cbf40d14e931ef6db5b0210329cf193b15ba9d605";
+    const POD_UID: &str = "275ecb36-5aa8-4c2a-9c47-d8bb681b9aff";
+
+    fn fixture(name: &str) -> PathBuf {
+        [env!("CARGO_MANIFEST_DIR"), "tests/data/resource", name]
+            .iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_pod_uid_from_segment() {
+        assert_eq!(
+            pod_uid_from_segment(&format!("pod{POD_UID}")).as_deref(),
+            Some(POD_UID)
+        );
+        assert_eq!(
+            pod_uid_from_segment(&format!(
+                "kubepods-burstable-pod{}.slice",
+                POD_UID.replace('-', "_")
+            ))
+            .as_deref(),
+            Some(POD_UID)
+        );
+        assert_eq!(pod_uid_from_segment("kubepods"), None);
+        assert_eq!(pod_uid_from_segment("podinfo"), None);
+    }
+
+    #[test]
+    fn test_detect_from_fixtures() {
+        temp_env::with_vars(
+            [
+                ("K8S_POD_NAME", None::<&str>),
+                ("K8S_POD_UID", None),
+                ("K8S_NAMESPACE_NAME", None),
+                ("K8S_NODE_NAME", Some("node-1")),
+            ],
+            || {
+                let detector = K8sResourceDetector::new()
+                    .with_root(fixture("k8s"))
+                    .with_pod_name(K8sAttributeSource::File("/etc/podinfo/name".into()));
+                assert_eq!(
+                    detector.detect(Duration::from_secs(0)),
+                    Resource::new(vec![
+                        KeyValue::new("container.id", CONTAINER_ID),
+                        KeyValue::new("k8s.pod.name", "my-pod-7d4ec78312"),
+                        KeyValue::new("k8s.pod.uid", POD_UID),
+                        KeyValue::new("k8s.namespace.name", "my-namespace"),
+                        KeyValue::new("k8s.node.name", "node-1"),
+                    ])
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_env_sources_take_precedence() {
+        temp_env::with_vars(
+            [
+                ("K8S_POD_NAME", Some("env-pod")),
+                ("K8S_POD_UID", Some("env-uid")),
+                ("K8S_NAMESPACE_NAME", Some("env-namespace")),
+                ("K8S_NODE_NAME", None),
+                ("MY_NODE", Some("my-node")),
+            ],
+            || {
+                let detector = K8sResourceDetector::new()
+                    .with_root(fixture("k8s"))
+                    .with_node_name(K8sAttributeSource::Env("MY_NODE".into()));
+                assert_eq!(
+                    detector.detect(Duration::from_secs(0)),
+                    Resource::new(vec![
+                        KeyValue::new("container.id", CONTAINER_ID),
+                        KeyValue::new("k8s.pod.name", "env-pod"),
+                        KeyValue::new("k8s.pod.uid", "env-uid"),
+                        KeyValue::new("k8s.namespace.name", "env-namespace"),
+                        KeyValue::new("k8s.node.name", "my-node"),
+                    ])
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_outside_k8s() {
+        temp_env::with_vars(
+            [
+                ("K8S_POD_NAME", None::<&str>),
+                ("K8S_POD_UID", None),
+                ("K8S_NAMESPACE_NAME", None),
+                ("K8S_NODE_NAME", None),
+            ],
+            || {
+                let detector = K8sResourceDetector::new().with_root(fixture("host"));
+                assert_eq!(detector.detect(Duration::from_secs(0)), Resource::empty());
+            },
+        );
+    }
+}
@@ -18,15 +18,52 @@
 //! - [`EnvResourceDetector`] - detect resource from environmental variables.
 //! - [`TelemetryResourceDetector`] - detect telemetry SDK's information.
 //!
-//! The OS and Process resource detectors are now packaged separately in the `opentelemetry-resource-detector` [crate](https://github.com/open-telemetry/opentelemetry-rust-contrib/tree/main/opentelemetry-resource-detectors).
+//! The following detectors are also provided, but not used by the default
+//! [`Resource`]. Pass them to [`Resource::from_detectors`] to opt in.
+//!
+//! - [`ProcessResourceDetector`] - detect the running process.
+//! - [`HostResourceDetector`] - detect the host's name, id and architecture.
+//! - [`OsResourceDetector`] - detect the operating system.
+//! - [`ContainerResourceDetector`] - detect the id of the container.
+//! - [`K8sResourceDetector`] - detect the container id and the Kubernetes pod.
+//!
+//! The OS and process detectors are also packaged in the
+//! `opentelemetry-resource-detectors` [crate](https://github.com/open-telemetry/opentelemetry-rust-contrib/tree/main/opentelemetry-resource-detectors),
+//! whose detectors of the same names detect a subset of these attributes.
+//!
+//! ```
+//! use opentelemetry_sdk::resource::{
+//!     HostResourceDetector, OsResourceDetector, ProcessResourceDetector, Resource,
+//! };
+//! use std::time::Duration;
+//!
+//! let resource = Resource::default().merge(&Resource::from_detectors(
+//!     Duration::from_secs(0),
+//!     vec![
+//!         Box::new(ProcessResourceDetector),
+//!         Box::new(HostResourceDetector),
+//!         Box::new(OsResourceDetector),
+//!     ],
+//! ));
+//! ```
+mod container;
 mod env;
+mod host;
+mod k8s;
+mod os;
+mod process;
 mod telemetry;
 
 mod attributes;
 pub(crate) use attributes::*;
 
+pub use container::ContainerResourceDetector;
 pub use env::EnvResourceDetector;
 pub use env::SdkProvidedResourceDetector;
+pub use host::HostResourceDetector;
+pub use k8s::{K8sAttributeSource, K8sResourceDetector};
+pub use os::OsResourceDetector;
+pub use process::ProcessResourceDetector;
 pub use telemetry::TelemetryResourceDetector;
 
 use opentelemetry::{Key, KeyValue, Value};
@@ -0,0 +1,102 @@
+//! Operating system resource detector
+//!
+//! Implementation of `ResourceDetector` to describe the operating system the
+//! process is running on.
+use crate::resource::{Resource, ResourceDetector};
+use opentelemetry::KeyValue;
+use std::env;
+use std::fs;
+use std::time::Duration;
+
+/// Files holding the operating system identification, in order of preference.
+const OS_RELEASE_PATHS: [&str; 2] = ["/etc/os-release", "/usr/lib/os-release"];
+
+/// Detect the operating system the process is running on.
+///
+/// It provides:
+/// - The operating system type (`os.type`), e.g. `linux`, `windows` or `darwin`.
+/// - A human readable description of the operating system (`os.description`),
+///   read from the `PRETTY_NAME` field of `/etc/os-release` or
+///   `/usr/lib/os-release`, when available.
+///
+/// See [semantic conventions](https://github.com/open-telemetry/semantic-conventions/blob/main/docs/resource/os.md) for details.
+#[derive(Debug)]
+pub struct OsResourceDetector;
+
+impl ResourceDetector for OsResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let mut attributes = vec![KeyValue::new(super::OS_TYPE, os_type())];
+        if let Some(description) = OS_RELEASE_PATHS
+            .iter()
+            .find_map(|path| fs::read_to_string(path).ok())
+            .and_then(|os_release| pretty_name(&os_release))
+        {
+            attributes.push(KeyValue::new(super::OS_DESCRIPTION, description));
+        }
+
+        Resource::new(attributes)
+    }
+}
+
+/// Maps the target operating system to the well-known values of `os.type`.
+fn os_type() -> &'static str {
+    match env::consts::OS {
+        "macos" => "darwin",
+        "dragonfly" => "dragonflybsd",
+        os => os,
+    }
+}
+
+/// Extracts the `PRETTY_NAME` field of an [os-release] file.
+///
+/// [os-release]: https://www.freedesktop.org/software/systemd/man/latest/os-release.html
+fn pretty_name(os_release: &str) -> Option<String> {
+    os_release
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("PRETTY_NAME="))
+        .map(|value| value.trim_matches(|c| c == '"' || c == '\'').to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pretty_name, OsResourceDetector};
+    use crate::resource::ResourceDetector;
+    use opentelemetry::{Key, Value};
+    use std::time::Duration;
+
+    #[test]
+    fn test_os_resource_detector() {
+        let resource = OsResourceDetector.detect(Duration::from_secs(0));
+
+        #[cfg(target_os = "linux")]
+        assert_eq!(
+            resource.get(Key::from_static_str(crate::resource::OS_TYPE)),
+            Some(Value::from("linux"))
+        );
+        #[cfg(target_os = "macos")]
+        assert_eq!(
+            resource.get(Key::from_static_str(crate::resource::OS_TYPE)),
+            Some(Value::from("darwin"))
+        );
+        assert!(resource
+            .get(Key::from_static_str(crate::resource::OS_TYPE))
+            .is_some());
+    }
+
+    #[test]
+    fn test_pretty_name() {
+        let os_release = r#"NAME="Ubuntu"
+VERSION_ID="22.04"
+PRETTY_NAME="Ubuntu 22.04.3 LTS"
+ID=ubuntu
+"#;
+        assert_eq!(
+            pretty_name(os_release),
+            Some("Ubuntu 22.04.3 LTS".to_string())
+        );
+        assert_eq!(pretty_name("PRETTY_NAME=Alpine"), Some("Alpine".into()));
+        assert_eq!(pretty_name("NAME=Linux\nPRETTY_NAME=\"\""), None);
+        assert_eq!(pretty_name(""), None);
+    }
+}
@@ -0,0 +1,138 @@
+//! Process resource detector
+//!
+//! Implementation of `ResourceDetector` to describe the running process.
+use crate::resource::{Resource, ResourceDetector};
+use opentelemetry::{Array, KeyValue, StringValue, Value};
+use std::env;
+use std::time::Duration;
+
+const RUNTIME_NAME: &str = "rustc";
+const RUSTC_VERSION: &str = env!("OTEL_RUSTC_VERSION");
+
+/// Detect the running process.
+///
+/// It provides:
+/// - The process identifier (`process.pid`) and, on Unix, the parent process identifier (`process.parent_pid`).
+/// - The name and the full path of the process executable (`process.executable.name`, `process.executable.path`).
+/// - The command used to launch the process (`process.command`) and all its arguments (`process.command_args`).
+/// - The compiler the SDK was built with (`process.runtime.name`, `process.runtime.version`, `process.runtime.description`).
+/// - On Linux, the name of the user owning the process (`process.owner`).
+///
+/// Attributes which cannot be detected are omitted.
+///
+/// See [semantic conventions](https://github.com/open-telemetry/semantic-conventions/blob/main/docs/resource/process.md) for details.
+#[derive(Debug)]
+pub struct ProcessResourceDetector;
+
+impl ResourceDetector for ProcessResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let mut attributes = vec![KeyValue::new(
+            super::PROCESS_PID,
+            i64::from(std::process::id()),
+        )];
+
+        #[cfg(unix)]
+        attributes.push(KeyValue::new(
+            super::PROCESS_PARENT_PID,
+            i64::from(std::os::unix::process::parent_id()),
+        ));
+
+        if let Ok(path) = env::current_exe() {
+            if let Some(name) = path.file_name() {
+                attributes.push(KeyValue::new(
+                    super::PROCESS_EXECUTABLE_NAME,
+                    name.to_string_lossy().into_owned(),
+                ));
+            }
+            attributes.push(KeyValue::new(
+                super::PROCESS_EXECUTABLE_PATH,
+                path.to_string_lossy().into_owned(),
+            ));
+        }
+
+        let args = env::args_os()
+            .map(|arg| StringValue::from(arg.to_string_lossy().into_owned()))
+            .collect::<Vec<_>>();
+        if let Some(command) = args.first() {
+            attributes.push(KeyValue::new(super::PROCESS_COMMAND, command.clone()));
+        }
+        attributes.push(KeyValue::new(
+            super::PROCESS_COMMAND_ARGS,
+            Value::Array(Array::String(args)),
+        ));
+
+        attributes.push(KeyValue::new(super::PROCESS_RUNTIME_NAME, RUNTIME_NAME));
+        // `rustc 1.75.0 (82e1608df 2023-12-21)`
+        if let Some(version) = RUSTC_VERSION.split_whitespace().nth(1) {
+            attributes.push(KeyValue::new(super::PROCESS_RUNTIME_VERSION, version));
+            attributes.push(KeyValue::new(
+                super::PROCESS_RUNTIME_DESCRIPTION,
+                RUSTC_VERSION,
+            ));
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(owner) = owner() {
+            attributes.push(KeyValue::new(super::PROCESS_OWNER, owner));
+        }
+
+        Resource::new(attributes)
+    }
+}
+
+/// Returns the name of the effective user of the process, read from
+/// `/proc/self/status` and `/etc/passwd`.
+#[cfg(target_os = "linux")]
+fn owner() -> Option<String> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    // Uid: <real> <effective> <saved set> <filesystem>
+    let uid = status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))?
+        .split_whitespace()
+        .nth(1)?;
+
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+    passwd.lines().find_map(|line| {
+        // name:password:uid:gid:gecos:home:shell
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        (fields.nth(1)? == uid).then(|| name.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProcessResourceDetector;
+    use crate::resource::ResourceDetector;
+    use opentelemetry::{Key, Value};
+    use std::time::Duration;
+
+    #[test]
+    fn test_process_resource_detector() {
+        let resource = ProcessResourceDetector.detect(Duration::from_secs(0));
+
+        assert_eq!(
+            resource.get(Key::from_static_str(crate::resource::PROCESS_PID)),
+            Some(Value::I64(i64::from(std::process::id())))
+        );
+        assert_eq!(
+            resource.get(Key::from_static_str(crate::resource::PROCESS_RUNTIME_NAME)),
+            Some(Value::from("rustc"))
+        );
+        assert!(resource
+            .get(Key::from_static_str(
+                crate::resource::PROCESS_RUNTIME_VERSION
+            ))
+            .is_some());
+        assert!(matches!(
+            resource.get(Key::from_static_str(crate::resource::PROCESS_COMMAND_ARGS)),
+            Some(Value::Array(_))
+        ));
+        assert!(resource
+            .get(Key::from_static_str(
+                crate::resource::PROCESS_EXECUTABLE_PATH
+            ))
+            .is_some());
+    }
+}
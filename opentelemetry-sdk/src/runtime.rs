@@ -165,6 +165,15 @@ pub enum TrySendError {
     Other(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
 }
 
+impl<T> From<std::sync::mpsc::TrySendError<T>> for TrySendError {
+    fn from(err: std::sync::mpsc::TrySendError<T>) -> Self {
+        match err {
+            std::sync::mpsc::TrySendError::Full(_) => TrySendError::ChannelFull,
+            std::sync::mpsc::TrySendError::Disconnected(_) => TrySendError::ChannelClosed,
+        }
+    }
+}
+
 /// TrySend is an abstraction of `Sender` that is capable of sending messages through a reference.
 pub trait TrySend: Sync + Send {
     /// The message that will be sent.
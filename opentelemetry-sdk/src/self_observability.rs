@@ -0,0 +1,322 @@
+//! Metrics describing the telemetry pipelines of the SDK itself.
+//!
+//! The instruments are only created when a [MeterProvider] is given to one of
+//! the `with_self_observability` methods of the processor, reader or provider
+//! builders. Otherwise, and when the `metrics` feature is disabled, recording
+//! is a no-op.
+//!
+//! [MeterProvider]: opentelemetry::metrics::MeterProvider
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+use std::time::Duration;
+
+#[cfg(any(feature = "trace", feature = "logs"))]
+use crate::runtime::TrySendError;
+
+#[cfg(all(feature = "metrics", any(feature = "trace", feature = "logs")))]
+use opentelemetry::metrics::UpDownCounter;
+#[cfg(feature = "metrics")]
+use opentelemetry::{
+    metrics::{Counter, Histogram, Meter, MeterProvider, Unit},
+    KeyValue,
+};
+#[cfg(feature = "metrics")]
+use std::{borrow::Cow, sync::Arc};
+
+/// The telemetry exported by the instrumented exporter.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Signal {
+    #[cfg(feature = "trace")]
+    Traces,
+    #[cfg(feature = "logs")]
+    Logs,
+    Metrics,
+}
+
+#[cfg(feature = "metrics")]
+impl Signal {
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "trace")]
+            Signal::Traces => "traces",
+            #[cfg(feature = "logs")]
+            Signal::Logs => "logs",
+            Signal::Metrics => "metrics",
+        }
+    }
+
+    fn exported(self) -> (&'static str, &'static str) {
+        match self {
+            #[cfg(feature = "trace")]
+            Signal::Traces => ("otel.sdk.exporter.spans.exported", "{span}"),
+            #[cfg(feature = "logs")]
+            Signal::Logs => ("otel.sdk.exporter.logs.exported", "{log_record}"),
+            Signal::Metrics => ("otel.sdk.exporter.metric_points.exported", "{data_point}"),
+        }
+    }
+}
+
+/// The telemetry exported through the instrumented batch processor.
+#[cfg(all(feature = "metrics", any(feature = "trace", feature = "logs")))]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ProcessorSignal {
+    #[cfg(feature = "trace")]
+    Traces,
+    #[cfg(feature = "logs")]
+    Logs,
+}
+
+#[cfg(all(feature = "metrics", any(feature = "trace", feature = "logs")))]
+impl ProcessorSignal {
+    /// The names of the queue size, dropped items and batch size instruments.
+    fn instruments(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            #[cfg(feature = "trace")]
+            ProcessorSignal::Traces => (
+                "otel.sdk.span.processor.queue_size",
+                "otel.sdk.span.processor.spans.dropped",
+                "otel.sdk.span.processor.batch_size",
+            ),
+            #[cfg(feature = "logs")]
+            ProcessorSignal::Logs => (
+                "otel.sdk.log.processor.queue_size",
+                "otel.sdk.log.processor.logs.dropped",
+                "otel.sdk.log.processor.batch_size",
+            ),
+        }
+    }
+}
+
+#[cfg(all(feature = "metrics", any(feature = "trace", feature = "logs")))]
+impl From<ProcessorSignal> for Signal {
+    fn from(signal: ProcessorSignal) -> Self {
+        match signal {
+            #[cfg(feature = "trace")]
+            ProcessorSignal::Traces => Signal::Traces,
+            #[cfg(feature = "logs")]
+            ProcessorSignal::Logs => Signal::Logs,
+        }
+    }
+}
+
+/// The meter of the instruments of the SDK.
+#[cfg(feature = "metrics")]
+pub(crate) fn meter(meter_provider: &impl MeterProvider) -> Meter {
+    meter_provider.versioned_meter(
+        "opentelemetry_sdk",
+        Some(env!("CARGO_PKG_VERSION")),
+        None::<Cow<'static, str>>,
+        None,
+    )
+}
+
+/// Instruments of an exporter: the number of exported items and the duration
+/// of the exports, by outcome.
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ExporterMetrics {
+    #[cfg(feature = "metrics")]
+    instruments: Option<Arc<ExporterInstruments>>,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug)]
+struct ExporterInstruments {
+    exported: Counter<u64>,
+    duration: Histogram<f64>,
+    success: [KeyValue; 2],
+    failure: [KeyValue; 2],
+}
+
+#[cfg(any(feature = "trace", feature = "metrics", feature = "logs"))]
+impl ExporterMetrics {
+    #[cfg(feature = "metrics")]
+    pub(crate) fn new(meter: &Meter, signal: Signal) -> Self {
+        let (exported, unit) = signal.exported();
+        let signal = KeyValue::new("signal", signal.name());
+        ExporterMetrics {
+            instruments: Some(Arc::new(ExporterInstruments {
+                exported: meter
+                    .u64_counter(exported)
+                    .with_description("The number of items passed to the exporter, by outcome.")
+                    .with_unit(Unit::new(unit))
+                    .init(),
+                duration: meter
+                    .f64_histogram("otel.sdk.exporter.duration")
+                    .with_description("The duration of the exports, by signal and outcome.")
+                    .with_unit(Unit::new("s"))
+                    .init(),
+                success: [signal.clone(), KeyValue::new("outcome", "success")],
+                failure: [signal, KeyValue::new("outcome", "failure")],
+            })),
+        }
+    }
+
+    /// Records an export of `count` items, which took `duration`.
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    pub(crate) fn exported(&self, count: usize, success: bool, duration: Duration) {
+        #[cfg(feature = "metrics")]
+        if let Some(instruments) = &self.instruments {
+            let attributes = if success {
+                &instruments.success
+            } else {
+                &instruments.failure
+            };
+            instruments.exported.add(count as u64, attributes);
+            instruments
+                .duration
+                .record(duration.as_secs_f64(), attributes);
+        }
+    }
+}
+
+/// Instruments of a batch processor: the number of queued and dropped items,
+/// the size of the batches, and the metrics of its exporter.
+#[cfg(any(feature = "trace", feature = "logs"))]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProcessorMetrics {
+    pub(crate) exporter: ExporterMetrics,
+    #[cfg(feature = "metrics")]
+    instruments: Option<Arc<ProcessorInstruments>>,
+}
+
+#[cfg(all(feature = "metrics", any(feature = "trace", feature = "logs")))]
+#[derive(Debug)]
+struct ProcessorInstruments {
+    queue_size: UpDownCounter<i64>,
+    dropped: Counter<u64>,
+    batch_size: Histogram<u64>,
+    queue_full: [KeyValue; 1],
+    shutdown: [KeyValue; 1],
+}
+
+#[cfg(any(feature = "trace", feature = "logs"))]
+impl ProcessorMetrics {
+    #[cfg(feature = "metrics")]
+    pub(crate) fn new(meter_provider: &impl MeterProvider, signal: ProcessorSignal) -> Self {
+        let meter = meter(meter_provider);
+        let (queue_size, dropped, batch_size) = signal.instruments();
+        ProcessorMetrics {
+            exporter: ExporterMetrics::new(&meter, signal.into()),
+            instruments: Some(Arc::new(ProcessorInstruments {
+                queue_size: meter
+                    .i64_up_down_counter(queue_size)
+                    .with_description(
+                        "The number of items queued in the processor, waiting to be exported.",
+                    )
+                    .init(),
+                dropped: meter
+                    .u64_counter(dropped)
+                    .with_description("The number of items dropped by the processor, by reason.")
+                    .init(),
+                batch_size: meter
+                    .u64_histogram(batch_size)
+                    .with_description("The number of items of the batches passed to the exporter.")
+                    .init(),
+                queue_full: [KeyValue::new("reason", "queue_full")],
+                shutdown: [KeyValue::new("reason", "processor_shutdown")],
+            })),
+        }
+    }
+
+    /// Records an item added to the queue.
+    pub(crate) fn enqueued(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(instruments) = &self.instruments {
+            instruments.queue_size.add(1, &[]);
+        }
+    }
+
+    /// Records an item dropped because it couldn't be added to the queue.
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    pub(crate) fn dropped(&self, err: &TrySendError) {
+        #[cfg(feature = "metrics")]
+        if let Some(instruments) = &self.instruments {
+            let reason = match err {
+                TrySendError::ChannelFull => &instruments.queue_full,
+                _ => &instruments.shutdown,
+            };
+            instruments.dropped.add(1, reason);
+        }
+    }
+
+    /// Records a batch of `size` items taken out of the queue to be exported.
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    pub(crate) fn batch(&self, size: usize) {
+        #[cfg(feature = "metrics")]
+        if let Some(instruments) = &self.instruments {
+            instruments.queue_size.add(-(size as i64), &[]);
+            instruments.batch_size.record(size as u64, &[]);
+        }
+    }
+}
+
+/// Instruments of a meter provider: the measurements that overflowed the
+/// cardinality limit of their stream.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MeterProviderMetrics {
+    overflow: Option<Counter<u64>>,
+}
+
+#[cfg(feature = "metrics")]
+impl MeterProviderMetrics {
+    pub(crate) fn new(meter_provider: &impl MeterProvider) -> Self {
+        MeterProviderMetrics {
+            overflow: Some(
+                meter(meter_provider)
+                    .u64_counter("otel.sdk.metric.overflow.count")
+                    .with_description(
+                        "The number of measurements aggregated into the overflow data point of their stream.",
+                    )
+                    .with_unit(Unit::new("{measurement}"))
+                    .init(),
+            ),
+        }
+    }
+
+    /// Records `dropped` measurements of the stream `name` of `scope` that
+    /// overflowed its cardinality limit.
+    pub(crate) fn overflowed(&self, scope: &crate::Scope, name: &str, dropped: u64) {
+        if let Some(overflow) = &self.overflow {
+            overflow.add(
+                dropped,
+                &[
+                    KeyValue::new("otel.scope.name", scope.name.clone()),
+                    KeyValue::new("otel.metric.name", name.to_string()),
+                ],
+            );
+        }
+    }
+}
+
+/// The number of data points of `rm`.
+#[cfg(feature = "metrics")]
+pub(crate) fn data_points(rm: &crate::metrics::data::ResourceMetrics) -> usize {
+    use crate::metrics::data::{Aggregation, ExponentialHistogram, Gauge, Histogram, Sum};
+
+    fn count<T: 'static>(data: &dyn Aggregation) -> Option<usize> {
+        let data = data.as_any();
+        if let Some(gauge) = data.downcast_ref::<Gauge<T>>() {
+            Some(gauge.data_points.len())
+        } else if let Some(sum) = data.downcast_ref::<Sum<T>>() {
+            Some(sum.data_points.len())
+        } else if let Some(histogram) = data.downcast_ref::<Histogram<T>>() {
+            Some(histogram.data_points.len())
+        } else {
+            data.downcast_ref::<ExponentialHistogram<T>>()
+                .map(|histogram| histogram.data_points.len())
+        }
+    }
+
+    rm.scope_metrics
+        .iter()
+        .flat_map(|scope_metrics| &scope_metrics.metrics)
+        .filter_map(|metric| {
+            let data = metric.data.as_ref();
+            count::<u64>(data)
+                .or_else(|| count::<i64>(data))
+                .or_else(|| count::<f64>(data))
+        })
+        .sum()
+}
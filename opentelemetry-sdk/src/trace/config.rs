@@ -2,7 +2,10 @@
 //!
 //! Configuration represents the global tracing configuration, overrides
 //! can be set for the default OpenTelemetry limits and Sampler.
-use crate::trace::{span_limit::SpanLimits, IdGenerator, RandomIdGenerator, Sampler, ShouldSample};
+use crate::trace::{
+    span_limit::SpanLimits, IdGenerator, RandomIdGenerator, RateLimitingSampler, Sampler,
+    ShouldSample,
+};
 use crate::Resource;
 use opentelemetry::global::{handle_error, Error};
 use std::borrow::Cow;
@@ -73,6 +76,13 @@ impl Config {
         self
     }
 
+    /// Specify the max length, in characters, of the string attribute values of
+    /// spans, events and links. Longer values are truncated.
+    pub fn with_max_attribute_value_length(mut self, max_length: u32) -> Self {
+        self.span_limits.max_attribute_value_length = Some(max_length);
+        self
+    }
+
     /// Specify all limit via the span_limits
     pub fn with_span_limits(mut self, span_limits: SpanLimits) -> Self {
         self.span_limits = span_limits;
@@ -117,6 +127,14 @@ impl Default for Config {
             config.span_limits.max_links_per_span = max_links_per_span;
         }
 
+        if let Some(max_attribute_value_length) = env::var("OTEL_SPAN_ATTRIBUTE_VALUE_LENGTH_LIMIT")
+            .or_else(|_| env::var("OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT"))
+            .ok()
+            .and_then(|max_length| u32::from_str(&max_length).ok())
+        {
+            config.span_limits.max_attribute_value_length = Some(max_attribute_value_length);
+        }
+
         let sampler_arg = env::var("OTEL_TRACES_SAMPLER_ARG").ok();
         if let Ok(sampler) = env::var("OTEL_TRACES_SAMPLER") {
             config.sampler = match sampler.as_str() {
@@ -156,6 +174,10 @@ impl Default for Config {
                         ))))
                     }
                 }
+                "ratelimited" => Box::new(Sampler::RateLimited(rate_limiting_sampler(sampler_arg))),
+                "parentbased_ratelimited" => Box::new(Sampler::ParentBased(Box::new(
+                    Sampler::RateLimited(rate_limiting_sampler(sampler_arg)),
+                ))),
                 "parentbased_jaeger_remote" => {
                     handle_error(
                         Error::Other(String::from(
@@ -186,3 +208,85 @@ impl Default for Config {
         config
     }
 }
+
+/// Default number of traces per second sampled by the `ratelimited` samplers.
+const DEFAULT_TRACES_PER_SECOND: f64 = 100.0;
+
+fn rate_limiting_sampler(sampler_arg: Option<String>) -> RateLimitingSampler {
+    let rate = sampler_arg
+        .and_then(|r| r.parse::<f64>().ok())
+        .filter(|r| *r >= 0.0);
+    RateLimitingSampler::new(rate.unwrap_or_else(|| {
+        handle_error(Error::Other(format!(
+            "Missing or invalid OTEL_TRACES_SAMPLER_ARG value. Falling back to default: {}",
+            DEFAULT_TRACES_PER_SECOND
+        )));
+        DEFAULT_TRACES_PER_SECOND
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+
+    #[test]
+    fn test_rate_limited_sampler_from_env() {
+        for (sampler, arg, expected) in [
+            (
+                "ratelimited",
+                Some("20"),
+                "RateLimited(RateLimitingSampler(20.0))",
+            ),
+            (
+                "parentbased_ratelimited",
+                Some("0.5"),
+                "ParentBased(RateLimited(RateLimitingSampler(0.5)))",
+            ),
+            (
+                "ratelimited",
+                None,
+                "RateLimited(RateLimitingSampler(100.0))",
+            ),
+            (
+                "ratelimited",
+                Some("invalid"),
+                "RateLimited(RateLimitingSampler(100.0))",
+            ),
+        ] {
+            let config = temp_env::with_vars(
+                [
+                    ("OTEL_TRACES_SAMPLER", Some(sampler)),
+                    ("OTEL_TRACES_SAMPLER_ARG", arg),
+                ],
+                Config::default,
+            );
+            assert_eq!(
+                format!("{:?}", config.sampler),
+                expected,
+                "{sampler} {arg:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_attribute_value_length_limit_from_env() {
+        for (general, span, expected) in [
+            (None, None, None),
+            (Some("10"), None, Some(10)),
+            (Some("10"), Some("20"), Some(20)),
+            (Some("invalid"), None, None),
+        ] {
+            let config = temp_env::with_vars(
+                [
+                    ("OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT", general),
+                    ("OTEL_SPAN_ATTRIBUTE_VALUE_LENGTH_LIMIT", span),
+                ],
+                Config::default,
+            );
+            assert_eq!(
+                config.span_limits.max_attribute_value_length, expected,
+                "{general:?} {span:?}"
+            );
+        }
+    }
+}
@@ -15,6 +15,8 @@ mod sampler;
 mod span;
 mod span_limit;
 mod span_processor;
+mod tail_sampling;
+mod threaded_span_processor;
 mod tracer;
 
 pub use config::{config, Config};
@@ -28,13 +30,20 @@ pub use id_generator::aws::XrayIdGenerator;
 pub use id_generator::{IdGenerator, RandomIdGenerator};
 pub use links::SpanLinks;
 pub use provider::{Builder, TracerProvider};
-pub use sampler::{Sampler, ShouldSample};
+pub use sampler::{
+    ParentState, RateLimitingSampler, RuleBasedSampler, Sampler, SamplingRule, ShouldSample,
+    StringMatcher,
+};
 pub use span::Span;
 pub use span_limit::SpanLimits;
 pub use span_processor::{
     BatchConfig, BatchConfigBuilder, BatchSpanProcessor, BatchSpanProcessorBuilder,
     SimpleSpanProcessor, SpanProcessor,
 };
+pub use tail_sampling::{
+    TailSamplingPolicy, TailSamplingSpanProcessor, TailSamplingSpanProcessorBuilder,
+};
+pub use threaded_span_processor::{ThreadedBatchSpanProcessor, ThreadedBatchSpanProcessorBuilder};
 pub use tracer::Tracer;
 
 #[cfg(feature = "jaeger_remote_sampler")]
@@ -52,7 +61,8 @@ mod tests {
     };
     use opentelemetry::testing::trace::TestSpan;
     use opentelemetry::trace::{
-        SamplingDecision, SamplingResult, SpanKind, Status, TraceContextExt, TraceState,
+        SamplingDecision, SamplingResult, SpanKind, Status, TraceContextExt, TraceResult,
+        TraceState,
     };
     use opentelemetry::{
         trace::{
@@ -327,4 +337,115 @@ mod tests {
         );
         assert_eq!(span.span_context().trace_state().get("foo"), Some("bar"));
     }
+
+    /// Redacts the `user.email` attribute and records the end of the span.
+    #[derive(Debug)]
+    struct RedactingProcessor;
+
+    impl SpanProcessor for RedactingProcessor {
+        fn on_start(&self, _span: &mut crate::trace::Span, _cx: &Context) {}
+
+        fn on_ending(&self, span: &mut crate::trace::Span) {
+            for kv in span.attributes_mut() {
+                if kv.key.as_str() == "user.email" {
+                    kv.value = "<redacted>".into();
+                }
+            }
+            span.retain_attributes(|kv| kv.key.as_str() != "user.password");
+            span.set_attribute(KeyValue::new("ended", true));
+            // Ending the span again while it is ending is ignored.
+            span.end();
+            assert!(span.is_recording());
+        }
+
+        fn on_end(&self, _span: crate::export::trace::SpanData) {}
+
+        fn force_flush(&self) -> TraceResult<()> {
+            Ok(())
+        }
+
+        fn shutdown(&mut self) -> TraceResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn on_ending_changes_are_seen_by_the_next_processors() {
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let provider = TracerProvider::builder()
+            .with_span_processor(RedactingProcessor)
+            .with_span_processor(SimpleSpanProcessor::new(Box::new(exporter.clone())))
+            .build();
+
+        let mut span = provider.tracer("test").start("span");
+        span.set_attribute(KeyValue::new("user.email", "user@example.com"));
+        span.set_attribute(KeyValue::new("user.password", "hunter2"));
+        span.end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            spans[0].attributes,
+            vec![
+                KeyValue::new("user.email", "<redacted>"),
+                KeyValue::new("ended", true)
+            ]
+        );
+        assert_eq!(spans[0].dropped_attributes_count, 1);
+    }
+
+    /// Writes long values and more attributes than allowed while the span is ending.
+    #[derive(Debug)]
+    struct OverflowingProcessor;
+
+    impl SpanProcessor for OverflowingProcessor {
+        fn on_start(&self, _span: &mut crate::trace::Span, _cx: &Context) {}
+
+        fn on_ending(&self, span: &mut crate::trace::Span) {
+            for kv in span.attributes_mut() {
+                kv.value = "a long value".into();
+            }
+            span.set_attribute(KeyValue::new("over_the_limit", "a long value"));
+        }
+
+        fn on_end(&self, _span: crate::export::trace::SpanData) {}
+
+        fn force_flush(&self) -> TraceResult<()> {
+            Ok(())
+        }
+
+        fn shutdown(&mut self) -> TraceResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn on_ending_changes_are_limited() {
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let provider = TracerProvider::builder()
+            .with_config(
+                config()
+                    .with_max_attributes_per_span(2)
+                    .with_max_attribute_value_length(4),
+            )
+            .with_span_processor(OverflowingProcessor)
+            .with_span_processor(SimpleSpanProcessor::new(Box::new(exporter.clone())))
+            .build();
+
+        let mut span = provider.tracer("test").start("span");
+        span.set_attribute(KeyValue::new("first", "a"));
+        span.set_attribute(KeyValue::new("second", "b"));
+        span.end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            spans[0].attributes,
+            vec![
+                KeyValue::new("first", "a lo"),
+                KeyValue::new("second", "a lo")
+            ]
+        );
+        assert_eq!(spans[0].dropped_attributes_count, 1);
+    }
 }
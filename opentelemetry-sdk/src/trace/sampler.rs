@@ -7,11 +7,15 @@ use opentelemetry::{
 
 #[cfg(feature = "jaeger_remote_sampler")]
 mod jaeger_remote;
+mod rate_limit;
+mod rule_based;
 
 #[cfg(feature = "jaeger_remote_sampler")]
 pub use jaeger_remote::{JaegerRemoteSampler, JaegerRemoteSamplerBuilder};
 #[cfg(feature = "jaeger_remote_sampler")]
 use opentelemetry_http::HttpClient;
+pub use rate_limit::RateLimitingSampler;
+pub use rule_based::{ParentState, RuleBasedSampler, SamplingRule, StringMatcher};
 
 /// The [`ShouldSample`] interface allows implementations to provide samplers
 /// which will return a sampling [`SamplingResult`] based on information that
@@ -123,6 +127,11 @@ pub enum Sampler {
     /// *Note:* If this is used then all Spans in a trace will become sampled assuming that the
     /// first span is sampled as it is based on the `trace_id` not the `span_id`
     TraceIdRatioBased(f64),
+    /// Sample at most a given number of traces per second, see [`RateLimitingSampler`].
+    ///
+    /// Typically used as the root sampler of [`Sampler::ParentBased`], so that the
+    /// rate only applies to root spans.
+    RateLimited(RateLimitingSampler),
     /// Jaeger remote sampler supports any remote service that implemented the jaeger remote sampler protocol.
     /// The proto definition can be found [here](https://github.com/jaegertracing/jaeger-idl/blob/main/proto/api_v2/sampling.proto)
     ///
@@ -205,6 +214,12 @@ impl ShouldSample for Sampler {
                 ),
             // Probabilistically sample the trace.
             Sampler::TraceIdRatioBased(prob) => sample_based_on_probability(prob, trace_id),
+            // Sample up to the rate of the sampler.
+            Sampler::RateLimited(rate_limited) => {
+                rate_limited
+                    .should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+                    .decision
+            }
             #[cfg(feature = "jaeger_remote_sampler")]
             Sampler::JaegerRemote(remote_sampler) => {
                 remote_sampler
@@ -293,6 +308,12 @@ mod tests {
             ("sampled_parent_or_else_with_always_off", Sampler::ParentBased(Box::new(Sampler::AlwaysOff)), 1.0, true, true),
             ("sampled_parent_or_else_with_ratio_.25", Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(0.25))), 1.0, true, true),
 
+            // Rate limited samplers only apply their rate to root spans when parent based
+            ("rate_limited_0", Sampler::RateLimited(RateLimitingSampler::new(0.0)), 0.0, false, false),
+            ("delegate_to_rate_limited_0", Sampler::ParentBased(Box::new(Sampler::RateLimited(RateLimitingSampler::new(0.0)))), 0.0, false, false),
+            ("unsampled_parent_or_else_with_rate_limited", Sampler::ParentBased(Box::new(Sampler::RateLimited(RateLimitingSampler::new(1_000_000.0)))), 0.0, true, false),
+            ("sampled_parent_or_else_with_rate_limited_0", Sampler::ParentBased(Box::new(Sampler::RateLimited(RateLimitingSampler::new(0.0)))), 1.0, true, true),
+
             // Spans with a sampled parent, but when using the NeverSample Sampler, aren't sampled
             ("sampled_parent_span_with_never_sample", Sampler::AlwaysOff, 0.0, true, true),
         ]
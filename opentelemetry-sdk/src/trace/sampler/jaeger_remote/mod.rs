@@ -1,4 +1,3 @@
-mod rate_limit;
 mod remote;
 mod sampler;
 mod sampling_strategy;
@@ -0,0 +1,264 @@
+use crate::trace::ShouldSample;
+use opentelemetry::trace::{
+    Link, SamplingDecision, SamplingResult, SpanKind, TraceContextExt, TraceError, TraceId,
+    TraceState,
+};
+use opentelemetry::{Context, KeyValue};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// A sampler sampling at most a given number of traces per second.
+///
+/// The rate is enforced with a leaky bucket, refilled continuously and holding
+/// up to one second of traces, so that short bursts are sampled up to the rate.
+///
+/// Every span the sampler is asked about is counted, so it is usually the root
+/// sampler of a [`Sampler::ParentBased`] sampler, which samples the children
+/// of sampled spans regardless of the rate:
+///
+/// ```
+/// use opentelemetry_sdk::trace::{RateLimitingSampler, Sampler};
+///
+/// // sample up to 100 traces per second
+/// let sampler = Sampler::ParentBased(Box::new(Sampler::RateLimited(
+///     RateLimitingSampler::new(100.0),
+/// )));
+/// ```
+///
+/// The clones of a sampler share the same bucket.
+///
+/// [`Sampler::ParentBased`]: crate::trace::Sampler::ParentBased
+#[derive(Clone)]
+pub struct RateLimitingSampler {
+    traces_per_second: f64,
+    bucket: Arc<Mutex<LeakyBucket>>,
+}
+
+impl RateLimitingSampler {
+    /// Create a sampler sampling at most `traces_per_second` traces per second.
+    pub fn new(traces_per_second: f64) -> Self {
+        let traces_per_second = traces_per_second.max(0.0);
+        // a bucket holding less than one trace would never sample
+        let bucket_size = if traces_per_second > 0.0 {
+            traces_per_second.max(1.0)
+        } else {
+            0.0
+        };
+        RateLimitingSampler {
+            traces_per_second,
+            bucket: Arc::new(Mutex::new(LeakyBucket::new(bucket_size, traces_per_second))),
+        }
+    }
+
+    /// The maximum number of traces sampled per second.
+    pub fn traces_per_second(&self) -> f64 {
+        self.traces_per_second
+    }
+}
+
+impl fmt::Debug for RateLimitingSampler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RateLimitingSampler")
+            .field(&self.traces_per_second)
+            .finish()
+    }
+}
+
+impl ShouldSample for RateLimitingSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        _trace_id: TraceId,
+        _name: &str,
+        _span_kind: &SpanKind,
+        _attributes: &[KeyValue],
+        _links: &[Link],
+    ) -> SamplingResult {
+        let sampled = self
+            .bucket
+            .lock()
+            .map(|mut bucket| bucket.should_sample())
+            .unwrap_or(false);
+        SamplingResult {
+            decision: if sampled {
+                SamplingDecision::RecordAndSample
+            } else {
+                SamplingDecision::Drop
+            },
+            attributes: Vec::new(),
+            trace_state: match parent_context {
+                Some(ctx) => ctx.span().span_context().trace_state().clone(),
+                None => TraceState::default(),
+            },
+        }
+    }
+}
+
+// leaky bucket based rate limit
+// should be Send+Sync
+pub(crate) struct LeakyBucket {
+    span_per_sec: f64,
+    available: f64,
+    bucket_size: f64,
+    last_time: SystemTime,
+}
+
+impl LeakyBucket {
+    pub(crate) fn new(bucket_size: f64, span_per_sec: f64) -> LeakyBucket {
+        LeakyBucket {
+            span_per_sec,
+            available: bucket_size,
+            bucket_size,
+            last_time: opentelemetry::time::now(),
+        }
+    }
+
+    #[cfg(feature = "jaeger_remote_sampler")]
+    pub(crate) fn update(&mut self, span_per_sec: f64) {
+        self.span_per_sec = span_per_sec;
+    }
+
+    pub(crate) fn should_sample(&mut self) -> bool {
+        self.check_availability(opentelemetry::time::now)
+    }
+
+    fn check_availability<F>(&mut self, now: F) -> bool
+    where
+        F: Fn() -> SystemTime,
+    {
+        if self.available >= 1.0 {
+            self.available -= 1.0;
+            true
+        } else {
+            let cur_time = now();
+            let elapsed = cur_time.duration_since(self.last_time);
+            match elapsed {
+                Ok(dur) => {
+                    self.last_time = cur_time;
+                    self.available = f64::min(
+                        dur.as_secs_f64() * self.span_per_sec + self.available,
+                        self.bucket_size,
+                    );
+
+                    if self.available >= 1.0 {
+                        self.available -= 1.0;
+                        true
+                    } else {
+                        false
+                    }
+                }
+                Err(_) => {
+                    opentelemetry::global::handle_error(TraceError::Other(
+                        "rate limiting sampler gets rewinded timestamp".into(),
+                    ));
+                    true
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LeakyBucket, RateLimitingSampler};
+    use crate::trace::ShouldSample;
+    use opentelemetry::trace::{SamplingDecision, SpanKind, TraceId};
+    use std::ops::{Add, Sub};
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_leaky_bucket() {
+        // maximum bucket size 2, add 1 allowance every 10 seconds
+        let mut leaky_bucket = LeakyBucket::new(2.0, 0.1);
+        let current_time = SystemTime::now();
+        leaky_bucket.last_time = current_time;
+
+        let test_cases = vec![
+            (0, vec![true, true, false]),
+            (1, vec![false]),
+            (5, vec![false]),
+            (10, vec![true, false]),
+            (15, vec![false]),
+            (20, vec![true, false]),
+            (60, vec![true, true, false]), // maximum allowance is 2
+        ];
+
+        for (elapsed_sec, cases) in test_cases.into_iter() {
+            for should_pass in cases {
+                assert_eq!(
+                    should_pass,
+                    leaky_bucket.check_availability(|| {
+                        current_time.add(Duration::from_secs(elapsed_sec))
+                    })
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn test_rewind_clock_should_pass() {
+        let mut leaky_bucket = LeakyBucket::new(2.0, 0.1);
+        let current_time = SystemTime::now();
+        leaky_bucket.last_time = current_time;
+
+        assert!(leaky_bucket.check_availability(|| { current_time.sub(Duration::from_secs(10)) }))
+    }
+
+    #[test]
+    fn test_leaky_bucket_refills_within_a_second() {
+        // add 1 allowance every 100 milliseconds
+        let mut leaky_bucket = LeakyBucket::new(1.0, 10.0);
+        let current_time = SystemTime::now();
+        leaky_bucket.last_time = current_time;
+
+        let test_cases = vec![
+            (0, true),
+            (50, false),
+            (100, true),
+            (150, false),
+            (250, true),
+        ];
+        for (elapsed_ms, expected) in test_cases {
+            assert_eq!(
+                leaky_bucket
+                    .check_availability(|| current_time.add(Duration::from_millis(elapsed_ms))),
+                expected,
+                "at {elapsed_ms}ms"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rate_limiting_sampler() {
+        let sampler = RateLimitingSampler::new(2.0);
+        let clone = sampler.clone();
+        let decisions = (0..4)
+            .map(|i| {
+                let sampler = if i % 2 == 0 { &sampler } else { &clone };
+                sampler
+                    .should_sample(
+                        None,
+                        TraceId::from(i + 1),
+                        "span",
+                        &SpanKind::Internal,
+                        &[],
+                        &[],
+                    )
+                    .decision
+            })
+            .collect::<Vec<_>>();
+        // the bucket holds one second of traces, and is shared by the clones
+        assert_eq!(
+            decisions[..2],
+            [
+                SamplingDecision::RecordAndSample,
+                SamplingDecision::RecordAndSample
+            ]
+        );
+        assert!(decisions[2..]
+            .iter()
+            .all(|decision| *decision == SamplingDecision::Drop));
+        assert_eq!(sampler.traces_per_second(), 2.0);
+    }
+}
@@ -0,0 +1,446 @@
+use crate::trace::ShouldSample;
+use opentelemetry::trace::{Link, SamplingResult, SpanKind, TraceContextExt, TraceError, TraceId};
+use opentelemetry::{Context, Key, KeyValue};
+use std::fmt;
+
+/// Matches the name of a span or the value of an attribute in a [`SamplingRule`].
+#[derive(Clone)]
+pub struct StringMatcher(Matcher);
+
+#[derive(Clone)]
+enum Matcher {
+    Exact(String),
+    Glob(glob::Pattern),
+    #[cfg(feature = "sampler_regex")]
+    Regex(regex::Regex),
+}
+
+impl StringMatcher {
+    /// Matches strings equal to `value`.
+    pub fn exact<T: Into<String>>(value: T) -> Self {
+        StringMatcher(Matcher::Exact(value.into()))
+    }
+
+    /// Matches strings with a Unix shell style `pattern`, where `?` matches any
+    /// single character, `*` any sequence of characters and `[...]` any
+    /// character inside the brackets.
+    ///
+    /// Returns an error if the pattern is invalid.
+    pub fn glob(pattern: &str) -> Result<Self, TraceError> {
+        let pattern = glob::Pattern::new(pattern)
+            .map_err(|err| TraceError::from(format!("invalid glob pattern: {err}")))?;
+        Ok(StringMatcher(Matcher::Glob(pattern)))
+    }
+
+    /// Matches strings containing a match of the regular expression `regex`.
+    ///
+    /// The expression isn't anchored, use `^` and `$` to match whole strings.
+    ///
+    /// Returns an error if the expression is invalid.
+    #[cfg(feature = "sampler_regex")]
+    pub fn regex(regex: &str) -> Result<Self, TraceError> {
+        let regex = regex::Regex::new(regex)
+            .map_err(|err| TraceError::from(format!("invalid regular expression: {err}")))?;
+        Ok(StringMatcher(Matcher::Regex(regex)))
+    }
+
+    /// Returns `true` if `value` is matched.
+    pub fn matches(&self, value: &str) -> bool {
+        match &self.0 {
+            Matcher::Exact(expected) => expected == value,
+            Matcher::Glob(pattern) => pattern.matches(value),
+            #[cfg(feature = "sampler_regex")]
+            Matcher::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+impl fmt::Debug for StringMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Matcher::Exact(value) => f.debug_tuple("Exact").field(value).finish(),
+            Matcher::Glob(pattern) => f.debug_tuple("Glob").field(&pattern.as_str()).finish(),
+            #[cfg(feature = "sampler_regex")]
+            Matcher::Regex(regex) => f.debug_tuple("Regex").field(&regex.as_str()).finish(),
+        }
+    }
+}
+
+/// The state of the parent of a span, matched by a [`SamplingRule`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParentState {
+    /// The span is a root span.
+    None,
+    /// The parent span is sampled.
+    Sampled,
+    /// The parent span isn't sampled.
+    NotSampled,
+}
+
+impl ParentState {
+    fn of(parent_context: Option<&Context>) -> Self {
+        match parent_context.filter(|cx| cx.has_active_span()) {
+            Some(cx) if cx.span().span_context().is_sampled() => ParentState::Sampled,
+            Some(_) => ParentState::NotSampled,
+            None => ParentState::None,
+        }
+    }
+}
+
+/// A rule of a [`RuleBasedSampler`], delegating the sampling decision of the
+/// spans it matches to a sampler.
+///
+/// A span is matched if all of its conditions are met. A rule without any
+/// condition matches all spans.
+#[derive(Clone, Debug)]
+pub struct SamplingRule {
+    span_name: Option<StringMatcher>,
+    span_kind: Option<SpanKind>,
+    attributes: Vec<(Key, StringMatcher)>,
+    parent: Option<ParentState>,
+    sampler: Box<dyn ShouldSample>,
+}
+
+impl SamplingRule {
+    /// Create a rule delegating to `sampler`.
+    pub fn new<T: ShouldSample + 'static>(sampler: T) -> Self {
+        SamplingRule {
+            span_name: None,
+            span_kind: None,
+            attributes: Vec::new(),
+            parent: None,
+            sampler: Box::new(sampler),
+        }
+    }
+
+    /// Only match spans whose name is matched by `matcher`.
+    pub fn with_span_name(mut self, matcher: StringMatcher) -> Self {
+        self.span_name = Some(matcher);
+        self
+    }
+
+    /// Only match spans of the given kind.
+    pub fn with_span_kind(mut self, span_kind: SpanKind) -> Self {
+        self.span_kind = Some(span_kind);
+        self
+    }
+
+    /// Only match spans with the attribute `key`, whose value converted to a
+    /// string is matched by `matcher`.
+    ///
+    /// Only the attributes given when the span is started are available.
+    pub fn with_attribute<K: Into<Key>>(mut self, key: K, matcher: StringMatcher) -> Self {
+        self.attributes.push((key.into(), matcher));
+        self
+    }
+
+    /// Only match spans whose parent is in the given state.
+    pub fn with_parent(mut self, parent: ParentState) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    fn matches(
+        &self,
+        parent: ParentState,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+    ) -> bool {
+        self.parent.map_or(true, |expected| expected == parent)
+            && self
+                .span_kind
+                .as_ref()
+                .map_or(true, |kind| kind == span_kind)
+            && self
+                .span_name
+                .as_ref()
+                .map_or(true, |matcher| matcher.matches(name))
+            && self.attributes.iter().all(|(key, matcher)| {
+                attributes
+                    .iter()
+                    .any(|kv| &kv.key == key && matcher.matches(&kv.value.as_str()))
+            })
+    }
+}
+
+/// A sampler delegating the sampling decision to the sampler of the first
+/// [`SamplingRule`] matching the span, or to a default sampler if none match.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::trace::SpanKind;
+/// use opentelemetry_sdk::trace::{RuleBasedSampler, Sampler, SamplingRule, StringMatcher};
+///
+/// # fn main() -> Result<(), opentelemetry::trace::TraceError> {
+/// let sampler = RuleBasedSampler::new(Sampler::TraceIdRatioBased(0.1))
+///     // drop the health checks
+///     .with_rule(
+///         SamplingRule::new(Sampler::AlwaysOff)
+///             .with_span_kind(SpanKind::Server)
+///             .with_attribute("url.path", StringMatcher::glob("/health*")?),
+///     )
+///     // always sample the checkouts
+///     .with_rule(
+///         SamplingRule::new(Sampler::AlwaysOn)
+///             .with_attribute("url.path", StringMatcher::exact("/checkout")),
+///     );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RuleBasedSampler {
+    rules: Vec<SamplingRule>,
+    default: Box<dyn ShouldSample>,
+}
+
+impl RuleBasedSampler {
+    /// Create a sampler delegating to `default` the spans no rule matches.
+    pub fn new<T: ShouldSample + 'static>(default: T) -> Self {
+        RuleBasedSampler {
+            rules: Vec::new(),
+            default: Box::new(default),
+        }
+    }
+
+    /// Add a rule, evaluated after the rules already added.
+    pub fn with_rule(mut self, rule: SamplingRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+}
+
+impl ShouldSample for RuleBasedSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        let parent = ParentState::of(parent_context);
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(parent, name, span_kind, attributes))
+            .map_or(&self.default, |rule| &rule.sampler)
+            .should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+#[cfg(all(test, feature = "testing", feature = "trace"))]
+mod tests {
+    use super::{ParentState, RuleBasedSampler, SamplingRule, StringMatcher};
+    use crate::testing::trace::TestSpan;
+    use crate::trace::{Sampler, ShouldSample};
+    use opentelemetry::trace::{
+        SamplingDecision, SpanContext, SpanId, SpanKind, TraceContextExt, TraceFlags, TraceId,
+        TraceState,
+    };
+    use opentelemetry::{Context, KeyValue};
+
+    fn decision(
+        sampler: &RuleBasedSampler,
+        parent_context: Option<&Context>,
+        name: &str,
+        span_kind: SpanKind,
+        attributes: &[KeyValue],
+    ) -> SamplingDecision {
+        sampler
+            .should_sample(
+                parent_context,
+                TraceId::from_u128(1),
+                name,
+                &span_kind,
+                attributes,
+                &[],
+            )
+            .decision
+    }
+
+    fn parent(sampled: bool) -> Context {
+        let trace_flags = if sampled {
+            TraceFlags::SAMPLED
+        } else {
+            TraceFlags::default()
+        };
+        Context::current_with_span(TestSpan(SpanContext::new(
+            TraceId::from_u128(1),
+            SpanId::from_u64(1),
+            trace_flags,
+            false,
+            TraceState::default(),
+        )))
+    }
+
+    #[test]
+    fn test_string_matcher() {
+        assert!(StringMatcher::exact("GET /").matches("GET /"));
+        assert!(!StringMatcher::exact("GET /").matches("GET /users"));
+
+        let glob = StringMatcher::glob("GET /users/*").unwrap();
+        assert!(glob.matches("GET /users/42"));
+        assert!(!glob.matches("POST /users/42"));
+        assert!(StringMatcher::glob("[").is_err());
+    }
+
+    #[cfg(feature = "sampler_regex")]
+    #[test]
+    fn test_regex_matcher() {
+        let regex = StringMatcher::regex(r"^/users/\d+$").unwrap();
+        assert!(regex.matches("/users/42"));
+        assert!(!regex.matches("/users/me"));
+        assert!(StringMatcher::regex("(").is_err());
+    }
+
+    #[test]
+    fn test_first_matching_rule_decides() {
+        let sampler = RuleBasedSampler::new(Sampler::AlwaysOff)
+            .with_rule(
+                SamplingRule::new(Sampler::AlwaysOff)
+                    .with_span_kind(SpanKind::Server)
+                    .with_attribute("url.path", StringMatcher::glob("/health*").unwrap()),
+            )
+            .with_rule(
+                SamplingRule::new(Sampler::AlwaysOn)
+                    .with_attribute("url.path", StringMatcher::exact("/checkout")),
+            )
+            .with_rule(
+                SamplingRule::new(Sampler::AlwaysOn)
+                    .with_span_name(StringMatcher::glob("GET *").unwrap()),
+            );
+
+        for (name, span_kind, path, expected) in [
+            (
+                "GET /healthz",
+                SpanKind::Server,
+                "/healthz",
+                SamplingDecision::Drop,
+            ),
+            (
+                "GET /healthz",
+                SpanKind::Client,
+                "/healthz",
+                SamplingDecision::RecordAndSample,
+            ),
+            (
+                "POST /checkout",
+                SpanKind::Server,
+                "/checkout",
+                SamplingDecision::RecordAndSample,
+            ),
+            (
+                "GET /cart",
+                SpanKind::Server,
+                "/cart",
+                SamplingDecision::RecordAndSample,
+            ),
+            (
+                "POST /cart",
+                SpanKind::Server,
+                "/cart",
+                SamplingDecision::Drop,
+            ),
+        ] {
+            assert_eq!(
+                decision(
+                    &sampler,
+                    None,
+                    name,
+                    span_kind.clone(),
+                    &[KeyValue::new("url.path", path)]
+                ),
+                expected,
+                "{name} {span_kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_attribute_values_are_matched_as_strings() {
+        let sampler = RuleBasedSampler::new(Sampler::AlwaysOff).with_rule(
+            SamplingRule::new(Sampler::AlwaysOn).with_attribute(
+                "http.response.status_code",
+                StringMatcher::glob("5*").unwrap(),
+            ),
+        );
+
+        let matched = [KeyValue::new("http.response.status_code", 503)];
+        let not_matched = [KeyValue::new("http.response.status_code", 200)];
+        assert_eq!(
+            decision(&sampler, None, "span", SpanKind::Internal, &matched),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(
+            decision(&sampler, None, "span", SpanKind::Internal, &not_matched),
+            SamplingDecision::Drop
+        );
+        assert_eq!(
+            decision(&sampler, None, "span", SpanKind::Internal, &[]),
+            SamplingDecision::Drop
+        );
+    }
+
+    #[test]
+    fn test_parent_state() {
+        let sampler = RuleBasedSampler::new(Sampler::AlwaysOff)
+            .with_rule(SamplingRule::new(Sampler::AlwaysOn).with_parent(ParentState::Sampled))
+            .with_rule(SamplingRule::new(Sampler::AlwaysOn).with_parent(ParentState::None));
+
+        let sampled = parent(true);
+        let not_sampled = parent(false);
+        assert_eq!(
+            decision(&sampler, Some(&sampled), "span", SpanKind::Internal, &[]),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(
+            decision(
+                &sampler,
+                Some(&not_sampled),
+                "span",
+                SpanKind::Internal,
+                &[]
+            ),
+            SamplingDecision::Drop
+        );
+        assert_eq!(
+            decision(&sampler, None, "span", SpanKind::Internal, &[]),
+            SamplingDecision::RecordAndSample
+        );
+        assert_eq!(
+            decision(
+                &sampler,
+                Some(&Context::new()),
+                "span",
+                SpanKind::Internal,
+                &[]
+            ),
+            SamplingDecision::RecordAndSample
+        );
+    }
+
+    #[test]
+    fn test_trace_state_of_delegate() {
+        let sampler = RuleBasedSampler::new(Sampler::AlwaysOn);
+        let trace_state = TraceState::from_key_value([("vendor", "value")]).unwrap();
+        let parent = Context::current_with_span(TestSpan(SpanContext::new(
+            TraceId::from_u128(1),
+            SpanId::from_u64(1),
+            TraceFlags::SAMPLED,
+            false,
+            trace_state.clone(),
+        )));
+        let result = sampler.should_sample(
+            Some(&parent),
+            TraceId::from_u128(1),
+            "span",
+            &SpanKind::Internal,
+            &[],
+            &[],
+        );
+        assert_eq!(result.trace_state, trace_state);
+    }
+}
@@ -22,6 +22,8 @@ pub struct Span {
     data: Option<SpanData>,
     tracer: crate::trace::Tracer,
     span_limits: SpanLimits,
+    /// Whether the span processors are being notified that the span is ending.
+    ending: bool,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -61,6 +63,7 @@ impl Span {
             data,
             tracer,
             span_limits: span_limit,
+            ending: false,
         }
     }
 
@@ -72,6 +75,41 @@ impl Span {
         self.data.as_mut().map(f)
     }
 
+    /// The attributes of the span, empty once the span has ended.
+    pub fn attributes(&self) -> &[KeyValue] {
+        self.data
+            .as_ref()
+            .map_or(&[], |data| data.attributes.as_slice())
+    }
+
+    /// Mutable access to the attributes of the span, to update their values
+    /// in place, like from [`SpanProcessor::on_ending`].
+    ///
+    /// The values are cut to [`SpanLimits::max_attribute_value_length`] once
+    /// the span has ended, as the ones set with `set_attribute`.
+    ///
+    /// [`SpanProcessor::on_ending`]: crate::trace::SpanProcessor::on_ending
+    pub fn attributes_mut(&mut self) -> &mut [KeyValue] {
+        match self.data.as_mut() {
+            Some(data) => data.attributes.as_mut_slice(),
+            None => &mut [],
+        }
+    }
+
+    /// Removes the attributes of the span for which `f` returns `false`,
+    /// counting them as dropped.
+    pub fn retain_attributes<F>(&mut self, f: F)
+    where
+        F: FnMut(&KeyValue) -> bool,
+    {
+        self.with_data(|data| {
+            let len = data.attributes.len();
+            data.attributes.retain(f);
+            let removed = (len - data.attributes.len()) as u32;
+            data.dropped_attributes_count = data.dropped_attributes_count.saturating_add(removed);
+        });
+    }
+
     /// Convert information in this span into `exporter::trace::SpanData`.
     /// This function copies all data from the current span, which will create a
     /// overhead.
@@ -99,13 +137,15 @@ impl opentelemetry::trace::Span for Span {
     ) where
         T: Into<Cow<'static, str>>,
     {
-        let span_events_limit = self.span_limits.max_events_per_span as usize;
-        let event_attributes_limit = self.span_limits.max_attributes_per_event as usize;
+        let span_limits = self.span_limits;
+        let span_events_limit = span_limits.max_events_per_span as usize;
+        let event_attributes_limit = span_limits.max_attributes_per_event as usize;
         self.with_data(|data| {
             if data.events.len() < span_events_limit {
                 let dropped_attributes_count =
                     attributes.len().saturating_sub(event_attributes_limit);
                 attributes.truncate(event_attributes_limit);
+                span_limits.truncate_attribute_values(&mut attributes);
 
                 data.events.add_event(Event::new(
                     name,
@@ -136,10 +176,11 @@ impl opentelemetry::trace::Span for Span {
     /// Note that the OpenTelemetry project documents certain ["standard
     /// attributes"](https://github.com/open-telemetry/opentelemetry-specification/tree/v0.5.0/specification/trace/semantic_conventions/README.md)
     /// that have prescribed semantic meanings.
-    fn set_attribute(&mut self, attribute: KeyValue) {
-        let span_attribute_limit = self.span_limits.max_attributes_per_span as usize;
+    fn set_attribute(&mut self, mut attribute: KeyValue) {
+        let span_limits = self.span_limits;
         self.with_data(|data| {
-            if data.attributes.len() < span_attribute_limit {
+            if data.attributes.len() < span_limits.max_attributes_per_span as usize {
+                span_limits.truncate_attribute_values(std::slice::from_mut(&mut attribute));
                 data.attributes.push(attribute);
             } else {
                 data.dropped_attributes_count += 1;
@@ -173,14 +214,16 @@ impl opentelemetry::trace::Span for Span {
     /// Add `Link` to this `Span`
     ///
     fn add_link(&mut self, span_context: SpanContext, attributes: Vec<KeyValue>) {
-        let span_links_limit = self.span_limits.max_links_per_span as usize;
-        let link_attributes_limit = self.span_limits.max_attributes_per_link as usize;
+        let span_limits = self.span_limits;
+        let span_links_limit = span_limits.max_links_per_span as usize;
+        let link_attributes_limit = span_limits.max_attributes_per_link as usize;
         self.with_data(|data| {
             if data.links.links.len() < span_links_limit {
                 let dropped_attributes_count =
                     attributes.len().saturating_sub(link_attributes_limit);
                 let mut attributes = attributes;
                 attributes.truncate(link_attributes_limit);
+                span_limits.truncate_attribute_values(&mut attributes);
                 data.links.add_link(Link::new(
                     span_context,
                     attributes,
@@ -200,24 +243,45 @@ impl opentelemetry::trace::Span for Span {
 
 impl Span {
     fn ensure_ended_and_exported(&mut self, timestamp: Option<SystemTime>) {
-        // skip if data has already been exported
-        let mut data = match self.data.take() {
-            Some(data) => data,
-            None => return,
-        };
+        // skip if data has already been exported, or if the span is ended by
+        // a processor while ending
+        if self.data.is_none() || self.ending {
+            return;
+        }
 
         // skip if provider has been shut down
         let provider = match self.tracer.provider() {
             Some(provider) => provider,
-            None => return,
+            None => {
+                self.data = None;
+                return;
+            }
         };
 
         // ensure end time is set via explicit end or implicitly on drop
-        if let Some(timestamp) = timestamp {
-            data.end_time = timestamp;
-        } else if data.end_time == data.start_time {
-            data.end_time = opentelemetry::time::now();
+        self.with_data(|data| {
+            if let Some(timestamp) = timestamp {
+                data.end_time = timestamp;
+            } else if data.end_time == data.start_time {
+                data.end_time = opentelemetry::time::now();
+            }
+        });
+
+        // let the processors update the span, in order, before it is read-only
+        self.ending = true;
+        for processor in provider.span_processors() {
+            processor.on_ending(self);
         }
+        self.ending = false;
+
+        // the values updated through `attributes_mut` are not truncated yet
+        let span_limits = self.span_limits;
+        self.with_data(|data| span_limits.truncate_attribute_values(&mut data.attributes));
+
+        let data = match self.data.take() {
+            Some(data) => data,
+            None => return,
+        };
 
         match provider.span_processors().as_slice() {
             [] => {}
@@ -285,6 +349,7 @@ mod tests {
     use crate::trace::{SpanEvents, SpanLinks};
     use opentelemetry::trace::{self, SpanBuilder, TraceFlags, TraceId, Tracer};
     use opentelemetry::{trace::Span as _, trace::TracerProvider};
+    use opentelemetry::{StringValue, Value};
     use std::time::Duration;
     use std::vec;
 
@@ -573,6 +638,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn exceed_attribute_value_length_limit() {
+        let provider = crate::trace::TracerProvider::builder()
+            .with_config(crate::trace::config().with_max_attribute_value_length(3))
+            .build();
+        let tracer = provider.tracer("opentelemetry-test");
+
+        let span_builder = SpanBuilder::from_name("test_span")
+            .with_attributes(vec![KeyValue::new("multibyte", "héllo")])
+            .with_links(vec![Link::new(
+                SpanContext::new(
+                    TraceId::from_u128(12),
+                    SpanId::from_u64(12),
+                    TraceFlags::default(),
+                    false,
+                    Default::default(),
+                ),
+                vec![KeyValue::new("link", "abcdef")],
+                0,
+            )]);
+        let mut span = tracer.build(span_builder);
+        span.set_attribute(KeyValue::new("short", "abc"));
+        span.set_attribute(KeyValue::new("int", 123456));
+        span.set_attribute(KeyValue::new(
+            "array",
+            Value::Array(vec![StringValue::from("abcd"), StringValue::from("ab")].into()),
+        ));
+        span.add_event("event", vec![KeyValue::new("event", "€€€€")]);
+
+        let actual_span = span
+            .data
+            .clone()
+            .expect("span data should not be empty as we already set it before");
+        assert_eq!(
+            actual_span.attributes,
+            vec![
+                KeyValue::new("multibyte", "hél"),
+                KeyValue::new("short", "abc"),
+                KeyValue::new("int", 123456),
+                KeyValue::new(
+                    "array",
+                    Value::Array(vec![StringValue::from("abc"), StringValue::from("ab")].into()),
+                ),
+            ]
+        );
+        assert_eq!(
+            actual_span.links.links[0].attributes,
+            vec![KeyValue::new("link", "abc")]
+        );
+        assert_eq!(
+            actual_span.events.events[0].attributes,
+            vec![KeyValue::new("event", "€€€")]
+        );
+    }
+
     #[test]
     fn exceed_event_attributes_limit() {
         let exporter = NoopSpanExporter::new();
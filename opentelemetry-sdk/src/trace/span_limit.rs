@@ -9,9 +9,14 @@
 ///  - Maximum allowed span link count
 ///  - Maximum allowed attribute per span event count
 ///  - Maximum allowed attribute per span link count
+///  - Maximum allowed length of the attribute values of spans, events and links
 ///
 /// If the limit has been breached. The attributes, events or links will be dropped based on their
-/// index in the collection. The one added to collections later will be dropped first.
+/// index in the collection. The one added to collections later will be dropped first. The string
+/// values, and the strings of the string array values, longer than the length limit are truncated
+/// to their first characters.
+use crate::util::truncated;
+use opentelemetry::{Array, KeyValue, StringValue, Value};
 
 pub(crate) const DEFAULT_MAX_EVENT_PER_SPAN: u32 = 128;
 pub(crate) const DEFAULT_MAX_ATTRIBUTES_PER_SPAN: u32 = 128;
@@ -21,6 +26,7 @@ pub(crate) const DEFAULT_MAX_ATTRIBUTES_PER_LINK: u32 = 128;
 
 /// Span limit configuration to keep attributes, events and links to a span in a reasonable number.
 #[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
 pub struct SpanLimits {
     /// The max events that can be added to a `Span`.
     pub max_events_per_span: u32,
@@ -32,6 +38,9 @@ pub struct SpanLimits {
     pub max_attributes_per_event: u32,
     /// The max attributes that can be added into a `Link`
     pub max_attributes_per_link: u32,
+    /// The max length, in characters, of the string attribute values of a
+    /// `Span`, its `Event`s and its `Link`s. Unlimited if `None`.
+    pub max_attribute_value_length: Option<u32>,
 }
 
 impl Default for SpanLimits {
@@ -42,6 +51,36 @@ impl Default for SpanLimits {
             max_links_per_span: DEFAULT_MAX_LINKS_PER_SPAN,
             max_attributes_per_link: DEFAULT_MAX_ATTRIBUTES_PER_LINK,
             max_attributes_per_event: DEFAULT_MAX_ATTRIBUTES_PER_EVENT,
+            max_attribute_value_length: None,
         }
     }
 }
+
+impl SpanLimits {
+    /// Truncates the values of `attributes` longer than the length limit.
+    pub(crate) fn truncate_attribute_values(&self, attributes: &mut [KeyValue]) {
+        if let Some(max_length) = self.max_attribute_value_length {
+            for attribute in attributes {
+                truncate_value(&mut attribute.value, max_length as usize);
+            }
+        }
+    }
+}
+
+fn truncate_value(value: &mut Value, max_length: usize) {
+    match value {
+        Value::String(value) => truncate_string(value, max_length),
+        Value::Array(Array::String(values)) => {
+            for value in values {
+                truncate_string(value, max_length);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn truncate_string(value: &mut StringValue, max_length: usize) {
+    if let Some(prefix) = truncated(value.as_str(), max_length).map(str::to_owned) {
+        *value = prefix.into();
+    }
+}
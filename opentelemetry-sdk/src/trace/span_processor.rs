@@ -36,6 +36,7 @@
 
 use crate::export::trace::{ExportResult, SpanData, SpanExporter};
 use crate::runtime::{RuntimeChannel, TrySend};
+use crate::self_observability::ProcessorMetrics;
 use crate::trace::Span;
 use futures_channel::oneshot;
 use futures_util::{
@@ -51,6 +52,7 @@ use opentelemetry::{
 };
 use std::cmp::min;
 use std::sync::Mutex;
+use std::time::Instant;
 use std::{env, fmt, str::FromStr, time::Duration};
 
 /// Delay interval between two consecutive exports.
@@ -83,6 +85,15 @@ pub trait SpanProcessor: Send + Sync + std::fmt::Debug {
     /// synchronously on the thread that started the span, therefore it should
     /// not block or throw exceptions.
     fn on_start(&self, span: &mut Span, cx: &Context);
+    /// `on_ending` is called when a `Span` is ending, after its end timestamp
+    /// is set but while it can still be modified, before `on_end` is called on
+    /// any processor. The processors are called in the order they were
+    /// registered, each one seeing the changes of the previous ones, which
+    /// makes it the place to redact or enrich spans for all the processors
+    /// and exporters. This method is called synchronously within the
+    /// `Span::end` API, therefore it should not block or throw an exception.
+    /// Ending the span from this method has no effect.
+    fn on_ending(&self, _span: &mut Span) {}
     /// `on_end` is called after a `Span` is ended (i.e., the end timestamp is
     /// already set). This method is called synchronously within the `Span::end`
     /// API, therefore it should not block or throw an exception.
@@ -211,6 +222,7 @@ impl SpanProcessor for SimpleSpanProcessor {
 /// [`async-std`]: https://async.rs
 pub struct BatchSpanProcessor<R: RuntimeChannel> {
     message_sender: R::Sender<BatchMessage>,
+    metrics: ProcessorMetrics,
 }
 
 impl<R: RuntimeChannel> fmt::Debug for BatchSpanProcessor<R> {
@@ -233,8 +245,12 @@ impl<R: RuntimeChannel> SpanProcessor for BatchSpanProcessor<R> {
 
         let result = self.message_sender.try_send(BatchMessage::ExportSpan(span));
 
-        if let Err(err) = result {
-            global::handle_error(TraceError::Other(err.into()));
+        match result {
+            Ok(()) => self.metrics.enqueued(),
+            Err(err) => {
+                self.metrics.dropped(&err);
+                global::handle_error(TraceError::Other(err.into()));
+            }
         }
     }
 
@@ -283,6 +299,7 @@ struct BatchSpanProcessorInternal<R> {
     runtime: R,
     exporter: Box<dyn SpanExporter>,
     config: BatchConfig,
+    metrics: ProcessorMetrics,
 }
 
 impl<R: RuntimeChannel> BatchSpanProcessorInternal<R> {
@@ -387,15 +404,22 @@ impl<R: RuntimeChannel> BatchSpanProcessorInternal<R> {
             return Box::pin(future::ready(Ok(())));
         }
 
+        let batch_size = self.spans.len();
+        self.metrics.batch(batch_size);
+        let metrics = self.metrics.exporter.clone();
+        let start = Instant::now();
+
         let export = self.exporter.export(self.spans.split_off(0));
         let timeout = self.runtime.delay(self.config.max_export_timeout);
         let time_out = self.config.max_export_timeout;
 
         Box::pin(async move {
-            match future::select(export, timeout).await {
+            let result = match future::select(export, timeout).await {
                 Either::Left((export_res, _)) => export_res,
                 Either::Right((_, _)) => ExportResult::Err(TraceError::ExportTimedOut(time_out)),
-            }
+            };
+            metrics.exported(batch_size, result.is_ok(), start.elapsed());
+            result
         })
     }
 
@@ -423,7 +447,12 @@ impl<R: RuntimeChannel> BatchSpanProcessorInternal<R> {
 }
 
 impl<R: RuntimeChannel> BatchSpanProcessor<R> {
-    pub(crate) fn new(exporter: Box<dyn SpanExporter>, config: BatchConfig, runtime: R) -> Self {
+    pub(crate) fn new(
+        exporter: Box<dyn SpanExporter>,
+        config: BatchConfig,
+        runtime: R,
+        metrics: ProcessorMetrics,
+    ) -> Self {
         let (message_sender, message_receiver) =
             runtime.batch_message_channel(config.max_queue_size);
         let ticker = runtime
@@ -438,13 +467,17 @@ impl<R: RuntimeChannel> BatchSpanProcessor<R> {
             runtime: timeout_runtime,
             config,
             exporter,
+            metrics: metrics.clone(),
         };
 
         // Spawn worker process via user-defined spawn function.
         runtime.spawn(Box::pin(processor.run(messages)));
 
         // Return batch processor with link to worker
-        BatchSpanProcessor { message_sender }
+        BatchSpanProcessor {
+            message_sender,
+            metrics,
+        }
     }
 
     /// Create a new batch processor builder
@@ -456,6 +489,7 @@ impl<R: RuntimeChannel> BatchSpanProcessor<R> {
             exporter,
             config: Default::default(),
             runtime,
+            metrics: Default::default(),
         }
     }
 }
@@ -466,20 +500,20 @@ impl<R: RuntimeChannel> BatchSpanProcessor<R> {
 pub struct BatchConfig {
     /// The maximum queue size to buffer spans for delayed processing. If the
     /// queue gets full it drops the spans. The default value of is 2048.
-    max_queue_size: usize,
+    pub(crate) max_queue_size: usize,
 
     /// The delay interval in milliseconds between two consecutive processing
     /// of batches. The default value is 5 seconds.
-    scheduled_delay: Duration,
+    pub(crate) scheduled_delay: Duration,
 
     /// The maximum number of spans to process in a single batch. If there are
     /// more than one batch worth of spans then it processes multiple batches
     /// of spans one batch after the other without any delay. The default value
     /// is 512.
-    max_export_batch_size: usize,
+    pub(crate) max_export_batch_size: usize,
 
     /// The maximum duration to export a batch of data.
-    max_export_timeout: Duration,
+    pub(crate) max_export_timeout: Duration,
 
     /// Maximum number of concurrent exports
     ///
@@ -642,6 +676,7 @@ pub struct BatchSpanProcessorBuilder<E, R> {
     exporter: E,
     config: BatchConfig,
     runtime: R,
+    metrics: ProcessorMetrics,
 }
 
 impl<E, R> BatchSpanProcessorBuilder<E, R>
@@ -654,9 +689,37 @@ where
         BatchSpanProcessorBuilder { config, ..self }
     }
 
+    /// Record metrics about the processor and its exporter with `meter_provider`.
+    ///
+    /// The processor reports the number of spans waiting in its queue
+    /// (`otel.sdk.span.processor.queue_size`), the spans dropped because the
+    /// queue is full or the processor is shut down
+    /// (`otel.sdk.span.processor.spans.dropped`), the size of the exported batches
+    /// (`otel.sdk.span.processor.batch_size`), the number of exported spans by
+    /// outcome (`otel.sdk.exporter.spans.exported`) and the duration of the
+    /// exports (`otel.sdk.exporter.duration`).
+    #[cfg(feature = "metrics")]
+    pub fn with_self_observability<P>(self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        BatchSpanProcessorBuilder {
+            metrics: ProcessorMetrics::new(
+                meter_provider,
+                crate::self_observability::ProcessorSignal::Traces,
+            ),
+            ..self
+        }
+    }
+
     /// Build a batch processor
     pub fn build(self) -> BatchSpanProcessor<R> {
-        BatchSpanProcessor::new(Box::new(self.exporter), self.config, self.runtime)
+        BatchSpanProcessor::new(
+            Box::new(self.exporter),
+            self.config,
+            self.runtime,
+            self.metrics,
+        )
     }
 }
 
@@ -876,8 +939,12 @@ mod tests {
             scheduled_delay: Duration::from_secs(60 * 60 * 24), // set the tick to 24 hours so we know the span must be exported via force_flush
             ..Default::default()
         };
-        let mut processor =
-            BatchSpanProcessor::new(Box::new(exporter), config, runtime::TokioCurrentThread);
+        let mut processor = BatchSpanProcessor::new(
+            Box::new(exporter),
+            config,
+            runtime::TokioCurrentThread,
+            Default::default(),
+        );
         let handle = tokio::spawn(async move {
             loop {
                 if let Some(span) = export_receiver.recv().await {
@@ -900,6 +967,53 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_batch_span_processor_self_observability() {
+        use crate::metrics::{data, PeriodicReader, SdkMeterProvider};
+        use crate::testing::metrics::InMemoryMetricsExporter;
+
+        let metrics_exporter = InMemoryMetricsExporter::default();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(metrics_exporter.clone(), runtime::Tokio).build())
+            .build();
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let mut processor = BatchSpanProcessor::builder(exporter.clone(), runtime::Tokio)
+            .with_self_observability(&meter_provider)
+            .build();
+
+        for _ in 0..3 {
+            processor.on_end(new_test_export_span_data());
+        }
+        processor.force_flush().unwrap();
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 3);
+        processor.shutdown().unwrap();
+        meter_provider.force_flush().unwrap();
+
+        let resource_metrics = metrics_exporter.get_finished_metrics().unwrap();
+        let metrics = &resource_metrics.last().unwrap().scope_metrics[0].metrics;
+        let sum = |name: &str| -> i64 {
+            let metric = metrics
+                .iter()
+                .find(|metric| metric.name == name)
+                .unwrap_or_else(|| panic!("{name} is expected to be exported"));
+            let data = metric.data.as_any();
+            if let Some(sum) = data.downcast_ref::<data::Sum<u64>>() {
+                sum.data_points.iter().map(|dp| dp.value as i64).sum()
+            } else {
+                let sum = data.downcast_ref::<data::Sum<i64>>().unwrap();
+                sum.data_points.iter().map(|dp| dp.value).sum()
+            }
+        };
+        assert_eq!(sum("otel.sdk.exporter.spans.exported"), 3);
+        assert_eq!(sum("otel.sdk.span.processor.queue_size"), 0);
+        assert!(metrics
+            .iter()
+            .any(|metric| metric.name == "otel.sdk.exporter.duration"));
+        assert!(!metrics
+            .iter()
+            .any(|metric| metric.name == "otel.sdk.span.processor.spans.dropped"));
+    }
+
     struct BlockingExporter<D> {
         delay_for: Duration,
         delay_fn: D,
@@ -976,7 +1090,12 @@ mod tests {
             delay_for: Duration::from_millis(if !time_out { 5 } else { 60 }),
             delay_fn: async_std::task::sleep,
         };
-        let mut processor = BatchSpanProcessor::new(Box::new(exporter), config, runtime::AsyncStd);
+        let mut processor = BatchSpanProcessor::new(
+            Box::new(exporter),
+            config,
+            runtime::AsyncStd,
+            Default::default(),
+        );
         processor.on_end(new_test_export_span_data());
         let flush_res = processor.force_flush();
         if time_out {
@@ -1000,8 +1119,12 @@ mod tests {
             delay_for: Duration::from_millis(if !time_out { 5 } else { 60 }),
             delay_fn: tokio::time::sleep,
         };
-        let mut processor =
-            BatchSpanProcessor::new(Box::new(exporter), config, runtime::TokioCurrentThread);
+        let mut processor = BatchSpanProcessor::new(
+            Box::new(exporter),
+            config,
+            runtime::TokioCurrentThread,
+            Default::default(),
+        );
         tokio::time::sleep(Duration::from_secs(1)).await; // skip the first
         processor.on_end(new_test_export_span_data());
         let flush_res = processor.force_flush();
@@ -0,0 +1,914 @@
+//! Tail-based sampling.
+//!
+//! The [`Sampler`]s decide whether a span is sampled when it starts, before
+//! anything is known about its outcome. The [`TailSamplingSpanProcessor`]
+//! instead buffers the ended spans of each trace, and decides whether to export
+//! the trace once its local root span ended, so that traces can be kept for
+//! their errors or latency.
+//!
+//! [`Sampler`]: crate::trace::Sampler
+
+use crate::export::trace::{ExportResult, SpanData, SpanExporter};
+use crate::runtime::TrySendError;
+use crate::trace::{sampler::sample_based_on_probability, Span, SpanProcessor, StringMatcher};
+use crate::util::block_on_with_timeout;
+use opentelemetry::{
+    global,
+    trace::{
+        SamplingDecision, Span as _, SpanId, Status, TraceContextExt, TraceError, TraceId,
+        TraceResult,
+    },
+    Context, Key,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Default time waited for the local root span of a trace to end, 30 seconds.
+const DEFAULT_DECISION_WAIT: Duration = Duration::from_secs(30);
+/// Default maximum number of traces buffered at once.
+const DEFAULT_MAX_TRACES: usize = 10_000;
+/// Default maximum number of spans buffered per trace.
+const DEFAULT_MAX_SPANS_PER_TRACE: usize = 1_000;
+/// Default capacity of the channel to the sampling thread.
+const DEFAULT_MAX_QUEUE_SIZE: usize = 2_048;
+/// Default timeout of an export, 30 seconds.
+const DEFAULT_MAX_EXPORT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Default delay before exporting the spans ending after their trace was kept.
+const DEFAULT_SCHEDULED_DELAY: Duration = Duration::from_secs(5);
+/// Default maximum number of late spans exported at once.
+const DEFAULT_MAX_EXPORT_BATCH_SIZE: usize = 512;
+
+/// A policy of a [`TailSamplingSpanProcessor`], deciding whether a trace is
+/// exported from its spans.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum TailSamplingPolicy {
+    /// Keep the traces with a span whose status is [`Status::Error`].
+    Error,
+    /// Keep the traces lasting longer than the given duration, from the start
+    /// of their first span to the end of their last one.
+    Latency(Duration),
+    /// Keep the traces with a span having the attribute `key`, whose value
+    /// converted to a string is matched by the matcher.
+    Attribute(Key, StringMatcher),
+    /// Keep a fraction of the traces, between 0 and 1, based on their trace
+    /// id like [`Sampler::TraceIdRatioBased`].
+    ///
+    /// [`Sampler::TraceIdRatioBased`]: crate::trace::Sampler::TraceIdRatioBased
+    Probabilistic(f64),
+}
+
+impl TailSamplingPolicy {
+    /// Returns `true` if `span` alone is enough to keep its trace.
+    fn keeps_span(&self, span: &SpanData) -> bool {
+        match self {
+            TailSamplingPolicy::Error => matches!(span.status, Status::Error { .. }),
+            TailSamplingPolicy::Attribute(key, matcher) => span
+                .attributes
+                .iter()
+                .any(|kv| &kv.key == key && matcher.matches(&kv.value.as_str())),
+            TailSamplingPolicy::Latency(_) | TailSamplingPolicy::Probabilistic(_) => false,
+        }
+    }
+
+    /// Returns `true` if the trace is kept, once all of its spans are known.
+    fn keeps_trace(&self, trace: &PendingTrace) -> bool {
+        match self {
+            TailSamplingPolicy::Latency(threshold) => trace
+                .end
+                .duration_since(trace.start)
+                .map_or(false, |latency| latency > *threshold),
+            TailSamplingPolicy::Probabilistic(probability) => {
+                sample_based_on_probability(probability, trace.trace_id)
+                    == SamplingDecision::RecordAndSample
+            }
+            TailSamplingPolicy::Error | TailSamplingPolicy::Attribute(..) => false,
+        }
+    }
+}
+
+/// Messages sent from the application threads to the sampling thread.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+enum Message {
+    /// A local root span of the trace started.
+    LocalRoot(TraceId, SpanId),
+    /// A span of the trace ended.
+    End(SpanData),
+    /// Decide on the buffered traces, and send back the result.
+    Flush(mpsc::Sender<ExportResult>),
+    /// Decide on the buffered traces, shut down the exporter and stop the
+    /// thread.
+    Shutdown(mpsc::Sender<ExportResult>),
+}
+
+/// A [`SpanProcessor`] exporting the traces selected by tail-based sampling
+/// policies.
+///
+/// The ended spans are buffered per trace until the local root span of the
+/// trace ends, i.e. the span started without a parent or with a remote
+/// parent, or until the decision wait elapses. The trace is then exported if
+/// any of the [`TailSamplingPolicy`]s keeps it, and dropped otherwise. Without
+/// any policy, all traces are kept. The spans ending after the decision follow
+/// it, as long as it is one of the last `max_traces` decisions: those of the
+/// kept traces are exported in batches of at most `max_export_batch_size`
+/// spans, after at most `scheduled_delay`.
+///
+/// Only the spans sampled by the [`Sampler`] of the tracer provider are seen
+/// by the processor, so the provider would usually sample all spans.
+///
+/// At most `max_traces` traces are buffered: when a new trace would go past
+/// it, the decision on the oldest trace is taken early. At most
+/// `max_spans_per_trace` spans of a trace are buffered, the other ones are
+/// dropped but still taken into account to keep the trace. Their number is
+/// reported to the global error handler when the trace is decided.
+///
+/// The spans are buffered, and the traces exported, by a dedicated background
+/// thread. The exports are blocking calls of the exporter, abandoned after
+/// `max_export_timeout`. [`force_flush`] and [`shutdown`] decide on all the
+/// buffered traces.
+///
+/// ```
+/// use opentelemetry_sdk::testing::trace::NoopSpanExporter;
+/// use opentelemetry_sdk::trace::{self, Sampler, TailSamplingPolicy, TailSamplingSpanProcessor};
+/// use std::time::Duration;
+///
+/// let processor = TailSamplingSpanProcessor::builder(NoopSpanExporter::new())
+///     .with_policy(TailSamplingPolicy::Error)
+///     .with_policy(TailSamplingPolicy::Latency(Duration::from_secs(1)))
+///     .with_policy(TailSamplingPolicy::Probabilistic(0.01))
+///     .build();
+///
+/// let provider = trace::TracerProvider::builder()
+///     .with_config(trace::config().with_sampler(Sampler::AlwaysOn))
+///     .with_span_processor(processor)
+///     .build();
+/// # drop(provider);
+/// ```
+///
+/// [`Sampler`]: crate::trace::Sampler
+/// [`force_flush`]: SpanProcessor::force_flush
+/// [`shutdown`]: SpanProcessor::shutdown
+pub struct TailSamplingSpanProcessor {
+    message_sender: SyncSender<Message>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for TailSamplingSpanProcessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TailSamplingSpanProcessor")
+            .field("message_sender", &self.message_sender)
+            .finish()
+    }
+}
+
+impl TailSamplingSpanProcessor {
+    /// Create a new tail sampling processor builder
+    pub fn builder<E>(exporter: E) -> TailSamplingSpanProcessorBuilder<E>
+    where
+        E: SpanExporter,
+    {
+        TailSamplingSpanProcessorBuilder {
+            exporter,
+            config: TailSamplingConfig {
+                policies: Vec::new(),
+                decision_wait: DEFAULT_DECISION_WAIT,
+                max_traces: DEFAULT_MAX_TRACES,
+                max_spans_per_trace: DEFAULT_MAX_SPANS_PER_TRACE,
+                max_queue_size: DEFAULT_MAX_QUEUE_SIZE,
+                max_export_timeout: DEFAULT_MAX_EXPORT_TIMEOUT,
+                scheduled_delay: DEFAULT_SCHEDULED_DELAY,
+                max_export_batch_size: DEFAULT_MAX_EXPORT_BATCH_SIZE,
+            },
+        }
+    }
+
+    fn new(exporter: Box<dyn SpanExporter>, config: TailSamplingConfig) -> Self {
+        let (message_sender, message_receiver) = mpsc::sync_channel(config.max_queue_size);
+        let worker = SamplingWorker {
+            exporter,
+            config,
+            pending: HashMap::new(),
+            pending_order: VecDeque::new(),
+            decided: HashMap::new(),
+            decided_order: VecDeque::new(),
+            late_spans: Vec::new(),
+            late_deadline: None,
+        };
+
+        let handle = thread::Builder::new()
+            .name("opentelemetry-tail-sampling-processor".into())
+            .spawn(move || worker.run(message_receiver))
+            .expect("failed to spawn the tail sampling processor thread");
+
+        TailSamplingSpanProcessor {
+            message_sender,
+            handle: Some(handle),
+        }
+    }
+
+    fn send(&self, message: Message) {
+        if let Err(err) = self.message_sender.try_send(message) {
+            global::handle_error(TraceError::Other(TrySendError::from(err).into()));
+        }
+    }
+
+    /// Sends a control message, waiting for room in the queue, then waits for
+    /// the sampling thread to answer it.
+    fn request(
+        &self,
+        message: impl FnOnce(mpsc::Sender<ExportResult>) -> Message,
+    ) -> TraceResult<()> {
+        let (res_sender, res_receiver) = mpsc::channel();
+        self.message_sender
+            .send(message(res_sender))
+            .map_err(|_| TraceError::from("the tail sampling processor is shut down"))?;
+
+        res_receiver
+            .recv()
+            .map_err(|err| TraceError::Other(err.into()))
+            .and_then(std::convert::identity)
+    }
+}
+
+impl SpanProcessor for TailSamplingSpanProcessor {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        let span_context = span.span_context();
+        if !span_context.is_sampled() {
+            return;
+        }
+
+        let is_local_root = !cx.has_active_span() || cx.span().span_context().is_remote();
+        if is_local_root {
+            self.send(Message::LocalRoot(
+                span_context.trace_id(),
+                span_context.span_id(),
+            ));
+        }
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if span.span_context.is_sampled() {
+            self.send(Message::End(span));
+        }
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        self.request(Message::Flush)
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        let result = self.request(Message::Shutdown);
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|_| TraceError::from("the tail sampling processor thread panicked"))?;
+        }
+        result
+    }
+}
+
+impl Drop for TailSamplingSpanProcessor {
+    /// Decides on the buffered traces and stops the thread, unless already
+    /// shut down.
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            if let Err(err) = self.shutdown() {
+                global::handle_error(err);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TailSamplingConfig {
+    policies: Vec<TailSamplingPolicy>,
+    decision_wait: Duration,
+    max_traces: usize,
+    max_spans_per_trace: usize,
+    max_queue_size: usize,
+    max_export_timeout: Duration,
+    scheduled_delay: Duration,
+    max_export_batch_size: usize,
+}
+
+/// The spans of a trace waiting for a decision.
+struct PendingTrace {
+    trace_id: TraceId,
+    spans: Vec<SpanData>,
+    /// The local root spans which haven't ended yet.
+    local_roots: HashSet<SpanId>,
+    deadline: Instant,
+    /// Whether one of the spans was already enough to keep the trace.
+    keep: bool,
+    /// The number of spans dropped over `max_spans_per_trace`.
+    dropped_spans: usize,
+    start: SystemTime,
+    end: SystemTime,
+}
+
+struct SamplingWorker {
+    exporter: Box<dyn SpanExporter>,
+    config: TailSamplingConfig,
+    pending: HashMap<TraceId, PendingTrace>,
+    /// The pending traces with their deadline, oldest first. The traces
+    /// decided before their deadline are left in it until they come first.
+    pending_order: VecDeque<(TraceId, Instant)>,
+    /// Whether the recently decided traces were kept, for their late spans.
+    decided: HashMap<TraceId, bool>,
+    decided_order: VecDeque<TraceId>,
+    /// The spans ending after their trace was kept, waiting to be exported.
+    late_spans: Vec<SpanData>,
+    late_deadline: Option<Instant>,
+}
+
+impl SamplingWorker {
+    fn run(mut self, messages: Receiver<Message>) {
+        loop {
+            let message = match self.next_wakeup() {
+                Some(deadline) => {
+                    messages.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => messages.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match message {
+                Ok(Message::LocalRoot(trace_id, span_id)) => {
+                    if let Some(trace) = self.pending_trace(trace_id) {
+                        trace.local_roots.insert(span_id);
+                    }
+                }
+                Ok(Message::End(span)) => {
+                    if let Err(err) = self.on_end(span) {
+                        global::handle_error(err);
+                    }
+                }
+                Ok(Message::Flush(ch)) => {
+                    let result = self.decide_all();
+                    if let Err(result) = ch.send(result) {
+                        global::handle_error(TraceError::from(format!(
+                            "failed to send flush result: {:?}",
+                            result
+                        )));
+                    }
+                }
+                Ok(Message::Shutdown(ch)) => {
+                    let result = self.decide_all();
+                    self.exporter.shutdown();
+                    if let Err(result) = ch.send(result) {
+                        global::handle_error(TraceError::from(format!(
+                            "failed to send tail sampling processor shutdown result: {:?}",
+                            result
+                        )));
+                    }
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Err(err) = self.decide_expired() {
+                        global::handle_error(err);
+                    }
+                    if self
+                        .late_deadline
+                        .map_or(false, |deadline| deadline <= Instant::now())
+                    {
+                        if let Err(err) = self.export_late_spans() {
+                            global::handle_error(err);
+                        }
+                    }
+                }
+                // The processor was dropped without being shut down.
+                Err(RecvTimeoutError::Disconnected) => {
+                    if let Err(err) = self.decide_all() {
+                        global::handle_error(err);
+                    }
+                    self.exporter.shutdown();
+                    break;
+                }
+            }
+        }
+    }
+
+    /// The next time the thread has to wake up, to decide on a trace or to
+    /// export the late spans.
+    fn next_wakeup(&mut self) -> Option<Instant> {
+        match (self.next_deadline(), self.late_deadline) {
+            (Some(deadline), Some(late_deadline)) => Some(deadline.min(late_deadline)),
+            (deadline, late_deadline) => deadline.or(late_deadline),
+        }
+    }
+
+    /// The deadline of the oldest pending trace.
+    fn next_deadline(&mut self) -> Option<Instant> {
+        while let Some((trace_id, deadline)) = self.pending_order.front() {
+            if self
+                .pending
+                .get(trace_id)
+                .map_or(false, |trace| trace.deadline == *deadline)
+            {
+                return Some(*deadline);
+            }
+            self.pending_order.pop_front();
+        }
+        None
+    }
+
+    /// Returns the pending trace `trace_id`, creating it unless it was
+    /// already decided.
+    fn pending_trace(&mut self, trace_id: TraceId) -> Option<&mut PendingTrace> {
+        if self.decided.contains_key(&trace_id) {
+            return None;
+        }
+
+        if !self.pending.contains_key(&trace_id) {
+            if self.pending.len() >= self.config.max_traces {
+                if let Err(err) = self.decide_oldest() {
+                    global::handle_error(err);
+                }
+            }
+
+            let deadline = Instant::now() + self.config.decision_wait;
+            self.pending_order.push_back((trace_id, deadline));
+            self.pending.insert(
+                trace_id,
+                PendingTrace {
+                    trace_id,
+                    spans: Vec::new(),
+                    local_roots: HashSet::new(),
+                    deadline,
+                    keep: false,
+                    dropped_spans: 0,
+                    start: SystemTime::now(),
+                    end: SystemTime::UNIX_EPOCH,
+                },
+            );
+        }
+
+        self.pending.get_mut(&trace_id)
+    }
+
+    fn on_end(&mut self, span: SpanData) -> ExportResult {
+        let trace_id = span.span_context.trace_id();
+        if let Some(&keep) = self.decided.get(&trace_id) {
+            if !keep {
+                return Ok(());
+            }
+            if self.late_spans.is_empty() {
+                self.late_deadline = Some(Instant::now() + self.config.scheduled_delay);
+            }
+            self.late_spans.push(span);
+            return if self.late_spans.len() >= self.config.max_export_batch_size {
+                self.export_late_spans()
+            } else {
+                Ok(())
+            };
+        }
+
+        let keeps_span = self
+            .config
+            .policies
+            .iter()
+            .any(|policy| policy.keeps_span(&span));
+        let max_spans_per_trace = self.config.max_spans_per_trace;
+        let trace = self
+            .pending_trace(trace_id)
+            .expect("undecided traces are pending");
+
+        if trace.spans.is_empty() {
+            trace.start = span.start_time;
+        }
+        trace.start = trace.start.min(span.start_time);
+        trace.end = trace.end.max(span.end_time);
+        trace.keep |= keeps_span;
+        let ends_trace =
+            trace.local_roots.remove(&span.span_context.span_id()) && trace.local_roots.is_empty();
+        if trace.spans.len() < max_spans_per_trace {
+            trace.spans.push(span);
+        } else {
+            trace.dropped_spans += 1;
+        }
+
+        if ends_trace {
+            self.decide(trace_id)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Decides on the pending trace `trace_id`, exporting it if kept.
+    fn decide(&mut self, trace_id: TraceId) -> ExportResult {
+        let trace = match self.pending.remove(&trace_id) {
+            Some(trace) => trace,
+            None => return Ok(()),
+        };
+        if trace.dropped_spans > 0 {
+            global::handle_error(TraceError::Other(
+                format!(
+                    "{} spans of trace {} dropped over max_spans_per_trace",
+                    trace.dropped_spans, trace_id
+                )
+                .into(),
+            ));
+        }
+        let keep = trace.keep
+            || self.config.policies.is_empty()
+            || self
+                .config
+                .policies
+                .iter()
+                .any(|policy| policy.keeps_trace(&trace));
+
+        if self.decided_order.len() >= self.config.max_traces {
+            if let Some(oldest) = self.decided_order.pop_front() {
+                self.decided.remove(&oldest);
+            }
+        }
+        self.decided.insert(trace_id, keep);
+        self.decided_order.push_back(trace_id);
+
+        if keep {
+            self.export(trace.spans)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn decide_oldest(&mut self) -> ExportResult {
+        match self.next_deadline() {
+            Some(_) => {
+                let (trace_id, _) = self
+                    .pending_order
+                    .pop_front()
+                    .expect("the next deadline is the one of the first trace");
+                self.decide(trace_id)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Decides on the traces whose deadline passed.
+    fn decide_expired(&mut self) -> ExportResult {
+        let mut result = Ok(());
+        let now = Instant::now();
+        while self
+            .next_deadline()
+            .map_or(false, |deadline| deadline <= now)
+        {
+            if let Err(err) = self.decide_oldest() {
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    /// Decides on all the pending traces, and exports the late spans.
+    fn decide_all(&mut self) -> ExportResult {
+        let mut result = Ok(());
+        while self.next_deadline().is_some() {
+            if let Err(err) = self.decide_oldest() {
+                result = Err(err);
+            }
+        }
+        if let Err(err) = self.export_late_spans() {
+            result = Err(err);
+        }
+        result
+    }
+
+    fn export_late_spans(&mut self) -> ExportResult {
+        self.late_deadline = None;
+        let spans = std::mem::take(&mut self.late_spans);
+        self.export(spans)
+    }
+
+    fn export(&mut self, spans: Vec<SpanData>) -> ExportResult {
+        if spans.is_empty() {
+            return Ok(());
+        }
+
+        let export = self.exporter.export(spans);
+        block_on_with_timeout(export, self.config.max_export_timeout).unwrap_or(Err(
+            TraceError::ExportTimedOut(self.config.max_export_timeout),
+        ))
+    }
+}
+
+/// A builder for creating [`TailSamplingSpanProcessor`] instances.
+#[derive(Debug)]
+pub struct TailSamplingSpanProcessorBuilder<E> {
+    exporter: E,
+    config: TailSamplingConfig,
+}
+
+impl<E> TailSamplingSpanProcessorBuilder<E>
+where
+    E: SpanExporter + 'static,
+{
+    /// Add a policy keeping traces. A trace is kept if any policy keeps it.
+    pub fn with_policy(mut self, policy: TailSamplingPolicy) -> Self {
+        self.config.policies.push(policy);
+        self
+    }
+
+    /// Set the time waited for the local root span of a trace to end after the
+    /// trace is first seen, before deciding on it with the spans ended so far.
+    /// Default to 30 seconds.
+    pub fn with_decision_wait(mut self, decision_wait: Duration) -> Self {
+        self.config.decision_wait = decision_wait;
+        self
+    }
+
+    /// Set the maximum number of traces buffered at once. Default to 10 000.
+    pub fn with_max_traces(mut self, max_traces: usize) -> Self {
+        self.config.max_traces = max_traces.max(1);
+        self
+    }
+
+    /// Set the maximum number of spans buffered per trace. Default to 1000.
+    pub fn with_max_spans_per_trace(mut self, max_spans_per_trace: usize) -> Self {
+        self.config.max_spans_per_trace = max_spans_per_trace;
+        self
+    }
+
+    /// Set the capacity of the channel to the sampling thread. The spans
+    /// ending while it is full are dropped. Default to 2048.
+    pub fn with_max_queue_size(mut self, max_queue_size: usize) -> Self {
+        self.config.max_queue_size = max_queue_size;
+        self
+    }
+
+    /// Set the time after which an export is abandoned. Default to 30 seconds.
+    pub fn with_max_export_timeout(mut self, max_export_timeout: Duration) -> Self {
+        self.config.max_export_timeout = max_export_timeout;
+        self
+    }
+
+    /// Set the maximum delay before exporting the spans ending after their
+    /// trace was kept. Default to 5 seconds.
+    pub fn with_scheduled_delay(mut self, scheduled_delay: Duration) -> Self {
+        self.config.scheduled_delay = scheduled_delay;
+        self
+    }
+
+    /// Set the maximum number of spans ending after their trace was kept which
+    /// are exported at once. Default to 512.
+    pub fn with_max_export_batch_size(mut self, max_export_batch_size: usize) -> Self {
+        self.config.max_export_batch_size = max_export_batch_size.max(1);
+        self
+    }
+
+    /// Build a tail sampling processor, spawning its sampling thread.
+    pub fn build(self) -> TailSamplingSpanProcessor {
+        TailSamplingSpanProcessor::new(Box::new(self.exporter), self.config)
+    }
+}
+
+#[cfg(all(test, feature = "testing", feature = "trace"))]
+mod tests {
+    use super::{TailSamplingPolicy, TailSamplingSpanProcessor};
+    use crate::export::trace::{ExportResult, SpanData, SpanExporter};
+    use crate::testing::trace::{new_test_export_span_data, InMemorySpanExporter};
+    use crate::trace::{SpanProcessor, StringMatcher, TracerProvider};
+    use futures_util::future::BoxFuture;
+    use opentelemetry::trace::{Span, Status, TraceContextExt, Tracer, TracerProvider as _};
+    use opentelemetry::{Context, KeyValue};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    /// Records the size of the exported batches.
+    #[derive(Debug)]
+    struct BatchSizesExporter(Arc<Mutex<Vec<usize>>>);
+
+    impl SpanExporter for BatchSizesExporter {
+        fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+            self.0.lock().unwrap().push(batch.len());
+            Box::pin(futures_util::future::ready(Ok(())))
+        }
+    }
+
+    fn provider(processor: TailSamplingSpanProcessor) -> TracerProvider {
+        TracerProvider::builder()
+            .with_span_processor(processor)
+            .build()
+    }
+
+    fn exported_names(exporter: &InMemorySpanExporter) -> Vec<String> {
+        let mut names: Vec<String> = exporter
+            .get_finished_spans()
+            .unwrap()
+            .into_iter()
+            .map(|span| span.name.into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Ends a trace made of a root span and a child, the child being updated
+    /// by `child`.
+    fn trace(provider: &TracerProvider, name: &str, child: impl FnOnce(&mut crate::trace::Span)) {
+        let tracer = provider.tracer("test");
+        let root = tracer.start(format!("{}-root", name));
+        let cx = Context::current_with_span(root);
+        let mut span = tracer.start_with_context(format!("{}-child", name), &cx);
+        child(&mut span);
+        span.end();
+        cx.span().end();
+    }
+
+    #[test]
+    fn keeps_traces_with_errors() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = provider(
+            TailSamplingSpanProcessor::builder(exporter.clone())
+                .with_policy(TailSamplingPolicy::Error)
+                .build(),
+        );
+
+        trace(&provider, "ok", |_| {});
+        trace(&provider, "failed", |span| {
+            span.set_status(Status::error("boom"))
+        });
+        provider.force_flush();
+
+        assert_eq!(exported_names(&exporter), ["failed-child", "failed-root"]);
+    }
+
+    #[test]
+    fn keeps_traces_by_latency_and_attribute() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = provider(
+            TailSamplingSpanProcessor::builder(exporter.clone())
+                .with_policy(TailSamplingPolicy::Latency(Duration::from_secs(1)))
+                .with_policy(TailSamplingPolicy::Attribute(
+                    "user.tier".into(),
+                    StringMatcher::exact("premium"),
+                ))
+                .build(),
+        );
+
+        trace(&provider, "fast", |_| {});
+        trace(&provider, "slow", |span| {
+            span.end_with_timestamp(SystemTime::now() + Duration::from_secs(2))
+        });
+        trace(&provider, "premium", |span| {
+            span.set_attribute(KeyValue::new("user.tier", "premium"))
+        });
+        provider.force_flush();
+
+        assert_eq!(
+            exported_names(&exporter),
+            ["premium-child", "premium-root", "slow-child", "slow-root"]
+        );
+    }
+
+    #[test]
+    fn probabilistic_policy_uses_the_ratio() {
+        for (ratio, expected) in [(1.0, 2), (0.0, 0)] {
+            let exporter = InMemorySpanExporter::default();
+            let provider = provider(
+                TailSamplingSpanProcessor::builder(exporter.clone())
+                    .with_policy(TailSamplingPolicy::Probabilistic(ratio))
+                    .build(),
+            );
+
+            trace(&provider, "trace", |_| {});
+            provider.force_flush();
+
+            assert_eq!(exported_names(&exporter).len(), expected);
+        }
+    }
+
+    #[test]
+    fn decides_after_the_decision_wait() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = provider(
+            TailSamplingSpanProcessor::builder(exporter.clone())
+                .with_policy(TailSamplingPolicy::Error)
+                .with_decision_wait(Duration::from_millis(50))
+                .build(),
+        );
+        let tracer = provider.tracer("test");
+
+        let root = tracer.start("root");
+        let cx = Context::current_with_span(root);
+        let mut child = tracer.start_with_context("child", &cx);
+        child.set_status(Status::error("boom"));
+        child.end();
+        std::thread::sleep(Duration::from_millis(300));
+
+        // The root span is still running.
+        assert_eq!(exported_names(&exporter), ["child"]);
+
+        // The spans ending after the decision follow it.
+        cx.span().end();
+        provider.force_flush();
+        assert_eq!(exported_names(&exporter), ["child", "root"]);
+    }
+
+    #[test]
+    fn decides_early_over_max_traces() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = provider(
+            TailSamplingSpanProcessor::builder(exporter.clone())
+                .with_policy(TailSamplingPolicy::Error)
+                .with_decision_wait(Duration::from_secs(3600))
+                .with_max_traces(1)
+                .build(),
+        );
+        let tracer = provider.tracer("test");
+
+        let first = Context::current_with_span(tracer.start("first-root"));
+        let mut child = tracer.start_with_context("first-child", &first);
+        child.set_status(Status::error("boom"));
+        child.end();
+        // The second trace pushes the first one out of the buffer.
+        let second = Context::current_with_span(tracer.start("second-root"));
+        std::thread::sleep(Duration::from_millis(200));
+
+        assert_eq!(exported_names(&exporter), ["first-child"]);
+
+        first.span().end();
+        second.span().end();
+        provider.force_flush();
+        assert_eq!(exported_names(&exporter), ["first-child", "first-root"]);
+    }
+
+    #[test]
+    fn shutdown_stops_the_thread() {
+        let mut processor =
+            TailSamplingSpanProcessor::builder(InMemorySpanExporter::default()).build();
+
+        processor.shutdown().expect("shutdown should succeed");
+
+        assert!(processor.handle.is_none());
+        assert!(processor.force_flush().is_err());
+    }
+
+    #[test]
+    fn keeps_all_traces_without_policy() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = provider(TailSamplingSpanProcessor::builder(exporter.clone()).build());
+
+        trace(&provider, "ok", |_| {});
+        provider.force_flush();
+
+        assert_eq!(exported_names(&exporter), ["ok-child", "ok-root"]);
+    }
+
+    #[test]
+    fn exports_late_spans_in_batches() {
+        let batch_sizes = Arc::new(Mutex::new(Vec::new()));
+        let provider = provider(
+            TailSamplingSpanProcessor::builder(BatchSizesExporter(batch_sizes.clone()))
+                .with_policy(TailSamplingPolicy::Error)
+                .with_max_export_batch_size(2)
+                .build(),
+        );
+        let tracer = provider.tracer("test");
+
+        let cx = Context::current_with_span(tracer.start("root"));
+        let children: Vec<_> = (0..3)
+            .map(|i| tracer.start_with_context(format!("child-{}", i), &cx))
+            .collect();
+        cx.span().set_status(Status::error("boom"));
+        cx.span().end();
+        for mut child in children {
+            child.end();
+        }
+        provider.force_flush();
+
+        assert_eq!(*batch_sizes.lock().unwrap(), [1, 2, 1]);
+    }
+
+    #[test]
+    fn drops_spans_over_max_spans_per_trace() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = provider(
+            TailSamplingSpanProcessor::builder(exporter.clone())
+                .with_policy(TailSamplingPolicy::Error)
+                .with_max_spans_per_trace(1)
+                .build(),
+        );
+
+        trace(&provider, "failed", |span| {
+            span.set_status(Status::error("boom"))
+        });
+        provider.force_flush();
+
+        assert_eq!(exported_names(&exporter), ["failed-child"]);
+    }
+
+    #[test]
+    fn drop_decides_on_the_buffered_traces() {
+        let batch_sizes = Arc::new(Mutex::new(Vec::new()));
+        let processor =
+            TailSamplingSpanProcessor::builder(BatchSizesExporter(batch_sizes.clone())).build();
+
+        processor.on_end(new_test_export_span_data());
+        drop(processor);
+
+        assert_eq!(*batch_sizes.lock().unwrap(), [1]);
+    }
+}
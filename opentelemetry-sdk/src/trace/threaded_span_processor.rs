@@ -0,0 +1,450 @@
+//! A batch span processor exporting from a dedicated OS thread.
+//!
+//! Unlike [`BatchSpanProcessor`], the [`ThreadedBatchSpanProcessor`] doesn't
+//! need an async runtime: the spans are queued in a bounded channel, and a
+//! background thread blocks on the exporter to export them. It suits
+//! synchronous applications, and applications whose runtime cannot host the
+//! batch processor, like tokio's current thread runtime.
+//!
+//! [`BatchSpanProcessor`]: crate::trace::BatchSpanProcessor
+
+use crate::export::trace::{ExportResult, SpanData, SpanExporter};
+use crate::runtime::TrySendError;
+use crate::self_observability::ProcessorMetrics;
+use crate::trace::{BatchConfig, Span, SpanProcessor};
+use crate::util::block_on_with_timeout;
+use opentelemetry::{
+    global,
+    trace::{TraceError, TraceResult},
+    Context,
+};
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+/// Messages sent from the application threads to the export thread.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+enum BatchMessage {
+    /// Queue a span for export.
+    ExportSpan(SpanData),
+    /// Export the queued spans, and send back the result.
+    Flush(mpsc::Sender<ExportResult>),
+    /// Export the queued spans, shut down the exporter and stop the thread.
+    Shutdown(mpsc::Sender<ExportResult>),
+}
+
+/// A [`SpanProcessor`] that buffers finished spans and exports them in
+/// batches from a dedicated background thread.
+///
+/// Spans are queued in a channel bounded by the `max_queue_size` of the
+/// [`BatchConfig`], and dropped when it is full. The thread exports a batch
+/// once `max_export_batch_size` spans are buffered, every `scheduled_delay`,
+/// and when the processor is flushed or shut down. The exports are blocking
+/// calls of the exporter, abandoned after `max_export_timeout`.
+/// The exporter is polled outside of any async runtime, so it must not
+/// depend on one, like an exporter using a blocking HTTP client.
+///
+/// [`force_flush`] and [`shutdown`] block the calling thread until the export
+/// thread has handled them, so they can be called from any context, including
+/// a single threaded async runtime.
+///
+/// ```
+/// use opentelemetry_sdk::testing::trace::NoopSpanExporter;
+/// use opentelemetry_sdk::trace::{self, BatchConfigBuilder, ThreadedBatchSpanProcessor};
+/// use std::time::Duration;
+///
+/// let processor = ThreadedBatchSpanProcessor::builder(NoopSpanExporter::new())
+///     .with_batch_config(
+///         BatchConfigBuilder::default()
+///             .with_scheduled_delay(Duration::from_secs(1))
+///             .build(),
+///     )
+///     .build();
+///
+/// let provider = trace::TracerProvider::builder()
+///     .with_span_processor(processor)
+///     .build();
+/// # drop(provider);
+/// ```
+///
+/// [`force_flush`]: SpanProcessor::force_flush
+/// [`shutdown`]: SpanProcessor::shutdown
+pub struct ThreadedBatchSpanProcessor {
+    message_sender: SyncSender<BatchMessage>,
+    handle: Option<JoinHandle<()>>,
+    metrics: ProcessorMetrics,
+}
+
+impl fmt::Debug for ThreadedBatchSpanProcessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThreadedBatchSpanProcessor")
+            .field("message_sender", &self.message_sender)
+            .finish()
+    }
+}
+
+impl ThreadedBatchSpanProcessor {
+    pub(crate) fn new(
+        exporter: Box<dyn SpanExporter>,
+        config: BatchConfig,
+        metrics: ProcessorMetrics,
+    ) -> Self {
+        let (message_sender, message_receiver) = mpsc::sync_channel(config.max_queue_size);
+        let worker = BatchWorker {
+            spans: Vec::new(),
+            exporter,
+            config,
+            metrics: metrics.clone(),
+        };
+
+        let handle = thread::Builder::new()
+            .name("opentelemetry-batch-span-processor".into())
+            .spawn(move || worker.run(message_receiver))
+            .expect("failed to spawn the batch span processor thread");
+
+        ThreadedBatchSpanProcessor {
+            message_sender,
+            handle: Some(handle),
+            metrics,
+        }
+    }
+
+    /// Create a new threaded batch processor builder
+    pub fn builder<E>(exporter: E) -> ThreadedBatchSpanProcessorBuilder<E>
+    where
+        E: SpanExporter,
+    {
+        ThreadedBatchSpanProcessorBuilder {
+            exporter,
+            config: Default::default(),
+            metrics: Default::default(),
+        }
+    }
+
+    /// Sends a control message, waiting for room in the queue, then waits for
+    /// the export thread to answer it.
+    fn request(
+        &self,
+        message: impl FnOnce(mpsc::Sender<ExportResult>) -> BatchMessage,
+    ) -> TraceResult<()> {
+        let (res_sender, res_receiver) = mpsc::channel();
+        self.message_sender
+            .send(message(res_sender))
+            .map_err(|_| TraceError::from("the batch span processor is shut down"))?;
+
+        res_receiver
+            .recv()
+            .map_err(|err| TraceError::Other(err.into()))
+            .and_then(std::convert::identity)
+    }
+}
+
+impl SpanProcessor for ThreadedBatchSpanProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {
+        // Ignored
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if !span.span_context.is_sampled() {
+            return;
+        }
+
+        match self
+            .message_sender
+            .try_send(BatchMessage::ExportSpan(span))
+            .map_err(TrySendError::from)
+        {
+            Ok(()) => self.metrics.enqueued(),
+            Err(err) => {
+                self.metrics.dropped(&err);
+                global::handle_error(TraceError::Other(err.into()));
+            }
+        }
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        self.request(BatchMessage::Flush)
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        let result = self.request(BatchMessage::Shutdown);
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|_| TraceError::from("the batch span processor thread panicked"))?;
+        }
+        result
+    }
+}
+
+impl Drop for ThreadedBatchSpanProcessor {
+    /// Exports the queued spans and stops the thread, unless already shut down.
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            if let Err(err) = self.shutdown() {
+                global::handle_error(err);
+            }
+        }
+    }
+}
+
+struct BatchWorker {
+    spans: Vec<SpanData>,
+    exporter: Box<dyn SpanExporter>,
+    config: BatchConfig,
+    metrics: ProcessorMetrics,
+}
+
+impl BatchWorker {
+    fn run(mut self, messages: Receiver<BatchMessage>) {
+        let mut next_export = Instant::now() + self.config.scheduled_delay;
+
+        loop {
+            let timeout = next_export.saturating_duration_since(Instant::now());
+            match messages.recv_timeout(timeout) {
+                Ok(BatchMessage::ExportSpan(span)) => {
+                    self.spans.push(span);
+
+                    if self.spans.len() >= self.config.max_export_batch_size {
+                        if let Err(err) = self.export() {
+                            global::handle_error(err);
+                        }
+                    }
+                }
+                Ok(BatchMessage::Flush(ch)) => {
+                    let result = self.export();
+                    next_export = Instant::now() + self.config.scheduled_delay;
+
+                    if let Err(result) = ch.send(result) {
+                        global::handle_error(TraceError::from(format!(
+                            "failed to send flush result: {:?}",
+                            result
+                        )));
+                    }
+                }
+                Ok(BatchMessage::Shutdown(ch)) => {
+                    let result = self.export();
+                    self.exporter.shutdown();
+
+                    if let Err(result) = ch.send(result) {
+                        global::handle_error(TraceError::from(format!(
+                            "failed to send batch processor shutdown result: {:?}",
+                            result
+                        )));
+                    }
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Err(err) = self.export() {
+                        global::handle_error(err);
+                    }
+                    next_export = Instant::now() + self.config.scheduled_delay;
+                }
+                // The processor was dropped without being shut down.
+                Err(RecvTimeoutError::Disconnected) => {
+                    if let Err(err) = self.export() {
+                        global::handle_error(err);
+                    }
+                    self.exporter.shutdown();
+                    break;
+                }
+            }
+        }
+    }
+
+    fn export(&mut self) -> ExportResult {
+        // Batch size check for flush / shutdown. Those methods may be called
+        // when there's no work to do.
+        if self.spans.is_empty() {
+            return Ok(());
+        }
+
+        let batch_size = self.spans.len();
+        self.metrics.batch(batch_size);
+        let start = Instant::now();
+
+        let export = self.exporter.export(self.spans.split_off(0));
+        let result = block_on_with_timeout(export, self.config.max_export_timeout).unwrap_or(Err(
+            TraceError::ExportTimedOut(self.config.max_export_timeout),
+        ));
+
+        self.metrics
+            .exporter
+            .exported(batch_size, result.is_ok(), start.elapsed());
+        result
+    }
+}
+
+/// A builder for creating [`ThreadedBatchSpanProcessor`] instances.
+#[derive(Debug)]
+pub struct ThreadedBatchSpanProcessorBuilder<E> {
+    exporter: E,
+    config: BatchConfig,
+    metrics: ProcessorMetrics,
+}
+
+impl<E> ThreadedBatchSpanProcessorBuilder<E>
+where
+    E: SpanExporter + 'static,
+{
+    /// Set the BatchConfig for [ThreadedBatchSpanProcessorBuilder].
+    ///
+    /// `max_concurrent_exports` is ignored, the batches are exported one after
+    /// the other.
+    pub fn with_batch_config(self, config: BatchConfig) -> Self {
+        ThreadedBatchSpanProcessorBuilder { config, ..self }
+    }
+
+    /// Record metrics about the processor and its exporter with `meter_provider`.
+    ///
+    /// The instruments are the ones of
+    /// [`BatchSpanProcessorBuilder::with_self_observability`].
+    ///
+    /// [`BatchSpanProcessorBuilder::with_self_observability`]: crate::trace::BatchSpanProcessorBuilder::with_self_observability
+    #[cfg(feature = "metrics")]
+    pub fn with_self_observability<P>(self, meter_provider: &P) -> Self
+    where
+        P: opentelemetry::metrics::MeterProvider,
+    {
+        ThreadedBatchSpanProcessorBuilder {
+            metrics: ProcessorMetrics::new(
+                meter_provider,
+                crate::self_observability::ProcessorSignal::Traces,
+            ),
+            ..self
+        }
+    }
+
+    /// Build a threaded batch processor, spawning its export thread.
+    pub fn build(self) -> ThreadedBatchSpanProcessor {
+        ThreadedBatchSpanProcessor::new(Box::new(self.exporter), self.config, self.metrics)
+    }
+}
+
+#[cfg(all(test, feature = "testing", feature = "trace"))]
+mod tests {
+    use super::ThreadedBatchSpanProcessor;
+    use crate::export::trace::{ExportResult, SpanData, SpanExporter};
+    use crate::testing::trace::{new_test_export_span_data, InMemorySpanExporterBuilder};
+    use crate::trace::{BatchConfigBuilder, SpanProcessor};
+    use futures_util::future::BoxFuture;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn exports_full_batches_and_flushes() {
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let processor = ThreadedBatchSpanProcessor::builder(exporter.clone())
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_max_export_batch_size(2)
+                    .with_scheduled_delay(Duration::from_secs(3600))
+                    .build(),
+            )
+            .build();
+
+        for _ in 0..3 {
+            processor.on_end(new_test_export_span_data());
+        }
+        processor.force_flush().expect("flush should succeed");
+
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn exports_on_scheduled_delay() {
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let processor = ThreadedBatchSpanProcessor::builder(exporter.clone())
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_scheduled_delay(Duration::from_millis(10))
+                    .build(),
+            )
+            .build();
+
+        processor.on_end(new_test_export_span_data());
+        std::thread::sleep(Duration::from_millis(200));
+
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn shutdown_exports_and_stops_the_thread() {
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let mut processor = ThreadedBatchSpanProcessor::builder(exporter.clone()).build();
+
+        processor.on_end(new_test_export_span_data());
+        processor.shutdown().expect("shutdown should succeed");
+
+        assert!(processor.handle.is_none());
+        assert!(processor.force_flush().is_err());
+        assert!(processor.shutdown().is_err());
+    }
+
+    /// Counts the exported spans, which `InMemorySpanExporter` clears on shutdown.
+    #[derive(Debug)]
+    struct CountingExporter(Arc<AtomicUsize>);
+
+    impl SpanExporter for CountingExporter {
+        fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+            self.0.fetch_add(batch.len(), Ordering::SeqCst);
+            Box::pin(futures_util::future::ready(Ok(())))
+        }
+    }
+
+    #[test]
+    fn drop_exports_the_queued_spans() {
+        let exported = Arc::new(AtomicUsize::new(0));
+        let processor = ThreadedBatchSpanProcessor::builder(CountingExporter(exported.clone()))
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_scheduled_delay(Duration::from_secs(3600))
+                    .build(),
+            )
+            .build();
+
+        processor.on_end(new_test_export_span_data());
+        drop(processor);
+
+        assert_eq!(exported.load(Ordering::SeqCst), 1);
+    }
+
+    #[derive(Debug)]
+    struct PendingExporter;
+
+    impl SpanExporter for PendingExporter {
+        fn export(&mut self, _batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+            Box::pin(futures_util::future::pending())
+        }
+    }
+
+    #[test]
+    fn export_times_out() {
+        let processor = ThreadedBatchSpanProcessor::builder(PendingExporter)
+            .with_batch_config(
+                BatchConfigBuilder::default()
+                    .with_max_export_timeout(Duration::from_millis(10))
+                    .build(),
+            )
+            .build();
+
+        processor.on_end(new_test_export_span_data());
+
+        assert!(processor.force_flush().is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn flush_in_current_thread_runtime() {
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let mut processor = ThreadedBatchSpanProcessor::builder(exporter.clone()).build();
+
+        processor.on_end(new_test_export_span_data());
+        processor.force_flush().expect("flush should succeed");
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 1);
+
+        processor.shutdown().expect("shutdown should succeed");
+    }
+}
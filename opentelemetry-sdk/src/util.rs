@@ -7,3 +7,59 @@ pub fn tokio_interval_stream(
 ) -> tokio_stream::wrappers::IntervalStream {
     tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(period))
 }
+
+/// Runs `future` to completion on the current thread, giving up once `timeout`
+/// has elapsed.
+///
+/// Returns `None` if the future didn't complete in time. It is used by the
+/// components exporting on a dedicated thread, which have no runtime to race
+/// the export against a timer.
+#[cfg(any(feature = "trace", feature = "logs", feature = "metrics"))]
+pub(crate) fn block_on_with_timeout<F: std::future::Future>(
+    future: F,
+    timeout: std::time::Duration,
+) -> Option<F::Output> {
+    use futures_util::task::{waker, ArcWake};
+    use std::{
+        sync::Arc,
+        task::{Context, Poll},
+        thread::{self, Thread},
+        time::Instant,
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl ArcWake for ThreadWaker {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.unpark();
+        }
+    }
+
+    let deadline = Instant::now() + timeout;
+    let waker = waker(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    futures_util::pin_mut!(future);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return Some(output);
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return None;
+        }
+        // Spurious wake ups only cause an extra poll.
+        thread::park_timeout(deadline - now);
+    }
+}
+
+/// Returns the first `max_length` characters of `value`, or `None` if it isn't
+/// longer than that.
+#[cfg(any(feature = "trace", feature = "logs"))]
+pub(crate) fn truncated(value: &str, max_length: usize) -> Option<&str> {
+    value
+        .char_indices()
+        .nth(max_length)
+        .map(|(end, _)| &value[..end])
+}
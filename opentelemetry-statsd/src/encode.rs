@@ -0,0 +1,564 @@
+//! Encoding of metrics as StatsD lines and packing of the lines in packets.
+use opentelemetry_sdk::{
+    metrics::data::{
+        self, ExponentialBucket, ExponentialHistogramDataPoint, HistogramDataPoint,
+        ResourceMetrics, Temporality,
+    },
+    AttributeSet,
+};
+use std::fmt::{self, Display, Write};
+
+use crate::Flavor;
+
+/// Encodes metrics as StatsD lines.
+#[derive(Debug)]
+pub(crate) struct Encoder {
+    pub(crate) flavor: Flavor,
+    pub(crate) prefix: Option<String>,
+}
+
+impl Encoder {
+    /// The lines of all metrics.
+    pub(crate) fn encode(&self, metrics: &ResourceMetrics) -> Vec<String> {
+        let mut lines = Vec::new();
+        for scope_metrics in &metrics.scope_metrics {
+            for metric in &scope_metrics.metrics {
+                let name = self.name(metric);
+                let data = metric.data.as_any();
+                if let Some(sum) = data.downcast_ref::<data::Sum<u64>>() {
+                    self.encode_sum(&mut lines, &name, sum);
+                } else if let Some(sum) = data.downcast_ref::<data::Sum<i64>>() {
+                    self.encode_sum(&mut lines, &name, sum);
+                } else if let Some(sum) = data.downcast_ref::<data::Sum<f64>>() {
+                    self.encode_sum(&mut lines, &name, sum);
+                } else if let Some(gauge) = data.downcast_ref::<data::Gauge<u64>>() {
+                    self.encode_gauge(&mut lines, &name, &gauge.data_points);
+                } else if let Some(gauge) = data.downcast_ref::<data::Gauge<i64>>() {
+                    self.encode_gauge(&mut lines, &name, &gauge.data_points);
+                } else if let Some(gauge) = data.downcast_ref::<data::Gauge<f64>>() {
+                    self.encode_gauge(&mut lines, &name, &gauge.data_points);
+                } else if let Some(hist) = data.downcast_ref::<data::Histogram<u64>>() {
+                    self.encode_histogram(&mut lines, &name, &metric.unit, hist);
+                } else if let Some(hist) = data.downcast_ref::<data::Histogram<i64>>() {
+                    self.encode_histogram(&mut lines, &name, &metric.unit, hist);
+                } else if let Some(hist) = data.downcast_ref::<data::Histogram<f64>>() {
+                    self.encode_histogram(&mut lines, &name, &metric.unit, hist);
+                } else if let Some(hist) = data.downcast_ref::<data::ExponentialHistogram<u64>>() {
+                    self.encode_exponential_histogram(&mut lines, &name, &metric.unit, hist);
+                } else if let Some(hist) = data.downcast_ref::<data::ExponentialHistogram<i64>>() {
+                    self.encode_exponential_histogram(&mut lines, &name, &metric.unit, hist);
+                } else if let Some(hist) = data.downcast_ref::<data::ExponentialHistogram<f64>>() {
+                    self.encode_exponential_histogram(&mut lines, &name, &metric.unit, hist);
+                }
+            }
+        }
+        lines
+    }
+
+    fn name(&self, metric: &data::Metric) -> String {
+        let name = sanitize(&metric.name, &[':', '|', '@', '\n']);
+        match &self.prefix {
+            Some(prefix) => format!("{prefix}.{name}"),
+            None => name,
+        }
+    }
+
+    fn encode_sum<T: Numeric>(&self, lines: &mut Vec<String>, name: &str, sum: &data::Sum<T>) {
+        for dp in &sum.data_points {
+            match sum.temporality {
+                Temporality::Delta => {
+                    lines.push(self.line(name, &dp.value, "c", None, &dp.attributes))
+                }
+                // A cumulative sum is the current value of what it counts.
+                _ => self.encode_gauge(lines, name, std::slice::from_ref(dp)),
+            }
+        }
+    }
+
+    fn encode_gauge<T: Numeric>(
+        &self,
+        lines: &mut Vec<String>,
+        name: &str,
+        data_points: &[data::DataPoint<T>],
+    ) {
+        for dp in data_points {
+            // StatsD reads signed gauge values as changes of the current value,
+            // negative values are set by resetting the gauge first.
+            if self.flavor == Flavor::Statsd && dp.value.as_f64() < 0.0 {
+                lines.push(self.line(name, &0, "g", None, &dp.attributes));
+            }
+            lines.push(self.line(name, &dp.value, "g", None, &dp.attributes));
+        }
+    }
+
+    fn encode_histogram<T: Numeric>(
+        &self,
+        lines: &mut Vec<String>,
+        name: &str,
+        unit: &opentelemetry::metrics::Unit,
+        histogram: &data::Histogram<T>,
+    ) {
+        for dp in &histogram.data_points {
+            let samples = histogram_samples(dp);
+            self.encode_samples(lines, name, unit, samples, &dp.attributes);
+        }
+    }
+
+    fn encode_exponential_histogram<T: Numeric>(
+        &self,
+        lines: &mut Vec<String>,
+        name: &str,
+        unit: &opentelemetry::metrics::Unit,
+        histogram: &data::ExponentialHistogram<T>,
+    ) {
+        for dp in &histogram.data_points {
+            let samples = exponential_histogram_samples(dp);
+            self.encode_samples(lines, name, unit, samples, &dp.attributes);
+        }
+    }
+
+    /// Encodes the samples of a histogram as timers or distributions, each
+    /// with the sample rate of its count.
+    fn encode_samples(
+        &self,
+        lines: &mut Vec<String>,
+        name: &str,
+        unit: &opentelemetry::metrics::Unit,
+        samples: Vec<(f64, u64)>,
+        attributes: &AttributeSet,
+    ) {
+        let (metric_type, scale) = match self.flavor {
+            // Timers are in milliseconds.
+            Flavor::Statsd => ("ms", milliseconds(unit.as_str()).unwrap_or(1.0)),
+            Flavor::DogStatsd => ("d", 1.0),
+        };
+        for (value, count) in samples {
+            let sample_rate = (count > 1).then(|| 1.0 / count as f64);
+            lines.push(self.line(name, &(value * scale), metric_type, sample_rate, attributes));
+        }
+    }
+
+    fn line(
+        &self,
+        name: &str,
+        value: &dyn Display,
+        metric_type: &str,
+        sample_rate: Option<f64>,
+        attributes: &AttributeSet,
+    ) -> String {
+        let mut line = format!("{name}:{value}|{metric_type}");
+        if let Some(sample_rate) = sample_rate {
+            let _ = write!(line, "|@{sample_rate}");
+        }
+        // Plain StatsD has no tags.
+        if self.flavor == Flavor::DogStatsd {
+            let mut separator = "|#";
+            for (key, value) in attributes.iter() {
+                let tag = sanitize(&format!("{key}:{value}"), &[',', '|', '\n']);
+                let _ = write!(line, "{separator}{tag}");
+                separator = ",";
+            }
+        }
+        line
+    }
+}
+
+/// The values of a histogram, each bucket being represented by the middle of
+/// its range, clamped to the minimum and maximum, with its count.
+fn histogram_samples<T: Numeric>(dp: &HistogramDataPoint<T>) -> Vec<(f64, u64)> {
+    let ranges = dp.bucket_counts.iter().enumerate().map(|(i, count)| {
+        let lower = i.checked_sub(1).and_then(|i| dp.bounds.get(i)).copied();
+        (lower, dp.bounds.get(i).copied(), *count)
+    });
+    samples(ranges, dp.min.as_ref(), dp.max.as_ref(), &dp.sum, dp.count)
+}
+
+/// The values of an exponential histogram, each bucket being represented by
+/// the middle of its range, clamped to the minimum and maximum, with its count.
+fn exponential_histogram_samples<T: Numeric>(
+    dp: &ExponentialHistogramDataPoint<T>,
+) -> Vec<(f64, u64)> {
+    let base = 2f64.powf(2f64.powi(-(dp.scale as i32)));
+    let ranges = |bucket: &'_ ExponentialBucket| {
+        bucket
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(i, count)| {
+                let index = bucket.offset + i as i32;
+                (base.powi(index), base.powi(index + 1), *count)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The negative bucket `i` is `[-base^(i+1), -base^i)`, the positive bucket
+    // `i` is `(base^i, base^(i+1)]`.
+    let negative = ranges(&dp.negative_bucket)
+        .into_iter()
+        .rev()
+        .map(|(lower, upper, count)| (Some(-upper), Some(-lower), count));
+    let zero = std::iter::once((Some(0.0), Some(0.0), dp.zero_count));
+    let positive = ranges(&dp.positive_bucket)
+        .into_iter()
+        .map(|(lower, upper, count)| (Some(lower), Some(upper), count));
+    samples(
+        negative.chain(zero).chain(positive),
+        dp.min.as_ref(),
+        dp.max.as_ref(),
+        &dp.sum,
+        dp.count as u64,
+    )
+}
+
+fn samples<T: Numeric>(
+    ranges: impl Iterator<Item = (Option<f64>, Option<f64>, u64)>,
+    min: Option<&T>,
+    max: Option<&T>,
+    sum: &T,
+    count: u64,
+) -> Vec<(f64, u64)> {
+    let min = min.map(Numeric::as_f64);
+    let max = max.map(Numeric::as_f64);
+    ranges
+        .filter(|(_, _, count)| *count > 0)
+        .map(|(lower, upper, bucket_count)| {
+            let lower = match (lower, min) {
+                (Some(lower), Some(min)) => Some(lower.max(min)),
+                (lower, min) => lower.or(min),
+            };
+            let upper = match (upper, max) {
+                (Some(upper), Some(max)) => Some(upper.min(max)),
+                (upper, max) => upper.or(max),
+            };
+            let value = match (lower, upper) {
+                (Some(lower), Some(upper)) => (lower + upper) / 2.0,
+                (Some(bound), None) | (None, Some(bound)) => bound,
+                (None, None) => sum.as_f64() / count as f64,
+            };
+            (value, bucket_count)
+        })
+        .collect()
+}
+
+/// The number of milliseconds in the time unit `unit`.
+fn milliseconds(unit: &str) -> Option<f64> {
+    match unit {
+        "ns" => Some(1e-6),
+        "us" => Some(1e-3),
+        "ms" => Some(1.0),
+        "s" => Some(1e3),
+        "min" => Some(60e3),
+        "h" => Some(3600e3),
+        "d" => Some(86400e3),
+        _ => None,
+    }
+}
+
+fn sanitize(s: &str, reserved: &[char]) -> String {
+    s.chars()
+        .map(|c| if reserved.contains(&c) { '_' } else { c })
+        .collect()
+}
+
+/// Packs the lines in packets of at most `max_packet_size` bytes.
+///
+/// Without `auto_split`, all lines must fit in one packet. With it, the lines
+/// which don't fit in a packet on their own are skipped and counted.
+pub(crate) fn packets(
+    lines: &[String],
+    max_packet_size: usize,
+    auto_split: bool,
+) -> (Vec<Vec<u8>>, Result<(), PacketTooLong>) {
+    if !auto_split {
+        let packet = lines.join("\n").into_bytes();
+        if packet.len() > max_packet_size {
+            return (
+                Vec::new(),
+                Err(PacketTooLong {
+                    size: packet.len(),
+                    dropped: lines.len(),
+                }),
+            );
+        }
+        return (vec![packet], Ok(()));
+    }
+
+    let mut packets = Vec::new();
+    let mut packet: Vec<u8> = Vec::with_capacity(max_packet_size);
+    let mut too_long: Option<PacketTooLong> = None;
+    for line in lines {
+        if line.len() > max_packet_size {
+            let too_long = too_long.get_or_insert(PacketTooLong {
+                size: 0,
+                dropped: 0,
+            });
+            too_long.size = too_long.size.max(line.len());
+            too_long.dropped += 1;
+            continue;
+        }
+        if !packet.is_empty() && packet.len() + 1 + line.len() > max_packet_size {
+            packets.push(std::mem::replace(
+                &mut packet,
+                Vec::with_capacity(max_packet_size),
+            ));
+        }
+        if !packet.is_empty() {
+            packet.push(b'\n');
+        }
+        packet.extend_from_slice(line.as_bytes());
+    }
+    if !packet.is_empty() {
+        packets.push(packet);
+    }
+
+    (packets, too_long.map_or(Ok(()), Err))
+}
+
+/// Lines dropped because they don't fit in a packet.
+#[derive(Debug, PartialEq)]
+pub(crate) struct PacketTooLong {
+    /// The size of the largest packet.
+    pub(crate) size: usize,
+    /// The number of dropped lines.
+    pub(crate) dropped: usize,
+}
+
+pub(crate) trait Numeric: Display + fmt::Debug {
+    // lossy at large values for u64 and i64 but StatsD only handles floats
+    fn as_f64(&self) -> f64;
+}
+
+impl Numeric for u64 {
+    fn as_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl Numeric for i64 {
+    fn as_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl Numeric for f64 {
+    fn as_f64(&self) -> f64 {
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::{
+        metrics::data::{DataPoint, Gauge, Histogram, Metric, ScopeMetrics, Sum},
+        Resource, Scope,
+    };
+    use std::time::SystemTime;
+
+    fn resource_metrics(
+        data: Vec<(&'static str, &'static str, Box<dyn data::Aggregation>)>,
+    ) -> ResourceMetrics {
+        ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: vec![ScopeMetrics {
+                scope: Scope::default(),
+                metrics: data
+                    .into_iter()
+                    .map(|(name, unit, data)| Metric {
+                        name: name.into(),
+                        description: "".into(),
+                        unit: opentelemetry::metrics::Unit::new(unit),
+                        data,
+                    })
+                    .collect(),
+            }],
+        }
+    }
+
+    fn data_point<T>(value: T, attributes: &[KeyValue]) -> DataPoint<T> {
+        DataPoint {
+            attributes: AttributeSet::from(attributes),
+            start_time: None,
+            time: None,
+            value,
+            exemplars: Vec::new(),
+        }
+    }
+
+    fn histogram_data_point(
+        bounds: Vec<f64>,
+        bucket_counts: Vec<u64>,
+        min: f64,
+        max: f64,
+        sum: f64,
+    ) -> HistogramDataPoint<f64> {
+        HistogramDataPoint {
+            attributes: AttributeSet::from(&[][..]),
+            start_time: SystemTime::now(),
+            time: SystemTime::now(),
+            count: bucket_counts.iter().sum(),
+            bounds,
+            bucket_counts,
+            min: Some(min),
+            max: Some(max),
+            sum,
+            exemplars: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let metrics = resource_metrics(vec![
+            (
+                "requests",
+                "",
+                Box::new(Sum {
+                    data_points: vec![data_point(3u64, &[KeyValue::new("method", "GET")])],
+                    temporality: Temporality::Delta,
+                    is_monotonic: true,
+                }),
+            ),
+            (
+                "connections",
+                "",
+                Box::new(Sum {
+                    data_points: vec![data_point(5i64, &[])],
+                    temporality: Temporality::Cumulative,
+                    is_monotonic: false,
+                }),
+            ),
+            (
+                "temperature|celsius",
+                "",
+                Box::new(Gauge {
+                    data_points: vec![data_point(
+                        -1.5,
+                        &[KeyValue::new("room", "a,b"), KeyValue::new("floor", 1)],
+                    )],
+                }),
+            ),
+            (
+                "latency",
+                "s",
+                Box::new(Histogram {
+                    data_points: vec![histogram_data_point(
+                        vec![0.1, 1.0],
+                        vec![1, 0, 2],
+                        0.05,
+                        3.0,
+                        5.05,
+                    )],
+                    temporality: Temporality::Delta,
+                }),
+            ),
+        ]);
+
+        for (flavor, expected) in [
+            (
+                Flavor::DogStatsd,
+                vec![
+                    "app.requests:3|c|#method:GET",
+                    "app.connections:5|g",
+                    "app.temperature_celsius:-1.5|g|#floor:1,room:a_b",
+                    "app.latency:0.07500000000000001|d",
+                    "app.latency:2|d|@0.5",
+                ],
+            ),
+            (
+                Flavor::Statsd,
+                vec![
+                    "app.requests:3|c",
+                    "app.connections:5|g",
+                    "app.temperature_celsius:0|g",
+                    "app.temperature_celsius:-1.5|g",
+                    "app.latency:75.00000000000001|ms",
+                    "app.latency:2000|ms|@0.5",
+                ],
+            ),
+        ] {
+            let encoder = Encoder {
+                flavor,
+                prefix: Some("app".into()),
+            };
+            assert_eq!(encoder.encode(&metrics), expected, "{flavor:?}");
+        }
+    }
+
+    #[test]
+    fn test_histogram_samples() {
+        for (name, dp, expected) in [
+            (
+                "clamped to min and max",
+                histogram_data_point(vec![0.0, 10.0], vec![0, 2, 1], 2.0, 50.0, 58.0),
+                vec![(6.0, 2), (30.0, 1)],
+            ),
+            (
+                "no bounds",
+                histogram_data_point(vec![], vec![4], 1.0, 3.0, 8.0),
+                vec![(2.0, 4)],
+            ),
+        ] {
+            assert_eq!(histogram_samples(&dp), expected, "{name}");
+        }
+
+        let dp = ExponentialHistogramDataPoint {
+            attributes: AttributeSet::from(&[][..]),
+            start_time: SystemTime::now(),
+            time: SystemTime::now(),
+            count: 4,
+            min: Some(-3.0),
+            max: Some(4.0),
+            sum: 3.0,
+            scale: 0,
+            zero_count: 1,
+            positive_bucket: ExponentialBucket {
+                offset: 0,
+                counts: vec![1, 1],
+            },
+            negative_bucket: ExponentialBucket {
+                offset: 1,
+                counts: vec![1],
+            },
+            zero_threshold: 0.0,
+            exemplars: Vec::new(),
+        };
+        assert_eq!(
+            exponential_histogram_samples(&dp),
+            vec![(-2.5, 1), (0.0, 1), (1.5, 1), (3.0, 1)]
+        );
+    }
+
+    #[test]
+    fn test_packets() {
+        let lines: Vec<String> = ["a:1|c", "b:2|c", "c:3|c", "too_long:4|c"]
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+
+        assert_eq!(
+            packets(&lines[..3], 100, false),
+            (vec![b"a:1|c\nb:2|c\nc:3|c".to_vec()], Ok(()))
+        );
+        assert_eq!(
+            packets(&lines[..3], 10, false),
+            (
+                vec![],
+                Err(PacketTooLong {
+                    size: 17,
+                    dropped: 3
+                })
+            )
+        );
+        assert_eq!(
+            packets(&lines, 11, true),
+            (
+                vec![b"a:1|c\nb:2|c".to_vec(), b"c:3|c".to_vec()],
+                Err(PacketTooLong {
+                    size: 12,
+                    dropped: 1
+                })
+            )
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use async_trait::async_trait;
+use opentelemetry::{
+    global,
+    metrics::{MetricsError, Result},
+    ExportError,
+};
+use opentelemetry_sdk::metrics::{
+    data::{ResourceMetrics, Temporality},
+    exporter::PushMetricsExporter,
+    reader::{AggregationSelector, DefaultAggregationSelector, TemporalitySelector},
+    Aggregation, InstrumentKind,
+};
+use std::{
+    fmt, io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::{Arc, Mutex},
+};
+
+use crate::encode::{packets, Encoder};
+
+/// The address of the local StatsD agent.
+const DEFAULT_ENDPOINT: &str = "127.0.0.1:8125";
+
+/// The max size of UDP packet we want to send, fitting in the Ethernet MTU.
+const DEFAULT_MAX_PACKET_SIZE: usize = 1_432;
+
+/// The format of the metrics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Flavor {
+    /// The [StatsD] format: histograms are sent as timers and attributes are
+    /// dropped.
+    ///
+    /// [StatsD]: https://github.com/statsd/statsd/blob/master/docs/metric_types.md
+    Statsd,
+    /// The [DogStatsD] format: histograms are sent as distributions and
+    /// attributes as tags.
+    ///
+    /// [DogStatsD]: https://docs.datadoghq.com/developers/dogstatsd/datagram_shell/
+    #[default]
+    DogStatsd,
+}
+
+/// Exports metrics to a StatsD or DogStatsD agent over UDP.
+///
+/// The metrics are collected with delta temporality and sent as:
+///
+/// - counters, for sums.
+/// - gauges, for gauges.
+/// - timers or distributions, depending on the [Flavor], for histograms and
+///   exponential histograms. Each bucket is sent as a single value, the middle
+///   of the bucket clamped to the minimum and maximum of the histogram, with
+///   the sample rate of the bucket count.
+///
+/// ## UDP packet max length
+///
+/// The lines of the metrics are packed in UDP packets of at most
+/// [`with_max_packet_size`] bytes, `1432` by default to fit in the Ethernet
+/// MTU. By default, the metrics of an export are split in as many packets as
+/// needed, see [`with_auto_split_batch`].
+///
+/// The socket is non-blocking: the packets which can't be sent right away,
+/// like the ones failing to be sent, are dropped and counted in the error of
+/// the export.
+///
+/// [`with_max_packet_size`]: StatsdExporterBuilder::with_max_packet_size
+/// [`with_auto_split_batch`]: StatsdExporterBuilder::with_auto_split_batch
+pub struct StatsdExporter {
+    socket: Mutex<Option<Arc<UdpSocket>>>,
+    encoder: Encoder,
+    max_packet_size: usize,
+    auto_split_batch: bool,
+    aggregation_selector: Box<dyn AggregationSelector>,
+}
+
+impl StatsdExporter {
+    /// Create a builder to configure this exporter.
+    pub fn builder() -> StatsdExporterBuilder {
+        StatsdExporterBuilder::default()
+    }
+}
+
+impl fmt::Debug for StatsdExporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatsdExporter")
+            .field("socket", &self.socket)
+            .field("encoder", &self.encoder)
+            .field("max_packet_size", &self.max_packet_size)
+            .field("auto_split_batch", &self.auto_split_batch)
+            .finish()
+    }
+}
+
+impl TemporalitySelector for StatsdExporter {
+    /// Note: StatsD aggregates the values it receives so this will always be
+    /// [Temporality::Delta].
+    fn temporality(&self, _kind: InstrumentKind) -> Temporality {
+        Temporality::Delta
+    }
+}
+
+impl AggregationSelector for StatsdExporter {
+    fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+        self.aggregation_selector.aggregation(kind)
+    }
+}
+
+#[async_trait]
+impl PushMetricsExporter for StatsdExporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> Result<()> {
+        let socket = self.socket.lock()?.clone().ok_or(Error::ShutDown)?;
+
+        let lines = self.encoder.encode(metrics);
+        let (packets, too_long) = packets(&lines, self.max_packet_size, self.auto_split_batch);
+        let too_long = too_long.map_err(|too_long| Error::PacketTooLong {
+            size: too_long.size,
+            max_packet_size: self.max_packet_size,
+            dropped: too_long.dropped,
+        });
+
+        let total = packets.len();
+        let mut failed = 0;
+        let mut last_error = None;
+        for packet in packets {
+            if let Err(err) = socket.send(&packet) {
+                failed += 1;
+                last_error = Some(err);
+            }
+        }
+
+        match last_error {
+            Some(error) => {
+                if let Err(err) = too_long {
+                    global::handle_error(MetricsError::from(err));
+                }
+                Err(Error::Send {
+                    failed,
+                    total,
+                    error,
+                }
+                .into())
+            }
+            None => too_long.map_err(Into::into),
+        }
+    }
+
+    async fn force_flush(&self) -> Result<()> {
+        // exporter holds no state, nothing to flush
+        Ok(())
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        self.socket.lock()?.take();
+        Ok(())
+    }
+}
+
+/// [StatsdExporter] configuration options
+pub struct StatsdExporterBuilder {
+    endpoint: String,
+    flavor: Flavor,
+    prefix: Option<String>,
+    max_packet_size: usize,
+    auto_split_batch: bool,
+    aggregation_selector: Option<Box<dyn AggregationSelector>>,
+}
+
+impl Default for StatsdExporterBuilder {
+    fn default() -> Self {
+        StatsdExporterBuilder {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            flavor: Flavor::default(),
+            prefix: None,
+            max_packet_size: DEFAULT_MAX_PACKET_SIZE,
+            auto_split_batch: true,
+            aggregation_selector: None,
+        }
+    }
+}
+
+impl fmt::Debug for StatsdExporterBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatsdExporterBuilder")
+            .field("endpoint", &self.endpoint)
+            .field("flavor", &self.flavor)
+            .field("prefix", &self.prefix)
+            .field("max_packet_size", &self.max_packet_size)
+            .field("auto_split_batch", &self.auto_split_batch)
+            .finish()
+    }
+}
+
+impl StatsdExporterBuilder {
+    /// Set the address of the agent.
+    ///
+    /// Any value resolving to a socket address can be used.
+    ///
+    /// Default to be `127.0.0.1:8125`.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Set the format of the metrics.
+    ///
+    /// Default to be [Flavor::DogStatsd].
+    pub fn with_flavor(mut self, flavor: Flavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+
+    /// Set a prefix of the metric names, separated from the names by a `.`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Assign the max packet size in bytes.
+    ///
+    /// It should be consistent with the limit of platforms and agents.
+    ///
+    /// Default to be `1432`.
+    pub fn with_max_packet_size(mut self, max_packet_size: usize) -> Self {
+        self.max_packet_size = max_packet_size;
+        self
+    }
+
+    /// Config whether to auto split batches.
+    ///
+    /// When auto split is set to `true`, the metrics of an export are split in
+    /// as many packets as needed, and only the metrics longer than the
+    /// `max_packet_size` on their own are dropped. When set to `false`, the
+    /// metrics of an export are sent in a single packet, and all of them are
+    /// dropped if they don't fit in it.
+    ///
+    /// Both cases return an error when metrics are dropped.
+    ///
+    /// Default to be `true`.
+    pub fn with_auto_split_batch(mut self, should_auto_split: bool) -> Self {
+        self.auto_split_batch = should_auto_split;
+        self
+    }
+
+    /// Configure the [AggregationSelector] the exporter will use.
+    ///
+    /// If no selector is provided, the [DefaultAggregationSelector] is used.
+    pub fn with_aggregation_selector(mut self, agg: impl AggregationSelector + 'static) -> Self {
+        self.aggregation_selector = Some(Box::new(agg));
+        self
+    }
+
+    /// Create a [StatsdExporter] with the current configuration.
+    ///
+    /// Returns an error if the endpoint can't be resolved or the socket can't
+    /// be created.
+    pub fn build(self) -> std::result::Result<StatsdExporter, Error> {
+        let addrs: Vec<SocketAddr> = self
+            .endpoint
+            .to_socket_addrs()
+            .map_err(|_| Error::InvalidEndpoint(self.endpoint.clone()))?
+            .collect();
+        let local_addr = match addrs.first() {
+            Some(SocketAddr::V4(_)) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            Some(SocketAddr::V6(_)) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+            None => return Err(Error::InvalidEndpoint(self.endpoint)),
+        };
+        let socket = UdpSocket::bind(local_addr)?;
+        socket.connect(addrs.as_slice())?;
+        socket.set_nonblocking(true)?;
+
+        Ok(StatsdExporter {
+            socket: Mutex::new(Some(Arc::new(socket))),
+            encoder: Encoder {
+                flavor: self.flavor,
+                prefix: self.prefix,
+            },
+            max_packet_size: self.max_packet_size,
+            auto_split_batch: self.auto_split_batch,
+            aggregation_selector: self
+                .aggregation_selector
+                .unwrap_or_else(|| Box::new(DefaultAggregationSelector::new())),
+        })
+    }
+}
+
+/// Wrap type for errors from this crate.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The endpoint doesn't resolve to a socket address.
+    #[error("invalid endpoint {0}")]
+    InvalidEndpoint(String),
+
+    /// The socket can't be created.
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+
+    /// Packets couldn't be sent, the other packets of the export were sent.
+    #[error("{failed} of {total} packets couldn't be sent, the last error being: {error}")]
+    Send {
+        /// The number of packets which couldn't be sent.
+        failed: usize,
+        /// The number of packets of the export.
+        total: usize,
+        /// The error of the last packet which couldn't be sent.
+        #[source]
+        error: io::Error,
+    },
+
+    /// Metrics were dropped because they don't fit in a packet.
+    #[error("{dropped} metrics dropped because they take {size} bytes, more than the max packet size of {max_packet_size} bytes")]
+    PacketTooLong {
+        /// The size of the largest packet.
+        size: usize,
+        /// The max packet size.
+        max_packet_size: usize,
+        /// The number of dropped metrics.
+        dropped: usize,
+    },
+
+    /// The exporter is shut down.
+    #[error("exporter is already shut down")]
+    ShutDown,
+}
+
+impl ExportError for Error {
+    fn exporter_name(&self) -> &'static str {
+        "statsd"
+    }
+}
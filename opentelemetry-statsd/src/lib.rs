@@ -0,0 +1,61 @@
+//! # OpenTelemetry StatsD Exporter
+//!
+//! Exports metrics over UDP to [StatsD] or [DogStatsD] agents, for
+//! infrastructure ingesting StatsD metrics.
+//!
+//! The [StatsdExporter] is a push exporter, collecting the metrics with delta
+//! temporality: register it with a [PeriodicReader] to send the metrics at a
+//! fixed interval.
+//!
+//! ```no_run
+//! use opentelemetry::{metrics::MeterProvider, KeyValue};
+//! use opentelemetry_sdk::{
+//!     metrics::{PeriodicReader, SdkMeterProvider},
+//!     runtime,
+//! };
+//! use opentelemetry_statsd::{Flavor, StatsdExporter};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let exporter = StatsdExporter::builder()
+//!     .with_endpoint("localhost:8125")
+//!     .with_flavor(Flavor::DogStatsd)
+//!     .with_prefix("my_app")
+//!     .build()?;
+//! let reader = PeriodicReader::builder(exporter, runtime::Tokio).build();
+//! let provider = SdkMeterProvider::builder().with_reader(reader).build();
+//!
+//! let counter = provider.meter("my-component").u64_counter("requests").init();
+//!
+//! // sent as `my_app.requests:1|c|#method:GET`
+//! counter.add(1, &[KeyValue::new("method", "GET")]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [StatsD]: https://github.com/statsd/statsd
+//! [DogStatsD]: https://docs.datadoghq.com/developers/dogstatsd/
+//! [PeriodicReader]: opentelemetry_sdk::metrics::PeriodicReader
+#![warn(
+    future_incompatible,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    rust_2018_idioms,
+    unreachable_pub,
+    unused
+)]
+#![cfg_attr(
+    docsrs,
+    feature(doc_cfg, doc_auto_cfg),
+    deny(rustdoc::broken_intra_doc_links)
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/open-telemetry/opentelemetry-rust/main/assets/logo.svg"
+)]
+#![cfg_attr(test, deny(warnings))]
+
+mod encode;
+mod exporter;
+
+pub use exporter::{Error, Flavor, StatsdExporter, StatsdExporterBuilder};
@@ -0,0 +1,93 @@
+use std::{net::UdpSocket, time::Duration};
+
+use opentelemetry::{metrics::MeterProvider as _, KeyValue};
+use opentelemetry_sdk::{
+    metrics::{PeriodicReader, SdkMeterProvider},
+    runtime,
+};
+use opentelemetry_statsd::{Flavor, StatsdExporter};
+
+fn receive(agent: &UdpSocket) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut buffer = [0; 1_432];
+    while let Ok(size) = agent.recv(&mut buffer) {
+        let packet = std::str::from_utf8(&buffer[..size]).unwrap();
+        lines.extend(packet.lines().map(str::to_string));
+    }
+    lines.sort();
+    lines
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_to_agent() {
+    let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+    agent
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+
+    let exporter = StatsdExporter::builder()
+        .with_endpoint(agent.local_addr().unwrap().to_string())
+        .with_flavor(Flavor::DogStatsd)
+        .with_prefix("app")
+        .with_max_packet_size(64)
+        .build()
+        .unwrap();
+    let reader = PeriodicReader::builder(exporter, runtime::Tokio).build();
+    let provider = SdkMeterProvider::builder().with_reader(reader).build();
+    let meter = provider.meter("test");
+
+    let counter = meter.u64_counter("requests").init();
+    let histogram = meter.f64_histogram("latency").init();
+    counter.add(3, &[KeyValue::new("method", "GET")]);
+    counter.add(2, &[KeyValue::new("method", "POST")]);
+    histogram.record(7.0, &[]);
+    histogram.record(8.0, &[]);
+    provider.force_flush().unwrap();
+
+    assert_eq!(
+        receive(&agent),
+        vec![
+            "app.latency:7.5|d|@0.5",
+            "app.requests:2|c|#method:POST",
+            "app.requests:3|c|#method:GET",
+        ]
+    );
+
+    // Counters are sent as deltas.
+    counter.add(1, &[KeyValue::new("method", "GET")]);
+    provider.force_flush().unwrap();
+
+    assert_eq!(receive(&agent), vec!["app.requests:1|c|#method:GET"]);
+
+    provider.shutdown().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn send_errors_do_not_stop_the_export() {
+    // Nothing listens on the endpoint, so the packets following one sent to it
+    // fail with ECONNREFUSED, alternately.
+    let endpoint = UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let exporter = StatsdExporter::builder()
+        .with_endpoint(endpoint.to_string())
+        .with_max_packet_size(32)
+        .build()
+        .unwrap();
+    let reader = PeriodicReader::builder(exporter, runtime::Tokio).build();
+    let provider = SdkMeterProvider::builder().with_reader(reader).build();
+    let meter = provider.meter("test");
+
+    let counter = meter.u64_counter("requests").init();
+    for method in ["GET", "POST", "PUT"] {
+        counter.add(1, &[KeyValue::new("method", method)]);
+    }
+
+    // The packets following the failed one are still sent.
+    let err = provider.force_flush().unwrap_err();
+    assert!(err.to_string().contains("Send {"), "{err}");
+    assert!(err.to_string().contains("total: 3"), "{err}");
+
+    provider.shutdown().unwrap();
+}
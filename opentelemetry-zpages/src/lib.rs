@@ -0,0 +1,60 @@
+//! # OpenTelemetry zPages
+//!
+//! zPages are in-process web pages displaying telemetry collected by the
+//! OpenTelemetry SDK, without the need of an external backend. They are useful
+//! to debug a running service, e.g. to find the operations a stuck service is
+//! waiting on.
+//!
+//! This crate implements the [tracez] page. Register the [ZPagesSpanProcessor]
+//! on the tracer provider and query the collected spans with the matching
+//! [TracezQuerier]:
+//!
+//! - the number of running spans, of ended spans per latency bucket and of
+//!   ended spans with an error status, per span name.
+//! - samples of the running spans, of the ended spans of each latency bucket and
+//!   of the spans which ended with an error.
+//!
+//! ```
+//! use opentelemetry::{global, trace::Tracer};
+//! use opentelemetry_sdk::trace::TracerProvider;
+//!
+//! let (processor, querier) = opentelemetry_zpages::tracez(5);
+//! let provider = TracerProvider::builder()
+//!     .with_span_processor(processor)
+//!     .build();
+//! global::set_tracer_provider(provider);
+//!
+//! global::tracer("my-component").in_span("work", |_cx| {
+//!     assert_eq!(querier.aggregations()[0].running, 1);
+//! });
+//! ```
+//!
+//! ## Crate Feature Flags
+//!
+//! * `hyper`: provides [`TracezHandler`], which serves the tracez page as HTML
+//!   and the collected data as protobuf messages over HTTP.
+//!
+//! [tracez]: https://opencensus.io/zpages/#tracez
+//! [`TracezHandler`]: trace::TracezHandler
+#![warn(
+    future_incompatible,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    rust_2018_idioms,
+    unreachable_pub,
+    unused
+)]
+#![cfg_attr(
+    docsrs,
+    feature(doc_cfg, doc_auto_cfg),
+    deny(rustdoc::broken_intra_doc_links)
+)]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/open-telemetry/opentelemetry-rust/main/assets/logo.svg"
+)]
+#![cfg_attr(test, deny(warnings))]
+
+pub mod trace;
+
+pub use trace::{tracez, TracezQuerier, ZPagesSpanProcessor};
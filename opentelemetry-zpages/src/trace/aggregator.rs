@@ -0,0 +1,283 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, VecDeque},
+    time::Duration,
+};
+
+use opentelemetry::trace::{SpanId, Status};
+use opentelemetry_proto::tonic::tracez::v1::TracezCounts;
+use opentelemetry_sdk::export::trace::SpanData;
+
+/// Upper bounds (exclusive) of the latency buckets, the last bucket being
+/// unbounded.
+const LATENCY_BUCKET_BOUNDS: [Duration; LATENCY_BUCKET_COUNT - 1] = [
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+    Duration::from_secs(10),
+    Duration::from_secs(100),
+];
+
+/// The number of latency buckets ended spans are sorted into.
+pub(crate) const LATENCY_BUCKET_COUNT: usize = 9;
+
+/// The maximum number of span names the ended spans are counted and sampled
+/// for.
+pub(crate) const MAX_SPAN_NAMES: usize = 1000;
+
+/// The lower bound of each latency bucket, as displayed on the tracez page.
+#[cfg(feature = "hyper")]
+pub(crate) const LATENCY_BUCKET_NAMES: [&str; LATENCY_BUCKET_COUNT] = [
+    ">0us", ">10us", ">100us", ">1ms", ">10ms", ">100ms", ">1s", ">10s", ">100s",
+];
+
+/// Returns the index of the latency bucket of a span which lasted `latency`.
+pub(crate) fn latency_bucket(latency: Duration) -> usize {
+    LATENCY_BUCKET_BOUNDS.partition_point(|bound| *bound <= latency)
+}
+
+/// Collected ended spans of a span name.
+#[derive(Debug, Default)]
+pub(crate) struct SpanSummary {
+    pub(crate) latency_counts: [u32; LATENCY_BUCKET_COUNT],
+    pub(crate) latency_samples: [VecDeque<SpanData>; LATENCY_BUCKET_COUNT],
+    pub(crate) error_count: u32,
+    pub(crate) error_samples: VecDeque<SpanData>,
+}
+
+/// Keeps the running spans and samples of the ended spans, per span name.
+///
+/// Running spans are keyed by span ID, as their name can change before they
+/// end. Ended spans are only collected for the first [MAX_SPAN_NAMES] span
+/// names.
+#[derive(Debug)]
+pub(crate) struct SpanAggregator {
+    sample_size: usize,
+    running: HashMap<SpanId, SpanData>,
+    summaries: HashMap<Cow<'static, str>, SpanSummary>,
+}
+
+impl SpanAggregator {
+    pub(crate) fn new(sample_size: usize) -> Self {
+        SpanAggregator {
+            sample_size,
+            running: HashMap::new(),
+            summaries: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn start(&mut self, span: SpanData) {
+        self.running.insert(span.span_context.span_id(), span);
+    }
+
+    pub(crate) fn end(&mut self, span: SpanData) {
+        self.running.remove(&span.span_context.span_id());
+
+        let summaries_len = self.summaries.len();
+        let summary = match self.summaries.get_mut(&span.name) {
+            Some(summary) => summary,
+            None if summaries_len < MAX_SPAN_NAMES => {
+                self.summaries.entry(span.name.clone()).or_default()
+            }
+            None => return,
+        };
+
+        let samples = if matches!(span.status, Status::Error { .. }) {
+            summary.error_count = summary.error_count.saturating_add(1);
+            &mut summary.error_samples
+        } else {
+            let latency = span
+                .end_time
+                .duration_since(span.start_time)
+                .unwrap_or_default();
+            let bucket = latency_bucket(latency);
+            summary.latency_counts[bucket] = summary.latency_counts[bucket].saturating_add(1);
+            &mut summary.latency_samples[bucket]
+        };
+
+        if self.sample_size == 0 {
+            return;
+        }
+        if samples.len() == self.sample_size {
+            samples.pop_front();
+        }
+        samples.push_back(span);
+    }
+
+    /// Returns the counts of running, ended and failed spans, sorted by span name.
+    pub(crate) fn counts(&self) -> Vec<TracezCounts> {
+        let mut counts = self
+            .summaries
+            .iter()
+            .map(|(name, summary)| {
+                let counts = TracezCounts {
+                    spanname: name.to_string(),
+                    latency: summary.latency_counts.to_vec(),
+                    running: 0,
+                    error: summary.error_count,
+                };
+                (name.as_ref(), counts)
+            })
+            .collect::<BTreeMap<_, _>>();
+        for span in self.running.values() {
+            let counts = counts
+                .entry(span.name.as_ref())
+                .or_insert_with(|| TracezCounts {
+                    spanname: span.name.to_string(),
+                    latency: vec![0; LATENCY_BUCKET_COUNT],
+                    running: 0,
+                    error: 0,
+                });
+            counts.running = counts.running.saturating_add(1);
+        }
+        counts.into_values().collect()
+    }
+
+    /// Returns the running spans named `span_name`.
+    pub(crate) fn running<'a>(&'a self, span_name: &'a str) -> impl Iterator<Item = &'a SpanData> {
+        self.running
+            .values()
+            .filter(move |span| span.name == span_name)
+    }
+
+    pub(crate) fn summary(&self, span_name: &str) -> Option<&SpanSummary> {
+        self.summaries.get(span_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use opentelemetry::trace::{SpanContext, SpanKind, TraceFlags, TraceId, TraceState};
+    use opentelemetry_sdk::{
+        trace::{SpanEvents, SpanLinks},
+        Resource,
+    };
+
+    use super::*;
+
+    fn span(name: &'static str, id: u64, latency: Duration, status: Status) -> SpanData {
+        let start_time = SystemTime::now();
+        SpanData {
+            span_context: SpanContext::new(
+                TraceId::from(1),
+                SpanId::from(id),
+                TraceFlags::SAMPLED,
+                false,
+                TraceState::default(),
+            ),
+            parent_span_id: SpanId::INVALID,
+            span_kind: SpanKind::Internal,
+            name: name.into(),
+            start_time,
+            end_time: start_time + latency,
+            attributes: Vec::new(),
+            dropped_attributes_count: 0,
+            events: SpanEvents::default(),
+            links: SpanLinks::default(),
+            status,
+            resource: Cow::Owned(Resource::empty()),
+            instrumentation_lib: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_latency_bucket() {
+        assert_eq!(latency_bucket(Duration::ZERO), 0);
+        assert_eq!(latency_bucket(Duration::from_micros(9)), 0);
+        assert_eq!(latency_bucket(Duration::from_micros(10)), 1);
+        assert_eq!(latency_bucket(Duration::from_millis(5)), 3);
+        assert_eq!(latency_bucket(Duration::from_secs(1)), 6);
+        assert_eq!(latency_bucket(Duration::from_secs(1000)), 8);
+    }
+
+    #[test]
+    fn test_running_spans() {
+        let mut aggregator = SpanAggregator::new(2);
+        aggregator.start(span("a", 1, Duration::ZERO, Status::Unset));
+        aggregator.start(span("a", 2, Duration::ZERO, Status::Unset));
+        assert_eq!(aggregator.counts()[0].running, 2);
+
+        aggregator.end(span("a", 1, Duration::from_millis(5), Status::Unset));
+        let counts = aggregator.counts();
+        assert_eq!(counts[0].running, 1);
+        assert_eq!(counts[0].latency[3], 1);
+    }
+
+    #[test]
+    fn test_renamed_running_span() {
+        let mut aggregator = SpanAggregator::new(2);
+        aggregator.start(span("a", 1, Duration::ZERO, Status::Unset));
+        aggregator.end(span("b", 1, Duration::from_millis(5), Status::Unset));
+
+        let counts = aggregator.counts();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].spanname, "b");
+        assert_eq!(counts[0].running, 0);
+        assert_eq!(aggregator.running("a").count(), 0);
+    }
+
+    #[test]
+    fn test_span_names_are_bounded() {
+        let mut aggregator = SpanAggregator::new(1);
+        for id in 0..=MAX_SPAN_NAMES as u64 {
+            let name = format!("span-{id}");
+            let mut span = span("", id + 1, Duration::ZERO, Status::Unset);
+            span.name = name.into();
+            aggregator.end(span);
+        }
+
+        assert_eq!(aggregator.counts().len(), MAX_SPAN_NAMES);
+        assert!(aggregator
+            .summary(&format!("span-{MAX_SPAN_NAMES}"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_samples_are_bounded() {
+        let mut aggregator = SpanAggregator::new(2);
+        for id in 1..=3 {
+            aggregator.end(span("a", id, Duration::from_millis(5), Status::Unset));
+            aggregator.end(span("a", id, Duration::ZERO, Status::error("failed")));
+        }
+
+        let counts = aggregator.counts();
+        assert_eq!(counts[0].latency[3], 3);
+        assert_eq!(counts[0].error, 3);
+
+        let summary = aggregator.summary("a").unwrap();
+        let sampled_ids = |samples: &VecDeque<SpanData>| {
+            samples
+                .iter()
+                .map(|span| span.span_context.span_id())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sampled_ids(&summary.latency_samples[3]),
+            vec![SpanId::from(2), SpanId::from(3)]
+        );
+        assert_eq!(
+            sampled_ids(&summary.error_samples),
+            vec![SpanId::from(2), SpanId::from(3)]
+        );
+    }
+
+    #[test]
+    fn test_counts_sorted_by_name() {
+        let mut aggregator = SpanAggregator::new(0);
+        aggregator.end(span("b", 1, Duration::ZERO, Status::Ok));
+        aggregator.end(span("a", 2, Duration::ZERO, Status::Ok));
+
+        let names = aggregator
+            .counts()
+            .into_iter()
+            .map(|counts| counts.spanname)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "b"]);
+        assert!(aggregator.summary("a").unwrap().latency_samples[0].is_empty());
+    }
+}
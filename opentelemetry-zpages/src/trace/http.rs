@@ -0,0 +1,478 @@
+use std::fmt::Write;
+
+use hyper::{header, Body, Method, Request, Response, StatusCode};
+use opentelemetry_proto::tonic::{
+    common::v1::{any_value, AnyValue, KeyValue},
+    tracez::v1::TracezCounts,
+};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use prost::Message;
+
+use super::{
+    aggregator::{LATENCY_BUCKET_COUNT, LATENCY_BUCKET_NAMES},
+    TracezQuerier,
+};
+
+const TRACEZ_PATH: &str = "/tracez";
+const API_PATH: &str = "/tracez/api/";
+const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+
+// values of the `ztype` query parameter of the HTML page
+const RUNNING: u8 = 0;
+const LATENCY: u8 = 1;
+const ERROR: u8 = 2;
+
+/// Serves the spans collected by a [ZPagesSpanProcessor] over HTTP.
+///
+/// The following `GET` routes are handled:
+///
+/// - `/tracez`: the tracez page, as HTML. The spans of a span name are listed
+///   with the `zspanname` query parameter, along with `ztype=0` for the running
+///   spans, `ztype=1&zlatencybucket={bucket}` for the spans of a latency bucket
+///   or `ztype=2` for the spans which ended with an error.
+/// - `/tracez/api/aggregations`: the `TracezCounts` of each span name.
+/// - `/tracez/api/running/{span name}`: the `RunningData` of the running spans.
+/// - `/tracez/api/latency/{bucket}/{span name}`: the `LatencyData` of the spans
+///   of a latency bucket.
+/// - `/tracez/api/error/{span name}`: the `ErrorData` of the spans which ended
+///   with an error.
+///
+/// The `/tracez/api` routes respond with length-delimited protobuf messages,
+/// as defined in `opentelemetry-proto/src/proto/tracez.proto`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::{convert::Infallible, net::SocketAddr};
+///
+/// use hyper::{
+///     service::{make_service_fn, service_fn},
+///     Server,
+/// };
+/// use opentelemetry_zpages::trace::TracezHandler;
+///
+/// # async fn run() -> hyper::Result<()> {
+/// let (processor, querier) = opentelemetry_zpages::tracez(5);
+/// // register `processor` on the tracer provider..
+///
+/// let handler = TracezHandler::new(querier);
+/// let make_service = make_service_fn(move |_conn| {
+///     let handler = handler.clone();
+///     async move {
+///         Ok::<_, Infallible>(service_fn(move |req| {
+///             let response = handler.handle(&req);
+///             async move { Ok::<_, Infallible>(response) }
+///         }))
+///     }
+/// });
+///
+/// Server::bind(&SocketAddr::from(([127, 0, 0, 1], 8888)))
+///     .serve(make_service)
+///     .await
+/// # }
+/// ```
+///
+/// [ZPagesSpanProcessor]: super::ZPagesSpanProcessor
+#[derive(Clone, Debug)]
+pub struct TracezHandler {
+    querier: TracezQuerier,
+}
+
+impl TracezHandler {
+    /// Creates a handler serving the spans read by `querier`.
+    pub fn new(querier: TracezQuerier) -> Self {
+        TracezHandler { querier }
+    }
+
+    /// Returns the response to `req`.
+    pub fn handle<B>(&self, req: &Request<B>) -> Response<Body> {
+        if req.method() != Method::GET {
+            return status(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        let path = req.uri().path();
+        if path == TRACEZ_PATH {
+            return html(self.page(req.uri().query().unwrap_or_default()));
+        }
+
+        let route = match path.strip_prefix(API_PATH) {
+            Some(route) => route,
+            None => return status(StatusCode::NOT_FOUND),
+        };
+        if route == "aggregations" {
+            return protobuf(&self.querier.aggregations());
+        }
+        let (kind, rest) = route.split_once('/').unwrap_or((route, ""));
+        match kind {
+            "running" => protobuf(&self.querier.running(&decode(rest))),
+            "error" => protobuf(&self.querier.errors(&decode(rest))),
+            "latency" => match rest
+                .split_once('/')
+                .and_then(|(bucket, name)| Some((bucket.parse().ok()?, name)))
+            {
+                Some((bucket, name)) if bucket < LATENCY_BUCKET_COUNT => {
+                    protobuf(&self.querier.latency(bucket, &decode(name)))
+                }
+                _ => status(StatusCode::BAD_REQUEST),
+            },
+            _ => status(StatusCode::NOT_FOUND),
+        }
+    }
+
+    fn page(&self, query: &str) -> String {
+        let mut span_name = None;
+        let mut kind = None;
+        let mut bucket = None;
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            let value = decode(&value.replace('+', " "));
+            match key {
+                "zspanname" => span_name = Some(value),
+                "ztype" => kind = value.parse::<u8>().ok(),
+                "zlatencybucket" => bucket = value.parse::<usize>().ok(),
+                _ => {}
+            }
+        }
+
+        let mut page = String::from(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>TraceZ</title>\
+             <style>table,th,td{border:1px solid #ccc;border-collapse:collapse;padding:4px}\
+             </style></head><body><h1>TraceZ Summary</h1>\n",
+        );
+        self.write_summary(&mut page);
+        if let Some(span_name) = span_name {
+            match (kind, bucket) {
+                (Some(RUNNING), _) => {
+                    let _ = writeln!(page, "<h2>Running spans of {}</h2>", escape(&span_name));
+                    let rows = self.querier.running(&span_name).into_iter().map(|span| {
+                        span_row(
+                            &span.traceid,
+                            &span.spanid,
+                            &span.parentid,
+                            span.starttime,
+                            String::new(),
+                            &span.attributes,
+                        )
+                    });
+                    write_spans(&mut page, "", rows);
+                }
+                (Some(LATENCY), Some(bucket)) if bucket < LATENCY_BUCKET_COUNT => {
+                    let _ = writeln!(
+                        page,
+                        "<h2>Spans of {} with a latency {}</h2>",
+                        escape(&span_name),
+                        escape(LATENCY_BUCKET_NAMES[bucket])
+                    );
+                    let rows = self
+                        .querier
+                        .latency(bucket, &span_name)
+                        .into_iter()
+                        .map(|span| {
+                            span_row(
+                                &span.traceid,
+                                &span.spanid,
+                                &span.parentid,
+                                span.starttime,
+                                format_nanos(span.endtime.saturating_sub(span.starttime)),
+                                &span.attributes,
+                            )
+                        });
+                    write_spans(&mut page, "Latency", rows);
+                }
+                (Some(ERROR), _) => {
+                    let _ = writeln!(page, "<h2>Error spans of {}</h2>", escape(&span_name));
+                    let rows = self.querier.errors(&span_name).into_iter().map(|span| {
+                        span_row(
+                            &span.traceid,
+                            &span.spanid,
+                            &span.parentid,
+                            span.starttime,
+                            escape(&span.status.map(|s| s.message).unwrap_or_default()),
+                            &span.attributes,
+                        )
+                    });
+                    write_spans(&mut page, "Status", rows);
+                }
+                _ => page.push_str("<p>Unknown span type.</p>\n"),
+            }
+        }
+        page.push_str("</body></html>\n");
+        page
+    }
+
+    fn write_summary(&self, page: &mut String) {
+        page.push_str("<table><tr><th>Span Name</th><th>Running</th>");
+        for name in LATENCY_BUCKET_NAMES {
+            let _ = write!(page, "<th>{}</th>", escape(name));
+        }
+        page.push_str("<th>Errors</th></tr>\n");
+
+        for TracezCounts {
+            spanname,
+            latency,
+            running,
+            error,
+        } in self.querier.aggregations()
+        {
+            let name = utf8_percent_encode(&spanname, NON_ALPHANUMERIC).to_string();
+            let _ = write!(page, "<tr><td>{}</td>", escape(&spanname));
+            write_count(page, running, &format!("zspanname={name}&ztype={RUNNING}"));
+            for (bucket, count) in latency.into_iter().enumerate() {
+                let link = format!("zspanname={name}&ztype={LATENCY}&zlatencybucket={bucket}");
+                write_count(page, count, &link);
+            }
+            write_count(page, error, &format!("zspanname={name}&ztype={ERROR}"));
+            page.push_str("</tr>\n");
+        }
+        page.push_str("</table>\n");
+    }
+}
+
+fn write_count(page: &mut String, count: u32, query: &str) {
+    if count == 0 {
+        page.push_str("<td>0</td>");
+    } else {
+        let _ = write!(
+            page,
+            "<td><a href=\"{TRACEZ_PATH}?{}\">{count}</a></td>",
+            escape(query)
+        );
+    }
+}
+
+fn write_spans(page: &mut String, extra_column: &str, rows: impl Iterator<Item = String>) {
+    page.push_str(
+        "<table><tr><th>Trace ID</th><th>Span ID</th><th>Parent ID</th><th>Start Time</th>",
+    );
+    if !extra_column.is_empty() {
+        let _ = write!(page, "<th>{extra_column}</th>");
+    }
+    page.push_str("<th>Attributes</th></tr>\n");
+    for row in rows {
+        page.push_str(&row);
+    }
+    page.push_str("</table>\n");
+}
+
+fn span_row(
+    trace_id: &[u8],
+    span_id: &[u8],
+    parent_id: &[u8],
+    start_time: u64,
+    extra_column: String,
+    attributes: &[KeyValue],
+) -> String {
+    let mut row = format!(
+        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td>",
+        hex(trace_id),
+        hex(span_id),
+        hex(parent_id),
+        format_nanos(start_time)
+    );
+    if !extra_column.is_empty() {
+        let _ = write!(row, "<td>{extra_column}</td>");
+    }
+    let attributes = attributes
+        .iter()
+        .map(|kv| format!("{}={}", kv.key, format_value(kv.value.as_ref())))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let _ = writeln!(row, "<td>{}</td></tr>", escape(&attributes));
+    row
+}
+
+fn format_value(value: Option<&AnyValue>) -> String {
+    match value.and_then(|v| v.value.as_ref()) {
+        Some(any_value::Value::StringValue(s)) => s.clone(),
+        Some(any_value::Value::BoolValue(b)) => b.to_string(),
+        Some(any_value::Value::IntValue(i)) => i.to_string(),
+        Some(any_value::Value::DoubleValue(d)) => d.to_string(),
+        Some(any_value::Value::ArrayValue(array)) => format!(
+            "[{}]",
+            array
+                .values
+                .iter()
+                .map(|v| format_value(Some(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Some(any_value::Value::KvlistValue(list)) => format!(
+            "{{{}}}",
+            list.values
+                .iter()
+                .map(|kv| format!("{}={}", kv.key, format_value(kv.value.as_ref())))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Some(any_value::Value::BytesValue(bytes)) => hex(bytes),
+        None => String::new(),
+    }
+}
+
+/// Formats a duration or a time since the unix epoch in nanoseconds as seconds.
+fn format_nanos(nanos: u64) -> String {
+    format!("{}.{:09}s", nanos / 1_000_000_000, nanos % 1_000_000_000)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut s, b| {
+        let _ = write!(s, "{b:02x}");
+        s
+    })
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn decode(s: &str) -> String {
+    percent_decode_str(s).decode_utf8_lossy().into_owned()
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+fn html(page: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(page));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("text/html; charset=utf-8"),
+    );
+    response
+}
+
+fn protobuf<M: Message>(messages: &[M]) -> Response<Body> {
+    let mut body = Vec::new();
+    for message in messages {
+        // encoding into a vec can't fail
+        let _ = message.encode_length_delimited(&mut body);
+    }
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static(PROTOBUF_CONTENT_TYPE),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::body::to_bytes;
+    use opentelemetry::trace::{Span, Status, Tracer, TracerProvider as _};
+    use opentelemetry_proto::tonic::tracez::v1::{ErrorData, LatencyData};
+    use opentelemetry_sdk::trace::TracerProvider;
+
+    use super::*;
+    use crate::tracez;
+
+    fn request(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    async fn body(response: Response<Body>) -> Vec<u8> {
+        to_bytes(response.into_body()).await.unwrap().to_vec()
+    }
+
+    fn handler() -> (TracerProvider, TracezHandler) {
+        let (processor, querier) = tracez(5);
+        let provider = TracerProvider::builder()
+            .with_span_processor(processor)
+            .build();
+        let tracer = provider.tracer("test");
+        tracer.start("my span").end();
+        let mut failed = tracer.start("my span");
+        failed.set_status(Status::error("<failed>"));
+        failed.end();
+
+        (provider, TracezHandler::new(querier))
+    }
+
+    #[tokio::test]
+    async fn test_html_summary() {
+        let (_provider, handler) = handler();
+
+        let response = handler.handle(&request("/tracez"));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+        let page = String::from_utf8(body(response).await).unwrap();
+        assert!(page.contains("<td>my span</td>"));
+        assert!(page.contains("/tracez?zspanname=my%20span&amp;ztype=2"));
+    }
+
+    #[tokio::test]
+    async fn test_html_errors() {
+        let (_provider, handler) = handler();
+
+        let response = handler.handle(&request("/tracez?zspanname=my+span&ztype=2"));
+        let page = String::from_utf8(body(response).await).unwrap();
+        assert!(page.contains("Error spans of my span"));
+        assert!(page.contains("&lt;failed&gt;"));
+    }
+
+    #[tokio::test]
+    async fn test_protobuf_api() {
+        let (_provider, handler) = handler();
+
+        let response = handler.handle(&request("/tracez/api/aggregations"));
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            PROTOBUF_CONTENT_TYPE
+        );
+        let counts = TracezCounts::decode_length_delimited(body(response).await.as_slice())
+            .expect("valid message");
+        assert_eq!(counts.spanname, "my span");
+        assert_eq!(counts.error, 1);
+        let bucket = counts
+            .latency
+            .iter()
+            .position(|count| *count == 1)
+            .expect("one span in a latency bucket");
+
+        let response = handler.handle(&request("/tracez/api/error/my%20span"));
+        let errors = ErrorData::decode_length_delimited(body(response).await.as_slice())
+            .expect("valid message");
+        assert_eq!(errors.status.unwrap().message, "<failed>");
+
+        let response = handler.handle(&request(&format!("/tracez/api/latency/{bucket}/my%20span")));
+        let latency = LatencyData::decode_length_delimited(body(response).await.as_slice())
+            .expect("valid message");
+        assert!(latency.endtime >= latency.starttime);
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let (_provider, handler) = handler();
+
+        let post = Request::post("/tracez").body(Body::empty()).unwrap();
+        assert_eq!(
+            handler.handle(&post).status(),
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+        assert_eq!(
+            handler.handle(&request("/unknown")).status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            handler
+                .handle(&request("/tracez/api/latency/9/my%20span"))
+                .status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
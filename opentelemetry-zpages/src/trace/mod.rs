@@ -0,0 +1,109 @@
+//! Tracez page of the zPages.
+//!
+//! [tracez] returns a [ZPagesSpanProcessor], collecting the spans of a tracer
+//! provider, and a [TracezQuerier] to read them.
+use std::sync::{Arc, Mutex};
+
+use opentelemetry_proto::tonic::tracez::v1::{ErrorData, LatencyData, RunningData, TracezCounts};
+
+use aggregator::SpanAggregator;
+
+mod aggregator;
+#[cfg(feature = "hyper")]
+mod http;
+mod span_processor;
+
+#[cfg(feature = "hyper")]
+pub use http::TracezHandler;
+pub use span_processor::ZPagesSpanProcessor;
+
+/// Creates a [ZPagesSpanProcessor] and the [TracezQuerier] reading the spans it
+/// collects.
+///
+/// `sample_size` is the number of spans kept for each span name in each
+/// latency bucket and among the spans which ended with an error. The most
+/// recent spans are kept. Ended spans are collected for up to 1000 span names,
+/// the spans of other names are only reported while they run.
+pub fn tracez(sample_size: usize) -> (ZPagesSpanProcessor, TracezQuerier) {
+    let aggregator = Arc::new(Mutex::new(SpanAggregator::new(sample_size)));
+    (
+        ZPagesSpanProcessor::new(Arc::clone(&aggregator)),
+        TracezQuerier { aggregator },
+    )
+}
+
+/// Reads the spans collected by a [ZPagesSpanProcessor].
+///
+/// Running spans are reported with the data they had when they started.
+#[derive(Clone, Debug)]
+pub struct TracezQuerier {
+    aggregator: Arc<Mutex<SpanAggregator>>,
+}
+
+impl TracezQuerier {
+    /// The number of running spans, of ended spans per latency bucket and of
+    /// spans which ended with an error, for each span name.
+    ///
+    /// The latency buckets are `[0, 10µs)`, `[10µs, 100µs)`, `[100µs, 1ms)`,
+    /// `[1ms, 10ms)`, `[10ms, 100ms)`, `[100ms, 1s)`, `[1s, 10s)`,
+    /// `[10s, 100s)` and `[100s, ∞)`. Spans which ended with an error are not
+    /// counted in the latency buckets.
+    pub fn aggregations(&self) -> Vec<TracezCounts> {
+        self.aggregator
+            .lock()
+            .map(|aggregator| aggregator.counts())
+            .unwrap_or_default()
+    }
+
+    /// The running spans named `span_name`.
+    pub fn running(&self, span_name: &str) -> Vec<RunningData> {
+        self.aggregator
+            .lock()
+            .map(|aggregator| {
+                aggregator
+                    .running(span_name)
+                    .cloned()
+                    .map(Into::into)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The sampled spans named `span_name` which ended with an error.
+    pub fn errors(&self, span_name: &str) -> Vec<ErrorData> {
+        self.query(span_name, |summary| {
+            summary
+                .error_samples
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .collect()
+        })
+    }
+
+    /// The sampled spans named `span_name` in the latency bucket of index
+    /// `bucket`.
+    ///
+    /// No span is returned if `bucket` is out of range.
+    pub fn latency(&self, bucket: usize, span_name: &str) -> Vec<LatencyData> {
+        self.query(span_name, |summary| {
+            summary
+                .latency_samples
+                .get(bucket)
+                .map(|samples| samples.iter().cloned().map(Into::into).collect())
+                .unwrap_or_default()
+        })
+    }
+
+    fn query<T>(
+        &self,
+        span_name: &str,
+        f: impl FnOnce(&aggregator::SpanSummary) -> Vec<T>,
+    ) -> Vec<T> {
+        self.aggregator
+            .lock()
+            .ok()
+            .and_then(|aggregator| aggregator.summary(span_name).map(f))
+            .unwrap_or_default()
+    }
+}
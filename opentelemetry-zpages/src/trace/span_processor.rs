@@ -0,0 +1,105 @@
+use std::sync::{Arc, Mutex};
+
+use opentelemetry::{trace::TraceResult, Context};
+use opentelemetry_sdk::{
+    export::trace::SpanData,
+    trace::{Span, SpanProcessor},
+};
+
+use super::aggregator::SpanAggregator;
+
+/// A [SpanProcessor] collecting the running spans and samples of the ended
+/// spans for the tracez page.
+///
+/// Created along with its [TracezQuerier] by [tracez].
+///
+/// [TracezQuerier]: super::TracezQuerier
+/// [tracez]: super::tracez
+#[derive(Debug)]
+pub struct ZPagesSpanProcessor {
+    aggregator: Arc<Mutex<SpanAggregator>>,
+}
+
+impl ZPagesSpanProcessor {
+    pub(crate) fn new(aggregator: Arc<Mutex<SpanAggregator>>) -> Self {
+        ZPagesSpanProcessor { aggregator }
+    }
+}
+
+impl SpanProcessor for ZPagesSpanProcessor {
+    fn on_start(&self, span: &mut Span, _cx: &Context) {
+        if let Some(data) = span.exported_data() {
+            if let Ok(mut aggregator) = self.aggregator.lock() {
+                aggregator.start(data);
+            }
+        }
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if let Ok(mut aggregator) = self.aggregator.lock() {
+            aggregator.end(span);
+        }
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        Ok(())
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::{Span as _, Status, TraceContextExt, Tracer, TracerProvider as _};
+    use opentelemetry_sdk::trace::TracerProvider;
+
+    use crate::tracez;
+
+    #[test]
+    fn test_tracez() {
+        let (processor, querier) = tracez(5);
+        let provider = TracerProvider::builder()
+            .with_span_processor(processor)
+            .build();
+        let tracer = provider.tracer("test");
+
+        tracer.in_span("parent", |cx| {
+            let counts = querier.aggregations();
+            assert_eq!(counts.len(), 1);
+            assert_eq!(counts[0].spanname, "parent");
+            assert_eq!(counts[0].running, 1);
+
+            let running = querier.running("parent");
+            assert_eq!(running.len(), 1);
+            assert_eq!(
+                running[0].spanid,
+                cx.span().span_context().span_id().to_bytes().to_vec()
+            );
+
+            let mut child = tracer.start("child");
+            child.set_status(Status::error("failed"));
+            child.end();
+        });
+
+        let counts = querier.aggregations();
+        assert_eq!(counts[0].spanname, "child");
+        assert_eq!(counts[0].error, 1);
+        assert_eq!(counts[0].latency.iter().sum::<u32>(), 0);
+        assert_eq!(counts[1].spanname, "parent");
+        assert_eq!(counts[1].running, 0);
+        assert_eq!(counts[1].latency.iter().sum::<u32>(), 1);
+
+        assert!(querier.running("parent").is_empty());
+        assert_eq!(querier.errors("child").len(), 1);
+        assert_eq!(
+            (0..9)
+                .map(|bucket| querier.latency(bucket, "parent").len())
+                .sum::<usize>(),
+            1
+        );
+        assert!(querier.latency(9, "parent").is_empty());
+        assert!(querier.errors("unknown").is_empty());
+    }
+}
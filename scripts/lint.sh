@@ -11,6 +11,7 @@ cargo_feature() {
 
 if rustup component add clippy; then
  crates=( "opentelemetry"
+                "opentelemetry-config"
                 "opentelemetry-http"
                 "opentelemetry-jaeger"
                 "opentelemetry-jaeger-propagator"
@@ -21,8 +22,10 @@ if rustup component add clippy; then
                 "opentelemetry-proto"
                 "opentelemetry-sdk"
                 "opentelemetry-semantic-conventions"
+                "opentelemetry-statsd"
                 "opentelemetry-stdout"
-                "opentelemetry-zipkin")
+                "opentelemetry-zipkin"
+                "opentelemetry-zpages")
   for crate in "${crates[@]}"; do
       cargo clippy --manifest-path=$crate/Cargo.toml --all-targets --all-features -- \
           `# Exit with a nonzero code if there are clippy warnings` \
@@ -34,11 +37,18 @@ if rustup component add clippy; then
   cargo_feature opentelemetry-otlp "default"
   cargo_feature opentelemetry-otlp "default,tls"
   cargo_feature opentelemetry-otlp "default,tls-roots"
+  cargo_feature opentelemetry-otlp "default,zstd-tonic"
   cargo_feature opentelemetry-otlp "http-proto"
   cargo_feature opentelemetry-otlp "http-proto, reqwest-blocking-client"
   cargo_feature opentelemetry-otlp "http-proto, reqwest-client"
   cargo_feature opentelemetry-otlp "http-proto, reqwest-rustls"
+  cargo_feature opentelemetry-otlp "http-proto, reqwest-rustls, reqwest-client"
+  cargo_feature opentelemetry-otlp "http-proto, gzip-http, zstd-http"
+  cargo_feature opentelemetry-otlp "http-json, reqwest-client"
+  cargo_feature opentelemetry-otlp "file"
+  cargo_feature opentelemetry-otlp "file, trace, metrics, logs"
   cargo_feature opentelemetry-otlp "metrics"
+  cargo_feature opentelemetry-otlp "testing"
 
   cargo_feature opentelemetry-jaeger "isahc_collector_client"
   cargo_feature opentelemetry-jaeger "reqwest_blocking_collector_client"
@@ -60,4 +70,21 @@ if rustup component add clippy; then
   cargo_feature opentelemetry-proto "gen-tonic,metrics"
   cargo_feature opentelemetry-proto "gen-tonic,logs"
 
+  cargo_feature opentelemetry-prometheus "server"
+  cargo_feature opentelemetry-prometheus "remote-write"
+
+  cargo_feature opentelemetry-zpages "hyper"
+
+  cargo_feature opentelemetry-sdk "trace,persistent_queue"
+  cargo_feature opentelemetry-sdk "logs,persistent_queue"
+  cargo_feature opentelemetry-sdk "metrics,persistent_queue"
+
+  cargo_feature opentelemetry-config ""
+  cargo_feature opentelemetry-config "default"
+  cargo_feature opentelemetry-config "trace"
+  cargo_feature opentelemetry-config "console"
+  cargo_feature opentelemetry-config "metrics, otlp-grpc"
+  cargo_feature opentelemetry-config "logs, otlp-http"
+  cargo_feature opentelemetry-config "otlp-http-json"
+
 fi